@@ -11,6 +11,8 @@ SET bvm_exit_code=%ERRORLEVEL%
 IF "%1" == "install" GOTO checkenvchanges
 IF "%1" == "uninstall" GOTO checkenvchanges
 IF "%1" == "use" GOTO checkenvchanges
+IF "%1" == "disable" GOTO checkenvchanges
+IF "%1" == "enable" GOTO checkenvchanges
 GOTO end
 
 :checkenvchanges
@@ -76,15 +76,9 @@ if ($args[0] -eq "exec-command") {
   # Format: bvm exec-command [command-name] [...args]
   $command_name = $args[1]
   $exec_args = $args[2..$args.Length]
-  $env_messages=((bvm-bin hidden resolve-command $command_name) | Out-String)
-  $should_snapshot_env=(has_env_changes $env_messages)
-  if ($should_snapshot_env -eq 1) { $env_snapshot=(snapshot_env) }
-  try {
-    bvm_handle_env_messages $env_messages $exec_args
-  } finally {
-    if ($should_snapshot_env -eq 1) { restore_env $env_snapshot }
-  }
-  if ($lastexitcode -ne 0) { exit $lastexitcode }
+  # bvm-bin runs the executable so that ctrl+c is handled by it and its exit code is propagated
+  & bvm-bin hidden exec-command $command_name @exec_args # splat
+  exit $lastexitcode
 } elseif ($args[0] -eq "exec") {
   # Format: bvm exec [name-selector] [version-selector] [command-name] [...args]
   $exec_name = $args[1]
@@ -117,7 +111,7 @@ if ($args[0] -eq "exec-command") {
 } else {
   bvm-bin @args # splat
 
-  if (($args[0] -eq "install") -or ($args[0] -eq "uninstall") -or ($args[0] -eq "use")) {
+  if (($args[0] -eq "install") -or ($args[0] -eq "uninstall") -or ($args[0] -eq "use") -or ($args[0] -eq "disable") -or ($args[0] -eq "enable")) {
     bvm_handle_env_messages ((bvm-bin hidden get-pending-env-changes) | Out-String)
     if ($lastexitcode -ne 0) { exit $lastexitcode }
     bvm-bin hidden clear-pending-env-changes
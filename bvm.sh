@@ -68,11 +68,8 @@ bvm()
     bvm_exec_command=$2
     shift 2
 
-    # use a sub shell to prevent exporting variables
-    (
-      bvm_handle_env_messages "$($bvm_bin hidden resolve-command "$bvm_exec_command")" "$@" || { return $?; }
-    )
-
+    # bvm-bin replaces itself with the executable, so signals and the exit code go directly to it
+    $bvm_bin hidden exec-command "$bvm_exec_command" "$@"
     return $?;
 
   elif [ "$1" = "exec" ]
@@ -106,14 +103,15 @@ bvm()
     (
       bvm_handle_env_messages "$($bvm_bin hidden get-exec-env-changes "$bvm_exec_name" "$bvm_exec_version")" || { return $?; }
 
-      $bvm_executable_path "$@"
+      # replace the sub shell so signals and the exit code go directly to the executable
+      exec $bvm_executable_path "$@"
     )
     return $?
   fi
 
   $bvm_bin "$@"
 
-  if [ "$1" = "install" ] || [ "$1" = "uninstall" ] || [ "$1" = "use" ]
+  if [ "$1" = "install" ] || [ "$1" = "uninstall" ] || [ "$1" = "use" ] || [ "$1" = "disable" ] || [ "$1" = "enable" ]
   then
     local pending_changes
     pending_changes=$($bvm_bin hidden get-pending-env-changes)
@@ -0,0 +1,41 @@
+use dprint_cli_core::types::ErrBox;
+use std::path::{Path, PathBuf};
+
+use crate::environment::Environment;
+
+/// Finds the nearest config file by searching the current directory then its ancestors.
+pub fn find_config_file(environment: &impl Environment) -> Result<Option<PathBuf>, ErrBox> {
+    let cwd = environment.cwd()?;
+
+    // note: ancestors() includes the provided directory
+    for ancestor_dir in cwd.ancestors() {
+        if let Some(config_file_path) = get_config_file_in_dir(environment, ancestor_dir) {
+            return Ok(Some(config_file_path));
+        }
+    }
+
+    Ok(None)
+}
+
+pub const CONFIG_FILE_NAME: &'static str = "bvm.json";
+pub const HIDDEN_CONFIG_FILE_NAME: &'static str = ".bvm.json";
+pub const TOML_CONFIG_FILE_NAME: &str = "bvm.toml";
+pub const HIDDEN_TOML_CONFIG_FILE_NAME: &str = ".bvm.toml";
+
+/// The config file names in order of precedence.
+pub const CONFIG_FILE_NAMES: [&str; 4] = [
+    CONFIG_FILE_NAME,
+    HIDDEN_CONFIG_FILE_NAME,
+    TOML_CONFIG_FILE_NAME,
+    HIDDEN_TOML_CONFIG_FILE_NAME,
+];
+
+pub fn get_config_file_in_dir(environment: &impl Environment, dir: &Path) -> Option<PathBuf> {
+    for file_name in CONFIG_FILE_NAMES.iter() {
+        let config_path = dir.join(file_name);
+        if environment.path_exists(&config_path) {
+            return Some(config_path);
+        }
+    }
+    None
+}
@@ -0,0 +1,102 @@
+use dprint_cli_core::types::ErrBox;
+
+use crate::environment::Environment;
+
+/// Replaces the `${NAME}` references to environment variables in a config file value. A reference
+/// may be escaped as `$${NAME}` to keep it in the value as-is.
+pub fn interpolate_env_vars(environment: &impl Environment, text: &str) -> Result<String, ErrBox> {
+    interpolate(text, |name| environment.get_env_var(name))
+}
+
+fn interpolate(text: &str, get_env_var: impl Fn(&str) -> Option<String>) -> Result<String, ErrBox> {
+    let mut result = String::new();
+    let mut remaining = text;
+    while let Some(index) = remaining.find("${") {
+        if remaining[..index].ends_with('$') {
+            result.push_str(&remaining[..index - 1]);
+            result.push_str("${");
+            remaining = &remaining[index + 2..];
+            continue;
+        }
+        result.push_str(&remaining[..index]);
+        let after_brace = &remaining[index + 2..];
+        let end_index = match after_brace.find('}') {
+            Some(end_index) => end_index,
+            None => return err!("Expected a closing brace for the environment variable in '{}'.", text),
+        };
+        let name = &after_brace[..end_index];
+        if !is_valid_env_var_name(name) {
+            return err!("Invalid environment variable name '{}' in '{}'.", name, text);
+        }
+        match get_env_var(name) {
+            Some(value) => result.push_str(&value),
+            None => {
+                return err!(
+                    "Environment variable '{}' is not set, but is referenced in '{}'. Set it or escape the reference as $${{{}}}.",
+                    name,
+                    text,
+                    name
+                )
+            }
+        }
+        remaining = &after_brace[end_index + 1..];
+    }
+    result.push_str(remaining);
+    Ok(result)
+}
+
+fn is_valid_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_env_var(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("localhost".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn should_replace_env_vars() {
+        assert_eq!(
+            interpolate("https://${HOST}/${HOST}.json", get_env_var).unwrap(),
+            "https://localhost/localhost.json"
+        );
+        assert_eq!(interpolate("a${EMPTY}b", get_env_var).unwrap(), "ab");
+        assert_eq!(interpolate("$HOST {HOST}", get_env_var).unwrap(), "$HOST {HOST}");
+    }
+
+    #[test]
+    fn should_keep_escaped_env_vars() {
+        assert_eq!(
+            interpolate("$${HOST}/${HOST}", get_env_var).unwrap(),
+            "${HOST}/localhost"
+        );
+        assert_eq!(interpolate("$${UNSET}", get_env_var).unwrap(), "${UNSET}");
+    }
+
+    #[test]
+    fn should_error_for_invalid_env_vars() {
+        assert_eq!(
+            interpolate("https://${UNSET}/", get_env_var).err().unwrap().to_string(),
+            "Environment variable 'UNSET' is not set, but is referenced in 'https://${UNSET}/'. Set it or escape the reference as $${UNSET}."
+        );
+        assert_eq!(
+            interpolate("https://${HOST", get_env_var).err().unwrap().to_string(),
+            "Expected a closing brace for the environment variable in 'https://${HOST'."
+        );
+        assert_eq!(
+            interpolate("${1A}", get_env_var).err().unwrap().to_string(),
+            "Invalid environment variable name '1A' in '${1A}'."
+        );
+    }
+}
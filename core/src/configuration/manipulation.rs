@@ -0,0 +1,821 @@
+use dprint_cli_core::types::ErrBox;
+use jsonc_parser::{ast::Value, common::Ranged, parse_to_ast, ParseOptions};
+use std::path::Path;
+
+use super::{read_toml_config_file, ConfigFileBinary, ConfigFileFormat};
+use crate::environment::Environment;
+
+// todo: cleanup :)
+
+pub fn add_binary_to_config_file(
+    environment: &impl Environment,
+    file_path: &Path,
+    binary: &ConfigFileBinary,
+    replace_index: Option<usize>,
+) -> Result<(), ErrBox> {
+    match ConfigFileFormat::from_path(file_path) {
+        ConfigFileFormat::Json => add_binary_to_json_config_file(environment, file_path, binary, replace_index),
+        ConfigFileFormat::Toml => add_binary_to_toml_config_file(environment, file_path, binary, replace_index),
+    }
+}
+
+fn add_binary_to_json_config_file(
+    environment: &impl Environment,
+    file_path: &Path,
+    binary: &ConfigFileBinary,
+    replace_index: Option<usize>,
+) -> Result<(), ErrBox> {
+    // todo: improve jsonc-parser
+    let mut file_text = environment.read_file_text(&file_path)?;
+    let value = parse_to_ast(
+        &file_text,
+        &ParseOptions {
+            comments: false,
+            tokens: false,
+        },
+    )?
+    .value;
+    let root_obj = match value {
+        Some(Value::Object(obj)) => obj,
+        _ => return err!("Expected a root object in the json file."),
+    };
+
+    // todo: remove clone here (improve jsonc-parser)
+    let binaries_prop = root_obj
+        .properties
+        .iter()
+        .filter(|p| p.name.clone().to_string().as_str() == "binaries")
+        .next()
+        .ok_or_else(|| err_obj!("Expected to find a 'binaries' array."))?;
+
+    let binaries_array = match &binaries_prop.value {
+        Value::Array(array) => array,
+        _ => return err!("Expected the 'binaries' property to contain an array."),
+    };
+
+    let previous_element_end = match replace_index {
+        Some(replace_index) => {
+            if replace_index == 0 {
+                None
+            } else {
+                binaries_array.elements.get(replace_index - 1)
+            }
+        }
+        None => binaries_array.elements.last(),
+    }
+    .map(|l| l.end());
+
+    let (mut insert_pos, mut insert_end) = match replace_index {
+        Some(replace_index) => {
+            let element = binaries_array.elements.get(replace_index).unwrap();
+            (element.start(), element.end())
+        }
+        None => {
+            let end_bracket_pos = binaries_array.end() - 1; // -1 for before bracket
+            let start_pos = previous_element_end.unwrap_or(binaries_array.start() + 1); // +1 for after bracket
+            (
+                // insert after any comments that might exist before the end bracket
+                start_pos + file_text[start_pos..end_bracket_pos].trim_end().len(),
+                end_bracket_pos,
+            )
+        }
+    };
+
+    let indentation = get_indentation(&file_text, binaries_prop.start());
+
+    // add a comma if necessary
+    let mut has_trailing_comma = false;
+    if let Some(previous_element_end) = previous_element_end {
+        let has_comma = get_next_non_comment_char(&file_text[previous_element_end..]) == Some(',');
+
+        if has_comma {
+            has_trailing_comma = replace_index.is_none();
+        } else {
+            file_text = format!(
+                "{},{}",
+                &file_text[..previous_element_end],
+                &file_text[previous_element_end..]
+            );
+            // add +1 for the comma
+            insert_pos += 1;
+            insert_end += 1;
+        }
+    }
+
+    // now add the element
+    let newline_char = get_newline_char(&file_text);
+    let mut insert_text = String::new();
+    if replace_index.is_none() {
+        insert_text.push_str(&newline_char);
+        insert_text.push_str(&indentation.repeat(2));
+    }
+    insert_text.push_str("{");
+    insert_text.push_str(&newline_char);
+    insert_text.push_str(&indentation.repeat(3));
+    insert_text.push_str("\"path\": \"");
+    insert_text.push_str(&escape_quotes(&binary.path.path_or_url));
+    insert_text.push_str("\"");
+
+    if let Some(checksum) = &binary.path.checksum {
+        insert_text.push_str(",");
+        insert_text.push_str(&newline_char);
+        insert_text.push_str(&indentation.repeat(3));
+        insert_text.push_str("\"checksum\": \"");
+        insert_text.push_str(&escape_quotes(&checksum));
+        insert_text.push_str("\"");
+    }
+
+    if let Some(version) = get_binary_version_text(binary) {
+        insert_text.push_str(",");
+        insert_text.push_str(&newline_char);
+        insert_text.push_str(&indentation.repeat(3));
+        insert_text.push_str("\"version\": \"");
+        insert_text.push_str(&escape_quotes(version));
+        insert_text.push_str("\"");
+    }
+
+    for (key, value) in get_binary_hooks(binary) {
+        insert_text.push(',');
+        insert_text.push_str(&newline_char);
+        insert_text.push_str(&indentation.repeat(3));
+        insert_text.push_str(&format!("\"{}\": \"{}\"", key, escape_quotes(value)));
+    }
+
+    if let Some(platforms) = &binary.platforms {
+        insert_text.push(',');
+        insert_text.push_str(&newline_char);
+        insert_text.push_str(&indentation.repeat(3));
+        let platforms = platforms
+            .iter()
+            .map(|p| format!("\"{}\"", escape_quotes(p)))
+            .collect::<Vec<_>>();
+        insert_text.push_str(&format!("\"platforms\": [{}]", platforms.join(", ")));
+    }
+
+    if !binary.commands.is_empty() {
+        insert_text.push(',');
+        insert_text.push_str(&newline_char);
+        insert_text.push_str(&indentation.repeat(3));
+        let commands = binary
+            .commands
+            .iter()
+            .map(|(name, new_name)| format!("\"{}\": \"{}\"", escape_quotes(name), escape_quotes(new_name)))
+            .collect::<Vec<_>>();
+        insert_text.push_str(&format!("\"commands\": {{ {} }}", commands.join(", ")));
+    }
+
+    if binary.optional {
+        insert_text.push(',');
+        insert_text.push_str(&newline_char);
+        insert_text.push_str(&indentation.repeat(3));
+        insert_text.push_str("\"optional\": true");
+    }
+
+    insert_text.push_str(&newline_char);
+    insert_text.push_str(&indentation.repeat(2));
+    insert_text.push_str("}");
+
+    if has_trailing_comma {
+        // maintain the trailing comma style of the file
+        insert_text.push(',');
+    }
+
+    if replace_index.is_none() {
+        insert_text.push_str(&newline_char);
+        insert_text.push_str(&indentation);
+    }
+
+    file_text = format!(
+        "{}{}{}",
+        &file_text[..insert_pos],
+        insert_text,
+        &file_text[insert_end..]
+    );
+
+    environment.write_file_text(&file_path, &file_text)?;
+
+    Ok(())
+}
+
+fn add_binary_to_toml_config_file(
+    environment: &impl Environment,
+    file_path: &Path,
+    binary: &ConfigFileBinary,
+    replace_index: Option<usize>,
+) -> Result<(), ErrBox> {
+    let file_text = environment.read_file_text(file_path)?;
+    let file_text = add_binary_to_toml_text(&file_text, binary, replace_index)?;
+    environment.write_file_text(file_path, &file_text)?;
+
+    Ok(())
+}
+
+fn add_binary_to_toml_text(
+    file_text: &str,
+    binary: &ConfigFileBinary,
+    replace_index: Option<usize>,
+) -> Result<String, ErrBox> {
+    let config_file = read_toml_config_file(file_text)?;
+    let table_ranges = get_toml_binaries_table_ranges(file_text);
+    if table_ranges.len() != config_file.binaries.len() {
+        return err!("Expected the binaries in the toml file to be specified as [[binaries]] tables.");
+    }
+
+    let newline_char = get_newline_char(file_text);
+    if let Some(replace_index) = replace_index {
+        let (start, end) = table_ranges[replace_index];
+        let table_text = replace_toml_binary_table_keys(&file_text[start..end], binary, &newline_char);
+        return Ok(format!("{}{}{}", &file_text[..start], table_text, &file_text[end..]));
+    }
+
+    let mut insert_text = String::new();
+    insert_text.push_str("[[binaries]]");
+    insert_text.push_str(&newline_char);
+    insert_text.push_str(&format!("path = \"{}\"", escape_toml_string(&binary.path.path_or_url)));
+    insert_text.push_str(&newline_char);
+    if let Some(checksum) = &binary.path.checksum {
+        insert_text.push_str(&format!("checksum = \"{}\"", escape_toml_string(checksum)));
+        insert_text.push_str(&newline_char);
+    }
+    if let Some(version) = get_binary_version_text(binary) {
+        insert_text.push_str(&format!("version = \"{}\"", escape_toml_string(version)));
+        insert_text.push_str(&newline_char);
+    }
+    for (key, value) in get_binary_hooks(binary) {
+        insert_text.push_str(&format!("{} = \"{}\"", key, escape_toml_string(value)));
+        insert_text.push_str(&newline_char);
+    }
+    if let Some(platforms) = &binary.platforms {
+        let platforms = platforms
+            .iter()
+            .map(|p| format!("\"{}\"", escape_toml_string(p)))
+            .collect::<Vec<_>>();
+        insert_text.push_str(&format!("platforms = [{}]", platforms.join(", ")));
+        insert_text.push_str(&newline_char);
+    }
+    if !binary.commands.is_empty() {
+        let commands = binary
+            .commands
+            .iter()
+            .map(|(name, new_name)| {
+                format!(
+                    "\"{}\" = \"{}\"",
+                    escape_toml_string(name),
+                    escape_toml_string(new_name)
+                )
+            })
+            .collect::<Vec<_>>();
+        insert_text.push_str(&format!("commands = {{ {} }}", commands.join(", ")));
+        insert_text.push_str(&newline_char);
+    }
+    if binary.optional {
+        insert_text.push_str("optional = true");
+        insert_text.push_str(&newline_char);
+    }
+
+    let leading_text = file_text.trim_end();
+    if leading_text.is_empty() {
+        Ok(insert_text)
+    } else {
+        Ok(format!(
+            "{}{}{}{}",
+            leading_text, newline_char, newline_char, insert_text
+        ))
+    }
+}
+
+/// Updates the path, checksum, and version keys of a `[[binaries]]` table in place, which keeps
+/// the table's comments, other keys, and their order as written.
+fn replace_toml_binary_table_keys(table_text: &str, binary: &ConfigFileBinary, newline_char: &str) -> String {
+    let mut keys = vec![
+        ("path", Some(binary.path.path_or_url.as_str())),
+        ("checksum", binary.path.checksum.as_deref()),
+        ("version", get_binary_version_text(binary)),
+    ];
+    let mut lines = table_text.split_inclusive('\n').map(String::from).collect::<Vec<_>>();
+    // the keys the table doesn't have are added after the last updated key or otherwise the header
+    let mut insert_index = 1;
+
+    let mut i = 1;
+    while i < lines.len() {
+        let line = &lines[i];
+        let key_index = get_toml_line_key(line).and_then(|key| keys.iter().position(|(name, _)| *name == key));
+        match key_index {
+            Some(key_index) => {
+                let (name, value) = keys.remove(key_index);
+                match value {
+                    Some(value) => {
+                        let indent_len = line.len() - line.trim_start().len();
+                        let line_end = &line[line.trim_end_matches(['\r', '\n']).len()..];
+                        lines[i] = format!(
+                            "{}{} = \"{}\"{}{}",
+                            &line[..indent_len],
+                            name,
+                            escape_toml_string(value),
+                            get_toml_string_value_comment(line),
+                            line_end
+                        );
+                        i += 1;
+                        insert_index = i;
+                    }
+                    None => {
+                        lines.remove(i);
+                    }
+                }
+            }
+            None => i += 1,
+        }
+    }
+
+    if !lines[insert_index - 1].ends_with('\n') {
+        lines[insert_index - 1].push_str(newline_char);
+    }
+    let insert_lines = keys
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .map(|(name, value)| format!("{} = \"{}\"{}", name, escape_toml_string(value), newline_char))
+        .collect::<Vec<_>>();
+    lines.splice(insert_index..insert_index, insert_lines);
+    lines.concat()
+}
+
+/// Gets the key of a `key = value` line in a toml table.
+fn get_toml_line_key(line: &str) -> Option<&str> {
+    let trimmed_line = line.trim_start();
+    if trimmed_line.starts_with('#') {
+        return None;
+    }
+    let key = trimmed_line[..trimmed_line.find('=')?].trim();
+    Some(key.trim_matches('"'))
+}
+
+/// Gets the comment following the string value of a `key = "value"` line, including its leading whitespace.
+fn get_toml_string_value_comment(line: &str) -> &str {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let value_start = match line.find('=') {
+        Some(index) => index + 1 + (line[index + 1..].len() - line[index + 1..].trim_start().len()),
+        None => return "",
+    };
+    let mut chars = line[value_start..].char_indices();
+    let quote_char = match chars.next() {
+        Some((_, c)) if c == '"' || c == '\'' => c,
+        _ => return "",
+    };
+    let mut is_escaped = false;
+    for (index, c) in chars {
+        if is_escaped {
+            is_escaped = false;
+        } else if c == '\\' && quote_char == '"' {
+            is_escaped = true;
+        } else if c == quote_char {
+            let remaining_text = &line[value_start + index + 1..];
+            return if remaining_text.trim_start().starts_with('#') {
+                remaining_text
+            } else {
+                ""
+            };
+        }
+    }
+    ""
+}
+
+/// Replaces the checksum of the binary at the provided index of the configuration file's own binaries,
+/// returning false when the binary's entry doesn't contain the checksum (ex. it's an environment variable).
+pub fn replace_checksum_in_config_file(
+    environment: &impl Environment,
+    file_path: &Path,
+    binary_index: usize,
+    old_checksum: &str,
+    new_checksum: &str,
+) -> Result<bool, ErrBox> {
+    let file_text = environment.read_file_text(file_path)?;
+    let (start, end) = match ConfigFileFormat::from_path(file_path) {
+        ConfigFileFormat::Json => get_json_binary_checksum_range(&file_text, binary_index)?,
+        ConfigFileFormat::Toml => get_toml_binary_checksum_range(&file_text, binary_index)?,
+    };
+    // only replace it in the binary's entry since other entries may have the same checksum
+    let entry_text = &file_text[start..end];
+    if old_checksum.is_empty() || !entry_text.contains(old_checksum) {
+        return Ok(false);
+    }
+    let file_text = format!(
+        "{}{}{}",
+        &file_text[..start],
+        entry_text.replacen(old_checksum, new_checksum, 1),
+        &file_text[end..]
+    );
+    environment.write_file_text(file_path, &file_text)?;
+    Ok(true)
+}
+
+/// Gets the text range of the checksum of the binary at the index in the 'binaries' array, which is
+/// the range of its path when the checksum is part of it (ex. `"https://.../1.0.0.json@<checksum>"`).
+fn get_json_binary_checksum_range(file_text: &str, binary_index: usize) -> Result<(usize, usize), ErrBox> {
+    let value = parse_to_ast(
+        file_text,
+        &ParseOptions {
+            comments: false,
+            tokens: false,
+        },
+    )?
+    .value;
+    let root_obj = match value {
+        Some(Value::Object(obj)) => obj,
+        _ => return err!("Expected a root object in the json file."),
+    };
+    let binaries_prop = root_obj
+        .properties
+        .iter()
+        .find(|p| p.name.clone().to_string().as_str() == "binaries")
+        .ok_or_else(|| err_obj!("Expected to find a 'binaries' array."))?;
+    let element = match &binaries_prop.value {
+        Value::Array(array) => match array.elements.get(binary_index) {
+            Some(element) => element,
+            None => return err!("Expected a binary at index {} in the 'binaries' array.", binary_index),
+        },
+        _ => return err!("Expected the 'binaries' property to contain an array."),
+    };
+    Ok(match element {
+        Value::Object(obj) => {
+            let find_prop = |name: &str| {
+                obj.properties
+                    .iter()
+                    .find(|p| p.name.clone().to_string().as_str() == name)
+            };
+            match find_prop("checksum").or_else(|| find_prop("path")) {
+                Some(prop) => (prop.value.start(), prop.value.end()),
+                None => (element.start(), element.start()),
+            }
+        }
+        _ => (element.start(), element.end()),
+    })
+}
+
+/// Gets the text range of the `[[binaries]]` table at the index.
+fn get_toml_binary_checksum_range(file_text: &str, binary_index: usize) -> Result<(usize, usize), ErrBox> {
+    let config_file = read_toml_config_file(file_text)?;
+    let table_ranges = get_toml_binaries_table_ranges(file_text);
+    if table_ranges.len() != config_file.binaries.len() {
+        return err!("Expected the binaries in the toml file to be specified as [[binaries]] tables.");
+    }
+    match table_ranges.get(binary_index) {
+        Some(range) => Ok(*range),
+        None => err!("Expected a binary at index {} in the toml file.", binary_index),
+    }
+}
+
+/// Removes the binary at the provided index of the configuration file's own binaries.
+pub fn remove_binary_from_config_file(
+    environment: &impl Environment,
+    file_path: &Path,
+    remove_index: usize,
+) -> Result<(), ErrBox> {
+    let file_text = environment.read_file_text(file_path)?;
+    let file_text = match ConfigFileFormat::from_path(file_path) {
+        ConfigFileFormat::Json => remove_binary_from_json_text(&file_text, remove_index)?,
+        ConfigFileFormat::Toml => remove_binary_from_toml_text(&file_text, remove_index)?,
+    };
+    environment.write_file_text(file_path, &file_text)
+}
+
+fn remove_binary_from_json_text(file_text: &str, remove_index: usize) -> Result<String, ErrBox> {
+    let value = parse_to_ast(
+        file_text,
+        &ParseOptions {
+            comments: false,
+            tokens: false,
+        },
+    )?
+    .value;
+    let root_obj = match value {
+        Some(Value::Object(obj)) => obj,
+        _ => return err!("Expected a root object in the json file."),
+    };
+    let binaries_prop = root_obj
+        .properties
+        .iter()
+        .find(|p| p.name.clone().to_string().as_str() == "binaries")
+        .ok_or_else(|| err_obj!("Expected to find a 'binaries' array."))?;
+    let binaries_array = match &binaries_prop.value {
+        Value::Array(array) => array,
+        _ => return err!("Expected the 'binaries' property to contain an array."),
+    };
+    let elements = &binaries_array.elements;
+    if remove_index >= elements.len() {
+        return err!("Expected a binary at index {} in the 'binaries' array.", remove_index);
+    }
+
+    let (start, end) = if remove_index > 0 {
+        // remove the element along with the comma separating it from the previous element,
+        // which keeps any comma following it
+        (elements[remove_index - 1].end(), elements[remove_index].end())
+    } else if let Some(next_element) = elements.get(1) {
+        (elements[0].start(), next_element.start())
+    } else {
+        // the only element, so empty the array
+        (binaries_array.start() + 1, binaries_array.end() - 1)
+    };
+    Ok(format!("{}{}", &file_text[..start], &file_text[end..]))
+}
+
+fn remove_binary_from_toml_text(file_text: &str, remove_index: usize) -> Result<String, ErrBox> {
+    let config_file = read_toml_config_file(file_text)?;
+    let table_ranges = get_toml_binaries_table_ranges(file_text);
+    if table_ranges.len() != config_file.binaries.len() {
+        return err!("Expected the binaries in the toml file to be specified as [[binaries]] tables.");
+    }
+    let (start, end) = match table_ranges.get(remove_index) {
+        Some(range) => *range,
+        None => return err!("Expected a binary at index {} in the toml file.", remove_index),
+    };
+
+    // also remove the blank lines that separated the table from the next one
+    let remaining_text = file_text[end..].trim_start_matches(['\r', '\n']);
+    if remaining_text.trim().is_empty() {
+        let leading_text = file_text[..start].trim_end();
+        if leading_text.is_empty() {
+            Ok(String::new())
+        } else {
+            Ok(format!("{}{}", leading_text, get_newline_char(file_text)))
+        }
+    } else {
+        Ok(format!("{}{}", &file_text[..start], remaining_text))
+    }
+}
+
+fn get_binary_version_text(binary: &ConfigFileBinary) -> Option<&str> {
+    match &binary.version {
+        Some(version) => Some(version.as_str()),
+        None => binary.version_alias.as_deref(),
+    }
+}
+
+fn get_binary_hooks(binary: &ConfigFileBinary) -> Vec<(&'static str, &str)> {
+    let mut hooks = Vec::new();
+    if let Some(on_install) = &binary.on_install {
+        hooks.push(("onInstall", on_install.as_str()));
+    }
+    if let Some(on_use) = &binary.on_use {
+        hooks.push(("onUse", on_use.as_str()));
+    }
+    hooks
+}
+
+/// Gets the text ranges of each `[[binaries]]` table, excluding any trailing blank lines or comments.
+fn get_toml_binaries_table_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut current_range: Option<(usize, usize)> = None;
+    let mut pos = 0;
+
+    while pos < text.len() {
+        let line_start = pos;
+        pos = text[pos..]
+            .find('\n')
+            .map(|i| pos + i + 1)
+            .unwrap_or_else(|| text.len());
+        let line = &text[line_start..pos];
+
+        let trimmed_line = line.trim();
+        if trimmed_line.starts_with('[') {
+            if let Some(range) = current_range.take() {
+                ranges.push(range);
+            }
+            if trimmed_line.starts_with("[[binaries]]") {
+                current_range = Some((line_start, pos));
+            }
+        } else if let Some(range) = current_range.as_mut() {
+            if !trimmed_line.is_empty() && !trimmed_line.starts_with('#') {
+                range.1 = pos;
+            }
+        }
+    }
+
+    if let Some(range) = current_range.take() {
+        ranges.push(range);
+    }
+
+    ranges
+}
+
+fn escape_toml_string(text: &str) -> String {
+    text.replace("\\", "\\\\").replace("\"", "\\\"")
+}
+
+fn get_newline_char(text: &str) -> String {
+    // todo: don't search the entire string. Just find the first "\n" occurrence
+    if text.find("\r\n").is_some() {
+        "\r\n".to_string()
+    } else {
+        "\n".to_string() // prefer this
+    }
+}
+
+/// Gets the next character that is not whitespace or within a comment.
+fn get_next_non_comment_char(text: &str) -> Option<char> {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' | '\r' | '\n' => continue,
+            '/' if chars.peek() == Some(&'/') => {
+                // skip to the end of the line
+                for c in &mut chars {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last_char = None;
+                for c in &mut chars {
+                    if last_char == Some('*') && c == '/' {
+                        break;
+                    }
+                    last_char = Some(c);
+                }
+            }
+            _ => return Some(c),
+        }
+    }
+    None
+}
+
+fn get_indentation(text: &str, end_bracket_pos: usize) -> String {
+    let leading_chars = &text[..end_bracket_pos].chars().collect::<Vec<_>>();
+    let previous_char = leading_chars.get(leading_chars.len() - 1);
+    match previous_char {
+        Some(' ') => {
+            let mut count = 1;
+            for c in leading_chars.iter().rev().skip(1) {
+                if c == &' ' {
+                    count += 1;
+                } else {
+                    break;
+                }
+            }
+
+            return " ".repeat(count);
+        }
+        Some('\t') => {
+            return "\t".to_string();
+        }
+        _ => {}
+    }
+
+    "  ".to_string() // default
+}
+
+fn escape_quotes(text: &str) -> String {
+    text.replace("\"", "\\\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use dprint_cli_core::checksums::ChecksumPathOrUrl;
+
+    #[test]
+    fn get_indentation_two_spaces() {
+        assert_eq!(get_indentation("\n  ]", 3), "  ");
+    }
+
+    #[test]
+    fn get_indentation_four_spaces() {
+        assert_eq!(get_indentation("\n    ]", 5), "    ");
+    }
+
+    #[test]
+    fn get_indentation_eight_spaces() {
+        assert_eq!(get_indentation("\n        ]", 9), "        ");
+    }
+
+    #[test]
+    fn get_indentation_tabs() {
+        assert_eq!(get_indentation("\n\t]", 2), "\t");
+    }
+
+    #[test]
+    fn get_toml_binaries_table_ranges_multiple() {
+        let text =
+            "onPreInstall = \"\"\n[[binaries]]\npath = \"a\"\n\n# comment\n[[binaries]]\npath = \"b\"\n[other]\n";
+        assert_eq!(get_toml_binaries_table_ranges(text), vec![(18, 42), (53, 77)]);
+    }
+
+    #[test]
+    fn get_toml_binaries_table_ranges_no_trailing_newline() {
+        let text = "[[binaries]]\npath = \"a\"";
+        assert_eq!(get_toml_binaries_table_ranges(text), vec![(0, text.len())]);
+    }
+
+    #[test]
+    fn get_json_binary_checksum_range_is_entry_of_index() {
+        let text = "{ \"binaries\": [\"a@1\", { \"path\": \"b\", \"checksum\": \"1\" }, { \"path\": \"c@1\" }] }";
+        let get_text = |index: usize| {
+            let (start, end) = get_json_binary_checksum_range(text, index).unwrap();
+            &text[start..end]
+        };
+        assert_eq!(get_text(0), "\"a@1\"");
+        assert_eq!(get_text(1), "\"1\"");
+        assert_eq!(get_text(2), "\"c@1\"");
+    }
+
+    #[test]
+    fn get_toml_binary_checksum_range_is_table_of_index() {
+        let text = "[[binaries]]\npath = \"a\"\nchecksum = \"1\"\n[[binaries]]\npath = \"b\"\nchecksum = \"1\"\n";
+        assert_eq!(get_toml_binary_checksum_range(text, 1).unwrap(), (39, text.len()));
+    }
+
+    #[test]
+    fn remove_binary_from_json_text_keeps_formatting() {
+        let text = "{\n  // comment\n  \"binaries\": [\n    \"a\",\n    { \"path\": \"b\" },\n    \"c\"\n  ]\n}\n";
+        assert_eq!(
+            remove_binary_from_json_text(text, 0).unwrap(),
+            "{\n  // comment\n  \"binaries\": [\n    { \"path\": \"b\" },\n    \"c\"\n  ]\n}\n"
+        );
+        assert_eq!(
+            remove_binary_from_json_text(text, 1).unwrap(),
+            "{\n  // comment\n  \"binaries\": [\n    \"a\",\n    \"c\"\n  ]\n}\n"
+        );
+        assert_eq!(
+            remove_binary_from_json_text(text, 2).unwrap(),
+            "{\n  // comment\n  \"binaries\": [\n    \"a\",\n    { \"path\": \"b\" }\n  ]\n}\n"
+        );
+        assert_eq!(
+            remove_binary_from_json_text("{ \"binaries\": [\"a\",] }", 0).unwrap(),
+            "{ \"binaries\": [] }"
+        );
+    }
+
+    #[test]
+    fn remove_binary_from_toml_text_removes_table() {
+        let text = "onPreInstall = \"\"\n\n[[binaries]]\npath = \"a\"\n\n[[binaries]]\npath = \"b\"\n";
+        assert_eq!(
+            remove_binary_from_toml_text(text, 0).unwrap(),
+            "onPreInstall = \"\"\n\n[[binaries]]\npath = \"b\"\n"
+        );
+        assert_eq!(
+            remove_binary_from_toml_text(text, 1).unwrap(),
+            "onPreInstall = \"\"\n\n[[binaries]]\npath = \"a\"\n"
+        );
+        assert_eq!(
+            remove_binary_from_toml_text("[[binaries]]\npath = \"a\"\n", 0).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn add_binary_to_toml_text_replaces_keys_in_place() {
+        let text = concat!(
+            "[[binaries]]\n",
+            "# the main binary\n",
+            "path = \"a\"\n",
+            "onUse = \"echo used\" # runs on use\n",
+            "checksum = \"old\"\n",
+            "unknown = 1\n",
+            "\n",
+            "[[binaries]]\n",
+            "path = \"b\"\n",
+        );
+        let binary = ConfigFileBinary {
+            path: ChecksumPathOrUrl {
+                path_or_url: "c".to_string(),
+                checksum: Some("new".to_string()),
+            },
+            version: None,
+            version_alias: Some("stable".to_string()),
+            on_install: None,
+            on_use: Some("echo used".to_string()),
+            platforms: None,
+            commands: Default::default(),
+            optional: false,
+            inherited: false,
+        };
+        assert_eq!(
+            add_binary_to_toml_text(text, &binary, Some(0)).unwrap(),
+            concat!(
+                "[[binaries]]\n",
+                "# the main binary\n",
+                "path = \"c\"\n",
+                "onUse = \"echo used\" # runs on use\n",
+                "checksum = \"new\"\n",
+                "version = \"stable\"\n",
+                "unknown = 1\n",
+                "\n",
+                "[[binaries]]\n",
+                "path = \"b\"\n",
+            )
+        );
+        assert_eq!(
+            add_binary_to_toml_text("[[binaries]]\npath = 'b' # comment\nversion = \"1\"", &binary, Some(0)).unwrap(),
+            "[[binaries]]\npath = \"c\" # comment\nversion = \"stable\"\nchecksum = \"new\"\n"
+        );
+    }
+
+    #[test]
+    fn get_next_non_comment_char_skips_comments() {
+        assert_eq!(get_next_non_comment_char("  ,"), Some(','));
+        assert_eq!(get_next_non_comment_char(" // test, \n ,"), Some(','));
+        assert_eq!(get_next_non_comment_char(" /* test, */ \n ]"), Some(']'));
+        assert_eq!(get_next_non_comment_char(" // test"), None);
+    }
+}
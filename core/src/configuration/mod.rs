@@ -0,0 +1,13 @@
+mod find_config_file;
+mod interpolate_env_vars;
+mod manipulation;
+mod read_config_file;
+mod read_toml_config_file;
+mod resolve_config_file_extends;
+
+pub use find_config_file::*;
+pub use interpolate_env_vars::*;
+pub use manipulation::*;
+pub use read_config_file::*;
+use read_toml_config_file::*;
+pub use resolve_config_file_extends::*;
@@ -0,0 +1,290 @@
+use crate::plugins::PLATFORM_KEYS;
+use crate::types::{is_valid_version_alias, CommandName, Version, VersionSelector};
+use crate::utils::verify_min_bvm_version;
+use dprint_cli_core::checksums::{parse_checksum_path_or_url, ChecksumPathOrUrl};
+use dprint_cli_core::types::ErrBox;
+use jsonc_parser::{parse_to_value, JsonValue};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::read_toml_config_file;
+
+pub struct ConfigFile {
+    pub extends: Option<String>,
+    /// Relative paths to the directories of workspace members.
+    pub workspaces: Vec<String>,
+    pub scripts: BTreeMap<String, String>,
+    pub on_pre_install: Option<String>,
+    pub on_post_install: Option<String>,
+    /// Whether to refuse installing archives that aren't verified by a checksum.
+    pub require_checksums: Option<bool>,
+    /// Registry urls to search for the binaries of the project.
+    pub registries: Vec<String>,
+    /// Registry urls that the binaries of an owner are only resolved from. Key is the owner.
+    pub owner_registries: BTreeMap<String, String>,
+    pub binaries: Vec<ConfigFileBinary>,
+}
+
+pub struct ConfigFileBinary {
+    pub path: ChecksumPathOrUrl,
+    pub version: Option<VersionSelector>,
+    /// Name of a user defined version alias or registry tag to resolve the version from.
+    pub version_alias: Option<String>,
+    /// Command to run after the binary is installed.
+    pub on_install: Option<String>,
+    /// Command to run after the binary is used.
+    pub on_use: Option<String>,
+    /// Operating systems (ex. `linux`) or targets (ex. `darwin-aarch64`) the binary is only used on.
+    pub platforms: Option<Vec<String>>,
+    /// Names to expose the binary's commands as in the project. Key is the binary's command name.
+    pub commands: BTreeMap<String, String>,
+    /// If the binary is skipped instead of failing the install when it doesn't support the current platform.
+    pub optional: bool,
+    /// If the binary was inherited from an extended config file.
+    pub inherited: bool,
+}
+
+impl ConfigFileBinary {
+    /// Gets if the binary is used on the provided target (ex. `linux-x86_64`).
+    pub fn is_for_platform(&self, target: &str) -> bool {
+        match &self.platforms {
+            Some(platforms) => platforms
+                .iter()
+                .any(|platform| target == platform || target.starts_with(&format!("{}-", platform))),
+            None => true,
+        }
+    }
+
+    /// Gets the name the binary's command is exposed as in the project.
+    pub fn get_command_name(&self, command_name: &CommandName) -> CommandName {
+        self.commands
+            .iter()
+            .find(|(name, _)| &CommandName::from_string(name.to_string()) == command_name)
+            .map(|(_, new_name)| CommandName::from_string(new_name.to_string()))
+            .unwrap_or_else(|| command_name.clone())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ConfigFileFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFileFormat {
+    pub fn from_path(file_path: &Path) -> ConfigFileFormat {
+        match file_path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFileFormat::Toml,
+            _ => ConfigFileFormat::Json,
+        }
+    }
+}
+
+pub fn read_config_file(file_text: &str, format: ConfigFileFormat) -> Result<ConfigFile, ErrBox> {
+    match format {
+        ConfigFileFormat::Json => read_json_config_file(file_text),
+        ConfigFileFormat::Toml => read_toml_config_file(file_text),
+    }
+}
+
+/// Checks that this version of bvm is at least the `bvmVersion` the config file requires. This is done
+/// before reading the rest of the file so that features of newer versions aren't misread.
+pub(super) fn verify_config_bvm_version(bvm_version: Option<String>) -> Result<(), ErrBox> {
+    if let Some(bvm_version) = bvm_version {
+        match Version::parse(&bvm_version) {
+            Ok(bvm_version) => verify_min_bvm_version(&bvm_version, "configuration file")?,
+            Err(_) => return err!("Expected 'bvmVersion' to be a version, but found '{}'.", bvm_version),
+        }
+    }
+    Ok(())
+}
+
+/// Parses the version of a binary, which is either a version selector or the name of a version alias.
+pub(super) fn parse_binary_version(
+    version: Option<String>,
+) -> Result<(Option<VersionSelector>, Option<String>), ErrBox> {
+    Ok(match version {
+        Some(version) if is_valid_version_alias(&version) => (None, Some(version)),
+        Some(version) => (Some(VersionSelector::parse_for_config(&version)?), None),
+        None => (None, None),
+    })
+}
+
+/// Checks that the platforms of a binary are operating systems or targets that bvm supports.
+pub(super) fn parse_binary_platforms(platforms: Vec<String>) -> Result<Vec<String>, ErrBox> {
+    for platform in platforms.iter() {
+        let is_known = PLATFORM_KEYS
+            .iter()
+            .any(|key| key == platform || key.starts_with(&format!("{}-", platform)));
+        if !is_known {
+            return err!(
+                "Unknown platform '{}'. Expected an operating system (linux, darwin, windows) or one of the targets: {}",
+                platform,
+                PLATFORM_KEYS.join(", ")
+            );
+        }
+    }
+    Ok(platforms)
+}
+
+/// Checks that the names the commands of a binary are exposed as are valid and distinct.
+pub(super) fn parse_binary_commands(commands: Vec<(String, String)>) -> Result<BTreeMap<String, String>, ErrBox> {
+    let mut new_names: Vec<CommandName> = Vec::new();
+    for (command_name, new_name) in commands.iter() {
+        if new_name.is_empty() || new_name.contains(['/', '\\', ' ']) {
+            return err!("Invalid name '{}' for command '{}'.", new_name, command_name);
+        }
+        let new_name = CommandName::from_string(new_name.to_string());
+        if new_names.contains(&new_name) {
+            return err!("Multiple commands were renamed to '{}'.", new_name);
+        }
+        new_names.push(new_name);
+    }
+    Ok(commands.into_iter().collect())
+}
+
+fn read_json_config_file(file_text: &str) -> Result<ConfigFile, ErrBox> {
+    let value = parse_to_value(file_text)?;
+    let mut root_object = match value {
+        Some(JsonValue::Object(obj)) => obj,
+        _ => return err!("Expected a root object in the json file."),
+    };
+    verify_config_bvm_version(root_object.take_string("bvmVersion"))?;
+
+    let json_binaries = root_object
+        .take_array("binaries")
+        .ok_or_else(|| err_obj!("Expected to find a 'binaries' array."))?;
+
+    let mut binaries = Vec::new();
+    for value in json_binaries.into_iter() {
+        binaries.push(match value {
+            JsonValue::String(text) => ConfigFileBinary {
+                path: parse_checksum_path_or_url(&text),
+                version: None,
+                version_alias: None,
+                on_install: None,
+                on_use: None,
+                platforms: None,
+                commands: BTreeMap::new(),
+                optional: false,
+                inherited: false,
+            },
+            JsonValue::Object(mut obj) => {
+                let path = obj
+                    .take_string("path")
+                    .ok_or_else(|| err_obj!("Expected to find a 'path' string in binary object."))?;
+                let version = obj.take_string("version");
+                let checksum = obj.take_string("checksum");
+                let on_install = obj.take_string("onInstall");
+                let (version, version_alias) = parse_binary_version(version)?;
+                let on_use = obj.take_string("onUse");
+                let platforms = match obj.take_array("platforms") {
+                    Some(json_platforms) => {
+                        let mut platforms = Vec::new();
+                        for value in json_platforms.into_iter() {
+                            match value {
+                                JsonValue::String(text) => platforms.push(text),
+                                _ => return err!("Expected a string for items in 'platforms' array."),
+                            }
+                        }
+                        Some(parse_binary_platforms(platforms)?)
+                    }
+                    None => None,
+                };
+                let commands = match obj.take_object("commands") {
+                    Some(json_commands) => {
+                        let mut commands = Vec::new();
+                        for (command_name, value) in json_commands.into_iter() {
+                            match value {
+                                JsonValue::String(new_name) => commands.push((command_name, new_name)),
+                                _ => return err!("Expected a string for the name of command '{}'.", command_name),
+                            }
+                        }
+                        parse_binary_commands(commands)?
+                    }
+                    None => BTreeMap::new(),
+                };
+                let optional = obj.take_boolean("optional").unwrap_or(false);
+
+                ConfigFileBinary {
+                    path: if let Some(checksum) = checksum {
+                        ChecksumPathOrUrl {
+                            path_or_url: path,
+                            checksum: Some(checksum),
+                        }
+                    } else {
+                        parse_checksum_path_or_url(&path)
+                    },
+                    version,
+                    version_alias,
+                    on_install,
+                    on_use,
+                    platforms,
+                    commands,
+                    optional,
+                    inherited: false,
+                }
+            }
+            _ => return err!("Expected a string or object for items in 'binaries' array."),
+        });
+    }
+
+    let extends = root_object.take_string("extends");
+    let mut workspaces = Vec::new();
+    if let Some(json_workspaces) = root_object.take_array("workspaces") {
+        for value in json_workspaces.into_iter() {
+            match value {
+                JsonValue::String(text) => workspaces.push(text),
+                _ => return err!("Expected a string for items in 'workspaces' array."),
+            }
+        }
+    }
+    let mut scripts = BTreeMap::new();
+    if let Some(json_scripts) = root_object.take_object("scripts") {
+        for (name, value) in json_scripts.into_iter() {
+            match value {
+                JsonValue::String(text) => scripts.insert(name, text),
+                _ => return err!("Expected a string for script '{}'.", name),
+            };
+        }
+    }
+
+    let mut registries = Vec::new();
+    if let Some(json_registries) = root_object.take_array("registries") {
+        for value in json_registries.into_iter() {
+            match value {
+                JsonValue::String(url) => registries.push(url),
+                _ => return err!("Expected a string for items in 'registries' array."),
+            }
+        }
+    }
+    let mut owner_registries = BTreeMap::new();
+    if let Some(json_owner_registries) = root_object.take_object("ownerRegistries") {
+        for (owner, value) in json_owner_registries.into_iter() {
+            match value {
+                JsonValue::String(url) => owner_registries.insert(owner, url),
+                _ => return err!("Expected a string for the registry url of owner '{}'.", owner),
+            };
+        }
+    }
+
+    let on_pre_install = root_object.take_string("onPreInstall");
+    let on_post_install = root_object.take_string("onPostInstall");
+    let require_checksums = root_object.take_boolean("requireChecksums");
+
+    if let Some((key, _)) = root_object.into_iter().next() {
+        return err!("Unknown key '{}'", key);
+    }
+
+    Ok(ConfigFile {
+        extends,
+        workspaces,
+        scripts,
+        binaries,
+        on_pre_install,
+        on_post_install,
+        require_checksums,
+        registries,
+        owner_registries,
+    })
+}
@@ -0,0 +1,383 @@
+use dprint_cli_core::checksums::{parse_checksum_path_or_url, ChecksumPathOrUrl};
+use dprint_cli_core::types::ErrBox;
+use std::collections::BTreeMap;
+use toml::Value;
+
+use super::{
+    parse_binary_commands, parse_binary_platforms, parse_binary_version, verify_config_bvm_version, ConfigFile,
+    ConfigFileBinary,
+};
+
+pub fn read_toml_config_file(file_text: &str) -> Result<ConfigFile, ErrBox> {
+    let mut root_table = match file_text.parse::<Value>()? {
+        Value::Table(table) => table,
+        _ => return err!("Expected a root table in the toml file."),
+    };
+    verify_config_bvm_version(take_string(&mut root_table, "bvmVersion")?)?;
+
+    let toml_binaries = match root_table.remove("binaries") {
+        Some(Value::Array(array)) => array,
+        _ => return err!("Expected to find a 'binaries' array."),
+    };
+
+    let mut binaries = Vec::new();
+    for value in toml_binaries.into_iter() {
+        binaries.push(match value {
+            Value::String(text) => ConfigFileBinary {
+                path: parse_checksum_path_or_url(&text),
+                version: None,
+                version_alias: None,
+                on_install: None,
+                on_use: None,
+                platforms: None,
+                commands: BTreeMap::new(),
+                optional: false,
+                inherited: false,
+            },
+            Value::Table(mut table) => {
+                let path = take_string(&mut table, "path")?
+                    .ok_or_else(|| err_obj!("Expected to find a 'path' string in binary table."))?;
+                let version = take_string(&mut table, "version")?;
+                let checksum = take_string(&mut table, "checksum")?;
+                let on_install = take_string(&mut table, "onInstall")?;
+                let (version, version_alias) = parse_binary_version(version)?;
+                let on_use = take_string(&mut table, "onUse")?;
+                let platforms = match table.remove("platforms") {
+                    Some(Value::Array(array)) => {
+                        let mut platforms = Vec::new();
+                        for value in array.into_iter() {
+                            match value {
+                                Value::String(text) => platforms.push(text),
+                                _ => return err!("Expected a string for items in 'platforms' array."),
+                            }
+                        }
+                        Some(parse_binary_platforms(platforms)?)
+                    }
+                    Some(_) => return err!("Expected 'platforms' to be an array."),
+                    None => None,
+                };
+                let commands = match table.remove("commands") {
+                    Some(Value::Table(commands_table)) => {
+                        let mut commands = Vec::new();
+                        for (command_name, value) in commands_table.into_iter() {
+                            match value {
+                                Value::String(new_name) => commands.push((command_name, new_name)),
+                                _ => return err!("Expected a string for the name of command '{}'.", command_name),
+                            }
+                        }
+                        parse_binary_commands(commands)?
+                    }
+                    Some(_) => return err!("Expected 'commands' to be a table."),
+                    None => BTreeMap::new(),
+                };
+                let optional = match table.remove("optional") {
+                    Some(Value::Boolean(value)) => value,
+                    Some(_) => return err!("Expected 'optional' to be a boolean."),
+                    None => false,
+                };
+
+                ConfigFileBinary {
+                    path: if let Some(checksum) = checksum {
+                        ChecksumPathOrUrl {
+                            path_or_url: path,
+                            checksum: Some(checksum),
+                        }
+                    } else {
+                        parse_checksum_path_or_url(&path)
+                    },
+                    version,
+                    version_alias,
+                    on_install,
+                    on_use,
+                    platforms,
+                    commands,
+                    optional,
+                    inherited: false,
+                }
+            }
+            _ => return err!("Expected a string or table for items in 'binaries' array."),
+        });
+    }
+
+    let extends = take_string(&mut root_table, "extends")?;
+    let mut workspaces = Vec::new();
+    match root_table.remove("workspaces") {
+        Some(Value::Array(array)) => {
+            for value in array.into_iter() {
+                match value {
+                    Value::String(text) => workspaces.push(text),
+                    _ => return err!("Expected a string for items in 'workspaces' array."),
+                }
+            }
+        }
+        Some(_) => return err!("Expected 'workspaces' to be an array."),
+        None => {}
+    }
+    let mut scripts = BTreeMap::new();
+    match root_table.remove("scripts") {
+        Some(Value::Table(table)) => {
+            for (name, value) in table.into_iter() {
+                match value {
+                    Value::String(text) => scripts.insert(name, text),
+                    _ => return err!("Expected a string for script '{}'.", name),
+                };
+            }
+        }
+        Some(_) => return err!("Expected 'scripts' to be a table."),
+        None => {}
+    }
+
+    let mut registries = Vec::new();
+    match root_table.remove("registries") {
+        Some(Value::Array(array)) => {
+            for value in array.into_iter() {
+                match value {
+                    Value::String(url) => registries.push(url),
+                    _ => return err!("Expected a string for items in 'registries' array."),
+                }
+            }
+        }
+        Some(_) => return err!("Expected 'registries' to be an array."),
+        None => {}
+    }
+    let mut owner_registries = BTreeMap::new();
+    match root_table.remove("ownerRegistries") {
+        Some(Value::Table(table)) => {
+            for (owner, value) in table.into_iter() {
+                match value {
+                    Value::String(url) => owner_registries.insert(owner, url),
+                    _ => return err!("Expected a string for the registry url of owner '{}'.", owner),
+                };
+            }
+        }
+        Some(_) => return err!("Expected 'ownerRegistries' to be a table."),
+        None => {}
+    }
+
+    let on_pre_install = take_string(&mut root_table, "onPreInstall")?;
+    let on_post_install = take_string(&mut root_table, "onPostInstall")?;
+    let require_checksums = match root_table.remove("requireChecksums") {
+        Some(Value::Boolean(value)) => Some(value),
+        Some(_) => return err!("Expected 'requireChecksums' to be a boolean."),
+        None => None,
+    };
+
+    if let Some((key, _)) = root_table.into_iter().next() {
+        return err!("Unknown key '{}'", key);
+    }
+
+    Ok(ConfigFile {
+        extends,
+        workspaces,
+        scripts,
+        binaries,
+        on_pre_install,
+        on_post_install,
+        require_checksums,
+        registries,
+        owner_registries,
+    })
+}
+
+fn take_string(table: &mut toml::value::Table, key: &str) -> Result<Option<String>, ErrBox> {
+    match table.remove(key) {
+        Some(Value::String(text)) => Ok(Some(text)),
+        Some(_) => err!("Expected '{}' to be a string.", key),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::CommandName;
+
+    #[test]
+    fn reads_binaries_tables() {
+        let config_file = read_toml_config_file(
+            r#"
+onPreInstall = "echo pre"
+requireChecksums = true
+
+[[binaries]]
+path = "https://localhost/package.json"
+checksum = "123"
+version = "^1.0.0"
+
+[[binaries]]
+path = "https://localhost/other.json@456"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config_file.on_pre_install, Some("echo pre".to_string()));
+        assert_eq!(config_file.on_post_install, None);
+        assert_eq!(config_file.require_checksums, Some(true));
+        assert_eq!(config_file.binaries.len(), 2);
+        assert_eq!(
+            config_file.binaries[0].path.path_or_url,
+            "https://localhost/package.json"
+        );
+        assert_eq!(config_file.binaries[0].path.checksum, Some("123".to_string()));
+        assert_eq!(config_file.binaries[0].version.as_ref().unwrap().as_str(), "^1.0.0");
+        assert_eq!(config_file.binaries[1].path.path_or_url, "https://localhost/other.json");
+        assert_eq!(config_file.binaries[1].path.checksum, Some("456".to_string()));
+        assert!(config_file.binaries[1].version.is_none());
+    }
+
+    #[test]
+    fn verifies_bvm_version() {
+        let config_file = read_toml_config_file(
+            r#"
+bvmVersion = "0.1.0"
+
+[[binaries]]
+path = "https://localhost/package.json"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config_file.binaries.len(), 1);
+
+        let err = read_toml_config_file(
+            r#"
+bvmVersion = "999.0.0"
+newFeature = true
+"#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "This configuration file requires bvm >= 999.0.0, you have {}. Run `bvm self-update` to upgrade.",
+                crate::utils::BVM_VERSION
+            )
+        );
+
+        let err = read_toml_config_file(r#"bvmVersion = "latest""#).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Expected 'bvmVersion' to be a version, but found 'latest'."
+        );
+    }
+
+    #[test]
+    fn reads_binary_platforms() {
+        let config_file = read_toml_config_file(
+            r#"
+[[binaries]]
+path = "https://localhost/package.json"
+platforms = ["linux", "darwin-aarch64"]
+optional = true
+"#,
+        )
+        .unwrap();
+        let binary = &config_file.binaries[0];
+        assert_eq!(
+            binary.platforms,
+            Some(vec!["linux".to_string(), "darwin-aarch64".to_string()])
+        );
+        assert_eq!(binary.optional, true);
+        assert_eq!(binary.is_for_platform("linux-x86_64"), true);
+        assert_eq!(binary.is_for_platform("linux-x86_64-musl"), true);
+        assert_eq!(binary.is_for_platform("darwin-aarch64"), true);
+        assert_eq!(binary.is_for_platform("darwin-x86_64"), false);
+        assert_eq!(binary.is_for_platform("windows-x86_64"), false);
+
+        let err = read_toml_config_file(
+            r#"
+[[binaries]]
+path = "https://localhost/package.json"
+platforms = ["macos"]
+"#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Unknown platform 'macos'. Expected an operating system (linux, darwin, windows) or one of the targets: linux-x86_64, linux-x86_64-musl, darwin-x86_64, darwin-aarch64, windows-x86_64, windows-aarch64"
+        );
+    }
+
+    #[test]
+    fn reads_binary_commands() {
+        let config_file = read_toml_config_file(
+            r#"
+[[binaries]]
+path = "https://localhost/package.json"
+commands = { node = "node14" }
+"#,
+        )
+        .unwrap();
+        let binary = &config_file.binaries[0];
+        assert_eq!(
+            binary.get_command_name(&CommandName::from_string("node".to_string())),
+            CommandName::from_string("node14".to_string())
+        );
+        assert_eq!(
+            binary.get_command_name(&CommandName::from_string("npm".to_string())),
+            CommandName::from_string("npm".to_string())
+        );
+
+        let err = read_toml_config_file(
+            r#"
+[[binaries]]
+path = "https://localhost/package.json"
+commands = { node = "node14", npm = "node14" }
+"#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "Multiple commands were renamed to 'node14'.");
+    }
+
+    #[test]
+    fn reads_inline_binaries_array() {
+        let config_file = read_toml_config_file(r#"binaries = ["https://localhost/package.json"]"#).unwrap();
+        assert_eq!(config_file.binaries.len(), 1);
+        assert_eq!(
+            config_file.binaries[0].path.path_or_url,
+            "https://localhost/package.json"
+        );
+    }
+
+    #[test]
+    fn reads_owner_registries_table() {
+        let config_file = read_toml_config_file(
+            r#"
+binaries = []
+
+[ownerRegistries]
+myorg = "https://localhost/registry.json"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config_file.owner_registries.get("myorg"),
+            Some(&"https://localhost/registry.json".to_string())
+        );
+    }
+
+    #[test]
+    fn reads_registries_array() {
+        let config_file = read_toml_config_file(
+            r#"
+registries = ["https://localhost/registry.json"]
+binaries = []
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config_file.registries,
+            vec!["https://localhost/registry.json".to_string()]
+        );
+        let err = read_toml_config_file("registries = [5]\nbinaries = []").err().unwrap();
+        assert_eq!(err.to_string(), "Expected a string for items in 'registries' array.");
+    }
+
+    #[test]
+    fn errors_unknown_key() {
+        let err = read_toml_config_file("test = 5\nbinaries = []").err().unwrap();
+        assert_eq!(err.to_string(), "Unknown key 'test'");
+    }
+}
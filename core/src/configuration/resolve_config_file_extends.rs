@@ -0,0 +1,196 @@
+use dprint_cli_core::types::ErrBox;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use super::{interpolate_env_vars, read_config_file, ConfigFile, ConfigFileFormat};
+use crate::environment::Environment;
+use crate::plugins::{self, resolve_plugin_file_url};
+use crate::registry;
+use crate::utils::{normalize_path, UrlCache};
+
+enum ConfigFileLocation {
+    Path(PathBuf),
+    Url(Url),
+}
+
+impl ConfigFileLocation {
+    fn resolve(&self, specifier: &str) -> Result<ConfigFileLocation, ErrBox> {
+        if let Ok(url) = Url::parse(specifier) {
+            if url.scheme() == "http" || url.scheme() == "https" {
+                return Ok(ConfigFileLocation::Url(url));
+            }
+        }
+
+        Ok(match self {
+            ConfigFileLocation::Path(file_path) => {
+                let base_dir = file_path.parent().unwrap_or_else(|| Path::new(""));
+                ConfigFileLocation::Path(normalize_path(&base_dir.join(specifier)))
+            }
+            ConfigFileLocation::Url(url) => ConfigFileLocation::Url(url.join(specifier)?),
+        })
+    }
+
+    /// Resolves a binary's plugin file path relative to the config file.
+    fn resolve_binary_path(&self, path_or_url: &str) -> Result<String, ErrBox> {
+        match self {
+            ConfigFileLocation::Path(file_path) => {
+                resolve_plugin_file_url(file_path.parent().unwrap_or_else(|| Path::new("")), path_or_url)
+            }
+            ConfigFileLocation::Url(url) => match Url::parse(path_or_url) {
+                Ok(_) => Ok(path_or_url.to_string()),
+                Err(_) => Ok(url.join(path_or_url)?.to_string()),
+            },
+        }
+    }
+
+    fn display(&self) -> String {
+        match self {
+            ConfigFileLocation::Path(file_path) => file_path.display().to_string(),
+            ConfigFileLocation::Url(url) => url.to_string(),
+        }
+    }
+
+    fn format(&self) -> ConfigFileFormat {
+        match self {
+            ConfigFileLocation::Path(file_path) => ConfigFileFormat::from_path(file_path),
+            ConfigFileLocation::Url(url) => ConfigFileFormat::from_path(Path::new(url.path())),
+        }
+    }
+
+    fn read_text(&self, environment: &impl Environment) -> Result<String, ErrBox> {
+        match self {
+            ConfigFileLocation::Path(file_path) => environment.read_file_text(file_path),
+            ConfigFileLocation::Url(url) => {
+                // the file is read whenever the config file is, so keep a copy like a registry file
+                plugins::verify_trusted_host(environment, url.as_str())?;
+                let cache = UrlCache::new(environment, "extends-cache");
+                let bytes = registry::download_cached_file(environment, &cache, "extended config file", url.as_str())?;
+                Ok(String::from_utf8(bytes)?)
+            }
+        }
+    }
+}
+
+/// Merges the config files specified in the "extends" property of the config file
+/// into the config file. Local entries take precedence over inherited ones.
+pub fn resolve_config_file_extends(
+    environment: &impl Environment,
+    config_file_path: &Path,
+    config_file: ConfigFile,
+) -> Result<ConfigFile, ErrBox> {
+    let mut resolved_locations = vec![config_file_path.display().to_string()];
+    resolve_for_location(
+        environment,
+        &ConfigFileLocation::Path(config_file_path.to_path_buf()),
+        config_file,
+        &mut resolved_locations,
+    )
+}
+
+fn resolve_for_location(
+    environment: &impl Environment,
+    location: &ConfigFileLocation,
+    mut config_file: ConfigFile,
+    resolved_locations: &mut Vec<String>,
+) -> Result<ConfigFile, ErrBox> {
+    interpolate_config_file_env_vars(environment, &mut config_file)
+        .map_err(|err| err_obj!("Error reading {}: {}", location.display(), err.to_string()))?;
+    for binary in config_file.binaries.iter_mut() {
+        binary.path.path_or_url = location.resolve_binary_path(&binary.path.path_or_url)?;
+    }
+
+    let extends = match config_file.extends.take() {
+        Some(extends) => extends,
+        None => return Ok(config_file),
+    };
+    let extends_location = location.resolve(&extends)?;
+    let extends_display = extends_location.display();
+    if resolved_locations.contains(&extends_display) {
+        return err!("Circular extends detected for {}", extends_display);
+    }
+    resolved_locations.push(extends_display.clone());
+
+    let extends_config_file = read_config_file(&extends_location.read_text(environment)?, extends_location.format())
+        .map_err(|err| err_obj!("Error reading {}: {}", extends_display, err.to_string()))?;
+    let extends_config_file =
+        resolve_for_location(environment, &extends_location, extends_config_file, resolved_locations)?;
+
+    Ok(merge_config_files(extends_config_file, config_file))
+}
+
+/// Replaces the environment variables referenced in the urls and checksums of the config file.
+/// The references in a binary's url are only checked since its source is stored and displayed
+/// with them (ex. a token in the url), so they're replaced when the url is downloaded.
+fn interpolate_config_file_env_vars(
+    environment: &impl Environment,
+    config_file: &mut ConfigFile,
+) -> Result<(), ErrBox> {
+    for binary in config_file.binaries.iter_mut() {
+        let path_or_url = interpolate_env_vars(environment, &binary.path.path_or_url)?;
+        // a relative path is resolved to a url, which would escape the references
+        if !is_url(&binary.path.path_or_url) {
+            binary.path.path_or_url = path_or_url;
+        }
+        if let Some(checksum) = &binary.path.checksum {
+            binary.path.checksum = Some(interpolate_env_vars(environment, checksum)?);
+        }
+    }
+    if let Some(extends) = &config_file.extends {
+        config_file.extends = Some(interpolate_env_vars(environment, extends)?);
+    }
+    for url in config_file.registries.iter_mut() {
+        *url = interpolate_env_vars(environment, url)?;
+    }
+    for url in config_file.owner_registries.values_mut() {
+        *url = interpolate_env_vars(environment, url)?;
+    }
+    Ok(())
+}
+
+fn is_url(text: &str) -> bool {
+    matches!(Url::parse(text), Ok(url) if url.scheme().len() > 1)
+}
+
+fn merge_config_files(base: ConfigFile, local: ConfigFile) -> ConfigFile {
+    // inherited binaries come first so that local binaries are resolved last and override them
+    let mut binaries = Vec::new();
+    for mut binary in base.binaries.into_iter() {
+        let is_overridden = local
+            .binaries
+            .iter()
+            .any(|local_binary| local_binary.path.path_or_url == binary.path.path_or_url);
+        if !is_overridden {
+            binary.inherited = true;
+            binaries.push(binary);
+        }
+    }
+    binaries.extend(local.binaries);
+
+    ConfigFile {
+        extends: None,
+        workspaces: local.workspaces,
+        scripts: {
+            let mut scripts = base.scripts;
+            scripts.extend(local.scripts);
+            scripts
+        },
+        on_pre_install: local.on_pre_install.or(base.on_pre_install),
+        on_post_install: local.on_post_install.or(base.on_post_install),
+        require_checksums: local.require_checksums.or(base.require_checksums),
+        registries: {
+            let mut registries = local.registries;
+            for url in base.registries.into_iter() {
+                if !registries.contains(&url) {
+                    registries.push(url);
+                }
+            }
+            registries
+        },
+        owner_registries: {
+            let mut owner_registries = base.owner_registries;
+            owner_registries.extend(local.owner_registries);
+            owner_registries
+        },
+        binaries,
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Time without any downloaded bytes after which the limiter stops accounting for earlier downloads,
+/// so being idle doesn't allow a burst over the limit afterwards.
+const IDLE_RESET_DURATION: Duration = Duration::from_secs(1);
+
+/// Limits the combined throughput of all the downloads of a process, including parallel ones.
+pub struct BandwidthLimiter {
+    /// Maximum bytes per second or 0 for no limit.
+    max_bytes_per_sec: AtomicU64,
+    /// Start time of the current period of downloading, the bytes downloaded in it, and the time bytes were last downloaded.
+    state: Mutex<Option<(Instant, u64, Instant)>>,
+}
+
+impl BandwidthLimiter {
+    pub fn new(max_bytes_per_sec: Option<u64>) -> BandwidthLimiter {
+        BandwidthLimiter {
+            max_bytes_per_sec: AtomicU64::new(max_bytes_per_sec.unwrap_or(0)),
+            state: Mutex::new(None),
+        }
+    }
+
+    pub fn get_max_bytes_per_sec(&self) -> Option<u64> {
+        match self.max_bytes_per_sec.load(Ordering::SeqCst) {
+            0 => None,
+            value => Some(value),
+        }
+    }
+
+    pub fn set_max_bytes_per_sec(&self, value: Option<u64>) {
+        self.max_bytes_per_sec.store(value.unwrap_or(0), Ordering::SeqCst);
+    }
+
+    /// Records the downloaded bytes and sleeps for however long is necessary to stay under the limit.
+    pub fn consume(&self, byte_count: usize) {
+        let max_bytes_per_sec = match self.get_max_bytes_per_sec() {
+            Some(value) => value,
+            None => return,
+        };
+        let delay = {
+            let mut state = self.state.lock().unwrap();
+            let now = Instant::now();
+            let (start, total_bytes) = match *state {
+                Some((start, total_bytes, last_time)) if now.duration_since(last_time) < IDLE_RESET_DURATION => {
+                    (start, total_bytes + byte_count as u64)
+                }
+                _ => (now, byte_count as u64),
+            };
+            let delay = get_throttle_delay(total_bytes, now.duration_since(start), max_bytes_per_sec);
+            *state = Some((start, total_bytes, now + delay));
+            delay
+        };
+        if delay > Duration::from_millis(0) {
+            std::thread::sleep(delay);
+        }
+    }
+}
+
+/// Gets how long to wait so that the bytes downloaded over the elapsed time don't exceed the limit.
+fn get_throttle_delay(total_bytes: u64, elapsed: Duration, max_bytes_per_sec: u64) -> Duration {
+    let expected = Duration::from_secs_f64(total_bytes as f64 / max_bytes_per_sec as f64);
+    expected.checked_sub(elapsed).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_get_throttle_delay() {
+        assert_eq!(
+            get_throttle_delay(1024, Duration::from_millis(0), 1024),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            get_throttle_delay(2048, Duration::from_millis(500), 1024),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            get_throttle_delay(1024, Duration::from_secs(2), 1024),
+            Duration::from_millis(0)
+        );
+    }
+
+    #[test]
+    fn should_not_limit_without_max() {
+        let limiter = BandwidthLimiter::new(None);
+        assert_eq!(limiter.get_max_bytes_per_sec(), None);
+        limiter.consume(usize::MAX);
+        limiter.set_max_bytes_per_sec(Some(1024));
+        assert_eq!(limiter.get_max_bytes_per_sec(), Some(1024));
+    }
+}
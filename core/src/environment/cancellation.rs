@@ -0,0 +1,113 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static IS_CTRL_C_PRESSED: AtomicBool = AtomicBool::new(false);
+static CTRL_C_GUARD_COUNT: AtomicUsize = AtomicUsize::new(0);
+/// Signal handler that was set before the first guard, which is restored once the last guard is dropped.
+#[cfg(unix)]
+static PREVIOUS_SIGINT_HANDLER: AtomicUsize = AtomicUsize::new(0);
+
+/// Error for when a download was cancelled with ctrl+c.
+#[derive(Debug)]
+pub struct DownloadCancelledError {
+    pub url: String,
+}
+
+impl fmt::Display for DownloadCancelledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Cancelled downloading {}.", self.url)
+    }
+}
+
+impl std::error::Error for DownloadCancelledError {}
+
+/// Gets if ctrl+c was pressed while a download was in progress.
+pub fn is_ctrl_c_pressed() -> bool {
+    IS_CTRL_C_PRESSED.load(Ordering::SeqCst)
+}
+
+/// Handles ctrl+c while held by marking the downloads as cancelled instead of terminating the process,
+/// so that the download fails and any partial files and locks are cleaned up while the error is returned.
+/// Dropping the outermost guard restores the previous handler and clears the cancellation so that it
+/// doesn't affect later downloads.
+pub(super) struct CtrlCGuard;
+
+impl CtrlCGuard {
+    pub fn new() -> CtrlCGuard {
+        if CTRL_C_GUARD_COUNT.fetch_add(1, Ordering::SeqCst) == 0 {
+            set_ctrl_c_handler(true);
+        }
+        CtrlCGuard
+    }
+}
+
+impl Drop for CtrlCGuard {
+    fn drop(&mut self) {
+        if CTRL_C_GUARD_COUNT.fetch_sub(1, Ordering::SeqCst) == 1 {
+            set_ctrl_c_handler(false);
+            IS_CTRL_C_PRESSED.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn set_ctrl_c_handler(is_enabled: bool) {
+    extern "C" {
+        fn signal(signum: i32, handler: usize) -> usize;
+    }
+    extern "C" fn handle_sigint(_: i32) {
+        IS_CTRL_C_PRESSED.store(true, Ordering::SeqCst);
+    }
+    const SIGINT: i32 = 2;
+    const SIG_DFL: usize = 0;
+    const SIG_ERR: usize = usize::MAX;
+    unsafe {
+        if is_enabled {
+            let previous_handler = signal(SIGINT, handle_sigint as *const () as usize);
+            let previous_handler = if previous_handler == SIG_ERR {
+                SIG_DFL
+            } else {
+                previous_handler
+            };
+            PREVIOUS_SIGINT_HANDLER.store(previous_handler, Ordering::SeqCst);
+        } else {
+            signal(SIGINT, PREVIOUS_SIGINT_HANDLER.load(Ordering::SeqCst));
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_ctrl_c_handler(is_enabled: bool) {
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: Option<unsafe extern "system" fn(u32) -> i32>, add: i32) -> i32;
+    }
+    unsafe extern "system" fn handle_ctrl_c(ctrl_type: u32) -> i32 {
+        const CTRL_C_EVENT: u32 = 0;
+        const CTRL_BREAK_EVENT: u32 = 1;
+        if ctrl_type == CTRL_C_EVENT || ctrl_type == CTRL_BREAK_EVENT {
+            IS_CTRL_C_PRESSED.store(true, Ordering::SeqCst);
+            1
+        } else {
+            0
+        }
+    }
+    unsafe {
+        SetConsoleCtrlHandler(Some(handle_ctrl_c), is_enabled as i32);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_clear_cancellation_when_outermost_guard_dropped() {
+        let outer_guard = CtrlCGuard::new();
+        let inner_guard = CtrlCGuard::new();
+        IS_CTRL_C_PRESSED.store(true, Ordering::SeqCst);
+        drop(inner_guard);
+        assert!(is_ctrl_c_pressed());
+        drop(outer_guard);
+        assert!(!is_ctrl_c_pressed());
+    }
+}
@@ -0,0 +1,21 @@
+/// The separator used for the system path
+pub const SYS_PATH_DELIMITER: &'static str = if cfg!(target_os = "windows") { ";" } else { ":" };
+
+/// Errors when making changes to the machine-wide installation without the privileges to do so.
+pub fn ensure_system_privileges(environment: &impl super::Environment) -> Result<(), dprint_cli_core::types::ErrBox> {
+    if !environment.is_system_mode() || environment.has_system_privileges() {
+        Ok(())
+    } else if cfg!(target_os = "windows") {
+        err!("Changing the system-wide installation requires administrator privileges. Run the command again from an elevated terminal.")
+    } else {
+        err!("Changing the system-wide installation requires root privileges. Run the command again with `sudo`.")
+    }
+}
+
+/// Gets the local file path of a `file://` url.
+pub fn get_file_url_path(url: &str) -> Option<std::path::PathBuf> {
+    match url::Url::parse(url) {
+        Ok(url) if url.scheme() == "file" => url.to_file_path().ok(),
+        _ => None,
+    }
+}
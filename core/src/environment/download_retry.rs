@@ -0,0 +1,154 @@
+use dprint_cli_core::types::ErrBox;
+use std::fmt;
+use std::time::Duration;
+
+use super::{Environment, UserConfig};
+use crate::utils::yellow;
+
+/// How downloads that fail with a transient error are retried.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Number of times to attempt a download, including the first attempt.
+    pub attempts: u32,
+    /// Time to wait before the first retry, which doubles for each retry after.
+    pub backoff: Duration,
+    /// Response statuses that are considered transient.
+    pub retry_on_status: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_millis(1000),
+            retry_on_status: vec![408, 429, 500, 502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_user_config(user_config: &UserConfig) -> RetryPolicy {
+        let default = RetryPolicy::default();
+        match &user_config.download_retry {
+            Some(config) => RetryPolicy {
+                attempts: config.attempts.unwrap_or(default.attempts).max(1),
+                backoff: config.backoff_ms.map(Duration::from_millis).unwrap_or(default.backoff),
+                retry_on_status: config.retry_on_status.clone().unwrap_or(default.retry_on_status),
+            },
+            None => default,
+        }
+    }
+
+    /// Gets the time to wait before the provided retry, where the first retry is 1.
+    fn get_backoff(&self, retry: u32) -> Duration {
+        self.backoff * 2u32.saturating_pow(retry - 1)
+    }
+
+    fn is_transient(&self, err: &ErrBox) -> bool {
+        if let Some(err) = err.downcast_ref::<DownloadStatusError>() {
+            self.retry_on_status.contains(&err.status)
+        } else {
+            err.is::<DownloadNetworkError>()
+        }
+    }
+}
+
+/// Error for when the server responded to a download with an unexpected status.
+#[derive(Debug)]
+pub struct DownloadStatusError {
+    pub url: String,
+    pub status: u16,
+}
+
+impl fmt::Display for DownloadStatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error downloading {}. Status: {}", self.url, self.status)
+    }
+}
+
+impl std::error::Error for DownloadStatusError {}
+
+/// Error for when a download failed because of the connection.
+#[derive(Debug)]
+pub struct DownloadNetworkError {
+    pub url: String,
+    pub message: String,
+}
+
+impl fmt::Display for DownloadNetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error downloading {}. {}", self.url, self.message)
+    }
+}
+
+impl std::error::Error for DownloadNetworkError {}
+
+/// Runs the download, retrying it with exponential backoff while it fails with a transient error.
+pub fn download_with_retries<T>(
+    environment: &impl Environment,
+    policy: &RetryPolicy,
+    mut download: impl FnMut() -> Result<T, ErrBox>,
+) -> Result<T, ErrBox> {
+    let mut attempt = 1;
+    loop {
+        match download() {
+            Err(err) if attempt < policy.attempts && policy.is_transient(&err) => {
+                let backoff = policy.get_backoff(attempt);
+                attempt += 1;
+                environment.log_error(&yellow(&format!(
+                    "Warning: {}. Retrying in {} (attempt {} of {})...",
+                    err,
+                    display_duration(backoff),
+                    attempt,
+                    policy.attempts
+                )));
+                environment.sleep(backoff);
+            }
+            result => return result,
+        }
+    }
+}
+
+fn display_duration(duration: Duration) -> String {
+    if duration.subsec_millis() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_double_backoff() {
+        let policy = RetryPolicy {
+            backoff: Duration::from_millis(500),
+            ..Default::default()
+        };
+        assert_eq!(policy.get_backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.get_backoff(2), Duration::from_millis(1000));
+        assert_eq!(policy.get_backoff(3), Duration::from_millis(2000));
+    }
+
+    #[test]
+    fn should_only_retry_transient_errors() {
+        let policy = RetryPolicy::default();
+        let status_err = |status: u16| -> ErrBox {
+            Box::new(DownloadStatusError {
+                url: "https://localhost".to_string(),
+                status,
+            })
+        };
+        assert_eq!(policy.is_transient(&status_err(502)), true);
+        assert_eq!(policy.is_transient(&status_err(404)), false);
+        let network_err: ErrBox = Box::new(DownloadNetworkError {
+            url: "https://localhost".to_string(),
+            message: "Connection Failed".to_string(),
+        });
+        assert_eq!(policy.is_transient(&network_err), true);
+        let other_err: ErrBox = dprint_cli_core::err_obj!("Other");
+        assert_eq!(policy.is_transient(&other_err), false);
+    }
+}
@@ -0,0 +1,444 @@
+use dprint_cli_core::logging::{ProgressBar, ProgressBarStyle, ProgressBars};
+use dprint_cli_core::types::ErrBox;
+use std::io::{BufReader, Read};
+use std::sync::Arc;
+use url::Url;
+
+use super::{
+    get_netrc_file_path, is_ctrl_c_pressed, BandwidthLimiter, CacheValidators, ConditionalDownload,
+    DownloadCancelledError, DownloadNetworkError, DownloadStatusError, Netrc, RetryPolicy, UserConfig,
+};
+use crate::registry::SourceRequest;
+
+/// Milliseconds to wait for the connection to the server by default.
+const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 10_000;
+/// Milliseconds to wait for the server to send more data by default.
+const DEFAULT_READ_TIMEOUT_MS: u64 = 30_000;
+
+/// Options used when downloading from a url.
+#[derive(Default)]
+pub struct DownloadOptions {
+    http_proxy: Option<String>,
+    https_proxy: Option<String>,
+    no_proxy: Vec<String>,
+    tls_config: Option<Arc<rustls::ClientConfig>>,
+    netrc: Option<Netrc>,
+    /// Milliseconds to wait for the connection, where 0 waits indefinitely.
+    connect_timeout_ms: u64,
+    /// Milliseconds to wait for each read of the response, where 0 waits indefinitely.
+    read_timeout_ms: u64,
+    pub retry_policy: RetryPolicy,
+}
+
+impl DownloadOptions {
+    pub fn new(
+        user_config: &UserConfig,
+        get_env_var: impl Fn(&str) -> Option<String>,
+    ) -> Result<DownloadOptions, ErrBox> {
+        let get_env_var = |name: &str| get_env_var(name).or_else(|| get_env_var(&name.to_lowercase()));
+        let (http_proxy, https_proxy) = match &user_config.proxy {
+            Some(proxy) => (Some(proxy.clone()), Some(proxy.clone())),
+            None => (get_env_var("HTTP_PROXY"), get_env_var("HTTPS_PROXY")),
+        };
+        let no_proxy = user_config.no_proxy.clone().or_else(|| get_env_var("NO_PROXY"));
+        let ca_bundle = get_env_var("BVM_CA_BUNDLE").or_else(|| user_config.ca_bundle.clone());
+        let netrc = get_netrc_file_path(get_env_var)
+            .and_then(|file_path| std::fs::read_to_string(file_path).ok())
+            .map(|text| Netrc::parse(&text));
+
+        Ok(DownloadOptions {
+            http_proxy: http_proxy.filter(|p| !p.is_empty()),
+            https_proxy: https_proxy.filter(|p| !p.is_empty()),
+            no_proxy: no_proxy
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(|host| host.trim().to_lowercase())
+                        .filter(|host| !host.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            tls_config: match ca_bundle.filter(|p| !p.is_empty()) {
+                Some(ca_bundle) => Some(Arc::new(create_tls_config(&ca_bundle)?)),
+                None => None,
+            },
+            netrc,
+            connect_timeout_ms: user_config
+                .download_timeout
+                .as_ref()
+                .and_then(|timeout| timeout.connect_ms)
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT_MS),
+            read_timeout_ms: user_config
+                .download_timeout
+                .as_ref()
+                .and_then(|timeout| timeout.read_ms)
+                .unwrap_or(DEFAULT_READ_TIMEOUT_MS),
+            retry_policy: RetryPolicy::from_user_config(user_config),
+        })
+    }
+
+    fn get_proxy(&self, url: &Url) -> Option<&String> {
+        let proxy = match url.scheme() {
+            "https" => self.https_proxy.as_ref(),
+            "http" => self.http_proxy.as_ref(),
+            _ => return None,
+        };
+        match url.host_str() {
+            Some(host) if self.is_no_proxy_host(host) => None,
+            _ => proxy,
+        }
+    }
+
+    fn is_no_proxy_host(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        self.no_proxy.iter().any(|no_proxy_host| {
+            if no_proxy_host == "*" {
+                return true;
+            }
+            // ignore any port and match subdomains (ex. `example.com` or `.example.com` match `sub.example.com`)
+            let no_proxy_host = no_proxy_host.split(':').next().unwrap().trim_start_matches('.');
+            host == no_proxy_host || host.ends_with(&format!(".{}", no_proxy_host))
+        })
+    }
+}
+
+/// Creates a TLS configuration that trusts the default root certificates and the certificates in the provided file.
+fn create_tls_config(ca_bundle_path: &str) -> Result<rustls::ClientConfig, ErrBox> {
+    let file = match std::fs::File::open(ca_bundle_path) {
+        Ok(file) => file,
+        Err(err) => return err!("Error reading CA bundle {}. {}", ca_bundle_path, err.to_string()),
+    };
+    let mut config = rustls::ClientConfig::new();
+    config
+        .root_store
+        .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+    match config.root_store.add_pem_file(&mut BufReader::new(file)) {
+        Ok((valid_count, _)) if valid_count > 0 => Ok(config),
+        _ => err!("Could not find any valid certificates in CA bundle {}.", ca_bundle_path),
+    }
+}
+
+pub fn download_url(
+    url: &str,
+    source_request: &SourceRequest,
+    options: &DownloadOptions,
+    progress_bars: &Option<ProgressBars>,
+    bandwidth_limiter: &BandwidthLimiter,
+) -> Result<Vec<u8>, ErrBox> {
+    match download_url_if_modified(
+        url,
+        source_request,
+        options,
+        progress_bars,
+        bandwidth_limiter,
+        &CacheValidators::default(),
+    )? {
+        ConditionalDownload::Modified(bytes, _) => Ok(bytes),
+        ConditionalDownload::NotModified => err!("Error downloading {}. Status: 304", url),
+    }
+}
+
+pub fn download_url_if_modified(
+    url: &str,
+    source_request: &SourceRequest,
+    options: &DownloadOptions,
+    progress_bars: &Option<ProgressBars>,
+    bandwidth_limiter: &BandwidthLimiter,
+    validators: &CacheValidators,
+) -> Result<ConditionalDownload, ErrBox> {
+    let response = match send_download_request(url, source_request, options, validators)? {
+        Some(response) => response,
+        None => return Ok(ConditionalDownload::NotModified),
+    };
+    let validators = response.validators.clone();
+    let mut reader = response.into_reader(url, progress_bars, bandwidth_limiter);
+    match inner_download(&mut reader) {
+        Ok(result) => Ok(ConditionalDownload::Modified(result, validators)),
+        Err(_) if is_ctrl_c_pressed() => Err(Box::new(DownloadCancelledError { url: url.to_string() })),
+        Err(err) => Err(Box::new(DownloadNetworkError {
+            url: url.to_string(),
+            message: err.to_string(),
+        })),
+    }
+}
+
+/// Response of a download request whose body hasn't been read yet.
+pub struct DownloadResponse {
+    response: ureq::Response,
+    total_size: usize,
+    pub validators: CacheValidators,
+}
+
+impl DownloadResponse {
+    /// Gets a reader of the body that shows the download's progress and limits its throughput as it's read.
+    pub fn into_reader<'a>(
+        self,
+        url: &str,
+        progress_bars: &Option<ProgressBars>,
+        bandwidth_limiter: &'a BandwidthLimiter,
+    ) -> DownloadReader<'a, impl Read> {
+        let progress_bar = progress_bars.as_ref().map(|progress_bars| {
+            let message = format!("Downloading {}", url);
+            progress_bars.add_progress(message, ProgressBarStyle::Download, self.total_size)
+        });
+        DownloadReader {
+            reader: self.response.into_reader(),
+            total_size: self.total_size,
+            position: 0,
+            progress_bar,
+            bandwidth_limiter,
+        }
+    }
+}
+
+/// Sends the request for the url, which resolves to `None` when the server responds that the file
+/// matching the validators wasn't modified. The url is what's shown in messages, while the
+/// source request is what's requested, so that any signatures or tokens it has aren't shown.
+pub fn send_download_request(
+    url: &str,
+    source_request: &SourceRequest,
+    options: &DownloadOptions,
+    validators: &CacheValidators,
+) -> Result<Option<DownloadResponse>, ErrBox> {
+    let parsed_url = match Url::parse(&source_request.url) {
+        Ok(url) => url,
+        Err(err) => return err!("Error parsing url {}. {}", url, err.to_string()),
+    };
+    if is_ctrl_c_pressed() {
+        return Err(Box::new(DownloadCancelledError { url: url.to_string() }));
+    }
+    let mut request = ureq::get(&source_request.url);
+    request
+        .timeout_connect(options.connect_timeout_ms)
+        .timeout_read(options.read_timeout_ms);
+    if let Some(proxy) = options.get_proxy(&parsed_url) {
+        match ureq::Proxy::new(proxy.trim_end_matches('/')) {
+            Ok(proxy) => request.set_proxy(proxy),
+            Err(err) => return err!("Error parsing proxy {}. {}", proxy, err.to_string()),
+        };
+    }
+    if let Some(tls_config) = &options.tls_config {
+        request.set_tls_config(tls_config.clone());
+    }
+    let has_authorization = source_request
+        .headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("Authorization"));
+    if parsed_url.username().is_empty() && !has_authorization {
+        if let (Some(netrc), Some(host)) = (&options.netrc, parsed_url.host_str()) {
+            if let Some(credentials) = netrc.get_credentials(host) {
+                request.auth(&credentials.login, &credentials.password);
+            }
+        }
+    }
+
+    for (name, value) in source_request.headers.iter() {
+        request.set(name, value);
+    }
+    if let Some(etag) = &validators.etag {
+        request.set("If-None-Match", etag);
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        request.set("If-Modified-Since", last_modified);
+    }
+
+    let resp = request.call();
+    if let Some(err) = resp.synthetic_error() {
+        if is_ctrl_c_pressed() {
+            return Err(Box::new(DownloadCancelledError { url: url.to_string() }));
+        }
+        return match err {
+            ureq::Error::BadUrl(_) | ureq::Error::UnknownScheme(_) => {
+                err!("Error downloading {}. {}", url, err.to_string())
+            }
+            _ => Err(Box::new(DownloadNetworkError {
+                url: url.to_string(),
+                message: err.to_string(),
+            })),
+        };
+    }
+    if resp.status() == 304 {
+        return Ok(None);
+    }
+    let total_size = {
+        if resp.status() == 200 {
+            resp.header("Content-Length")
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(0)
+        } else {
+            return Err(Box::new(DownloadStatusError {
+                url: url.to_string(),
+                status: resp.status(),
+            }));
+        }
+    };
+    let validators = CacheValidators {
+        etag: resp.header("ETag").map(String::from),
+        last_modified: resp.header("Last-Modified").map(String::from),
+    };
+    Ok(Some(DownloadResponse {
+        response: resp,
+        total_size,
+        validators,
+    }))
+}
+
+fn inner_download(reader: &mut DownloadReader<impl Read>) -> Result<Vec<u8>, ErrBox> {
+    let mut final_bytes = Vec::with_capacity(reader.total_size);
+    if reader.progress_bar.is_none() && reader.bandwidth_limiter.get_max_bytes_per_sec().is_none() {
+        reader.read_to_end(&mut final_bytes)?;
+        return Ok(final_bytes);
+    }
+
+    let mut buf: [u8; 512] = [0; 512]; // ensure progress bars update often and throttling is smooth
+    loop {
+        let bytes_read = reader.read(&mut buf)?;
+        if bytes_read == 0 {
+            break;
+        }
+        final_bytes.extend(&buf[..bytes_read]);
+    }
+    reader.finish();
+    Ok(final_bytes)
+}
+
+/// Reader of a download's body that updates its progress bar and limits its throughput.
+pub struct DownloadReader<'a, TRead: Read> {
+    reader: TRead,
+    total_size: usize,
+    position: usize,
+    progress_bar: Option<ProgressBar>,
+    bandwidth_limiter: &'a BandwidthLimiter,
+}
+
+impl<'a, TRead: Read> DownloadReader<'a, TRead> {
+    /// Removes the progress bar once the download is done being read.
+    pub fn finish(&mut self) {
+        if let Some(progress_bar) = self.progress_bar.take() {
+            progress_bar.finish();
+        }
+    }
+}
+
+impl<'a, TRead: Read> Read for DownloadReader<'a, TRead> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if is_ctrl_c_pressed() {
+            return Err(std::io::Error::other("Cancelled."));
+        }
+        let bytes_read = self.reader.read(buf)?;
+        self.position += bytes_read;
+        if let Some(progress_bar) = &self.progress_bar {
+            progress_bar.set_position(self.position);
+        }
+        self.bandwidth_limiter.consume(bytes_read);
+        Ok(bytes_read)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::environment::UserDownloadTimeoutConfig;
+    use std::collections::HashMap;
+
+    fn get_options(user_config: UserConfig, env_vars: Vec<(&str, &str)>) -> DownloadOptions {
+        let env_vars = env_vars
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        DownloadOptions::new(&user_config, |name| env_vars.get(name).cloned()).unwrap()
+    }
+
+    fn get_proxy(options: &DownloadOptions, url: &str) -> Option<String> {
+        options.get_proxy(&Url::parse(url).unwrap()).cloned()
+    }
+
+    #[test]
+    fn should_use_proxy_env_vars_based_on_scheme() {
+        let options = get_options(
+            UserConfig::default(),
+            vec![
+                ("HTTP_PROXY", "http://proxy:80"),
+                ("https_proxy", "http://secure-proxy:443"),
+            ],
+        );
+        assert_eq!(
+            get_proxy(&options, "http://localhost/file"),
+            Some("http://proxy:80".to_string())
+        );
+        assert_eq!(
+            get_proxy(&options, "https://localhost/file"),
+            Some("http://secure-proxy:443".to_string())
+        );
+    }
+
+    #[test]
+    fn should_prefer_user_config_proxy() {
+        let options = get_options(
+            UserConfig {
+                proxy: Some("http://config-proxy:8080".to_string()),
+                ..Default::default()
+            },
+            vec![("HTTPS_PROXY", "http://proxy:80")],
+        );
+        assert_eq!(
+            get_proxy(&options, "https://localhost/file"),
+            Some("http://config-proxy:8080".to_string())
+        );
+    }
+
+    #[test]
+    fn should_not_use_proxy_for_no_proxy_hosts() {
+        let options = get_options(
+            UserConfig::default(),
+            vec![
+                ("HTTPS_PROXY", "http://proxy:80"),
+                ("NO_PROXY", "localhost, .internal.com,other.com:443"),
+            ],
+        );
+        assert_eq!(get_proxy(&options, "https://localhost/file"), None);
+        assert_eq!(get_proxy(&options, "https://internal.com/file"), None);
+        assert_eq!(get_proxy(&options, "https://sub.internal.com/file"), None);
+        assert_eq!(get_proxy(&options, "https://other.com/file"), None);
+        assert_eq!(
+            get_proxy(&options, "https://notinternal.com/file"),
+            Some("http://proxy:80".to_string())
+        );
+
+        let options = get_options(
+            UserConfig::default(),
+            vec![("HTTPS_PROXY", "http://proxy:80"), ("NO_PROXY", "*")],
+        );
+        assert_eq!(get_proxy(&options, "https://bvm.land/file"), None);
+    }
+
+    #[test]
+    fn should_use_download_timeouts() {
+        let options = get_options(UserConfig::default(), Vec::new());
+        assert_eq!(options.connect_timeout_ms, DEFAULT_CONNECT_TIMEOUT_MS);
+        assert_eq!(options.read_timeout_ms, DEFAULT_READ_TIMEOUT_MS);
+
+        let options = get_options(
+            UserConfig {
+                download_timeout: Some(UserDownloadTimeoutConfig {
+                    connect_ms: Some(0),
+                    read_ms: Some(60_000),
+                }),
+                ..Default::default()
+            },
+            Vec::new(),
+        );
+        assert_eq!(options.connect_timeout_ms, 0);
+        assert_eq!(options.read_timeout_ms, 60_000);
+    }
+
+    #[test]
+    fn should_error_for_invalid_ca_bundle() {
+        let user_config = UserConfig {
+            ca_bundle: Some("/non-existent/ca-bundle.pem".to_string()),
+            ..Default::default()
+        };
+        let error_message = DownloadOptions::new(&user_config, |_| None).err().unwrap().to_string();
+        assert!(error_message.starts_with("Error reading CA bundle /non-existent/ca-bundle.pem."));
+    }
+}
@@ -0,0 +1,284 @@
+use dprint_cli_core::types::ErrBox;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::Event;
+use crate::errors::get_error_code;
+
+/// Validators of a previously downloaded copy of a file that are used to make a conditional request.
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+pub enum ConditionalDownload {
+    /// The previously downloaded copy is still current.
+    NotModified,
+    Modified(Vec<u8>, CacheValidators),
+}
+
+/// Format of the messages logged to stderr.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogFormat {
+    Text,
+    /// A JSON object per line with the level and message.
+    Json,
+    /// Workflow commands that GitHub Actions annotates errors and debug messages from.
+    GitHubActions,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogLevel {
+    Error,
+    Info,
+    Verbose,
+}
+
+/// Formats a message to log to stderr.
+pub fn format_log_message(format: LogFormat, level: LogLevel, text: &str) -> String {
+    match format {
+        LogFormat::Text => match level {
+            LogLevel::Verbose => format!("[VERBOSE]: {}", text),
+            LogLevel::Error | LogLevel::Info => text.to_string(),
+        },
+        LogFormat::Json => {
+            let level = match level {
+                LogLevel::Error => "error",
+                LogLevel::Info => "info",
+                LogLevel::Verbose => "verbose",
+            };
+            serde_json::json!({ "level": level, "message": text }).to_string()
+        }
+        LogFormat::GitHubActions => match level {
+            LogLevel::Error => format!("::error::{}", escape_workflow_command_data(text)),
+            LogLevel::Verbose => format!("::debug::{}", escape_workflow_command_data(text)),
+            LogLevel::Info => text.to_string(),
+        },
+    }
+}
+
+/// Formats an error to log to stderr. As JSON, this includes the code of the kind of failure so that
+/// tools wrapping bvm may branch on it.
+pub fn format_error_log_message(format: LogFormat, err: &ErrBox) -> String {
+    match format {
+        LogFormat::Json => serde_json::json!({
+            "level": "error",
+            "code": get_error_code(err).as_str(),
+            "message": err.to_string(),
+        })
+        .to_string(),
+        _ => format_log_message(format, LogLevel::Error, &err.to_string()),
+    }
+}
+
+/// Escapes the message of a GitHub Actions workflow command so it stays on one line.
+fn escape_workflow_command_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// How confirmations and other prompts are answered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PromptMode {
+    /// Ask the user when run interactively.
+    Auto,
+    /// Answer yes to confirmations without asking.
+    AssumeYes,
+    /// Never ask, as if not run interactively.
+    Never,
+}
+
+/// When to color the output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorChoice {
+    /// Color the output when it's a terminal and the `NO_COLOR` environment variable isn't set.
+    Auto,
+    Always,
+    Never,
+}
+
+/// Gets if the output should be colored based on the color choice, if the output is a terminal,
+/// and the value of the `NO_COLOR` environment variable.
+pub fn is_color_enabled(choice: ColorChoice, is_terminal: bool, no_color_var: Option<String>) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => is_terminal && no_color_var.map(|value| value.is_empty()).unwrap_or(true),
+    }
+}
+
+pub trait Environment: Clone + std::marker::Send + std::marker::Sync + 'static {
+    fn is_real(&self) -> bool;
+    fn read_file(&self, file_path: &Path) -> Result<Vec<u8>, ErrBox>;
+    fn read_file_text(&self, file_path: &Path) -> Result<String, ErrBox>;
+    fn write_file(&self, file_path: &Path, bytes: &[u8]) -> Result<(), ErrBox>;
+    fn write_file_text(&self, file_path: &Path, file_text: &str) -> Result<(), ErrBox>;
+    /// Creates the file with the provided text only if it doesn't exist. Returns false when it exists.
+    fn create_new_file_text(&self, file_path: &Path, file_text: &str) -> Result<bool, ErrBox>;
+    fn remove_file(&self, file_path: &Path) -> Result<(), ErrBox>;
+    fn remove_dir_all(&self, dir_path: &Path) -> Result<(), ErrBox>;
+    /// Moves the file or directory to the new path.
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), ErrBox>;
+    fn path_exists(&self, file_path: &Path) -> bool;
+    fn is_dir(&self, path: &Path) -> bool;
+    fn is_dir_empty(&self, dir_path: &Path) -> Result<bool, ErrBox>;
+    /// Gets the total size in bytes of the files within the directory.
+    fn get_dir_size(&self, dir_path: &Path) -> Result<u64, ErrBox>;
+    /// Gets the paths of the directories directly within the directory.
+    fn get_dir_paths(&self, dir_path: &Path) -> Result<Vec<PathBuf>, ErrBox>;
+    /// Gets the paths of the files within the directory and its descendant directories, excluding symlinks.
+    fn get_file_paths(&self, dir_path: &Path) -> Result<Vec<PathBuf>, ErrBox>;
+    /// Gets the paths of the executable files within the directory and its descendant directories.
+    fn get_executable_file_paths(&self, dir_path: &Path) -> Result<Vec<PathBuf>, ErrBox>;
+    /// Creates a hard link at the new path to the existing file.
+    fn hard_link(&self, existing_path: &Path, new_path: &Path) -> Result<(), ErrBox>;
+    /// Gets the number of hard links to the file, which includes the path itself.
+    /// Returns `None` when not supported on the current operating system.
+    fn get_hard_link_count(&self, file_path: &Path) -> Result<Option<u64>, ErrBox>;
+    fn create_dir_all(&self, path: &Path) -> Result<(), ErrBox>;
+    fn cwd(&self) -> Result<PathBuf, ErrBox>;
+    /// Logs output to stdout. Colors are removed when the output shouldn't be colored.
+    fn log(&self, text: &str);
+    /// Logs a diagnostic message to stderr, which is omitted when quiet.
+    fn log_error(&self, text: &str);
+    /// Logs a message that's only shown when verbose. Use the `log_verbose!` macro instead.
+    fn log_verbose(&self, text: &str);
+    /// Asks the user a yes or no question. Returns `None` when not run interactively or prompts are disabled.
+    fn confirm(&self, message: &str) -> Result<Option<bool>, ErrBox>;
+    /// Asks the user to pick one of the items. Returns the index of the picked item or `None` when not run
+    /// interactively or prompts are disabled (including with `PromptMode::AssumeYes`).
+    fn select(&self, message: &str, items: &[String]) -> Result<Option<usize>, ErrBox>;
+    /// Asks the user to check any number of the items. Returns the indexes of the checked items or `None`
+    /// when not run interactively or prompts are disabled (including with `PromptMode::AssumeYes`).
+    fn multi_select(&self, message: &str, items: &[String]) -> Result<Option<Vec<usize>>, ErrBox>;
+    fn download_file(&self, url: &str) -> Result<Vec<u8>, ErrBox>;
+    /// Downloads the file only if it changed since the copy the validators were received with.
+    fn download_file_if_modified(&self, url: &str, validators: &CacheValidators)
+        -> Result<ConditionalDownload, ErrBox>;
+    /// Downloads the file while providing its bytes to the handler as they're received so that large files
+    /// don't need to be held in memory. Only sending the request is retried since the handler consumes the body.
+    fn download_file_streaming(
+        &self,
+        url: &str,
+        handler: &mut dyn FnMut(&mut dyn Read) -> Result<(), ErrBox>,
+    ) -> Result<(), ErrBox>;
+    /// Notifies the event handler, if any, of the progress of the pipeline.
+    fn emit_event(&self, event: Event);
+    fn log_action_with_progress<
+        TResult: std::marker::Send + std::marker::Sync,
+        TCreate: FnOnce(Box<dyn Fn(usize)>) -> TResult + std::marker::Send + std::marker::Sync,
+    >(
+        &self,
+        message: &str,
+        action: TCreate,
+        total_size: usize,
+    ) -> TResult;
+    /// Data that is specific to a user on a local machine. Relocated by `BVM_CACHE_DIR` or `"cacheDir"`.
+    fn get_local_user_data_dir(&self) -> PathBuf;
+    /// Data that is specific to a user across machines. Relocated by `BVM_DATA_DIR` or `"dataDir"`.
+    fn get_user_data_dir(&self) -> PathBuf;
+    /// Directory of the user config.json file (ex. `~/.config/bvm`), which isn't relocated with the user data directory.
+    fn get_user_config_dir(&self) -> PathBuf;
+    fn get_user_home_dir(&self) -> PathBuf;
+    /// Gets the path of the currently running executable.
+    fn get_current_exe(&self) -> Result<PathBuf, ErrBox>;
+    fn get_time_secs(&self) -> u64;
+    fn sleep(&self, duration: std::time::Duration);
+    /// Gets if a process with the provided id is running on this machine.
+    fn is_process_running(&self, process_id: u32) -> bool;
+    /// Gets the specified environment variable.
+    fn get_env_var(&self, key: &str) -> Option<String>;
+    /// Gets the environment path variable.
+    fn get_env_path(&self) -> String {
+        self.get_env_var("PATH").unwrap_or(String::new())
+    }
+    /// Ensures the provided directory to be on the system path environment variable. In system mode, this
+    /// is the path of all users instead of the current user's.
+    #[cfg(windows)]
+    fn ensure_system_path(&self, directory_path: &str) -> Result<(), ErrBox>;
+    /// Ensures the provided directory is at the start of the system path environment variable.
+    #[cfg(windows)]
+    fn ensure_system_path_pre(&self, directory_path: &str) -> Result<(), ErrBox>;
+    #[cfg(windows)]
+    fn remove_system_path(&self, directory_path: &str) -> Result<(), ErrBox>;
+    /// Persistently sets a user environment variable. This is stored in the registry on Windows and
+    /// in a block managed by bvm at the end of the shell profiles otherwise. In system mode, the variable
+    /// is set for all users instead.
+    fn set_env_variable(&self, key: String, value: String) -> Result<(), ErrBox>;
+    /// Removes a user environment variable persisted by `set_env_variable`.
+    fn remove_env_variable(&self, key: &str) -> Result<(), ErrBox>;
+    fn run_shell_command(&self, cwd: &Path, command: &str) -> Result<(), ErrBox>;
+    /// Runs a shell command with the provided value as the path environment variable.
+    fn run_shell_command_with_env_path(&self, cwd: &Path, command: &str, env_path: &str) -> Result<(), ErrBox>;
+    /// Runs a shell command with the provided environment variables and writes the input to its stdin.
+    fn run_shell_command_with_input(
+        &self,
+        cwd: &Path,
+        command: &str,
+        env_vars: &[(String, String)],
+        input: &[u8],
+    ) -> Result<(), ErrBox>;
+    /// Runs a shell command with the provided environment variable changes (`None` removes the variable)
+    /// inheriting the standard streams, then returns its exit code.
+    fn run_shell_command_with_env_changes(
+        &self,
+        cwd: &Path,
+        command: &str,
+        env_changes: &[(String, Option<String>)],
+    ) -> Result<i32, ErrBox>;
+    /// Runs the executable with the provided arguments and environment variable changes (`None` removes
+    /// the variable) inheriting the standard streams, then returns its exit code.
+    fn run_executable(
+        &self,
+        file_path: &Path,
+        args: &[String],
+        env_changes: &[(String, Option<String>)],
+    ) -> Result<i32, ErrBox>;
+    /// Runs the executable with the provided arguments without inheriting the standard streams, then
+    /// returns its exit code along with the text it output to stdout and stderr.
+    fn get_executable_output(&self, file_path: &Path, args: &[String]) -> Result<(i32, String), ErrBox>;
+    /// Starts the current executable with the provided arguments in a detached process without the
+    /// standard streams and doesn't wait for it to finish.
+    fn spawn_background_exe(&self, args: &[String]) -> Result<(), ErrBox>;
+    fn exit(&self, code: i32) -> Result<(), ErrBox>;
+    fn is_verbose(&self) -> bool;
+    fn set_verbose(&self, value: bool);
+    /// Gets if diagnostic messages and progress should not be output.
+    fn is_quiet(&self) -> bool;
+    fn set_quiet(&self, value: bool);
+    fn get_log_format(&self) -> LogFormat;
+    fn set_log_format(&self, format: LogFormat);
+    fn get_color_choice(&self) -> ColorChoice;
+    fn set_color_choice(&self, choice: ColorChoice);
+    /// Gets if the network should never be accessed.
+    fn is_offline(&self) -> bool;
+    fn set_offline(&self, value: bool);
+    /// Gets if cached registry files should be revalidated regardless of their age.
+    fn is_refresh(&self) -> bool;
+    fn set_refresh(&self, value: bool);
+    /// Gets if download hosts that aren't trusted should be trusted without asking.
+    fn is_trust_hosts(&self) -> bool;
+    fn set_trust_hosts(&self, value: bool);
+    fn get_prompt_mode(&self) -> PromptMode;
+    fn set_prompt_mode(&self, mode: PromptMode);
+    /// Gets the maximum combined speed of downloads in bytes per second.
+    fn get_max_download_speed(&self) -> Option<u64>;
+    fn set_max_download_speed(&self, value: Option<u64>);
+    /// Gets if the machine-wide installation shared by all users is used instead of the user's (`--system`).
+    fn is_system_mode(&self) -> bool;
+    fn set_system_mode(&self, value: bool);
+    /// Gets if the process is allowed to change the machine-wide installation.
+    fn has_system_privileges(&self) -> bool;
+}
+
+// use a macro here so the expression provided is only evaluated when in verbose mode
+#[macro_export]
+macro_rules! log_verbose {
+    ($environment:expr, $($arg:tt)*) => {
+        if $environment.is_verbose() {
+            $environment.log_verbose(&format!($($arg)*));
+        }
+    }
+}
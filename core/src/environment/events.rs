@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use crate::types::{BinaryName, CommandName, Version};
+
+/// Progress of the install and resolve pipeline, which is provided to the event handler of the environment.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// The archive of a binary started downloading.
+    DownloadStarted {
+        name: BinaryName,
+        version: Version,
+        url: String,
+    },
+    /// A binary was extracted and set up in its directory.
+    Extracted {
+        name: BinaryName,
+        version: Version,
+        dir_path: PathBuf,
+    },
+    /// The manifest of the installed binaries was written.
+    ManifestUpdated,
+    /// The global binary of a command was changed. The binary is `None` when the command now
+    /// resolves to the executable on the path.
+    VersionSwitched {
+        command_name: CommandName,
+        binary: Option<(BinaryName, Version)>,
+    },
+}
@@ -0,0 +1,32 @@
+#[macro_use]
+mod environment;
+
+mod bandwidth_limiter;
+mod cancellation;
+mod common;
+mod download_retry;
+mod download_url;
+mod events;
+mod netrc;
+mod shell_profile;
+mod user_config;
+
+mod real_environment;
+#[cfg(any(test, feature = "testing"))]
+mod test_environment;
+
+pub use environment::*;
+pub use events::*;
+pub use real_environment::*;
+
+#[cfg(any(test, feature = "testing"))]
+pub use test_environment::*;
+
+use bandwidth_limiter::*;
+pub use cancellation::*;
+pub use common::*;
+pub use download_retry::*;
+use download_url::*;
+use netrc::*;
+pub use shell_profile::*;
+pub use user_config::*;
@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+
+/// Credentials for a host from a `.netrc` file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NetrcCredentials {
+    pub login: String,
+    pub password: String,
+}
+
+/// Parsed `.netrc` file used to find credentials for hosts requiring authentication.
+#[derive(Debug, PartialEq, Default)]
+pub struct Netrc {
+    machines: Vec<(String, NetrcCredentials)>,
+    default: Option<NetrcCredentials>,
+}
+
+impl Netrc {
+    pub fn parse(text: &str) -> Netrc {
+        let mut netrc = Netrc::default();
+        let mut lines = text.lines();
+        // the current entry's machine (None for the default entry) and credentials
+        let mut current: Option<(Option<String>, NetrcCredentials)> = None;
+
+        while let Some(line) = lines.next() {
+            let mut tokens = line.split_whitespace();
+            while let Some(token) = tokens.next() {
+                match token {
+                    "machine" | "default" => {
+                        netrc.add_entry(current.take());
+                        let machine = if token == "machine" {
+                            tokens.next().map(|t| t.to_lowercase())
+                        } else {
+                            None
+                        };
+                        current = Some((
+                            machine,
+                            NetrcCredentials {
+                                login: String::new(),
+                                password: String::new(),
+                            },
+                        ));
+                    }
+                    "login" => {
+                        if let (Some((_, credentials)), Some(value)) = (&mut current, tokens.next()) {
+                            credentials.login = value.to_string();
+                        }
+                    }
+                    "password" => {
+                        if let (Some((_, credentials)), Some(value)) = (&mut current, tokens.next()) {
+                            credentials.password = value.to_string();
+                        }
+                    }
+                    "account" => {
+                        tokens.next();
+                    }
+                    "macdef" => {
+                        // macro definitions continue until an empty line
+                        netrc.add_entry(current.take());
+                        for line in lines.by_ref() {
+                            if line.trim().is_empty() {
+                                break;
+                            }
+                        }
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        netrc.add_entry(current);
+
+        netrc
+    }
+
+    fn add_entry(&mut self, entry: Option<(Option<String>, NetrcCredentials)>) {
+        match entry {
+            Some((Some(machine), credentials)) => self.machines.push((machine, credentials)),
+            Some((None, credentials)) => self.default = Some(credentials),
+            None => {}
+        }
+    }
+
+    pub fn get_credentials(&self, host: &str) -> Option<&NetrcCredentials> {
+        let host = host.to_lowercase();
+        self.machines
+            .iter()
+            .find(|(machine, _)| machine == &host)
+            .map(|(_, credentials)| credentials)
+            .or(self.default.as_ref())
+    }
+}
+
+/// Gets the path to the `.netrc` file, which may be overridden by the `NETRC` environment variable.
+pub fn get_netrc_file_path(get_env_var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    if let Some(path) = get_env_var("NETRC") {
+        return Some(PathBuf::from(path));
+    }
+    let home_dir = dirs::home_dir()?;
+    if cfg!(target_os = "windows") {
+        let file_path = home_dir.join("_netrc");
+        if file_path.exists() {
+            return Some(file_path);
+        }
+    }
+    Some(home_dir.join(".netrc"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn credentials(login: &str, password: &str) -> NetrcCredentials {
+        NetrcCredentials {
+            login: login.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_parse_machines() {
+        let netrc = Netrc::parse(
+            "machine example.com login user password pass\n\nmachine other.com\n  login other\n  account acc\n  password secret\n",
+        );
+        assert_eq!(netrc.get_credentials("example.com"), Some(&credentials("user", "pass")));
+        assert_eq!(netrc.get_credentials("EXAMPLE.com"), Some(&credentials("user", "pass")));
+        assert_eq!(
+            netrc.get_credentials("other.com"),
+            Some(&credentials("other", "secret"))
+        );
+        assert_eq!(netrc.get_credentials("unknown.com"), None);
+    }
+
+    #[test]
+    fn should_use_default_entry() {
+        let netrc =
+            Netrc::parse("machine example.com login user password pass\ndefault login anonymous password guest");
+        assert_eq!(netrc.get_credentials("example.com"), Some(&credentials("user", "pass")));
+        assert_eq!(
+            netrc.get_credentials("unknown.com"),
+            Some(&credentials("anonymous", "guest"))
+        );
+    }
+
+    #[test]
+    fn should_skip_macro_definitions() {
+        let netrc = Netrc::parse(
+            "macdef init\nmachine fake.com login fake password fake\n\nmachine example.com login user password pass",
+        );
+        assert_eq!(netrc.get_credentials("fake.com"), None);
+        assert_eq!(netrc.get_credentials("example.com"), Some(&credentials("user", "pass")));
+    }
+}
@@ -0,0 +1,1131 @@
+use dprint_cli_core::logging::{log_action_with_progress, show_multi_select, Logger, ProgressBars};
+use dprint_cli_core::types::ErrBox;
+use std::borrow::Cow;
+use std::env;
+use std::fs;
+use std::io::{ErrorKind, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use super::{
+    download_url, download_url_if_modified, download_with_retries, format_error_log_message, format_log_message,
+    get_dir_override, get_file_url_path, is_color_enabled, is_ctrl_c_pressed, read_user_config, send_download_request,
+    BandwidthLimiter, CacheValidators, ColorChoice, ConditionalDownload, CtrlCGuard, DownloadCancelledError,
+    DownloadOptions, Environment, Event, LogFormat, LogLevel, PromptMode,
+};
+#[cfg(not(windows))]
+use super::{get_profile_env_vars, set_profile_env_vars, SHELL_PROFILE_FILE_NAMES};
+use crate::errors::ErrorCode;
+use crate::registry::{resolve_source_request, SourceContext, SourceRequest};
+use crate::utils::{red, strip_ansi_codes};
+
+type EventHandler = Arc<dyn Fn(&Event) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct RealEnvironment {
+    logger: Logger,
+    progress_bars: Option<ProgressBars>,
+    is_verbose: Arc<AtomicBool>,
+    is_quiet: Arc<AtomicBool>,
+    log_format: Arc<Mutex<LogFormat>>,
+    color_choice: Arc<Mutex<ColorChoice>>,
+    prompt_mode: Arc<Mutex<PromptMode>>,
+    is_offline: Arc<AtomicBool>,
+    is_refresh: Arc<AtomicBool>,
+    is_trust_hosts: Arc<AtomicBool>,
+    download_options: Arc<Mutex<Arc<DownloadOptions>>>,
+    bandwidth_limiter: Arc<BandwidthLimiter>,
+    event_handler: Arc<Mutex<Option<EventHandler>>>,
+    is_system_mode: Arc<AtomicBool>,
+}
+
+impl RealEnvironment {
+    pub fn new(is_verbose: bool) -> Result<RealEnvironment, ErrBox> {
+        let logger = Logger::new("bvm", /* is silent */ false);
+        let progress_bars = ProgressBars::new(&logger);
+        let environment = RealEnvironment {
+            logger,
+            progress_bars,
+            is_verbose: Arc::new(AtomicBool::new(is_verbose)),
+            is_quiet: Arc::new(AtomicBool::new(false)),
+            log_format: Arc::new(Mutex::new(LogFormat::Text)),
+            color_choice: Arc::new(Mutex::new(ColorChoice::Auto)),
+            prompt_mode: Arc::new(Mutex::new(PromptMode::Auto)),
+            is_offline: Arc::new(AtomicBool::new(false)),
+            is_refresh: Arc::new(AtomicBool::new(false)),
+            is_trust_hosts: Arc::new(AtomicBool::new(false)),
+            download_options: Default::default(),
+            bandwidth_limiter: Arc::new(BandwidthLimiter::new(None)),
+            event_handler: Arc::new(Mutex::new(None)),
+            is_system_mode: Arc::new(AtomicBool::new(false)),
+        };
+
+        environment.create_dir_all(&environment.get_local_user_data_dir())?;
+        environment.create_dir_all(&environment.get_user_data_dir())?;
+        environment.load_user_config_options()?;
+
+        Ok(environment)
+    }
+
+    /// Applies the download options of the user config, which differs between the user and system installations.
+    fn load_user_config_options(&self) -> Result<(), ErrBox> {
+        let user_config = read_user_config(self)?;
+        self.set_max_download_speed(user_config.get_max_download_speed()?);
+        let download_options = DownloadOptions::new(&user_config, |name| self.get_env_var(name))?;
+        *self.download_options.lock().unwrap() = Arc::new(download_options);
+        Ok(())
+    }
+
+    /// Sets the handler that's called with the events of the pipeline, such as to show a custom progress UI.
+    pub fn set_event_handler(&self, handler: impl Fn(&Event) + Send + Sync + 'static) {
+        *self.event_handler.lock().unwrap() = Some(Arc::new(handler));
+    }
+
+    /// Formats an error that will exit the process.
+    pub fn format_fatal_error(&self, err: &ErrBox) -> String {
+        let text = format_error_log_message(self.get_log_format(), err);
+        if self.is_stderr_color_enabled() {
+            red(&text)
+        } else {
+            text
+        }
+    }
+
+    fn is_stderr_color_enabled(&self) -> bool {
+        // colors would corrupt json output
+        self.get_log_format() == LogFormat::Text && self.is_color_enabled(std::io::stderr().is_terminal())
+    }
+
+    fn is_color_enabled(&self, is_terminal: bool) -> bool {
+        is_color_enabled(self.get_color_choice(), is_terminal, env::var("NO_COLOR").ok())
+    }
+
+    fn get_stderr_text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.is_stderr_color_enabled() {
+            Cow::Borrowed(text)
+        } else {
+            strip_ansi_codes(text)
+        }
+    }
+
+    fn resolve_source_request(&self, url: &str) -> Result<SourceRequest, ErrBox> {
+        resolve_source_request(
+            url,
+            &SourceContext {
+                get_env_var: &|name| self.get_env_var(name),
+                time_secs: self.get_time_secs(),
+            },
+        )
+    }
+
+    /// Opens the registry key of the environment variables of the user or, in system mode, of all users.
+    #[cfg(windows)]
+    fn open_env_registry_key(&self) -> Result<winreg::RegKey, ErrBox> {
+        use winreg::{enums::*, RegKey};
+        let (env, _) = if self.is_system_mode() {
+            RegKey::predef(HKEY_LOCAL_MACHINE)
+                .create_subkey(r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment")?
+        } else {
+            RegKey::predef(HKEY_CURRENT_USER).create_subkey("Environment")?
+        };
+        Ok(env)
+    }
+
+    /// Updates the environment variables in bvm's managed block of each existing shell profile,
+    /// creating a `.profile` file when none exist. In system mode, the block is in a script that
+    /// the login shells of all users source instead.
+    #[cfg(not(windows))]
+    fn update_profile_env_vars(&self, update: impl Fn(&mut Vec<(String, String)>)) -> Result<(), ErrBox> {
+        let profile_paths = if self.is_system_mode() {
+            vec![PathBuf::from(SYSTEM_PROFILE_SCRIPT_PATH)]
+        } else {
+            let home_dir = dirs::home_dir().expect("Could not get home data dir");
+            let mut profile_paths = SHELL_PROFILE_FILE_NAMES
+                .iter()
+                .map(|file_name| home_dir.join(file_name))
+                .filter(|profile_path| profile_path.exists())
+                .collect::<Vec<_>>();
+            if profile_paths.is_empty() {
+                profile_paths.push(home_dir.join(".profile"));
+            }
+            profile_paths
+        };
+
+        for profile_path in profile_paths {
+            let text = if profile_path.exists() {
+                self.read_file_text(&profile_path)?
+            } else {
+                String::new()
+            };
+            let mut env_vars = get_profile_env_vars(&text);
+            update(&mut env_vars);
+            let new_text = set_profile_env_vars(&text, &env_vars);
+            if new_text != text {
+                self.write_file_text(&profile_path, &new_text)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Environment for RealEnvironment {
+    fn is_real(&self) -> bool {
+        true
+    }
+
+    fn read_file_text(&self, file_path: &Path) -> Result<String, ErrBox> {
+        let file_bytes = self.read_file(file_path)?;
+        Ok(String::from_utf8(file_bytes)?)
+    }
+
+    fn read_file(&self, file_path: &Path) -> Result<Vec<u8>, ErrBox> {
+        log_verbose!(self, "Reading file: {}", file_path.display());
+        match fs::read(file_path) {
+            Ok(bytes) => Ok(bytes),
+            Err(err) => err!("Error reading file {}: {}", file_path.display(), err.to_string()),
+        }
+    }
+
+    fn write_file_text(&self, file_path: &Path, file_text: &str) -> Result<(), ErrBox> {
+        self.write_file(file_path, file_text.as_bytes())
+    }
+
+    fn create_new_file_text(&self, file_path: &Path, file_text: &str) -> Result<bool, ErrBox> {
+        log_verbose!(self, "Creating file: {}", file_path.display());
+        match fs::OpenOptions::new().write(true).create_new(true).open(file_path) {
+            Ok(mut file) => match file.write_all(file_text.as_bytes()) {
+                Ok(_) => Ok(true),
+                Err(err) => err!("Error writing file {}: {}", file_path.display(), err.to_string()),
+            },
+            Err(err) => {
+                if err.kind() == ErrorKind::AlreadyExists {
+                    Ok(false)
+                } else {
+                    err!("Error creating file {}: {}", file_path.display(), err.to_string())
+                }
+            }
+        }
+    }
+
+    fn write_file(&self, file_path: &Path, bytes: &[u8]) -> Result<(), ErrBox> {
+        log_verbose!(self, "Writing file: {}", file_path.display());
+        match fs::write(file_path, bytes) {
+            Ok(_) => Ok(()),
+            Err(err) => err!("Error writing file {}: {}", file_path.display(), err.to_string()),
+        }
+    }
+
+    fn remove_file(&self, file_path: &Path) -> Result<(), ErrBox> {
+        log_verbose!(self, "Deleting file: {}", file_path.display());
+        match fs::remove_file(file_path) {
+            Ok(_) => Ok(()),
+            Err(err) => err!("Error deleting file {}: {}", file_path.display(), err.to_string()),
+        }
+    }
+
+    fn remove_dir_all(&self, dir_path: &Path) -> Result<(), ErrBox> {
+        log_verbose!(self, "Deleting directory: {}", dir_path.display());
+        match fs::remove_dir_all(dir_path) {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                if err.kind() == ErrorKind::NotFound {
+                    Ok(())
+                } else {
+                    err!("Error removing directory {}: {}", dir_path.display(), err.to_string())
+                }
+            }
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), ErrBox> {
+        log_verbose!(self, "Renaming: {} -> {}", from.display(), to.display());
+        match fs::rename(from, to) {
+            Ok(_) => Ok(()),
+            Err(err) => err!(
+                "Error renaming {} to {}: {}",
+                from.display(),
+                to.display(),
+                err.to_string()
+            ),
+        }
+    }
+
+    fn download_file(&self, url: &str) -> Result<Vec<u8>, ErrBox> {
+        if let Some(file_path) = get_file_url_path(url) {
+            return self.read_file(&file_path);
+        }
+        if self.is_offline() {
+            return coded_err!(ErrorCode::Offline, "Could not download {} because bvm is offline.", url);
+        }
+        log_verbose!(self, "Downloading url: {}", url);
+        let source_request = self.resolve_source_request(url)?;
+        let download_options = self.download_options.lock().unwrap().clone();
+        let _ctrl_c_guard = CtrlCGuard::new();
+        download_with_retries(self, &download_options.retry_policy, || {
+            download_url(
+                url,
+                &source_request,
+                &download_options,
+                &self.progress_bars,
+                &self.bandwidth_limiter,
+            )
+        })
+    }
+
+    fn download_file_if_modified(
+        &self,
+        url: &str,
+        validators: &CacheValidators,
+    ) -> Result<ConditionalDownload, ErrBox> {
+        if get_file_url_path(url).is_some() {
+            return Ok(ConditionalDownload::Modified(
+                self.download_file(url)?,
+                CacheValidators::default(),
+            ));
+        }
+        if self.is_offline() {
+            return coded_err!(ErrorCode::Offline, "Could not download {} because bvm is offline.", url);
+        }
+        log_verbose!(self, "Downloading url if modified: {}", url);
+        let source_request = self.resolve_source_request(url)?;
+        let download_options = self.download_options.lock().unwrap().clone();
+        let _ctrl_c_guard = CtrlCGuard::new();
+        download_with_retries(self, &download_options.retry_policy, || {
+            download_url_if_modified(
+                url,
+                &source_request,
+                &download_options,
+                &self.progress_bars,
+                &self.bandwidth_limiter,
+                validators,
+            )
+        })
+    }
+
+    fn download_file_streaming(
+        &self,
+        url: &str,
+        handler: &mut dyn FnMut(&mut dyn Read) -> Result<(), ErrBox>,
+    ) -> Result<(), ErrBox> {
+        if let Some(file_path) = get_file_url_path(url) {
+            let mut file = match fs::File::open(&file_path) {
+                Ok(file) => file,
+                Err(err) => return err!("Error reading file {}: {}", file_path.display(), err.to_string()),
+            };
+            return handler(&mut file);
+        }
+        if self.is_offline() {
+            return coded_err!(ErrorCode::Offline, "Could not download {} because bvm is offline.", url);
+        }
+        log_verbose!(self, "Downloading url while streaming: {}", url);
+        let source_request = self.resolve_source_request(url)?;
+        let download_options = self.download_options.lock().unwrap().clone();
+        // held while the handler runs so that it fails and cleans up what it extracted when cancelled
+        let _ctrl_c_guard = CtrlCGuard::new();
+        let response = download_with_retries(self, &download_options.retry_policy, || {
+            match send_download_request(url, &source_request, &download_options, &CacheValidators::default())? {
+                Some(response) => Ok(response),
+                None => err!("Error downloading {}. Status: 304", url),
+            }
+        })?;
+        let mut reader = response.into_reader(url, &self.progress_bars, &self.bandwidth_limiter);
+        let result = handler(&mut reader);
+        reader.finish();
+        if is_ctrl_c_pressed() {
+            return Err(Box::new(DownloadCancelledError { url: url.to_string() }));
+        }
+        result
+    }
+
+    fn path_exists(&self, path: &Path) -> bool {
+        log_verbose!(self, "Checking path exists: {}", path.display());
+        path.exists()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        log_verbose!(self, "Checking path is directory: {}", path.display());
+        path.is_dir()
+    }
+
+    fn is_dir_empty(&self, dir_path: &Path) -> Result<bool, ErrBox> {
+        let mut result = match std::fs::read_dir(dir_path) {
+            Ok(result) => result,
+            Err(err) => {
+                return err!(
+                    "Error checking directory empty {}: {}",
+                    dir_path.display(),
+                    err.to_string()
+                )
+            }
+        };
+        Ok(result.next().is_none())
+    }
+
+    fn get_dir_size(&self, dir_path: &Path) -> Result<u64, ErrBox> {
+        log_verbose!(self, "Getting size of {}", dir_path.display());
+        let mut size = 0;
+        for entry in fs::read_dir(dir_path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                size += self.get_dir_size(&entry.path())?;
+            } else {
+                size += metadata.len();
+            }
+        }
+        Ok(size)
+    }
+
+    fn get_dir_paths(&self, dir_path: &Path) -> Result<Vec<PathBuf>, ErrBox> {
+        log_verbose!(self, "Getting directories in {}", dir_path.display());
+        let mut dir_paths = Vec::new();
+        for entry in fs::read_dir(dir_path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                dir_paths.push(entry.path());
+            }
+        }
+        Ok(dir_paths)
+    }
+
+    fn get_file_paths(&self, dir_path: &Path) -> Result<Vec<PathBuf>, ErrBox> {
+        log_verbose!(self, "Getting files in {}", dir_path.display());
+        let mut file_paths = Vec::new();
+        for entry in fs::read_dir(dir_path)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                file_paths.extend(self.get_file_paths(&entry.path())?);
+            } else if file_type.is_file() {
+                file_paths.push(entry.path());
+            }
+        }
+        Ok(file_paths)
+    }
+
+    fn get_executable_file_paths(&self, dir_path: &Path) -> Result<Vec<PathBuf>, ErrBox> {
+        log_verbose!(self, "Getting executable files in {}", dir_path.display());
+        let mut file_paths = Vec::new();
+        for entry in fs::read_dir(dir_path)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                file_paths.extend(self.get_executable_file_paths(&entry.path())?);
+            } else if is_executable_file(&entry.path(), &metadata) {
+                file_paths.push(entry.path());
+            }
+        }
+        Ok(file_paths)
+    }
+
+    fn hard_link(&self, existing_path: &Path, new_path: &Path) -> Result<(), ErrBox> {
+        log_verbose!(
+            self,
+            "Hard linking {} to {}",
+            new_path.display(),
+            existing_path.display()
+        );
+        match fs::hard_link(existing_path, new_path) {
+            Ok(_) => Ok(()),
+            Err(err) => err!(
+                "Error hard linking {} to {}: {}",
+                new_path.display(),
+                existing_path.display(),
+                err.to_string()
+            ),
+        }
+    }
+
+    fn get_hard_link_count(&self, file_path: &Path) -> Result<Option<u64>, ErrBox> {
+        Ok(get_hard_link_count(&fs::metadata(file_path)?))
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), ErrBox> {
+        log_verbose!(self, "Creating directory: {}", path.display());
+        match fs::create_dir_all(path) {
+            Ok(_) => Ok(()),
+            Err(err) => err!("Error creating directory {}: {}", path.display(), err.to_string()),
+        }
+    }
+
+    fn cwd(&self) -> Result<PathBuf, ErrBox> {
+        match env::current_dir() {
+            Ok(cwd) => Ok(cwd),
+            Err(err) => err!("Error getting current working: {}", err.to_string()),
+        }
+    }
+
+    fn log(&self, text: &str) {
+        if self.is_color_enabled(std::io::stdout().is_terminal()) {
+            self.logger.log(text, "bvm");
+        } else {
+            self.logger.log(&strip_ansi_codes(text), "bvm");
+        }
+    }
+
+    fn log_error(&self, text: &str) {
+        if !self.is_quiet() {
+            let text = self.get_stderr_text(text);
+            self.logger
+                .log_err(&format_log_message(self.get_log_format(), LogLevel::Info, &text), "bvm");
+        }
+    }
+
+    fn log_verbose(&self, text: &str) {
+        let text = self.get_stderr_text(text);
+        self.logger.log_err(
+            &format_log_message(self.get_log_format(), LogLevel::Verbose, &text),
+            "bvm",
+        );
+    }
+
+    fn confirm(&self, message: &str) -> Result<Option<bool>, ErrBox> {
+        match self.get_prompt_mode() {
+            PromptMode::AssumeYes => {
+                self.log_error(&format!("{} Yes (--yes)", message));
+                return Ok(Some(true));
+            }
+            PromptMode::Never => return Ok(None),
+            PromptMode::Auto => {}
+        }
+        if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+            return Ok(None);
+        }
+        eprint!("{} [y/N] ", message);
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        let answer = answer.trim().to_lowercase();
+        Ok(Some(answer == "y" || answer == "yes"))
+    }
+
+    fn select(&self, message: &str, items: &[String]) -> Result<Option<usize>, ErrBox> {
+        if self.get_prompt_mode() != PromptMode::Auto
+            || !std::io::stdin().is_terminal()
+            || !std::io::stderr().is_terminal()
+        {
+            return Ok(None);
+        }
+        eprintln!("{}", message);
+        for (i, item) in items.iter().enumerate() {
+            eprintln!("  {}) {}", i + 1, item);
+        }
+        loop {
+            eprint!("Enter a number (1-{}): ", items.len());
+            std::io::stderr().flush()?;
+            let mut answer = String::new();
+            if std::io::stdin().read_line(&mut answer)? == 0 {
+                return Ok(None);
+            }
+            match answer.trim().parse::<usize>() {
+                Ok(number) if number >= 1 && number <= items.len() => return Ok(Some(number - 1)),
+                _ => eprintln!("Invalid selection '{}'.", answer.trim()),
+            }
+        }
+    }
+
+    fn multi_select(&self, message: &str, items: &[String]) -> Result<Option<Vec<usize>>, ErrBox> {
+        if self.get_prompt_mode() != PromptMode::Auto
+            || !std::io::stdin().is_terminal()
+            || !std::io::stderr().is_terminal()
+        {
+            return Ok(None);
+        }
+        let items = items.iter().map(|item| (false, item)).collect();
+        Ok(Some(show_multi_select(&self.logger, "bvm", message, 0, items)?))
+    }
+
+    fn emit_event(&self, event: Event) {
+        // clone the handler so it may be replaced while handling the event
+        let handler = self.event_handler.lock().unwrap().clone();
+        if let Some(handler) = handler {
+            handler(&event);
+        }
+    }
+
+    fn log_action_with_progress<
+        TResult: std::marker::Send + std::marker::Sync,
+        TCreate: FnOnce(Box<dyn Fn(usize)>) -> TResult + std::marker::Send + std::marker::Sync,
+    >(
+        &self,
+        message: &str,
+        action: TCreate,
+        total_size: usize,
+    ) -> TResult {
+        if self.is_quiet() {
+            log_action_with_progress(&None, message, action, total_size)
+        } else if self.get_log_format() != LogFormat::Text {
+            // progress bars would corrupt the output
+            self.log_error(message);
+            log_action_with_progress(&None, message, action, total_size)
+        } else {
+            log_action_with_progress(&self.progress_bars, message, action, total_size)
+        }
+    }
+
+    fn get_local_user_data_dir(&self) -> PathBuf {
+        log_verbose!(self, "Getting local user data directory.");
+        if self.is_system_mode() {
+            return get_system_dir();
+        }
+        if let Some(dir) = get_dir_override(self, "BVM_CACHE_DIR", |config| config.cache_dir) {
+            return dir;
+        }
+        if cfg!(target_os = "windows") {
+            // %LOCALAPPDATA% is used because we don't want to sync this data across a domain.
+            let dir = dirs::data_local_dir().expect("Could not get local data dir");
+            dir.join("bvm")
+        } else {
+            get_home_dir()
+        }
+    }
+
+    fn get_user_data_dir(&self) -> PathBuf {
+        log_verbose!(self, "Getting user data directory.");
+        if self.is_system_mode() {
+            return get_system_dir();
+        }
+        if let Some(dir) = get_dir_override(self, "BVM_DATA_DIR", |config| config.data_dir) {
+            return dir;
+        }
+        get_default_user_data_dir()
+    }
+
+    fn get_user_config_dir(&self) -> PathBuf {
+        if self.is_system_mode() {
+            return get_system_dir();
+        }
+        if cfg!(target_os = "windows") {
+            return get_default_user_data_dir();
+        }
+        // keep using a config file created before it moved to the XDG config directory
+        let home_dir = get_home_dir();
+        if home_dir.join("config.json").exists() {
+            return home_dir;
+        }
+        match self.get_env_var("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir).join("bvm"),
+            None => {
+                let dir = dirs::home_dir().expect("Could not get home data dir");
+                dir.join(".config").join("bvm")
+            }
+        }
+    }
+
+    fn get_user_home_dir(&self) -> PathBuf {
+        log_verbose!(self, "Getting user home directory.");
+        if self.is_system_mode() {
+            return get_system_dir();
+        }
+        get_home_dir()
+    }
+
+    fn get_current_exe(&self) -> Result<PathBuf, ErrBox> {
+        log_verbose!(self, "Getting current executable.");
+        Ok(std::env::current_exe()?)
+    }
+
+    fn get_env_var(&self, key: &str) -> Option<String> {
+        log_verbose!(self, "Getting the {} environment variable.", key);
+
+        if let Some(path) = std::env::var_os(&key) {
+            Some(path.to_string_lossy().to_string())
+        } else {
+            None
+        }
+    }
+
+    #[cfg(windows)]
+    fn ensure_system_path(&self, directory_path: &str) -> Result<(), ErrBox> {
+        log_verbose!(self, "Ensuring '{}' is on the path.", directory_path);
+
+        let env = self.open_env_registry_key()?;
+        let mut path: String = env.get_value("Path")?;
+
+        // add to the path if it doesn't have this entry
+        if !path.split(";").any(|p| p == directory_path) {
+            if !path.is_empty() && !path.ends_with(';') {
+                path.push_str(";")
+            }
+            path.push_str(&directory_path);
+            set_registry_path(&env, &path)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn ensure_system_path_pre(&self, directory_path: &str) -> Result<(), ErrBox> {
+        // always puts the provided directory at the start of the path
+        let env = self.open_env_registry_key()?;
+        let path: String = env.get_value("Path")?;
+        let mut paths = path.split(";").collect::<Vec<_>>();
+        paths.retain(|p| p != &directory_path && !p.is_empty());
+        paths.insert(0, directory_path);
+        set_registry_path(&env, &paths.join(";"))?;
+
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn remove_system_path(&self, directory_path: &str) -> Result<(), ErrBox> {
+        log_verbose!(self, "Ensuring '{}' is on the path.", directory_path);
+
+        let env = self.open_env_registry_key()?;
+        let path: String = env.get_value("Path")?;
+        let mut paths = path.split(";").collect::<Vec<_>>();
+        let original_len = paths.len();
+
+        paths.retain(|p| p != &directory_path);
+
+        let was_removed = original_len != paths.len();
+        if was_removed {
+            set_registry_path(&env, &paths.join(";"))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn set_env_variable(&self, key: String, value: String) -> Result<(), ErrBox> {
+        log_verbose!(self, "Setting '{}' environment variable.", key);
+
+        let env = self.open_env_registry_key()?;
+        env.set_value(key, &value)?;
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn remove_env_variable(&self, key: &str) -> Result<(), ErrBox> {
+        log_verbose!(self, "Removing '{}' environment variable.", key);
+
+        let env = self.open_env_registry_key()?;
+
+        env.delete_value(key.to_string())?;
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    fn set_env_variable(&self, key: String, value: String) -> Result<(), ErrBox> {
+        log_verbose!(self, "Setting '{}' environment variable.", key);
+        self.update_profile_env_vars(|env_vars| {
+            env_vars.retain(|(k, _)| k != &key);
+            env_vars.push((key.clone(), value.clone()));
+        })
+    }
+
+    #[cfg(not(windows))]
+    fn remove_env_variable(&self, key: &str) -> Result<(), ErrBox> {
+        log_verbose!(self, "Removing '{}' environment variable.", key);
+        self.update_profile_env_vars(|env_vars| env_vars.retain(|(k, _)| k != key))
+    }
+
+    fn sleep(&self, duration: std::time::Duration) {
+        std::thread::sleep(duration);
+    }
+
+    fn get_time_secs(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn is_process_running(&self, process_id: u32) -> bool {
+        is_process_running(process_id)
+    }
+
+    fn run_shell_command(&self, cwd: &Path, command: &str) -> Result<(), ErrBox> {
+        run_shell_command(cwd, command, None)
+    }
+
+    fn run_shell_command_with_env_path(&self, cwd: &Path, command: &str, env_path: &str) -> Result<(), ErrBox> {
+        run_shell_command(cwd, command, Some(env_path))
+    }
+
+    fn run_shell_command_with_input(
+        &self,
+        cwd: &Path,
+        command: &str,
+        env_vars: &[(String, String)],
+        input: &[u8],
+    ) -> Result<(), ErrBox> {
+        run_shell_command_with_input(cwd, command, env_vars, input)
+    }
+
+    fn run_shell_command_with_env_changes(
+        &self,
+        cwd: &Path,
+        command: &str,
+        env_changes: &[(String, Option<String>)],
+    ) -> Result<i32, ErrBox> {
+        let mut command = create_shell_command(command);
+        for (key, value) in env_changes.iter() {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        let status = command
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .current_dir(cwd)
+            .status()?;
+        match status.code() {
+            Some(code) => Ok(code),
+            None => err!("Process terminated by signal."),
+        }
+    }
+
+    fn run_executable(
+        &self,
+        file_path: &Path,
+        args: &[String],
+        env_changes: &[(String, Option<String>)],
+    ) -> Result<i32, ErrBox> {
+        let mut command = Command::new(file_path);
+        command.args(args);
+        for (key, value) in env_changes.iter() {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+
+        // replace the current process so signals and the exit code go directly to the executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            let err = command.exec();
+            err!("Error running {}. {}", file_path.display(), err.to_string())
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            // let the executable decide how to handle ctrl+c since it's in the same console
+            ignore_ctrl_c();
+            let status = match command
+                .stdin(Stdio::inherit())
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit())
+                .status()
+            {
+                Ok(status) => status,
+                Err(err) => return err!("Error running {}. {}", file_path.display(), err.to_string()),
+            };
+            Ok(status.code().unwrap_or(1))
+        }
+    }
+
+    fn get_executable_output(&self, file_path: &Path, args: &[String]) -> Result<(i32, String), ErrBox> {
+        log_verbose!(self, "Running {}", file_path.display());
+        let output = match Command::new(file_path).args(args).stdin(Stdio::null()).output() {
+            Ok(output) => output,
+            Err(err) => return err!("Error running {}. {}", file_path.display(), err.to_string()),
+        };
+        let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok((output.status.code().unwrap_or(1), text))
+    }
+
+    fn spawn_background_exe(&self, args: &[String]) -> Result<(), ErrBox> {
+        let exe_path = self.get_current_exe()?;
+        let mut command = Command::new(&exe_path);
+        command
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        // don't receive the signals sent to the foreground process, such as from ctrl+c
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+
+        // don't attach to the console of the current process
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            const DETACHED_PROCESS: u32 = 0x00000008;
+            command.creation_flags(DETACHED_PROCESS);
+        }
+
+        match command.spawn() {
+            Ok(_) => Ok(()),
+            Err(err) => err!("Error running {}. {}", exe_path.display(), err.to_string()),
+        }
+    }
+
+    fn exit(&self, code: i32) -> Result<(), ErrBox> {
+        std::process::exit(code)
+    }
+
+    fn is_verbose(&self) -> bool {
+        self.is_verbose.load(Ordering::SeqCst)
+    }
+
+    fn set_verbose(&self, value: bool) {
+        self.is_verbose.store(value, Ordering::SeqCst);
+    }
+
+    fn is_quiet(&self) -> bool {
+        self.is_quiet.load(Ordering::SeqCst)
+    }
+
+    fn set_quiet(&self, value: bool) {
+        self.is_quiet.store(value, Ordering::SeqCst);
+    }
+
+    fn get_log_format(&self) -> LogFormat {
+        *self.log_format.lock().unwrap()
+    }
+
+    fn set_log_format(&self, format: LogFormat) {
+        *self.log_format.lock().unwrap() = format;
+    }
+
+    fn get_color_choice(&self) -> ColorChoice {
+        *self.color_choice.lock().unwrap()
+    }
+
+    fn set_color_choice(&self, choice: ColorChoice) {
+        *self.color_choice.lock().unwrap() = choice;
+    }
+
+    fn get_prompt_mode(&self) -> PromptMode {
+        *self.prompt_mode.lock().unwrap()
+    }
+
+    fn set_prompt_mode(&self, mode: PromptMode) {
+        *self.prompt_mode.lock().unwrap() = mode;
+    }
+
+    fn is_offline(&self) -> bool {
+        self.is_offline.load(Ordering::SeqCst)
+    }
+
+    fn set_offline(&self, value: bool) {
+        self.is_offline.store(value, Ordering::SeqCst);
+    }
+
+    fn is_refresh(&self) -> bool {
+        self.is_refresh.load(Ordering::SeqCst)
+    }
+
+    fn set_refresh(&self, value: bool) {
+        self.is_refresh.store(value, Ordering::SeqCst);
+    }
+
+    fn is_trust_hosts(&self) -> bool {
+        self.is_trust_hosts.load(Ordering::SeqCst)
+    }
+
+    fn set_trust_hosts(&self, value: bool) {
+        self.is_trust_hosts.store(value, Ordering::SeqCst);
+    }
+
+    fn get_max_download_speed(&self) -> Option<u64> {
+        self.bandwidth_limiter.get_max_bytes_per_sec()
+    }
+
+    fn set_max_download_speed(&self, value: Option<u64>) {
+        self.bandwidth_limiter.set_max_bytes_per_sec(value);
+    }
+
+    fn is_system_mode(&self) -> bool {
+        self.is_system_mode.load(Ordering::SeqCst)
+    }
+
+    fn set_system_mode(&self, value: bool) {
+        if self.is_system_mode.swap(value, Ordering::SeqCst) != value {
+            // errors are surfaced by the commands that use the user config
+            let _ = self.load_user_config_options();
+        }
+    }
+
+    fn has_system_privileges(&self) -> bool {
+        // check by writing a file since the privileges that are required differ between systems
+        let system_dir = get_system_dir();
+        let file_path = system_dir.join(".bvm-privileges-check");
+        let can_write = fs::create_dir_all(&system_dir).is_ok() && fs::write(&file_path, "").is_ok();
+        let _ = fs::remove_file(&file_path);
+        can_write
+    }
+}
+
+fn run_shell_command(cwd: &Path, command: &str, env_path: Option<&str>) -> Result<(), ErrBox> {
+    let mut command = create_shell_command(command);
+    if let Some(env_path) = env_path {
+        command.env("PATH", env_path);
+    }
+    let status = command
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .current_dir(cwd)
+        .status()?;
+    handle_exit_status(status)
+}
+
+fn run_shell_command_with_input(
+    cwd: &Path,
+    command: &str,
+    env_vars: &[(String, String)],
+    input: &[u8],
+) -> Result<(), ErrBox> {
+    let mut child = create_shell_command(command)
+        .envs(env_vars.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .current_dir(cwd)
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the command may exit without reading its input
+        let _ignore = stdin.write_all(input);
+    }
+    handle_exit_status(child.wait()?)
+}
+
+fn create_shell_command(command: &str) -> Command {
+    #[cfg(unix)]
+    let mut shell_command = Command::new("/bin/sh");
+    #[cfg(unix)]
+    shell_command.arg("-c");
+
+    #[cfg(target_os = "windows")]
+    let mut shell_command = Command::new("cmd");
+    #[cfg(target_os = "windows")]
+    shell_command.arg("/C");
+
+    shell_command.arg(command);
+    shell_command
+}
+
+fn handle_exit_status(exit_status: ExitStatus) -> Result<(), ErrBox> {
+    match exit_status.code() {
+        Some(code) => {
+            if code != 0 {
+                return err!("Received non zero exit code from shell command: {}", code);
+            } else {
+                Ok(())
+            }
+        }
+        None => err!("Process terminated by signal."),
+    }
+}
+
+#[cfg(unix)]
+fn is_process_running(process_id: u32) -> bool {
+    extern "C" {
+        fn kill(pid: i32, sig: i32) -> i32;
+    }
+    const EPERM: i32 = 1;
+    if process_id == 0 || process_id > i32::MAX as u32 {
+        return false;
+    }
+    // signal 0 only checks if the process exists, which it does when it's owned by another user
+    unsafe { kill(process_id as i32, 0) == 0 || std::io::Error::last_os_error().raw_os_error() == Some(EPERM) }
+}
+
+#[cfg(target_os = "windows")]
+fn is_process_running(process_id: u32) -> bool {
+    extern "system" {
+        fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> isize;
+        fn GetExitCodeProcess(process: isize, exit_code: *mut u32) -> i32;
+        fn CloseHandle(handle: isize) -> i32;
+        fn GetLastError() -> u32;
+    }
+    const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+    const STILL_ACTIVE: u32 = 259;
+    const ERROR_ACCESS_DENIED: u32 = 5;
+    unsafe {
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, process_id);
+        if process == 0 {
+            // the process exists when it's owned by another user
+            return GetLastError() == ERROR_ACCESS_DENIED;
+        }
+        let mut exit_code = 0;
+        let is_running = GetExitCodeProcess(process, &mut exit_code) != 0 && exit_code == STILL_ACTIVE;
+        CloseHandle(process);
+        is_running
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn ignore_ctrl_c() {
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: Option<unsafe extern "system" fn(u32) -> i32>, add: i32) -> i32;
+    }
+    unsafe {
+        SetConsoleCtrlHandler(None, 1);
+    }
+}
+
+fn get_default_user_data_dir() -> PathBuf {
+    if cfg!(target_os = "windows") {
+        let dir = dirs::data_dir().expect("Could not get data dir");
+        dir.join("bvm")
+    } else {
+        get_home_dir()
+    }
+}
+
+fn get_home_dir() -> PathBuf {
+    let dir = dirs::home_dir().expect("Could not get home data dir");
+    dir.join(".bvm")
+}
+
+/// Sets the path in the registry as an expandable string since it may contain entries like `%SystemRoot%\system32`.
+#[cfg(windows)]
+fn set_registry_path(env: &winreg::RegKey, path: &str) -> Result<(), ErrBox> {
+    use winreg::{enums::RegType, types::ToRegValue};
+    let mut value = path.to_reg_value();
+    value.vtype = RegType::REG_EXPAND_SZ;
+    env.set_raw_value("Path", &value)?;
+    Ok(())
+}
+
+/// Script that the login shells of all users source, which has the environment variables of the system installation.
+#[cfg(not(windows))]
+const SYSTEM_PROFILE_SCRIPT_PATH: &str = "/etc/profile.d/bvm.sh";
+
+/// Gets the directory of the machine-wide installation, which is used for everything in system mode.
+fn get_system_dir() -> PathBuf {
+    if cfg!(target_os = "windows") {
+        let program_files_dir = env::var_os("ProgramFiles").unwrap_or_else(|| "C:\\Program Files".into());
+        PathBuf::from(program_files_dir).join("bvm")
+    } else {
+        PathBuf::from("/usr/local/bvm")
+    }
+}
+
+#[cfg(unix)]
+fn is_executable_file(_: &Path, metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(windows)]
+fn is_executable_file(file_path: &Path, _: &fs::Metadata) -> bool {
+    match file_path.extension() {
+        Some(extension) => extension.to_string_lossy().to_lowercase() == "exe",
+        None => false,
+    }
+}
+
+#[cfg(unix)]
+fn get_hard_link_count(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.nlink())
+}
+
+#[cfg(windows)]
+fn get_hard_link_count(_: &fs::Metadata) -> Option<u64> {
+    // the link count is only available on stable rust by calling the win32 api directly
+    None
+}
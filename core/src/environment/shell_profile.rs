@@ -0,0 +1,159 @@
+/// Shell profile files in the home directory that bvm keeps its managed block in.
+pub const SHELL_PROFILE_FILE_NAMES: [&str; 4] = [".bashrc", ".bash_profile", ".profile", ".zshrc"];
+
+const MANAGED_BLOCK_START: &str = "# >>> bvm >>>";
+const MANAGED_BLOCK_END: &str = "# <<< bvm <<<";
+
+/// Gets the environment variables exported in bvm's managed block of a shell profile.
+pub fn get_profile_env_vars(text: &str) -> Vec<(String, String)> {
+    let mut env_vars = Vec::new();
+    for line in get_managed_block_lines(text) {
+        if let Some((key, value)) = line.trim().strip_prefix("export ").and_then(|e| e.split_once('=')) {
+            env_vars.push((key.to_string(), unquote_value(value)));
+        }
+    }
+    env_vars
+}
+
+/// Replaces bvm's managed block of a shell profile with one exporting the provided environment variables.
+/// The block is appended when the profile doesn't have one and removed when there are no variables.
+pub fn set_profile_env_vars(text: &str, env_vars: &[(String, String)]) -> String {
+    if env_vars.is_empty() {
+        return set_managed_block(text, None);
+    }
+
+    let mut block = String::new();
+    for (key, value) in env_vars {
+        block.push_str(&format!("export {}={}\n", key, quote_value(value)));
+    }
+    // the install directory is exported by setup, so also source the shell integration
+    if env_vars.iter().any(|(key, _)| key == "BVM_INSTALL_DIR") {
+        block.push_str(". \"$BVM_INSTALL_DIR/bin/bvm-init\"\n");
+    }
+    set_managed_block(text, Some(&block))
+}
+
+/// Replaces the contents of bvm's managed block in a shell configuration file, which is appended when the file
+/// doesn't have one. Providing `None` removes the block.
+pub fn set_managed_block(text: &str, contents: Option<&str>) -> String {
+    let block = contents.map(|contents| {
+        let mut block = String::from(MANAGED_BLOCK_START);
+        block.push('\n');
+        block.push_str(contents);
+        if !contents.ends_with('\n') {
+            block.push('\n');
+        }
+        block.push_str(MANAGED_BLOCK_END);
+        block
+    });
+
+    match find_managed_block(text) {
+        Some((start, end)) => {
+            let after = text[end..].strip_prefix('\n').unwrap_or(&text[end..]);
+            match block {
+                Some(block) => format!("{}{}\n{}", &text[..start], block, after),
+                None => format!("{}{}", &text[..start], after),
+            }
+        }
+        None => match block {
+            Some(block) => {
+                let mut new_text = text.to_string();
+                if !new_text.is_empty() && !new_text.ends_with('\n') {
+                    new_text.push('\n');
+                }
+                new_text.push_str(&block);
+                new_text.push('\n');
+                new_text
+            }
+            None => text.to_string(),
+        },
+    }
+}
+
+/// Gets the byte range of the managed block, excluding the trailing newline.
+fn find_managed_block(text: &str) -> Option<(usize, usize)> {
+    let start = text.find(MANAGED_BLOCK_START)?;
+    let end = match text[start..].find(MANAGED_BLOCK_END) {
+        Some(index) => start + index + MANAGED_BLOCK_END.len(),
+        None => text.len(),
+    };
+    Some((start, end))
+}
+
+fn get_managed_block_lines(text: &str) -> Vec<&str> {
+    match find_managed_block(text) {
+        Some((start, end)) => text[start..end]
+            .lines()
+            .filter(|line| *line != MANAGED_BLOCK_START && *line != MANAGED_BLOCK_END)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn quote_value(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn unquote_value(value: &str) -> String {
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        value[1..value.len() - 1].replace("'\\''", "'")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn env_vars(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn should_append_block() {
+        let text = set_profile_env_vars("alias ll='ls -l'", &env_vars(&[("KEY", "it's")]));
+        assert_eq!(
+            text,
+            "alias ll='ls -l'\n# >>> bvm >>>\nexport KEY='it'\\''s'\n# <<< bvm <<<\n"
+        );
+        assert_eq!(get_profile_env_vars(&text), env_vars(&[("KEY", "it's")]));
+    }
+
+    #[test]
+    fn should_replace_block() {
+        let text = "a\n# >>> bvm >>>\nexport KEY='1'\n# <<< bvm <<<\nb\n";
+        assert_eq!(
+            set_profile_env_vars(text, &env_vars(&[("KEY", "2"), ("OTHER", "3")])),
+            "a\n# >>> bvm >>>\nexport KEY='2'\nexport OTHER='3'\n# <<< bvm <<<\nb\n"
+        );
+    }
+
+    #[test]
+    fn should_remove_block_when_empty() {
+        let text = "a\n# >>> bvm >>>\nexport KEY='1'\n# <<< bvm <<<\nb\n";
+        assert_eq!(set_profile_env_vars(text, &[]), "a\nb\n");
+        assert_eq!(set_profile_env_vars("a\n", &[]), "a\n");
+    }
+
+    #[test]
+    fn should_source_init_script_with_install_dir() {
+        let text = set_profile_env_vars("", &env_vars(&[("BVM_INSTALL_DIR", "/home/user/.bvm")]));
+        assert_eq!(
+            text,
+            concat!(
+                "# >>> bvm >>>\n",
+                "export BVM_INSTALL_DIR='/home/user/.bvm'\n",
+                ". \"$BVM_INSTALL_DIR/bin/bvm-init\"\n",
+                "# <<< bvm <<<\n",
+            )
+        );
+        assert_eq!(
+            get_profile_env_vars(&text),
+            env_vars(&[("BVM_INSTALL_DIR", "/home/user/.bvm")])
+        );
+    }
+}
@@ -0,0 +1,1014 @@
+use dprint_cli_core::types::ErrBox;
+use path_clean::PathClean;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::{
+    download_with_retries, format_log_message, get_dir_override, get_file_url_path, is_color_enabled, read_user_config,
+    CacheValidators, ColorChoice, ConditionalDownload, DownloadCancelledError, DownloadStatusError, Environment, Event,
+    LogFormat, LogLevel, PromptMode, RetryPolicy,
+};
+use crate::errors::ErrorCode;
+use crate::registry::{resolve_source_request, SourceContext};
+use crate::utils::strip_ansi_codes;
+
+/// The command, relative path, and bytes of a file written when a shell command is run.
+type ShellCommandFile = (String, PathBuf, Vec<u8>);
+/// A shell command and what to do in the environment when it's run.
+type ShellCommandCallback = (String, Arc<dyn Fn(&TestEnvironment) + Send + Sync>);
+/// The environment variables and input of a shell command.
+type ShellCommandInput = (Vec<(String, String)>, String);
+/// Environment variable changes, where `None` removes the variable.
+type EnvChanges = Vec<(String, Option<String>)>;
+/// The file path, arguments, and environment variable changes of a run executable.
+type RunExecutable = (PathBuf, Vec<String>, Vec<(String, Option<String>)>);
+
+#[derive(Clone)]
+pub struct TestEnvironment {
+    // todo: single arc and mutex...
+    is_verbose: Arc<Mutex<bool>>,
+    is_quiet: Arc<Mutex<bool>>,
+    log_format: Arc<Mutex<LogFormat>>,
+    color_choice: Arc<Mutex<ColorChoice>>,
+    prompt_mode: Arc<Mutex<PromptMode>>,
+    /// If the output should be treated as a terminal.
+    is_terminal: Arc<Mutex<bool>>,
+    is_offline: Arc<Mutex<bool>>,
+    is_refresh: Arc<Mutex<bool>>,
+    is_trust_hosts: Arc<Mutex<bool>>,
+    /// Answer to give when asked to confirm or `None` to act as if not run interactively.
+    confirm_answer: Arc<Mutex<Option<bool>>>,
+    select_answer: Arc<Mutex<Option<usize>>>,
+    multi_select_answer: Arc<Mutex<Option<Vec<usize>>>>,
+    time_secs: Arc<Mutex<u64>>,
+    cwd: Arc<Mutex<String>>,
+    files: Arc<Mutex<HashMap<PathBuf, Vec<u8>>>>,
+    /// Paths of the files that were written.
+    written_files: Arc<Mutex<Vec<PathBuf>>>,
+    logged_messages: Arc<Mutex<Vec<String>>>,
+    logged_errors: Arc<Mutex<Vec<String>>>,
+    events: Arc<Mutex<Vec<Event>>>,
+    run_shell_commands: Arc<Mutex<Vec<(String, String)>>>,
+    run_shell_command_env_paths: Arc<Mutex<Vec<String>>>,
+    run_shell_command_env_changes: Arc<Mutex<Vec<EnvChanges>>>,
+    /// The environment variables and input of shell commands run with input.
+    run_shell_command_inputs: Arc<Mutex<Vec<ShellCommandInput>>>,
+    /// Files to write relative to the cwd when a shell command is run.
+    shell_command_files: Arc<Mutex<Vec<ShellCommandFile>>>,
+    /// Callbacks to run when a shell command is run, such as to inspect the environment at that time.
+    shell_command_callbacks: Arc<Mutex<Vec<ShellCommandCallback>>>,
+    run_executables: Arc<Mutex<Vec<RunExecutable>>>,
+    executable_exit_code: Arc<Mutex<i32>>,
+    executable_outputs: Arc<Mutex<HashMap<String, (i32, String)>>>,
+    /// Arguments of the processes of the current executable that were started in the background.
+    background_exe_args: Arc<Mutex<Vec<Vec<String>>>>,
+    remote_files: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    remote_file_etags: Arc<Mutex<HashMap<String, String>>>,
+    /// Statuses to respond with, in order, before a remote file is returned.
+    remote_file_failures: Arc<Mutex<HashMap<String, Vec<u16>>>>,
+    /// Urls whose downloads are cancelled as if ctrl+c was pressed halfway through.
+    cancelled_urls: Arc<Mutex<Vec<String>>>,
+    sleep_durations: Arc<Mutex<Vec<std::time::Duration>>>,
+    /// Ids of the processes that aren't running. Any other process is assumed to be running.
+    exited_process_ids: Arc<Mutex<Vec<u32>>>,
+    max_download_speed: Arc<Mutex<Option<u64>>>,
+    /// Urls of the remote files that were downloaded, excluding not modified conditional requests.
+    downloaded_urls: Arc<Mutex<Vec<String>>>,
+    /// Urls whose downloads were streamed rather than read into memory.
+    streamed_urls: Arc<Mutex<Vec<String>>>,
+    deleted_directories: Arc<Mutex<Vec<PathBuf>>>,
+    path_dirs: Arc<Mutex<Vec<PathBuf>>>,
+    sys_env_variables: Arc<Mutex<HashMap<String, String>>>,
+    env_variables: Arc<Mutex<HashMap<String, String>>>,
+    is_system_mode: Arc<Mutex<bool>>,
+    has_system_privileges: Arc<Mutex<bool>>,
+}
+
+impl Default for TestEnvironment {
+    fn default() -> Self {
+        TestEnvironment::new()
+    }
+}
+
+impl TestEnvironment {
+    pub fn new() -> TestEnvironment {
+        let mut env_variables = HashMap::new();
+        env_variables.insert("PATH".to_string(), "/data/shims".to_string());
+        TestEnvironment {
+            is_verbose: Arc::new(Mutex::new(false)),
+            is_quiet: Arc::new(Mutex::new(false)),
+            log_format: Arc::new(Mutex::new(LogFormat::Text)),
+            color_choice: Arc::new(Mutex::new(ColorChoice::Auto)),
+            prompt_mode: Arc::new(Mutex::new(PromptMode::Auto)),
+            is_terminal: Arc::new(Mutex::new(false)),
+            is_offline: Arc::new(Mutex::new(false)),
+            is_refresh: Arc::new(Mutex::new(false)),
+            is_trust_hosts: Arc::new(Mutex::new(false)),
+            confirm_answer: Arc::new(Mutex::new(None)),
+            select_answer: Arc::new(Mutex::new(None)),
+            multi_select_answer: Arc::new(Mutex::new(None)),
+            time_secs: Arc::new(Mutex::new(123456)),
+            cwd: Arc::new(Mutex::new(String::from("/"))),
+            files: Arc::new(Mutex::new(HashMap::new())),
+            written_files: Arc::new(Mutex::new(Vec::new())),
+            logged_messages: Arc::new(Mutex::new(Vec::new())),
+            logged_errors: Arc::new(Mutex::new(Vec::new())),
+            events: Arc::new(Mutex::new(Vec::new())),
+            run_shell_commands: Arc::new(Mutex::new(Vec::new())),
+            run_shell_command_env_paths: Arc::new(Mutex::new(Vec::new())),
+            run_shell_command_env_changes: Arc::new(Mutex::new(Vec::new())),
+            run_shell_command_inputs: Arc::new(Mutex::new(Vec::new())),
+            shell_command_files: Arc::new(Mutex::new(Vec::new())),
+            shell_command_callbacks: Arc::new(Mutex::new(Vec::new())),
+            run_executables: Arc::new(Mutex::new(Vec::new())),
+            executable_exit_code: Arc::new(Mutex::new(0)),
+            executable_outputs: Arc::new(Mutex::new(HashMap::new())),
+            background_exe_args: Arc::new(Mutex::new(Vec::new())),
+            remote_files: Arc::new(Mutex::new(HashMap::new())),
+            remote_file_etags: Arc::new(Mutex::new(HashMap::new())),
+            remote_file_failures: Arc::new(Mutex::new(HashMap::new())),
+            cancelled_urls: Arc::new(Mutex::new(Vec::new())),
+            sleep_durations: Arc::new(Mutex::new(Vec::new())),
+            exited_process_ids: Arc::new(Mutex::new(Vec::new())),
+            max_download_speed: Arc::new(Mutex::new(None)),
+            downloaded_urls: Arc::new(Mutex::new(Vec::new())),
+            streamed_urls: Arc::new(Mutex::new(Vec::new())),
+            deleted_directories: Arc::new(Mutex::new(Vec::new())),
+            path_dirs: Arc::new(Mutex::new(vec![PathBuf::from("/data/shims")])),
+            sys_env_variables: Arc::new(Mutex::new(HashMap::new())),
+            env_variables: Arc::new(Mutex::new(env_variables)),
+            is_system_mode: Arc::new(Mutex::new(false)),
+            has_system_privileges: Arc::new(Mutex::new(true)),
+        }
+    }
+
+    pub fn set_is_terminal(&self, value: bool) {
+        *self.is_terminal.lock().unwrap() = value;
+    }
+
+    fn is_color_enabled(&self) -> bool {
+        is_color_enabled(
+            self.get_color_choice(),
+            *self.is_terminal.lock().unwrap(),
+            self.get_env_var("NO_COLOR"),
+        )
+    }
+
+    fn get_stderr_text(&self, text: &str) -> String {
+        if self.get_log_format() == LogFormat::Text && self.is_color_enabled() {
+            text.to_string()
+        } else {
+            strip_ansi_codes(text).to_string()
+        }
+    }
+
+    pub fn take_logged_messages(&self) -> Vec<String> {
+        self.logged_messages.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn clear_logs(&self) {
+        self.logged_messages.lock().unwrap().clear();
+        self.logged_errors.lock().unwrap().clear();
+    }
+
+    pub fn take_logged_errors(&self) -> Vec<String> {
+        self.logged_errors.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn take_events(&self) -> Vec<Event> {
+        self.events.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn take_run_shell_commands(&self) -> Vec<(String, String)> {
+        self.run_shell_commands.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn take_run_shell_command_env_changes(&self) -> Vec<EnvChanges> {
+        self.run_shell_command_env_changes.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn take_run_executables(&self) -> Vec<RunExecutable> {
+        self.run_executables.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn take_background_exe_args(&self) -> Vec<Vec<String>> {
+        self.background_exe_args.lock().unwrap().drain(..).collect()
+    }
+
+    /// Sets the exit code returned when running executables and shell commands that return their exit code.
+    pub fn set_executable_exit_code(&self, code: i32) {
+        *self.executable_exit_code.lock().unwrap() = code;
+    }
+
+    /// Sets the exit code and output returned when getting the output of executables with the provided file name.
+    pub fn set_executable_output(&self, file_name: &str, exit_code: i32, output: &str) {
+        self.executable_outputs
+            .lock()
+            .unwrap()
+            .insert(file_name.to_string(), (exit_code, output.to_string()));
+    }
+
+    /// Takes the path environment variables of shell commands run with a custom path.
+    pub fn take_run_shell_command_env_paths(&self) -> Vec<String> {
+        self.run_shell_command_env_paths.lock().unwrap().drain(..).collect()
+    }
+
+    /// Takes the environment variables and input of shell commands run with input.
+    pub fn take_run_shell_command_inputs(&self) -> Vec<ShellCommandInput> {
+        self.run_shell_command_inputs.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn get_sys_env_variables(&self) -> Vec<(String, String)> {
+        let mut items = self
+            .sys_env_variables
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+        items.sort();
+        items
+    }
+
+    /// Writes the file relative to the command's cwd whenever the shell command is run.
+    pub fn add_shell_command_file(&self, command: &str, relative_path: &str, bytes: Vec<u8>) {
+        let mut shell_command_files = self.shell_command_files.lock().unwrap();
+        shell_command_files.push((command.to_string(), PathBuf::from(relative_path), bytes));
+    }
+
+    /// Runs the callback whenever the shell command is run.
+    pub fn add_shell_command_callback(
+        &self,
+        command: &str,
+        callback: impl Fn(&TestEnvironment) + Send + Sync + 'static,
+    ) {
+        let mut shell_command_callbacks = self.shell_command_callbacks.lock().unwrap();
+        shell_command_callbacks.push((command.to_string(), Arc::new(callback)));
+    }
+
+    pub fn add_remote_file(&self, path: &str, bytes: Vec<u8>) {
+        let mut remote_files = self.remote_files.lock().unwrap();
+        remote_files.insert(String::from(path), bytes);
+    }
+
+    pub fn remove_remote_file(&self, path: &str) -> Option<Vec<u8>> {
+        let mut remote_files = self.remote_files.lock().unwrap();
+        remote_files.remove(path)
+    }
+
+    /// Fails the next downloads of the remote file with the provided response statuses.
+    pub fn add_remote_file_failures(&self, url: &str, statuses: Vec<u16>) {
+        let mut remote_file_failures = self.remote_file_failures.lock().unwrap();
+        remote_file_failures
+            .entry(url.to_string())
+            .or_default()
+            .extend(statuses);
+    }
+
+    /// Cancels the downloads of the remote file as if ctrl+c was pressed halfway through.
+    pub fn set_remote_file_cancelled(&self, url: &str) {
+        self.cancelled_urls.lock().unwrap().push(url.to_string());
+    }
+
+    fn is_url_cancelled(&self, url: &str) -> bool {
+        self.cancelled_urls
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|cancelled_url| cancelled_url == url)
+    }
+
+    pub fn take_sleep_durations(&self) -> Vec<std::time::Duration> {
+        self.sleep_durations.lock().unwrap().drain(..).collect()
+    }
+
+    /// Sets the ETag returned for the remote file, which allows conditional requests for it.
+    pub fn set_remote_file_etag(&self, url: &str, etag: &str) {
+        let mut remote_file_etags = self.remote_file_etags.lock().unwrap();
+        remote_file_etags.insert(url.to_string(), etag.to_string());
+    }
+
+    pub fn take_downloaded_urls(&self) -> Vec<String> {
+        self.downloaded_urls.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn take_streamed_urls(&self) -> Vec<String> {
+        self.streamed_urls.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn take_written_files(&self) -> Vec<PathBuf> {
+        self.written_files.lock().unwrap().drain(..).collect()
+    }
+
+    pub fn set_confirm_answer(&self, answer: Option<bool>) {
+        *self.confirm_answer.lock().unwrap() = answer;
+    }
+
+    pub fn set_select_answer(&self, answer: Option<usize>) {
+        *self.select_answer.lock().unwrap() = answer;
+    }
+
+    pub fn set_multi_select_answer(&self, answer: Option<Vec<usize>>) {
+        *self.multi_select_answer.lock().unwrap() = answer;
+    }
+
+    pub fn set_has_system_privileges(&self, value: bool) {
+        *self.has_system_privileges.lock().unwrap() = value;
+    }
+
+    pub fn set_time_secs(&self, time_secs: u64) {
+        *self.time_secs.lock().unwrap() = time_secs;
+    }
+
+    /// Makes the process with the id appear to have exited.
+    pub fn set_process_exited(&self, process_id: u32) {
+        self.exited_process_ids.lock().unwrap().push(process_id);
+    }
+
+    pub fn is_dir_deleted(&self, path: &Path) -> bool {
+        let deleted_directories = self.deleted_directories.lock().unwrap();
+        deleted_directories.contains(&path.to_path_buf())
+    }
+
+    pub fn set_cwd(&self, new_path: &str) {
+        let mut cwd = self.cwd.lock().unwrap();
+        *cwd = String::from(new_path);
+    }
+
+    pub fn add_path_dir(&self, dir: PathBuf) {
+        let mut path_dirs = self.path_dirs.lock().unwrap();
+        path_dirs.push(dir);
+    }
+
+    pub fn get_system_path_dirs(&self) -> Vec<PathBuf> {
+        self.path_dirs.lock().unwrap().clone()
+    }
+
+    pub fn set_env_path(&self, new_path: &str) {
+        let mut env_variables = self.env_variables.lock().unwrap();
+        let env_path = env_variables.get_mut("PATH").unwrap();
+        *env_path = new_path.to_string();
+    }
+
+    pub fn set_env_var(&self, key: &str, value: &str) {
+        let mut env_variables = self.env_variables.lock().unwrap();
+        env_variables.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn remove_env_var(&self, key: &str) {
+        let mut env_variables = self.env_variables.lock().unwrap();
+        env_variables.remove(&key.to_string());
+    }
+}
+
+impl Drop for TestEnvironment {
+    fn drop(&mut self) {
+        // If this panics that means the logged messages or errors weren't inspected for a test.
+        // Use take_logged_messages() or take_logged_errors() and inspect the results.
+        if !std::thread::panicking() && Arc::strong_count(&self.logged_messages) == 1 {
+            assert_eq!(
+                self.logged_messages.lock().unwrap().clone(),
+                Vec::<String>::new(),
+                "should not have logged messages left on drop"
+            );
+            assert_eq!(
+                self.logged_errors.lock().unwrap().clone(),
+                Vec::<String>::new(),
+                "should not have logged errors left on drop"
+            );
+            assert_eq!(
+                self.run_shell_commands.lock().unwrap().clone(),
+                Vec::<(String, String)>::new(),
+                "should not have run shell commands left on drop"
+            );
+        }
+    }
+}
+
+impl Environment for TestEnvironment {
+    fn is_real(&self) -> bool {
+        false
+    }
+
+    fn read_file_text(&self, file_path: &Path) -> Result<String, ErrBox> {
+        let file_bytes = self.read_file(file_path)?;
+        Ok(String::from_utf8(file_bytes.to_vec()).unwrap())
+    }
+
+    fn read_file(&self, file_path: &Path) -> Result<Vec<u8>, ErrBox> {
+        let files = self.files.lock().unwrap();
+        // temporary until https://github.com/danreeves/path-clean/issues/4 is fixed in path-clean
+        let file_path = PathBuf::from(file_path.to_string_lossy().replace("\\", "/"));
+        match files.get(&file_path.clean()) {
+            Some(text) => Ok(text.clone()),
+            None => err!("Could not find file at path {}", file_path.display()),
+        }
+    }
+
+    fn write_file_text(&self, file_path: &Path, file_text: &str) -> Result<(), ErrBox> {
+        self.write_file(file_path, file_text.as_bytes())
+    }
+
+    fn create_new_file_text(&self, file_path: &Path, file_text: &str) -> Result<bool, ErrBox> {
+        let mut files = self.files.lock().unwrap();
+        match files.entry(file_path.to_path_buf().clean()) {
+            Entry::Occupied(_) => Ok(false),
+            Entry::Vacant(entry) => {
+                entry.insert(file_text.to_string().into_bytes());
+                Ok(true)
+            }
+        }
+    }
+
+    fn write_file(&self, file_path: &Path, bytes: &[u8]) -> Result<(), ErrBox> {
+        let mut files = self.files.lock().unwrap();
+        files.insert(file_path.to_path_buf().clean(), Vec::from(bytes));
+        self.written_files.lock().unwrap().push(file_path.to_path_buf().clean());
+        Ok(())
+    }
+
+    fn remove_file(&self, file_path: &Path) -> Result<(), ErrBox> {
+        let mut files = self.files.lock().unwrap();
+        files.remove(&file_path.to_path_buf().clean());
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, dir_path: &Path) -> Result<(), ErrBox> {
+        {
+            let mut deleted_directories = self.deleted_directories.lock().unwrap();
+            deleted_directories.push(dir_path.to_owned());
+        }
+        let dir_path = dir_path.to_path_buf().clean();
+        let mut files = self.files.lock().unwrap();
+        let mut delete_paths = Vec::new();
+        for (file_path, _) in files.iter() {
+            if file_path.starts_with(&dir_path) {
+                delete_paths.push(file_path.clone());
+            }
+        }
+        for path in delete_paths {
+            files.remove(&path);
+        }
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), ErrBox> {
+        let from = from.to_path_buf().clean();
+        let to = to.to_path_buf().clean();
+        let mut files = self.files.lock().unwrap();
+        let moved_paths = files
+            .keys()
+            .filter(|file_path| file_path.starts_with(&from))
+            .cloned()
+            .collect::<Vec<_>>();
+        for file_path in moved_paths {
+            let bytes = files.remove(&file_path).unwrap();
+            files.insert(to.join(file_path.strip_prefix(&from).unwrap()).clean(), bytes);
+        }
+        Ok(())
+    }
+
+    fn hard_link(&self, existing_path: &Path, new_path: &Path) -> Result<(), ErrBox> {
+        // links aren't tracked, so copy the file
+        let bytes = self.read_file(existing_path)?;
+        self.write_file(new_path, &bytes)
+    }
+
+    fn get_hard_link_count(&self, file_path: &Path) -> Result<Option<u64>, ErrBox> {
+        // approximate the links as the files with the same content
+        let bytes = self.read_file(file_path)?;
+        let files = self.files.lock().unwrap();
+        Ok(Some(
+            files.values().filter(|file_bytes| **file_bytes == bytes).count() as u64
+        ))
+    }
+
+    fn download_file(&self, url: &str) -> Result<Vec<u8>, ErrBox> {
+        if let Some(file_path) = get_file_url_path(url) {
+            return self.read_file(&file_path);
+        }
+        if self.is_offline() {
+            return coded_err!(ErrorCode::Offline, "Could not download {} because bvm is offline.", url);
+        }
+        if self.is_url_cancelled(url) {
+            return Err(Box::new(DownloadCancelledError { url: url.to_string() }));
+        }
+        // the urls of source providers are served by the remote file of the url they resolve to
+        let request_url = resolve_source_request(
+            url,
+            &SourceContext {
+                get_env_var: &|name| self.get_env_var(name),
+                time_secs: self.get_time_secs(),
+            },
+        )?
+        .url;
+        let retry_policy = RetryPolicy::from_user_config(&read_user_config(self)?);
+        download_with_retries(self, &retry_policy, || {
+            if let Some(statuses) = self.remote_file_failures.lock().unwrap().get_mut(url) {
+                if !statuses.is_empty() {
+                    return Err(Box::new(DownloadStatusError {
+                        url: url.to_string(),
+                        status: statuses.remove(0),
+                    }));
+                }
+            }
+            let remote_files = self.remote_files.lock().unwrap();
+            match remote_files.get(&request_url) {
+                Some(bytes) => {
+                    self.downloaded_urls.lock().unwrap().push(url.to_string());
+                    Ok(bytes.clone())
+                }
+                None => err!("Could not find file at url {}", url),
+            }
+        })
+    }
+
+    fn download_file_if_modified(
+        &self,
+        url: &str,
+        validators: &CacheValidators,
+    ) -> Result<ConditionalDownload, ErrBox> {
+        let etag = self.remote_file_etags.lock().unwrap().get(url).cloned();
+        if etag.is_some() && etag == validators.etag && !self.is_offline() {
+            return Ok(ConditionalDownload::NotModified);
+        }
+        let bytes = self.download_file(url)?;
+        Ok(ConditionalDownload::Modified(
+            bytes,
+            CacheValidators {
+                etag,
+                last_modified: None,
+            },
+        ))
+    }
+
+    fn download_file_streaming(
+        &self,
+        url: &str,
+        handler: &mut dyn FnMut(&mut dyn Read) -> Result<(), ErrBox>,
+    ) -> Result<(), ErrBox> {
+        if self.is_url_cancelled(url) {
+            // provide the first half of the file, then fail the read like the real environment does
+            let bytes = self.remote_files.lock().unwrap().get(url).cloned().unwrap_or_default();
+            let half_length = bytes.len() / 2;
+            let mut reader = std::io::Cursor::new(bytes)
+                .take(half_length as u64)
+                .chain(CancelledReader);
+            let _ignore = handler(&mut reader);
+            return Err(Box::new(DownloadCancelledError { url: url.to_string() }));
+        }
+        let bytes = self.download_file(url)?;
+        if get_file_url_path(url).is_none() {
+            self.streamed_urls.lock().unwrap().push(url.to_string());
+        }
+        handler(&mut std::io::Cursor::new(bytes))
+    }
+
+    fn path_exists(&self, file_path: &Path) -> bool {
+        let files = self.files.lock().unwrap();
+        files.contains_key(&file_path.to_path_buf().clean())
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        // directories aren't stored, so consider any path containing files a directory
+        let path = path.to_path_buf().clean();
+        let files = self.files.lock().unwrap();
+        files
+            .keys()
+            .any(|file_path| file_path != &path && file_path.starts_with(&path))
+    }
+
+    fn create_dir_all(&self, _: &Path) -> Result<(), ErrBox> {
+        Ok(())
+    }
+
+    fn is_dir_empty(&self, dir_path: &Path) -> Result<bool, ErrBox> {
+        let dir_path = dir_path.to_path_buf().clean();
+        let files = self.files.lock().unwrap();
+        for file_path in files.keys() {
+            if file_path.starts_with(&dir_path) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn get_dir_size(&self, dir_path: &Path) -> Result<u64, ErrBox> {
+        let dir_path = dir_path.to_path_buf().clean();
+        let files = self.files.lock().unwrap();
+        Ok(files
+            .iter()
+            .filter(|(file_path, _)| file_path.starts_with(&dir_path))
+            .map(|(_, bytes)| bytes.len() as u64)
+            .sum())
+    }
+
+    fn get_dir_paths(&self, dir_path: &Path) -> Result<Vec<PathBuf>, ErrBox> {
+        // directories aren't stored, so get the directories that files are within
+        let dir_path = dir_path.to_path_buf().clean();
+        let files = self.files.lock().unwrap();
+        let mut dir_paths = files
+            .keys()
+            .filter_map(|file_path| file_path.strip_prefix(&dir_path).ok())
+            .filter(|relative_path| relative_path.components().count() > 1)
+            .filter_map(|relative_path| relative_path.components().next())
+            .map(|component| dir_path.join(component))
+            .collect::<Vec<_>>();
+        dir_paths.sort();
+        dir_paths.dedup();
+        Ok(dir_paths)
+    }
+
+    fn get_file_paths(&self, dir_path: &Path) -> Result<Vec<PathBuf>, ErrBox> {
+        let dir_path = dir_path.to_path_buf().clean();
+        let files = self.files.lock().unwrap();
+        Ok(files
+            .keys()
+            .filter(|file_path| file_path.starts_with(&dir_path))
+            .cloned()
+            .collect())
+    }
+
+    fn get_executable_file_paths(&self, dir_path: &Path) -> Result<Vec<PathBuf>, ErrBox> {
+        let dir_path = dir_path.to_path_buf().clean();
+        let files = self.files.lock().unwrap();
+        // files don't have permissions here, so consider .exe files on windows and files without an extension otherwise
+        Ok(files
+            .keys()
+            .filter(|file_path| file_path.starts_with(&dir_path))
+            .filter(|file_path| match file_path.extension() {
+                Some(extension) => cfg!(target_os = "windows") && extension == "exe",
+                None => !cfg!(target_os = "windows"),
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn get_env_var(&self, key: &str) -> Option<String> {
+        let env_vars = self.env_variables.lock().unwrap();
+        env_vars.get(&key.to_string()).as_ref().map(|key| key.to_string())
+    }
+
+    #[cfg(windows)]
+    fn ensure_system_path(&self, directory_path: &str) -> Result<(), ErrBox> {
+        let mut path_dirs = self.path_dirs.lock().unwrap();
+        let directory_path = PathBuf::from(directory_path);
+        if !path_dirs.contains(&directory_path) {
+            path_dirs.push(directory_path);
+        }
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn ensure_system_path_pre(&self, directory_path: &str) -> Result<(), ErrBox> {
+        let mut path_dirs = self.path_dirs.lock().unwrap();
+        let directory_path = PathBuf::from(directory_path);
+        if let Some(pos) = path_dirs.iter().position(|p| p == &directory_path) {
+            path_dirs.remove(pos);
+        }
+        path_dirs.insert(0, directory_path);
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    fn remove_system_path(&self, directory_path: &str) -> Result<(), ErrBox> {
+        let mut path_dirs = self.path_dirs.lock().unwrap();
+        let directory_path = PathBuf::from(directory_path);
+        if let Some(pos) = path_dirs.iter().position(|p| p == &directory_path) {
+            path_dirs.remove(pos);
+        }
+        Ok(())
+    }
+
+    fn set_env_variable(&self, key: String, value: String) -> Result<(), ErrBox> {
+        let mut sys_env_variables = self.sys_env_variables.lock().unwrap();
+        sys_env_variables.insert(key, value);
+        Ok(())
+    }
+
+    fn remove_env_variable(&self, key: &str) -> Result<(), ErrBox> {
+        let mut sys_env_variables = self.sys_env_variables.lock().unwrap();
+        sys_env_variables.remove(key);
+        Ok(())
+    }
+
+    fn run_shell_command(&self, cwd: &Path, command: &str) -> Result<(), ErrBox> {
+        let mut run_shell_commands = self.run_shell_commands.lock().unwrap();
+        run_shell_commands.push((cwd.to_string_lossy().to_string(), command.to_string()));
+        let shell_command_files = self.shell_command_files.lock().unwrap();
+        for (_, relative_path, bytes) in shell_command_files.iter().filter(|(c, _, _)| c == command) {
+            self.write_file(&cwd.join(relative_path), bytes)?;
+        }
+        drop(shell_command_files);
+        drop(run_shell_commands);
+        // release the locks first since the callbacks may run shell commands
+        let callbacks = self
+            .shell_command_callbacks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(c, _)| c == command)
+            .map(|(_, callback)| callback.clone())
+            .collect::<Vec<_>>();
+        for callback in callbacks {
+            callback(self);
+        }
+        Ok(())
+    }
+
+    fn run_shell_command_with_env_path(&self, cwd: &Path, command: &str, env_path: &str) -> Result<(), ErrBox> {
+        self.run_shell_command(cwd, command)?;
+        self.run_shell_command_env_paths
+            .lock()
+            .unwrap()
+            .push(env_path.to_string());
+        Ok(())
+    }
+
+    fn run_shell_command_with_input(
+        &self,
+        cwd: &Path,
+        command: &str,
+        env_vars: &[(String, String)],
+        input: &[u8],
+    ) -> Result<(), ErrBox> {
+        self.run_shell_command(cwd, command)?;
+        self.run_shell_command_inputs
+            .lock()
+            .unwrap()
+            .push((env_vars.to_vec(), String::from_utf8_lossy(input).to_string()));
+        Ok(())
+    }
+
+    fn run_shell_command_with_env_changes(
+        &self,
+        cwd: &Path,
+        command: &str,
+        env_changes: &[(String, Option<String>)],
+    ) -> Result<i32, ErrBox> {
+        self.run_shell_command(cwd, command)?;
+        self.run_shell_command_env_changes
+            .lock()
+            .unwrap()
+            .push(env_changes.to_vec());
+        Ok(*self.executable_exit_code.lock().unwrap())
+    }
+
+    fn cwd(&self) -> Result<PathBuf, ErrBox> {
+        let cwd = self.cwd.lock().unwrap();
+        Ok(PathBuf::from(cwd.to_owned()))
+    }
+
+    fn log(&self, text: &str) {
+        let text = if self.is_color_enabled() {
+            text.to_string()
+        } else {
+            strip_ansi_codes(text).to_string()
+        };
+        self.logged_messages.lock().unwrap().push(text);
+    }
+
+    fn log_error(&self, text: &str) {
+        if !self.is_quiet() {
+            let text = format_log_message(self.get_log_format(), LogLevel::Info, &self.get_stderr_text(text));
+            self.logged_errors.lock().unwrap().push(text);
+        }
+    }
+
+    fn log_verbose(&self, text: &str) {
+        let text = format_log_message(self.get_log_format(), LogLevel::Verbose, &self.get_stderr_text(text));
+        self.logged_errors.lock().unwrap().push(text);
+    }
+
+    fn confirm(&self, message: &str) -> Result<Option<bool>, ErrBox> {
+        let answer = match self.get_prompt_mode() {
+            PromptMode::AssumeYes => {
+                self.log_error(&format!("{} Yes (--yes)", message));
+                return Ok(Some(true));
+            }
+            PromptMode::Never => None,
+            PromptMode::Auto => *self.confirm_answer.lock().unwrap(),
+        };
+        if answer.is_some() {
+            self.log_error(message);
+        }
+        Ok(answer)
+    }
+
+    fn select(&self, message: &str, items: &[String]) -> Result<Option<usize>, ErrBox> {
+        let answer = match self.get_prompt_mode() {
+            PromptMode::Auto => *self.select_answer.lock().unwrap(),
+            PromptMode::AssumeYes | PromptMode::Never => None,
+        };
+        if answer.is_some() {
+            self.log_error(&format!("{}\n  {}", message, items.join("\n  ")));
+        }
+        Ok(answer)
+    }
+
+    fn multi_select(&self, message: &str, items: &[String]) -> Result<Option<Vec<usize>>, ErrBox> {
+        let answer = match self.get_prompt_mode() {
+            PromptMode::Auto => self.multi_select_answer.lock().unwrap().clone(),
+            PromptMode::AssumeYes | PromptMode::Never => None,
+        };
+        if answer.is_some() {
+            self.log_error(&format!("{}\n  {}", message, items.join("\n  ")));
+        }
+        Ok(answer)
+    }
+
+    fn emit_event(&self, event: Event) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn log_action_with_progress<
+        TResult: std::marker::Send + std::marker::Sync,
+        TCreate: FnOnce(Box<dyn Fn(usize)>) -> TResult + std::marker::Send + std::marker::Sync,
+    >(
+        &self,
+        message: &str,
+        action: TCreate,
+        _: usize,
+    ) -> TResult {
+        self.log_error(message);
+        action(Box::new(|_| {}))
+    }
+
+    fn get_local_user_data_dir(&self) -> PathBuf {
+        if self.is_system_mode() {
+            return PathBuf::from("/system");
+        }
+        get_dir_override(self, "BVM_CACHE_DIR", |config| config.cache_dir)
+            .unwrap_or_else(|| PathBuf::from("/local-data"))
+    }
+
+    fn get_user_data_dir(&self) -> PathBuf {
+        if self.is_system_mode() {
+            return PathBuf::from("/system");
+        }
+        get_dir_override(self, "BVM_DATA_DIR", |config| config.data_dir).unwrap_or_else(|| self.get_user_config_dir())
+    }
+
+    fn get_user_config_dir(&self) -> PathBuf {
+        if self.is_system_mode() {
+            return PathBuf::from("/system");
+        }
+        PathBuf::from("/data")
+    }
+
+    fn get_user_home_dir(&self) -> PathBuf {
+        if self.is_system_mode() {
+            return PathBuf::from("/system");
+        }
+        PathBuf::from("/.bvm")
+    }
+
+    fn get_current_exe(&self) -> Result<PathBuf, ErrBox> {
+        Ok(PathBuf::from("/.bvm/bin/bvm-bin"))
+    }
+
+    fn get_time_secs(&self) -> u64 {
+        *self.time_secs.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: std::time::Duration) {
+        self.sleep_durations.lock().unwrap().push(duration);
+    }
+
+    fn is_process_running(&self, process_id: u32) -> bool {
+        !self.exited_process_ids.lock().unwrap().contains(&process_id)
+    }
+
+    fn run_executable(
+        &self,
+        file_path: &Path,
+        args: &[String],
+        env_changes: &[(String, Option<String>)],
+    ) -> Result<i32, ErrBox> {
+        if !self.path_exists(file_path) {
+            return err!("Error running {}. Could not find the file.", file_path.display());
+        }
+        self.run_executables
+            .lock()
+            .unwrap()
+            .push((file_path.to_path_buf(), args.to_vec(), env_changes.to_vec()));
+        Ok(*self.executable_exit_code.lock().unwrap())
+    }
+
+    fn get_executable_output(&self, file_path: &Path, _args: &[String]) -> Result<(i32, String), ErrBox> {
+        if !self.path_exists(file_path) {
+            return err!("Error running {}. Could not find the file.", file_path.display());
+        }
+        let file_name = file_path.file_name().map(|name| name.to_string_lossy().to_string());
+        let executable_outputs = self.executable_outputs.lock().unwrap();
+        Ok(file_name
+            .and_then(|file_name| executable_outputs.get(&file_name).cloned())
+            .unwrap_or((0, String::new())))
+    }
+
+    fn spawn_background_exe(&self, args: &[String]) -> Result<(), ErrBox> {
+        self.background_exe_args.lock().unwrap().push(args.to_vec());
+        Ok(())
+    }
+
+    fn exit(&self, code: i32) -> Result<(), ErrBox> {
+        err!("Exited with code {}", code)
+    }
+
+    fn is_verbose(&self) -> bool {
+        *self.is_verbose.lock().unwrap()
+    }
+
+    fn set_verbose(&self, value: bool) {
+        *self.is_verbose.lock().unwrap() = value;
+    }
+
+    fn is_quiet(&self) -> bool {
+        *self.is_quiet.lock().unwrap()
+    }
+
+    fn set_quiet(&self, value: bool) {
+        *self.is_quiet.lock().unwrap() = value;
+    }
+
+    fn get_log_format(&self) -> LogFormat {
+        *self.log_format.lock().unwrap()
+    }
+
+    fn set_log_format(&self, format: LogFormat) {
+        *self.log_format.lock().unwrap() = format;
+    }
+
+    fn get_color_choice(&self) -> ColorChoice {
+        *self.color_choice.lock().unwrap()
+    }
+
+    fn set_color_choice(&self, choice: ColorChoice) {
+        *self.color_choice.lock().unwrap() = choice;
+    }
+
+    fn get_prompt_mode(&self) -> PromptMode {
+        *self.prompt_mode.lock().unwrap()
+    }
+
+    fn set_prompt_mode(&self, mode: PromptMode) {
+        *self.prompt_mode.lock().unwrap() = mode;
+    }
+
+    fn is_offline(&self) -> bool {
+        *self.is_offline.lock().unwrap()
+    }
+
+    fn set_offline(&self, value: bool) {
+        *self.is_offline.lock().unwrap() = value;
+    }
+
+    fn is_refresh(&self) -> bool {
+        *self.is_refresh.lock().unwrap()
+    }
+
+    fn set_refresh(&self, value: bool) {
+        *self.is_refresh.lock().unwrap() = value;
+    }
+
+    fn is_trust_hosts(&self) -> bool {
+        *self.is_trust_hosts.lock().unwrap()
+    }
+
+    fn set_trust_hosts(&self, value: bool) {
+        *self.is_trust_hosts.lock().unwrap() = value;
+    }
+
+    fn get_max_download_speed(&self) -> Option<u64> {
+        *self.max_download_speed.lock().unwrap()
+    }
+
+    fn set_max_download_speed(&self, value: Option<u64>) {
+        *self.max_download_speed.lock().unwrap() = value;
+    }
+
+    fn is_system_mode(&self) -> bool {
+        *self.is_system_mode.lock().unwrap()
+    }
+
+    fn set_system_mode(&self, value: bool) {
+        *self.is_system_mode.lock().unwrap() = value;
+    }
+
+    fn has_system_privileges(&self) -> bool {
+        *self.has_system_privileges.lock().unwrap()
+    }
+}
+
+/// Reader that fails like the reader of a download that was cancelled.
+struct CancelledReader;
+
+impl Read for CancelledReader {
+    fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::other("Cancelled."))
+    }
+}
@@ -0,0 +1,258 @@
+use dprint_cli_core::types::ErrBox;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::{ensure_system_privileges, Environment};
+use crate::utils::parse_byte_size;
+
+/// Settings for the CLI that are stored in a `config.json` file in the user config directory.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UserConfig {
+    /// Proxy to use for all downloads. Takes precedence over the `HTTP_PROXY` and `HTTPS_PROXY` environment variables.
+    pub proxy: Option<String>,
+    /// Comma separated hosts that should not be downloaded via the proxy.
+    pub no_proxy: Option<String>,
+    /// Path to a PEM file with additional root certificates to trust when downloading.
+    pub ca_bundle: Option<String>,
+    /// Store the files of binaries in a content-addressed store and hard link them into the binary directories.
+    pub content_store: Option<bool>,
+    /// Only put the shims on the path and resolve the environment of a binary when its shim is run.
+    pub shims_only: Option<bool>,
+    /// Platform key of the target to install instead of detecting it from the current system (ex. `linux-x86_64`).
+    pub target: Option<String>,
+    /// Platform keys to try in order when a binary doesn't provide one for the current system (ex. `["linux-x86_64"]`).
+    pub target_fallbacks: Option<Vec<String>>,
+    /// Allow installing x86_64 binaries to run with Rosetta on Apple Silicon when no native binary is provided.
+    pub allow_rosetta: Option<bool>,
+    /// Whether AppImages are extracted when installed instead of being run with FUSE. Defaults to when FUSE isn't available.
+    pub app_image_extract: Option<bool>,
+    /// Whether installed archives are only extracted the first time one of their commands is run.
+    pub lazy_extraction: Option<bool>,
+    /// Number of the binaries of a configuration file to download and set up at the same time when installing it.
+    /// They're installed one at a time when this isn't set.
+    pub install_concurrency: Option<usize>,
+    /// Number of the most recent versions of each binary to keep installed.
+    pub keep_versions: Option<usize>,
+    /// Number of seconds a downloaded registry file is used before it's revalidated.
+    pub registry_cache_ttl: Option<u64>,
+    /// How downloads that fail with a transient error are retried.
+    pub download_retry: Option<UserDownloadRetryConfig>,
+    /// How long downloads wait on the server before failing.
+    pub download_timeout: Option<UserDownloadTimeoutConfig>,
+    /// Maximum combined speed of downloads in bytes per second with an optional unit (ex. `500K` or `2M`).
+    pub max_download_speed: Option<String>,
+    /// Hosts that binary manifest files and archives may be downloaded from. Downloading from other
+    /// hosts requires confirmation when this is set (ex. `["github.com", "*.example.com"]`).
+    pub trusted_hosts: Option<Vec<String>>,
+    /// Urls of registries to search for binary names that aren't associated with a registry.
+    pub registries: Option<Vec<String>>,
+    /// Registry urls that the binaries of an owner are only resolved from. Key is the owner (ex. `myorg`).
+    /// A project's config file may override these.
+    pub owner_registries: Option<HashMap<String, String>>,
+    /// Whether `bvm use` should install the binaries of the current directory's configuration file that
+    /// aren't installed instead of erroring.
+    pub auto_install: Option<bool>,
+    /// Whether to refuse installing archives that aren't verified by a checksum provided in their
+    /// binary manifest file. A project's config file may override this.
+    pub require_checksums: Option<bool>,
+    /// Whether running a command occasionally checks the registry for a newer version of its binary in the
+    /// background and outputs a notice when there is one. Defaults to on.
+    pub update_notifications: Option<bool>,
+    /// Directory to store the manifest, shims, and downloaded registry files in instead of the default user data directory.
+    pub data_dir: Option<String>,
+    /// Directory to install binaries in instead of the default local user data directory.
+    pub cache_dir: Option<String>,
+    /// Directory of another bvm installation that can't be written to (ex. `/opt/bvm` baked into a CI image),
+    /// whose installed binaries are used instead of downloading them again.
+    pub read_only_root: Option<String>,
+    /// Data directory of the Windows-side bvm (ex. `/mnt/c/Users/me/AppData/Roaming/bvm`) to share downloaded
+    /// registry files with when running inside WSL. Setting this also skips binaries with Windows executables.
+    pub wsl_windows_data_dir: Option<String>,
+    /// Whether command names and binary names are matched case insensitively, which defaults to on Windows.
+    pub case_insensitive_names: Option<bool>,
+    /// Owner to use for a binary name that's installed from multiple owners. Key is the binary name (ex. `dprint`).
+    /// Set when picking an owner interactively.
+    pub default_owners: Option<HashMap<String, String>>,
+    /// Commands to run on bvm events, which receive the details of the event.
+    pub hooks: Option<UserHooksConfig>,
+    /// Settings for specific binaries. Key is the binary name with or without an owner (ex. `owner/name` or `name`).
+    #[serde(default)]
+    pub binaries: HashMap<String, UserBinaryConfig>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UserBinaryConfig {
+    /// Number of the most recent versions of the binary to keep installed. Overrides the global setting.
+    pub keep_versions: Option<usize>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserHooksConfig {
+    /// Commands to run after a binary is installed.
+    #[serde(default)]
+    pub post_install: Vec<String>,
+    /// Commands to run after a command is switched to the version of a binary.
+    #[serde(default)]
+    pub post_use: Vec<String>,
+    /// Commands to run before a binary is uninstalled.
+    #[serde(default)]
+    pub pre_uninstall: Vec<String>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDownloadRetryConfig {
+    /// Number of times to attempt a download, including the first attempt.
+    pub attempts: Option<u32>,
+    /// Milliseconds to wait before the first retry, which doubles for each retry after.
+    pub backoff_ms: Option<u64>,
+    /// Response statuses to retry the download on.
+    pub retry_on_status: Option<Vec<u16>>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDownloadTimeoutConfig {
+    /// Milliseconds to wait for the connection to the server. 0 waits indefinitely.
+    pub connect_ms: Option<u64>,
+    /// Milliseconds to wait for the server to send more data. 0 waits indefinitely.
+    pub read_ms: Option<u64>,
+}
+
+impl UserConfig {
+    /// Gets the maximum combined speed of downloads in bytes per second.
+    pub fn get_max_download_speed(&self) -> Result<Option<u64>, ErrBox> {
+        match &self.max_download_speed {
+            Some(text) => match parse_byte_size(text) {
+                Ok(value) => Ok(Some(value)),
+                Err(err) => err!("Invalid value for 'maxDownloadSpeed'. {}", err.to_string()),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Gets the number of the most recent versions to keep installed for the binary with the provided owner and name.
+    pub fn get_keep_versions(&self, owner: &str, name: &str) -> Option<usize> {
+        self.binaries
+            .get(&format!("{}/{}", owner, name))
+            .or_else(|| self.binaries.get(name))
+            .and_then(|binary_config| binary_config.keep_versions)
+            .or(self.keep_versions)
+    }
+}
+
+/// The keys that may be set in the user config file.
+pub const USER_CONFIG_KEYS: [&str; 30] = [
+    "proxy",
+    "noProxy",
+    "caBundle",
+    "contentStore",
+    "shimsOnly",
+    "target",
+    "targetFallbacks",
+    "allowRosetta",
+    "appImageExtract",
+    "lazyExtraction",
+    "installConcurrency",
+    "keepVersions",
+    "registryCacheTtl",
+    "downloadRetry",
+    "downloadTimeout",
+    "maxDownloadSpeed",
+    "trustedHosts",
+    "registries",
+    "ownerRegistries",
+    "autoInstall",
+    "requireChecksums",
+    "updateNotifications",
+    "dataDir",
+    "cacheDir",
+    "readOnlyRoot",
+    "wslWindowsDataDir",
+    "caseInsensitiveNames",
+    "defaultOwners",
+    "hooks",
+    "binaries",
+];
+
+pub fn read_user_config(environment: &impl Environment) -> Result<UserConfig, ErrBox> {
+    let file_path = get_user_config_file_path(environment);
+    if !environment.path_exists(&file_path) {
+        return Ok(UserConfig::default());
+    }
+    let file_text = environment.read_file_text(&file_path)?;
+    match serde_json::from_str(&file_text) {
+        Ok(config) => Ok(config),
+        Err(err) => err!("Error deserializing {}. {}", file_path.display(), err.to_string()),
+    }
+}
+
+/// Sets or removes (when `None`) a value in the user config file.
+pub fn set_user_config_value(
+    environment: &impl Environment,
+    key: &str,
+    value: Option<serde_json::Value>,
+) -> Result<(), ErrBox> {
+    if !USER_CONFIG_KEYS.contains(&key) {
+        return err!(
+            "Unknown config key '{}'. Expected one of: {}",
+            key,
+            USER_CONFIG_KEYS.join(", ")
+        );
+    }
+    ensure_system_privileges(environment)?;
+
+    let file_path = get_user_config_file_path(environment);
+    let mut config = if environment.path_exists(&file_path) {
+        match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(
+            &environment.read_file_text(&file_path)?,
+        ) {
+            Ok(config) => config,
+            Err(err) => return err!("Error deserializing {}. {}", file_path.display(), err.to_string()),
+        }
+    } else {
+        serde_json::Map::new()
+    };
+    match value {
+        Some(value) => config.insert(key.to_string(), value),
+        None => config.remove(key),
+    };
+
+    // ensure the value is valid before saving it
+    let config = serde_json::Value::Object(config);
+    match serde_json::from_value::<UserConfig>(config.clone()) {
+        Ok(user_config) => {
+            user_config.get_max_download_speed()?;
+        }
+        Err(err) => return err!("Invalid value for '{}'. {}", key, err.to_string()),
+    }
+    environment.create_dir_all(&environment.get_user_config_dir())?;
+    environment.write_file_text(&file_path, &serde_json::to_string_pretty(&config)?)
+}
+
+/// Gets the directory that replaces a default directory from the environment variable or the user config.
+pub fn get_dir_override(
+    environment: &impl Environment,
+    env_var_name: &str,
+    get_config_dir: impl FnOnce(UserConfig) -> Option<String>,
+) -> Option<PathBuf> {
+    environment
+        .get_env_var(env_var_name)
+        .filter(|dir| !dir.trim().is_empty())
+        .or_else(|| read_user_config(environment).ok().and_then(get_config_dir))
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Gets the directory of the read-only bvm installation that's consulted before installing a binary.
+pub fn get_read_only_root(environment: &impl Environment) -> Option<PathBuf> {
+    get_dir_override(environment, "BVM_READ_ONLY_ROOT", |config| config.read_only_root)
+}
+
+fn get_user_config_file_path(environment: &impl Environment) -> PathBuf {
+    environment.get_user_config_dir().join("config.json")
+}
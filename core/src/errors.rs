@@ -0,0 +1,136 @@
+use dprint_cli_core::types::ErrBox;
+use std::fmt;
+
+use crate::environment::{DownloadCancelledError, DownloadNetworkError, DownloadStatusError};
+use crate::plugins::UnsupportedPlatformError;
+use crate::utils::ChecksumMismatchError;
+
+/// Creates an error with the provided code and a formatted message.
+#[macro_export]
+macro_rules! coded_err {
+    ($code:expr, $($arg:tt)*) => {
+        Err(Box::new($crate::errors::CodedError::new($code, format!($($arg)*))))
+    }
+}
+
+/// Kinds of failures that tools wrapping bvm may branch on. The codes of the kinds are stable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// A binary exists, but not with a version matching the selector.
+    VersionNotFound,
+    /// No binary with the name exists in the registries or in the installed binaries.
+    BinaryNotFound,
+    /// The binary or version must be installed first.
+    NotInstalled,
+    /// A download failed because of the connection.
+    Network,
+    /// A download was necessary, but bvm is offline.
+    Offline,
+    /// The server responded to a download with an unexpected status.
+    DownloadStatus,
+    /// A download didn't match its expected checksum.
+    ChecksumMismatch,
+    /// The binary doesn't support the current platform.
+    UnsupportedPlatform,
+    /// No configuration file was found for the current directory.
+    ConfigNotFound,
+    /// A configuration file or binary manifest file couldn't be read.
+    InvalidConfig,
+    /// A registry file couldn't be read or doesn't contain what was asked for.
+    Registry,
+    /// A download was cancelled with ctrl+c.
+    Cancelled,
+    /// Any other failure.
+    Unknown,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::VersionNotFound => "version-not-found",
+            ErrorCode::BinaryNotFound => "binary-not-found",
+            ErrorCode::NotInstalled => "not-installed",
+            ErrorCode::Network => "network",
+            ErrorCode::Offline => "offline",
+            ErrorCode::DownloadStatus => "download-status",
+            ErrorCode::ChecksumMismatch => "checksum-mismatch",
+            ErrorCode::UnsupportedPlatform => "unsupported-platform",
+            ErrorCode::ConfigNotFound => "config-not-found",
+            ErrorCode::InvalidConfig => "invalid-config",
+            ErrorCode::Registry => "registry",
+            ErrorCode::Cancelled => "cancelled",
+            ErrorCode::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error with a message and the code of its kind of failure.
+#[derive(Debug)]
+pub struct CodedError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl CodedError {
+    pub fn new(code: ErrorCode, message: String) -> CodedError {
+        CodedError { code, message }
+    }
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+/// Gets the code of the error's kind of failure. Use this when wrapping an error with more context
+/// so that the code of the original error is kept (ex. `coded_err!(get_error_code(&err), ...)`).
+pub fn get_error_code(err: &ErrBox) -> ErrorCode {
+    if let Some(err) = err.downcast_ref::<CodedError>() {
+        err.code
+    } else if err.is::<DownloadNetworkError>() {
+        ErrorCode::Network
+    } else if err.is::<DownloadStatusError>() {
+        ErrorCode::DownloadStatus
+    } else if err.is::<ChecksumMismatchError>() {
+        ErrorCode::ChecksumMismatch
+    } else if err.is::<UnsupportedPlatformError>() {
+        ErrorCode::UnsupportedPlatform
+    } else if err.is::<DownloadCancelledError>() {
+        ErrorCode::Cancelled
+    } else {
+        ErrorCode::Unknown
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_keep_code_of_wrapped_error() {
+        let err: ErrBox = Box::new(DownloadStatusError {
+            url: "https://localhost/file.zip".to_string(),
+            status: 404,
+        });
+        assert_eq!(get_error_code(&err), ErrorCode::DownloadStatus);
+        let result: Result<(), ErrBox> = coded_err!(get_error_code(&err), "Error installing binary. {}", err);
+        let err = result.err().unwrap();
+        assert_eq!(get_error_code(&err), ErrorCode::DownloadStatus);
+        assert_eq!(
+            err.to_string(),
+            "Error installing binary. Error downloading https://localhost/file.zip. Status: 404"
+        );
+
+        let err: ErrBox = Box::new(err_obj!("Other."));
+        assert_eq!(get_error_code(&err), ErrorCode::Unknown);
+    }
+}
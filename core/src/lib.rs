@@ -0,0 +1,25 @@
+//! The library behind bvm for resolving and installing binaries programmatically.
+//!
+//! - `errors` - Stable codes for the kinds of failures, which are kept when an error is wrapped.
+//! - `environment` - Abstracts the file system, network, and output. Use `RealEnvironment`, optionally
+//!   with an event handler to show a custom progress UI.
+//! - `configuration` - Reads and modifies the `bvm.json` (or `bvm.toml`) config files of projects.
+//! - `plugins` - Installs binaries and manages the manifest of installed binaries and global versions.
+//! - `registry` - Downloads registry files to resolve binary names to the urls of their versions.
+//! - `types` - Binary names, command names, versions, and selectors.
+
+#[macro_use(err_obj)]
+#[macro_use(err)]
+extern crate dprint_cli_core;
+#[macro_use]
+extern crate lazy_static;
+
+pub mod types;
+#[macro_use]
+pub mod errors;
+#[macro_use]
+pub mod environment;
+pub mod configuration;
+pub mod plugins;
+pub mod registry;
+pub mod utils;
@@ -0,0 +1,60 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::environment::{read_user_config, Environment};
+
+/// Gets if the files of binaries should be deduplicated in the content-addressed store.
+pub fn is_content_store_enabled(environment: &impl Environment) -> Result<bool, ErrBox> {
+    if let Some(value) = environment.get_env_var("BVM_CONTENT_STORE") {
+        return Ok(value == "1" || value.to_lowercase() == "true");
+    }
+    Ok(read_user_config(environment)?.content_store.unwrap_or(false))
+}
+
+fn get_store_dir(environment: &impl Environment) -> PathBuf {
+    environment.get_local_user_data_dir().join("store")
+}
+
+/// Moves the files of the directory into the store and hard links them back so that
+/// files with the same content are only stored once across binaries.
+pub fn link_dir_files_from_store(environment: &impl Environment, dir_path: &Path) -> Result<(), ErrBox> {
+    let store_dir = get_store_dir(environment);
+    environment.create_dir_all(&store_dir)?;
+    let executable_file_paths = environment
+        .get_executable_file_paths(dir_path)?
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    for file_path in environment.get_file_paths(dir_path)? {
+        let mut key = get_sha256_checksum(&environment.read_file(&file_path)?);
+        // hard links share permissions, so don't mix executables with other files
+        if executable_file_paths.contains(&file_path) {
+            key.push_str("-x");
+        }
+        let store_file_path = store_dir.join(key);
+        if environment.path_exists(&store_file_path) {
+            environment.remove_file(&file_path)?;
+        } else {
+            environment.rename(&file_path, &store_file_path)?;
+        }
+        environment.hard_link(&store_file_path, &file_path)?;
+    }
+
+    Ok(())
+}
+
+/// Removes the files in the store that are no longer linked to from any binary directory.
+pub fn remove_unused_store_files(environment: &impl Environment) -> Result<(), ErrBox> {
+    let file_paths = match environment.get_file_paths(&get_store_dir(environment)) {
+        Ok(file_paths) => file_paths,
+        Err(_) => return Ok(()), // the store doesn't exist
+    };
+    for file_path in file_paths {
+        if environment.get_hard_link_count(&file_path)? == Some(1) {
+            environment.remove_file(&file_path)?;
+        }
+    }
+    Ok(())
+}
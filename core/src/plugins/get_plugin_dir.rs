@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use crate::environment::{get_read_only_root, Environment};
+use crate::types::{BinaryName, Version};
+
+/// Gets the directory of a binary, which is in the read-only root when the binary is used from there.
+pub fn get_plugin_dir(environment: &impl Environment, binary_name: &BinaryName, version: &Version) -> PathBuf {
+    let plugin_dir = get_writable_plugin_dir(environment, binary_name, version);
+    if let Some(read_only_root) = get_read_only_root(environment) {
+        let read_only_plugin_dir = read_only_root.join(get_plugin_dir_relative_local_user_data(binary_name, version));
+        if !environment.is_dir(&plugin_dir) && environment.is_dir(&read_only_plugin_dir) {
+            return read_only_plugin_dir;
+        }
+    }
+    plugin_dir
+}
+
+/// Gets the directory a binary is installed in, which is never in the read-only root.
+pub fn get_writable_plugin_dir(environment: &impl Environment, binary_name: &BinaryName, version: &Version) -> PathBuf {
+    let local_data_dir = environment.get_local_user_data_dir(); // do not share across domains
+    local_data_dir.join(get_plugin_dir_relative_local_user_data(binary_name, version))
+}
+
+/// Gets the directory a binary is set up in before being moved to its plugin directory.
+pub fn get_plugin_staging_dir(environment: &impl Environment, binary_name: &BinaryName, version: &Version) -> PathBuf {
+    let local_data_dir = environment.get_local_user_data_dir();
+    local_data_dir
+        .join("staging")
+        .join(&binary_name.owner)
+        .join(binary_name.name.as_str())
+        .join(version.as_str())
+}
+
+pub fn get_plugin_dir_relative_local_user_data(binary_name: &BinaryName, version: &Version) -> PathBuf {
+    PathBuf::from("binaries")
+        .join(&binary_name.owner)
+        .join(binary_name.name.as_str())
+        .join(version.as_str())
+}
@@ -5,7 +5,8 @@ use std::path::PathBuf;
 
 use super::{get_plugin_dir, BinaryManifestItem, GlobalBinaryLocation, PluginsManifest};
 use crate::configuration::ConfigFileBinary;
-use crate::environment::{Environment, SYS_PATH_DELIMITER};
+use crate::environment::{read_user_config, set_user_config_value, Environment, SYS_PATH_DELIMITER};
+use crate::errors::ErrorCode;
 use crate::types::{CommandName, NameSelector, PathOrVersionSelector, VersionSelector};
 use crate::utils;
 
@@ -15,9 +16,15 @@ pub fn get_installed_binary_if_associated_config_file_binary<'a>(
 ) -> Option<&'a BinaryManifestItem> {
     // the url needs to be associated to an identifier for this to return anything
     if let Some(identifier) = manifest.get_identifier_from_url(&config_binary.path) {
-        // return the url version if installed
+        // return the url version if installed (when resolved from an alias or tag, only if it matches)
         if let Some(binary) = manifest.get_binary(&identifier) {
-            return Some(binary);
+            let is_version_match = match (&config_binary.version_alias, &config_binary.version) {
+                (Some(_), Some(version_selector)) => version_selector.matches(&binary.version),
+                _ => true,
+            };
+            if is_version_match {
+                return Some(binary);
+            }
         }
 
         // else check for the latest matching version in the manifest
@@ -39,7 +46,7 @@ pub fn get_latest_binary_matching_name_and_version<'a>(
     version_selector: &VersionSelector,
 ) -> Option<&'a BinaryManifestItem> {
     let binaries = manifest.get_binaries_matching_name_and_version(&name_selector, version_selector);
-    get_latest_binary(&binaries)
+    get_latest_binary(&binaries, version_selector.includes_prerelease())
 }
 
 pub fn get_binary_with_name_and_version<'a>(
@@ -52,24 +59,119 @@ pub fn get_binary_with_name_and_version<'a>(
     if binaries.len() == 0 {
         let binaries = plugin_manifest.get_binaries_matching_name(name_selector);
         if binaries.is_empty() {
-            err!("Could not find any installed binaries named '{}'", name_selector)
+            get_not_installed_error(plugin_manifest, name_selector)
         } else {
-            err!(
+            coded_err!(
+                ErrorCode::NotInstalled,
                 "Could not find binary '{}' that matched version '{}'\n\nInstalled versions:\n  {}",
                 name_selector,
                 version_selector,
                 display_binaries_versions(binaries).join("\n  "),
             )
         }
+    } else if name_selector.has_wildcard() && !binaries.iter().all(|b| b.name == binaries[0].name) {
+        let mut binaries = binaries;
+        binaries.sort();
+        err!(
+            "There were multiple binaries matching '{}' that matched version '{}'. Please specify a single binary.\n\nInstalled versions:\n  {}",
+            name_selector,
+            version_selector,
+            binaries
+                .iter()
+                .map(|b| format!("{} {}", b.name, b.version))
+                .collect::<Vec<_>>()
+                .join("\n  "),
+        )
     } else if !get_have_same_owner(&binaries) {
         return err!(
-            "There were multiple binaries with the specified name '{}' that matched version '{}'. Please include the owner to uninstall.\n\nInstalled versions:\n  {}",
+            "There were multiple binaries with the specified name '{}' that matched version '{}'. Please include the owner in the name.\n\nInstalled versions:\n  {}",
             name_selector,
             version_selector,
             display_binaries_versions(binaries).join("\n  "),
         );
     } else {
-        Ok(get_latest_binary(&binaries).unwrap())
+        Ok(get_latest_binary(&binaries, version_selector.includes_prerelease()).unwrap())
+    }
+}
+
+/// Fills in the owner of a name selector that matches the installed binaries of multiple owners. Uses the owner
+/// remembered for the name in the user config or asks the user to pick one, which is then remembered.
+/// The name selector is returned unchanged when it's not ambiguous or when not run interactively.
+pub fn resolve_name_selector_owner(
+    environment: &impl Environment,
+    plugin_manifest: &PluginsManifest,
+    name_selector: NameSelector,
+) -> Result<NameSelector, ErrBox> {
+    if name_selector.owner.is_some() || name_selector.has_wildcard() {
+        return Ok(name_selector);
+    }
+    let mut owners = plugin_manifest
+        .get_binaries_matching_name(&name_selector)
+        .into_iter()
+        .map(|b| b.name.owner.clone())
+        .collect::<Vec<_>>();
+    owners.sort();
+    owners.dedup();
+    if owners.len() < 2 {
+        return Ok(name_selector);
+    }
+
+    let user_config = read_user_config(environment)?;
+    let mut default_owners = user_config.default_owners.unwrap_or_default();
+    let default_owner = default_owners
+        .get(name_selector.name.as_str())
+        .filter(|owner| owners.contains(owner));
+    let owner = match default_owner {
+        Some(owner) => owner.clone(),
+        None => {
+            let message = format!(
+                "There were multiple binaries with the name '{}'. Which owner should be used?",
+                name_selector.name
+            );
+            let items = owners
+                .iter()
+                .map(|owner| format!("{}/{}", owner, name_selector.name))
+                .collect::<Vec<_>>();
+            let owner = match environment.select(&message, &items)? {
+                Some(index) => owners[index].clone(),
+                None => return Ok(name_selector),
+            };
+            default_owners.insert(name_selector.name.as_str().to_string(), owner.clone());
+            set_user_config_value(
+                environment,
+                "defaultOwners",
+                Some(serde_json::to_value(&default_owners)?),
+            )?;
+            environment.log_error(&format!(
+                "Saved '{}' as the owner to use for '{}'. Run `bvm config unset defaultOwners` to pick again.",
+                owner, name_selector.name
+            ));
+            owner
+        }
+    };
+    Ok(NameSelector {
+        owner: Some(owner),
+        name: name_selector.name,
+    })
+}
+
+/// Gets the error for when no installed binaries match the name selector, suggesting a close name if one exists.
+pub fn get_not_installed_error<T>(
+    plugin_manifest: &PluginsManifest,
+    name_selector: &NameSelector,
+) -> Result<T, ErrBox> {
+    match name_selector.get_closest_name(plugin_manifest.binaries().map(|b| &b.name)) {
+        Some(closest_name) => coded_err!(
+            ErrorCode::NotInstalled,
+            "Could not find any installed binaries named '{}'. Did you mean {}?",
+            name_selector,
+            closest_name
+        ),
+        None => coded_err!(
+            ErrorCode::NotInstalled,
+            "Could not find any installed binaries named '{}'",
+            name_selector
+        ),
     }
 }
 
@@ -104,7 +206,22 @@ pub fn get_have_same_owner(binaries: &Vec<&BinaryManifestItem>) -> bool {
     }
 }
 
-pub fn get_latest_binary<'a>(binaries: &Vec<&'a BinaryManifestItem>) -> Option<&'a BinaryManifestItem> {
+/// Gets the latest binary, preferring releases over pre-releases unless `include_prerelease` is true.
+pub fn get_latest_binary<'a>(
+    binaries: &Vec<&'a BinaryManifestItem>,
+    include_prerelease: bool,
+) -> Option<&'a BinaryManifestItem> {
+    if !include_prerelease {
+        let releases = binaries
+            .iter()
+            .filter(|b| !b.version.is_prerelease())
+            .copied()
+            .collect::<Vec<_>>();
+        if !releases.is_empty() {
+            return get_latest_binary(&releases, true);
+        }
+    }
+
     let mut latest_binary: Option<&'a BinaryManifestItem> = None;
 
     for binary in binaries.iter() {
@@ -134,6 +251,19 @@ pub fn get_global_binary_file_path(
                     err!("Binary '{}' is configured to use the executable on the path, but only the bvm version exists on the path. Run `bvm use {0} <some other version>` to select a version to run.", command_name)
                 }
             }
+            GlobalBinaryLocation::Bvm(identifier)
+                if plugin_manifest.is_binary_disabled(&identifier.get_binary_name()) =>
+            {
+                if let Some(path_executable_path) = utils::get_path_executable_path(environment, command_name) {
+                    Ok(path_executable_path)
+                } else {
+                    err!(
+                        "Could not find command '{}' on the path because {} is disabled. Run `bvm enable {1}` to use it again.",
+                        command_name,
+                        identifier.get_binary_name()
+                    )
+                }
+            }
             GlobalBinaryLocation::Bvm(identifier) => {
                 if let Some(item) = plugin_manifest.get_binary(&identifier) {
                     let command_exe_path = get_exec_binary_command_exe_path(environment, &item, command_name)
@@ -213,7 +343,11 @@ pub fn get_command_names_for_name_and_path_or_version_selector(
             {
                 Ok(latest_binary.get_command_names())
             } else {
-                return err!("Could not find any installed binaries named '{}'.", name_selector);
+                return coded_err!(
+                    ErrorCode::NotInstalled,
+                    "Could not find any installed binaries named '{}'.",
+                    name_selector
+                );
             }
         }
         PathOrVersionSelector::Version(version_selector) => {
@@ -0,0 +1,91 @@
+use super::get_plugin_dir;
+use crate::environment::{read_user_config, Environment};
+use crate::types::{BinaryName, CommandName, Version};
+use crate::utils;
+
+/// Events of bvm that the commands in the `hooks` of the user config run on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookEvent {
+    PostInstall,
+    PostUse,
+    PreUninstall,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::PostInstall => "post-install",
+            HookEvent::PostUse => "post-use",
+            HookEvent::PreUninstall => "pre-uninstall",
+        }
+    }
+}
+
+/// An event along with the binary it happened to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HookEventDetails {
+    pub event: HookEvent,
+    pub name: BinaryName,
+    pub version: Version,
+    /// Command that was switched to the binary for `post-use`.
+    pub command_name: Option<CommandName>,
+}
+
+/// Runs the user's hook commands of the event, which receive its details in `BVM_HOOK_*` environment
+/// variables and as JSON on stdin. A failing hook only logs a warning so that it can't break bvm.
+pub fn run_user_hooks(environment: &impl Environment, details: &HookEventDetails) {
+    let hooks = match read_user_config(environment) {
+        Ok(config) => config.hooks.unwrap_or_default(),
+        Err(err) => {
+            log_verbose!(environment, "Error reading the hooks. {}", err);
+            return;
+        }
+    };
+    let commands = match details.event {
+        HookEvent::PostInstall => hooks.post_install,
+        HookEvent::PostUse => hooks.post_use,
+        HookEvent::PreUninstall => hooks.pre_uninstall,
+    };
+    if commands.is_empty() {
+        return;
+    }
+
+    let dir_path = get_plugin_dir(environment, &details.name, &details.version);
+    let mut env_vars = vec![
+        ("BVM_HOOK_EVENT".to_string(), details.event.as_str().to_string()),
+        ("BVM_HOOK_OWNER".to_string(), details.name.owner.clone()),
+        ("BVM_HOOK_NAME".to_string(), details.name.name.as_str().to_string()),
+        ("BVM_HOOK_VERSION".to_string(), details.version.to_string()),
+        ("BVM_HOOK_DIR".to_string(), dir_path.to_string_lossy().to_string()),
+    ];
+    if let Some(command_name) = &details.command_name {
+        env_vars.push(("BVM_HOOK_COMMAND".to_string(), command_name.as_str().to_string()));
+    }
+    let input = serde_json::json!({
+        "event": details.event.as_str(),
+        "owner": details.name.owner,
+        "name": details.name.name.as_str(),
+        "version": details.version.to_string(),
+        "dir": dir_path.to_string_lossy(),
+        "command": details.command_name.as_ref().map(|name| name.as_str()),
+    })
+    .to_string();
+    let cwd = match environment.cwd() {
+        Ok(cwd) => cwd,
+        Err(_) => environment.get_user_config_dir(),
+    };
+
+    for command in commands.iter() {
+        log_verbose!(environment, "Running {} hook: {}", details.event.as_str(), command);
+        if let Err(err) = environment.run_shell_command_with_input(&cwd, command, &env_vars, input.as_bytes()) {
+            environment.log_error(&utils::yellow(&format!(
+                "Warning: The {} hook `{}` failed for {} {}. {}",
+                details.event.as_str(),
+                command,
+                details.name,
+                details.version,
+                err
+            )));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::environment::Environment;
+
+/// Name of the file in a binary's directory that stores the checksums of the binary's files.
+pub const INTEGRITY_MANIFEST_FILE_NAME: &str = ".bvm-integrity.json";
+
+#[derive(Serialize, Deserialize)]
+struct IntegrityManifest {
+    /// Checksums of the files keyed by their path relative to the binary's directory.
+    files: BTreeMap<String, String>,
+}
+
+/// The files of a binary's directory that no longer match its integrity manifest.
+#[derive(Debug, Default, PartialEq)]
+pub struct IntegrityReport {
+    pub modified: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_valid(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Records the checksums of the files in the directory of a set up binary.
+pub fn write_integrity_manifest(environment: &impl Environment, dir_path: &Path) -> Result<(), ErrBox> {
+    let manifest = IntegrityManifest {
+        files: get_file_checksums(environment, dir_path)?,
+    };
+    environment.write_file_text(
+        &dir_path.join(INTEGRITY_MANIFEST_FILE_NAME),
+        &serde_json::to_string_pretty(&manifest)?,
+    )
+}
+
+/// Re-hashes the files in the directory of a binary and compares them to its integrity manifest.
+/// Returns `None` when the binary was installed without one (ex. by an older version of bvm).
+pub fn verify_integrity_manifest(
+    environment: &impl Environment,
+    dir_path: &Path,
+) -> Result<Option<IntegrityReport>, ErrBox> {
+    let file_path = dir_path.join(INTEGRITY_MANIFEST_FILE_NAME);
+    if !environment.path_exists(&file_path) {
+        return Ok(None);
+    }
+    let manifest = match serde_json::from_str::<IntegrityManifest>(&environment.read_file_text(&file_path)?) {
+        Ok(manifest) => manifest,
+        Err(err) => return err!("Error deserializing {}. {}", file_path.display(), err.to_string()),
+    };
+
+    let checksums = get_file_checksums(environment, dir_path)?;
+    let mut report = IntegrityReport::default();
+    for (path, checksum) in manifest.files {
+        match checksums.get(&path) {
+            Some(actual_checksum) if *actual_checksum == checksum => {}
+            Some(_) => report.modified.push(path),
+            None => report.missing.push(path),
+        }
+    }
+    Ok(Some(report))
+}
+
+fn get_file_checksums(environment: &impl Environment, dir_path: &Path) -> Result<BTreeMap<String, String>, ErrBox> {
+    let mut checksums = BTreeMap::new();
+    for file_path in environment.get_file_paths(dir_path)? {
+        let relative_path = file_path.strip_prefix(dir_path)?;
+        if relative_path == Path::new(INTEGRITY_MANIFEST_FILE_NAME) {
+            continue;
+        }
+        // use the same separator on every system so the manifest is portable
+        let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+        checksums.insert(relative_path, get_sha256_checksum(&environment.read_file(&file_path)?));
+    }
+    Ok(checksums)
+}
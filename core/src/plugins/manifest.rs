@@ -0,0 +1,1058 @@
+use dprint_cli_core::checksums::ChecksumPathOrUrl;
+use dprint_cli_core::types::ErrBox;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ord, Ordering, PartialOrd};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use crate::environment::{get_read_only_root, Environment, Event};
+use crate::plugins::{get_plugin_dir, BinaryEnvironment};
+use crate::types::{get_name_comparison_key, BinaryName, CommandName, NameSelector, Version, VersionSelector};
+
+const PATH_GLOBAL_VERSION_VALUE: &'static str = "path";
+const IDENTIFIER_GLOBAL_PREFIX: &'static str = "identifier:";
+const MANIFEST_SCHEMA_VERSION: u32 = 2;
+const MANIFEST_FILE_NAME: &str = "binaries-manifest.json";
+
+/// The plugins manifest.
+///
+/// It's stored as a small index file along with a file per binary. The files of the binaries
+/// are only read once a binary's details are needed, so resolving a command stays fast when
+/// there are many installed binaries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluginsManifest {
+    // Key is url.
+    pub(super) urls_to_identifier: HashMap<String, BinaryIdentifier>,
+    pub(super) global_versions: GlobalVersionsMap,
+    binaries: HashMap<BinaryIdentifier, BinaryManifestEntry>,
+    /// Changes to the environment that need to be made.
+    pub(super) pending_env_changes: PendingEnvironmentChanges,
+    /// User defined version aliases for binaries (ex. "stable" -> "1.40.2").
+    pub(super) aliases: HashMap<BinaryName, HashMap<String, Version>>,
+    /// The versions registry tags resolved to when last installed (ex. "latest" -> "1.40.2").
+    pub(super) registry_tags: HashMap<BinaryName, HashMap<String, Version>>,
+    /// Binaries disabled by `bvm disable`, whose commands resolve to the executables on the path instead.
+    pub(super) disabled_binaries: HashSet<BinaryName>,
+    /// Commands added by `bvm use --suffix` that run a specific version of a binary (ex. "deno1.39").
+    pub(super) suffixed_commands: HashMap<CommandName, SuffixedCommand>,
+    /// Toolchains installed from a registry and the binaries they installed.
+    pub(super) toolchains: HashMap<BinaryIdentifier, Vec<BinaryIdentifier>>,
+    /// Binaries used from the read-only root, which aren't written to the user's manifest.
+    read_only_binaries: HashSet<BinaryIdentifier>,
+    /// Binaries whose files need to be written or removed when saving.
+    changed_binaries: HashSet<BinaryIdentifier>,
+    /// Whether the index changed since it was last written.
+    is_dirty: bool,
+    /// Number of transactions that have begun without being committed.
+    transaction_depth: usize,
+    binary_reader: BinaryFileReader,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedManifestIndex {
+    schema_version: u32,
+    urls_to_identifier: HashMap<String, BinaryIdentifier>,
+    global_versions: GlobalVersionsMap,
+    binaries: HashMap<BinaryIdentifier, BinaryIndexItem>,
+    pending_env_changes: PendingEnvironmentChanges,
+    #[serde(default)]
+    aliases: HashMap<BinaryName, HashMap<String, Version>>,
+    #[serde(default)]
+    registry_tags: HashMap<BinaryName, HashMap<String, Version>>,
+    #[serde(default)]
+    disabled_binaries: HashSet<BinaryName>,
+    #[serde(default)]
+    suffixed_commands: HashMap<CommandName, SuffixedCommand>,
+    #[serde(default)]
+    toolchains: HashMap<BinaryIdentifier, Vec<BinaryIdentifier>>,
+}
+
+/// The manifest from before the binaries were stored in separate files.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyPluginsManifest {
+    urls_to_identifier: HashMap<String, BinaryIdentifier>,
+    global_versions: GlobalVersionsMap,
+    binaries: HashMap<BinaryIdentifier, BinaryManifestItem>,
+    pending_env_changes: PendingEnvironmentChanges,
+    #[serde(default)]
+    aliases: HashMap<BinaryName, HashMap<String, Version>>,
+    #[serde(default)]
+    registry_tags: HashMap<BinaryName, HashMap<String, Version>>,
+}
+
+/// What's stored about a binary in the index.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct BinaryIndexItem {
+    name: BinaryName,
+    version: Version,
+    commands: Vec<CommandName>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct BinaryManifestEntry {
+    index: BinaryIndexItem,
+    /// The binary's details, which are read from its file on first use.
+    item: OnceLock<BinaryManifestItem>,
+}
+
+impl BinaryManifestEntry {
+    fn from_item(item: BinaryManifestItem) -> Self {
+        BinaryManifestEntry {
+            index: BinaryIndexItem {
+                name: item.name.clone(),
+                version: item.version.clone(),
+                commands: item.get_command_names(),
+            },
+            item: OnceLock::from(item),
+        }
+    }
+
+    fn is_match(&self, name_selector: &NameSelector) -> bool {
+        name_selector.is_match(&self.index.name)
+    }
+
+    fn has_command(&self, name: &CommandName) -> bool {
+        self.index.commands.contains(name)
+    }
+
+    fn cmp(&self, other: &BinaryManifestEntry) -> Ordering {
+        self.index
+            .name
+            .cmp(&other.index.name)
+            .then_with(|| self.index.version.partial_cmp(&other.index.version).unwrap())
+    }
+}
+
+type ReadBinaryFile = dyn Fn(&BinaryIdentifier) -> Result<BinaryManifestItem, ErrBox> + Send + Sync;
+
+/// Reads the file of a binary in the manifest.
+#[derive(Clone)]
+struct BinaryFileReader(Arc<ReadBinaryFile>);
+
+impl fmt::Debug for BinaryFileReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BinaryFileReader")
+    }
+}
+
+impl PartialEq for BinaryFileReader {
+    fn eq(&self, _: &BinaryFileReader) -> bool {
+        true
+    }
+}
+
+/// A command of a specific version of a binary exposed under a version-suffixed name.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuffixedCommand {
+    pub identifier: BinaryIdentifier,
+    /// Name of the binary's command that's run.
+    pub command_name: CommandName,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct PendingEnvironmentChanges {
+    added: HashSet<BinaryIdentifier>,
+    removed: HashSet<BinaryIdentifier>,
+}
+
+impl PendingEnvironmentChanges {
+    pub fn mark_for_adding(&mut self, identifier: BinaryIdentifier) {
+        // don't bother removing from self.removed as it allows
+        // figuring out the reverse in get-post-env-changes
+        self.added.insert(identifier);
+    }
+
+    pub fn mark_for_removal(&mut self, identifier: BinaryIdentifier) {
+        self.added.remove(&identifier);
+        self.removed.insert(identifier);
+    }
+
+    pub fn any(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.added.clear();
+        self.removed.clear();
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryManifestItemSource {
+    pub path: String,
+    pub checksum: String,
+}
+
+/// Where an installed binary came from, which is recorded so the executables on a machine can be audited.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryProvenance {
+    /// Url of the archive or package that was installed, which is the mirror it was downloaded from on fallback.
+    pub url: String,
+    /// Checksum of the archive, which is empty for packages installed by a package manager.
+    pub checksum: String,
+    /// Url of the registry that lists the binary manifest file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// Version of bvm that installed the binary.
+    pub bvm_version: String,
+}
+
+/// An archive that was downloaded and verified, but will be extracted the first time one of its commands is run.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingExtraction {
+    /// Download type of the archive (ex. `zip`).
+    pub download_type: String,
+    /// Directory relative to the binary's directory to extract the archive into.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryManifestItem {
+    pub name: BinaryName,
+    pub version: Version,
+    /// Created time in *seconds* since epoch.
+    pub created_time: u64,
+    pub commands: Vec<BinaryManifestItemCommand>,
+    // Source for reinstalling.
+    pub source: BinaryManifestItemSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<BinaryEnvironment>,
+    /// Platform key of the target that was installed (ex. `windows-x86_64`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Whether the binary is a local build registered with `bvm link`, which has absolute command paths.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub linked: bool,
+    /// Command from the binary manifest file to run in the binary's directory before it's uninstalled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_pre_uninstall: Option<String>,
+    /// Archive that still needs to be extracted when the binary was installed with lazy extraction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_extraction: Option<PendingExtraction>,
+    /// Where the binary came from, which isn't recorded for linked binaries or ones installed by older versions of bvm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<BinaryProvenance>,
+}
+
+impl BinaryManifestItem {
+    pub fn get_identifier(&self) -> BinaryIdentifier {
+        BinaryIdentifier::new(&self.name, &self.version)
+    }
+
+    pub fn get_command_names(&self) -> Vec<CommandName> {
+        // todo: return iterator somehow?
+        self.commands.iter().map(|c| c.name.clone()).collect()
+    }
+
+    pub fn get_env_paths(&self) -> Vec<String> {
+        self.environment
+            .as_ref()
+            .and_then(|e| e.paths.as_ref())
+            .map(|p| p.clone())
+            .unwrap_or(Vec::new())
+    }
+
+    pub fn get_resolved_env_paths(&self, environment: &impl Environment) -> Vec<PathBuf> {
+        let bin_dir = get_plugin_dir(environment, &self.name, &self.version);
+        self.get_env_paths()
+            .into_iter()
+            .map(|path| {
+                let resolved_value = PathBuf::from(get_resolved_env_value(&bin_dir, path));
+                if resolved_value.is_relative() {
+                    bin_dir.join(resolved_value)
+                } else {
+                    resolved_value
+                }
+            })
+            .collect()
+    }
+
+    pub fn get_env_variables(&self) -> HashMap<String, String> {
+        self.environment
+            .as_ref()
+            .and_then(|e| e.variables.as_ref())
+            .map(|p| p.clone())
+            .unwrap_or(HashMap::new())
+    }
+}
+
+impl PartialOrd for BinaryManifestItem {
+    fn partial_cmp(&self, other: &BinaryManifestItem) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BinaryManifestItem {
+    fn cmp(&self, other: &BinaryManifestItem) -> Ordering {
+        let name_ordering = self.name.cmp(&other.name);
+        match name_ordering {
+            Ordering::Equal => self.version.partial_cmp(&other.version).unwrap(),
+            _ => name_ordering,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryManifestItemCommand {
+    pub name: CommandName,
+    /// The relative path to the file name.
+    pub path: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryIdentifier(String);
+
+impl BinaryIdentifier {
+    pub fn new(name: &BinaryName, version: &Version) -> Self {
+        BinaryIdentifier(format!("{}||{}||{}", name.owner, name.name.as_str(), version))
+    }
+
+    pub fn get_binary_name(&self) -> BinaryName {
+        let parts = self.0.split("||").collect::<Vec<_>>();
+        BinaryName::new(parts[0].to_string(), parts[1].to_string())
+    }
+
+    pub fn get_version(&self) -> Version {
+        let parts = self.0.split("||").collect::<Vec<_>>();
+        Version::parse(parts[2]).unwrap()
+    }
+}
+
+#[derive(Clone)]
+pub enum GlobalBinaryLocation {
+    /// Use a bvm binary.
+    Bvm(BinaryIdentifier),
+    /// Use the binary on the path.
+    Path,
+}
+
+impl GlobalBinaryLocation {
+    pub fn to_identifier_option(&self) -> Option<BinaryIdentifier> {
+        if let GlobalBinaryLocation::Bvm(identifier) = self {
+            Some(identifier.clone())
+        } else {
+            None
+        }
+    }
+}
+
+impl From<BinaryIdentifier> for GlobalBinaryLocation {
+    fn from(identifier: BinaryIdentifier) -> Self {
+        GlobalBinaryLocation::Bvm(identifier)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Default)]
+pub(super) struct GlobalVersionsMap(HashMap<String, String>);
+
+impl GlobalVersionsMap {
+    pub(super) fn set(&mut self, command_name: CommandName, location: GlobalBinaryLocation) {
+        self.remove(&command_name);
+        self.0.insert(
+            command_name.into_string(),
+            match location {
+                GlobalBinaryLocation::Path => PATH_GLOBAL_VERSION_VALUE.to_string(),
+                GlobalBinaryLocation::Bvm(identifier) => format!("{}{}", IDENTIFIER_GLOBAL_PREFIX, identifier.0),
+            },
+        );
+    }
+
+    pub(super) fn get(&self, command_name: &CommandName) -> Option<GlobalBinaryLocation> {
+        self.get_key(command_name)
+            .and_then(|key| self.0.get(key))
+            .map(|value| self.value_to_location(&value))
+    }
+
+    pub(super) fn remove(&mut self, command_name: &CommandName) {
+        if let Some(key) = self.get_key(command_name).map(String::from) {
+            self.0.remove(&key);
+        }
+    }
+
+    /// Gets the key the command name is stored by, which may differ in case when names are case insensitive.
+    fn get_key(&self, command_name: &CommandName) -> Option<&str> {
+        if let Some((key, _)) = self.0.get_key_value(command_name.as_str()) {
+            return Some(key.as_str());
+        }
+        let comparison_key = get_name_comparison_key(command_name.as_str());
+        self.0
+            .keys()
+            .find(|key| get_name_comparison_key(key) == comparison_key)
+            .map(|key| key.as_str())
+    }
+
+    pub(super) fn get_entries(&self) -> Vec<(CommandName, GlobalBinaryLocation)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (CommandName::from_string(key.clone()), self.value_to_location(value)))
+            .collect()
+    }
+
+    pub(super) fn get_locations(&self) -> Vec<GlobalBinaryLocation> {
+        self.0.iter().map(|(_, value)| self.value_to_location(value)).collect()
+    }
+
+    fn value_to_location(&self, value: &str) -> GlobalBinaryLocation {
+        if value == PATH_GLOBAL_VERSION_VALUE {
+            GlobalBinaryLocation::Path
+        } else if value.starts_with(IDENTIFIER_GLOBAL_PREFIX) {
+            GlobalBinaryLocation::Bvm(BinaryIdentifier(value[IDENTIFIER_GLOBAL_PREFIX.len()..].to_string()))
+        } else {
+            // todo: don't panic and improve this
+            panic!("Unknown value: {}", value);
+        }
+    }
+}
+
+impl PluginsManifest {
+    fn new(binary_reader: BinaryFileReader) -> PluginsManifest {
+        PluginsManifest {
+            global_versions: GlobalVersionsMap(HashMap::new()),
+            binaries: HashMap::new(),
+            urls_to_identifier: HashMap::new(),
+            pending_env_changes: PendingEnvironmentChanges {
+                added: HashSet::new(),
+                removed: HashSet::new(),
+            },
+            aliases: HashMap::new(),
+            registry_tags: HashMap::new(),
+            disabled_binaries: HashSet::new(),
+            suffixed_commands: HashMap::new(),
+            toolchains: HashMap::new(),
+            read_only_binaries: HashSet::new(),
+            changed_binaries: HashSet::new(),
+            is_dirty: false,
+            transaction_depth: 0,
+            binary_reader,
+        }
+    }
+
+    pub fn load<TEnvironment: Environment>(environment: &TEnvironment) -> PluginsManifest {
+        // Don't take the manifest lock here. Some people might run
+        // "bvm util is-installed owner/name" while another process holds
+        // the lock, so only writers (PluginsMut::load) lock.
+        let binary_reader = {
+            let environment = environment.clone();
+            BinaryFileReader(Arc::new(move |identifier| read_binary_file(&environment, identifier)))
+        };
+        let mut manifest = PluginsManifest::new(binary_reader);
+        let file_path = get_manifest_file_path(environment);
+        if let Ok(text) = environment.read_file_text(&file_path) {
+            manifest.read_index_text(environment, &text);
+        }
+        if let Some(read_only_root) = get_read_only_root(environment) {
+            manifest.add_read_only_binaries(environment, &read_only_root);
+        }
+        manifest
+    }
+
+    fn read_index_text(&mut self, environment: &impl Environment, text: &str) {
+        match serde_json::from_str::<SerializedManifestIndex>(text) {
+            Ok(index) => {
+                self.urls_to_identifier = index.urls_to_identifier;
+                self.global_versions = index.global_versions;
+                self.pending_env_changes = index.pending_env_changes;
+                self.aliases = index.aliases;
+                self.registry_tags = index.registry_tags;
+                self.disabled_binaries = index.disabled_binaries;
+                self.suffixed_commands = index.suffixed_commands;
+                self.toolchains = index.toolchains;
+                self.binaries = index
+                    .binaries
+                    .into_iter()
+                    .map(|(identifier, index)| {
+                        let entry = BinaryManifestEntry {
+                            index,
+                            item: OnceLock::new(),
+                        };
+                        (identifier, entry)
+                    })
+                    .collect();
+            }
+            // upgrade a manifest that has the binaries inline, which will be written to their own files on the next save
+            Err(err) => match serde_json::from_str::<LegacyPluginsManifest>(text) {
+                Ok(legacy) => {
+                    self.urls_to_identifier = legacy.urls_to_identifier;
+                    self.global_versions = legacy.global_versions;
+                    self.pending_env_changes = legacy.pending_env_changes;
+                    self.aliases = legacy.aliases;
+                    self.registry_tags = legacy.registry_tags;
+                    self.is_dirty = true;
+                    for (identifier, item) in legacy.binaries.into_iter() {
+                        self.changed_binaries.insert(identifier.clone());
+                        self.binaries.insert(identifier, BinaryManifestEntry::from_item(item));
+                    }
+                }
+                Err(_) => environment.log_error(&format!("Error deserializing plugins manifest: {}", err)),
+            },
+        }
+    }
+
+    /// Adds the binaries of the read-only root that aren't installed in the user data directory.
+    fn add_read_only_binaries(&mut self, environment: &impl Environment, read_only_root: &Path) {
+        let file_path = read_only_root.join(MANIFEST_FILE_NAME);
+        let index = match environment.read_file_text(&file_path) {
+            Ok(text) => match serde_json::from_str::<SerializedManifestIndex>(&text) {
+                Ok(index) => index,
+                Err(err) => {
+                    environment.log_error(&format!("Error deserializing {}: {}", file_path.display(), err));
+                    return;
+                }
+            },
+            Err(_) => return,
+        };
+        for (identifier, index) in index.binaries.into_iter() {
+            if !self.binaries.contains_key(&identifier) {
+                self.read_only_binaries.insert(identifier.clone());
+                let entry = BinaryManifestEntry {
+                    index,
+                    item: OnceLock::new(),
+                };
+                self.binaries.insert(identifier, entry);
+            }
+        }
+        for (url, identifier) in index.urls_to_identifier.into_iter() {
+            self.urls_to_identifier.entry(url).or_insert(identifier);
+        }
+    }
+
+    // transactions
+
+    /// Begins a transaction, which defers writing the manifest until it's committed so that
+    /// many changes are written at once. Transactions may be nested.
+    pub fn begin(&mut self) {
+        self.transaction_depth += 1;
+    }
+
+    /// Commits the current transaction, returning true when it was the outermost one and
+    /// the changes should now be written.
+    pub fn commit(&mut self) -> bool {
+        if self.transaction_depth == 0 {
+            panic!("Internal error: Cannot commit when no transaction has begun.");
+        }
+        self.transaction_depth -= 1;
+        self.transaction_depth == 0
+    }
+
+    pub fn is_in_transaction(&self) -> bool {
+        self.transaction_depth > 0
+    }
+
+    /// Gets if there are changes that haven't been written.
+    pub fn is_dirty(&self) -> bool {
+        self.is_dirty || !self.changed_binaries.is_empty()
+    }
+
+    /// Marks the index as changed so that it's written on the next save.
+    pub(super) fn mark_dirty(&mut self) {
+        self.is_dirty = true;
+    }
+
+    /// Writes the index along with the files of any binaries that changed.
+    pub(super) fn write<TEnvironment: Environment>(&mut self, environment: &TEnvironment) -> Result<(), ErrBox> {
+        if !self.is_dirty() {
+            return Ok(());
+        }
+
+        // write the binary files before the index so that readers never find a binary without its file
+        // the changed binaries are only cleared once the index is written so that a failure is retried on the next save
+        let mut removed_binaries = Vec::new();
+        for identifier in self.changed_binaries.iter() {
+            let file_path = get_binary_file_path(environment, identifier);
+            match self.binaries.get(identifier).and_then(|entry| entry.item.get()) {
+                Some(item) => {
+                    environment.create_dir_all(file_path.parent().unwrap())?;
+                    environment.write_file_text(&file_path, &serde_json::to_string(item)?)?;
+                }
+                None => {
+                    removed_binaries.push(get_last_used_file_path(&file_path));
+                    removed_binaries.push(file_path);
+                }
+            }
+        }
+
+        let index = SerializedManifestIndex {
+            schema_version: MANIFEST_SCHEMA_VERSION,
+            urls_to_identifier: self.urls_to_identifier.clone(),
+            global_versions: self.global_versions.clone(),
+            binaries: self
+                .binaries
+                .iter()
+                .filter(|(identifier, _)| !self.read_only_binaries.contains(identifier))
+                .map(|(identifier, entry)| (identifier.clone(), entry.index.clone()))
+                .collect(),
+            pending_env_changes: self.pending_env_changes.clone(),
+            aliases: self.aliases.clone(),
+            registry_tags: self.registry_tags.clone(),
+            disabled_binaries: self.disabled_binaries.clone(),
+            suffixed_commands: self.suffixed_commands.clone(),
+            toolchains: self.toolchains.clone(),
+        };
+        environment.write_file_text(&get_manifest_file_path(environment), &serde_json::to_string(&index)?)?;
+        self.is_dirty = false;
+        self.changed_binaries.clear();
+
+        for file_path in removed_binaries {
+            let _ = environment.remove_file(&file_path);
+        }
+        environment.emit_event(Event::ManifestUpdated);
+        Ok(())
+    }
+
+    // url to identifier
+
+    pub fn get_identifier_from_url(&self, url: &ChecksumPathOrUrl) -> Option<&BinaryIdentifier> {
+        self.urls_to_identifier.get(&url.path_or_url)
+    }
+
+    // pending environment changes
+
+    pub fn get_relative_pending_added_paths(&self, environment: &impl Environment) -> Vec<String> {
+        self.get_change_paths(environment, self.get_pending_added_identifiers())
+    }
+
+    pub fn get_relative_pending_removed_paths(&self, environment: &impl Environment) -> Vec<String> {
+        self.get_change_paths(environment, self.pending_env_changes.removed.iter())
+    }
+
+    fn get_change_paths<'a>(
+        &self,
+        environment: &impl Environment,
+        changes: impl Iterator<Item = &'a BinaryIdentifier>,
+    ) -> Vec<String> {
+        let mut result = Vec::new();
+        for identifier in changes {
+            result.extend(self.get_binary_env_paths(environment, &identifier));
+        }
+        result
+    }
+
+    fn get_binary_env_paths(&self, environment: &impl Environment, identifier: &BinaryIdentifier) -> Vec<String> {
+        if let Some(binary) = self.get_binary(&identifier) {
+            binary
+                .get_resolved_env_paths(environment)
+                .into_iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn get_pending_added_env_variables(&self, environment: &impl Environment) -> HashMap<String, String> {
+        self.get_change_variables(environment, self.get_pending_added_identifiers())
+    }
+
+    pub fn get_pending_removed_env_variables(&self, environment: &impl Environment) -> HashMap<String, String> {
+        self.get_change_variables(environment, self.pending_env_changes.removed.iter())
+    }
+
+    /// Gets the binaries to add to the environment, excluding disabled binaries that were selected as the global version.
+    fn get_pending_added_identifiers(&self) -> impl Iterator<Item = &BinaryIdentifier> {
+        self.pending_env_changes
+            .added
+            .iter()
+            .filter(move |identifier| !self.is_binary_disabled(&identifier.get_binary_name()))
+    }
+
+    fn get_change_variables<'a>(
+        &self,
+        environment: &impl Environment,
+        changes: impl Iterator<Item = &'a BinaryIdentifier>,
+    ) -> HashMap<String, String> {
+        let mut result = HashMap::new();
+        for identifier in changes {
+            result.extend(self.get_binary_env_vars(environment, &identifier));
+        }
+        result
+    }
+
+    pub fn get_binary_env_vars(
+        &self,
+        environment: &impl Environment,
+        identifier: &BinaryIdentifier,
+    ) -> HashMap<String, String> {
+        if let Some(binary) = self.get_binary(&identifier) {
+            let bin_dir = get_plugin_dir(environment, &binary.name, &binary.version);
+            binary
+                .get_env_variables()
+                .into_iter()
+                .map(|(key, value)| (key, get_resolved_env_value(&bin_dir, value)))
+                .collect()
+        } else {
+            HashMap::new()
+        }
+    }
+
+    // binary environment paths
+
+    pub fn get_env_paths(&self, environment: &impl Environment) -> Vec<String> {
+        let mut result = Vec::new();
+        for identifier in self.get_enabled_global_identifiers() {
+            result.extend(self.get_binary_env_paths(environment, &identifier));
+        }
+        result
+    }
+
+    pub fn get_env_vars(&self, environment: &impl Environment) -> HashMap<String, String> {
+        let mut result = HashMap::new();
+        for identifier in self.get_enabled_global_identifiers() {
+            result.extend(self.get_binary_env_vars(environment, &identifier));
+        }
+        result
+    }
+
+    fn get_enabled_global_identifiers(&self) -> Vec<BinaryIdentifier> {
+        self.global_versions
+            .get_locations()
+            .into_iter()
+            .filter_map(|location| location.to_identifier_option())
+            .filter(|identifier| !self.is_binary_disabled(&identifier.get_binary_name()))
+            .collect()
+    }
+
+    // binary
+
+    pub fn get_binary(&self, identifier: &BinaryIdentifier) -> Option<&BinaryManifestItem> {
+        let entry = self.binaries.get(identifier)?;
+        if let Some(item) = entry.item.get() {
+            return Some(item);
+        }
+        match (self.binary_reader.0)(identifier) {
+            Ok(item) => Some(entry.item.get_or_init(|| item)),
+            // treat the binary as not installed when its file is missing or corrupt
+            Err(_) => None,
+        }
+    }
+
+    /// Gets if the binary is installed, which like `get_binary` is false when its file is missing or corrupt.
+    pub fn has_binary(&self, identifier: &BinaryIdentifier) -> bool {
+        self.get_binary(identifier).is_some()
+    }
+
+    /// Gets all the binaries, which reads the file of every binary.
+    pub fn binaries(&self) -> impl Iterator<Item = &BinaryManifestItem> {
+        self.binaries
+            .keys()
+            .filter_map(move |identifier| self.get_binary(identifier))
+    }
+
+    pub(super) fn insert_binary(&mut self, item: BinaryManifestItem) {
+        let identifier = item.get_identifier();
+        self.read_only_binaries.remove(&identifier);
+        self.is_dirty = true;
+        self.changed_binaries.insert(identifier.clone());
+        self.binaries.insert(identifier, BinaryManifestEntry::from_item(item));
+    }
+
+    pub(super) fn remove_binary(&mut self, identifier: &BinaryIdentifier) {
+        if self.binaries.remove(identifier).is_some() {
+            self.is_dirty = true;
+            self.changed_binaries.insert(identifier.clone());
+        }
+    }
+
+    /// Gets if the binary is used from the read-only root instead of being installed in the user data directory.
+    pub fn is_read_only_binary(&self, identifier: &BinaryIdentifier) -> bool {
+        self.read_only_binaries.contains(identifier)
+    }
+
+    pub fn has_binary_with_command(&self, name: &CommandName) -> bool {
+        self.binaries.values().any(|entry| entry.has_command(name))
+    }
+
+    pub fn binary_name_has_same_owner(&self, binary_name: &BinaryName) -> bool {
+        let mut owners = self
+            .binaries
+            .values()
+            .filter(|entry| entry.index.name.name == binary_name.name)
+            .map(|entry| &entry.index.name.owner);
+        match owners.next() {
+            Some(first_owner) => owners.all(|owner| owner == first_owner),
+            None => true,
+        }
+    }
+
+    pub fn get_binaries_matching_name(&self, name_selector: &NameSelector) -> Vec<&BinaryManifestItem> {
+        self.get_binaries_for_entries(|entry| entry.is_match(name_selector))
+    }
+
+    pub fn get_binaries_matching_name_and_version(
+        &self,
+        name_selector: &NameSelector,
+        version: &VersionSelector,
+    ) -> Vec<&BinaryManifestItem> {
+        self.get_binaries_for_entries(|entry| entry.is_match(name_selector) && version.matches(&entry.index.version))
+    }
+
+    pub fn get_binaries_with_command(&self, name: &CommandName) -> Vec<&BinaryManifestItem> {
+        self.get_binaries_for_entries(|entry| entry.has_command(name))
+    }
+
+    pub fn get_latest_binary_with_name(&self, name: &BinaryName) -> Option<&BinaryManifestItem> {
+        self.get_latest_binary_for_entries(|entry| &entry.index.name == name)
+    }
+
+    pub fn get_latest_binary_with_command(&self, name: &CommandName) -> Option<&BinaryManifestItem> {
+        self.get_latest_binary_for_entries(|entry| entry.has_command(name))
+    }
+
+    fn get_binaries_for_entries(&self, is_match: impl Fn(&BinaryManifestEntry) -> bool) -> Vec<&BinaryManifestItem> {
+        self.binaries
+            .iter()
+            .filter(|(_, entry)| is_match(entry))
+            .filter_map(|(identifier, _)| self.get_binary(identifier))
+            .collect()
+    }
+
+    fn get_latest_binary_for_entries(
+        &self,
+        is_match: impl Fn(&BinaryManifestEntry) -> bool,
+    ) -> Option<&BinaryManifestItem> {
+        let (identifier, _) = self
+            .binaries
+            .iter()
+            .filter(|(_, entry)| is_match(entry))
+            .max_by(|(_, a), (_, b)| a.cmp(b))?;
+        self.get_binary(identifier)
+    }
+
+    pub fn get_global_binary_location(&self, command_name: &CommandName) -> Option<GlobalBinaryLocation> {
+        self.global_versions.get(command_name)
+    }
+
+    pub fn is_global_version(&self, identifier: &BinaryIdentifier, command_name: &CommandName) -> bool {
+        if let Some(GlobalBinaryLocation::Bvm(global_version_identifier)) = self.global_versions.get(command_name) {
+            &global_version_identifier == identifier
+        } else {
+            false
+        }
+    }
+
+    pub fn has_any_global_command(&self, identifier: &BinaryIdentifier) -> bool {
+        if let Some(entry) = self.binaries.get(identifier) {
+            for command_name in entry.index.commands.iter() {
+                if self.is_global_version(identifier, command_name) {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Gets the commands whose global version is a binary that isn't in the manifest.
+    pub fn get_missing_global_binaries(&self) -> Vec<(CommandName, BinaryIdentifier)> {
+        let mut result = self
+            .global_versions
+            .get_entries()
+            .into_iter()
+            .filter_map(|(command_name, location)| match location {
+                GlobalBinaryLocation::Bvm(identifier) if !self.has_binary(&identifier) => {
+                    Some((command_name, identifier))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        result.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        result
+    }
+
+    pub fn has_environment_changes(&self, identifier: &BinaryIdentifier) -> bool {
+        if let Some(binary) = self.get_binary(identifier) {
+            !binary.get_env_paths().is_empty() || !binary.get_env_variables().is_empty()
+        } else {
+            false
+        }
+    }
+
+    pub fn get_global_command_names(&self, identifier: &BinaryIdentifier) -> Vec<CommandName> {
+        let mut result = Vec::new();
+        if let Some(entry) = self.binaries.get(identifier) {
+            for command_name in entry.index.commands.iter() {
+                if self.is_global_version(identifier, command_name) {
+                    result.push(command_name.clone());
+                }
+            }
+        }
+        result
+    }
+
+    // aliases
+
+    pub fn get_alias_version(&self, name: &BinaryName, alias: &str) -> Option<&Version> {
+        self.aliases.get(name).and_then(|aliases| aliases.get(alias))
+    }
+
+    /// Gets all the aliases sorted by binary name then alias.
+    pub fn get_aliases(&self) -> Vec<(&BinaryName, &String, &Version)> {
+        let mut result = Vec::new();
+        for (name, aliases) in self.aliases.iter() {
+            for (alias, version) in aliases.iter() {
+                result.push((name, alias, version));
+            }
+        }
+        result.sort_by(|a, b| a.0.cmp(b.0).then(a.1.cmp(b.1)));
+        result
+    }
+
+    pub fn get_registry_tag_version(&self, name: &BinaryName, tag: &str) -> Option<&Version> {
+        self.registry_tags.get(name).and_then(|tags| tags.get(tag))
+    }
+
+    // toolchains
+
+    /// Gets the binaries installed by the toolchain.
+    pub fn get_toolchain_binaries(&self, identifier: &BinaryIdentifier) -> Option<&Vec<BinaryIdentifier>> {
+        self.toolchains.get(identifier)
+    }
+
+    /// Gets if the binary was installed by one of the installed toolchains.
+    pub fn is_toolchain_binary(&self, identifier: &BinaryIdentifier) -> bool {
+        self.toolchains.values().any(|binaries| binaries.contains(identifier))
+    }
+
+    /// Gets the installed toolchains matching the name selector sorted by name and version.
+    pub fn get_toolchains_matching_name(&self, name_selector: &NameSelector) -> Vec<BinaryIdentifier> {
+        let mut identifiers = self
+            .toolchains
+            .keys()
+            .filter(|identifier| name_selector.is_match(&identifier.get_binary_name()))
+            .cloned()
+            .collect::<Vec<_>>();
+        identifiers.sort_by(|a, b| {
+            a.get_binary_name()
+                .cmp(&b.get_binary_name())
+                .then(a.get_version().cmp(&b.get_version()))
+        });
+        identifiers
+    }
+
+    // suffixed commands
+
+    pub fn get_suffixed_command(&self, command_name: &CommandName) -> Option<&SuffixedCommand> {
+        self.suffixed_commands.get(command_name)
+    }
+
+    /// Gets the version-suffixed command names that run the binary sorted by name.
+    pub fn get_suffixed_command_names(&self, identifier: &BinaryIdentifier) -> Vec<CommandName> {
+        let mut command_names = self
+            .suffixed_commands
+            .iter()
+            .filter(|(_, suffixed_command)| &suffixed_command.identifier == identifier)
+            .map(|(command_name, _)| command_name.clone())
+            .collect::<Vec<_>>();
+        command_names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        command_names
+    }
+
+    // disabled binaries
+
+    /// Gets if the commands of the binary were disabled by `bvm disable`.
+    pub fn is_binary_disabled(&self, name: &BinaryName) -> bool {
+        self.disabled_binaries.contains(name)
+    }
+
+    /// Gets the names of all the commands that have shims, including the version-suffixed ones.
+    pub fn get_all_command_names(&self) -> HashSet<CommandName> {
+        let mut command_names = HashSet::new();
+        for entry in self.binaries.values() {
+            command_names.extend(entry.index.commands.iter().cloned());
+        }
+        command_names.extend(self.suffixed_commands.keys().cloned());
+        command_names
+    }
+}
+
+/// Number of seconds a recorded last used time is kept before it's updated, so that running a binary
+/// only writes a file occasionally.
+const LAST_USED_RESOLUTION_SECS: u64 = 60 * 60;
+
+/// Records that the binary was run. Errors are ignored so they never prevent running the binary.
+pub fn record_binary_used(environment: &impl Environment, identifier: &BinaryIdentifier) {
+    let file_path = get_last_used_file_path(&get_binary_file_path(environment, identifier));
+    let current_time = environment.get_time_secs();
+    if let Some(last_used_time) = read_last_used_time(environment, &file_path) {
+        if current_time < last_used_time.saturating_add(LAST_USED_RESOLUTION_SECS) {
+            return;
+        }
+    }
+    let _ignore = environment.write_file_text(&file_path, &current_time.to_string());
+}
+
+/// Gets the time in seconds since epoch the binary was last run through bvm or when it was
+/// installed if it was never run.
+pub fn get_binary_last_used_time(environment: &impl Environment, binary: &BinaryManifestItem) -> u64 {
+    let file_path = get_last_used_file_path(&get_binary_file_path(environment, &binary.get_identifier()));
+    read_last_used_time(environment, &file_path).unwrap_or(binary.created_time)
+}
+
+fn read_last_used_time(environment: &impl Environment, file_path: &Path) -> Option<u64> {
+    environment
+        .read_file_text(file_path)
+        .ok()
+        .and_then(|text| text.trim().parse::<u64>().ok())
+}
+
+fn get_last_used_file_path(binary_file_path: &Path) -> PathBuf {
+    binary_file_path.with_extension("last-used")
+}
+
+pub(super) fn get_manifest_file_path(environment: &impl Environment) -> PathBuf {
+    let user_data_dir = environment.get_user_data_dir(); // share across domains
+    user_data_dir.join(MANIFEST_FILE_NAME)
+}
+
+fn get_binary_file_path(environment: &impl Environment, identifier: &BinaryIdentifier) -> PathBuf {
+    get_binary_file_path_in_dir(&environment.get_user_data_dir(), identifier)
+}
+
+fn get_binary_file_path_in_dir(data_dir: &Path, identifier: &BinaryIdentifier) -> PathBuf {
+    let binary_name = identifier.get_binary_name();
+    data_dir
+        .join("binaries-manifest")
+        .join(&binary_name.owner)
+        .join(binary_name.name.as_str())
+        .join(format!("{}.json", identifier.get_version()))
+}
+
+fn read_binary_file(
+    environment: &impl Environment,
+    identifier: &BinaryIdentifier,
+) -> Result<BinaryManifestItem, ErrBox> {
+    let file_path = get_binary_file_path(environment, identifier);
+    let file_text = match environment.read_file_text(&file_path) {
+        Ok(file_text) => file_text,
+        // otherwise the binary may be used from the read-only root
+        Err(err) => match get_read_only_root(environment) {
+            Some(read_only_root) => {
+                environment.read_file_text(&get_binary_file_path_in_dir(&read_only_root, identifier))?
+            }
+            None => return Err(err),
+        },
+    };
+    Ok(serde_json::from_str(&file_text)?)
+}
+
+fn get_resolved_env_value(bin_dir: &PathBuf, text: String) -> String {
+    if cfg!(target_os = "windows") {
+        text.replace("%BVM_CURRENT_BINARY_DIR%", &bin_dir.to_string_lossy())
+    } else {
+        // todo: make this resilient
+        text.replace("$BVM_CURRENT_BINARY_DIR", &bin_dir.to_string_lossy())
+    }
+}
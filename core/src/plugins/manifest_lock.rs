@@ -0,0 +1,43 @@
+use dprint_cli_core::types::ErrBox;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::environment::Environment;
+use crate::utils::LockFile;
+
+const RETRY_INTERVAL_MS: u64 = 100;
+const MAX_WAIT_MS: u64 = 5 * 60 * 1000;
+
+/// Prevents multiple bvm processes from modifying the plugins manifest at the
+/// same time by holding a lock file until dropped.
+pub(super) struct ManifestLock<TEnvironment: Environment> {
+    _lock_file: LockFile<TEnvironment>,
+}
+
+impl<TEnvironment: Environment> ManifestLock<TEnvironment> {
+    pub fn acquire(environment: &TEnvironment) -> Result<Self, ErrBox> {
+        let file_path = get_manifest_lock_file_path(environment);
+        let mut waited_ms = 0;
+
+        loop {
+            if let Some(lock_file) = LockFile::try_create(environment, &file_path, true)? {
+                return Ok(ManifestLock { _lock_file: lock_file });
+            }
+            if waited_ms == 0 {
+                environment.log_error("Waiting for another bvm process to finish...");
+            } else if waited_ms >= MAX_WAIT_MS {
+                return err!(
+                    "Timed out waiting for another bvm process to finish. If no other bvm process is running, delete the lock file at {}",
+                    file_path.display()
+                );
+            }
+            environment.sleep(Duration::from_millis(RETRY_INTERVAL_MS));
+            waited_ms += RETRY_INTERVAL_MS;
+        }
+    }
+}
+
+pub(super) fn get_manifest_lock_file_path(environment: &impl Environment) -> PathBuf {
+    let user_data_dir = environment.get_user_data_dir();
+    user_data_dir.join("binaries-manifest.json.lock")
+}
@@ -0,0 +1,39 @@
+mod content_store;
+mod get_plugin_dir;
+pub mod helpers;
+mod hooks;
+mod integrity_manifest;
+mod manifest;
+mod manifest_lock;
+mod plugins_mut;
+mod project_state;
+mod serialized_plugin_file;
+mod setup;
+mod update_check;
+
+pub use content_store::*;
+pub use get_plugin_dir::*;
+pub use hooks::*;
+pub use integrity_manifest::*;
+pub use manifest::*;
+pub use plugins_mut::*;
+pub use project_state::*;
+pub use serialized_plugin_file::*;
+pub use setup::create_shim;
+pub use setup::get_archive_plugin_file;
+pub use setup::get_github_release;
+pub use setup::get_install_target;
+pub use setup::get_plugin_file_cache;
+pub use setup::is_cargo_crate_url;
+pub use setup::is_github_release_url;
+pub use setup::is_npm_package_url;
+pub use setup::is_shims_only_enabled;
+pub use setup::pack_plugin_file;
+pub use setup::read_pack_spec;
+pub use setup::remove_compacted_files;
+pub use setup::resolve_plugin_file_url;
+pub use setup::validate_plugin_file;
+pub use setup::verify_trusted_host;
+pub use setup::GitHubReleaseSpecifier;
+pub use setup::UnsupportedPlatformError;
+pub use update_check::*;
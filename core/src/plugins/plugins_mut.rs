@@ -0,0 +1,886 @@
+use dprint_cli_core::checksums::ChecksumPathOrUrl;
+use dprint_cli_core::types::ErrBox;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+
+use super::helpers;
+use super::manifest_lock::ManifestLock;
+use super::setup::{
+    compact_binary_dir, create_shim, discard_staged_plugin, extract_pending_archive, get_install_target,
+    get_plugin_file, get_shim_paths, install_staged_plugin, is_shims_only_enabled, setup_plugin, stage_plugin,
+    PluginFile, StagedPlugin,
+};
+use super::{
+    run_user_hooks, BinaryIdentifier, BinaryManifestItem, GlobalBinaryLocation, HookEvent, HookEventDetails,
+    PluginsManifest,
+};
+use crate::configuration::ConfigFileBinary;
+use crate::environment::{ensure_system_privileges, Environment, Event};
+use crate::types::{BinaryName, CommandName, Version, VersionSelector};
+use crate::utils;
+
+pub enum UrlInstallAction {
+    None,
+    Install(Box<PluginFile>),
+}
+
+/// Used to make changes to the plugins manifest.
+pub struct PluginsMut<TEnvironment: Environment> {
+    environment: TEnvironment,
+    pub manifest: PluginsManifest,
+    allow_write: bool,
+    target: Option<String>,
+    require_checksums: Option<bool>,
+    lazy_extraction: Option<bool>,
+    /// Plugin files downloaded ahead of installing them by `prepare_plugins`. Key is the url and checksum.
+    prepared_plugin_files: HashMap<(String, Option<String>), PluginFile>,
+    /// Binaries staged ahead of installing them by `prepare_plugins`. Key is the url and checksum of the plugin file.
+    prepared_binaries: HashMap<(String, String), Result<StagedPlugin, ErrBox>>,
+    /// Events to run the user's hooks of once the manifest is saved.
+    pending_hook_events: Vec<HookEventDetails>,
+    /// Events of the saved changes to run the user's hooks of once the lock is released.
+    saved_hook_events: Vec<HookEventDetails>,
+    lock: Option<ManifestLock<TEnvironment>>,
+}
+
+/// Maximum number of binaries that are set up at the same time by `setup_plugins`.
+const MAX_SETUP_PLUGIN_THREADS: usize = 8;
+
+/// Plugin file to download ahead of installing it along with the version selector it's installed with.
+type PreparePluginJob = ((String, Option<String>), ChecksumPathOrUrl, Option<VersionSelector>);
+/// Downloaded plugin file along with the result of staging its binary when it wasn't installed.
+type PreparePluginResult = (
+    (String, Option<String>),
+    PluginFile,
+    Option<Result<StagedPlugin, ErrBox>>,
+);
+
+impl<TEnvironment: Environment> PluginsMut<TEnvironment> {
+    /// Loads the manifest while holding a lock so that other processes can't modify it until this is dropped.
+    pub fn load(environment: &TEnvironment) -> Result<Self, ErrBox> {
+        ensure_system_privileges(environment)?;
+        let lock = ManifestLock::acquire(environment)?;
+        Ok(PluginsMut {
+            environment: environment.clone(),
+            manifest: PluginsManifest::load(environment),
+            allow_write: true,
+            target: None,
+            require_checksums: None,
+            lazy_extraction: None,
+            prepared_plugin_files: HashMap::new(),
+            prepared_binaries: HashMap::new(),
+            pending_hook_events: Vec::new(),
+            saved_hook_events: Vec::new(),
+            lock: Some(lock),
+        })
+    }
+
+    /// Loads the manifest without a lock for reading it or making changes that are never saved.
+    pub fn load_disallow_write(environment: &TEnvironment) -> Self {
+        PluginsMut::from_manifest_disallow_write(environment, PluginsManifest::load(environment))
+    }
+
+    pub fn from_manifest_disallow_write(environment: &TEnvironment, manifest: PluginsManifest) -> Self {
+        PluginsMut {
+            environment: environment.clone(),
+            manifest,
+            allow_write: false,
+            target: None,
+            require_checksums: None,
+            lazy_extraction: None,
+            prepared_plugin_files: HashMap::new(),
+            prepared_binaries: HashMap::new(),
+            pending_hook_events: Vec::new(),
+            saved_hook_events: Vec::new(),
+            lock: None,
+        }
+    }
+
+    // general
+
+    /// Sets the target to install binaries for instead of the one detected from the current system.
+    pub fn set_target(&mut self, target: Option<String>) {
+        self.target = target;
+    }
+
+    /// Gets the target binaries are installed for.
+    pub fn get_target(&self) -> Result<&'static str, ErrBox> {
+        get_install_target(&self.environment, self.target.as_deref())
+    }
+
+    /// Sets whether archives must be verified by a provided checksum, which they also must be when the user config requires it.
+    pub fn set_require_checksums(&mut self, require_checksums: Option<bool>) {
+        self.require_checksums = require_checksums;
+    }
+
+    /// Sets whether archives are only extracted the first time one of their commands is run, overriding the user config.
+    pub fn set_lazy_extraction(&mut self, lazy_extraction: Option<bool>) {
+        self.lazy_extraction = lazy_extraction;
+    }
+
+    /// Extracts the archive of a binary that was installed with lazy extraction and records that it was extracted.
+    pub fn extract_pending_binary(&mut self, identifier: &BinaryIdentifier) -> Result<(), ErrBox> {
+        // another process may have extracted it while waiting for the lock
+        let mut item = match self.manifest.get_binary(identifier) {
+            Some(item) if item.pending_extraction.is_some() => item.clone(),
+            _ => return Ok(()),
+        };
+        extract_pending_archive(&self.environment, &item)?;
+        item.pending_extraction = None;
+        self.manifest.insert_binary(item);
+        self.save()
+    }
+
+    /// Archives the files of the binary to be extracted again the next time one of its commands is run. The
+    /// extracted files should be removed with `remove_compacted_files` once the manifest is saved.
+    pub fn compact_binary(&mut self, identifier: &BinaryIdentifier) -> Result<(), ErrBox> {
+        let mut item = match self.manifest.get_binary(identifier) {
+            Some(item) => item.clone(),
+            None => {
+                return err!(
+                    "Could not find binary: {} {}",
+                    identifier.get_binary_name(),
+                    identifier.get_version()
+                )
+            }
+        };
+        item.pending_extraction = Some(compact_binary_dir(&self.environment, &item)?);
+        self.manifest.insert_binary(item);
+        Ok(())
+    }
+
+    /// Gets the path of what's downloaded to install the binary of the plugin file for the target.
+    pub fn get_download_path(&self, plugin_file: &PluginFile) -> Result<String, ErrBox> {
+        plugin_file.get_download_path(&self.environment, self.target.as_deref())
+    }
+
+    /// Downloads and sets up the binary of the plugin file then adds it to the manifest.
+    pub fn setup_plugin<'a>(&'a mut self, plugin_file: &PluginFile) -> Result<&'a BinaryManifestItem, ErrBox> {
+        let prepared_key = (plugin_file.url.clone(), plugin_file.checksum.clone());
+        let item = match self.prepared_binaries.remove(&prepared_key) {
+            Some(result) => install_staged_plugin(&self.environment, plugin_file, result?)?,
+            None => setup_plugin(
+                &self.environment,
+                plugin_file,
+                self.target.as_deref(),
+                self.require_checksums,
+                self.lazy_extraction,
+            )?,
+        };
+        self.add_hook_event(HookEvent::PostInstall, &item.get_identifier(), None);
+        let identifier = self.replace_binary(item)?;
+        Ok(self.manifest.get_binary(&identifier).unwrap())
+    }
+
+    /// Sets up the plugins in parallel returning the result for each plugin file. The plugin files
+    /// should be of different binaries since binaries are staged in the same directory.
+    pub fn setup_plugins(&mut self, plugin_files: Vec<PluginFile>) -> Vec<Result<BinaryIdentifier, ErrBox>> {
+        let environment = self.environment.clone();
+        let target = self.target.clone();
+        let require_checksums = self.require_checksums;
+        let lazy_extraction = self.lazy_extraction;
+        let results = run_jobs_on_threads(plugin_files, MAX_SETUP_PLUGIN_THREADS, move |plugin_file| {
+            setup_plugin(
+                &environment,
+                &plugin_file,
+                target.as_deref(),
+                require_checksums,
+                lazy_extraction,
+            )
+        });
+
+        results
+            .into_iter()
+            .map(|result| {
+                let item = result.and_then(|result| result)?;
+                self.add_hook_event(HookEvent::PostInstall, &item.get_identifier(), None);
+                self.replace_binary(item)
+            })
+            .collect()
+    }
+
+    /// Downloads the plugin files of the urls and stages the binaries that aren't installed using up to the
+    /// provided number of threads, so that installing the urls afterwards doesn't wait on each download in turn.
+    ///
+    /// Each url is only downloaded once. Failures are ignored here and surface when the url is installed. The
+    /// binaries are only moved to their plugin directory and have their post install command run once they're
+    /// installed with `setup_plugin`, so ones that end up not being installed are removed by `discard_prepared_plugins`
+    /// without anything having run for them.
+    pub fn prepare_plugins(&mut self, urls: Vec<(ChecksumPathOrUrl, Option<VersionSelector>)>, concurrency: usize) {
+        let mut jobs = VecDeque::new();
+        for (url, version_selector) in urls.into_iter() {
+            let key = (url.path_or_url.clone(), url.checksum.clone());
+            let is_installed = self
+                .manifest
+                .get_identifier_from_url(&url)
+                .map(|identifier| self.manifest.has_binary(identifier))
+                .unwrap_or(false);
+            if !is_installed && !jobs.iter().any(|(job_key, _, _): &PreparePluginJob| job_key == &key) {
+                jobs.push_back((key, url, version_selector));
+            }
+        }
+        if jobs.is_empty() {
+            return;
+        }
+
+        let installed_identifiers = self
+            .manifest
+            .binaries()
+            .map(|binary| binary.get_identifier())
+            .collect::<HashSet<_>>();
+        // identifiers being staged, so that urls of the same binary aren't staged at the same time
+        let claimed_identifiers = Mutex::new(HashSet::new());
+        let environment = self.environment.clone();
+        let target = self.target.clone();
+        let require_checksums = self.require_checksums;
+        let lazy_extraction = self.lazy_extraction;
+        let results = run_jobs_on_threads(jobs.into(), concurrency, move |(key, url, version_selector)| {
+            let plugin_file = match get_plugin_file(&environment, &url) {
+                Ok(plugin_file) => plugin_file,
+                Err(err) => {
+                    log_verbose!(environment, "Error preparing {}. {}", url.path_or_url, err.to_string());
+                    return None;
+                }
+            };
+            // only stage what installing the url would, which is checked again when installing
+            let identifier = plugin_file.get_identifier();
+            let should_stage = !installed_identifiers.contains(&identifier)
+                && match &version_selector {
+                    Some(version_selector) => {
+                        version_selector.matches(&identifier.get_version())
+                            && !installed_identifiers.iter().any(|installed| {
+                                installed.get_binary_name() == identifier.get_binary_name()
+                                    && version_selector.matches(&installed.get_version())
+                            })
+                    }
+                    None => true,
+                }
+                && claimed_identifiers.lock().unwrap().insert(identifier);
+            let stage_result = if should_stage {
+                Some(stage_plugin(
+                    &environment,
+                    &plugin_file,
+                    target.as_deref(),
+                    require_checksums,
+                    lazy_extraction,
+                ))
+            } else {
+                None
+            };
+            let result: PreparePluginResult = (key, plugin_file, stage_result);
+            Some(result)
+        });
+
+        // failed jobs are ignored like failed downloads and surface when the url is installed
+        for (key, plugin_file, stage_result) in results.into_iter().flatten().flatten() {
+            if let Some(stage_result) = stage_result {
+                self.prepared_binaries
+                    .insert((plugin_file.url.clone(), plugin_file.checksum.clone()), stage_result);
+            }
+            self.prepared_plugin_files.insert(key, plugin_file);
+        }
+    }
+
+    /// Removes the binaries staged by `prepare_plugins` that weren't installed, such as when installing
+    /// another binary failed before getting to them.
+    pub fn discard_prepared_plugins(&mut self) -> Result<(), ErrBox> {
+        self.prepared_plugin_files.clear();
+        for (_, result) in self.prepared_binaries.drain() {
+            if let Ok(staged_plugin) = result {
+                discard_staged_plugin(&self.environment, staged_plugin);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a binary registered with `bvm link` to the manifest, replacing any previously linked binary with the same identifier.
+    pub fn link_binary(&mut self, item: BinaryManifestItem) -> Result<BinaryIdentifier, ErrBox> {
+        self.environment
+            .create_dir_all(&utils::get_shim_dir(&self.environment))?;
+        self.replace_binary(item)
+    }
+
+    /// Adds the set up binary to the manifest, replacing any previously installed
+    /// binary with the same identifier while keeping its global commands.
+    fn replace_binary(&mut self, item: BinaryManifestItem) -> Result<BinaryIdentifier, ErrBox> {
+        let identifier = item.get_identifier();
+        let command_names = item.get_command_names();
+        let previous_global_command_names = self.manifest.get_global_command_names(&identifier);
+        // the files of a binary in the read-only root stay there
+        if self.manifest.has_binary(&identifier) && !self.manifest.is_read_only_binary(&identifier) {
+            self.remove_binary(&identifier)?;
+        }
+
+        self.manifest.insert_binary(item);
+
+        for command_name in previous_global_command_names {
+            if command_names.contains(&command_name) {
+                self.use_global_version(command_name, GlobalBinaryLocation::Bvm(identifier.clone()))?;
+            }
+        }
+        // removing the previous binary may have deleted the shims
+        for command_name in command_names.iter() {
+            create_shim(&self.environment, command_name)?;
+        }
+
+        Ok(identifier)
+    }
+
+    /// Gets whether the binary of the url needs to be installed, providing its plugin file when it does.
+    pub fn get_url_install_action(
+        &mut self,
+        checksum_url: &ChecksumPathOrUrl,
+        version_selector: Option<&VersionSelector>,
+        force_install: bool,
+    ) -> Result<UrlInstallAction, ErrBox> {
+        // always install the url version for force
+        if force_install {
+            return Ok(UrlInstallAction::Install(Box::new(
+                self.get_and_associate_plugin_file(checksum_url)?,
+            )));
+        }
+
+        // check the cache for if the identifier is saved
+        let identifier = self.manifest.get_identifier_from_url(&checksum_url);
+
+        // use the exact version if installed
+        if let Some(identifier) = identifier.cloned() {
+            if self.manifest.has_binary(&identifier) {
+                self.error_if_identifier_not_matches_version_selector(&identifier, &version_selector)?;
+                self.set_up_read_only_binary(&identifier)?;
+                return Ok(UrlInstallAction::None);
+            }
+        }
+
+        let plugin_file = self.get_and_associate_plugin_file(checksum_url)?;
+        let identifier = plugin_file.get_identifier();
+
+        self.error_if_identifier_not_matches_version_selector(&identifier, &version_selector)?;
+
+        // check again if it's installed after associating the plugin file to an identifier
+        if self.manifest.has_binary(&identifier) {
+            self.set_up_read_only_binary(&identifier)?;
+            return Ok(UrlInstallAction::None);
+        }
+
+        // check if a version is installed that matches the provided version, if so use that
+        if let Some(version_selector) = &version_selector {
+            let name_selector = identifier.get_binary_name().to_selector();
+            let binary =
+                helpers::get_latest_binary_matching_name_and_version(&self.manifest, &name_selector, version_selector);
+            if let Some(binary) = binary {
+                let identifier = binary.get_identifier();
+                self.set_up_read_only_binary(&identifier)?;
+                return Ok(UrlInstallAction::None);
+            }
+        }
+
+        // install the specified url's plugin file
+        Ok(UrlInstallAction::Install(Box::new(plugin_file)))
+    }
+
+    /// Creates the shims of a binary used from the read-only root and uses its commands when nothing else
+    /// is used for them, which is what installing it would do.
+    fn set_up_read_only_binary(&mut self, identifier: &BinaryIdentifier) -> Result<(), ErrBox> {
+        if !self.allow_write || !self.manifest.is_read_only_binary(identifier) {
+            return Ok(());
+        }
+        let command_names = match self.manifest.get_binary(identifier) {
+            Some(item) => item.get_command_names(),
+            None => return Ok(()),
+        };
+        self.environment
+            .create_dir_all(&utils::get_shim_dir(&self.environment))?;
+        for command_name in command_names.iter() {
+            create_shim(&self.environment, command_name)?;
+            self.set_global_binary_if_not_set(identifier, command_name)?;
+        }
+        self.save()
+    }
+
+    fn error_if_identifier_not_matches_version_selector(
+        &self,
+        identifier: &BinaryIdentifier,
+        version_selector: &Option<&VersionSelector>,
+    ) -> Result<(), ErrBox> {
+        if let Some(version_selector) = version_selector {
+            let version = identifier.get_version();
+            if !version_selector.matches(&version) {
+                return err!("The specified version '{}' did not match '{}' in the path file. Please specify a different path or update the version.", version_selector, version);
+            }
+        }
+        Ok(())
+    }
+
+    fn get_and_associate_plugin_file(&mut self, checksum_url: &ChecksumPathOrUrl) -> Result<PluginFile, ErrBox> {
+        let prepared_key = (checksum_url.path_or_url.clone(), checksum_url.checksum.clone());
+        let plugin_file = match self.prepared_plugin_files.remove(&prepared_key) {
+            Some(plugin_file) => plugin_file,
+            None => get_plugin_file(&self.environment, checksum_url)?,
+        };
+        // associate the url to the binary identifier
+        let identifier = plugin_file.get_identifier();
+        self.set_identifier_for_url(&checksum_url, identifier);
+        if self.allow_write {
+            self.save()?; // todo: remove?
+        }
+        Ok(plugin_file)
+    }
+
+    /// Uses the binary as the global version of the command when no global version is set,
+    /// returning true when the binary is the global version.
+    pub fn set_global_binary_if_not_set(
+        &mut self,
+        identifier: &BinaryIdentifier,
+        command_name: &CommandName,
+    ) -> Result<bool, ErrBox> {
+        Ok(if self.manifest.get_global_binary_location(&command_name).is_none() {
+            if utils::get_path_executable_path(&self.environment, &command_name).is_some() {
+                self.use_global_version(command_name.clone(), GlobalBinaryLocation::Path)?;
+                false
+            } else {
+                self.use_global_version(command_name.clone(), GlobalBinaryLocation::Bvm(identifier.clone()))?;
+                true
+            }
+        } else {
+            self.manifest.is_global_version(identifier, command_name)
+        })
+    }
+
+    pub fn get_installed_binary_for_config_binary(
+        &mut self,
+        config_binary: &ConfigFileBinary,
+    ) -> Result<Option<&BinaryManifestItem>, ErrBox> {
+        // associate the url to an identifier in order to be able to tell the name
+        self.ensure_url_associated(&config_binary.path)?;
+
+        // now get the binary item based on the config file
+        Ok(helpers::get_installed_binary_if_associated_config_file_binary(
+            &self.manifest,
+            &config_binary,
+        ))
+    }
+
+    pub fn ensure_url_associated(&mut self, url: &ChecksumPathOrUrl) -> Result<(), ErrBox> {
+        // associate the url to an identifier in order to be able to tell the name
+        if self.manifest.get_identifier_from_url(&url).is_none() {
+            self.get_and_associate_plugin_file(&url)?;
+        }
+        Ok(())
+    }
+
+    // pending environment changes
+
+    pub fn set_identifier_for_url(&mut self, url: &ChecksumPathOrUrl, identifier: BinaryIdentifier) {
+        let previous_identifier = self
+            .manifest
+            .urls_to_identifier
+            .insert(url.path_or_url.clone(), identifier.clone());
+        if previous_identifier.as_ref() != Some(&identifier) {
+            self.manifest.mark_dirty();
+        }
+    }
+
+    // aliases
+
+    pub fn set_alias(&mut self, name: BinaryName, alias: String, version: Version) {
+        self.manifest.aliases.entry(name).or_default().insert(alias, version);
+        self.manifest.mark_dirty();
+    }
+
+    /// Removes the alias returning true if it existed.
+    pub fn remove_alias(&mut self, name: &BinaryName, alias: &str) -> bool {
+        let mut removed = false;
+        if let Some(aliases) = self.manifest.aliases.get_mut(name) {
+            removed = aliases.remove(alias).is_some();
+            if aliases.is_empty() {
+                self.manifest.aliases.remove(name);
+            }
+        }
+        if removed {
+            self.manifest.mark_dirty();
+        }
+        removed
+    }
+
+    pub fn set_registry_tag(&mut self, name: BinaryName, tag: String, version: Version) {
+        let previous_version = self
+            .manifest
+            .registry_tags
+            .entry(name)
+            .or_default()
+            .insert(tag, version.clone());
+        if previous_version.as_ref() != Some(&version) {
+            self.manifest.mark_dirty();
+        }
+    }
+
+    // toolchains
+
+    /// Records the binaries installed by the toolchain, replacing what was recorded for it.
+    pub fn set_toolchain(&mut self, identifier: BinaryIdentifier, binaries: Vec<BinaryIdentifier>) {
+        self.manifest.toolchains.insert(identifier, binaries);
+        self.manifest.mark_dirty();
+    }
+
+    /// Removes the record of the toolchain, returning the binaries it installed.
+    pub fn remove_toolchain(&mut self, identifier: &BinaryIdentifier) -> Option<Vec<BinaryIdentifier>> {
+        let binaries = self.manifest.toolchains.remove(identifier);
+        if binaries.is_some() {
+            self.manifest.mark_dirty();
+        }
+        binaries
+    }
+
+    // suffixed commands
+
+    /// Exposes the command of the binary under the version-suffixed name, creating its shim.
+    pub fn add_suffixed_command(
+        &mut self,
+        suffixed_name: CommandName,
+        identifier: BinaryIdentifier,
+        command_name: CommandName,
+    ) -> Result<(), ErrBox> {
+        if self.manifest.has_binary_with_command(&suffixed_name) {
+            return err!(
+                "Could not add command '{}' because an installed binary has a command with the same name.",
+                suffixed_name
+            );
+        }
+        create_shim(&self.environment, &suffixed_name)?;
+        let suffixed_command = super::SuffixedCommand {
+            identifier,
+            command_name,
+        };
+        self.manifest.suffixed_commands.insert(suffixed_name, suffixed_command);
+        self.manifest.mark_dirty();
+        Ok(())
+    }
+
+    // disabled binaries
+
+    /// Disables or enables the commands of the binary, returning false when it already was.
+    pub fn set_binary_disabled(&mut self, name: &BinaryName, disabled: bool) -> bool {
+        let changed = if disabled {
+            self.manifest.disabled_binaries.insert(name.clone())
+        } else {
+            self.manifest.disabled_binaries.remove(name)
+        };
+        if changed {
+            // update the environment of the shell for the globally used versions of the binary
+            let identifiers = self
+                .manifest
+                .binaries()
+                .filter(|binary| &binary.name == name)
+                .map(|binary| binary.get_identifier())
+                .filter(|identifier| {
+                    self.manifest.has_any_global_command(identifier)
+                        && self.manifest.has_environment_changes(identifier)
+                })
+                .collect::<Vec<_>>();
+            for identifier in identifiers {
+                if disabled {
+                    self.manifest.pending_env_changes.mark_for_removal(identifier);
+                } else {
+                    self.manifest.pending_env_changes.mark_for_adding(identifier);
+                }
+            }
+            self.manifest.mark_dirty();
+        }
+        changed
+    }
+
+    pub fn clear_cached_urls(&mut self) {
+        self.manifest.urls_to_identifier.clear();
+        self.manifest.mark_dirty();
+    }
+
+    pub fn clear_pending_env_changes(&mut self) {
+        if self.manifest.pending_env_changes.any() {
+            self.manifest.pending_env_changes.clear();
+            self.manifest.mark_dirty();
+        }
+    }
+
+    // binaries
+
+    /// Removes the binary from the manifest, updating the global versions and removing the shims that are no longer used.
+    pub fn remove_binary(&mut self, identifier: &BinaryIdentifier) -> Result<(), ErrBox> {
+        if self.manifest.is_read_only_binary(identifier) {
+            return err!(
+                "Cannot uninstall {} {} because it's used from the read-only root.",
+                identifier.get_binary_name(),
+                identifier.get_version()
+            );
+        }
+        let previous_global_command_names = self.manifest.get_global_command_names(&identifier);
+        let binary_info = if let Some(item) = self.manifest.get_binary(identifier) {
+            Some((item.name.clone(), item.get_command_names()))
+        } else {
+            None
+        };
+
+        self.manifest.remove_binary(identifier);
+
+        if let Some((binary_name, command_names)) = binary_info {
+            // update the selected global binary
+            for command_name in command_names {
+                if !self.manifest.has_binary_with_command(&command_name) {
+                    self.remove_global_binary(&command_name)?; // could be removing the path entry
+                } else {
+                    self.remove_if_global_binary(&binary_name, &command_name, identifier)?;
+                }
+            }
+
+            // a binary that's installed again later shouldn't start out disabled
+            if self.manifest.get_latest_binary_with_name(&binary_name).is_none() {
+                self.manifest.disabled_binaries.remove(&binary_name);
+            }
+        }
+
+        for suffixed_name in self.manifest.get_suffixed_command_names(identifier) {
+            self.manifest.suffixed_commands.remove(&suffixed_name);
+            for shim_path in get_shim_paths(&self.environment, &suffixed_name) {
+                self.environment.remove_file(&shim_path)?;
+            }
+        }
+
+        // check if this is the last binary with this command. If so, delete the shim
+        for command_name in previous_global_command_names.iter() {
+            if !self.manifest.has_binary_with_command(&command_name) {
+                for shim_path in get_shim_paths(&self.environment, &command_name) {
+                    self.environment.remove_file(&shim_path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the global version of the command, which is the binary run when not in a project.
+    pub fn use_global_version(
+        &mut self,
+        command_name: CommandName,
+        location: GlobalBinaryLocation,
+    ) -> Result<(), ErrBox> {
+        self.remove_global_binary(&command_name)?;
+
+        let new_identifier = location.to_identifier_option();
+        if let Some(new_identifier) = &new_identifier {
+            if !self.manifest.has_any_global_command(&new_identifier) {
+                if self.manifest.has_environment_changes(&new_identifier) {
+                    self.manifest
+                        .pending_env_changes
+                        .mark_for_adding(new_identifier.clone());
+                }
+            }
+        }
+
+        // resolving a command temporarily switches versions without saving
+        if self.allow_write {
+            if let Some(identifier) = &new_identifier {
+                self.add_hook_event(HookEvent::PostUse, identifier, Some(command_name.clone()));
+            }
+            self.environment.emit_event(Event::VersionSwitched {
+                command_name: command_name.clone(),
+                binary: new_identifier.map(|identifier| (identifier.get_binary_name(), identifier.get_version())),
+            });
+        }
+        self.manifest.global_versions.set(command_name, location);
+        self.manifest.mark_dirty();
+        Ok(())
+    }
+
+    /// Clears the global version of the command, removing its shim when no binary provides the command.
+    pub fn clear_global_binary(&mut self, command_name: &CommandName) -> Result<(), ErrBox> {
+        self.remove_global_binary(command_name)?;
+        if !self.manifest.has_binary_with_command(command_name) {
+            for shim_path in get_shim_paths(&self.environment, command_name) {
+                self.environment.remove_file(&shim_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_if_global_binary(
+        &mut self,
+        removed_binary_name: &BinaryName,
+        removed_command_name: &CommandName,
+        removed_binary_identifier: &BinaryIdentifier,
+    ) -> Result<(), ErrBox> {
+        if let Some(GlobalBinaryLocation::Bvm(current_identifier)) =
+            self.manifest.global_versions.get(removed_command_name)
+        {
+            if &current_identifier == removed_binary_identifier {
+                // set the latest binary as the global binary
+                let latest_binary = self
+                    .manifest
+                    .get_latest_binary_with_name(&removed_binary_name)
+                    .or_else(|| self.manifest.get_latest_binary_with_command(removed_command_name));
+                if let Some(latest_binary) = latest_binary {
+                    let latest_identifier = latest_binary.get_identifier();
+                    self.use_global_version(removed_command_name.clone(), latest_identifier.into())?;
+                } else {
+                    self.remove_global_binary(removed_command_name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_global_binary(&mut self, command_name: &CommandName) -> Result<(), ErrBox> {
+        let past_location = self.manifest.get_global_binary_location(&command_name);
+        if past_location.is_some() {
+            self.manifest.global_versions.remove(command_name);
+            self.manifest.mark_dirty();
+        }
+        let past_identifier = past_location.map(|l| l.to_identifier_option()).flatten();
+
+        if let Some(past_identifier) = past_identifier {
+            if !self.manifest.has_any_global_command(&past_identifier) {
+                if self.manifest.has_environment_changes(&past_identifier) {
+                    self.manifest
+                        .pending_env_changes
+                        .mark_for_removal(past_identifier.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // transactions
+
+    /// Begins a transaction in which saving is deferred until the matching `commit`.
+    pub fn begin(&mut self) {
+        self.manifest.begin();
+    }
+
+    /// Commits the current transaction, saving the changes once the outermost transaction is committed.
+    pub fn commit(&mut self) -> Result<(), ErrBox> {
+        if self.manifest.commit() {
+            self.save()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Saves the changes to the manifest, which is deferred while in a transaction.
+    pub fn save(&mut self) -> Result<(), ErrBox> {
+        if !self.allow_write {
+            panic!("Internal error: Cannot save when allow_write is false.");
+        }
+        if self.manifest.is_in_transaction() {
+            return Ok(());
+        }
+        self.write_changes()
+    }
+
+    /// Saves the changes made so far in the current transaction, which is necessary before running
+    /// anything that reads the manifest, such as a hook that runs an installed binary.
+    pub fn flush(&mut self) -> Result<(), ErrBox> {
+        if !self.allow_write {
+            panic!("Internal error: Cannot flush when allow_write is false.");
+        }
+        self.write_changes()
+    }
+
+    fn write_changes(&mut self) -> Result<(), ErrBox> {
+        // handle any pending changes
+        if self.manifest.pending_env_changes.any() {
+            if is_shims_only_enabled(&self.environment)? {
+                // the environment of the binaries is resolved when running their shims instead
+                self.manifest.pending_env_changes.clear();
+                self.manifest.mark_dirty();
+            } else {
+                // update the system path on windows (the environment manifest will be be set on the path on linux shell startup)
+                #[cfg(target_os = "windows")]
+                {
+                    for path in self.manifest.get_relative_pending_removed_paths(&self.environment) {
+                        self.environment.remove_system_path(&path)?;
+                    }
+                    for path in self.manifest.get_relative_pending_added_paths(&self.environment) {
+                        self.environment.ensure_system_path(&path)?;
+                    }
+                }
+
+                // persist the environment variables so they're available outside the interactive shell
+                for (key, _) in self.manifest.get_pending_removed_env_variables(&self.environment) {
+                    self.environment.remove_env_variable(&key)?;
+                }
+                for (key, value) in self.manifest.get_pending_added_env_variables(&self.environment) {
+                    self.environment.set_env_variable(key, value)?;
+                }
+            }
+        }
+
+        self.manifest.write(&self.environment)?;
+
+        let pending_hook_events = std::mem::take(&mut self.pending_hook_events);
+        self.saved_hook_events.extend(pending_hook_events);
+        Ok(())
+    }
+
+    fn add_hook_event(&mut self, event: HookEvent, identifier: &BinaryIdentifier, command_name: Option<CommandName>) {
+        if self.allow_write {
+            self.pending_hook_events.push(HookEventDetails {
+                event,
+                name: identifier.get_binary_name(),
+                version: identifier.get_version(),
+                command_name,
+            });
+        }
+    }
+}
+
+impl<TEnvironment: Environment> Drop for PluginsMut<TEnvironment> {
+    fn drop(&mut self) {
+        // release the lock first so the hooks may run bvm commands that change the manifest
+        self.lock.take();
+        for details in std::mem::take(&mut self.saved_hook_events) {
+            run_user_hooks(&self.environment, &details);
+        }
+    }
+}
+
+/// Runs the jobs on up to the provided number of threads, returning the result of each job in order.
+/// Jobs whose thread panicked get an error.
+fn run_jobs_on_threads<TJob: Send + 'static, TResult: Send + 'static>(
+    jobs: Vec<TJob>,
+    max_threads: usize,
+    run_job: impl Fn(TJob) -> TResult + Send + Sync + 'static,
+) -> Vec<Result<TResult, ErrBox>> {
+    let job_count = jobs.len();
+    let jobs = Arc::new(Mutex::new(jobs.into_iter().enumerate().collect::<VecDeque<_>>()));
+    let run_job = Arc::new(run_job);
+    let (sender, receiver) = channel();
+    let handles = (0..max_threads.max(1).min(job_count))
+        .map(|_| {
+            let jobs = jobs.clone();
+            let run_job = run_job.clone();
+            let sender = sender.clone();
+            std::thread::spawn(move || loop {
+                let job = jobs.lock().unwrap().pop_front();
+                match job {
+                    Some((index, job)) => {
+                        let _ignore = sender.send((index, run_job(job)));
+                    }
+                    None => break,
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(sender);
+
+    let mut results = (0..job_count).map(|_| None).collect::<Vec<_>>();
+    for (index, result) in receiver.iter() {
+        results[index] = Some(result);
+    }
+    for handle in handles.into_iter() {
+        let _ignore = handle.join();
+    }
+    results
+        .into_iter()
+        .map(|result| result.ok_or_else(|| err_obj!("The thread running the job panicked.").into()))
+        .collect()
+}
@@ -0,0 +1,76 @@
+use dprint_cli_core::types::ErrBox;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::manifest::GlobalVersionsMap;
+use super::{BinaryIdentifier, GlobalBinaryLocation};
+use crate::environment::Environment;
+use crate::types::CommandName;
+
+/// Directory in a project's directory that contains the project's local bvm state.
+const PROJECT_STATE_DIR_NAME: &str = ".bvm";
+
+/// Versions selected with `bvm use` within a project, which only affect how the commands resolve
+/// in that project. This is stored next to the project's configuration file in `.bvm/state.json`.
+pub struct ProjectState {
+    file_path: PathBuf,
+    commands: GlobalVersionsMap,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct SerializedProjectState {
+    #[serde(default)]
+    commands: GlobalVersionsMap,
+}
+
+impl ProjectState {
+    /// Loads the state of the project in the provided directory, which is empty when there is none.
+    pub fn load(environment: &impl Environment, project_dir: &Path) -> Result<ProjectState, ErrBox> {
+        let file_path = project_dir.join(PROJECT_STATE_DIR_NAME).join("state.json");
+        let state = if environment.path_exists(&file_path) {
+            let file_text = environment.read_file_text(&file_path)?;
+            match serde_json::from_str::<SerializedProjectState>(&file_text) {
+                Ok(state) => state,
+                Err(err) => return err!("Error deserializing {}. {}", file_path.display(), err.to_string()),
+            }
+        } else {
+            SerializedProjectState::default()
+        };
+        Ok(ProjectState {
+            file_path,
+            commands: state.commands,
+        })
+    }
+
+    pub fn get_location(&self, command_name: &CommandName) -> Option<GlobalBinaryLocation> {
+        self.commands.get(command_name)
+    }
+
+    /// Gets the binaries used by the project's commands.
+    pub fn get_identifiers(&self) -> Vec<BinaryIdentifier> {
+        self.commands
+            .get_locations()
+            .into_iter()
+            .filter_map(|location| location.to_identifier_option())
+            .collect()
+    }
+
+    pub fn set_location(&mut self, command_name: CommandName, location: GlobalBinaryLocation) {
+        self.commands.set(command_name, location);
+    }
+
+    pub fn save(&self, environment: &impl Environment) -> Result<(), ErrBox> {
+        let state_dir = self.file_path.parent().unwrap();
+        environment.create_dir_all(state_dir)?;
+        // the state is specific to the machine, so keep it out of version control
+        let gitignore_path = state_dir.join(".gitignore");
+        if !environment.path_exists(&gitignore_path) {
+            environment.write_file_text(&gitignore_path, "*\n")?;
+        }
+        let state = SerializedProjectState {
+            commands: self.commands.clone(),
+        };
+        environment.write_file_text(&self.file_path, &serde_json::to_string_pretty(&state)?)
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{self, Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::types::CommandName;
+use crate::types::Version;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedPluginFile {
+    pub schema_version: u32,
+    pub name: String,
+    pub owner: String,
+    pub version: Version,
+    pub description: String,
+    #[serde(rename = "linux-x86_64")]
+    pub linux: Option<PlatformInfo>,
+    #[serde(rename = "linux-x86_64-musl", default, skip_serializing_if = "Option::is_none")]
+    pub linux_musl: Option<PlatformInfo>,
+    #[serde(rename = "darwin-x86_64")]
+    pub mac: Option<PlatformInfo>,
+    #[serde(rename = "darwin-aarch64", default, skip_serializing_if = "Option::is_none")]
+    pub mac_aarch64: Option<PlatformInfo>,
+    #[serde(rename = "windows-x86_64")]
+    pub windows: Option<PlatformInfo>,
+    #[serde(rename = "windows-aarch64", default, skip_serializing_if = "Option::is_none")]
+    pub windows_aarch64: Option<PlatformInfo>,
+    /// Invocation run after the binary is set up to verify it executes on the current system.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smoke_test: Option<SmokeTest>,
+}
+
+impl SerializedPluginFile {
+    /// Gets the platform information for the provided platform key (ex. `windows-x86_64`).
+    pub fn get_platform_info(&self, platform_key: &str) -> Option<&PlatformInfo> {
+        match platform_key {
+            "linux-x86_64" => self.linux.as_ref(),
+            "linux-x86_64-musl" => self.linux_musl.as_ref(),
+            "darwin-x86_64" => self.mac.as_ref(),
+            "darwin-aarch64" => self.mac_aarch64.as_ref(),
+            "windows-x86_64" => self.windows.as_ref(),
+            "windows-aarch64" => self.windows_aarch64.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Platform keys of all the targets a plugin file may provide.
+pub const PLATFORM_KEYS: &[&str] = &[
+    "linux-x86_64",
+    "linux-x86_64-musl",
+    "darwin-x86_64",
+    "darwin-aarch64",
+    "windows-x86_64",
+    "windows-aarch64",
+];
+
+/// Gets the platform keys that may be installed on the current operating system and architecture in order of preference.
+pub fn get_platform_keys() -> &'static [&'static str] {
+    if cfg!(all(target_os = "windows", target_arch = "aarch64")) {
+        // x86_64 binaries run emulated on windows arm64
+        &["windows-aarch64", "windows-x86_64"]
+    } else if cfg!(target_os = "windows") {
+        &["windows-x86_64"]
+    } else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        // x86_64 binaries run with rosetta on apple silicon, which needs to be opted into
+        &["darwin-aarch64", "darwin-x86_64"]
+    } else if cfg!(target_os = "macos") {
+        &["darwin-x86_64"]
+    } else {
+        &["linux-x86_64"]
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformInfo {
+    pub path: String,
+    /// Urls to download the same file from, in order, when downloading from the path fails.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+    pub checksum: String,
+    #[serde(rename = "type")]
+    pub download_type: String,
+    pub output_dir: Option<String>,
+    pub commands: Vec<PlatformInfoCommand>,
+    pub on_pre_install: Option<String>,
+    pub on_post_install: Option<String>,
+    /// Command to run in the binary's directory before it's uninstalled (ex. to remove caches).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_pre_uninstall: Option<String>,
+    pub environment: Option<BinaryEnvironment>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SmokeTest {
+    /// Name of the command to run. Defaults to the first command of the binary.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<CommandName>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Regular expression that the command's combined stdout and stderr must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_output: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryEnvironment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformInfoCommand {
+    pub name: CommandName,
+    pub path: String,
+}
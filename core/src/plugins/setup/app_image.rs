@@ -0,0 +1,92 @@
+use dprint_cli_core::types::ErrBox;
+use std::path::Path;
+
+use crate::environment::{read_user_config, Environment};
+
+/// Sets up an AppImage at the path of its command. AppImages mount themselves with FUSE when run, so in
+/// environments without FUSE (ex. containers) the AppImage is extracted instead and the command runs the
+/// extracted `AppRun` entry point.
+pub(super) fn install_app_image<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    display_name: &str,
+    target: &str,
+    app_image_bytes: &[u8],
+    file_path: &Path,
+) -> Result<(), ErrBox> {
+    if !target.starts_with("linux-") {
+        return err!(
+            "The appimage download type may only be used for linux targets, but was used for {}.",
+            target
+        );
+    }
+    let dir_path = file_path.parent().unwrap();
+    let file_name = file_path.file_name().unwrap().to_string_lossy().to_string();
+    environment.create_dir_all(dir_path)?;
+    environment.write_file(file_path, app_image_bytes)?;
+    set_executable_permissions(environment, file_path)?;
+
+    if !should_extract_app_image(environment)? {
+        return Ok(());
+    }
+
+    let extracted_dir_path = dir_path.join("squashfs-root");
+    let result = environment.run_shell_command(dir_path, &format!("./\"{}\" --appimage-extract", file_name));
+    if let Err(err) = result {
+        environment.remove_dir_all(&extracted_dir_path)?;
+        return err!(
+            "Could not extract the AppImage of {}. Set the BVM_APPIMAGE_EXTRACT environment variable to 0 or `\"appImageExtract\": false` in the config.json file to run it without extracting. {}",
+            display_name,
+            err.to_string()
+        );
+    }
+    if !environment.path_exists(&extracted_dir_path.join("AppRun")) {
+        environment.remove_dir_all(&extracted_dir_path)?;
+        return err!(
+            "Could not find the AppRun entry point in the extracted AppImage of {}.",
+            display_name
+        );
+    }
+
+    let app_dir_name = format!("{}.AppDir", get_app_image_stem(&file_name));
+    environment.rename(&extracted_dir_path, &dir_path.join(&app_dir_name))?;
+    environment.remove_file(file_path)?;
+    // the binary's directory is moved after it's set up, so find the extracted files relative to the script
+    environment.write_file_text(
+        file_path,
+        &format!("#!/bin/sh\nexec \"$(dirname \"$0\")/{}/AppRun\" \"$@\"\n", app_dir_name),
+    )?;
+    set_executable_permissions(environment, file_path)
+}
+
+/// Gets if AppImages should be extracted, which defaults to when FUSE isn't available.
+fn should_extract_app_image(environment: &impl Environment) -> Result<bool, ErrBox> {
+    if let Some(value) = environment.get_env_var("BVM_APPIMAGE_EXTRACT") {
+        return Ok(value == "1" || value.to_lowercase() == "true");
+    }
+    if let Some(value) = read_user_config(environment)?.app_image_extract {
+        return Ok(value);
+    }
+    Ok(!(cfg!(target_os = "linux") && environment.path_exists(Path::new("/dev/fuse"))))
+}
+
+fn get_app_image_stem(file_name: &str) -> &str {
+    let lowercase_file_name = file_name.to_lowercase();
+    if lowercase_file_name.ends_with(".appimage") {
+        &file_name[..file_name.len() - ".appimage".len()]
+    } else {
+        file_name
+    }
+}
+
+fn set_executable_permissions(environment: &impl Environment, file_path: &Path) -> Result<(), ErrBox> {
+    #[cfg(unix)]
+    if environment.is_real() {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::set_permissions(file_path, fs::Permissions::from_mode(0o755))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (environment, file_path);
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+
+use super::{verify_trusted_host, PluginFile, PLUGIN_FILE_SCHEMA_VERSION};
+use crate::environment::Environment;
+use crate::plugins::{PlatformInfo, PlatformInfoCommand, SerializedPluginFile};
+use crate::types::{BinaryName, CommandName, Version};
+
+/// Creates an implicit plugin file for the current operating system from an archive or binary url.
+///
+/// The commands of an archive are detected from the executables it contains once extracted.
+pub fn get_archive_plugin_file<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    binary_name: &BinaryName,
+    version: &Version,
+    url: &str,
+) -> Result<PluginFile, ErrBox> {
+    let download_type = match get_download_type_from_file_name(url) {
+        Some(download_type) => download_type,
+        None => return err!("Unsupported archive type: {}", url),
+    };
+    verify_trusted_host(environment, url)?;
+    let archive_bytes = environment.download_file(url)?;
+    let commands = if download_type == "binary" {
+        let name = binary_name.name.as_str();
+        vec![PlatformInfoCommand {
+            name: CommandName::from_string(name.to_string()),
+            path: if cfg!(target_os = "windows") {
+                format!("{}.exe", name)
+            } else {
+                name.to_string()
+            },
+        }]
+    } else {
+        Vec::new()
+    };
+    let platform_info = PlatformInfo {
+        path: url.to_string(),
+        mirrors: Vec::new(),
+        checksum: get_sha256_checksum(&archive_bytes),
+        download_type: download_type.to_string(),
+        output_dir: None,
+        commands,
+        on_pre_install: None,
+        on_post_install: None,
+        on_pre_uninstall: None,
+        environment: None,
+    };
+    let file = SerializedPluginFile {
+        schema_version: PLUGIN_FILE_SCHEMA_VERSION,
+        name: binary_name.name.as_str().to_string(),
+        owner: binary_name.owner.clone(),
+        version: version.clone(),
+        description: format!("Installed from {}.", url),
+        linux: if cfg!(target_os = "linux") {
+            Some(platform_info.clone())
+        } else {
+            None
+        },
+        // the archive was provided for the current system, so also use it on musl based systems
+        linux_musl: if cfg!(target_os = "linux") {
+            Some(platform_info.clone())
+        } else {
+            None
+        },
+        mac: if cfg!(target_os = "macos") && !cfg!(target_arch = "aarch64") {
+            Some(platform_info.clone())
+        } else {
+            None
+        },
+        mac_aarch64: if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+            Some(platform_info.clone())
+        } else {
+            None
+        },
+        windows: if cfg!(target_os = "windows") && !cfg!(target_arch = "aarch64") {
+            Some(platform_info.clone())
+        } else {
+            None
+        },
+        windows_aarch64: if cfg!(all(target_os = "windows", target_arch = "aarch64")) {
+            Some(platform_info)
+        } else {
+            None
+        },
+        smoke_test: None,
+    };
+
+    Ok(PluginFile {
+        url: url.to_string(),
+        checksum: get_sha256_checksum(&serde_json::to_vec(&file)?),
+        file,
+        detect_commands: download_type != "binary",
+        has_provided_checksums: false,
+    })
+}
+
+/// Gets the download type from the file name of an archive or binary.
+pub(super) fn get_download_type_from_file_name(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    let file_name = name.rsplit('/').next().unwrap_or(&name);
+    if file_name.ends_with(".zip") {
+        Some("zip")
+    } else if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+        Some("tar.gz")
+    } else if file_name.ends_with(".exe") || !file_name.contains('.') {
+        Some("binary")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,183 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use serde::Deserialize;
+use std::path::Path;
+
+use super::{PluginFile, PLUGIN_FILE_SCHEMA_VERSION};
+use crate::environment::Environment;
+use crate::plugins::{PlatformInfo, SerializedPluginFile};
+use crate::types::{CommandName, Version};
+use crate::utils;
+
+const CARGO_CRATE_PREFIX: &str = "cargo:";
+
+/// A crate to install with `cargo install` (ex. `cargo:ripgrep@12.1.1`).
+#[derive(Debug, PartialEq)]
+pub struct CargoCrateSpecifier {
+    pub crate_name: String,
+    /// The exact version or `None` for the latest version.
+    pub version: Option<String>,
+}
+
+impl CargoCrateSpecifier {
+    pub fn parse(text: &str) -> Result<CargoCrateSpecifier, ErrBox> {
+        let invalid_err = || {
+            err_obj!(
+                "Invalid crate '{}'. Expected the format `cargo:crate@version` (ex. `cargo:ripgrep@12.1.1`).",
+                text
+            )
+        };
+        if !is_cargo_crate_url(text) {
+            return Err(invalid_err());
+        }
+        let text_without_prefix = &text[CARGO_CRATE_PREFIX.len()..];
+        let (crate_name, version) = match text_without_prefix.find('@') {
+            Some(index) => (
+                &text_without_prefix[..index],
+                Some(text_without_prefix[index + 1..].to_string()),
+            ),
+            None => (text_without_prefix, None),
+        };
+        let is_valid_crate_name = !crate_name.is_empty()
+            && crate_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !is_valid_crate_name || version.as_deref() == Some("") {
+            return Err(invalid_err());
+        }
+
+        Ok(CargoCrateSpecifier {
+            crate_name: crate_name.to_string(),
+            version,
+        })
+    }
+}
+
+pub fn is_cargo_crate_url(text: &str) -> bool {
+    text.starts_with(CARGO_CRATE_PREFIX)
+}
+
+#[derive(Deserialize)]
+struct CratesIoResponse {
+    #[serde(rename = "crate")]
+    crate_info: CratesIoCrate,
+}
+
+#[derive(Deserialize)]
+struct CratesIoCrate {
+    max_version: String,
+    #[serde(default)]
+    max_stable_version: Option<String>,
+}
+
+/// Creates a plugin file for the crate that installs it with `cargo install` and exposes the produced executables as commands.
+pub fn get_cargo_crate_plugin_file<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    url: &str,
+) -> Result<PluginFile, ErrBox> {
+    let specifier = CargoCrateSpecifier::parse(url)?;
+    let version = match &specifier.version {
+        Some(version) => version.clone(),
+        None => {
+            let crate_url = format!("https://crates.io/api/v1/crates/{}", specifier.crate_name);
+            let response: CratesIoResponse = match serde_json::from_slice(&environment.download_file(&crate_url)?) {
+                Ok(response) => response,
+                Err(err) => return err!("Error deserializing {}. {}", crate_url, err.to_string()),
+            };
+            response
+                .crate_info
+                .max_stable_version
+                .unwrap_or(response.crate_info.max_version)
+        }
+    };
+    let version = match Version::parse(&version) {
+        Ok(version) => version,
+        Err(_) => {
+            return err!(
+                "Expected an exact version for crate {}, but found '{}'.",
+                specifier.crate_name,
+                version
+            )
+        }
+    };
+    let platform_info = PlatformInfo {
+        path: format!("{}@{}", specifier.crate_name, version),
+        mirrors: Vec::new(),
+        checksum: String::new(),
+        download_type: "cargo".to_string(),
+        output_dir: None,
+        commands: Vec::new(),
+        on_pre_install: None,
+        on_post_install: None,
+        on_pre_uninstall: None,
+        environment: None,
+    };
+    let file = SerializedPluginFile {
+        schema_version: PLUGIN_FILE_SCHEMA_VERSION,
+        name: specifier.crate_name.clone(),
+        owner: "cargo".to_string(),
+        version,
+        description: format!("Crate {}.", specifier.crate_name),
+        linux: Some(platform_info.clone()),
+        linux_musl: Some(platform_info.clone()),
+        mac: Some(platform_info.clone()),
+        mac_aarch64: Some(platform_info.clone()),
+        windows: Some(platform_info.clone()),
+        windows_aarch64: Some(platform_info),
+        smoke_test: None,
+    };
+
+    Ok(PluginFile {
+        url: url.to_string(),
+        checksum: get_sha256_checksum(&serde_json::to_vec(&file)?),
+        file,
+        detect_commands: true,
+        has_provided_checksums: true,
+    })
+}
+
+/// Installs the crate (ex. `ripgrep@12.1.1`) with its executables in the `bin` folder of the provided directory.
+pub(super) fn install_cargo_crate<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    crate_and_version: &str,
+    dir_path: &Path,
+) -> Result<(), ErrBox> {
+    if utils::get_path_executable_path(environment, &CommandName::from_string("cargo".to_string())).is_none() {
+        return err!("Could not find cargo on the path. Install Rust to install crates.");
+    }
+    let (crate_name, version) = match crate_and_version.find('@') {
+        Some(index) => (&crate_and_version[..index], &crate_and_version[index + 1..]),
+        None => return err!("Expected a crate and version, but found '{}'.", crate_and_version),
+    };
+
+    environment.run_shell_command(
+        dir_path,
+        &format!("cargo install --root . --locked --version {} {}", version, crate_name),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_specifier() {
+        assert_eq!(
+            CargoCrateSpecifier::parse("cargo:ripgrep@12.1.1").unwrap(),
+            CargoCrateSpecifier {
+                crate_name: "ripgrep".to_string(),
+                version: Some("12.1.1".to_string()),
+            }
+        );
+        assert_eq!(
+            CargoCrateSpecifier::parse("cargo:cargo-edit").unwrap(),
+            CargoCrateSpecifier {
+                crate_name: "cargo-edit".to_string(),
+                version: None,
+            }
+        );
+        assert!(CargoCrateSpecifier::parse("cargo:").is_err());
+        assert!(CargoCrateSpecifier::parse("cargo:ripgrep@").is_err());
+        assert!(CargoCrateSpecifier::parse("cargo:ripgrep;ls").is_err());
+    }
+}
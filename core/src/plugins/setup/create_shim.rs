@@ -1,20 +1,30 @@
 use dprint_cli_core::types::ErrBox;
 use std::path::PathBuf;
 
-use crate::environment::Environment;
+use crate::environment::{read_user_config, Environment};
 use crate::types::CommandName;
 use crate::utils;
 
+/// Gets if only the shims should be on the path, with the path and environment variables
+/// of binaries being resolved when their shim is run.
+pub fn is_shims_only_enabled(environment: &impl Environment) -> Result<bool, ErrBox> {
+    if let Some(value) = environment.get_env_var("BVM_SHIMS_ONLY") {
+        return Ok(value == "1" || value.to_lowercase() == "true");
+    }
+    Ok(read_user_config(environment)?.shims_only.unwrap_or(false))
+}
+
 #[cfg(unix)]
 pub fn create_shim(environment: &impl Environment, command_name: &CommandName) -> Result<(), ErrBox> {
+    // exec so the shell is replaced by bvm-bin, which then replaces itself with the executable so that
+    // signals, the terminal, and the exit code are handled by the executable directly
     let shim_dir = utils::get_shim_dir(environment);
     let file_path = shim_dir.join(command_name.as_str());
     environment.write_file_text(
         &file_path,
         &format!(
             r#"#!/bin/sh
-. $BVM_INSTALL_DIR/bin/bvm
-bvm exec-command {} "$@"
+exec "$BVM_INSTALL_DIR/bin/bvm-bin" hidden exec-command {} "$@"
 "#,
             command_name.as_str()
         ),
@@ -27,12 +37,13 @@ bvm exec-command {} "$@"
 
 #[cfg(target_os = "windows")]
 pub fn create_shim(environment: &impl Environment, command_name: &CommandName) -> Result<(), ErrBox> {
+    // bvm-bin runs the executable directly so that the standard streams, exit code, and ctrl+c are handled by it
     let shim_dir = utils::get_shim_dir(environment);
     environment.write_file_text(
-        &shim_dir.join(format!("{}.bat", command_name.as_str())),
+        &shim_dir.join(format!("{}.cmd", command_name.as_str())),
         &format!(
-            r#"@ECHO OFF
-bvm exec-command {} %*
+            r#"@bvm-bin hidden exec-command {} %*
+@exit /b %ERRORLEVEL%
 "#,
             command_name.as_str()
         ),
@@ -41,7 +52,8 @@ bvm exec-command {} %*
         &shim_dir.join(format!("{}.ps1", command_name.as_str())),
         &format!(
             r#"#!/usr/bin/env pwsh
-. bvm exec-command {} $args
+& bvm-bin hidden exec-command {} @args
+exit $LASTEXITCODE
 "#,
             command_name.as_str()
         ),
@@ -54,7 +66,7 @@ pub fn get_shim_paths(environment: &impl Environment, command_name: &CommandName
     let shim_dir = utils::get_shim_dir(environment);
     #[cfg(target_os = "windows")]
     {
-        paths.push(shim_dir.join(format!("{}.bat", command_name.as_str())));
+        paths.push(shim_dir.join(format!("{}.cmd", command_name.as_str())));
         paths.push(shim_dir.join(format!("{}.ps1", command_name.as_str())));
     }
     #[cfg(unix)]
@@ -0,0 +1,166 @@
+use dprint_cli_core::checksums::{get_sha256_checksum, ChecksumPathOrUrl};
+use dprint_cli_core::types::ErrBox;
+use std::path::Path;
+use url::Url;
+
+use super::{
+    get_cargo_crate_plugin_file, get_github_release_plugin_file, get_npm_package_plugin_file, is_cargo_crate_url,
+    is_github_release_url, is_npm_package_url, read_plugin_file, verify_trusted_host, PluginFile,
+};
+use crate::configuration::interpolate_env_vars;
+use crate::environment::{CacheValidators, ConditionalDownload, Environment};
+use crate::plugins::SerializedPluginFile;
+use crate::utils::{self, normalize_path};
+
+pub fn get_plugin_file<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    checksum_url: &ChecksumPathOrUrl,
+) -> Result<PluginFile, ErrBox> {
+    // the url keeps its references to environment variables so that their values aren't stored or displayed
+    let url = interpolate_env_vars(environment, &checksum_url.path_or_url)?;
+    let mut plugin_file = get_plugin_file_at_url(environment, &url, checksum_url)?;
+    plugin_file.url = checksum_url.path_or_url.clone();
+    Ok(plugin_file)
+}
+
+fn get_plugin_file_at_url<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    url: &str,
+    checksum_url: &ChecksumPathOrUrl,
+) -> Result<PluginFile, ErrBox> {
+    if is_github_release_url(url) {
+        return get_github_release_plugin_file(environment, url);
+    }
+    if is_npm_package_url(url) {
+        return get_npm_package_plugin_file(environment, url);
+    }
+    if is_cargo_crate_url(url) {
+        return get_cargo_crate_plugin_file(environment, url);
+    }
+
+    // a fragment selects the version of a plugin file that describes multiple versions (ex. `https://host/deno.json#1.5.0`)
+    let (download_url, version) = split_version_fragment(url);
+    verify_trusted_host(environment, download_url)?;
+    log_verbose!(
+        environment,
+        "Downloading binary manifest file {}.",
+        split_version_fragment(&checksum_url.path_or_url).0
+    );
+    let plugin_file_bytes = download_plugin_file_bytes(environment, download_url)?;
+
+    let checksum = if let Some(checksum) = &checksum_url.checksum {
+        utils::verify_download_checksum(environment, download_url, &plugin_file_bytes, checksum)?;
+        checksum.clone()
+    } else {
+        get_sha256_checksum(&plugin_file_bytes)
+    };
+
+    let mut serialized_plugin_file = read_plugin_file(&plugin_file_bytes, version)?;
+    resolve_platform_paths(&mut serialized_plugin_file, download_url)?;
+    Ok(PluginFile {
+        url: checksum_url.path_or_url.clone(),
+        checksum,
+        file: serialized_plugin_file,
+        detect_commands: false,
+        has_provided_checksums: true,
+    })
+}
+
+/// Downloads a plugin file with a conditional request when a copy was previously downloaded so
+/// that an unchanged plugin file isn't downloaded again (ex. on every `bvm install` in CI).
+fn download_plugin_file_bytes(environment: &impl Environment, url: &str) -> Result<Vec<u8>, ErrBox> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return environment.download_file(url);
+    }
+
+    let cache = get_plugin_file_cache(environment);
+    let _download_lock = utils::DownloadLock::acquire(environment, url)?;
+    let cached_entry = cache.get(environment, url);
+    let validators = match &cached_entry {
+        Some(entry) => entry.metadata.validators.clone(),
+        None => CacheValidators::default(),
+    };
+    match environment.download_file_if_modified(url, &validators)? {
+        ConditionalDownload::Modified(file_bytes, validators) => {
+            // only keep a copy when it can be revalidated
+            if validators.etag.is_some() || validators.last_modified.is_some() {
+                let _ignore = cache.set(environment, url, &file_bytes, validators);
+            }
+            Ok(file_bytes)
+        }
+        ConditionalDownload::NotModified => match cached_entry {
+            Some(entry) => {
+                log_verbose!(environment, "Using the cached binary manifest file for {}.", url);
+                let _ignore = cache.set_validated(environment, &entry.metadata, validators);
+                Ok(entry.bytes)
+            }
+            None => err!(
+                "Error downloading {}. Received not modified without a cached copy.",
+                url
+            ),
+        },
+    }
+}
+
+/// Gets the cache of the downloaded binary manifest files.
+pub fn get_plugin_file_cache(environment: &impl Environment) -> utils::UrlCache {
+    utils::UrlCache::new(environment, "plugin-cache")
+}
+
+/// Resolves a path to a local plugin file relative to the provided directory as a `file://` url.
+/// Urls are returned as-is.
+pub fn resolve_plugin_file_url(base_dir: &Path, path_or_url: &str) -> Result<String, ErrBox> {
+    if is_url(path_or_url) {
+        return Ok(path_or_url.to_string());
+    }
+
+    let (path, version) = split_version_fragment(path_or_url);
+    let file_path = normalize_path(&base_dir.join(path));
+    match Url::from_file_path(&file_path) {
+        Ok(mut url) => {
+            url.set_fragment(version);
+            Ok(url.to_string())
+        }
+        Err(_) => err!("Could not resolve the plugin file path {}.", file_path.display()),
+    }
+}
+
+/// Splits a plugin file url into the url to download and the version specified in its fragment.
+pub fn split_version_fragment(path_or_url: &str) -> (&str, Option<&str>) {
+    match path_or_url.rfind('#') {
+        Some(index) if index + 1 < path_or_url.len() => (&path_or_url[..index], Some(&path_or_url[index + 1..])),
+        _ => (path_or_url, None),
+    }
+}
+
+/// Resolves any relative archive paths in the plugin file relative to the plugin file's url.
+fn resolve_platform_paths(plugin_file: &mut SerializedPluginFile, plugin_file_url: &str) -> Result<(), ErrBox> {
+    let plugin_file_url = match Url::parse(plugin_file_url) {
+        Ok(url) => url,
+        Err(_) => return Ok(()),
+    };
+    let platform_infos = vec![
+        &mut plugin_file.windows,
+        &mut plugin_file.windows_aarch64,
+        &mut plugin_file.linux,
+        &mut plugin_file.linux_musl,
+        &mut plugin_file.mac,
+        &mut plugin_file.mac_aarch64,
+    ];
+    for platform_info in platform_infos.into_iter().flatten() {
+        if !is_url(&platform_info.path) {
+            platform_info.path = plugin_file_url.join(&platform_info.path)?.to_string();
+        }
+        for mirror in platform_info.mirrors.iter_mut() {
+            if !is_url(mirror) {
+                *mirror = plugin_file_url.join(mirror)?.to_string();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_url(text: &str) -> bool {
+    // single letter schemes are windows drive letters (ex. C:\)
+    matches!(Url::parse(text), Ok(url) if url.scheme().len() > 1)
+}
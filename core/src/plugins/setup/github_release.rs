@@ -0,0 +1,442 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{get_download_type_from_file_name, PluginFile, PLUGIN_FILE_SCHEMA_VERSION};
+use crate::environment::Environment;
+use crate::plugins::{PlatformInfo, PlatformInfoCommand, SerializedPluginFile};
+use crate::types::{CommandName, Version};
+
+const GITHUB_RELEASE_PREFIX: &str = "gh:";
+
+/// A GitHub release to install from (ex. `gh:owner/repo@v1.2.3?asset=linux-musl`).
+#[derive(Debug, PartialEq)]
+pub struct GitHubReleaseSpecifier {
+    pub owner: String,
+    pub repo: String,
+    /// The release tag or `None` for the latest release.
+    pub tag: Option<String>,
+    /// Regular expression used to select the release asset instead of the default OS and architecture patterns.
+    pub asset_pattern: Option<String>,
+}
+
+impl GitHubReleaseSpecifier {
+    pub fn parse(text: &str) -> Result<GitHubReleaseSpecifier, ErrBox> {
+        let invalid_err = || {
+            err_obj!(
+                "Invalid GitHub release '{}'. Expected the format `gh:owner/repo@tag` (ex. `gh:dprint/dprint@0.9.0`).",
+                text
+            )
+        };
+        if !is_github_release_url(text) {
+            return Err(invalid_err());
+        }
+        let text_without_prefix = &text[GITHUB_RELEASE_PREFIX.len()..];
+        let (text_without_prefix, asset_pattern) = match text_without_prefix.find("?asset=") {
+            Some(index) => (
+                &text_without_prefix[..index],
+                Some(text_without_prefix[index + "?asset=".len()..].to_string()),
+            ),
+            None => (text_without_prefix, None),
+        };
+        let (name, tag) = match text_without_prefix.find('@') {
+            Some(index) => (
+                &text_without_prefix[..index],
+                Some(text_without_prefix[index + 1..].to_string()),
+            ),
+            None => (text_without_prefix, None),
+        };
+        let name_parts = name.split('/').collect::<Vec<_>>();
+        if name_parts.len() != 2 || name_parts.iter().any(|p| p.is_empty()) || tag.as_deref() == Some("") {
+            return Err(invalid_err());
+        }
+
+        Ok(GitHubReleaseSpecifier {
+            owner: name_parts[0].to_string(),
+            repo: name_parts[1].to_string(),
+            tag,
+            asset_pattern,
+        })
+    }
+
+    fn get_api_url(&self) -> String {
+        match &self.tag {
+            Some(tag) => format!(
+                "https://api.github.com/repos/{}/{}/releases/tags/{}",
+                self.owner, self.repo, tag
+            ),
+            None => format!(
+                "https://api.github.com/repos/{}/{}/releases/latest",
+                self.owner, self.repo
+            ),
+        }
+    }
+}
+
+pub fn is_github_release_url(text: &str) -> bool {
+    text.starts_with(GITHUB_RELEASE_PREFIX)
+}
+
+#[derive(Deserialize)]
+pub struct GitHubRelease {
+    pub tag_name: String,
+    pub assets: Vec<GitHubReleaseAsset>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct GitHubReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+    /// Digest of the asset (ex. `sha256:<hex>`), which is only provided for newer releases.
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+impl GitHubReleaseAsset {
+    /// Gets the sha256 checksum of the asset when the release provides a digest.
+    pub fn get_sha256_checksum(&self) -> Option<&str> {
+        self.digest.as_ref().and_then(|d| d.strip_prefix("sha256:"))
+    }
+}
+
+/// Downloads the information of the GitHub release.
+pub fn get_github_release<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    specifier: &GitHubReleaseSpecifier,
+) -> Result<GitHubRelease, ErrBox> {
+    let release_bytes = environment.download_file(&specifier.get_api_url())?;
+    match serde_json::from_slice(&release_bytes) {
+        Ok(release) => Ok(release),
+        Err(err) => err!(
+            "Error deserializing GitHub release for {}/{}. {}",
+            specifier.owner,
+            specifier.repo,
+            err.to_string()
+        ),
+    }
+}
+
+/// Creates a plugin file for the asset of the GitHub release that matches the current operating system.
+pub fn get_github_release_plugin_file<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    url: &str,
+) -> Result<PluginFile, ErrBox> {
+    let specifier = GitHubReleaseSpecifier::parse(url)?;
+    let release = get_github_release(environment, &specifier)?;
+    let version = match Version::parse(release.tag_name.trim_start_matches('v')) {
+        Ok(version) => version,
+        Err(_) => {
+            return err!(
+                "Could not parse the release tag '{}' of {}/{} as a version.",
+                release.tag_name,
+                specifier.owner,
+                specifier.repo
+            )
+        }
+    };
+    let asset_pattern = match &specifier.asset_pattern {
+        Some(pattern) => match Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(err) => return err!("Error parsing asset pattern '{}'. {}", pattern, err.to_string()),
+        },
+        None => None,
+    };
+    // prefer native assets on arm64 and otherwise fall back to x86_64 assets, which run emulated
+    let aarch64_asset = if asset_pattern.is_some() || !cfg!(target_arch = "aarch64") {
+        None
+    } else if cfg!(target_os = "windows") {
+        select_aarch64_asset(&release.assets, &WINDOWS_RE)
+    } else if cfg!(target_os = "macos") {
+        select_aarch64_asset(&release.assets, &MAC_RE)
+    } else {
+        None
+    };
+    let is_aarch64 = aarch64_asset.is_some();
+    let asset = match aarch64_asset.or_else(|| select_asset(&release.assets, asset_pattern.as_ref())) {
+        Some(asset) => asset,
+        None => {
+            return err!(
+                "Could not find a release asset of {}/{} {} for the current operating system. Provide an asset pattern to select one (ex. `gh:{}/{}@{}?asset=<regex>`). Assets: {}",
+                specifier.owner,
+                specifier.repo,
+                release.tag_name,
+                specifier.owner,
+                specifier.repo,
+                release.tag_name,
+                release.assets.iter().map(|a| a.name.as_str()).collect::<Vec<_>>().join(", ")
+            )
+        }
+    };
+
+    let download_type = match get_download_type_from_file_name(&asset.name) {
+        Some(download_type) => download_type,
+        None => return err!("Unsupported release asset type: {}", asset.name),
+    };
+    let has_provided_checksums = asset.get_sha256_checksum().is_some();
+    // without a digest, the checksum is left empty so that `requireChecksums` refuses the asset and otherwise
+    // the asset is trusted when it's downloaded to install it
+    let checksum = asset.get_sha256_checksum().unwrap_or_default().to_string();
+    let command_path = if cfg!(target_os = "windows") {
+        format!("{}.exe", specifier.repo)
+    } else {
+        specifier.repo.clone()
+    };
+    let platform_info = PlatformInfo {
+        path: asset.browser_download_url.clone(),
+        mirrors: Vec::new(),
+        checksum,
+        download_type: download_type.to_string(),
+        output_dir: None,
+        commands: vec![PlatformInfoCommand {
+            name: CommandName::from_string(specifier.repo.clone()),
+            path: command_path,
+        }],
+        on_pre_install: None,
+        on_post_install: None,
+        on_pre_uninstall: None,
+        environment: None,
+    };
+    let file = SerializedPluginFile {
+        schema_version: PLUGIN_FILE_SCHEMA_VERSION,
+        name: specifier.repo.clone(),
+        owner: specifier.owner.clone(),
+        version,
+        description: format!(
+            "GitHub release {} of {}/{}.",
+            release.tag_name, specifier.owner, specifier.repo
+        ),
+        linux: if cfg!(target_os = "linux") {
+            Some(platform_info.clone())
+        } else {
+            None
+        },
+        linux_musl: None,
+        mac: if cfg!(target_os = "macos") && !is_aarch64 {
+            Some(platform_info.clone())
+        } else {
+            None
+        },
+        mac_aarch64: if cfg!(target_os = "macos") && is_aarch64 {
+            Some(platform_info.clone())
+        } else {
+            None
+        },
+        windows: if cfg!(target_os = "windows") && !is_aarch64 {
+            Some(platform_info.clone())
+        } else {
+            None
+        },
+        windows_aarch64: if cfg!(target_os = "windows") && is_aarch64 {
+            Some(platform_info)
+        } else {
+            None
+        },
+        smoke_test: None,
+    };
+
+    Ok(PluginFile {
+        url: url.to_string(),
+        checksum: get_sha256_checksum(&serde_json::to_vec(&file)?),
+        file,
+        detect_commands: false,
+        has_provided_checksums,
+    })
+}
+
+lazy_static! {
+    static ref LINUX_RE: Regex = Regex::new(r"(?i)linux").unwrap();
+    static ref MAC_RE: Regex = Regex::new(r"(?i)(darwin|macos|osx|apple|[^a-z]mac[^a-z])").unwrap();
+    static ref WINDOWS_RE: Regex = Regex::new(r"(?i)(windows|win64|[^a-z]win[^a-z]|\.exe$)").unwrap();
+    static ref X86_64_RE: Regex = Regex::new(r"(?i)(x86_64|x86-64|amd64|x64)").unwrap();
+    static ref AARCH64_RE: Regex = Regex::new(r"(?i)(aarch64|arm64)").unwrap();
+    static ref OTHER_ARCH_RE: Regex =
+        Regex::new(r"(?i)(arm|aarch64|i386|i686|[^0-9]386|x86[^_-]|ppc|s390|riscv|mips)").unwrap();
+}
+
+/// Gets the platform key (ex. `linux-x86_64`) of a release asset from its file name.
+pub(super) fn get_asset_platform_key(file_name: &str) -> Option<&'static str> {
+    get_download_type_from_file_name(file_name)?;
+    let is_aarch64 = AARCH64_RE.is_match(file_name);
+    if !is_aarch64 && OTHER_ARCH_RE.is_match(file_name) {
+        return None;
+    }
+    if WINDOWS_RE.is_match(file_name) {
+        Some(if is_aarch64 {
+            "windows-aarch64"
+        } else {
+            "windows-x86_64"
+        })
+    } else if MAC_RE.is_match(file_name) {
+        Some(if is_aarch64 { "darwin-aarch64" } else { "darwin-x86_64" })
+    } else if LINUX_RE.is_match(file_name) && !is_aarch64 {
+        Some(if file_name.to_lowercase().contains("musl") {
+            "linux-x86_64-musl"
+        } else {
+            "linux-x86_64"
+        })
+    } else {
+        None
+    }
+}
+
+fn select_asset<'a>(assets: &'a [GitHubReleaseAsset], pattern: Option<&Regex>) -> Option<&'a GitHubReleaseAsset> {
+    if let Some(pattern) = pattern {
+        return assets.iter().find(|a| pattern.is_match(&a.name));
+    }
+
+    let os_re: &Regex = if cfg!(target_os = "windows") {
+        &WINDOWS_RE
+    } else if cfg!(target_os = "macos") {
+        &MAC_RE
+    } else {
+        &LINUX_RE
+    };
+
+    let mut candidates = assets
+        .iter()
+        .filter(|a| get_download_type_from_file_name(&a.name).is_some())
+        .filter(|a| os_re.is_match(&a.name) && !OTHER_ARCH_RE.is_match(&a.name))
+        .collect::<Vec<_>>();
+    // prefer assets that explicitly specify the architecture and then archives
+    candidates.sort_by_key(|a| {
+        (
+            !X86_64_RE.is_match(&a.name),
+            get_download_type_from_file_name(&a.name) == Some("binary"),
+        )
+    });
+    candidates.into_iter().next()
+}
+
+fn select_aarch64_asset<'a>(assets: &'a [GitHubReleaseAsset], os_re: &Regex) -> Option<&'a GitHubReleaseAsset> {
+    let mut candidates = assets
+        .iter()
+        .filter(|a| get_download_type_from_file_name(&a.name).is_some())
+        .filter(|a| os_re.is_match(&a.name) && AARCH64_RE.is_match(&a.name))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|a| get_download_type_from_file_name(&a.name) == Some("binary"));
+    candidates.into_iter().next()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_specifier() {
+        assert_eq!(
+            GitHubReleaseSpecifier::parse("gh:owner/repo@v1.2.3").unwrap(),
+            GitHubReleaseSpecifier {
+                owner: "owner".to_string(),
+                repo: "repo".to_string(),
+                tag: Some("v1.2.3".to_string()),
+                asset_pattern: None,
+            }
+        );
+        assert_eq!(
+            GitHubReleaseSpecifier::parse("gh:owner/repo?asset=musl").unwrap(),
+            GitHubReleaseSpecifier {
+                owner: "owner".to_string(),
+                repo: "repo".to_string(),
+                tag: None,
+                asset_pattern: Some("musl".to_string()),
+            }
+        );
+        assert!(GitHubReleaseSpecifier::parse("gh:owner").is_err());
+        assert!(GitHubReleaseSpecifier::parse("gh:owner/repo@").is_err());
+    }
+
+    fn asset(name: &str) -> GitHubReleaseAsset {
+        GitHubReleaseAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://github.com/owner/repo/releases/download/v1.0.0/{}", name),
+            digest: None,
+        }
+    }
+
+    #[test]
+    fn should_select_asset_for_current_os() {
+        let assets = vec![
+            asset("repo-aarch64-unknown-linux-gnu.zip"),
+            asset("repo-linux.zip"),
+            asset("repo-x86_64-unknown-linux-gnu.tar.gz"),
+            asset("repo-x86_64-unknown-linux-gnu.tar.gz.sha256"),
+            asset("repo-x86_64-apple-darwin.zip"),
+            asset("repo-x86_64-pc-windows-msvc.zip"),
+            asset("repo-windows-x64.exe"),
+        ];
+        let expected = if cfg!(target_os = "windows") {
+            "repo-x86_64-pc-windows-msvc.zip"
+        } else if cfg!(target_os = "macos") {
+            "repo-x86_64-apple-darwin.zip"
+        } else {
+            "repo-x86_64-unknown-linux-gnu.tar.gz"
+        };
+        assert_eq!(select_asset(&assets, None).map(|a| a.name.as_str()), Some(expected));
+    }
+
+    #[test]
+    fn should_select_aarch64_asset() {
+        let assets = vec![
+            asset("repo-aarch64-unknown-linux-gnu.zip"),
+            asset("repo-x86_64-pc-windows-msvc.zip"),
+            asset("repo-aarch64-pc-windows-msvc.exe"),
+            asset("repo-aarch64-pc-windows-msvc.zip"),
+        ];
+        assert_eq!(
+            select_aarch64_asset(&assets, &WINDOWS_RE).map(|a| a.name.as_str()),
+            Some("repo-aarch64-pc-windows-msvc.zip")
+        );
+        assert!(select_aarch64_asset(&assets[..2], &WINDOWS_RE).is_none());
+        assert!(select_aarch64_asset(&assets, &MAC_RE).is_none());
+        let assets = vec![
+            asset("repo-x86_64-apple-darwin.tar.gz"),
+            asset("repo-arm64-macos.tar.gz"),
+        ];
+        assert_eq!(
+            select_aarch64_asset(&assets, &MAC_RE).map(|a| a.name.as_str()),
+            Some("repo-arm64-macos.tar.gz")
+        );
+    }
+
+    #[test]
+    fn should_get_asset_platform_key() {
+        assert_eq!(
+            get_asset_platform_key("repo-x86_64-unknown-linux-gnu.tar.gz"),
+            Some("linux-x86_64")
+        );
+        assert_eq!(
+            get_asset_platform_key("repo-x86_64-unknown-linux-musl.zip"),
+            Some("linux-x86_64-musl")
+        );
+        assert_eq!(get_asset_platform_key("repo-aarch64-unknown-linux-gnu.zip"), None);
+        assert_eq!(
+            get_asset_platform_key("repo-x86_64-apple-darwin.zip"),
+            Some("darwin-x86_64")
+        );
+        assert_eq!(
+            get_asset_platform_key("repo-arm64-macos.tar.gz"),
+            Some("darwin-aarch64")
+        );
+        assert_eq!(get_asset_platform_key("repo-windows-x64.exe"), Some("windows-x86_64"));
+        assert_eq!(
+            get_asset_platform_key("repo-aarch64-pc-windows-msvc.zip"),
+            Some("windows-aarch64")
+        );
+        assert_eq!(get_asset_platform_key("repo-i686-pc-windows-msvc.zip"), None);
+        assert_eq!(
+            get_asset_platform_key("repo-x86_64-unknown-linux-gnu.tar.gz.sha256"),
+            None
+        );
+    }
+
+    #[test]
+    fn should_select_asset_with_pattern() {
+        let assets = vec![asset("repo-linux-gnu.zip"), asset("repo-linux-musl.zip")];
+        let pattern = Regex::new("musl").unwrap();
+        assert_eq!(
+            select_asset(&assets, Some(&pattern)).map(|a| a.name.as_str()),
+            Some("repo-linux-musl.zip")
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use dprint_cli_core::types::ErrBox;
+use std::path::{Component, Path, PathBuf};
+
+use crate::environment::Environment;
+use crate::types::CommandName;
+use crate::utils;
+
+/// Directory in the binary's directory that the installer is downloaded to and expanded in while it's set up.
+const INSTALLER_DIR_NAME: &str = ".bvm-installer";
+
+/// Installs a Windows Installer package with an administrative install, which extracts its files into the
+/// binary's directory without registering the product with the system or requiring elevation.
+pub(super) fn install_msi_package<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    display_name: &str,
+    target: &str,
+    installer_bytes: &[u8],
+    dir_path: &Path,
+) -> Result<(), ErrBox> {
+    if !target.starts_with("windows-") {
+        return err!(
+            "The msi download type may only be used for windows targets, but was used for {}.",
+            target
+        );
+    }
+    if utils::get_path_executable_path(environment, &CommandName::from_string("msiexec".to_string())).is_none() {
+        return err!("Could not find msiexec on the path. Installing msi installers requires Windows.");
+    }
+
+    let installer_dir_path = dir_path.join(INSTALLER_DIR_NAME);
+    environment.create_dir_all(&installer_dir_path)?;
+    environment.write_file(&installer_dir_path.join("installer.msi"), installer_bytes)?;
+    let result = environment.run_shell_command(
+        dir_path,
+        &format!(
+            "start \"\" /wait msiexec /a {}\\installer.msi /qn TARGETDIR=\"{}\"",
+            INSTALLER_DIR_NAME,
+            dir_path.display()
+        ),
+    );
+    // an administrative install also copies the installer into the target directory
+    let _ignore = environment.remove_file(&dir_path.join("installer.msi"));
+    environment.remove_dir_all(&installer_dir_path)?;
+    if let Err(err) = result {
+        return err!(
+            "Could not install {} into an isolated directory because its msi installer doesn't support an administrative install into a target directory. {}",
+            display_name,
+            err.to_string()
+        );
+    }
+    Ok(())
+}
+
+/// Expands a macOS installer package and moves the files of its payloads into the binary's directory. The
+/// package isn't run since `installer` only installs into the root of a volume, so packages that rely on
+/// installation scripts can't be installed in isolation and are refused.
+pub(super) fn install_pkg_package<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    display_name: &str,
+    target: &str,
+    installer_bytes: &[u8],
+    dir_path: &Path,
+) -> Result<(), ErrBox> {
+    if !target.starts_with("darwin-") {
+        return err!(
+            "The pkg download type may only be used for darwin targets, but was used for {}.",
+            target
+        );
+    }
+    if utils::get_path_executable_path(environment, &CommandName::from_string("pkgutil".to_string())).is_none() {
+        return err!("Could not find pkgutil on the path. Installing pkg installers requires macOS.");
+    }
+
+    let installer_dir_path = dir_path.join(INSTALLER_DIR_NAME);
+    environment.create_dir_all(&installer_dir_path)?;
+    environment.write_file(&installer_dir_path.join("installer.pkg"), installer_bytes)?;
+    let result = environment
+        .run_shell_command(
+            dir_path,
+            &format!(
+                "pkgutil --expand-full {0}/installer.pkg {0}/expanded",
+                INSTALLER_DIR_NAME
+            ),
+        )
+        .and_then(|_| {
+            move_pkg_payload_files(
+                environment,
+                display_name,
+                &installer_dir_path.join("expanded"),
+                dir_path,
+            )
+        });
+    environment.remove_dir_all(&installer_dir_path)?;
+    result
+}
+
+fn move_pkg_payload_files<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    display_name: &str,
+    expanded_dir_path: &Path,
+    dir_path: &Path,
+) -> Result<(), ErrBox> {
+    let mut payload_files = Vec::new();
+    for file_path in environment.get_file_paths(expanded_dir_path)? {
+        let relative_path = file_path.strip_prefix(expanded_dir_path)?;
+        let components = relative_path.components().collect::<Vec<_>>();
+        // the payload is at the root of a flat component package or in each component of a product package
+        let payload_index = components
+            .iter()
+            .position(|c| c == &Component::Normal("Payload".as_ref()));
+        let scripts_index = components
+            .iter()
+            .position(|c| c == &Component::Normal("Scripts".as_ref()));
+        match (payload_index, scripts_index) {
+            (Some(payload_index), Some(scripts_index)) if payload_index < scripts_index => {}
+            (_, Some(_)) => {
+                return err!(
+                "Could not install {} into an isolated directory because its pkg installer runs installation scripts.",
+                display_name
+            )
+            }
+            _ => {}
+        }
+        if let Some(payload_index) = payload_index {
+            let payload_path = components[payload_index + 1..].iter().collect::<PathBuf>();
+            payload_files.push((file_path.clone(), dir_path.join(payload_path)));
+        }
+    }
+
+    if payload_files.is_empty() {
+        return err!("Could not find any files in the pkg installer for {}.", display_name);
+    }
+    for (file_path, new_file_path) in payload_files {
+        if let Some(parent_dir_path) = new_file_path.parent() {
+            environment.create_dir_all(parent_dir_path)?;
+        }
+        // renaming keeps the file permissions
+        environment.rename(&file_path, &new_file_path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+mod app_image;
+mod archive_plugin_file;
+mod cargo_crate;
+mod create_shim;
+mod get_plugin_file;
+mod github_release;
+mod installer_package;
+mod npm_package;
+mod pack_plugin_file;
+mod read_plugin_file;
+mod setup_plugin;
+mod trusted_hosts;
+mod validate_plugin_file;
+
+use app_image::*;
+pub use archive_plugin_file::*;
+pub use cargo_crate::is_cargo_crate_url;
+use cargo_crate::*;
+pub use create_shim::*;
+pub use get_plugin_file::*;
+use github_release::*;
+pub use github_release::{get_github_release, is_github_release_url, GitHubReleaseSpecifier};
+use installer_package::*;
+pub use npm_package::is_npm_package_url;
+use npm_package::*;
+pub use pack_plugin_file::*;
+use read_plugin_file::*;
+pub use setup_plugin::*;
+pub use trusted_hosts::verify_trusted_host;
+pub use validate_plugin_file::*;
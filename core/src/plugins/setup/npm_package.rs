@@ -0,0 +1,270 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::{PluginFile, PLUGIN_FILE_SCHEMA_VERSION};
+use crate::environment::Environment;
+use crate::plugins::{PlatformInfo, PlatformInfoCommand, SerializedPluginFile};
+use crate::types::{CommandName, Version};
+use crate::utils;
+
+const NPM_PACKAGE_PREFIX: &str = "npm:";
+
+/// An npm package to install the bin entries of (ex. `npm:typescript@4.0.5`).
+#[derive(Debug, PartialEq)]
+pub struct NpmPackageSpecifier {
+    pub package_name: String,
+    /// The exact version or `None` for the latest version.
+    pub version: Option<String>,
+}
+
+impl NpmPackageSpecifier {
+    pub fn parse(text: &str) -> Result<NpmPackageSpecifier, ErrBox> {
+        let invalid_err = || {
+            err_obj!(
+                "Invalid npm package '{}'. Expected the format `npm:package@version` (ex. `npm:typescript@4.0.5`).",
+                text
+            )
+        };
+        if !is_npm_package_url(text) {
+            return Err(invalid_err());
+        }
+        let text_without_prefix = &text[NPM_PACKAGE_PREFIX.len()..];
+        // skip past the @ of a scoped package
+        let (package_name, version) = match text_without_prefix.get(1..).and_then(|t| t.rfind('@')) {
+            Some(index) => (
+                &text_without_prefix[..index + 1],
+                Some(text_without_prefix[index + 2..].to_string()),
+            ),
+            None => (text_without_prefix, None),
+        };
+        if !is_valid_package_name(package_name) || version.as_deref() == Some("") {
+            return Err(invalid_err());
+        }
+
+        Ok(NpmPackageSpecifier {
+            package_name: package_name.to_string(),
+            version,
+        })
+    }
+
+    /// Gets the owner and name of the binary for the package (`npm/<name>` or `<scope>/<name>` for scoped packages).
+    fn get_owner_and_name(&self) -> (String, String) {
+        match self
+            .package_name
+            .strip_prefix('@')
+            .and_then(|name| name.find('/').map(|i| (name, i)))
+        {
+            Some((name, index)) => (name[..index].to_string(), name[index + 1..].to_string()),
+            None => ("npm".to_string(), self.package_name.clone()),
+        }
+    }
+}
+
+pub fn is_npm_package_url(text: &str) -> bool {
+    text.starts_with(NPM_PACKAGE_PREFIX)
+}
+
+fn is_valid_package_name(name: &str) -> bool {
+    let name_without_scope = match name.strip_prefix('@') {
+        Some(name) => match name.find('/') {
+            Some(index) if index > 0 => &name[index + 1..],
+            _ => return false,
+        },
+        None => name,
+    };
+    !name_without_scope.is_empty()
+        && name_without_scope
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+#[derive(Deserialize)]
+struct NpmPackageVersion {
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct PackageJson {
+    #[serde(default)]
+    bin: Option<serde_json::Value>,
+}
+
+/// Creates a plugin file for the npm package that installs it with npm and exposes its bin entries as commands.
+pub fn get_npm_package_plugin_file<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    url: &str,
+) -> Result<PluginFile, ErrBox> {
+    let specifier = NpmPackageSpecifier::parse(url)?;
+    let version = match &specifier.version {
+        Some(version) => version.clone(),
+        None => {
+            let latest_url = format!(
+                "https://registry.npmjs.org/{}/latest",
+                specifier.package_name.replace('/', "%2F")
+            );
+            let latest: NpmPackageVersion = match serde_json::from_slice(&environment.download_file(&latest_url)?) {
+                Ok(latest) => latest,
+                Err(err) => return err!("Error deserializing {}. {}", latest_url, err.to_string()),
+            };
+            latest.version
+        }
+    };
+    let version = match Version::parse(&version) {
+        Ok(version) => version,
+        Err(_) => {
+            return err!(
+                "Expected an exact version for npm package {}, but found '{}'.",
+                specifier.package_name,
+                version
+            )
+        }
+    };
+    let platform_info = PlatformInfo {
+        path: format!("{}@{}", specifier.package_name, version),
+        mirrors: Vec::new(),
+        checksum: String::new(),
+        download_type: "npm".to_string(),
+        output_dir: None,
+        commands: Vec::new(),
+        on_pre_install: None,
+        on_post_install: None,
+        on_pre_uninstall: None,
+        environment: None,
+    };
+    let (owner, name) = specifier.get_owner_and_name();
+    let file = SerializedPluginFile {
+        schema_version: PLUGIN_FILE_SCHEMA_VERSION,
+        name,
+        owner,
+        version,
+        description: format!("npm package {}.", specifier.package_name),
+        linux: Some(platform_info.clone()),
+        linux_musl: Some(platform_info.clone()),
+        mac: Some(platform_info.clone()),
+        mac_aarch64: Some(platform_info.clone()),
+        windows: Some(platform_info.clone()),
+        windows_aarch64: Some(platform_info),
+        smoke_test: None,
+    };
+
+    Ok(PluginFile {
+        url: url.to_string(),
+        checksum: get_sha256_checksum(&serde_json::to_vec(&file)?),
+        file,
+        detect_commands: true,
+        has_provided_checksums: true,
+    })
+}
+
+/// Installs the npm package (ex. `typescript@4.0.5`) into the provided directory.
+pub(super) fn install_npm_package<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    package: &str,
+    dir_path: &Path,
+) -> Result<(), ErrBox> {
+    if utils::get_path_executable_path(environment, &CommandName::from_string("npm".to_string())).is_none() {
+        return err!("Could not find npm on the path. Install Node.js to install npm packages.");
+    }
+
+    environment.run_shell_command(
+        dir_path,
+        &format!("npm install --prefix . --no-save --no-package-lock {}", package),
+    )
+}
+
+/// Gets the commands of the bin entries in the installed npm package's package.json.
+pub(super) fn get_npm_package_commands<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    package: &str,
+    dir_path: &Path,
+) -> Result<Vec<PlatformInfoCommand>, ErrBox> {
+    let package_name = &package[..package[1..].rfind('@').map(|i| i + 1).unwrap_or(package.len())];
+    let package_json_path = dir_path.join("node_modules").join(package_name).join("package.json");
+    let package_json: PackageJson = match serde_json::from_slice(&environment.read_file(&package_json_path)?) {
+        Ok(package_json) => package_json,
+        Err(err) => {
+            return err!(
+                "Error deserializing {}. {}",
+                package_json_path.display(),
+                err.to_string()
+            )
+        }
+    };
+    let bin_names = match package_json.bin {
+        Some(serde_json::Value::String(_)) => vec![package_name.rsplit('/').next().unwrap().to_string()],
+        Some(serde_json::Value::Object(obj)) => obj.into_iter().map(|(key, _)| key).collect(),
+        _ => Vec::new(),
+    };
+    if bin_names.is_empty() {
+        return err!("The npm package {} does not have any bin entries.", package);
+    }
+
+    Ok(bin_names
+        .into_iter()
+        .map(|name| {
+            let file_name = if cfg!(target_os = "windows") {
+                format!("{}.cmd", name)
+            } else {
+                name.clone()
+            };
+            PlatformInfoCommand {
+                path: PathBuf::from("node_modules")
+                    .join(".bin")
+                    .join(file_name)
+                    .to_string_lossy()
+                    .to_string(),
+                name: CommandName::from_string(name),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_specifier() {
+        assert_eq!(
+            NpmPackageSpecifier::parse("npm:typescript@4.0.5").unwrap(),
+            NpmPackageSpecifier {
+                package_name: "typescript".to_string(),
+                version: Some("4.0.5".to_string()),
+            }
+        );
+        assert_eq!(
+            NpmPackageSpecifier::parse("npm:@scope/package").unwrap(),
+            NpmPackageSpecifier {
+                package_name: "@scope/package".to_string(),
+                version: None,
+            }
+        );
+        assert_eq!(
+            NpmPackageSpecifier::parse("npm:@scope/package@1.0.0").unwrap(),
+            NpmPackageSpecifier {
+                package_name: "@scope/package".to_string(),
+                version: Some("1.0.0".to_string()),
+            }
+        );
+        assert!(NpmPackageSpecifier::parse("npm:").is_err());
+        assert!(NpmPackageSpecifier::parse("npm:@scope").is_err());
+        assert!(NpmPackageSpecifier::parse("npm:package@").is_err());
+        assert!(NpmPackageSpecifier::parse("npm:package && rm").is_err());
+    }
+
+    #[test]
+    fn should_get_owner_and_name() {
+        let specifier = NpmPackageSpecifier::parse("npm:@scope/package").unwrap();
+        assert_eq!(
+            specifier.get_owner_and_name(),
+            ("scope".to_string(), "package".to_string())
+        );
+        let specifier = NpmPackageSpecifier::parse("npm:typescript").unwrap();
+        assert_eq!(
+            specifier.get_owner_and_name(),
+            ("npm".to_string(), "typescript".to_string())
+        );
+    }
+}
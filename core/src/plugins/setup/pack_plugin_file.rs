@@ -0,0 +1,173 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{
+    get_asset_platform_key, get_download_type_from_file_name, verify_trusted_host, PLUGIN_FILE_SCHEMA_VERSION,
+};
+use crate::environment::Environment;
+use crate::plugins::{PlatformInfo, PlatformInfoCommand, SerializedPluginFile, PLATFORM_KEYS};
+use crate::types::{BinaryName, CommandName, Version};
+
+/// Describes a release of a binary to create a binary manifest file for with `bvm registry pack`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PackSpec {
+    pub owner: String,
+    pub name: String,
+    pub version: Version,
+    #[serde(default)]
+    pub description: String,
+    /// Url the binary manifest file will be hosted at.
+    pub url: String,
+    /// Urls of the release assets by platform key.
+    #[serde(default)]
+    pub platforms: BTreeMap<String, String>,
+    /// Directory of release assets, relative to the spec file, whose platforms are detected from their file names.
+    pub assets_dir: Option<String>,
+    /// Url the files of the assets directory will be hosted at.
+    pub assets_url: Option<String>,
+    /// Commands of the binary. Defaults to an executable with the name of the binary.
+    #[serde(default)]
+    pub commands: Vec<PlatformInfoCommand>,
+}
+
+impl PackSpec {
+    pub fn get_binary_name(&self) -> BinaryName {
+        BinaryName::new(self.owner.clone(), self.name.clone())
+    }
+}
+
+/// Reads the spec file of `bvm registry pack`.
+pub fn read_pack_spec(environment: &impl Environment, file_path: &Path) -> Result<PackSpec, ErrBox> {
+    let file_bytes = environment.read_file(file_path)?;
+    match serde_json::from_slice::<PackSpec>(&file_bytes) {
+        Ok(spec) => Ok(spec),
+        Err(err) => err!("Error deserializing {}. {}", file_path.display(), err.to_string()),
+    }
+}
+
+/// Creates the binary manifest file of the spec, computing the checksums of its release assets.
+pub fn pack_plugin_file(
+    environment: &impl Environment,
+    spec_dir: &Path,
+    spec: &PackSpec,
+) -> Result<SerializedPluginFile, ErrBox> {
+    if spec.name.contains('/') || spec.owner.contains('/') {
+        return err!("The binary owner and name may not contain a forward slash.");
+    }
+
+    // (platform key, url, file bytes)
+    let mut assets: Vec<(&str, String, Vec<u8>)> = Vec::new();
+    for (platform_key, url) in spec.platforms.iter() {
+        let platform_key = match PLATFORM_KEYS.iter().find(|key| *key == platform_key) {
+            Some(key) => *key,
+            None => {
+                return err!(
+                    "Unknown platform '{}'. Expected one of: {}",
+                    platform_key,
+                    PLATFORM_KEYS.join(", ")
+                )
+            }
+        };
+        verify_trusted_host(environment, url)?;
+        assets.push((platform_key, url.clone(), environment.download_file(url)?));
+    }
+    if let Some(assets_dir) = &spec.assets_dir {
+        let assets_url = match &spec.assets_url {
+            Some(assets_url) => assets_url.trim_end_matches('/'),
+            None => return err!("Expected an 'assetsUrl' that the files of 'assetsDir' will be hosted at."),
+        };
+        let assets_dir = spec_dir.join(assets_dir);
+        let mut file_paths = environment.get_file_paths(&assets_dir)?;
+        file_paths.sort();
+        for file_path in file_paths.into_iter().filter(|path| path.parent() == Some(&assets_dir)) {
+            let file_name = file_path.file_name().unwrap().to_string_lossy().to_string();
+            let platform_key = match get_asset_platform_key(&file_name) {
+                Some(platform_key) => platform_key,
+                None => continue,
+            };
+            if let Some((_, other_url, _)) = assets.iter().find(|(key, _, _)| *key == platform_key) {
+                return err!(
+                    "Found multiple assets for {}: {} and {}. Specify the asset of the platform in 'platforms' instead.",
+                    platform_key,
+                    other_url,
+                    file_name
+                );
+            }
+            let bytes = environment.read_file(&file_path)?;
+            assets.push((platform_key, format!("{}/{}", assets_url, file_name), bytes));
+        }
+    }
+    if assets.is_empty() {
+        return err!("Could not find any release assets for {}.", spec.get_binary_name());
+    }
+
+    let mut file = SerializedPluginFile {
+        schema_version: PLUGIN_FILE_SCHEMA_VERSION,
+        name: spec.name.clone(),
+        owner: spec.owner.clone(),
+        version: spec.version.clone(),
+        description: spec.description.clone(),
+        linux: None,
+        linux_musl: None,
+        mac: None,
+        mac_aarch64: None,
+        windows: None,
+        windows_aarch64: None,
+        smoke_test: None,
+    };
+    for (platform_key, url, bytes) in assets {
+        let download_type = match get_download_type_from_file_name(&url) {
+            Some(download_type) => download_type,
+            None => return err!("Unsupported archive type: {}", url),
+        };
+        let platform_info = PlatformInfo {
+            path: url,
+            mirrors: Vec::new(),
+            checksum: get_sha256_checksum(&bytes),
+            download_type: download_type.to_string(),
+            output_dir: None,
+            commands: get_platform_commands(spec, platform_key.starts_with("windows")),
+            on_pre_install: None,
+            on_post_install: None,
+            on_pre_uninstall: None,
+            environment: None,
+        };
+        let platform = match platform_key {
+            "linux-x86_64" => &mut file.linux,
+            "linux-x86_64-musl" => &mut file.linux_musl,
+            "darwin-x86_64" => &mut file.mac,
+            "darwin-aarch64" => &mut file.mac_aarch64,
+            "windows-x86_64" => &mut file.windows,
+            _ => &mut file.windows_aarch64,
+        };
+        *platform = Some(platform_info);
+    }
+    Ok(file)
+}
+
+/// Gets the commands of a platform, where command paths without an extension get `.exe` on Windows.
+fn get_platform_commands(spec: &PackSpec, is_windows: bool) -> Vec<PlatformInfoCommand> {
+    let commands = if spec.commands.is_empty() {
+        vec![PlatformInfoCommand {
+            name: CommandName::from_string(spec.name.clone()),
+            path: spec.name.clone(),
+        }]
+    } else {
+        spec.commands.clone()
+    };
+    commands
+        .into_iter()
+        .map(|command| PlatformInfoCommand {
+            path: if is_windows && Path::new(&command.path).extension().is_none() {
+                format!("{}.exe", command.path)
+            } else {
+                command.path
+            },
+            name: command.name,
+        })
+        .collect()
+}
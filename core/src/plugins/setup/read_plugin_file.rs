@@ -0,0 +1,267 @@
+use dprint_cli_core::types::ErrBox;
+use serde_json::{Map, Value};
+
+use crate::errors::ErrorCode;
+use crate::plugins::{SerializedPluginFile, PLATFORM_KEYS};
+use crate::types::Version;
+use crate::utils::get_supported_schema_version;
+
+/// Latest schema version of binary manifest files that this version of bvm reads.
+pub const PLUGIN_FILE_SCHEMA_VERSION: u32 = 1;
+
+/// Reads a plugin file, selecting the provided version when the file describes multiple versions.
+pub fn read_plugin_file(file_bytes: &[u8], version: Option<&str>) -> Result<SerializedPluginFile, ErrBox> {
+    let value = match serde_json::from_slice::<Value>(&file_bytes) {
+        Ok(value) => expand_plugin_file(upgrade_plugin_file(value)?, version)?,
+        Err(err) => {
+            return coded_err!(
+                ErrorCode::InvalidConfig,
+                "Error deserializing binary manifest file. {}",
+                err.to_string()
+            )
+        }
+    };
+
+    match serde_json::from_value::<SerializedPluginFile>(value) {
+        Ok(file) => {
+            if file.name.contains("/") || file.owner.contains("/") {
+                return err!("The binary owner and name may not contain a forward slash.");
+            }
+
+            Ok(file)
+        }
+        Err(err) => coded_err!(
+            ErrorCode::InvalidConfig,
+            "Error deserializing binary manifest file. {}",
+            err.to_string()
+        ),
+    }
+}
+
+/// Checks the schema version of a plugin file and upgrades files of older schemas to the latest one.
+fn upgrade_plugin_file(value: Value) -> Result<Value, ErrBox> {
+    let mut file = match value {
+        Value::Object(file) => file,
+        _ => return err!("Expected the binary manifest file to be an object."),
+    };
+    // the only older files are the ones without a schema version, which have the same structure
+    get_supported_schema_version(&file, "binary manifest file", PLUGIN_FILE_SCHEMA_VERSION)?;
+    file.insert("schemaVersion".to_string(), PLUGIN_FILE_SCHEMA_VERSION.into());
+    Ok(Value::Object(file))
+}
+
+/// Transforms a plugin file to describe a single version with its placeholders substituted.
+///
+/// A plugin file may describe multiple versions by replacing `version` with a `versions` array
+/// of objects containing a version and its checksum per platform key. The latest non-pre-release
+/// version is selected when no version is provided.
+pub fn expand_plugin_file(value: Value, version: Option<&str>) -> Result<Value, ErrBox> {
+    let mut file = match value {
+        Value::Object(file) => file,
+        _ => return err!("Expected the binary manifest file to be an object."),
+    };
+
+    if let Some(versions) = file.remove("versions") {
+        let entry = select_version_entry(versions, version)?;
+        let checksums = entry
+            .get("checksums")
+            .and_then(|c| c.as_object())
+            .cloned()
+            .unwrap_or_default();
+        file.insert("version".to_string(), entry["version"].clone());
+        for platform_key in PLATFORM_KEYS.iter() {
+            let platform = match file.get_mut(*platform_key).and_then(|p| p.as_object_mut()) {
+                Some(platform) => platform,
+                None => continue,
+            };
+            match checksums.get(*platform_key) {
+                Some(checksum) => {
+                    platform.insert("checksum".to_string(), checksum.clone());
+                }
+                // the platform wasn't released for this version
+                None if !platform.contains_key("checksum") => {
+                    file.remove(*platform_key);
+                }
+                None => {}
+            }
+        }
+    } else if let Some(version) = version {
+        if file.get("version").and_then(|v| v.as_str()) != Some(version) {
+            return coded_err!(
+                ErrorCode::VersionNotFound,
+                "Could not find version {} in the binary manifest file.",
+                version
+            );
+        }
+    }
+
+    let version = match file.get("version").and_then(|v| v.as_str()) {
+        Some(version) => version.to_string(),
+        None => return Ok(Value::Object(file)),
+    };
+    for platform_key in PLATFORM_KEYS.iter() {
+        if let Some(platform) = file.get_mut(*platform_key) {
+            let mut parts = platform_key.split('-');
+            let os = parts.next().unwrap();
+            let arch = parts.next().unwrap();
+            substitute_placeholders(platform, &[("version", &version), ("os", os), ("arch", arch)]);
+        }
+    }
+
+    Ok(Value::Object(file))
+}
+
+fn select_version_entry(versions: Value, version: Option<&str>) -> Result<Map<String, Value>, ErrBox> {
+    let versions = match versions {
+        Value::Array(versions) => versions,
+        _ => return err!("Expected the binary manifest file's versions to be an array."),
+    };
+    let mut entries = Vec::new();
+    for entry in versions.iter() {
+        let entry = match entry.as_object() {
+            Some(entry) => entry,
+            None => return err!("Expected each item in the binary manifest file's versions to be an object."),
+        };
+        match entry.get("version").and_then(|v| v.as_str()) {
+            Some(entry_version) => entries.push((Version::parse(entry_version)?, entry)),
+            None => return err!("Expected each item in the binary manifest file's versions to have a version."),
+        }
+    }
+
+    let selected = match version {
+        Some(version) => entries
+            .into_iter()
+            .find(|(entry_version, _)| entry_version.as_str() == version),
+        None => {
+            let (pre_releases, releases): (Vec<_>, Vec<_>) = entries
+                .into_iter()
+                .partition(|(entry_version, _)| entry_version.is_prerelease());
+            let latest_release = releases.into_iter().max_by(|a, b| a.0.cmp(&b.0));
+            latest_release.or_else(|| pre_releases.into_iter().max_by(|a, b| a.0.cmp(&b.0)))
+        }
+    };
+    match selected {
+        Some((_, entry)) => Ok(entry.clone()),
+        None => match version {
+            Some(version) => coded_err!(
+                ErrorCode::VersionNotFound,
+                "Could not find version {} in the binary manifest file.",
+                version
+            ),
+            None => err!("Expected the binary manifest file's versions to contain at least one version."),
+        },
+    }
+}
+
+/// Replaces the `{{name}}` placeholders in all the strings of the provided value.
+fn substitute_placeholders(value: &mut Value, replacements: &[(&str, &str)]) {
+    match value {
+        Value::String(text) if text.contains("{{") => {
+            for (name, replacement) in replacements.iter() {
+                *text = text.replace(&format!("{{{{{}}}}}", name), replacement);
+            }
+        }
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                substitute_placeholders(item, replacements);
+            }
+        }
+        Value::Object(obj) => {
+            for (_, item) in obj.iter_mut() {
+                substitute_placeholders(item, replacements);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const MULTI_VERSION_FILE: &str = r#"{
+    "schemaVersion": 1,
+    "name": "name",
+    "owner": "owner",
+    "description": "Some description.",
+    "versions": [{
+        "version": "1.0.0",
+        "checksums": { "linux-x86_64": "a" }
+    }, {
+        "version": "1.1.0",
+        "checksums": { "linux-x86_64": "b", "windows-x86_64": "c" }
+    }, {
+        "version": "2.0.0-beta",
+        "checksums": { "linux-x86_64": "d" }
+    }],
+    "linux-x86_64": {
+        "path": "https://localhost/v{{version}}/name-{{os}}-{{arch}}.zip",
+        "type": "zip",
+        "commands": [{ "name": "name", "path": "name" }]
+    },
+    "windows-x86_64": {
+        "path": "https://localhost/v{{version}}/name-{{os}}-{{arch}}.zip",
+        "type": "zip",
+        "commands": [{ "name": "name", "path": "name.exe" }]
+    }
+}"#;
+
+    #[test]
+    fn should_select_version_from_multi_version_file() {
+        let file = read_plugin_file(MULTI_VERSION_FILE.as_bytes(), Some("1.0.0")).unwrap();
+        assert_eq!(file.version, Version::parse("1.0.0").unwrap());
+        let linux = file.linux.unwrap();
+        assert_eq!(linux.path, "https://localhost/v1.0.0/name-linux-x86_64.zip");
+        assert_eq!(linux.checksum, "a");
+        // not released for windows in this version
+        assert_eq!(file.windows, None);
+    }
+
+    #[test]
+    fn should_select_latest_release_from_multi_version_file() {
+        let file = read_plugin_file(MULTI_VERSION_FILE.as_bytes(), None).unwrap();
+        assert_eq!(file.version, Version::parse("1.1.0").unwrap());
+        let windows = file.windows.unwrap();
+        assert_eq!(windows.path, "https://localhost/v1.1.0/name-windows-x86_64.zip");
+        assert_eq!(windows.checksum, "c");
+    }
+
+    #[test]
+    fn should_error_for_missing_version() {
+        let err = read_plugin_file(MULTI_VERSION_FILE.as_bytes(), Some("3.0.0"))
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Could not find version 3.0.0 in the binary manifest file."
+        );
+    }
+
+    #[test]
+    fn should_substitute_placeholders_in_single_version_file() {
+        let file = read_plugin_file(
+            r#"{
+    "schemaVersion": 1,
+    "name": "name",
+    "owner": "owner",
+    "version": "1.2.3",
+    "description": "Some description.",
+    "darwin-x86_64": {
+        "path": "https://localhost/{{version}}/name-{{os}}.tar.gz",
+        "type": "tar.gz",
+        "checksum": "a",
+        "outputDir": "name-{{version}}",
+        "commands": [{ "name": "name", "path": "name-{{version}}/bin/name" }]
+    }
+}"#
+            .as_bytes(),
+            None,
+        )
+        .unwrap();
+        let mac = file.mac.unwrap();
+        assert_eq!(mac.path, "https://localhost/1.2.3/name-darwin.tar.gz");
+        assert_eq!(mac.output_dir, Some("name-1.2.3".to_string()));
+        assert_eq!(mac.commands[0].path, "name-1.2.3/bin/name");
+    }
+}
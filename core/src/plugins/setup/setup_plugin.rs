@@ -0,0 +1,1008 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::{
+    create_shim, get_npm_package_commands, install_app_image, install_cargo_crate, install_msi_package,
+    install_npm_package, install_pkg_package, verify_trusted_host,
+};
+use crate::environment::{read_user_config, DownloadCancelledError, Environment, Event};
+use crate::plugins::{
+    get_platform_keys, get_plugin_dir, get_plugin_staging_dir, get_writable_plugin_dir, is_content_store_enabled,
+    link_dir_files_from_store, write_integrity_manifest, BinaryIdentifier, BinaryManifestItem,
+    BinaryManifestItemCommand, BinaryManifestItemSource, BinaryProvenance, PendingExtraction, PlatformInfo,
+    PlatformInfoCommand, SerializedPluginFile, SmokeTest, PLATFORM_KEYS,
+};
+use crate::registry::find_registry_url_of_binary;
+use crate::types::{BinaryName, CommandName, Version};
+use crate::utils;
+
+/// Name of the file in a binary's directory that stores its archive until it's extracted on first use.
+const PENDING_ARCHIVE_FILE_NAME: &str = ".bvm-archive";
+
+pub struct PluginFile {
+    // todo: move these two properties down into PluginFile
+    pub url: String,
+    pub checksum: String,
+
+    pub(super) file: SerializedPluginFile,
+    /// Whether the commands should be detected from the executables in the extracted archive.
+    pub(super) detect_commands: bool,
+    /// Whether the archive checksums were provided rather than computed from a download.
+    pub(super) has_provided_checksums: bool,
+}
+
+pub enum DownloadType {
+    Zip,
+    Binary,
+    TarGz,
+    /// An npm package that is installed with npm.
+    Npm,
+    /// A crate that is installed with `cargo install`.
+    Cargo,
+    /// A Windows Installer package that's extracted with an administrative install.
+    Msi,
+    /// A macOS installer package whose payload is expanded.
+    Pkg,
+    /// A Linux AppImage that's made executable and extracted when FUSE isn't available.
+    AppImage,
+}
+
+impl PluginFile {
+    pub fn display(&self) -> String {
+        format!("{}/{} {}", self.file.owner, self.file.name, self.file.version)
+    }
+
+    pub fn get_binary_name(&self) -> BinaryName {
+        BinaryName::new(self.file.owner.clone(), self.file.name.clone())
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.file.version
+    }
+
+    /// Gets the platform key and information of the first of the provided targets the plugin file supports.
+    fn get_platform_target(&self, platform_keys: &[&'static str]) -> Result<(&'static str, &PlatformInfo), ErrBox> {
+        for platform_key in platform_keys.iter() {
+            if let Some(platform_info) = self.file.get_platform_info(platform_key) {
+                return Ok((platform_key, platform_info));
+            }
+        }
+        Err(Box::new(UnsupportedPlatformError {
+            message: "Unsupported operating system.".to_string(),
+        }))
+    }
+
+    pub fn get_identifier(&self) -> BinaryIdentifier {
+        let binary_name = BinaryName::new(self.file.owner.clone(), self.file.name.clone());
+        BinaryIdentifier::new(&binary_name, &self.file.version)
+    }
+
+    /// Gets the path of what's downloaded to install the binary for the target or the current system.
+    pub fn get_download_path(&self, environment: &impl Environment, target: Option<&str>) -> Result<String, ErrBox> {
+        let (_, platform_info) = select_platform(environment, self, target)?;
+        Ok(platform_info.path.clone())
+    }
+}
+
+/// Downloads the file of the platform from its path, falling back to its mirrors in order when
+/// the download fails or doesn't match the checksum. Returns the bytes and the url they were downloaded from.
+fn download_platform_file(
+    environment: &impl Environment,
+    plugin_file: &PluginFile,
+    platform_info: &PlatformInfo,
+) -> Result<(Vec<u8>, String), ErrBox> {
+    download_with_mirrors(environment, plugin_file, platform_info, |url| {
+        let url_file_bytes = environment.download_file(url)?;
+        // an empty checksum is of a file whose checksum wasn't provided (ex. a GitHub release asset without a digest)
+        if !platform_info.checksum.is_empty() {
+            utils::verify_download_checksum(environment, url, &url_file_bytes, &platform_info.checksum)?;
+        }
+        Ok((url_file_bytes, url.to_string()))
+    })
+}
+
+/// Extracts the platform's tar.gz archive into the directory while it's downloaded instead of after
+/// downloading all of it, which lowers the memory used for large archives. The extracted files are
+/// removed when the download fails or the archive doesn't match the checksum. Returns the url it was downloaded from.
+fn download_and_extract_tar_gz(
+    environment: &impl Environment,
+    plugin_file: &PluginFile,
+    platform_info: &PlatformInfo,
+    dir_path: &Path,
+) -> Result<String, ErrBox> {
+    download_with_mirrors(environment, plugin_file, platform_info, |url| {
+        let result = environment.download_file_streaming(url, &mut |reader| {
+            let mut reader = ChecksumReader {
+                reader,
+                hasher: Sha256::new(),
+            };
+            utils::extract_tar_gz_reader(environment, &mut reader, dir_path)?;
+            // the checksum is of the whole file, which may have padding after the end of the archive
+            std::io::copy(&mut reader, &mut std::io::sink())?;
+            let checksum = format!("{:x}", reader.hasher.finalize());
+            if checksum != platform_info.checksum {
+                // the archive was extracted while downloading, so there's no copy of it to quarantine
+                return Err(Box::new(utils::ChecksumMismatchError {
+                    url: url.to_string(),
+                    expected_checksum: platform_info.checksum.clone(),
+                    actual_checksum: checksum,
+                    quarantine_path: None,
+                }));
+            }
+            Ok(())
+        });
+        if result.is_err() {
+            environment.remove_dir_all(dir_path)?;
+            environment.create_dir_all(dir_path)?;
+        }
+        result.map(|_| url.to_string())
+    })
+}
+
+/// Reader that computes the checksum of the bytes read through it.
+struct ChecksumReader<'a> {
+    reader: &'a mut dyn Read,
+    hasher: Sha256,
+}
+
+impl<'a> Read for ChecksumReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let bytes_read = self.reader.read(buf)?;
+        self.hasher.update(&buf[..bytes_read]);
+        Ok(bytes_read)
+    }
+}
+
+fn download_with_mirrors<T>(
+    environment: &impl Environment,
+    plugin_file: &PluginFile,
+    platform_info: &PlatformInfo,
+    mut download: impl FnMut(&str) -> Result<T, ErrBox>,
+) -> Result<T, ErrBox> {
+    let mut last_failure: Option<(&String, ErrBox)> = None;
+    for url in std::iter::once(&platform_info.path).chain(platform_info.mirrors.iter()) {
+        if let Some((failed_url, err)) = last_failure.take() {
+            environment.log_error(&utils::yellow(&format!(
+                "Warning: Falling back to {} after failing to download {}: {}",
+                url, failed_url, err
+            )));
+        }
+        verify_trusted_host(environment, url)?;
+        environment.emit_event(Event::DownloadStarted {
+            name: plugin_file.get_binary_name(),
+            version: plugin_file.version().clone(),
+            url: url.to_string(),
+        });
+        match download(url) {
+            Ok(result) => return Ok(result),
+            // don't fall back to the mirrors when the download was cancelled
+            Err(err) if err.is::<DownloadCancelledError>() => return Err(err),
+            Err(err) => last_failure = Some((url, err)),
+        }
+    }
+    Err(last_failure.unwrap().1)
+}
+
+fn get_download_type(platform_info: &PlatformInfo) -> Result<DownloadType, ErrBox> {
+    parse_download_type(&platform_info.download_type)
+}
+
+fn parse_download_type(download_type: &str) -> Result<DownloadType, ErrBox> {
+    let download_type = download_type.to_lowercase();
+    Ok(match download_type.as_str() {
+        "zip" => DownloadType::Zip,
+        "binary" => DownloadType::Binary,
+        "tar.gz" => DownloadType::TarGz,
+        "npm" => DownloadType::Npm,
+        "cargo" => DownloadType::Cargo,
+        "msi" => DownloadType::Msi,
+        "pkg" => DownloadType::Pkg,
+        "appimage" => DownloadType::AppImage,
+        _ => return err!("Unknown download type: {}", download_type),
+    })
+}
+
+pub fn setup_plugin<'a, TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_file: &PluginFile,
+    target: Option<&str>,
+    require_checksums: Option<bool>,
+    lazy_extraction: Option<bool>,
+) -> Result<BinaryManifestItem, ErrBox> {
+    let staged_plugin = stage_plugin(environment, plugin_file, target, require_checksums, lazy_extraction)?;
+    install_staged_plugin(environment, plugin_file, staged_plugin)
+}
+
+/// A binary that was downloaded and set up in its staging directory, but not yet moved to its plugin directory.
+pub struct StagedPlugin {
+    target: &'static str,
+    platform_info: PlatformInfo,
+    has_target_override: bool,
+    is_lazy: bool,
+    source_url: String,
+    staging_dir_path: PathBuf,
+}
+
+/// Downloads and sets up the binary of the plugin file in its staging directory, which doesn't run
+/// anything that depends on the binary's final location (ex. the post install command).
+pub fn stage_plugin<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_file: &PluginFile,
+    target: Option<&str>,
+    require_checksums: Option<bool>,
+    lazy_extraction: Option<bool>,
+) -> Result<StagedPlugin, ErrBox> {
+    let has_target_override = get_target_override(environment, target)?.is_some();
+    let is_musl = !has_target_override && is_musl_host(environment);
+    let (target, platform_info) = select_platform(environment, plugin_file, target)?;
+    log_verbose!(environment, "Using the {} binary of {}.", target, plugin_file.display());
+    if is_musl && target == "linux-x86_64" {
+        environment.log_error(&utils::yellow(&format!(
+            "Warning: {} does not provide a binary for linux-x86_64-musl. Installed the linux-x86_64 binary, which may fail to run on this musl based system.",
+            plugin_file.display()
+        )));
+    }
+
+    let download_type = get_download_type(platform_info)?;
+    let is_lazy =
+        is_lazy_extraction_enabled(environment, lazy_extraction)? && can_extract_lazily(plugin_file, platform_info)?;
+    // download the url's bytes (packages are downloaded by their package manager and tar.gz archives are
+    // downloaded while they're extracted)
+    let mut source_url = platform_info.path.clone();
+    let url_file_bytes = match download_type {
+        DownloadType::Npm | DownloadType::Cargo => Some(Vec::new()),
+        _ => {
+            // a project can't opt out of the checksums the user requires
+            let require_checksums =
+                require_checksums.unwrap_or(false) || read_user_config(environment)?.require_checksums.unwrap_or(false);
+            if require_checksums && (platform_info.checksum.is_empty() || !plugin_file.has_provided_checksums) {
+                return err!(
+                    "Did not install {} because checksums are required and no checksum was provided for {}.",
+                    plugin_file.display(),
+                    platform_info.path
+                );
+            }
+            if should_stream_download(&download_type, is_lazy, platform_info) {
+                None
+            } else {
+                let (url_file_bytes, url) = download_platform_file(environment, plugin_file, platform_info)?;
+                source_url = url;
+                Some(url_file_bytes)
+            }
+        }
+    };
+
+    // set up the binary in a staging directory so that a failure doesn't leave a partially installed binary
+    let staging_dir_path = get_plugin_staging_dir(environment, &plugin_file.get_binary_name(), plugin_file.version());
+    let _ignore = environment.remove_dir_all(&staging_dir_path);
+    environment.create_dir_all(&staging_dir_path)?;
+    let setup_result = match (is_lazy, &url_file_bytes) {
+        (true, Some(url_file_bytes)) => {
+            // keep the verified archive to extract it the first time one of its commands is run
+            verify_commands(&platform_info.commands)
+                .and_then(|_| match &platform_info.output_dir {
+                    Some(output_dir) => verify_valid_relative_path(output_dir),
+                    None => Ok(()),
+                })
+                .and_then(|_| environment.write_file(&staging_dir_path.join(PENDING_ARCHIVE_FILE_NAME), url_file_bytes))
+        }
+        _ => setup_plugin_dir(
+            environment,
+            plugin_file,
+            target,
+            platform_info,
+            url_file_bytes.as_deref(),
+            &staging_dir_path,
+        )
+        .map(|streamed_url| {
+            if let Some(streamed_url) = streamed_url {
+                source_url = streamed_url;
+            }
+        }),
+    };
+    if let Err(err) = setup_result {
+        let _ignore = environment.remove_dir_all(&staging_dir_path);
+        return Err(err);
+    }
+
+    // record the checksum of what was installed when one wasn't provided
+    let mut platform_info = platform_info.clone();
+    if platform_info.checksum.is_empty() && !matches!(download_type, DownloadType::Npm | DownloadType::Cargo) {
+        if let Some(url_file_bytes) = &url_file_bytes {
+            platform_info.checksum = get_sha256_checksum(url_file_bytes);
+        }
+    }
+
+    Ok(StagedPlugin {
+        target,
+        platform_info,
+        has_target_override,
+        is_lazy,
+        source_url,
+        staging_dir_path,
+    })
+}
+
+/// Removes the staging directory of a binary that won't be installed.
+pub fn discard_staged_plugin(environment: &impl Environment, staged_plugin: StagedPlugin) {
+    let _ignore = environment.remove_dir_all(&staged_plugin.staging_dir_path);
+}
+
+/// Moves the staged binary to its plugin directory and finishes setting it up there.
+pub fn install_staged_plugin<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_file: &PluginFile,
+    staged_plugin: StagedPlugin,
+) -> Result<BinaryManifestItem, ErrBox> {
+    let StagedPlugin {
+        target,
+        platform_info,
+        has_target_override,
+        is_lazy,
+        source_url,
+        staging_dir_path,
+    } = staged_plugin;
+    let platform_info = &platform_info;
+
+    // now replace the plugin directory with the staging directory, keeping the previous one until the binary is set up
+    let plugin_cache_dir_path =
+        get_writable_plugin_dir(environment, &plugin_file.get_binary_name(), &plugin_file.version());
+    let previous_dir_path = match move_previous_plugin_dir(environment, &plugin_cache_dir_path, &staging_dir_path) {
+        Ok(previous_dir_path) => previous_dir_path,
+        Err(err) => {
+            let _ignore = environment.remove_dir_all(&staging_dir_path);
+            return Err(err);
+        }
+    };
+    let rename_result = match plugin_cache_dir_path.parent() {
+        Some(parent_dir_path) => environment.create_dir_all(parent_dir_path),
+        None => Ok(()),
+    }
+    .and_then(|_| environment.rename(&staging_dir_path, &plugin_cache_dir_path));
+    if let Err(err) = rename_result {
+        let _ignore = environment.remove_dir_all(&staging_dir_path);
+        restore_previous_plugin_dir(environment, &plugin_cache_dir_path, previous_dir_path.as_deref());
+        return Err(err);
+    }
+    let finish_result = if is_lazy {
+        Ok(platform_info.commands.clone())
+    } else {
+        finish_plugin_dir(
+            environment,
+            plugin_file,
+            platform_info,
+            has_target_override,
+            &plugin_cache_dir_path,
+        )
+    };
+    let commands = match finish_result {
+        Ok(commands) => commands,
+        Err(err) => {
+            restore_previous_plugin_dir(environment, &plugin_cache_dir_path, previous_dir_path.as_deref());
+            return Err(err);
+        }
+    };
+    if let Some(previous_dir_path) = previous_dir_path {
+        let _ignore = environment.remove_dir_all(&previous_dir_path);
+    }
+    if !is_lazy {
+        environment.emit_event(Event::Extracted {
+            name: plugin_file.get_binary_name(),
+            version: plugin_file.version().clone(),
+            dir_path: plugin_cache_dir_path.clone(),
+        });
+    }
+
+    // create the shims after in case the post install fails
+    environment.create_dir_all(&utils::get_shim_dir(environment))?;
+    for command in commands.iter() {
+        create_shim(environment, &command.name)?;
+    }
+
+    // add the plugin information to the manifest
+    let item = BinaryManifestItem {
+        name: plugin_file.get_binary_name(),
+        version: plugin_file.version().clone(),
+        created_time: environment.get_time_secs(),
+        commands: commands
+            .iter()
+            .map(|c| BinaryManifestItemCommand {
+                name: c.name.clone(),
+                path: c.path.clone(),
+            })
+            .collect(),
+        source: BinaryManifestItemSource {
+            path: plugin_file.url.clone(),
+            checksum: plugin_file.checksum.clone(),
+        },
+        environment: platform_info.environment.clone(),
+        target: Some(target.to_string()),
+        linked: false,
+        on_pre_uninstall: platform_info.on_pre_uninstall.clone(),
+        pending_extraction: if is_lazy {
+            Some(PendingExtraction {
+                download_type: platform_info.download_type.to_lowercase(),
+                output_dir: platform_info.output_dir.clone(),
+            })
+        } else {
+            None
+        },
+        provenance: Some(BinaryProvenance {
+            url: source_url,
+            checksum: platform_info.checksum.clone(),
+            registry: find_registry_url_of_binary(environment, &plugin_file.get_binary_name(), &plugin_file.url),
+            bvm_version: env!("CARGO_PKG_VERSION").to_string(),
+        }),
+    };
+    Ok(item)
+}
+
+fn is_lazy_extraction_enabled(environment: &impl Environment, lazy_extraction: Option<bool>) -> Result<bool, ErrBox> {
+    match lazy_extraction {
+        Some(lazy_extraction) => Ok(lazy_extraction),
+        None => Ok(read_user_config(environment)?.lazy_extraction.unwrap_or(false)),
+    }
+}
+
+/// Gets if the archive should be extracted while it's downloaded. This isn't done for an archive kept to
+/// extract lazily or when the pre-install command writes to the directory, which a failed download clears.
+fn should_stream_download(download_type: &DownloadType, is_lazy: bool, platform_info: &PlatformInfo) -> bool {
+    // a file without a checksum is downloaded first to get the checksum of what's installed
+    matches!(download_type, DownloadType::TarGz)
+        && !is_lazy
+        && platform_info.on_pre_install.is_none()
+        && !platform_info.checksum.is_empty()
+}
+
+/// Gets if the archive can be extracted on first use, which isn't possible when the commands are detected
+/// from its files or when it has install commands that expect the extracted files.
+fn can_extract_lazily(plugin_file: &PluginFile, platform_info: &PlatformInfo) -> Result<bool, ErrBox> {
+    Ok(matches!(
+        get_download_type(platform_info)?,
+        DownloadType::Zip | DownloadType::TarGz
+    ) && !plugin_file.detect_commands
+        && platform_info.on_pre_install.is_none()
+        && platform_info.on_post_install.is_none())
+}
+
+/// Archives the files of an installed binary into its directory so that the extracted files may be removed with
+/// `remove_compacted_files` once the binary's manifest item records the returned pending extraction.
+pub fn compact_binary_dir<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    item: &BinaryManifestItem,
+) -> Result<PendingExtraction, ErrBox> {
+    let dir_path = get_plugin_dir(environment, &item.name, &item.version);
+    let archive_bytes = utils::create_tar_gz(environment, &dir_path)?;
+    environment.write_file(&dir_path.join(PENDING_ARCHIVE_FILE_NAME), &archive_bytes)?;
+    Ok(PendingExtraction {
+        download_type: "tar.gz".to_string(),
+        output_dir: None,
+    })
+}
+
+/// Removes the extracted files of a binary that was compacted, leaving only its archive.
+pub fn remove_compacted_files<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    name: &BinaryName,
+    version: &Version,
+) -> Result<(), ErrBox> {
+    let dir_path = get_plugin_dir(environment, name, version);
+    let staging_dir_path = get_plugin_staging_dir(environment, name, version);
+    let _ignore = environment.remove_dir_all(&staging_dir_path);
+    environment.rename(&dir_path, &staging_dir_path)?;
+    environment.create_dir_all(&dir_path)?;
+    environment.rename(
+        &staging_dir_path.join(PENDING_ARCHIVE_FILE_NAME),
+        &dir_path.join(PENDING_ARCHIVE_FILE_NAME),
+    )?;
+    environment.remove_dir_all(&staging_dir_path)
+}
+
+/// Extracts the archive of a binary that was installed with lazy extraction or compacted into the binary's directory.
+/// The binary's manifest item should be updated to no longer have a pending extraction afterwards.
+pub fn extract_pending_archive<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    item: &BinaryManifestItem,
+) -> Result<(), ErrBox> {
+    let pending_extraction = match &item.pending_extraction {
+        Some(pending_extraction) => pending_extraction,
+        None => return Ok(()),
+    };
+    let dir_path = get_plugin_dir(environment, &item.name, &item.version);
+    let archive_bytes = environment.read_file(&dir_path.join(PENDING_ARCHIVE_FILE_NAME))?;
+
+    // extract into the staging directory so that a failure leaves the archive to try again
+    let staging_dir_path = get_plugin_staging_dir(environment, &item.name, &item.version);
+    let _ignore = environment.remove_dir_all(&staging_dir_path);
+    environment.create_dir_all(&staging_dir_path)?;
+    let result = (|| -> Result<(), ErrBox> {
+        let output_dir = match &pending_extraction.output_dir {
+            Some(output_dir) => {
+                verify_valid_relative_path(output_dir)?;
+                let output_dir = staging_dir_path.join(output_dir);
+                environment.create_dir_all(&output_dir)?;
+                output_dir
+            }
+            None => staging_dir_path.clone(),
+        };
+        let message = format!("Extracting archive for {} {}...", item.name, item.version);
+        match parse_download_type(&pending_extraction.download_type)? {
+            DownloadType::Zip => utils::extract_zip(&message, environment, &archive_bytes, &output_dir)?,
+            DownloadType::TarGz => utils::extract_tar_gz(&message, environment, &archive_bytes, &output_dir)?,
+            _ => {
+                return err!(
+                    "The {} download type can't be extracted on first use.",
+                    pending_extraction.download_type
+                )
+            }
+        }
+        if is_content_store_enabled(environment)? {
+            link_dir_files_from_store(environment, &staging_dir_path)?;
+        }
+        write_integrity_manifest(environment, &staging_dir_path)
+    })();
+    if let Err(err) = result {
+        let _ignore = environment.remove_dir_all(&staging_dir_path);
+        return Err(err);
+    }
+
+    environment.remove_dir_all(&dir_path)?;
+    environment.rename(&staging_dir_path, &dir_path)?;
+    environment.emit_event(Event::Extracted {
+        name: item.name.clone(),
+        version: item.version.clone(),
+        dir_path,
+    });
+    Ok(())
+}
+
+/// Verifies the binary may be installed for the selected target, which may need an opt-in when it's emulated.
+/// Selects the platform of the plugin file to install for the target or the current system.
+fn select_platform<'a>(
+    environment: &impl Environment,
+    plugin_file: &'a PluginFile,
+    target: Option<&str>,
+) -> Result<(&'static str, &'a PlatformInfo), ErrBox> {
+    let target_override = get_target_override(environment, target)?;
+    let platform_keys = match target_override {
+        Some(target) => vec![target],
+        None => {
+            let mut platform_keys = if is_musl_host(environment) {
+                vec!["linux-x86_64-musl", "linux-x86_64"]
+            } else {
+                get_platform_keys().to_vec()
+            };
+            for fallback in get_target_fallbacks(environment)? {
+                if !platform_keys.contains(&fallback) {
+                    platform_keys.push(fallback);
+                }
+            }
+            // binaries with windows executables would otherwise be installed for linux in wsl
+            if utils::is_wsl_interop_enabled(environment)? {
+                platform_keys.retain(|key| match plugin_file.file.get_platform_info(key) {
+                    Some(platform_info) => !platform_info.commands.iter().any(|c| c.path.ends_with(".exe")),
+                    None => true,
+                });
+            }
+            platform_keys
+        }
+    };
+    let (target, platform_info) = match plugin_file.get_platform_target(&platform_keys) {
+        Ok(platform_target) => platform_target,
+        Err(err) => match target_override {
+            Some(target) => {
+                return Err(Box::new(UnsupportedPlatformError {
+                    message: format!(
+                        "{} does not provide a binary for target {}.",
+                        plugin_file.display(),
+                        target
+                    ),
+                }))
+            }
+            None => return Err(err),
+        },
+    };
+    if target_override.is_none() {
+        verify_target_allowed(environment, plugin_file, target)?;
+    }
+    Ok((target, platform_info))
+}
+
+fn verify_target_allowed<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_file: &PluginFile,
+    target: &str,
+) -> Result<(), ErrBox> {
+    if cfg!(all(target_os = "macos", target_arch = "aarch64"))
+        && target == "darwin-x86_64"
+        && !is_rosetta_fallback_allowed(environment)?
+    {
+        return Err(Box::new(UnsupportedPlatformError {
+            message: format!(
+                "{} does not provide a binary for darwin-aarch64. Set the BVM_ALLOW_ROSETTA environment variable to 1 or `\"allowRosetta\": true` in the config.json file to install the darwin-x86_64 binary and run it with Rosetta.",
+                plugin_file.display()
+            ),
+        }));
+    }
+    Ok(())
+}
+
+/// Error for when a binary doesn't provide a binary that may be installed for the target.
+#[derive(Debug)]
+pub struct UnsupportedPlatformError {
+    message: String,
+}
+
+impl std::fmt::Display for UnsupportedPlatformError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for UnsupportedPlatformError {}
+
+/// Gets the target binaries are installed for, which is the provided or configured target or otherwise
+/// the target of the current system.
+pub fn get_install_target(environment: &impl Environment, target: Option<&str>) -> Result<&'static str, ErrBox> {
+    Ok(match get_target_override(environment, target)? {
+        Some(target) => target,
+        None if is_musl_host(environment) => "linux-x86_64-musl",
+        None => get_platform_keys()[0],
+    })
+}
+
+/// Gets the target to install that was provided or configured instead of detecting it from the current system.
+fn get_target_override(environment: &impl Environment, target: Option<&str>) -> Result<Option<&'static str>, ErrBox> {
+    let target = match target {
+        Some(target) => Some(target.to_string()),
+        None => match environment.get_env_var("BVM_TARGET").filter(|value| !value.is_empty()) {
+            Some(target) => Some(target),
+            None => read_user_config(environment)?.target,
+        },
+    };
+    match target {
+        Some(target) => match PLATFORM_KEYS.iter().find(|key| **key == target) {
+            Some(key) => Ok(Some(key)),
+            None => err!(
+                "Unknown target '{}'. Supported targets: {}",
+                target,
+                PLATFORM_KEYS.join(", ")
+            ),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Gets the targets from the user config to try when a binary doesn't provide one for the current system.
+fn get_target_fallbacks(environment: &impl Environment) -> Result<Vec<&'static str>, ErrBox> {
+    let mut fallbacks = Vec::new();
+    for target in read_user_config(environment)?.target_fallbacks.unwrap_or_default() {
+        match PLATFORM_KEYS.iter().find(|key| **key == target) {
+            Some(key) => fallbacks.push(*key),
+            None => {
+                return err!(
+                    "Unknown target fallback '{}'. Supported targets: {}",
+                    target,
+                    PLATFORM_KEYS.join(", ")
+                )
+            }
+        }
+    }
+    Ok(fallbacks)
+}
+
+/// Gets if the host is a musl based linux distribution (ex. Alpine).
+fn is_musl_host(environment: &impl Environment) -> bool {
+    cfg!(target_os = "linux") && environment.path_exists(Path::new("/lib/ld-musl-x86_64.so.1"))
+}
+
+fn is_rosetta_fallback_allowed(environment: &impl Environment) -> Result<bool, ErrBox> {
+    if let Some(value) = environment.get_env_var("BVM_ALLOW_ROSETTA") {
+        return Ok(value == "1" || value.to_lowercase() == "true");
+    }
+    Ok(read_user_config(environment)?.allow_rosetta.unwrap_or(false))
+}
+
+fn setup_plugin_dir<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_file: &PluginFile,
+    target: &str,
+    platform_info: &PlatformInfo,
+    downloaded_file_bytes: Option<&[u8]>,
+    dir_path: &Path,
+) -> Result<Option<String>, ErrBox> {
+    let download_type = get_download_type(platform_info)?;
+
+    // run the pre install command
+    if let Some(pre_install_command) = &platform_info.on_pre_install {
+        environment.run_shell_command(dir_path, pre_install_command)?;
+    }
+
+    // handle the setup based on the download type
+    let commands = &platform_info.commands;
+    if !plugin_file.detect_commands {
+        verify_commands(commands)?;
+    }
+    let output_dir = if let Some(output_dir) = &platform_info.output_dir {
+        verify_valid_relative_path(&output_dir)?;
+        let output_dir = dir_path.join(output_dir);
+        environment.create_dir_all(&output_dir)?;
+        output_dir
+    } else {
+        dir_path.to_path_buf()
+    };
+    // only tar.gz archives are downloaded while they're set up
+    let url_file_bytes = downloaded_file_bytes.unwrap_or_default();
+    let mut streamed_url = None;
+    match download_type {
+        DownloadType::Zip => utils::extract_zip(
+            &format!("Extracting archive for {}...", plugin_file.display(),),
+            environment,
+            url_file_bytes,
+            &output_dir,
+        )?,
+        DownloadType::TarGz => match downloaded_file_bytes {
+            Some(url_file_bytes) => utils::extract_tar_gz(
+                &format!("Extracting archive for {}...", plugin_file.display(),),
+                environment,
+                url_file_bytes,
+                &output_dir,
+            )?,
+            None => {
+                streamed_url = Some(download_and_extract_tar_gz(
+                    environment,
+                    plugin_file,
+                    platform_info,
+                    &output_dir,
+                )?)
+            }
+        },
+        DownloadType::Binary => {
+            if commands.len() != 1 {
+                return err!("The binary download type must have exactly one command specified.");
+            }
+            environment.write_file(&output_dir.join(&commands[0].path), url_file_bytes)?
+        }
+        DownloadType::Npm => install_npm_package(environment, &platform_info.path, &output_dir)?,
+        DownloadType::Cargo => install_cargo_crate(environment, &platform_info.path, &output_dir)?,
+        DownloadType::Msi => {
+            install_msi_package(environment, &plugin_file.display(), target, url_file_bytes, &output_dir)?
+        }
+        DownloadType::Pkg => {
+            install_pkg_package(environment, &plugin_file.display(), target, url_file_bytes, &output_dir)?
+        }
+        DownloadType::AppImage => {
+            if commands.len() != 1 {
+                return err!("The appimage download type must have exactly one command specified.");
+            }
+            install_app_image(
+                environment,
+                &plugin_file.display(),
+                target,
+                url_file_bytes,
+                &output_dir.join(&commands[0].path),
+            )?
+        }
+    }
+
+    Ok(streamed_url)
+}
+
+/// Finishes setting up the binary once it's in its plugin directory, since the post install command or the
+/// binary itself may depend on where it's located (ex. by writing absolute paths).
+fn finish_plugin_dir<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_file: &PluginFile,
+    platform_info: &PlatformInfo,
+    has_target_override: bool,
+    dir_path: &Path,
+) -> Result<Vec<PlatformInfoCommand>, ErrBox> {
+    // run the post install command
+    if let Some(post_install_command) = &platform_info.on_post_install {
+        environment.run_shell_command(dir_path, post_install_command)?;
+    }
+    if is_content_store_enabled(environment)? {
+        link_dir_files_from_store(environment, dir_path)?;
+    }
+    let commands = if plugin_file.detect_commands {
+        detect_commands(environment, plugin_file, platform_info, dir_path)?
+    } else {
+        platform_info.commands.clone()
+    };
+    // binaries installed for another target might not run on this system
+    if let Some(smoke_test) = &plugin_file.file.smoke_test {
+        if !has_target_override {
+            run_smoke_test(environment, plugin_file, smoke_test, &commands, dir_path)?;
+        }
+    }
+    write_integrity_manifest(environment, dir_path)?;
+    Ok(commands)
+}
+
+/// Moves an existing plugin directory out of the way, returning where it was moved to.
+fn move_previous_plugin_dir(
+    environment: &impl Environment,
+    plugin_dir_path: &Path,
+    staging_dir_path: &Path,
+) -> Result<Option<PathBuf>, ErrBox> {
+    if !environment.is_dir(plugin_dir_path) {
+        return Ok(None);
+    }
+    let mut previous_dir_path = staging_dir_path.as_os_str().to_owned();
+    previous_dir_path.push(".previous");
+    let previous_dir_path = PathBuf::from(previous_dir_path);
+    let _ignore = environment.remove_dir_all(&previous_dir_path);
+    environment.rename(plugin_dir_path, &previous_dir_path)?;
+    Ok(Some(previous_dir_path))
+}
+
+/// Removes the partially set up plugin directory and moves back the previous one.
+fn restore_previous_plugin_dir(
+    environment: &impl Environment,
+    plugin_dir_path: &Path,
+    previous_dir_path: Option<&Path>,
+) {
+    let _ignore = environment.remove_dir_all(plugin_dir_path);
+    if let Some(previous_dir_path) = previous_dir_path {
+        let _ignore = environment.rename(previous_dir_path, plugin_dir_path);
+    }
+}
+
+/// Runs the plugin file's smoke test to catch a binary that doesn't execute on this system (ex. one built
+/// for a different architecture or linking to a missing library) at install time instead of at first use.
+fn run_smoke_test<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_file: &PluginFile,
+    smoke_test: &SmokeTest,
+    commands: &[PlatformInfoCommand],
+    dir_path: &Path,
+) -> Result<(), ErrBox> {
+    let command = match &smoke_test.command {
+        Some(command_name) => match commands.iter().find(|c| &c.name == command_name) {
+            Some(command) => command,
+            None => {
+                return err!(
+                    "Did not install {} because the command '{}' of its smoke test is not one of its commands.",
+                    plugin_file.display(),
+                    command_name
+                )
+            }
+        },
+        None => match commands.first() {
+            Some(command) => command,
+            None => return Ok(()),
+        },
+    };
+    let invocation = std::iter::once(command.name.as_str())
+        .chain(smoke_test.args.iter().map(|arg| arg.as_str()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    log_verbose!(
+        environment,
+        "Running the smoke test of {}: {}",
+        plugin_file.display(),
+        invocation
+    );
+
+    let (exit_code, output) = match environment.get_executable_output(&dir_path.join(&command.path), &smoke_test.args) {
+        Ok(result) => result,
+        Err(err) => {
+            return err!(
+                "Did not install {} because its smoke test failed. {} It may be built for a different architecture or need a library that isn't installed.",
+                plugin_file.display(),
+                err.to_string()
+            )
+        }
+    };
+    if exit_code != 0 {
+        return err!(
+            "Did not install {} because its smoke test failed. `{}` exited with code {}. It may be built for a different architecture or need a library that isn't installed.{}",
+            plugin_file.display(),
+            invocation,
+            exit_code,
+            format_smoke_test_output(&output)
+        );
+    }
+    if let Some(expected_output) = &smoke_test.expected_output {
+        let regex = match Regex::new(expected_output) {
+            Ok(regex) => regex,
+            Err(err) => {
+                return err!(
+                    "Did not install {} because the expected output of its smoke test is not a valid regular expression. {}",
+                    plugin_file.display(),
+                    err.to_string()
+                )
+            }
+        };
+        if !regex.is_match(&output) {
+            return err!(
+                "Did not install {} because its smoke test failed. The output of `{}` did not match `{}`.{}",
+                plugin_file.display(),
+                invocation,
+                expected_output,
+                format_smoke_test_output(&output)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn format_smoke_test_output(output: &str) -> String {
+    let output = output.trim();
+    if output.is_empty() {
+        String::new()
+    } else {
+        format!("\n{}", output)
+    }
+}
+
+/// Gets the commands of the executables found in the set up binary's directory.
+fn detect_commands<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_file: &PluginFile,
+    platform_info: &PlatformInfo,
+    dir_path: &Path,
+) -> Result<Vec<PlatformInfoCommand>, ErrBox> {
+    if let DownloadType::Npm = get_download_type(platform_info)? {
+        let output_dir = match &platform_info.output_dir {
+            Some(output_dir) => dir_path.join(output_dir),
+            None => dir_path.to_path_buf(),
+        };
+        return get_npm_package_commands(environment, &platform_info.path, &output_dir);
+    }
+
+    let mut commands: Vec<PlatformInfoCommand> = Vec::new();
+    let mut file_paths = environment.get_executable_file_paths(dir_path)?;
+    file_paths.sort();
+    for file_path in file_paths {
+        let name = match file_path.file_stem() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => continue,
+        };
+        if commands.iter().any(|c| c.name.as_str() == name) {
+            continue;
+        }
+        let relative_path = file_path.strip_prefix(dir_path)?;
+        commands.push(PlatformInfoCommand {
+            name: CommandName::from_string(name),
+            path: relative_path.to_string_lossy().to_string(),
+        });
+    }
+
+    if commands.is_empty() {
+        return err!(
+            "Could not find any executables in the archive for {}.",
+            plugin_file.display()
+        );
+    }
+
+    Ok(commands)
+}
+
+fn verify_commands(commands: &Vec<PlatformInfoCommand>) -> Result<(), ErrBox> {
+    if commands.is_empty() {
+        return err!("One command must be specified.");
+    }
+
+    // prevent funny business
+    for command in commands.iter() {
+        verify_valid_relative_path(&command.path)?;
+    }
+
+    Ok(())
+}
+
+fn verify_valid_relative_path(path: &str) -> Result<(), ErrBox> {
+    if path.contains("../") || path.contains("..\\") {
+        return err!("Invalid path '{}'. A path cannot go down directories.", path);
+    }
+    if PathBuf::from(&path).is_absolute() {
+        return err!("Invalid path '{}'. A path cannot be absolute.", path);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use dprint_cli_core::types::ErrBox;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use url::Url;
+
+use crate::environment::{read_user_config, Environment};
+use crate::registry::is_source_provider_scheme;
+
+lazy_static! {
+    /// Ensures binaries being set up in parallel don't ask to trust a host at the same time.
+    static ref CONFIRM_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Ensures the host of the url may be downloaded from when trusted hosts are configured.
+///
+/// Hosts that aren't trusted are trusted after the user confirms or when the `--trust` flag
+/// is provided, then saved so they're trusted in the future.
+pub fn verify_trusted_host<TEnvironment: Environment>(environment: &TEnvironment, url: &str) -> Result<(), ErrBox> {
+    let host = match Url::parse(url) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" || is_source_provider_scheme(url.scheme()) => {
+            match url.host_str() {
+                Some(host) => host.to_lowercase(),
+                None => return Ok(()),
+            }
+        }
+        _ => return Ok(()),
+    };
+    let configured_hosts = match read_user_config(environment)?.trusted_hosts {
+        Some(hosts) => hosts,
+        None => return Ok(()),
+    };
+
+    let _lock = CONFIRM_LOCK.lock().unwrap();
+    let saved_hosts = read_saved_trusted_hosts(environment);
+    if configured_hosts
+        .iter()
+        .chain(saved_hosts.iter())
+        .any(|pattern| is_host_match(pattern, &host))
+    {
+        return Ok(());
+    }
+
+    if !environment.is_trust_hosts() {
+        let message = format!("{} is not a trusted host. Trust it and download {}?", host, url);
+        match environment.confirm(&message)? {
+            Some(true) => {}
+            Some(false) => return err!("Did not download {} because {} is not a trusted host.", url, host),
+            None => {
+                return err!(
+                    "Did not download {} because {} is not a trusted host. Provide the `--trust` flag to trust it or add it to \"trustedHosts\" in the user config.json file.",
+                    url,
+                    host
+                )
+            }
+        }
+    }
+
+    let mut saved_hosts = saved_hosts;
+    saved_hosts.push(host);
+    environment.write_file_text(
+        &get_saved_trusted_hosts_file_path(environment),
+        &serde_json::to_string_pretty(&saved_hosts)?,
+    )?;
+    Ok(())
+}
+
+/// Gets if the host matches a trusted host pattern. Patterns starting with `*.` match any subdomain.
+fn is_host_match(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.ends_with(&format!(".{}", domain)),
+        None => pattern == host,
+    }
+}
+
+fn read_saved_trusted_hosts(environment: &impl Environment) -> Vec<String> {
+    environment
+        .read_file(&get_saved_trusted_hosts_file_path(environment))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn get_saved_trusted_hosts_file_path(environment: &impl Environment) -> PathBuf {
+    environment.get_user_data_dir().join("trusted-hosts.json")
+}
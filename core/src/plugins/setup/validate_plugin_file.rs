@@ -0,0 +1,416 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use regex::Regex;
+use serde_json::{Map, Value};
+use url::Url;
+
+use super::{expand_plugin_file, split_version_fragment};
+use crate::environment::Environment;
+use crate::plugins::PLATFORM_KEYS;
+use crate::types::Version;
+
+const DOWNLOAD_TYPES: &[&str] = &["zip", "tar.gz", "binary", "npm", "cargo", "msi", "pkg", "appimage"];
+const DOWNLOAD_TYPES_WITHOUT_CHECKSUM: &[&str] = &["npm", "cargo"];
+
+/// Validates the plugin file at the provided url against the schema, returning all the problems found.
+/// When `fetch` is true, each platform's path is downloaded and its checksum verified.
+pub fn validate_plugin_file<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    url: &str,
+    fetch: bool,
+) -> Result<Vec<String>, ErrBox> {
+    let (url, version) = split_version_fragment(url);
+    let file_bytes = environment.download_file(url)?;
+    let file = match parse_plugin_file(&file_bytes, version) {
+        Ok(file) => file,
+        Err(problem) => return Ok(vec![problem]),
+    };
+    let mut problems = get_plugin_file_problems(&file);
+
+    if fetch && problems.is_empty() {
+        for platform_key in PLATFORM_KEYS.iter() {
+            let platform = match file.get(*platform_key).and_then(|value| value.as_object()) {
+                Some(platform) => platform,
+                None => continue,
+            };
+            let download_type = platform["type"].as_str().unwrap().to_lowercase();
+            if DOWNLOAD_TYPES_WITHOUT_CHECKSUM.contains(&download_type.as_str()) {
+                continue;
+            }
+            let path = platform["path"].as_str().unwrap();
+            let path_url = match Url::parse(url).and_then(|url| url.join(path)) {
+                Ok(path_url) => path_url.to_string(),
+                Err(err) => {
+                    problems.push(format!("{}: Could not resolve path {}. {}", platform_key, path, err));
+                    continue;
+                }
+            };
+            match environment.download_file(&path_url) {
+                Ok(bytes) => {
+                    let checksum = get_sha256_checksum(&bytes);
+                    if !checksum.eq_ignore_ascii_case(platform["checksum"].as_str().unwrap()) {
+                        problems.push(format!(
+                            "{}: The checksum did not match the file at {}. Expected {}.",
+                            platform_key, path_url, checksum
+                        ));
+                    }
+                }
+                Err(err) => problems.push(format!("{}: Could not download {}. {}", platform_key, path_url, err)),
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Parses the plugin file and selects the version to validate when it describes multiple versions.
+fn parse_plugin_file(file_bytes: &[u8], version: Option<&str>) -> Result<Map<String, Value>, String> {
+    let value = match serde_json::from_slice::<Value>(file_bytes) {
+        Ok(value @ Value::Object(_)) => value,
+        Ok(_) => return Err("The plugin file must be an object.".to_string()),
+        Err(err) => return Err(format!("The plugin file is not valid JSON. {}", err)),
+    };
+    match expand_plugin_file(value, version) {
+        Ok(Value::Object(file)) => Ok(file),
+        Ok(_) => unreachable!(),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+fn get_plugin_file_problems(file: &Map<String, Value>) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    match file.get("schemaVersion") {
+        Some(Value::Number(number)) if number.as_u64() == Some(1) => {}
+        Some(Value::Number(number)) => problems.push(format!("schemaVersion: Expected 1, but found {}.", number)),
+        Some(_) => problems.push("schemaVersion: Expected a number.".to_string()),
+        None => problems.push("schemaVersion: Missing required property.".to_string()),
+    }
+    for property_name in ["name", "owner"].iter() {
+        if let Some(value) = get_required_string(file, property_name, "", &mut problems) {
+            if value.is_empty() {
+                problems.push(format!("{}: Expected a non-empty string.", property_name));
+            } else if value.contains('/') {
+                problems.push(format!("{}: May not contain a forward slash.", property_name));
+            }
+        }
+    }
+    if let Some(version) = get_required_string(file, "version", "", &mut problems) {
+        if Version::parse(version).is_err() {
+            problems.push(format!(
+                "version: Expected a version in the format `x.x.x`, but found '{}'.",
+                version
+            ));
+        }
+    }
+    get_required_string(file, "description", "", &mut problems);
+
+    let mut has_platform = false;
+    for (key, value) in file.iter() {
+        match key.as_str() {
+            "schemaVersion" | "name" | "owner" | "version" | "description" => {}
+            "minBvmVersion" => match value.as_str() {
+                Some(text) if Version::parse(text).is_ok() => {}
+                _ => problems.push("minBvmVersion: Expected a version in the format `x.x.x`.".to_string()),
+            },
+            "smokeTest" => add_smoke_test_problems(value, &mut problems),
+            key if PLATFORM_KEYS.contains(&key) => {
+                if !value.is_null() {
+                    has_platform = true;
+                    add_platform_problems(key, value, &mut problems);
+                }
+            }
+            key => problems.push(format!(
+                "{}: Unknown property. Supported platform keys: {}",
+                key,
+                PLATFORM_KEYS.join(", ")
+            )),
+        }
+    }
+    if !has_platform {
+        problems.push(format!(
+            "Expected a binary for at least one platform ({}).",
+            PLATFORM_KEYS.join(", ")
+        ));
+    }
+
+    problems
+}
+
+fn add_smoke_test_problems(value: &Value, problems: &mut Vec<String>) {
+    let smoke_test = match value {
+        Value::Null => return,
+        Value::Object(smoke_test) => smoke_test,
+        _ => {
+            problems.push("smokeTest: Expected an object.".to_string());
+            return;
+        }
+    };
+    if !matches!(
+        smoke_test.get("command"),
+        None | Some(Value::Null) | Some(Value::String(_))
+    ) {
+        problems.push("smokeTest.command: Expected a string.".to_string());
+    }
+    match smoke_test.get("args") {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(args)) if args.iter().all(|arg| arg.is_string()) => {}
+        Some(_) => problems.push("smokeTest.args: Expected an array of strings.".to_string()),
+    }
+    match smoke_test.get("expectedOutput") {
+        None | Some(Value::Null) => {}
+        Some(Value::String(expected_output)) => {
+            if let Err(err) = Regex::new(expected_output) {
+                problems.push(format!(
+                    "smokeTest.expectedOutput: Expected a regular expression. {}",
+                    err
+                ));
+            }
+        }
+        Some(_) => problems.push("smokeTest.expectedOutput: Expected a string.".to_string()),
+    }
+}
+
+fn add_platform_problems(platform_key: &str, value: &Value, problems: &mut Vec<String>) {
+    let platform = match value.as_object() {
+        Some(platform) => platform,
+        None => {
+            problems.push(format!("{}: Expected an object.", platform_key));
+            return;
+        }
+    };
+
+    get_required_string(platform, "path", platform_key, problems);
+    match platform.get("mirrors") {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(mirrors)) => {
+            if !mirrors.iter().all(|mirror| mirror.is_string()) {
+                problems.push(format!("{}.mirrors: Expected an array of strings.", platform_key));
+            }
+        }
+        Some(_) => problems.push(format!("{}.mirrors: Expected an array of strings.", platform_key)),
+    }
+    let download_type = get_required_string(platform, "type", platform_key, problems).map(|t| t.to_lowercase());
+    if let Some(download_type) = &download_type {
+        if !DOWNLOAD_TYPES.contains(&download_type.as_str()) {
+            problems.push(format!(
+                "{}.type: Unknown download type '{}'. Expected one of: {}",
+                platform_key,
+                download_type,
+                DOWNLOAD_TYPES.join(", ")
+            ));
+        } else if download_type == "msi" && !platform_key.starts_with("windows-") {
+            problems.push(format!(
+                "{}.type: The msi download type may only be used for windows targets.",
+                platform_key
+            ));
+        } else if download_type == "pkg" && !platform_key.starts_with("darwin-") {
+            problems.push(format!(
+                "{}.type: The pkg download type may only be used for darwin targets.",
+                platform_key
+            ));
+        } else if download_type == "appimage" && !platform_key.starts_with("linux-") {
+            problems.push(format!(
+                "{}.type: The appimage download type may only be used for linux targets.",
+                platform_key
+            ));
+        }
+    }
+    if let Some(checksum) = get_required_string(platform, "checksum", platform_key, problems) {
+        let requires_checksum = match &download_type {
+            Some(download_type) => !DOWNLOAD_TYPES_WITHOUT_CHECKSUM.contains(&download_type.as_str()),
+            None => true,
+        };
+        let is_sha256 = checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit());
+        if (requires_checksum || !checksum.is_empty()) && !is_sha256 {
+            problems.push(format!(
+                "{}.checksum: Expected a sha256 checksum of 64 hexadecimal characters.",
+                platform_key
+            ));
+        }
+    }
+    for property_name in ["outputDir", "onPreInstall", "onPostInstall", "onPreUninstall"].iter() {
+        if !matches!(
+            platform.get(*property_name),
+            None | Some(Value::Null) | Some(Value::String(_))
+        ) {
+            problems.push(format!("{}.{}: Expected a string.", platform_key, property_name));
+        }
+    }
+
+    match platform.get("commands") {
+        Some(Value::Array(commands)) => {
+            if commands.is_empty() {
+                problems.push(format!("{}.commands: Expected at least one command.", platform_key));
+            }
+            for (i, command) in commands.iter().enumerate() {
+                let prefix = format!("{}.commands[{}]", platform_key, i);
+                match command.as_object() {
+                    Some(command) => {
+                        get_required_string(command, "name", &prefix, problems);
+                        get_required_string(command, "path", &prefix, problems);
+                    }
+                    None => problems.push(format!("{}: Expected an object.", prefix)),
+                }
+            }
+        }
+        Some(_) => problems.push(format!("{}.commands: Expected an array.", platform_key)),
+        None => problems.push(format!("{}.commands: Missing required property.", platform_key)),
+    }
+
+    match platform.get("environment") {
+        None | Some(Value::Null) => {}
+        Some(Value::Object(environment)) => {
+            match environment.get("variables") {
+                None | Some(Value::Null) => {}
+                Some(Value::Object(variables)) => {
+                    for (name, value) in variables.iter() {
+                        if !value.is_string() {
+                            problems.push(format!(
+                                "{}.environment.variables.{}: Expected a string.",
+                                platform_key, name
+                            ));
+                        }
+                    }
+                }
+                Some(_) => problems.push(format!("{}.environment.variables: Expected an object.", platform_key)),
+            }
+            match environment.get("paths") {
+                None | Some(Value::Null) => {}
+                Some(Value::Array(paths)) if paths.iter().all(|path| path.is_string()) => {}
+                Some(_) => problems.push(format!(
+                    "{}.environment.paths: Expected an array of strings.",
+                    platform_key
+                )),
+            }
+        }
+        Some(_) => problems.push(format!("{}.environment: Expected an object.", platform_key)),
+    }
+}
+
+fn get_required_string<'a>(
+    obj: &'a Map<String, Value>,
+    property_name: &str,
+    prefix: &str,
+    problems: &mut Vec<String>,
+) -> Option<&'a str> {
+    let display_name = if prefix.is_empty() {
+        property_name.to_string()
+    } else {
+        format!("{}.{}", prefix, property_name)
+    };
+    match obj.get(property_name) {
+        Some(Value::String(value)) => Some(value.as_str()),
+        Some(_) => {
+            problems.push(format!("{}: Expected a string.", display_name));
+            None
+        }
+        None => {
+            problems.push(format!("{}: Missing required property.", display_name));
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_problems(text: &str) -> Vec<String> {
+        match parse_plugin_file(text.as_bytes(), None) {
+            Ok(file) => get_plugin_file_problems(&file),
+            Err(problem) => vec![problem],
+        }
+    }
+
+    #[test]
+    fn should_not_have_problems_for_valid_file() {
+        let problems = get_problems(
+            r#"{
+    "schemaVersion": 1,
+    "name": "name",
+    "owner": "owner",
+    "version": "1.0.0",
+    "description": "Some description.",
+    "linux-x86_64": {
+        "path": "https://localhost/file.zip",
+        "type": "zip",
+        "checksum": "4f8a8bd76a9a2b4a8a6e0c6f1b1c3e0a7d1d9fa6b1d7e0a36a3d5c3b7b7f4e21",
+        "commands": [{ "name": "name", "path": "name" }],
+        "environment": { "variables": { "NAME_HOME": "dir" }, "paths": ["dir"] }
+    },
+    "darwin-x86_64": {
+        "path": "npm:name@1.0.0",
+        "type": "npm",
+        "checksum": "",
+        "commands": [{ "name": "name", "path": "name" }]
+    }
+}"#,
+        );
+        assert_eq!(problems, Vec::<String>::new());
+    }
+
+    #[test]
+    fn should_report_all_problems() {
+        let problems = get_problems(
+            r#"{
+    "schemaVersion": 2,
+    "name": "owner/name",
+    "version": "1.0",
+    "description": "Some description.",
+    "linux-x86_64": {
+        "path": "https://localhost/file.zip",
+        "type": "rar",
+        "checksum": "abc",
+        "commands": [{ "name": "name" }],
+        "environment": { "paths": "dir" }
+    },
+    "linux-arm": {}
+}"#,
+        );
+        assert_eq!(
+            problems,
+            vec![
+                "schemaVersion: Expected 1, but found 2.".to_string(),
+                "name: May not contain a forward slash.".to_string(),
+                "owner: Missing required property.".to_string(),
+                "version: Expected a version in the format `x.x.x`, but found '1.0'.".to_string(),
+                "linux-arm: Unknown property. Supported platform keys: linux-x86_64, linux-x86_64-musl, darwin-x86_64, darwin-aarch64, windows-x86_64, windows-aarch64".to_string(),
+                "linux-x86_64.type: Unknown download type 'rar'. Expected one of: zip, tar.gz, binary, npm, cargo, msi, pkg, appimage".to_string(),
+                "linux-x86_64.checksum: Expected a sha256 checksum of 64 hexadecimal characters.".to_string(),
+                "linux-x86_64.commands[0].path: Missing required property.".to_string(),
+                "linux-x86_64.environment.paths: Expected an array of strings.".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_smoke_test_problems() {
+        let problems = get_problems(
+            r#"{
+    "schemaVersion": 1,
+    "name": "name",
+    "owner": "owner",
+    "version": "1.0.0",
+    "description": "Some description.",
+    "linux-x86_64": {
+        "path": "npm:name@1.0.0",
+        "type": "npm",
+        "checksum": "",
+        "commands": [{ "name": "name", "path": "name" }]
+    },
+    "smokeTest": { "command": 5, "args": "--version", "expectedOutput": "(" }
+}"#,
+        );
+        assert_eq!(problems.len(), 3);
+        assert_eq!(problems[0], "smokeTest.command: Expected a string.");
+        assert_eq!(problems[1], "smokeTest.args: Expected an array of strings.");
+        assert!(problems[2].starts_with("smokeTest.expectedOutput: Expected a regular expression."));
+    }
+
+    #[test]
+    fn should_report_invalid_json() {
+        let problems = get_problems("[]");
+        assert_eq!(problems, vec!["The plugin file must be an object.".to_string()]);
+    }
+}
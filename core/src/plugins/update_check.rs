@@ -0,0 +1,64 @@
+use dprint_cli_core::types::ErrBox;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::environment::Environment;
+use crate::types::{BinaryName, Version};
+
+/// Number of seconds after which the registry is checked again for a newer version of a binary.
+pub const UPDATE_CHECK_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+/// Result of the last check of the registry for a newer version of a binary.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCheck {
+    /// Time in *seconds* since epoch the check was started.
+    pub checked_time: u64,
+    /// Version selector the newer version had to match or `None` for any newer version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_selector: Option<String>,
+    /// Newest version in the registry that's newer than the version that was checked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available_version: Option<Version>,
+}
+
+impl UpdateCheck {
+    /// Gets if the binary should be checked again for the provided version selector.
+    pub fn is_stale(&self, current_time: u64, version_selector: Option<&str>) -> bool {
+        self.version_selector.as_deref() != version_selector
+            || current_time >= self.checked_time.saturating_add(UPDATE_CHECK_INTERVAL_SECS)
+    }
+}
+
+/// Gets the last check for a newer version of the binary.
+pub fn get_update_check(environment: &impl Environment, name: &BinaryName) -> Option<UpdateCheck> {
+    read_update_checks(environment).remove(&name.to_string())
+}
+
+/// Stores the result of checking for a newer version of the binary.
+pub fn set_update_check(
+    environment: &impl Environment,
+    name: &BinaryName,
+    update_check: UpdateCheck,
+) -> Result<(), ErrBox> {
+    let mut update_checks = read_update_checks(environment);
+    update_checks.insert(name.to_string(), update_check);
+    environment.write_file_text(
+        &get_update_checks_file_path(environment),
+        &serde_json::to_string_pretty(&update_checks)?,
+    )
+}
+
+fn read_update_checks(environment: &impl Environment) -> BTreeMap<String, UpdateCheck> {
+    // the file is only a cache, so start over when it can't be read
+    environment
+        .read_file_text(&get_update_checks_file_path(environment))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn get_update_checks_file_path(environment: &impl Environment) -> PathBuf {
+    environment.get_local_user_data_dir().join("update-checks.json")
+}
@@ -0,0 +1,441 @@
+use dprint_cli_core::checksums::ChecksumPathOrUrl;
+use dprint_cli_core::types::ErrBox;
+use serde::{self, Deserialize, Serialize};
+use std::collections::HashMap;
+use url::Url;
+
+use super::Registry;
+use crate::environment::{read_user_config, CacheValidators, ConditionalDownload, Environment};
+use crate::errors::ErrorCode;
+use crate::types::{BinaryName, Version};
+use crate::utils;
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryFile {
+    pub schema_version: u32,
+    pub binaries: Vec<RegistryBinary>,
+}
+
+impl RegistryFile {
+    pub fn take_binary_with_name(self, name: &BinaryName) -> Option<RegistryBinary> {
+        self.binaries
+            .into_iter()
+            .filter(|b| b.owner == name.owner && b.name == name.name)
+            .next()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryBinary {
+    name: String,
+    owner: String,
+    pub description: String,
+    pub versions: Vec<RegistryVersionInfo>,
+    /// Names that refer to a specific version (ex. "latest", "lts", "nightly").
+    #[serde(default)]
+    pub tags: HashMap<String, Version>,
+    /// Url of the release notes of a version with `{version}` in place of the version
+    /// (ex. "https://github.com/owner/name/releases/tag/v{version}").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_notes_url: Option<String>,
+    /// Url of a file containing more versions for binaries with many versions (schema version 2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_page: Option<String>,
+}
+
+impl RegistryBinary {
+    pub fn get_binary_name(&self) -> BinaryName {
+        BinaryName::new(self.owner.clone(), self.name.clone())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryVersionInfo {
+    pub version: Version,
+    /// Url of the binary manifest file, which is empty for toolchains.
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub checksum: String,
+    /// The date the version was released (ex. "2020-11-03").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<String>,
+    /// The platforms the version provides a binary for (ex. "linux-x86_64"). Not known when not provided.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<String>>,
+    /// The binaries installed by the version when it's a toolchain, which are binary names with
+    /// a version (ex. "denoland/deno@1.6.0") or binary manifest urls.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binaries: Vec<String>,
+}
+
+impl RegistryVersionInfo {
+    /// Gets if the version is a toolchain that installs a set of other binaries instead of a binary of its own.
+    pub fn is_toolchain(&self) -> bool {
+        !self.binaries.is_empty()
+    }
+
+    /// Gets if the version provides a binary for any of the provided platforms. Assumes it does if the
+    /// registry didn't specify the platforms.
+    pub fn is_available_for_platforms(&self, platform_keys: &[&str]) -> bool {
+        match &self.platforms {
+            Some(platforms) => platforms
+                .iter()
+                .any(|platform| platform_keys.contains(&platform.as_str())),
+            None => true,
+        }
+    }
+
+    pub fn get_url(&self) -> ChecksumPathOrUrl {
+        ChecksumPathOrUrl {
+            path_or_url: self.path.clone(),
+            checksum: Some(self.checksum.clone()),
+        }
+    }
+}
+
+/// Adds the version of the binary to the text of a registry file, replacing the version when it exists
+/// and creating the binary or file when they don't. Returns the text of the updated file.
+pub fn add_version_to_registry_file(
+    file_text: Option<&str>,
+    binary_name: &BinaryName,
+    description: &str,
+    version_info: RegistryVersionInfo,
+) -> Result<String, ErrBox> {
+    let mut file = match file_text {
+        Some(file_text) => read_registry_file(file_text.as_bytes())?,
+        None => RegistryFile {
+            schema_version: REGISTRY_FILE_SCHEMA_VERSION,
+            binaries: Vec::new(),
+        },
+    };
+    let binary = match file
+        .binaries
+        .iter()
+        .position(|b| b.owner == binary_name.owner && b.name == binary_name.name)
+    {
+        Some(index) => &mut file.binaries[index],
+        None => {
+            file.binaries.push(RegistryBinary {
+                name: binary_name.name.as_str().to_string(),
+                owner: binary_name.owner.clone(),
+                description: description.to_string(),
+                versions: Vec::new(),
+                tags: HashMap::new(),
+                release_notes_url: None,
+                next_page: None,
+            });
+            file.binaries.last_mut().unwrap()
+        }
+    };
+    binary.versions.retain(|v| v.version != version_info.version);
+    binary.versions.push(version_info);
+    // newest versions first
+    binary.versions.sort_by(|a, b| b.version.cmp(&a.version));
+    Ok(format!("{}\n", serde_json::to_string_pretty(&file)?))
+}
+
+/// A page of versions referenced by a binary's `nextPage`.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct RegistryVersionsPage {
+    versions: Vec<RegistryVersionInfo>,
+    #[serde(default)]
+    next_page: Option<String>,
+}
+
+/// Caches downloaded registry files so each registry is only downloaded once per invocation.
+#[derive(Default)]
+pub struct RegistryFileCache {
+    files: HashMap<String, RegistryFile>,
+}
+
+impl RegistryFileCache {
+    pub fn new() -> RegistryFileCache {
+        RegistryFileCache { files: HashMap::new() }
+    }
+
+    pub fn get<TEnvironment: Environment>(
+        &mut self,
+        environment: &TEnvironment,
+        url: &str,
+    ) -> Result<RegistryFile, ErrBox> {
+        if let Some(file) = self.files.get(url) {
+            return Ok(file.clone());
+        }
+        let file = download_registry_file(environment, url)?;
+        self.files.insert(url.to_string(), file.clone());
+        Ok(file)
+    }
+
+    /// Downloads the registry files that aren't cached in parallel.
+    pub fn prefetch<TEnvironment: Environment>(
+        &mut self,
+        environment: &TEnvironment,
+        urls: Vec<String>,
+    ) -> Result<(), ErrBox> {
+        let mut handles = Vec::new();
+        for url in urls.into_iter() {
+            if self.files.contains_key(&url) || handles.iter().any(|(handle_url, _)| handle_url == &url) {
+                continue;
+            }
+            let environment = environment.clone();
+            let thread_url = url.clone();
+            handles.push((
+                url,
+                std::thread::spawn(move || download_registry_file(&environment, &thread_url)),
+            ));
+        }
+        for (url, handle) in handles.into_iter() {
+            let file = handle.join().unwrap()?;
+            self.files.insert(url, file);
+        }
+        Ok(())
+    }
+}
+
+pub fn download_registry_file<'a, TEnvironment: Environment>(
+    environment: &TEnvironment,
+    url: &str,
+) -> Result<RegistryFile, ErrBox> {
+    let mut registry_file = read_registry_file(&download_registry_bytes(environment, url)?)?;
+
+    for binary in registry_file.binaries.iter_mut() {
+        let mut visited_urls = Vec::new();
+        while let Some(page_url) = binary.next_page.take() {
+            let page_url = resolve_page_url(url, &page_url)?;
+            if visited_urls.contains(&page_url) {
+                return err!(
+                    "Found a cycle in the version pages of {}/{}.",
+                    binary.owner,
+                    binary.name
+                );
+            }
+            let page = read_versions_page(&download_registry_bytes(environment, &page_url)?)?;
+            binary.versions.extend(page.versions);
+            binary.next_page = page.next_page;
+            visited_urls.push(page_url);
+        }
+    }
+
+    Ok(registry_file)
+}
+
+/// Finds the url of the registry that lists the binary manifest file at the provided url.
+pub fn find_registry_url_of_binary(
+    environment: &impl Environment,
+    binary_name: &BinaryName,
+    plugin_file_url: &str,
+) -> Option<String> {
+    let registry = Registry::load(environment);
+    for url_result in registry.get_urls(&binary_name.to_selector()) {
+        let registry_binary = match download_registry_file(environment, &url_result.url) {
+            Ok(registry_file) => registry_file.take_binary_with_name(binary_name),
+            Err(err) => {
+                log_verbose!(environment, "Error downloading registry {}: {}", url_result.url, err);
+                None
+            }
+        };
+        if let Some(registry_binary) = registry_binary {
+            if registry_binary.versions.iter().any(|v| v.path == plugin_file_url) {
+                return Some(url_result.url);
+            }
+        }
+    }
+    None
+}
+
+/// Default number of seconds a cached registry file is used without being revalidated.
+const DEFAULT_REGISTRY_CACHE_TTL: u64 = 300;
+
+fn download_registry_bytes(environment: &impl Environment, url: &str) -> Result<Vec<u8>, ErrBox> {
+    download_cached_file(environment, &get_registry_cache(environment), "registry", url)
+}
+
+/// Downloads the file at the url through the cache. A cached copy is used without being revalidated
+/// within the registry cache ttl and is the only copy used when offline.
+pub fn download_cached_file(
+    environment: &impl Environment,
+    cache: &utils::UrlCache,
+    file_description: &str,
+    url: &str,
+) -> Result<Vec<u8>, ErrBox> {
+    if environment.is_offline() {
+        return match cache.get(environment, url) {
+            Some(entry) => Ok(entry.bytes),
+            None => coded_err!(
+                ErrorCode::Offline,
+                "Could not find a cached copy of the {} at {}. Run the command without being offline to download it.",
+                file_description,
+                url
+            ),
+        };
+    }
+    if let Some(entry) = cache.get(environment, url) {
+        if is_fresh_cached_entry(environment, &entry) {
+            return Ok(entry.bytes);
+        }
+    }
+
+    // another process may have updated the cached copy while waiting for the lock
+    let _download_lock = utils::DownloadLock::acquire(environment, url)?;
+    let cached_entry = cache.get(environment, url);
+    if let Some(entry) = &cached_entry {
+        if is_fresh_cached_entry(environment, entry) {
+            return Ok(entry.bytes.clone());
+        }
+    }
+
+    let validators = match &cached_entry {
+        Some(entry) => entry.metadata.validators.clone(),
+        None => CacheValidators::default(),
+    };
+    match environment.download_file_if_modified(&url, &validators)? {
+        ConditionalDownload::Modified(file_bytes, validators) => {
+            // keep a copy for when offline
+            let _ignore = cache.set(environment, url, &file_bytes, validators);
+            Ok(file_bytes)
+        }
+        ConditionalDownload::NotModified => match cached_entry {
+            Some(entry) => {
+                let _ignore = cache.set_validated(environment, &entry.metadata, validators);
+                Ok(entry.bytes)
+            }
+            None => err!(
+                "Error downloading {}. Received not modified without a cached copy.",
+                url
+            ),
+        },
+    }
+}
+
+/// Gets if the cached copy was validated within the cache ttl.
+fn is_fresh_cached_entry(environment: &impl Environment, entry: &utils::UrlCacheEntry) -> bool {
+    let ttl = get_registry_cache_ttl(environment);
+    !environment.is_refresh() && environment.get_time_secs() < entry.metadata.validated_time.saturating_add(ttl)
+}
+
+fn get_registry_cache_ttl(environment: &impl Environment) -> u64 {
+    if let Some(ttl) = environment
+        .get_env_var("BVM_REGISTRY_CACHE_TTL")
+        .and_then(|value| value.parse::<u64>().ok())
+    {
+        return ttl;
+    }
+    read_user_config(environment)
+        .ok()
+        .and_then(|config| config.registry_cache_ttl)
+        .unwrap_or(DEFAULT_REGISTRY_CACHE_TTL)
+}
+
+/// Resolves a page url relative to the registry file's url.
+fn resolve_page_url(registry_url: &str, page_url: &str) -> Result<String, ErrBox> {
+    match Url::parse(registry_url).and_then(|url| url.join(page_url)) {
+        Ok(url) => Ok(url.to_string()),
+        Err(err) => err!("Error resolving registry page url {}. {}", page_url, err.to_string()),
+    }
+}
+
+/// Removes the cached copies of the registry file and its version pages, returning the number removed.
+pub fn remove_cached_registry_file(environment: &impl Environment, url: &str) -> Result<usize, ErrBox> {
+    let cache = get_registry_cache(environment);
+    let mut removed_count = 0;
+    // (url, the url of the registry file when it's a version page)
+    let mut pending = vec![(url.to_string(), None::<String>)];
+    let mut visited_urls = Vec::new();
+    while let Some((url, registry_url)) = pending.pop() {
+        if visited_urls.contains(&url) {
+            continue;
+        }
+        if let Some(entry) = cache.get(environment, &url) {
+            let base_url = registry_url.clone().unwrap_or_else(|| url.clone());
+            let page_urls: Vec<String> = match &registry_url {
+                None => read_registry_file(&entry.bytes)
+                    .map(|file| file.binaries.into_iter().filter_map(|b| b.next_page).collect())
+                    .unwrap_or_default(),
+                Some(_) => read_versions_page(&entry.bytes)
+                    .map(|page| page.next_page.into_iter().collect())
+                    .unwrap_or_default(),
+            };
+            for page_url in page_urls {
+                if let Ok(page_url) = resolve_page_url(&base_url, &page_url) {
+                    pending.push((page_url, Some(base_url.clone())));
+                }
+            }
+        }
+        if cache.remove(environment, &url)? {
+            removed_count += 1;
+        }
+        visited_urls.push(url);
+    }
+    Ok(removed_count)
+}
+
+/// Gets the cache of the downloaded registry files.
+pub fn get_registry_cache(environment: &impl Environment) -> utils::UrlCache {
+    utils::UrlCache::new(environment, "registry-cache")
+}
+
+/// Latest schema version of registry files that this version of bvm reads.
+const REGISTRY_FILE_SCHEMA_VERSION: u32 = 2;
+
+fn read_registry_file(file_bytes: &[u8]) -> Result<RegistryFile, ErrBox> {
+    let value = match serde_json::from_slice::<serde_json::Value>(&file_bytes) {
+        Ok(value) => upgrade_registry_file(value)?,
+        Err(err) => {
+            return coded_err!(
+                ErrorCode::Registry,
+                "Error deserializing registry file. {}",
+                err.to_string()
+            )
+        }
+    };
+    match serde_json::from_value::<RegistryFile>(value) {
+        Ok(file) => {
+            for binary in file.binaries.iter() {
+                verify_binary_name(&binary)?;
+            }
+
+            Ok(file)
+        }
+        Err(err) => coded_err!(
+            ErrorCode::Registry,
+            "Error deserializing registry file. {}",
+            err.to_string()
+        ),
+    }
+}
+
+/// Checks the schema version of a registry file and upgrades files of older schemas to the latest one.
+fn upgrade_registry_file(value: serde_json::Value) -> Result<serde_json::Value, ErrBox> {
+    let mut file = match value {
+        serde_json::Value::Object(file) => file,
+        _ => return coded_err!(ErrorCode::Registry, "Expected the registry file to be an object."),
+    };
+    // schema version 2 only adds optional properties to version 1, so nothing needs to change
+    utils::get_supported_schema_version(&file, "registry file", REGISTRY_FILE_SCHEMA_VERSION)?;
+    file.insert("schemaVersion".to_string(), REGISTRY_FILE_SCHEMA_VERSION.into());
+    Ok(serde_json::Value::Object(file))
+}
+
+fn read_versions_page(file_bytes: &[u8]) -> Result<RegistryVersionsPage, ErrBox> {
+    match serde_json::from_slice::<RegistryVersionsPage>(file_bytes) {
+        Ok(page) => Ok(page),
+        Err(err) => coded_err!(
+            ErrorCode::Registry,
+            "Error deserializing registry page file. {}",
+            err.to_string()
+        ),
+    }
+}
+
+fn verify_binary_name(binary: &RegistryBinary) -> Result<(), ErrBox> {
+    if binary.name.contains("/") || binary.owner.contains("/") {
+        return err!("The binary owner and name may not contain a forward slash.");
+    }
+
+    Ok(())
+}
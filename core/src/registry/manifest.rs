@@ -11,6 +11,9 @@ use crate::types::{BinaryName, NameSelector};
 #[serde(rename_all = "camelCase")]
 pub struct Registry {
     name_to_urls: HashMap<BinaryName, Vec<String>>,
+    /// Names given to the registry urls with `bvm registry add --name` or `bvm registry rename`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    url_names: HashMap<String, String>,
 }
 
 pub struct RegistryItem {
@@ -32,6 +35,7 @@ impl RegistryItem {
     }
 }
 
+#[derive(PartialEq)]
 pub struct UrlResult {
     pub owner: String,
     pub url: String,
@@ -41,6 +45,7 @@ impl Registry {
     fn new() -> Registry {
         Registry {
             name_to_urls: HashMap::new(),
+            url_names: HashMap::new(),
         }
     }
 
@@ -112,6 +117,50 @@ impl Registry {
         for key in keys_to_remove {
             self.name_to_urls.remove(&key);
         }
+        self.url_names.remove(url);
+    }
+
+    /// Gets the sorted urls of the registries.
+    pub fn urls(&self) -> Vec<String> {
+        let mut urls = self.name_to_urls.values().flatten().cloned().collect::<Vec<_>>();
+        urls.sort();
+        urls.dedup();
+        urls
+    }
+
+    /// Gets the sorted names of the binaries associated with the registry url.
+    pub fn get_url_binary_names(&self, url: &str) -> Vec<BinaryName> {
+        let mut names = self
+            .name_to_urls
+            .iter()
+            .filter(|(_, urls)| urls.iter().any(|item| item == url))
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    pub fn get_url_name(&self, url: &str) -> Option<&String> {
+        self.url_names.get(url)
+    }
+
+    pub fn set_url_name(&mut self, url: &str, name: Option<String>) {
+        match name {
+            Some(name) => self.url_names.insert(url.to_string(), name),
+            None => self.url_names.remove(url),
+        };
+    }
+
+    /// Finds the url of the registry with the provided name or url.
+    pub fn find_url(&self, url_or_name: &str) -> Option<String> {
+        if let Some((url, _)) = self.url_names.iter().find(|(_, name)| *name == url_or_name) {
+            return Some(url.clone());
+        }
+        self.urls().into_iter().find(|url| url == url_or_name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &BinaryName> {
+        self.name_to_urls.keys()
     }
 
     pub fn items(&self) -> Vec<RegistryItem> {
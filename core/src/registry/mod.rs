@@ -1,5 +1,7 @@
 mod download_registry_file;
 mod manifest;
+mod source_provider;
 
 pub use download_registry_file::*;
 pub use manifest::*;
+pub use source_provider::*;
@@ -4,7 +4,35 @@ use semver::{Version as SemVersion, VersionReq as SemVersionReq};
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::{Ord, Ordering, PartialOrd};
 use std::fmt;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+
+use crate::utils::{get_edit_distance, is_glob_match};
+
+static IS_CASE_INSENSITIVE_NAMES: AtomicBool = AtomicBool::new(cfg!(target_os = "windows"));
+
+/// Sets whether command names and the names of name selectors are matched case insensitively,
+/// which is the default on Windows because its shells and file system are.
+pub fn set_case_insensitive_names(value: bool) {
+    IS_CASE_INSENSITIVE_NAMES.store(value, AtomicOrdering::SeqCst);
+}
+
+pub fn is_case_insensitive_names() -> bool {
+    IS_CASE_INSENSITIVE_NAMES.load(AtomicOrdering::SeqCst)
+}
+
+/// Gets the text to compare a name by, which is lowercase when names are case insensitive.
+pub fn get_name_comparison_key(name: &str) -> std::borrow::Cow<'_, str> {
+    if is_case_insensitive_names() {
+        std::borrow::Cow::Owned(name.to_lowercase())
+    } else {
+        std::borrow::Cow::Borrowed(name)
+    }
+}
+
+fn is_name_glob_match(pattern: &str, name: &str) -> bool {
+    is_glob_match(&get_name_comparison_key(pattern), &get_name_comparison_key(name))
+}
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct NameSelector {
@@ -13,10 +41,11 @@ pub struct NameSelector {
 }
 
 impl NameSelector {
+    /// Gets if the name matches, where the owner and name of the selector may contain `*` and `?` wildcards.
     pub fn is_match(&self, name: &BinaryName) -> bool {
-        if name.name.as_str() == self.name {
+        if is_name_glob_match(&self.name, &name.name) {
             if let Some(owner_name) = &self.owner {
-                owner_name == &name.owner
+                is_name_glob_match(owner_name, &name.owner)
             } else {
                 true
             }
@@ -24,6 +53,32 @@ impl NameSelector {
             false
         }
     }
+
+    pub fn has_wildcard(&self) -> bool {
+        let has_wildcard = |text: &str| text.contains(['*', '?']);
+        has_wildcard(&self.name) || self.owner.as_deref().map(has_wildcard).unwrap_or(false)
+    }
+
+    /// Gets the name that's the closest misspelling of the selector, if any are close enough to suggest.
+    pub fn get_closest_name<'a>(&self, names: impl Iterator<Item = &'a BinaryName>) -> Option<&'a BinaryName> {
+        if self.has_wildcard() {
+            return None;
+        }
+        let selector_text = self.to_string();
+        let max_distance = std::cmp::max(1, selector_text.chars().count() / 3);
+        names
+            .map(|name| {
+                // differences in case are the most likely mistake, so don't count them
+                let distance = match &self.owner {
+                    Some(_) => get_edit_distance(&selector_text.to_lowercase(), &name.to_string().to_lowercase()),
+                    None => get_edit_distance(&self.name.to_lowercase(), &name.name.to_lowercase()),
+                };
+                (distance, name)
+            })
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by(|(a_distance, a_name), (b_distance, b_name)| a_distance.cmp(b_distance).then(a_name.cmp(b_name)))
+            .map(|(_, name)| name)
+    }
 }
 
 impl fmt::Display for NameSelector {
@@ -78,13 +133,18 @@ impl Version {
         self.sem_ver.is_prerelease()
     }
 
+    /// Gets the major and minor parts of the version (ex. `1.39` for `1.39.2`).
+    pub fn get_major_minor(&self) -> String {
+        format!("{}.{}", self.sem_ver.major, self.sem_ver.minor)
+    }
+
     pub fn to_selector(&self) -> VersionSelector {
         VersionSelector::parse(&self.full_text).unwrap() // should always work
     }
 }
 
 /// For testing purposes.
-#[cfg(test)]
+#[cfg(any(test, feature = "testing"))]
 impl From<&str> for Version {
     fn from(value: &str) -> Self {
         Version::parse(value).unwrap()
@@ -145,9 +205,11 @@ impl<'de> Deserialize<'de> for Version {
     }
 }
 
+#[derive(Clone)]
 pub struct VersionSelector {
     full_text: String,
     version_req: SemVersionReq,
+    include_prerelease: bool,
 }
 
 impl VersionSelector {
@@ -180,16 +242,49 @@ impl VersionSelector {
         Ok(VersionSelector {
             full_text: full_text.to_string(),
             version_req,
+            include_prerelease: false,
         })
     }
 
+    /// Opts into matching pre-release versions that are within the selector's range.
+    pub fn with_prerelease(mut self) -> VersionSelector {
+        self.include_prerelease = true;
+        self
+    }
+
+    /// Gets if pre-release versions may be selected, which is when explicitly
+    /// opted into or when the selector is a pre-release range (ex. `^1.2.0-beta`).
+    pub fn includes_prerelease(&self) -> bool {
+        lazy_static! {
+            static ref PRE_RELEASE_RE: Regex = Regex::new(r"[0-9]-[0-9A-Za-z]").unwrap();
+        }
+        self.include_prerelease || PRE_RELEASE_RE.is_match(&self.full_text)
+    }
+
     pub fn as_str(&self) -> &str {
         &self.full_text
     }
 
     pub fn matches(&self, version: &Version) -> bool {
-        self.version_req.matches(&version.sem_ver)
+        if self.version_req.matches(&version.sem_ver) {
+            true
+        } else if self.include_prerelease && version.is_prerelease() {
+            // match based on the release version
+            let mut release_version = version.sem_ver.clone();
+            release_version.pre = Vec::new();
+            self.version_req.matches(&release_version)
+        } else {
+            false
+        }
+    }
+}
+
+/// Gets if the text can be used as the name of a user defined version alias (ex. "stable").
+pub fn is_valid_version_alias(text: &str) -> bool {
+    lazy_static! {
+        static ref ALIAS_RE: Regex = Regex::new(r"^[A-Za-z][A-Za-z0-9_\-]*$").unwrap();
     }
+    ALIAS_RE.is_match(text) && text.to_lowercase() != "path" && VersionSelector::parse(text).is_err()
 }
 
 impl fmt::Display for VersionSelector {
@@ -283,9 +378,24 @@ impl Ord for BinaryName {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+/// Name of a command, which is compared case insensitively when names are case insensitive.
+#[derive(Debug, Clone)]
 pub struct CommandName(String);
 
+impl PartialEq for CommandName {
+    fn eq(&self, other: &Self) -> bool {
+        get_name_comparison_key(&self.0) == get_name_comparison_key(&other.0)
+    }
+}
+
+impl Eq for CommandName {}
+
+impl Hash for CommandName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        get_name_comparison_key(&self.0).hash(state);
+    }
+}
+
 impl CommandName {
     pub fn from_string(value: String) -> CommandName {
         CommandName(value)
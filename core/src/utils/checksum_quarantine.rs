@@ -0,0 +1,95 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::environment::Environment;
+
+/// Error for when a download didn't match its expected checksum.
+#[derive(Debug)]
+pub struct ChecksumMismatchError {
+    pub url: String,
+    pub expected_checksum: String,
+    pub actual_checksum: String,
+    /// Where the download was moved to for inspecting it.
+    pub quarantine_path: Option<PathBuf>,
+}
+
+impl fmt::Display for ChecksumMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The checksum {} did not match the expected checksum of {}.",
+            self.actual_checksum, self.expected_checksum
+        )?;
+        if let Some(quarantine_path) = &self.quarantine_path {
+            write!(f, " The download was quarantined at {}.", quarantine_path.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ChecksumMismatchError {}
+
+/// Verifies the checksum of the bytes downloaded from the url. When it doesn't match, the bytes are
+/// kept in a quarantine directory so that what was downloaded may be inspected.
+pub fn verify_download_checksum(
+    environment: &impl Environment,
+    url: &str,
+    bytes: &[u8],
+    expected_checksum: &str,
+) -> Result<(), ErrBox> {
+    let actual_checksum = get_sha256_checksum(bytes);
+    if actual_checksum == expected_checksum {
+        return Ok(());
+    }
+    let quarantine_path =
+        get_quarantine_dir(environment).join(format!("{}-{}", actual_checksum, get_url_file_name(url)));
+    // a failure to quarantine shouldn't hide the mismatch
+    let was_quarantined = environment.create_dir_all(quarantine_path.parent().unwrap()).is_ok()
+        && environment.write_file(&quarantine_path, bytes).is_ok();
+    Err(Box::new(ChecksumMismatchError {
+        url: url.to_string(),
+        expected_checksum: expected_checksum.to_string(),
+        actual_checksum,
+        quarantine_path: if was_quarantined { Some(quarantine_path) } else { None },
+    }))
+}
+
+/// Gets the directory downloads that didn't match their checksum are moved to.
+pub fn get_quarantine_dir(environment: &impl Environment) -> PathBuf {
+    environment.get_user_data_dir().join("quarantine")
+}
+
+fn get_url_file_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap();
+    match path.rsplit(['/', '\\']).next() {
+        Some(file_name) if !file_name.is_empty() => file_name,
+        _ => "download",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::environment::TestEnvironment;
+
+    #[test]
+    fn should_quarantine_mismatched_download() {
+        let environment = TestEnvironment::new();
+        let checksum = get_sha256_checksum(b"text");
+        assert_eq!(
+            verify_download_checksum(&environment, "https://localhost/file.zip", b"text", &checksum).is_ok(),
+            true
+        );
+        assert_eq!(environment.path_exists(&get_quarantine_dir(&environment)), false);
+
+        let err = verify_download_checksum(&environment, "https://localhost/dir/file.zip?x=1", b"text", "expected")
+            .err()
+            .unwrap();
+        let err = err.downcast_ref::<ChecksumMismatchError>().unwrap();
+        let quarantine_path = get_quarantine_dir(&environment).join(format!("{}-file.zip", checksum));
+        assert_eq!(err.quarantine_path, Some(quarantine_path.clone()));
+        assert_eq!(environment.read_file_text(&quarantine_path).unwrap(), "text");
+    }
+}
@@ -0,0 +1,44 @@
+use regex::Regex;
+use std::borrow::Cow;
+
+// Styled text contains ANSI escape codes, which the environment removes before
+// outputting when color is disabled.
+
+pub fn bold(text: &str) -> String {
+    style(text, "1")
+}
+
+pub fn green(text: &str) -> String {
+    style(text, "32")
+}
+
+pub fn red(text: &str) -> String {
+    style(text, "31")
+}
+
+pub fn yellow(text: &str) -> String {
+    style(text, "33")
+}
+
+fn style(text: &str, code: &str) -> String {
+    format!("\u{1b}[{}m{}\u{1b}[0m", code, text)
+}
+
+pub fn strip_ansi_codes(text: &str) -> Cow<'_, str> {
+    lazy_static! {
+        static ref ANSI_CODE_RE: Regex = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+    }
+    ANSI_CODE_RE.replace_all(text, "")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_strip_ansi_codes() {
+        let text = format!("{} {}", bold("owner/name"), green("1.0.0"));
+        assert_eq!(text, "\u{1b}[1mowner/name\u{1b}[0m \u{1b}[32m1.0.0\u{1b}[0m");
+        assert_eq!(strip_ansi_codes(&text), "owner/name 1.0.0");
+    }
+}
@@ -0,0 +1,31 @@
+use dprint_cli_core::types::ErrBox;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::environment::Environment;
+
+/// Creates a tar.gz archive of the files in the directory, which keeps whether each file is executable.
+pub fn create_tar_gz(environment: &impl Environment, dir_path: &Path) -> Result<Vec<u8>, ErrBox> {
+    let executable_file_paths = environment
+        .get_executable_file_paths(dir_path)?
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let mut file_paths = environment.get_file_paths(dir_path)?;
+    file_paths.sort();
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for file_path in file_paths {
+        let bytes = environment.read_file(&file_path)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(if executable_file_paths.contains(&file_path) {
+            0o755
+        } else {
+            0o644
+        });
+        builder.append_data(&mut header, file_path.strip_prefix(dir_path)?, bytes.as_slice())?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
@@ -0,0 +1,65 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::{get_download_cache_dir, get_wsl_windows_data_dir, remove_stale_lock, LockFile, STALE_LOCK_SECS};
+use crate::environment::Environment;
+
+const RETRY_INTERVAL_MS: u64 = 100;
+
+/// Prevents multiple bvm processes from downloading the same url into the cache at the
+/// same time by holding a lock file for the url until dropped.
+pub struct DownloadLock<TEnvironment: Environment> {
+    _lock_file: LockFile<TEnvironment>,
+}
+
+impl<TEnvironment: Environment> DownloadLock<TEnvironment> {
+    /// Acquires the lock for the url, waiting for another process to finish downloading it.
+    pub fn acquire(environment: &TEnvironment, url: &str) -> Result<Self, ErrBox> {
+        let file_path = get_download_lock_file_path(environment, url);
+        if let Some(parent_dir_path) = file_path.parent() {
+            environment.create_dir_all(parent_dir_path)?;
+        }
+        // the lock may be held by a Windows process when the downloads are shared with it from WSL
+        let is_local = !matches!(get_wsl_windows_data_dir(environment), Ok(Some(_)));
+        let mut waited_ms = 0;
+        let mut last_lock_text = None;
+        let mut unchanged_ms = 0;
+
+        loop {
+            if let Some(lock_file) = LockFile::try_create(environment, &file_path, is_local)? {
+                return Ok(DownloadLock { _lock_file: lock_file });
+            }
+            // the lock is refreshed while held, so one that didn't change for too long was left behind
+            // even when its time looks recent (ex. the clocks of WSL and Windows differ)
+            let lock_text = environment.read_file_text(&file_path).ok();
+            if lock_text != last_lock_text {
+                last_lock_text = lock_text;
+                unchanged_ms = 0;
+            } else if unchanged_ms >= STALE_LOCK_SECS * 1000 {
+                if let Some(lock_text) = &last_lock_text {
+                    log_verbose!(
+                        environment,
+                        "Removing unchanged download lock at {}.",
+                        file_path.display()
+                    );
+                    remove_stale_lock(environment, &file_path, lock_text);
+                }
+                continue;
+            }
+            if waited_ms == 0 {
+                environment.log_error(&format!("Waiting for another bvm process to download {}...", url));
+            }
+            environment.sleep(Duration::from_millis(RETRY_INTERVAL_MS));
+            waited_ms += RETRY_INTERVAL_MS;
+            unchanged_ms += RETRY_INTERVAL_MS;
+        }
+    }
+}
+
+pub fn get_download_lock_file_path(environment: &impl Environment, url: &str) -> PathBuf {
+    get_download_cache_dir(environment)
+        .join("downloads")
+        .join(format!("{}.lock", get_sha256_checksum(url.as_bytes())))
+}
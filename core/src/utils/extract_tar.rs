@@ -0,0 +1,142 @@
+use dprint_cli_core::types::ErrBox;
+use flate2::read::GzDecoder;
+use std::io::prelude::*;
+use std::path::{Component, Path, PathBuf};
+
+use super::FileWritePool;
+use crate::environment::Environment;
+use tar::{Archive, EntryType};
+
+/// Size of the largest file that's read into memory to be written by the pool of threads. Larger
+/// files are written as they're decompressed.
+const MAX_POOLED_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+pub fn extract_tar_gz(
+    message: &str,
+    environment: &impl Environment,
+    tar_gz_bytes: &[u8],
+    dir_path: &Path,
+) -> Result<(), ErrBox> {
+    let tar_bytes = super::gz_decompress(&tar_gz_bytes)?;
+    extract_tar(message, environment, &tar_bytes, dir_path)
+}
+
+/// Extracts the tar.gz archive as it's read, which is used to extract an archive while it's downloaded.
+pub fn extract_tar_gz_reader(environment: &impl Environment, reader: impl Read, dir_path: &Path) -> Result<(), ErrBox> {
+    let mut archive = Archive::new(GzDecoder::new(reader));
+    extract_entries(environment, &mut archive, dir_path, |_| {})
+}
+
+pub fn extract_tar(
+    message: &str,
+    environment: &impl Environment,
+    tar_bytes: &[u8],
+    dir_path: &Path,
+) -> Result<(), ErrBox> {
+    let length = tar_bytes.len();
+
+    environment.log_action_with_progress(
+        message,
+        move |update_size| -> Result<(), ErrBox> {
+            let reader = std::io::Cursor::new(tar_bytes);
+            let mut a = Archive::new(reader);
+            let mut position = 0;
+            extract_entries(environment, &mut a, dir_path, |entry_size| {
+                position += entry_size;
+                update_size(position as usize);
+            })
+        },
+        length,
+    )
+}
+
+fn extract_entries(
+    environment: &impl Environment,
+    archive: &mut Archive<impl Read>,
+    dir_path: &Path,
+    mut on_entry_extracted: impl FnMut(u64),
+) -> Result<(), ErrBox> {
+    let mut pool = FileWritePool::new(environment);
+    // paths of the symlinks in the archive, which a later entry could be written through
+    let mut symlink_paths: Vec<PathBuf> = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.to_path_buf();
+        let file_path = dir_path.join(&entry_path);
+        if environment.is_real() {
+            let normalized_path = entry_path
+                .components()
+                .filter(|c| !matches!(c, Component::CurDir))
+                .collect::<PathBuf>();
+            let is_pooled_file = entry.header().entry_type() == EntryType::Regular
+                && entry.size() <= MAX_POOLED_FILE_SIZE
+                // let the tar crate handle paths that could be outside the directory
+                && entry_path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+                && !symlink_paths.iter().any(|symlink_path| normalized_path.starts_with(symlink_path));
+            if entry.header().entry_type().is_symlink() {
+                symlink_paths.push(normalized_path);
+            }
+            if is_pooled_file {
+                let mut bytes = Vec::with_capacity(entry.size() as usize);
+                entry.read_to_end(&mut bytes)?;
+                pool.write_file(file_path.clone(), bytes)?;
+                if cfg!(unix) {
+                    pool.set_permissions(file_path, entry.header().mode()?);
+                }
+            } else {
+                if entry.header().entry_type().is_hard_link() || entry.header().entry_type().is_symlink() {
+                    // the file being linked to may still be being written
+                    pool.wait_for_writes()?;
+                }
+                if cfg!(unix) {
+                    entry.set_preserve_permissions(true);
+                }
+
+                entry.unpack_in(&dir_path)?;
+            }
+        } else {
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes)?;
+            pool.write_file(file_path, bytes)?;
+        }
+        on_entry_extracted(entry.size());
+    }
+    pool.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::environment::RealEnvironment;
+
+    #[cfg(unix)]
+    #[test]
+    fn should_not_extract_files_through_symlinks_outside_the_directory() {
+        let temp_dir = std::env::temp_dir().join(format!("bvm-extract-tar-test-{}", std::process::id()));
+        let dir_path = temp_dir.join("dir");
+        let outside_dir_path = temp_dir.join("outside");
+        std::fs::create_dir_all(&dir_path).unwrap();
+        std::fs::create_dir_all(&outside_dir_path).unwrap();
+
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(EntryType::Symlink);
+        header.set_size(0);
+        header.set_link_name(&outside_dir_path).unwrap();
+        builder.append_data(&mut header, "sub", std::io::empty()).unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, "sub/passwd", "text".as_bytes())
+            .unwrap();
+        let tar_bytes = builder.into_inner().unwrap();
+
+        let environment = RealEnvironment::new(false).unwrap();
+        let result = extract_entries(&environment, &mut Archive::new(tar_bytes.as_slice()), &dir_path, |_| {});
+        let outside_file_exists = outside_dir_path.join("passwd").exists();
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+        assert_eq!(result.is_err(), true);
+        assert_eq!(outside_file_exists, false);
+    }
+}
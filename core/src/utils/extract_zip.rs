@@ -2,6 +2,7 @@ use dprint_cli_core::types::ErrBox;
 use std::io::prelude::*;
 use std::path::Path;
 
+use super::FileWritePool;
 use crate::environment::Environment;
 
 // todo: consolidate with code in dprint
@@ -20,7 +21,7 @@ pub fn extract_zip(
     environment.log_action_with_progress(
         message,
         move |update_size| -> Result<(), ErrBox> {
-            // todo: consider parallelizing this
+            let mut pool = FileWritePool::new(environment);
             for i in 0..zip.len() {
                 update_size(i);
                 let mut file = zip.by_index(i).unwrap();
@@ -30,23 +31,16 @@ pub fn extract_zip(
                 if !file.is_dir() {
                     let mut file_bytes = Vec::with_capacity(file.size() as usize);
                     file.read_to_end(&mut file_bytes)?;
-                    environment.write_file(&file_path, &file_bytes)?;
+                    pool.write_file(file_path.clone(), file_bytes)?;
                 } else {
-                    environment.create_dir_all(&file_path)?
+                    pool.create_dir_all(&file_path)?
                 }
 
-                // Get and Set permissions
-                #[cfg(unix)]
-                if environment.is_real() {
-                    use std::fs;
-                    use std::os::unix::fs::PermissionsExt;
-
-                    if let Some(mode) = file.unix_mode() {
-                        fs::set_permissions(&file_path, fs::Permissions::from_mode(mode))?;
-                    }
+                if let Some(mode) = file.unix_mode() {
+                    pool.set_permissions(file_path, mode);
                 }
             }
-            Ok(())
+            pool.finish()
         },
         length,
     )
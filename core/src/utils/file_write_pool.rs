@@ -0,0 +1,173 @@
+use dprint_cli_core::types::ErrBox;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::environment::Environment;
+
+/// Number of files after which the remaining files are written in parallel. Starting the threads isn't
+/// worth it for archives with only a few files.
+const PARALLEL_FILE_COUNT: usize = 64;
+/// Maximum number of threads that write files.
+const MAX_WORKER_COUNT: usize = 8;
+
+struct FileWrite {
+    file_path: PathBuf,
+    bytes: Vec<u8>,
+}
+
+/// Writes the files extracted from an archive. Once an archive has many files, the writes are fanned
+/// out to a pool of threads while the archive continues to be decompressed on the current thread.
+/// Permissions are set for all the files after they're written.
+pub struct FileWritePool<'a, TEnvironment: Environment> {
+    environment: &'a TEnvironment,
+    file_count: usize,
+    sender: Option<SyncSender<FileWrite>>,
+    workers: Vec<JoinHandle<Result<(), ErrBox>>>,
+    created_dir_paths: HashSet<PathBuf>,
+    permissions: Vec<(PathBuf, u32)>,
+}
+
+impl<'a, TEnvironment: Environment> FileWritePool<'a, TEnvironment> {
+    pub fn new(environment: &'a TEnvironment) -> Self {
+        FileWritePool {
+            environment,
+            file_count: 0,
+            sender: None,
+            workers: Vec::new(),
+            created_dir_paths: HashSet::new(),
+            permissions: Vec::new(),
+        }
+    }
+
+    pub fn create_dir_all(&mut self, dir_path: &Path) -> Result<(), ErrBox> {
+        if !self.created_dir_paths.contains(dir_path) {
+            self.environment.create_dir_all(dir_path)?;
+            self.created_dir_paths.insert(dir_path.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// Writes the file, which may still be in progress once this returns.
+    pub fn write_file(&mut self, file_path: PathBuf, bytes: Vec<u8>) -> Result<(), ErrBox> {
+        // create the directories on the current thread so the threads don't race to create them
+        if let Some(parent_dir_path) = file_path.parent() {
+            self.create_dir_all(parent_dir_path)?;
+        }
+        self.file_count += 1;
+        if self.sender.is_none() && self.file_count >= PARALLEL_FILE_COUNT {
+            self.start_workers();
+        }
+        match &self.sender {
+            Some(sender) => {
+                if sender.send(FileWrite { file_path, bytes }).is_err() {
+                    // every thread stopped because of an error, so surface it
+                    self.wait_for_writes()?;
+                }
+                Ok(())
+            }
+            None => self.environment.write_file(&file_path, &bytes),
+        }
+    }
+
+    /// Sets the permissions of the file once all the files are written. This is only done
+    /// on unix for the real file system.
+    pub fn set_permissions(&mut self, file_path: PathBuf, mode: u32) {
+        self.permissions.push((file_path, mode));
+    }
+
+    /// Waits for the files being written, which is necessary before anything refers to them (ex. a link).
+    pub fn wait_for_writes(&mut self) -> Result<(), ErrBox> {
+        self.sender.take();
+        let mut result = Ok(());
+        for worker in self.workers.drain(..) {
+            let worker_result = worker.join().unwrap();
+            if result.is_ok() {
+                result = worker_result;
+            }
+        }
+        result
+    }
+
+    /// Waits for the files to be written then sets their permissions.
+    pub fn finish(mut self) -> Result<(), ErrBox> {
+        self.wait_for_writes()?;
+        let permissions = std::mem::take(&mut self.permissions);
+        #[cfg(unix)]
+        if self.environment.is_real() {
+            use std::fs;
+            use std::os::unix::fs::PermissionsExt;
+
+            for (file_path, mode) in permissions {
+                fs::set_permissions(&file_path, fs::Permissions::from_mode(mode))?;
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = permissions;
+        Ok(())
+    }
+
+    fn start_workers(&mut self) {
+        let worker_count = std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1)
+            .min(MAX_WORKER_COUNT);
+        // bound the number of queued files so that decompressing doesn't get too far ahead of writing
+        let (sender, receiver) = sync_channel::<FileWrite>(worker_count * 4);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..worker_count {
+            let environment = self.environment.clone();
+            let receiver = receiver.clone();
+            self.workers
+                .push(std::thread::spawn(move || write_queued_files(&environment, &receiver)));
+        }
+        self.sender = Some(sender);
+    }
+}
+
+impl<'a, TEnvironment: Environment> Drop for FileWritePool<'a, TEnvironment> {
+    fn drop(&mut self) {
+        // don't leave threads writing into a directory that's being cleaned up after an error
+        let _ignore = self.wait_for_writes();
+    }
+}
+
+fn write_queued_files(environment: &impl Environment, receiver: &Mutex<Receiver<FileWrite>>) -> Result<(), ErrBox> {
+    loop {
+        let file_write = match receiver.lock().unwrap().recv() {
+            Ok(file_write) => file_write,
+            Err(_) => return Ok(()), // all the files were queued
+        };
+        environment.write_file(&file_write.file_path, &file_write.bytes)?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::environment::TestEnvironment;
+
+    #[test]
+    fn should_write_files_in_parallel() {
+        let environment = TestEnvironment::new();
+        let mut pool = FileWritePool::new(&environment);
+        let file_count = PARALLEL_FILE_COUNT * 3;
+        for i in 0..file_count {
+            pool.write_file(PathBuf::from(format!("/dir/sub/{}.txt", i)), i.to_string().into_bytes())
+                .unwrap();
+        }
+        assert_eq!(pool.workers.is_empty(), false);
+        pool.finish().unwrap();
+
+        for i in 0..file_count {
+            assert_eq!(
+                environment
+                    .read_file_text(&PathBuf::from(format!("/dir/sub/{}.txt", i)))
+                    .unwrap(),
+                i.to_string()
+            );
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+
+use crate::environment::Environment;
+use crate::types::CommandName;
+
+pub fn get_path_executable_path(environment: &impl Environment, command_name: &CommandName) -> Option<PathBuf> {
+    let shim_dir = super::get_shim_dir(environment);
+    let env_path = environment.get_env_path();
+    let paths = std::env::split_paths(&env_path).filter(|path_dir| path_dir != &shim_dir);
+    get_command_executable_path_in_dirs(environment, command_name, paths)
+}
+
+/// Gets the path of the first executable for the command in the directories, which are searched in order.
+/// On Windows, each directory is searched for the extensions of the `PATHEXT` environment variable in order
+/// like cmd.exe does. The current directory is not searched first because cmd.exe would have already found
+/// an executable there before running the shim.
+pub fn get_command_executable_path_in_dirs(
+    environment: &impl Environment,
+    command_name: &CommandName,
+    dirs: impl Iterator<Item = PathBuf>,
+) -> Option<PathBuf> {
+    let executable_file_names = get_executable_file_names_for_command(environment, &command_name);
+
+    for path_dir in dirs {
+        if path_dir.as_os_str().is_empty() {
+            continue;
+        }
+        for executable_file_name in executable_file_names.iter() {
+            let final_path = path_dir.join(executable_file_name);
+            if environment.path_exists(&final_path) {
+                return Some(final_path);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(unix)]
+fn get_executable_file_names_for_command(_environment: &impl Environment, command_name: &CommandName) -> Vec<String> {
+    let command_name = command_name.as_str().to_lowercase();
+    vec![command_name.to_string(), format!("{}.sh", command_name)]
+}
+
+#[cfg(target_os = "windows")]
+fn get_executable_file_names_for_command(environment: &impl Environment, command_name: &CommandName) -> Vec<String> {
+    get_path_ext_file_names(command_name.as_str(), environment.get_env_var("PATHEXT").as_deref())
+}
+
+/// Extensions cmd.exe uses when the `PATHEXT` environment variable isn't set.
+#[cfg(any(target_os = "windows", test))]
+const DEFAULT_PATH_EXT: &str = ".COM;.EXE;.BAT;.CMD;.VBS;.VBE;.JS;.JSE;.WSF;.WSH;.MSC";
+
+/// Gets the file names cmd.exe tries in each directory for the command. A command that already has an
+/// extension is tried as-is first. Otherwise, or when that doesn't exist, each extension is appended in order.
+#[cfg(any(target_os = "windows", test))]
+fn get_path_ext_file_names(command_name: &str, path_ext: Option<&str>) -> Vec<String> {
+    let command_name = command_name.to_lowercase();
+    let path_ext = path_ext
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or(DEFAULT_PATH_EXT);
+    let mut results = Vec::new();
+    if std::path::Path::new(&command_name).extension().is_some() {
+        results.push(command_name.clone());
+    }
+    for extension in path_ext.split(';').map(|ext| ext.trim()).filter(|ext| !ext.is_empty()) {
+        let extension = extension.to_lowercase();
+        let extension = if extension.starts_with('.') {
+            extension
+        } else {
+            format!(".{}", extension)
+        };
+        let file_name = format!("{}{}", command_name, extension);
+        if !results.contains(&file_name) {
+            results.push(file_name);
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_use_path_ext_order() {
+        assert_eq!(
+            get_path_ext_file_names("Deno", Some(".COM;.EXE;.BAT;.CMD;.PS1")),
+            vec!["deno.com", "deno.exe", "deno.bat", "deno.cmd", "deno.ps1"]
+        );
+        assert_eq!(
+            get_path_ext_file_names("deno", Some(" ;exe; .CMD")),
+            vec!["deno.exe", "deno.cmd"]
+        );
+    }
+
+    #[test]
+    fn should_use_default_path_ext() {
+        assert_eq!(
+            get_path_ext_file_names("deno", None),
+            get_path_ext_file_names("deno", Some(""))
+        );
+        assert_eq!(
+            get_path_ext_file_names("deno", None)[..3],
+            ["deno.com", "deno.exe", "deno.bat"]
+        );
+    }
+
+    #[test]
+    fn should_try_provided_extension_first() {
+        assert_eq!(
+            get_path_ext_file_names("deno.exe", Some(".COM;.EXE")),
+            vec!["deno.exe", "deno.exe.com", "deno.exe.exe"]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use dprint_cli_core::types::ErrBox;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::environment::Environment;
+
+/// Number of seconds after which a lock that wasn't refreshed is assumed to be left behind by a process that exited.
+pub const STALE_LOCK_SECS: u64 = 2 * 60;
+/// Number of seconds between updating the time in the lock file while it's held.
+const REFRESH_INTERVAL_SECS: u64 = 30;
+
+/// A file containing the id of the process holding a lock and the time the lock was last refreshed,
+/// which is refreshed while held and removed once dropped.
+pub struct LockFile<TEnvironment: Environment> {
+    environment: TEnvironment,
+    file_path: PathBuf,
+    lock_text: Arc<Mutex<String>>,
+    refresh_sender: Option<Sender<()>>,
+    refresh_thread: Option<JoinHandle<()>>,
+}
+
+impl<TEnvironment: Environment> LockFile<TEnvironment> {
+    /// Creates the lock file, returning `None` when another process holds the lock. A stale lock is
+    /// taken over. Whether the process holding the lock exited is only checked when `is_local` is true
+    /// since the ids of processes on other machines (ex. Windows from WSL) can't be checked.
+    pub fn try_create(environment: &TEnvironment, file_path: &Path, is_local: bool) -> Result<Option<Self>, ErrBox> {
+        loop {
+            let lock_text = get_lock_text(environment);
+            if environment.create_new_file_text(file_path, &lock_text)? {
+                return Ok(Some(LockFile::new(environment, file_path, lock_text)));
+            }
+            match get_stale_lock_text(environment, file_path, is_local) {
+                Some(stale_lock_text) => {
+                    log_verbose!(environment, "Removing stale lock at {}.", file_path.display());
+                    remove_stale_lock(environment, file_path, &stale_lock_text);
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn new(environment: &TEnvironment, file_path: &Path, lock_text: String) -> Self {
+        // keep the lock from looking stale while it's held for a long time (ex. a slow download)
+        let lock_text = Arc::new(Mutex::new(lock_text));
+        let (refresh_sender, receiver) = channel::<()>();
+        let refresh_thread = std::thread::spawn({
+            let environment = environment.clone();
+            let file_path = file_path.to_path_buf();
+            let lock_text = lock_text.clone();
+            move || {
+                while let Err(RecvTimeoutError::Timeout) =
+                    receiver.recv_timeout(Duration::from_secs(REFRESH_INTERVAL_SECS))
+                {
+                    let mut lock_text = lock_text.lock().unwrap();
+                    if environment.read_file_text(&file_path).ok().as_ref() != Some(&*lock_text) {
+                        // another process took over the lock
+                        break;
+                    }
+                    let new_lock_text = get_lock_text(&environment);
+                    if environment.write_file_text(&file_path, &new_lock_text).is_ok() {
+                        *lock_text = new_lock_text;
+                    }
+                }
+            }
+        });
+
+        LockFile {
+            environment: environment.clone(),
+            file_path: file_path.to_path_buf(),
+            lock_text,
+            refresh_sender: Some(refresh_sender),
+            refresh_thread: Some(refresh_thread),
+        }
+    }
+}
+
+impl<TEnvironment: Environment> Drop for LockFile<TEnvironment> {
+    fn drop(&mut self) {
+        // stop refreshing before removing the file so it isn't created again
+        self.refresh_sender.take();
+        if let Some(refresh_thread) = self.refresh_thread.take() {
+            let _ignore = refresh_thread.join();
+        }
+        // don't remove the lock of another process that took it over
+        let lock_text = self.lock_text.lock().unwrap();
+        if self.environment.read_file_text(&self.file_path).ok().as_ref() == Some(&*lock_text) {
+            let _ignore = self.environment.remove_file(&self.file_path);
+        }
+    }
+}
+
+fn get_lock_text(environment: &impl Environment) -> String {
+    format!("{} {}", std::process::id(), environment.get_time_secs())
+}
+
+/// Gets the text of the lock when its process exited or its time wasn't refreshed for too long.
+fn get_stale_lock_text(environment: &impl Environment, file_path: &Path, is_local: bool) -> Option<String> {
+    let text = environment.read_file_text(file_path).ok()?;
+    let mut parts = text.split(' ');
+    let process_id = parts.next().and_then(|id| id.trim().parse::<u32>().ok());
+    let lock_time = parts.next().and_then(|time| time.trim().parse::<u64>().ok());
+    let is_process_exited = match process_id {
+        Some(process_id) => is_local && process_id != std::process::id() && !environment.is_process_running(process_id),
+        None => false,
+    };
+    let is_expired = match lock_time {
+        Some(lock_time) => environment.get_time_secs() >= lock_time.saturating_add(STALE_LOCK_SECS),
+        None => false,
+    };
+    if is_process_exited || is_expired {
+        Some(text)
+    } else {
+        None
+    }
+}
+
+/// Removes the stale lock by moving it aside first so that a lock another process created after
+/// it was found to be stale is never removed. Another process may have already removed it.
+pub fn remove_stale_lock(environment: &impl Environment, file_path: &Path, stale_lock_text: &str) {
+    let moved_file_path = file_path.with_extension(format!("lock.{}.stale", std::process::id()));
+    if environment.rename(file_path, &moved_file_path).is_err() {
+        return;
+    }
+    if environment.read_file_text(&moved_file_path).ok().as_deref() != Some(stale_lock_text) {
+        // another process acquired the lock in the meantime, so give it back
+        let _ignore = environment.rename(&moved_file_path, file_path);
+    } else {
+        let _ignore = environment.remove_file(&moved_file_path);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::environment::TestEnvironment;
+
+    #[test]
+    fn should_take_over_lock_of_exited_process() {
+        let environment = TestEnvironment::new();
+        let file_path = PathBuf::from("/test.lock");
+        environment.set_time_secs(1000);
+        environment.write_file_text(&file_path, "12345 1000").unwrap();
+        assert_eq!(
+            LockFile::try_create(&environment, &file_path, true).unwrap().is_none(),
+            true
+        );
+
+        environment.set_process_exited(12345);
+        // the process may be on another machine
+        assert_eq!(
+            LockFile::try_create(&environment, &file_path, false).unwrap().is_none(),
+            true
+        );
+        let lock_file = LockFile::try_create(&environment, &file_path, true).unwrap();
+        assert_eq!(lock_file.is_some(), true);
+        assert_eq!(
+            environment.read_file_text(&file_path).unwrap(),
+            format!("{} 1000", std::process::id())
+        );
+        drop(lock_file);
+        assert_eq!(environment.path_exists(&file_path), false);
+    }
+
+    #[test]
+    fn should_not_remove_lock_taken_over_by_another_process() {
+        let environment = TestEnvironment::new();
+        let file_path = PathBuf::from("/test.lock");
+        let lock_file = LockFile::try_create(&environment, &file_path, true).unwrap().unwrap();
+        environment.write_file_text(&file_path, "12345 0").unwrap();
+        drop(lock_file);
+        assert_eq!(environment.read_file_text(&file_path).unwrap(), "12345 0");
+    }
+}
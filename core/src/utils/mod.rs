@@ -0,0 +1,33 @@
+mod checksum_quarantine;
+mod colors;
+mod create_tar;
+mod download_lock;
+mod extract_tar;
+mod extract_zip;
+mod file_write_pool;
+mod get_path_executable_path;
+mod get_shim_dir;
+mod gz_decompress;
+mod lock_file;
+mod normalize_path;
+mod schema_version;
+mod string_utils;
+mod url_cache;
+mod wsl;
+
+pub use checksum_quarantine::*;
+pub use colors::*;
+pub use create_tar::*;
+pub use download_lock::*;
+pub use extract_tar::*;
+pub use extract_zip::*;
+pub use file_write_pool::*;
+pub use get_path_executable_path::*;
+pub use get_shim_dir::*;
+pub use gz_decompress::*;
+pub use lock_file::*;
+pub use normalize_path::*;
+pub use schema_version::*;
+pub use string_utils::*;
+pub use url_cache::*;
+pub use wsl::*;
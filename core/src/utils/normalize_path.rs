@@ -0,0 +1,16 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Lexically resolves any `.` and `..` components of the path.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            _ => result.push(component),
+        }
+    }
+    result
+}
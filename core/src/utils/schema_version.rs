@@ -0,0 +1,127 @@
+use dprint_cli_core::types::ErrBox;
+use serde_json::{Map, Value};
+
+use crate::types::Version;
+
+/// Version of bvm, which is released together with this crate.
+pub const BVM_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Gets the schema version of a file after checking that this version of bvm can read it.
+///
+/// A file may provide the minimum version of bvm that reads it in a `minBvmVersion` property, which is
+/// used to say what to upgrade to when the file is too new. Files without a `schemaVersion` were created
+/// before it was introduced and are read as schema version 1.
+pub fn get_supported_schema_version(
+    file: &Map<String, Value>,
+    file_kind: &str,
+    latest_schema_version: u32,
+) -> Result<u32, ErrBox> {
+    let current_version = Version::parse(BVM_VERSION)?;
+    let min_bvm_version = match file.get("minBvmVersion") {
+        Some(Value::String(text)) => match Version::parse(text) {
+            Ok(version) => Some(version),
+            Err(_) => {
+                return err!(
+                    "Expected the minBvmVersion of the {} to be a version, but found '{}'.",
+                    file_kind,
+                    text
+                )
+            }
+        },
+        Some(_) => return err!("Expected the minBvmVersion of the {} to be a string.", file_kind),
+        None => None,
+    };
+    if let Some(min_bvm_version) = &min_bvm_version {
+        verify_min_bvm_version(min_bvm_version, file_kind)?;
+    }
+
+    let schema_version = match file.get("schemaVersion") {
+        Some(value) => match value.as_u64() {
+            Some(schema_version) if schema_version >= 1 => schema_version,
+            _ => {
+                return err!(
+                    "Expected the schemaVersion of the {} to be a positive number.",
+                    file_kind
+                )
+            }
+        },
+        None => 1,
+    };
+    if schema_version > latest_schema_version as u64 {
+        return err!(
+            "This {} uses schema version {}, but bvm {} only supports up to schema version {}. Run `bvm self-update` to upgrade.",
+            file_kind,
+            schema_version,
+            current_version,
+            latest_schema_version
+        );
+    }
+    Ok(schema_version as u32)
+}
+
+/// Checks that this version of bvm is at least the minimum version that a file requires.
+pub fn verify_min_bvm_version(min_bvm_version: &Version, file_kind: &str) -> Result<(), ErrBox> {
+    let current_version = Version::parse(BVM_VERSION)?;
+    if min_bvm_version.gt(&current_version) {
+        return err!(
+            "This {} requires bvm >= {}, you have {}. Run `bvm self-update` to upgrade.",
+            file_kind,
+            min_bvm_version,
+            current_version
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_file(text: &str) -> Map<String, Value> {
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn should_get_schema_version() {
+        assert_eq!(
+            get_supported_schema_version(&get_file(r#"{ "schemaVersion": 2 }"#), "file", 2).unwrap(),
+            2
+        );
+        assert_eq!(get_supported_schema_version(&get_file("{}"), "file", 2).unwrap(), 1);
+        assert_eq!(
+            get_supported_schema_version(&get_file(r#"{ "schemaVersion": "1" }"#), "file", 2)
+                .err()
+                .unwrap()
+                .to_string(),
+            "Expected the schemaVersion of the file to be a positive number."
+        );
+    }
+
+    #[test]
+    fn should_error_for_newer_schema_version() {
+        assert_eq!(
+            get_supported_schema_version(&get_file(r#"{ "schemaVersion": 3 }"#), "file", 2)
+                .err()
+                .unwrap()
+                .to_string(),
+            format!(
+                "This file uses schema version 3, but bvm {} only supports up to schema version 2. Run `bvm self-update` to upgrade.",
+                BVM_VERSION
+            )
+        );
+        assert_eq!(
+            get_supported_schema_version(
+                &get_file(r#"{ "schemaVersion": 3, "minBvmVersion": "999.0.0" }"#),
+                "file",
+                2
+            )
+            .err()
+            .unwrap()
+            .to_string(),
+            format!(
+                "This file requires bvm >= 999.0.0, you have {}. Run `bvm self-update` to upgrade.",
+                BVM_VERSION
+            )
+        );
+    }
+}
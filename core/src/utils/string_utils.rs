@@ -0,0 +1,198 @@
+use dprint_cli_core::types::ErrBox;
+
+pub fn format_bytes(bytes: u64) -> String {
+    const KB: u64 = 1024;
+    const MB: u64 = KB * 1024;
+    const GB: u64 = MB * 1024;
+    if bytes >= GB {
+        format!("{:.1} GB", bytes as f64 / GB as f64)
+    } else if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else if bytes >= KB {
+        format!("{:.1} KB", bytes as f64 / KB as f64)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+/// Parses a number of bytes with an optional `K`, `M`, or `G` unit (ex. `500K` or `2MB`), which are
+/// multiples of 1024 like the units of `format_bytes`.
+pub fn parse_byte_size(text: &str) -> Result<u64, ErrBox> {
+    let text = text.trim();
+    let (amount, unit) = text.split_at(
+        text.find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(text.len()),
+    );
+    let unit_bytes: u64 = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        _ => {
+            return err!(
+                "Invalid size '{}'. Expected a number optionally followed by K, M, or G (ex. 500K).",
+                text
+            )
+        }
+    };
+    match amount.parse::<f64>() {
+        Ok(amount) if amount.is_finite() => Ok((amount * unit_bytes as f64) as u64),
+        _ => err!(
+            "Invalid size '{}'. Expected a number optionally followed by K, M, or G (ex. 500K).",
+            text
+        ),
+    }
+}
+
+/// Formats a time in seconds since epoch as an ISO 8601 UTC date and time (ex. `2021-03-04T05:06:07Z`).
+pub fn format_utc_time(time_secs: u64) -> String {
+    let days = time_secs / 86_400;
+    let day_secs = time_secs % 86_400;
+    // converts the days since epoch to a civil date (http://howardhinnant.github.io/date_algorithms.html#civil_from_days)
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        day_secs / 3600,
+        day_secs % 3600 / 60,
+        day_secs % 60
+    )
+}
+
+pub fn sentence_join(items: &Vec<String>) -> String {
+    match items.len() {
+        0 => String::new(),
+        1 => items[0].clone(),
+        2 => format!("{} and {}", items[0], items[1]),
+        _ => {
+            let mut text = String::new();
+
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    text.push_str(", ");
+                }
+                if i == items.len() - 1 {
+                    text.push_str("and ");
+                }
+
+                text.push_str(&item);
+            }
+
+            text
+        }
+    }
+}
+
+/// Gets if the text matches the pattern, where `*` matches any number of characters and `?` matches one character.
+pub fn is_glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut pattern_index, mut text_index) = (0, 0);
+    // the position of the last `*` and the text index it's matched up to
+    let mut backtrack = None;
+
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, text_index));
+                pattern_index += 1;
+            }
+            Some(c) if *c == '?' || *c == text[text_index] => {
+                pattern_index += 1;
+                text_index += 1;
+            }
+            _ => match backtrack {
+                // have the last `*` match one more character
+                Some((star_index, star_text_index)) => {
+                    backtrack = Some((star_index, star_text_index + 1));
+                    pattern_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|c| *c == '*')
+}
+
+/// Gets the number of single character insertions, deletions, or substitutions to change one text into the other.
+pub fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous_row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = if a_char == *b_char { 0 } else { 1 };
+            row.push(
+                (previous_row[j] + substitution_cost)
+                    .min(previous_row[j + 1] + 1)
+                    .min(row[j] + 1),
+            );
+        }
+        previous_row = row;
+    }
+    previous_row[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_parse_byte_sizes() {
+        assert_eq!(parse_byte_size("100").unwrap(), 100);
+        assert_eq!(parse_byte_size("500K").unwrap(), 500 * 1024);
+        assert_eq!(parse_byte_size("2mb").unwrap(), 2 * 1024 * 1024);
+        assert_eq!(parse_byte_size("1.5M").unwrap(), 1536 * 1024);
+        assert_eq!(parse_byte_size("1 GiB").unwrap(), 1024 * 1024 * 1024);
+        assert_eq!(
+            parse_byte_size("fast").err().unwrap().to_string(),
+            "Invalid size 'fast'. Expected a number optionally followed by K, M, or G (ex. 500K)."
+        );
+        assert!(parse_byte_size("5T").is_err());
+    }
+
+    #[test]
+    fn should_format_utc_times() {
+        assert_eq!(format_utc_time(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc_time(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc_time(1_614_834_367), "2021-03-04T05:06:07Z");
+        assert_eq!(format_utc_time(1_735_689_599), "2024-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn should_match_globs() {
+        assert_eq!(is_glob_match("dprint*", "dprint"), true);
+        assert_eq!(is_glob_match("dprint*", "dprint-plugin-json"), true);
+        assert_eq!(is_glob_match("*-plugin-*", "dprint-plugin-json"), true);
+        assert_eq!(is_glob_match("d?no", "deno"), true);
+        assert_eq!(is_glob_match("*", ""), true);
+        assert_eq!(is_glob_match("dprint*", "deno"), false);
+        assert_eq!(is_glob_match("d?no", "dno"), false);
+        assert_eq!(is_glob_match("*json", "json-plugin"), false);
+    }
+
+    #[test]
+    fn should_get_edit_distance() {
+        assert_eq!(get_edit_distance("deno", "deno"), 0);
+        assert_eq!(get_edit_distance("denp", "deno"), 1);
+        assert_eq!(get_edit_distance("dno", "deno"), 1);
+        assert_eq!(get_edit_distance("kitten", "sitting"), 3);
+        assert_eq!(get_edit_distance("", "abc"), 3);
+    }
+}
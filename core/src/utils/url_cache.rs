@@ -0,0 +1,221 @@
+use dprint_cli_core::checksums::get_sha256_checksum;
+use dprint_cli_core::types::ErrBox;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::get_download_cache_dir;
+use crate::environment::{CacheValidators, Environment};
+
+/// Information about a cached copy of a file downloaded from a url, which is stored in a
+/// `.meta.json` file next to the copy.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlCacheEntryMetadata {
+    pub url: String,
+    pub checksum: String,
+    pub size: u64,
+    /// Time in seconds the copy was downloaded.
+    pub downloaded_time: u64,
+    /// Time in seconds the copy was last downloaded or revalidated.
+    pub validated_time: u64,
+    #[serde(flatten)]
+    pub validators: CacheValidators,
+}
+
+pub struct UrlCacheEntry {
+    pub bytes: Vec<u8>,
+    pub metadata: UrlCacheEntryMetadata,
+}
+
+/// Cache of the files downloaded from urls, such as registry files and binary manifest files.
+///
+/// The files are written atomically and a copy is only used when it matches the checksum and size in
+/// its metadata, so a copy that's being written by another process or that came from a cache shared
+/// between machines (ex. a CI cache) is downloaded again instead of being used when it's incomplete.
+pub struct UrlCache {
+    dir_path: PathBuf,
+}
+
+impl UrlCache {
+    /// Gets the cache stored in the provided directory of the download cache directory.
+    pub fn new(environment: &impl Environment, dir_name: &str) -> UrlCache {
+        UrlCache {
+            dir_path: get_download_cache_dir(environment).join(dir_name),
+        }
+    }
+
+    pub fn dir_path(&self) -> &Path {
+        &self.dir_path
+    }
+
+    /// Gets the cached copy of the url when it's intact.
+    pub fn get(&self, environment: &impl Environment, url: &str) -> Option<UrlCacheEntry> {
+        let file_path = self.get_file_path(url);
+        let metadata = read_metadata(environment, &get_metadata_file_path(&file_path))?;
+        let bytes = environment.read_file(&file_path).ok()?;
+        if metadata.url != url
+            || metadata.size != bytes.len() as u64
+            || metadata.checksum != get_sha256_checksum(&bytes)
+        {
+            log_verbose!(
+                environment,
+                "Ignoring the cached copy of {} that didn't match its metadata.",
+                url
+            );
+            return None;
+        }
+        Some(UrlCacheEntry { bytes, metadata })
+    }
+
+    /// Stores a copy of the file downloaded from the url.
+    pub fn set(
+        &self,
+        environment: &impl Environment,
+        url: &str,
+        bytes: &[u8],
+        validators: CacheValidators,
+    ) -> Result<UrlCacheEntryMetadata, ErrBox> {
+        let current_time = environment.get_time_secs();
+        let metadata = UrlCacheEntryMetadata {
+            url: url.to_string(),
+            checksum: get_sha256_checksum(bytes),
+            size: bytes.len() as u64,
+            downloaded_time: current_time,
+            validated_time: current_time,
+            validators,
+        };
+        let file_path = self.get_file_path(url);
+        environment.create_dir_all(&self.dir_path)?;
+        write_file_atomic(environment, &file_path, bytes)?;
+        write_file_atomic(
+            environment,
+            &get_metadata_file_path(&file_path),
+            &serde_json::to_vec(&metadata)?,
+        )?;
+        Ok(metadata)
+    }
+
+    /// Updates the time the cached copy was last revalidated with the server.
+    pub fn set_validated(
+        &self,
+        environment: &impl Environment,
+        metadata: &UrlCacheEntryMetadata,
+        validators: CacheValidators,
+    ) -> Result<(), ErrBox> {
+        let metadata = UrlCacheEntryMetadata {
+            validated_time: environment.get_time_secs(),
+            validators,
+            ..metadata.clone()
+        };
+        write_file_atomic(
+            environment,
+            &get_metadata_file_path(&self.get_file_path(&metadata.url)),
+            &serde_json::to_vec(&metadata)?,
+        )
+    }
+
+    /// Removes the cached copy of the url, returning if there was one.
+    pub fn remove(&self, environment: &impl Environment, url: &str) -> Result<bool, ErrBox> {
+        let file_path = self.get_file_path(url);
+        if !environment.path_exists(&file_path) {
+            return Ok(false);
+        }
+        environment.remove_file(&file_path)?;
+        let _ignore = environment.remove_file(&get_metadata_file_path(&file_path));
+        Ok(true)
+    }
+
+    /// Removes the copies that are incomplete or don't match their metadata, returning the number removed.
+    pub fn remove_invalid_entries(&self, environment: &impl Environment) -> Result<usize, ErrBox> {
+        if !environment.is_dir(&self.dir_path) {
+            return Ok(0);
+        }
+        let mut removed_count = 0;
+        for file_path in environment.get_file_paths(&self.dir_path)? {
+            let file_name = file_path.file_name().map(|name| name.to_string_lossy().to_string());
+            let file_name = match file_name {
+                Some(file_name) if !file_name.ends_with(".meta.json") => file_name,
+                _ => continue,
+            };
+            let is_valid = !file_name.ends_with(".tmp")
+                && read_metadata(environment, &get_metadata_file_path(&file_path))
+                    .filter(|metadata| self.get_file_path(&metadata.url) == file_path)
+                    .map(|metadata| self.get(environment, &metadata.url).is_some())
+                    .unwrap_or(false);
+            if !is_valid {
+                environment.remove_file(&file_path)?;
+                let _ignore = environment.remove_file(&get_metadata_file_path(&file_path));
+                removed_count += 1;
+            }
+        }
+        Ok(removed_count)
+    }
+
+    fn get_file_path(&self, url: &str) -> PathBuf {
+        self.dir_path
+            .join(format!("{}.json", get_sha256_checksum(url.as_bytes())))
+    }
+}
+
+fn get_metadata_file_path(file_path: &Path) -> PathBuf {
+    file_path.with_extension("meta.json")
+}
+
+fn read_metadata(environment: &impl Environment, metadata_file_path: &Path) -> Option<UrlCacheEntryMetadata> {
+    environment
+        .read_file(metadata_file_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<UrlCacheEntryMetadata>(&bytes).ok())
+}
+
+/// Writes the file to a temporary file then moves it into place so that other processes never
+/// read a partially written file.
+fn write_file_atomic(environment: &impl Environment, file_path: &Path, bytes: &[u8]) -> Result<(), ErrBox> {
+    static TEMP_FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+    let temp_file_path = file_path.with_file_name(format!(
+        "{}.{}-{}.tmp",
+        file_path.file_name().unwrap().to_string_lossy(),
+        std::process::id(),
+        TEMP_FILE_COUNT.fetch_add(1, Ordering::SeqCst),
+    ));
+    environment.write_file(&temp_file_path, bytes)?;
+    if let Err(err) = environment.rename(&temp_file_path, file_path) {
+        let _ignore = environment.remove_file(&temp_file_path);
+        return Err(err);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::environment::TestEnvironment;
+
+    #[test]
+    fn should_only_use_intact_entries() {
+        let environment = TestEnvironment::new();
+        let cache = UrlCache::new(&environment, "test-cache");
+        let url = "https://localhost/file.json";
+        assert_eq!(cache.get(&environment, url).is_none(), true);
+
+        let metadata = cache
+            .set(&environment, url, b"text", CacheValidators::default())
+            .unwrap();
+        let entry = cache.get(&environment, url).unwrap();
+        assert_eq!(entry.bytes, b"text");
+        assert_eq!(entry.metadata, metadata);
+        assert_eq!(metadata.size, 4);
+        assert_eq!(metadata.checksum, get_sha256_checksum(b"text"));
+        assert_eq!(cache.remove_invalid_entries(&environment).unwrap(), 0);
+        assert_eq!(environment.get_file_paths(cache.dir_path()).unwrap().len(), 2);
+
+        // ex. a copy that was truncated
+        let file_path = cache.get_file_path(url);
+        environment.write_file(&file_path, b"te").unwrap();
+        assert_eq!(cache.get(&environment, url).is_none(), true);
+        assert_eq!(cache.remove_invalid_entries(&environment).unwrap(), 1);
+        assert_eq!(environment.path_exists(&file_path), false);
+        assert_eq!(environment.path_exists(&get_metadata_file_path(&file_path)), false);
+    }
+}
@@ -0,0 +1,51 @@
+use dprint_cli_core::types::ErrBox;
+use std::path::PathBuf;
+
+use crate::environment::{read_user_config, Environment};
+
+/// Gets the Windows-side bvm data directory to share downloads with when running inside WSL with
+/// the `wslWindowsDataDir` user config property set.
+pub fn get_wsl_windows_data_dir(environment: &impl Environment) -> Result<Option<PathBuf>, ErrBox> {
+    if !cfg!(target_os = "linux")
+        || environment
+            .get_env_var("WSL_DISTRO_NAME")
+            .unwrap_or_default()
+            .is_empty()
+    {
+        return Ok(None);
+    }
+    Ok(read_user_config(environment)?
+        .wsl_windows_data_dir
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from))
+}
+
+pub fn is_wsl_interop_enabled(environment: &impl Environment) -> Result<bool, ErrBox> {
+    Ok(get_wsl_windows_data_dir(environment)?.is_some())
+}
+
+/// Gets the directory downloaded registry files and their locks are stored in, which is shared
+/// with the Windows-side bvm in WSL interop mode.
+pub fn get_download_cache_dir(environment: &impl Environment) -> PathBuf {
+    match get_wsl_windows_data_dir(environment) {
+        Ok(Some(dir)) => dir,
+        _ => environment.get_user_data_dir(),
+    }
+}
+
+/// Gets if the bytes are the start of an ELF executable, which is the format of Linux binaries.
+pub fn is_elf_executable(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"\x7fELF")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_detect_elf_executables() {
+        assert_eq!(is_elf_executable(b"\x7fELF\x02\x01\x01"), true);
+        assert_eq!(is_elf_executable(b"MZ\x90\x00"), false);
+        assert_eq!(is_elf_executable(b""), false);
+    }
+}
@@ -12,8 +12,19 @@ if ($PSVersionTable.PSEdition -ne 'Core') {
   $IsMacOS = $false
 }
 
+$IsSystem = $env:BVM_SYSTEM -eq '1' -or $env:BVM_SYSTEM -eq 'true'
+if ($IsSystem) {
+  # install for all users of the machine
+  $Principal = [Security.Principal.WindowsPrincipal][Security.Principal.WindowsIdentity]::GetCurrent()
+  if (!$Principal.IsInRole([Security.Principal.WindowsBuiltInRole]::Administrator)) {
+    Write-Error "Installing bvm system-wide requires administrator privileges. Run the install script from an elevated terminal."
+  }
+}
+
 $BinDir = if ($env:BVM_INSTALL_DIR) {
   "$env:BVM_INSTALL_DIR\bin"
+} elseif ($IsSystem) {
+  "$env:ProgramFiles\bvm\bin"
 } elseif ($IsWindows) {
   "$Home\.bvm\bin"
 }
@@ -61,7 +72,8 @@ Remove-Item $BvmZip
 
 Start-Process -FilePath "$BinDir\bvm-bin" -ArgumentList "hidden","windows-install"
 
-$Env:Path = "$env:APPDATA\bvm\shims;" + $Env:Path
+$ShimsDir = if ($IsSystem) { "$env:ProgramFiles\bvm\shims" } else { "$env:APPDATA\bvm\shims" }
+$Env:Path = "$ShimsDir;" + $Env:Path
 $Env:Path = "$BinDir;" + $Env:Path
 
 Write-Output "bvm was installed successfully to $BinDir"
@@ -27,8 +27,23 @@ else
   bvm_uri="https://github.com/dsherret/bvm/releases/download/${1}/bvm-${target}.zip"
 fi
 
-bvm_install="${BVM_INSTALL_DIR:-$HOME/.bvm}"
+case "$BVM_SYSTEM" in
+1|true)
+  # install for all users of the machine
+  if [ "$(id -u)" != "0" ]; then
+    echo "Error: Installing bvm system-wide requires root privileges. Run the install script with sudo." 1>&2
+    exit 1
+  fi
+  export BVM_SYSTEM
+  is_system=1
+  bvm_default_install="/usr/local/bvm"
+  ;;
+*) bvm_default_install="$HOME/.bvm" ;;
+esac
+
+bvm_install="${BVM_INSTALL_DIR:-$bvm_default_install}"
 BVM_INSTALL_DIR="$bvm_install"
+export BVM_INSTALL_DIR
 bin_dir="$bvm_install/bin"
 exe="$bin_dir/bvm"
 
@@ -65,16 +80,11 @@ else
   echo "bvm was installed successfully to $exe"
   if command -v bvm >/dev/null; then
     echo "Run 'bvm --help' to get started"
+  elif [ -n "$is_system" ]; then
+    echo "Log in again to load the bvm setup added to /etc/profile.d/bvm.sh"
+    echo "Run '$exe --help' to get started"
   else
-    case $SHELL in
-    /bin/zsh) shell_profile=".zshrc" ;;
-    *) shell_profile=".bash_profile" ;;
-    esac
-    echo "Manually add the following to your \$HOME/$shell_profile (or similar)"
-    echo ""
-    echo "export BVM_INSTALL_DIR=\"$bvm_install\""
-    echo ". \"\$BVM_INSTALL_DIR/bin/bvm-init\""
-    echo ""
+    echo "Restart your shell to load the bvm setup added to your shell profile"
     echo "Run '$exe --help' to get started"
   fi
 fi
@@ -1,44 +1,219 @@
+use std::path::Path;
 use url::Url;
 
 use dprint_cli_core::checksums::{parse_checksum_path_or_url, ChecksumPathOrUrl};
 use dprint_cli_core::types::ErrBox;
 
-use super::types::{CommandName, NameSelector, PathOrVersionSelector, Version, VersionSelector};
+use super::environment::{ColorChoice, LogFormat, PromptMode};
+use super::plugins::{is_cargo_crate_url, is_github_release_url, is_npm_package_url, PLATFORM_KEYS};
+use super::types::{
+    is_valid_version_alias, BinaryName, CommandName, NameSelector, PathOrVersionSelector, Version, VersionSelector,
+};
+use super::utils::parse_byte_size;
 
 pub struct CliArgs {
     pub sub_command: SubCommand,
+    pub offline: bool,
+    pub refresh: bool,
+    pub verbose: bool,
+    pub quiet: bool,
+    pub log_format: Option<LogFormat>,
+    pub color: Option<ColorChoice>,
+    pub trust: bool,
+    pub max_download_speed: Option<u64>,
+    pub prompt_mode: Option<PromptMode>,
+    pub system: bool,
 }
 
 pub enum SubCommand {
     Use,
     UseBinary(UseBinaryCommand),
-    List,
+    List(ListCommand),
+    ListRemote(ListRemoteCommand),
+    Changelog(ChangelogCommand),
+    Info(InfoCommand),
+    Resolve(ResolveCommand),
+    Which(WhichCommand),
+    Why(WhyCommand),
+    Env(EnvCommand),
+    Export(ExportSubCommand),
+    Snapshot(SnapshotSubCommand),
+    CacheKey,
     Install(InstallCommand),
     InstallUrl(InstallUrlCommand),
+    InstallArchive(InstallArchiveCommand),
     Uninstall(UninstallCommand),
+    Prune(PruneCommand),
+    Verify(VerifyCommand),
+    Repair,
+    Stats(StatsCommand),
+    Compact(CompactCommand),
+    Link(LinkCommand),
+    Unlink(UnlinkCommand),
+    Disable(DisableCommand),
+    Enable(EnableCommand),
     Registry(RegistrySubCommand),
+    Config(ConfigSubCommand),
+    Alias(AliasSubCommand),
+    Util(UtilSubCommand),
     Add(AddCommand),
+    Remove(RemoveCommand),
+    Pin(PinCommand),
+    Run(RunCommand),
+    RunWith(RunWithCommand),
+    Shell(ShellCommand),
     Version,
     Init,
     ClearUrlCache,
+    SelfUpdate,
+    UninstallSelf(UninstallSelfCommand),
     Hidden(HiddenSubCommand),
     Help(String),
 }
 
 pub struct UseBinaryCommand {
     pub name_selector: NameSelector,
-    pub version_selector: PathOrVersionSelector,
+    pub version: UseVersion,
+    /// Only use the version for this command of the binary.
+    pub command_name: Option<CommandName>,
+    /// Expose the commands with a version suffix (ex. `deno1.39`) instead of changing the global version.
+    pub suffix: bool,
+    /// Change the globally used version even when in a project.
+    pub global: bool,
+}
+
+pub enum UseVersion {
+    Selector(PathOrVersionSelector),
+    Alias(String),
+    /// No version was provided, so the version is picked interactively or the latest matching the selector is used.
+    Unspecified(PathOrVersionSelector),
+}
+
+pub struct ListCommand {
+    pub name_selector: Option<NameSelector>,
+    pub owner: Option<String>,
+    /// Only list the versions that are older than the latest version in the registries.
+    pub outdated: bool,
+    pub sort: ListSort,
+    /// Output the directory of each version on its own line without any decoration.
+    pub paths: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ListSort {
+    Name,
+    /// Newest versions first.
+    Version,
+    /// Largest binaries first.
+    Size,
+    /// Most recently used binaries first.
+    LastUsed,
+}
+
+pub struct ListRemoteCommand {
+    pub name_selector: NameSelector,
+    pub version_selector: Option<VersionSelector>,
+}
+
+pub struct ChangelogCommand {
+    pub name_selector: NameSelector,
+    /// Version to output the release notes of instead of the versions newer than the installed version.
+    pub version_selector: Option<VersionSelector>,
+    /// Open the release notes in the browser instead of outputting their urls.
+    pub open: bool,
+}
+
+pub struct EnvCommand {
+    pub shell: EnvShell,
+}
+
+/// Shell syntax to output environment changes in.
+#[derive(Debug, PartialEq)]
+pub enum EnvShell {
+    Bash,
+    Fish,
+    Pwsh,
+    Cmd,
+    /// Nushell, which is output as a JSON record to load with `from json | load-env`.
+    Nu,
+}
+
+/// Formats to export the binaries of the current configuration file to for reproducing them elsewhere.
+pub enum ExportSubCommand {
+    Dockerfile,
+    ShellScript,
+}
+
+pub enum SnapshotSubCommand {
+    Export,
+    Import(SnapshotImportCommand),
+}
+
+pub struct SnapshotImportCommand {
+    pub file_path: String,
+}
+
+pub struct InfoCommand {
+    pub name_selector: NameSelector,
+    /// Whether to output the information as JSON.
+    pub json: bool,
+}
+
+pub struct ResolveCommand {
+    pub name_selector: NameSelector,
+    /// Version to resolve instead of the one used by the current directory.
+    pub version_selector: Option<VersionSelector>,
+    pub command_name: Option<CommandName>,
+}
+
+pub struct WhichCommand {
+    pub command_name: CommandName,
+    /// Only output the path of the executable.
+    pub quiet: bool,
+}
+
+pub struct WhyCommand {
+    pub name_selector: NameSelector,
+    /// Version to explain instead of the one used by the current directory.
+    pub version_selector: Option<VersionSelector>,
 }
 
 pub struct InstallCommand {
     pub use_command: bool,
     pub force: bool,
+    pub lazy: bool,
+    pub target: Option<String>,
+    pub dry_run: Option<DryRunFormat>,
+    pub accept_new_checksum: bool,
 }
 
 pub struct InstallUrlCommand {
-    pub url_or_name: UrlOrName,
+    pub url_or_names: Vec<UrlOrName>,
+    /// Tag of the first name when the two names may instead be the `<name> <tag>` form (ex. `bvm install node lts`).
+    pub legacy_tag: Option<String>,
+    pub use_command: bool,
+    pub force: bool,
+    pub lazy: bool,
+    pub target: Option<String>,
+    pub dry_run: Option<DryRunFormat>,
+}
+
+/// Format of what would be done when providing `--dry-run`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DryRunFormat {
+    Text,
+    Json,
+}
+
+/// Installs an archive or binary that has no plugin file.
+pub struct InstallArchiveCommand {
+    pub binary_name: BinaryName,
+    pub version: Version,
+    pub url: String,
     pub use_command: bool,
     pub force: bool,
+    pub lazy: bool,
+    pub target: Option<String>,
 }
 
 pub enum UrlOrName {
@@ -49,40 +224,195 @@ pub enum UrlOrName {
 pub struct InstallName {
     pub name_selector: NameSelector,
     pub version_selector: Option<VersionSelector>,
+    /// Registry tag to resolve the version from (ex. "latest").
+    pub tag: Option<String>,
 }
 
 pub struct UninstallCommand {
     pub name_selector: NameSelector,
+    /// The version to uninstall, which is `None` when uninstalling all matching versions.
+    pub version: Option<Version>,
+    pub all: bool,
+    /// Pick the versions to uninstall from a list of the installed versions matching the name.
+    pub interactive: bool,
+}
+
+pub struct PruneCommand {
+    /// Number of seconds a version must have gone without being run to be removed.
+    pub unused_for_secs: u64,
+}
+
+pub struct VerifyCommand {
+    /// Only verify the binaries matching the name selector.
+    pub name_selector: Option<NameSelector>,
+}
+
+pub struct StatsCommand {
+    /// Only report the binaries matching the name selector.
+    pub name_selector: Option<NameSelector>,
+}
+
+pub struct CompactCommand {
+    /// Only compact the binaries matching the name selector.
+    pub name_selector: Option<NameSelector>,
+}
+
+pub struct UninstallSelfCommand {
+    /// Also remove the data and cache directories along with all the installed binaries.
+    pub purge: bool,
+}
+
+/// Registers a locally built executable or directory of executables as a binary.
+pub struct LinkCommand {
+    pub binary_name: BinaryName,
     pub version: Version,
+    pub path: String,
+}
+
+pub struct UnlinkCommand {
+    pub name_selector: NameSelector,
+    pub version: Option<Version>,
+}
+
+pub struct DisableCommand {
+    pub name_selector: NameSelector,
+}
+
+pub struct EnableCommand {
+    pub name_selector: NameSelector,
 }
 
 pub enum RegistrySubCommand {
     Add(RegistryAddCommand),
     Remove(RegistryRemoveCommand),
+    Rename(RegistryRenameCommand),
     List,
+    Validate(RegistryValidateCommand),
+    Pack(RegistryPackCommand),
 }
 
 pub struct RegistryAddCommand {
     pub url: String,
+    pub name: Option<String>,
 }
 
 pub struct RegistryRemoveCommand {
-    pub url: String,
+    pub url_or_name: String,
+}
+
+pub struct RegistryRenameCommand {
+    pub url_or_name: String,
+    pub new_name: String,
+}
+
+pub struct RegistryValidateCommand {
+    pub path_or_url: String,
+    /// Downloads each platform's file to verify it's reachable and matches its checksum.
+    pub fetch: bool,
+}
+
+pub struct RegistryPackCommand {
+    pub spec_path: String,
+    /// Path of the registry file to add the version to. Defaults to `registry.json` next to the spec file.
+    pub registry_path: Option<String>,
+}
+
+pub enum ConfigSubCommand {
+    Set(ConfigSetCommand),
+    Unset(ConfigUnsetCommand),
+}
+
+pub struct ConfigSetCommand {
+    pub key: String,
+    pub value: String,
+}
+
+pub struct ConfigUnsetCommand {
+    pub key: String,
+}
+
+pub enum UtilSubCommand {
+    Hash(UtilHashCommand),
+}
+
+pub struct UtilHashCommand {
+    pub path_or_url: String,
+    pub algorithm: HashAlgorithm,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+pub enum AliasSubCommand {
+    Set(AliasSetCommand),
+    Remove(AliasRemoveCommand),
+    List,
+}
+
+pub struct AliasSetCommand {
+    pub name_selector: NameSelector,
+    pub alias: String,
+    pub version: Version,
+}
+
+pub struct AliasRemoveCommand {
+    pub name_selector: NameSelector,
+    pub alias: String,
 }
 
 pub struct AddCommand {
     pub url_or_name: UrlOrName,
 }
 
+pub struct RemoveCommand {
+    pub name_selector: NameSelector,
+    /// Also uninstall the removed binary's version when nothing else uses it.
+    pub uninstall: bool,
+}
+
+pub struct PinCommand {
+    pub name_selector: NameSelector,
+    pub version: Option<Version>,
+}
+
+pub struct RunCommand {
+    pub script_name: String,
+    pub args: Vec<String>,
+}
+
+/// Runs a command with the provided binaries on the path, installing them when they aren't installed.
+pub struct RunWithCommand {
+    pub binaries: Vec<UrlOrName>,
+    /// Command and its arguments.
+    pub command: Vec<String>,
+}
+
+pub struct ShellCommand {
+    pub binaries: Vec<ShellBinary>,
+    /// Command and its arguments to run instead of a subshell.
+    pub command: Vec<String>,
+}
+
+pub struct ShellBinary {
+    pub name_selector: NameSelector,
+    pub version_selector: VersionSelector,
+}
+
 pub enum HiddenSubCommand {
     ResolveCommand(HiddenResolveCommand),
+    ExecCommand(HiddenExecCommand),
     GetExecEnvChanges(HiddenExecEnvChangesCommand),
     GetExecCommandPath(HiddenGetExecCommandPathCommand),
     HasCommand(HiddenHasCommandCommand),
-    GetPendingEnvChanges,
+    GetPendingEnvChanges(HiddenEnvOutputCommand),
     ClearPendingEnvChanges,
     GetPaths,
-    GetEnvVars,
+    GetEnvVars(HiddenEnvOutputCommand),
+    ShellInit(HiddenShellInitCommand),
+    CheckUpdate(HiddenCheckUpdateCommand),
     #[cfg(not(target_os = "windows"))]
     UnixInstall,
     #[cfg(target_os = "windows")]
@@ -91,10 +421,32 @@ pub enum HiddenSubCommand {
     WindowsUninstall,
 }
 
+pub struct HiddenEnvOutputCommand {
+    /// Shell syntax to output the environment changes in instead of the messages parsed by the shell scripts.
+    pub shell: Option<EnvShell>,
+}
+
+/// Outputs the script that sets up bvm in shells that can't source the POSIX shell scripts.
+pub struct HiddenShellInitCommand {
+    pub shell: EnvShell,
+}
+
 pub struct HiddenResolveCommand {
     pub command_name: CommandName,
 }
 
+pub struct HiddenExecCommand {
+    pub command_name: CommandName,
+    pub args: Vec<String>,
+}
+
+/// Checks the registry for a newer version of a binary in the background of running one of its commands.
+pub struct HiddenCheckUpdateCommand {
+    pub name_selector: NameSelector,
+    pub version: Version,
+    pub version_selector: Option<VersionSelector>,
+}
+
 pub struct HiddenExecEnvChangesCommand {
     pub name_selector: NameSelector,
     pub version_selector: PathOrVersionSelector,
@@ -133,6 +485,40 @@ pub fn parse_args(args: Vec<String>) -> Result<CliArgs, ErrBox> {
                     .map(String::from)
                     .map(|value| CommandName::from_string(value)),
             })),
+            offline: false,
+            refresh: false,
+            trust: false,
+            verbose: false,
+            quiet: false,
+            log_format: None,
+            color: None,
+            max_download_speed: None,
+            prompt_mode: None,
+            system: false,
+        });
+    }
+
+    // pass the remaining arguments through to the executable as-is
+    if args.get(1).map(|s| s.as_str()) == Some("hidden") && args.get(2).map(|s| s.as_str()) == Some("exec-command") {
+        return Ok(CliArgs {
+            sub_command: SubCommand::Hidden(HiddenSubCommand::ExecCommand(HiddenExecCommand {
+                command_name: CommandName::from_string(
+                    args.get(3)
+                        .map(String::from)
+                        .expect("Expected to have a command name argument."),
+                ),
+                args: args.into_iter().skip(4).collect(),
+            })),
+            offline: false,
+            refresh: false,
+            trust: false,
+            verbose: false,
+            quiet: false,
+            log_format: None,
+            color: None,
+            max_download_speed: None,
+            prompt_mode: None,
+            system: false,
         });
     }
 
@@ -151,13 +537,24 @@ pub fn parse_args(args: Vec<String>) -> Result<CliArgs, ErrBox> {
                 command_name: CommandName::from_string(matches.value_of("command_name").map(String::from).unwrap()),
             }))
         } else if matches.is_present("get-pending-env-changes") {
-            SubCommand::Hidden(HiddenSubCommand::GetPendingEnvChanges)
+            let matches = matches.subcommand_matches("get-pending-env-changes").unwrap();
+            SubCommand::Hidden(HiddenSubCommand::GetPendingEnvChanges(HiddenEnvOutputCommand {
+                shell: matches.value_of("shell").map(parse_env_shell).transpose()?,
+            }))
         } else if matches.is_present("clear-pending-env-changes") {
             SubCommand::Hidden(HiddenSubCommand::ClearPendingEnvChanges)
         } else if matches.is_present("get-paths") {
             SubCommand::Hidden(HiddenSubCommand::GetPaths)
         } else if matches.is_present("get-env-vars") {
-            SubCommand::Hidden(HiddenSubCommand::GetEnvVars)
+            let matches = matches.subcommand_matches("get-env-vars").unwrap();
+            SubCommand::Hidden(HiddenSubCommand::GetEnvVars(HiddenEnvOutputCommand {
+                shell: matches.value_of("shell").map(parse_env_shell).transpose()?,
+            }))
+        } else if matches.is_present("shell-init") {
+            let matches = matches.subcommand_matches("shell-init").unwrap();
+            SubCommand::Hidden(HiddenSubCommand::ShellInit(HiddenShellInitCommand {
+                shell: parse_env_shell(matches.value_of("shell").unwrap())?,
+            }))
         } else if matches.is_present("get-exec-env-changes") {
             let matches = matches.subcommand_matches("get-exec-env-changes").unwrap();
             SubCommand::Hidden(HiddenSubCommand::GetExecEnvChanges(HiddenExecEnvChangesCommand {
@@ -175,6 +572,16 @@ pub fn parse_args(args: Vec<String>) -> Result<CliArgs, ErrBox> {
                 )?,
                 command_name: CommandName::from_string(matches.value_of("command_name").map(String::from).unwrap()),
             }))
+        } else if matches.is_present("check-update") {
+            let matches = matches.subcommand_matches("check-update").unwrap();
+            SubCommand::Hidden(HiddenSubCommand::CheckUpdate(HiddenCheckUpdateCommand {
+                name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+                version: Version::parse(matches.value_of("version").unwrap())?,
+                version_selector: matches
+                    .value_of("version_selector")
+                    .map(VersionSelector::parse)
+                    .transpose()?,
+            }))
         } else {
             #[cfg(not(target_os = "windows"))]
             if matches.is_present("unix-install") {
@@ -197,94 +604,463 @@ pub fn parse_args(args: Vec<String>) -> Result<CliArgs, ErrBox> {
         let install_matches = matches.subcommand_matches("install").unwrap();
         let use_command = install_matches.is_present("use");
         let force = install_matches.is_present("force");
-        if let Some(url_or_name) = install_matches.value_of("url_or_name").map(String::from) {
-            let version = install_matches.value_of("version").map(String::from);
-            if version.is_some() || Url::parse(&url_or_name).is_err() {
-                let name_selector = parse_name_selector(url_or_name);
-                SubCommand::InstallUrl(InstallUrlCommand {
-                    url_or_name: UrlOrName::Name(InstallName {
-                        name_selector,
-                        version_selector: if let Some(v) = &version {
-                            Some(VersionSelector::parse(v)?)
-                        } else {
-                            None
-                        },
-                    }),
-                    use_command,
-                    force,
-                })
+        let lazy = install_matches.is_present("lazy");
+        let include_prerelease = install_matches.is_present("pre");
+        let target = install_matches.value_of("target").map(String::from);
+        let dry_run = if !install_matches.is_present("dry-run") {
+            None
+        } else if install_matches.is_present("json") {
+            Some(DryRunFormat::Json)
+        } else {
+            Some(DryRunFormat::Text)
+        };
+        if let Some(name) = install_matches.value_of("name") {
+            let urls = install_matches.values_of("url_or_names").map(|v| v.collect::<Vec<_>>());
+            let (url, version) = match (urls.as_deref(), install_matches.value_of("archive_version")) {
+                (Some([url]), Some(version)) => (url.to_string(), version),
+                _ => return err!("The `--name` flag must be provided with a `--version` and a single archive url."),
+            };
+            let binary_name = match parse_name_selector(name.to_string()) {
+                NameSelector {
+                    owner: Some(owner),
+                    name,
+                } if !owner.is_empty() && !name.is_empty() => BinaryName::new(owner, name),
+                _ => {
+                    return err!(
+                        "Expected the `--name` to include an owner (ex. `owner/tool`), but found '{}'.",
+                        name
+                    )
+                }
+            };
+            SubCommand::InstallArchive(InstallArchiveCommand {
+                binary_name,
+                version: Version::parse(version)?,
+                url,
+                use_command,
+                force,
+                lazy,
+                target,
+            })
+        } else if let Some(values) = install_matches.values_of("url_or_names") {
+            let values = values.map(String::from).collect::<Vec<_>>();
+            let url_or_names = if values.len() == 2 && VersionSelector::parse(&values[1]).is_ok() {
+                // support the `bvm install <name> <version>` form
+                vec![UrlOrName::Name(parse_install_name(
+                    values[0].clone(),
+                    Some(values[1].clone()),
+                    include_prerelease,
+                )?)]
             } else {
-                SubCommand::InstallUrl(InstallUrlCommand {
-                    url_or_name: UrlOrName::Url(parse_checksum_path_or_url(&url_or_name)),
-                    use_command,
-                    force,
-                })
-            }
+                let mut url_or_names = Vec::new();
+                for value in values.iter() {
+                    url_or_names.push(parse_url_or_name(value, include_prerelease)?);
+                }
+                url_or_names
+            };
+            // `bvm install <name> <tag>` can't be told apart from two names until the registries are read
+            let legacy_tag = match url_or_names.as_slice() {
+                [UrlOrName::Name(_), UrlOrName::Name(_)]
+                    if !values[0].contains('@') && is_valid_version_alias(&values[1]) =>
+                {
+                    Some(values[1].clone())
+                }
+                _ => None,
+            };
+            SubCommand::InstallUrl(InstallUrlCommand {
+                url_or_names,
+                legacy_tag,
+                use_command,
+                force,
+                lazy,
+                target,
+                dry_run,
+            })
         } else {
-            SubCommand::Install(InstallCommand { use_command, force })
+            SubCommand::Install(InstallCommand {
+                use_command,
+                force,
+                lazy,
+                target,
+                dry_run,
+                accept_new_checksum: install_matches.is_present("accept-new-checksum"),
+            })
         }
     } else if matches.is_present("use") {
         let use_matches = matches.subcommand_matches("use").unwrap();
         if let Some(binary_name) = use_matches.value_of("binary_name").map(String::from) {
-            let name_selector = parse_name_selector(binary_name);
+            let name_selector = with_owner_arg(parse_name_selector(binary_name), use_matches)?;
+            let version_arg = use_matches.value_of("version").map(String::from);
+            let is_unspecified = version_arg.is_none();
+            let version = version_arg.unwrap_or("*".to_string());
             SubCommand::UseBinary(UseBinaryCommand {
                 name_selector,
-                version_selector: PathOrVersionSelector::parse(
-                    &use_matches
-                        .value_of("version")
-                        .map(String::from)
-                        .unwrap_or("*".to_string()),
-                )?,
+                version: if is_valid_version_alias(&version) {
+                    UseVersion::Alias(version)
+                } else {
+                    let version_selector = match PathOrVersionSelector::parse(&version)? {
+                        PathOrVersionSelector::Version(version_selector) if use_matches.is_present("pre") => {
+                            PathOrVersionSelector::Version(version_selector.with_prerelease())
+                        }
+                        version_selector => version_selector,
+                    };
+                    if is_unspecified {
+                        UseVersion::Unspecified(version_selector)
+                    } else {
+                        UseVersion::Selector(version_selector)
+                    }
+                },
+                command_name: use_matches
+                    .value_of("command")
+                    .map(|name| CommandName::from_string(name.to_string())),
+                suffix: use_matches.is_present("suffix"),
+                global: use_matches.is_present("global"),
             })
         } else {
             SubCommand::Use
         }
     } else if matches.is_present("uninstall") {
         let uninstall_matches = matches.subcommand_matches("uninstall").unwrap();
-        let name_selector = parse_name_selector(uninstall_matches.value_of("binary_name").map(String::from).unwrap());
+        let name_selector = with_owner_arg(
+            // all the binaries are listed when picking interactively without a name
+            parse_name_selector(uninstall_matches.value_of("binary_name").unwrap_or("*").to_string()),
+            uninstall_matches,
+        )?;
         SubCommand::Uninstall(UninstallCommand {
             name_selector,
-            version: Version::parse(&uninstall_matches.value_of("version").map(String::from).unwrap())?,
+            version: match uninstall_matches.value_of("version") {
+                Some(version) => Some(Version::parse(version)?),
+                None => None,
+            },
+            all: uninstall_matches.is_present("all"),
+            interactive: uninstall_matches.is_present("interactive"),
+        })
+    } else if matches.is_present("link") {
+        let matches = matches.subcommand_matches("link").unwrap();
+        let name_selector = parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap());
+        SubCommand::Link(LinkCommand {
+            binary_name: BinaryName::new(
+                name_selector.owner.unwrap_or_else(|| "local".to_string()),
+                name_selector.name.as_str().to_string(),
+            ),
+            version: Version::parse(matches.value_of("version").unwrap_or("0.0.0-local"))?,
+            path: matches.value_of("path").map(String::from).unwrap(),
+        })
+    } else if matches.is_present("unlink") {
+        let matches = matches.subcommand_matches("unlink").unwrap();
+        SubCommand::Unlink(UnlinkCommand {
+            name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+            version: match matches.value_of("version") {
+                Some(version) => Some(Version::parse(version)?),
+                None => None,
+            },
+        })
+    } else if matches.is_present("disable") {
+        let matches = matches.subcommand_matches("disable").unwrap();
+        SubCommand::Disable(DisableCommand {
+            name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+        })
+    } else if matches.is_present("enable") {
+        let matches = matches.subcommand_matches("enable").unwrap();
+        SubCommand::Enable(EnableCommand {
+            name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
         })
     } else if matches.is_present("list") {
-        SubCommand::List
+        let matches = matches.subcommand_matches("list").unwrap();
+        SubCommand::List(ListCommand {
+            name_selector: matches
+                .value_of("binary_name")
+                .or_else(|| matches.value_of("name"))
+                .map(|name| parse_name_selector(name.to_string())),
+            owner: matches.value_of("owner").map(String::from),
+            outdated: matches.is_present("outdated"),
+            sort: match matches.value_of("sort") {
+                Some("version") => ListSort::Version,
+                Some("size") => ListSort::Size,
+                Some("last-used") => ListSort::LastUsed,
+                _ => ListSort::Name,
+            },
+            paths: matches.is_present("paths"),
+        })
+    } else if matches.is_present("ls-remote") {
+        let matches = matches.subcommand_matches("ls-remote").unwrap();
+        SubCommand::ListRemote(ListRemoteCommand {
+            name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+            version_selector: match matches.value_of("version") {
+                Some(version) => Some(parse_version_selector(version, matches.is_present("pre"))?),
+                None => None,
+            },
+        })
+    } else if matches.is_present("changelog") {
+        let matches = matches.subcommand_matches("changelog").unwrap();
+        SubCommand::Changelog(ChangelogCommand {
+            name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+            version_selector: match matches.value_of("version") {
+                Some(version) => Some(parse_version_selector(version, false)?),
+                None => None,
+            },
+            open: matches.is_present("open"),
+        })
+    } else if matches.is_present("info") {
+        let matches = matches.subcommand_matches("info").unwrap();
+        SubCommand::Info(InfoCommand {
+            name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+            json: matches.is_present("json"),
+        })
+    } else if matches.is_present("resolve") {
+        let matches = matches.subcommand_matches("resolve").unwrap();
+        let value = matches.value_of("binary").unwrap();
+        let (name, version_selector) = match value.rfind('@') {
+            Some(index) => (
+                &value[..index],
+                Some(parse_version_selector(&value[index + 1..], false)?),
+            ),
+            None => (value, None),
+        };
+        SubCommand::Resolve(ResolveCommand {
+            name_selector: parse_name_selector(name.to_string()),
+            version_selector,
+            command_name: matches
+                .value_of("command")
+                .map(|name| CommandName::from_string(name.to_string())),
+        })
+    } else if matches.is_present("which") {
+        let matches = matches.subcommand_matches("which").unwrap();
+        SubCommand::Which(WhichCommand {
+            command_name: CommandName::from_string(matches.value_of("command").unwrap().to_string()),
+            // the global `--quiet` flag also only outputs the path
+            quiet: matches.is_present("quiet"),
+        })
+    } else if matches.is_present("why") {
+        let matches = matches.subcommand_matches("why").unwrap();
+        let value = matches.value_of("binary").unwrap();
+        let (name, version_selector) = match value.rfind('@') {
+            Some(index) => (
+                &value[..index],
+                Some(parse_version_selector(&value[index + 1..], false)?),
+            ),
+            None => (value, None),
+        };
+        SubCommand::Why(WhyCommand {
+            name_selector: parse_name_selector(name.to_string()),
+            version_selector,
+        })
+    } else if matches.is_present("env") {
+        let matches = matches.subcommand_matches("env").unwrap();
+        SubCommand::Env(EnvCommand {
+            shell: match matches.value_of("shell") {
+                Some(shell) => parse_env_shell(shell)?,
+                None if cfg!(target_os = "windows") => EnvShell::Cmd,
+                None => EnvShell::Bash,
+            },
+        })
+    } else if matches.is_present("export") {
+        match matches.subcommand_matches("export").unwrap().subcommand() {
+            ("dockerfile", _) => SubCommand::Export(ExportSubCommand::Dockerfile),
+            ("shell-script", _) => SubCommand::Export(ExportSubCommand::ShellScript),
+            _ => unreachable!(),
+        }
+    } else if matches.is_present("snapshot") {
+        match matches.subcommand_matches("snapshot").unwrap().subcommand() {
+            ("export", _) => SubCommand::Snapshot(SnapshotSubCommand::Export),
+            ("import", Some(matches)) => SubCommand::Snapshot(SnapshotSubCommand::Import(SnapshotImportCommand {
+                file_path: matches.value_of("file").map(String::from).unwrap(),
+            })),
+            _ => unreachable!(),
+        }
+    } else if matches.is_present("cache-key") {
+        SubCommand::CacheKey
     } else if matches.is_present("init") {
         SubCommand::Init
+    } else if matches.is_present("prune") {
+        let matches = matches.subcommand_matches("prune").unwrap();
+        SubCommand::Prune(PruneCommand {
+            unused_for_secs: parse_duration_secs(matches.value_of("unused_for").unwrap())?,
+        })
+    } else if matches.is_present("verify") {
+        let matches = matches.subcommand_matches("verify").unwrap();
+        SubCommand::Verify(VerifyCommand {
+            name_selector: matches
+                .value_of("binary_name")
+                .map(|name| parse_name_selector(name.to_string())),
+        })
+    } else if matches.is_present("repair") {
+        SubCommand::Repair
+    } else if matches.is_present("stats") {
+        let matches = matches.subcommand_matches("stats").unwrap();
+        SubCommand::Stats(StatsCommand {
+            name_selector: matches
+                .value_of("binary_name")
+                .map(|name| parse_name_selector(name.to_string())),
+        })
+    } else if matches.is_present("compact") {
+        let matches = matches.subcommand_matches("compact").unwrap();
+        SubCommand::Compact(CompactCommand {
+            name_selector: matches
+                .value_of("binary_name")
+                .map(|name| parse_name_selector(name.to_string())),
+        })
     } else if matches.is_present("clear-url-cache") {
         SubCommand::ClearUrlCache
+    } else if matches.is_present("self-update") {
+        SubCommand::SelfUpdate
+    } else if matches.is_present("uninstall-self") {
+        let matches = matches.subcommand_matches("uninstall-self").unwrap();
+        SubCommand::UninstallSelf(UninstallSelfCommand {
+            purge: matches.is_present("purge"),
+        })
     } else if matches.is_present("registry") {
         let registry_sub_command = matches.subcommand_matches("registry").unwrap();
         match registry_sub_command.subcommand() {
             ("add", Some(matches)) => SubCommand::Registry(RegistrySubCommand::Add(RegistryAddCommand {
                 url: matches.value_of("url").map(String::from).unwrap(),
+                name: matches.value_of("name").map(String::from),
             })),
             ("remove", Some(matches)) => SubCommand::Registry(RegistrySubCommand::Remove(RegistryRemoveCommand {
-                url: matches.value_of("url").map(String::from).unwrap(),
+                url_or_name: matches.value_of("url_or_name").map(String::from).unwrap(),
+            })),
+            ("rename", Some(matches)) => SubCommand::Registry(RegistrySubCommand::Rename(RegistryRenameCommand {
+                url_or_name: matches.value_of("url_or_name").map(String::from).unwrap(),
+                new_name: matches.value_of("new_name").map(String::from).unwrap(),
             })),
             ("list", _) => SubCommand::Registry(RegistrySubCommand::List),
+            ("validate", Some(matches)) => {
+                SubCommand::Registry(RegistrySubCommand::Validate(RegistryValidateCommand {
+                    path_or_url: matches.value_of("path_or_url").map(String::from).unwrap(),
+                    fetch: matches.is_present("fetch"),
+                }))
+            }
+            ("pack", Some(matches)) => SubCommand::Registry(RegistrySubCommand::Pack(RegistryPackCommand {
+                spec_path: matches.value_of("spec_path").map(String::from).unwrap(),
+                registry_path: matches.value_of("registry").map(String::from),
+            })),
+            _ => unreachable!(),
+        }
+    } else if matches.is_present("config") {
+        let config_sub_command = matches.subcommand_matches("config").unwrap();
+        match config_sub_command.subcommand() {
+            ("set", Some(matches)) => SubCommand::Config(ConfigSubCommand::Set(ConfigSetCommand {
+                key: matches.value_of("key").map(String::from).unwrap(),
+                value: matches.value_of("value").map(String::from).unwrap(),
+            })),
+            ("unset", Some(matches)) => SubCommand::Config(ConfigSubCommand::Unset(ConfigUnsetCommand {
+                key: matches.value_of("key").map(String::from).unwrap(),
+            })),
+            _ => unreachable!(),
+        }
+    } else if matches.is_present("util") {
+        let util_sub_command = matches.subcommand_matches("util").unwrap();
+        match util_sub_command.subcommand() {
+            ("hash", Some(matches)) => SubCommand::Util(UtilSubCommand::Hash(UtilHashCommand {
+                path_or_url: matches.value_of("path_or_url").map(String::from).unwrap(),
+                algorithm: match matches.value_of("algo") {
+                    Some("sha512") => HashAlgorithm::Sha512,
+                    _ => HashAlgorithm::Sha256,
+                },
+            })),
+            _ => unreachable!(),
+        }
+    } else if matches.is_present("alias") {
+        let alias_matches = matches.subcommand_matches("alias").unwrap();
+        match alias_matches.subcommand() {
+            ("remove", Some(matches)) => SubCommand::Alias(AliasSubCommand::Remove(AliasRemoveCommand {
+                name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+                alias: matches.value_of("alias").map(String::from).unwrap(),
+            })),
+            ("list", _) => SubCommand::Alias(AliasSubCommand::List),
+            ("set", Some(matches)) => {
+                let alias = matches.value_of("alias").map(String::from).unwrap();
+                if !is_valid_version_alias(&alias) {
+                    return err!(
+                        "Invalid alias '{}'. An alias must start with a letter, only contain letters, numbers, dashes, or underscores, and not be a version selector.",
+                        alias
+                    );
+                }
+                SubCommand::Alias(AliasSubCommand::Set(AliasSetCommand {
+                    name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+                    alias,
+                    version: Version::parse(matches.value_of("version").unwrap())?,
+                }))
+            }
             _ => unreachable!(),
         }
     } else if matches.is_present("add") {
         let matches = matches.subcommand_matches("add").unwrap();
         let url_or_name = matches.value_of("url_or_name").map(String::from).unwrap();
         let version = matches.value_of("version").map(String::from);
-        if version.is_some() || Url::parse(&url_or_name).is_err() {
-            let name_selector = parse_name_selector(url_or_name);
+        if version.is_some() || (Url::parse(&url_or_name).is_err() && !is_local_path(&url_or_name)) {
             SubCommand::Add(AddCommand {
-                url_or_name: UrlOrName::Name(InstallName {
-                    name_selector,
-                    version_selector: if let Some(v) = &version {
-                        Some(VersionSelector::parse(v)?)
-                    } else {
-                        None
-                    },
-                }),
+                url_or_name: UrlOrName::Name(parse_install_name(url_or_name, version, false)?),
             })
         } else {
             SubCommand::Add(AddCommand {
-                url_or_name: UrlOrName::Url(parse_checksum_path_or_url(&url_or_name)),
+                url_or_name: UrlOrName::Url(parse_url(&url_or_name)),
+            })
+        }
+    } else if matches.is_present("remove") {
+        let matches = matches.subcommand_matches("remove").unwrap();
+        SubCommand::Remove(RemoveCommand {
+            name_selector: parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+            uninstall: matches.is_present("uninstall"),
+        })
+    } else if matches.is_present("pin") {
+        let matches = matches.subcommand_matches("pin").unwrap();
+        SubCommand::Pin(PinCommand {
+            name_selector: with_owner_arg(
+                parse_name_selector(matches.value_of("binary_name").map(String::from).unwrap()),
+                matches,
+            )?,
+            version: match matches.value_of("version") {
+                Some(version) => Some(Version::parse(version)?),
+                None => None,
+            },
+        })
+    } else if matches.is_present("run") {
+        let matches = matches.subcommand_matches("run").unwrap();
+        if let Some(values) = matches.values_of("with") {
+            let mut binaries = Vec::new();
+            for value in values {
+                binaries.push(parse_url_or_name(value, false)?);
+            }
+            // the script name is the command when running with binaries
+            SubCommand::RunWith(RunWithCommand {
+                binaries,
+                command: matches
+                    .values_of("script_name")
+                    .into_iter()
+                    .flatten()
+                    .chain(matches.values_of("args").into_iter().flatten())
+                    .map(String::from)
+                    .collect(),
+            })
+        } else {
+            SubCommand::Run(RunCommand {
+                script_name: matches.value_of("script_name").map(String::from).unwrap(),
+                args: matches
+                    .values_of("args")
+                    .map(|values| values.map(String::from).collect())
+                    .unwrap_or_default(),
             })
         }
+    } else if matches.is_present("shell") {
+        let matches = matches.subcommand_matches("shell").unwrap();
+        let mut binaries = Vec::new();
+        for value in matches.values_of("binaries").unwrap() {
+            let (name, version) = match value.rfind('@') {
+                Some(index) => (&value[..index], &value[index + 1..]),
+                None => return err!("Expected a version for '{}' (ex. `{}@1.2.3`).", value, value),
+            };
+            binaries.push(ShellBinary {
+                name_selector: parse_name_selector(name.to_string()),
+                version_selector: parse_version_selector(version, false)?,
+            });
+        }
+        SubCommand::Shell(ShellCommand {
+            binaries,
+            command: matches
+                .values_of("command")
+                .map(|values| values.map(String::from).collect())
+                .unwrap_or_default(),
+        })
     } else {
         SubCommand::Help({
             let mut text = Vec::new();
@@ -293,7 +1069,167 @@ pub fn parse_args(args: Vec<String>) -> Result<CliArgs, ErrBox> {
         })
     };
 
-    Ok(CliArgs { sub_command })
+    Ok(CliArgs {
+        sub_command,
+        offline: is_present_in_any_subcommand(&matches, "offline"),
+        refresh: is_present_in_any_subcommand(&matches, "refresh"),
+        verbose: is_present_in_any_subcommand(&matches, "verbose"),
+        quiet: is_present_in_any_subcommand(&matches, "quiet"),
+        log_format: match value_of_in_any_subcommand(&matches, "log-format") {
+            Some("json") => Some(LogFormat::Json),
+            Some(_) => Some(LogFormat::Text),
+            None if is_present_in_any_subcommand(&matches, "github-actions") => Some(LogFormat::GitHubActions),
+            None => None,
+        },
+        color: match value_of_in_any_subcommand(&matches, "color") {
+            Some("always") => Some(ColorChoice::Always),
+            Some("never") => Some(ColorChoice::Never),
+            Some(_) => Some(ColorChoice::Auto),
+            None => None,
+        },
+        trust: is_present_in_any_subcommand(&matches, "trust"),
+        max_download_speed: match value_of_in_any_subcommand(&matches, "max-download-speed") {
+            Some(value) => Some(parse_byte_size(value)?),
+            None => None,
+        },
+        system: is_present_in_any_subcommand(&matches, "system"),
+        prompt_mode: if is_present_in_any_subcommand(&matches, "yes") {
+            Some(PromptMode::AssumeYes)
+        } else if is_present_in_any_subcommand(&matches, "no-prompt") {
+            Some(PromptMode::Never)
+        } else {
+            None
+        },
+    })
+}
+
+/// Gets the value of a global option provided at the deepest level of the subcommands.
+fn value_of_in_any_subcommand<'a>(matches: &'a clap::ArgMatches, name: &str) -> Option<&'a str> {
+    let subcommand_value = matches
+        .subcommand()
+        .1
+        .and_then(|matches| value_of_in_any_subcommand(matches, name));
+    subcommand_value.or_else(|| matches.value_of(name))
+}
+
+/// Gets if a global flag was provided at any level of the subcommands.
+fn is_present_in_any_subcommand(matches: &clap::ArgMatches, name: &str) -> bool {
+    if matches.is_present(name) {
+        true
+    } else if let Some(matches) = matches.subcommand().1 {
+        is_present_in_any_subcommand(matches, name)
+    } else {
+        false
+    }
+}
+
+/// Parses a name and version where the version may also be provided in the name (ex. `deno@1.40` or `deno@latest`).
+/// Gets if the text is a path to a local plugin file (ex. `./my-tool.json`) instead of a binary name.
+fn is_local_path(text: &str) -> bool {
+    text.starts_with("./")
+        || text.starts_with("../")
+        || text.starts_with(".\\")
+        || text.starts_with("..\\")
+        || Path::new(text).is_absolute()
+        || text.to_lowercase().ends_with(".json")
+}
+
+fn parse_env_shell(text: &str) -> Result<EnvShell, ErrBox> {
+    match text {
+        "bash" => Ok(EnvShell::Bash),
+        "fish" => Ok(EnvShell::Fish),
+        "pwsh" => Ok(EnvShell::Pwsh),
+        "cmd" => Ok(EnvShell::Cmd),
+        "nu" => Ok(EnvShell::Nu),
+        _ => err!("Unknown shell: {}", text),
+    }
+}
+
+/// Parses a duration with a unit (ex. `90d`, `12h`, or `2w`) to seconds.
+fn parse_duration_secs(text: &str) -> Result<u64, ErrBox> {
+    let (amount, unit) = text.split_at(text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len()));
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return err!(
+                "Invalid duration '{}'. Expected a number followed by s, m, h, d, or w (ex. 90d).",
+                text
+            )
+        }
+    };
+    match amount.parse::<u64>() {
+        Ok(amount) => Ok(amount.saturating_mul(unit_secs)),
+        Err(_) => err!(
+            "Invalid duration '{}'. Expected a number followed by s, m, h, d, or w (ex. 90d).",
+            text
+        ),
+    }
+}
+
+/// Parses a url, local path, or a binary name with an optional `@<version>` (ex. `owner/name@1.2.3`).
+pub fn parse_url_or_name(value: &str, include_prerelease: bool) -> Result<UrlOrName, ErrBox> {
+    Ok(if Url::parse(value).is_err() && !is_local_path(value) {
+        UrlOrName::Name(parse_install_name(value.to_string(), None, include_prerelease)?)
+    } else {
+        UrlOrName::Url(parse_url(value))
+    })
+}
+
+fn parse_url(text: &str) -> ChecksumPathOrUrl {
+    if is_github_release_url(text) || is_npm_package_url(text) || is_cargo_crate_url(text) {
+        // the `@` specifies the tag or version and not the checksum
+        ChecksumPathOrUrl {
+            path_or_url: text.to_string(),
+            checksum: None,
+        }
+    } else {
+        parse_checksum_path_or_url(text)
+    }
+}
+
+fn parse_install_name(name: String, version: Option<String>, include_prerelease: bool) -> Result<InstallName, ErrBox> {
+    let (name, version) = match version {
+        Some(version) => (name, Some(version)),
+        None => match name.rfind('@') {
+            Some(index) => (name[..index].to_string(), Some(name[index + 1..].to_string())),
+            None => (name, None),
+        },
+    };
+    let name_selector = parse_name_selector(name);
+    Ok(match version {
+        Some(version) if is_valid_version_alias(&version) => InstallName {
+            name_selector,
+            version_selector: None,
+            tag: Some(version),
+        },
+        Some(version) => InstallName {
+            name_selector,
+            version_selector: Some(parse_version_selector(&version, include_prerelease)?),
+            tag: None,
+        },
+        None => InstallName {
+            name_selector,
+            version_selector: if include_prerelease {
+                Some(parse_version_selector("*", include_prerelease)?)
+            } else {
+                None
+            },
+            tag: None,
+        },
+    })
+}
+
+fn parse_version_selector(text: &str, include_prerelease: bool) -> Result<VersionSelector, ErrBox> {
+    let version_selector = VersionSelector::parse(text)?;
+    Ok(if include_prerelease {
+        version_selector.with_prerelease()
+    } else {
+        version_selector
+    })
 }
 
 fn parse_name_selector(text: String) -> NameSelector {
@@ -313,6 +1249,20 @@ fn parse_name_selector(text: String) -> NameSelector {
     }
 }
 
+/// Sets the owner of the name selector from the `--owner` flag, if provided.
+fn with_owner_arg(name_selector: NameSelector, matches: &clap::ArgMatches) -> Result<NameSelector, ErrBox> {
+    match matches.value_of("owner") {
+        Some(_) if name_selector.owner.is_some() => {
+            err!("The `--owner` flag cannot be provided with a binary name that includes the owner.")
+        }
+        Some(owner) => Ok(NameSelector {
+            owner: Some(owner.to_string()),
+            name: name_selector.name,
+        }),
+        None => Ok(name_selector),
+    }
+}
+
 fn create_cli_parser<'a, 'b>() -> clap::App<'a, 'b> {
     use clap::{App, AppSettings, Arg, SubCommand};
     App::new("bvm")
@@ -344,36 +1294,204 @@ ARGS:
 {after-help}"#,
         )
         .after_help(r#"TODO: Will fill in this info later..."#)
-        .subcommand(
-            SubCommand::with_name("install")
-                .about("Installs the binaries for the current configuration file when no arguments or installs based on the provided arguments.")
-                .arg(
-                    Arg::with_name("url_or_name")
-                        .help("The url of the binary manifest to install or the name if also providing a version.")
-                        .takes_value(true)
-                        .conflicts_with("name"),
-                )
-                .arg(
-                    Arg::with_name("version")
-                        .help("The version of the binary to install.")
-                        .takes_value(true)
-                )
-                .arg(
-                    Arg::with_name("use")
-                        .help("Use the installed binary/binaries on the path.")
-                        .long("use")
-                        .takes_value(false),
+        .arg(
+            Arg::with_name("offline")
+                .help("Never access the network. Only use binaries that are installed and registries that are cached.")
+                .long("offline")
+                .global(true)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("refresh")
+                .help("Revalidate the cached registry files instead of using them until they expire.")
+                .long("refresh")
+                .global(true)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("trust")
+                .help("Trust and save the hosts that are downloaded from when trusted hosts are configured.")
+                .long("trust")
+                .global(true)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("yes")
+                .help("Answer yes to confirmations, such as trusting a host, without asking.")
+                .short("y")
+                .long("yes")
+                .global(true)
+                .conflicts_with("no-prompt")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("no-prompt")
+                .help("Never ask for input and error instead. This is the default when the `CI` environment variable is set.")
+                .long("no-prompt")
+                .global(true)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("system")
+                .help("Use the installation shared by all users of the machine. This is the default when the `BVM_SYSTEM` environment variable is set.")
+                .long("system")
+                .global(true)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("max-download-speed")
+                .help("Limit the combined speed of downloads (ex. 500K or 2M).")
+                .long("max-download-speed")
+                .global(true)
+                .value_name("SPEED")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Outputs information about what's being done, such as the files read and urls downloaded.")
+                .short("v")
+                .long("verbose")
+                .global(true)
+                .conflicts_with("quiet")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .help("Only outputs errors and the output of commands.")
+                .short("q")
+                .long("quiet")
+                .global(true)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("log-format")
+                .help("Format of the messages logged to stderr.")
+                .long("log-format")
+                .global(true)
+                .takes_value(true)
+                .possible_values(&["text", "json"]),
+        )
+        .arg(
+            Arg::with_name("github-actions")
+                .help("Outputs GitHub Actions workflow commands and adds the installed binaries to $GITHUB_PATH and their versions to $GITHUB_OUTPUT. Defaults to on when the GITHUB_ACTIONS environment variable is set.")
+                .long("github-actions")
+                .global(true)
+                .conflicts_with("log-format")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("color")
+                .help("When to color the output. Colors are disabled by default when the NO_COLOR environment variable is set.")
+                .long("color")
+                .global(true)
+                .takes_value(true)
+                .possible_values(&["auto", "always", "never"]),
+        )
+        .subcommand(
+            SubCommand::with_name("install")
+                .about("Installs the binaries for the current configuration file when no arguments or installs based on the provided arguments.")
+                .arg(
+                    Arg::with_name("url_or_names")
+                        .help("The urls of the binary manifests or the names (ex. `deno@1.40`) of the binaries to install. A single name may also be followed by a version or tag.")
+                        .takes_value(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name("use")
+                        .help("Use the installed binary/binaries on the path.")
+                        .long("use")
+                        .takes_value(false),
                 )
                 .arg(
                     Arg::with_name("force")
                         .help("Reinstall the binary/binaries if it is already installed.")
                         .long("force")
                         .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("lazy")
+                        .help("Only extract the archives of the binaries the first time one of their commands is run.")
+                        .long("lazy")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("pre")
+                        .help("Allow selecting pre-release versions when installing by name.")
+                        .long("pre")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("name")
+                        .help("Install the provided archive url as this binary name (ex. `owner/tool`) instead of using a plugin file.")
+                        .long("name")
+                        .value_name("owner/name")
+                        .takes_value(true)
+                        .requires("archive_version"),
+                )
+                .arg(
+                    Arg::with_name("archive_version")
+                        .help("The version of the binary when installing an archive url with `--name`.")
+                        .long("version")
+                        .value_name("version")
+                        .takes_value(true)
+                        .requires("name"),
+                )
+                .arg(
+                    Arg::with_name("target")
+                        .help("Install the binaries for this target instead of the one detected from the current system.")
+                        .long("target")
+                        .value_name("os-arch")
+                        .takes_value(true)
+                        .possible_values(PLATFORM_KEYS),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .help("Outputs the versions that would be installed, the urls that would be downloaded, and the versions that would be removed without installing.")
+                        .long("dry-run")
+                        .takes_value(false)
+                        .conflicts_with("name"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .help("Outputs what would be done by `--dry-run` as JSON.")
+                        .long("json")
+                        .takes_value(false)
+                        .requires("dry-run"),
+                )
+                .arg(
+                    Arg::with_name("accept-new-checksum")
+                        .help("Updates the checksums in the configuration file of the binary manifests that no longer match because they were republished.")
+                        .long("accept-new-checksum")
+                        .takes_value(false)
+                        .conflicts_with_all(&["url_or_names", "dry-run"]),
                 ),
         )
         .subcommand(
-            SubCommand::with_name("uninstall")
-                .about("Uninstalls the specified binary version.")
+            SubCommand::with_name("link")
+                .about("Registers a locally built executable or directory of executables as a binary that may be used like an installed one.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("The binary name with an optional owner (ex. `owner/name`). The owner defaults to `local`.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("path")
+                        .help("The path to the executable or the directory containing the executables.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .help("The version to link the binary as.")
+                        .long("version")
+                        .takes_value(true)
+                        .default_value("0.0.0-local"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("unlink")
+                .about("Removes a binary registered with `bvm link`.")
                 .arg(
                     Arg::with_name("binary_name")
                         .help("The binary name.")
@@ -382,43 +1500,452 @@ ARGS:
                 )
                 .arg(
                     Arg::with_name("version")
-                        .help("The version of the binary to uninstall.")
+                        .help("The version of the linked binary to remove. Removes all the linked versions when not provided.")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("disable")
+                .about("Hides the commands of a binary without uninstalling it so the executables on the path are run instead.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("The binary name. May contain `*` and `?` wildcards (ex. `myorg/*`).")
                         .takes_value(true)
                         .required(true),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("enable")
+                .about("Restores the commands of a binary hidden by `bvm disable`.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("The binary name. May contain `*` and `?` wildcards (ex. `myorg/*`).")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("uninstall")
+                .about("Uninstalls the specified binary version.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("The binary name. May contain `*` and `?` wildcards (ex. `myorg/*`) when providing `--all` or `--interactive`.")
+                        .takes_value(true)
+                        .required_unless("interactive"),
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .help("The version of the binary to uninstall.")
+                        .takes_value(true)
+                        .required_unless_one(&["all", "interactive"]),
+                )
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .help("Uninstall all installed versions of the binaries matching the name.")
+                        .takes_value(false)
+                        .conflicts_with("version"),
+                )
+                .arg(
+                    Arg::with_name("interactive")
+                        .long("interactive")
+                        .short("i")
+                        .help("Select the installed versions to uninstall from a list, which includes every binary when no name is provided.")
+                        .takes_value(false)
+                        .conflicts_with_all(&["version", "all"]),
+                )
+                .arg(
+                    Arg::with_name("owner")
+                        .help("The owner of the binary when multiple owners provide a binary with the name.")
+                        .long("owner")
+                        .takes_value(true),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("use")
-                .about("Select a different version to use globally of a binary. Specify no arguments to use the binaries in the current configuration file.")
+                .about("Select a different version of a binary to use in the current project or globally when not in a project. Specify no arguments to use the binaries in the current configuration file globally.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("The binary name.")
+                        .takes_value(true)
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .help("The version of the binary to use, an alias, or 'path' to use the binary on the path.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("pre")
+                        .help("Allow selecting pre-release versions.")
+                        .long("pre")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("command")
+                        .help("Only use the version for the specified command of the binary.")
+                        .long("command")
+                        .takes_value(true)
+                        .requires("binary_name"),
+                )
+                .arg(
+                    Arg::with_name("owner")
+                        .help("The owner of the binary when multiple owners provide a binary with the name.")
+                        .long("owner")
+                        .takes_value(true)
+                        .requires("binary_name"),
+                )
+                .arg(
+                    Arg::with_name("suffix")
+                        .help("Add commands suffixed with the major and minor version (ex. `deno1.39`) instead of changing the globally used version.")
+                        .long("suffix")
+                        .takes_value(false)
+                        .requires("binary_name"),
+                )
+                .arg(
+                    Arg::with_name("global")
+                        .help("Change the globally used version even when in a project.")
+                        .long("global")
+                        .short("g")
+                        .takes_value(false)
+                        .requires("binary_name")
+                        .conflicts_with("suffix"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("Output a list of installed binary versions.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("Only list the binaries matching the name selector, which may contain `*` and `?` wildcards (ex. `name`, `owner/name` or `dprint*`).")
+                        .takes_value(true)
+                        .conflicts_with("name"),
+                )
+                .arg(
+                    Arg::with_name("name")
+                        .help("Only list the binaries matching the name selector (ex. `name` or `owner/name`).")
+                        .long("name")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("owner")
+                        .help("Only list the binaries of the owner.")
+                        .long("owner")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("outdated")
+                        .help("Only list the versions that are older than the latest version in the registries.")
+                        .long("outdated")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::with_name("sort")
+                        .help("How to sort the binaries.")
+                        .long("sort")
+                        .takes_value(true)
+                        .possible_values(&["name", "version", "size", "last-used"])
+                        .default_value("name"),
+                )
+                .arg(
+                    Arg::with_name("paths")
+                        .help("Outputs the directory of each version on its own line for use in scripts.")
+                        .long("paths")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("ls-remote")
+                .about("Output a list of the versions of a binary available in the registries.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("The binary name.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .help("Optional version selector to filter the versions by.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("pre")
+                        .help("Include pre-release versions within the version selector's range.")
+                        .long("pre")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("changelog")
+                .about("Outputs the release notes urls of the versions newer than the installed version of a binary or of the provided version.")
                 .arg(
                     Arg::with_name("binary_name")
                         .help("The binary name.")
                         .takes_value(true)
+                        .required(true),
                 )
                 .arg(
                     Arg::with_name("version")
-                        .help("The version of the binary to use or 'path' to use the binary on the path.")
+                        .help("Optional version selector of the version to output the release notes of.")
                         .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("open")
+                        .help("Open the release notes of the newest version in the browser.")
+                        .long("open")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("info")
+                .about("Outputs information about a binary, including where each installed version came from.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("The binary name.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .help("Outputs the information as JSON.")
+                        .long("json")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("resolve")
+                .about("Outputs the path of a binary's executable, which is the version used by the current directory unless one is provided.")
+                .arg(
+                    Arg::with_name("binary")
+                        .help("The binary name with an optional version (ex. deno or deno@1.3).")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("command")
+                        .help("The command of the binary to output the executable path of when it has multiple commands.")
+                        .long("command")
+                        .value_name("command")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("which")
+                .about("Outputs the path of the executable that a command runs in the current directory.")
+                .arg(
+                    Arg::with_name("command")
+                        .help("The command name (ex. node).")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("why")
+                .about("Explains why a binary version is installed and which version the commands use in the current directory.")
+                .arg(
+                    Arg::with_name("binary")
+                        .help("The binary name with an optional version (ex. deno or deno@1.3).")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("env")
+                .about("Outputs the environment changes for using the binaries of the current configuration file (ex. `eval \"$(bvm env)\"`).")
+                .arg(
+                    Arg::with_name("shell")
+                        .help("The shell to output the environment changes for. Defaults to `cmd` on Windows and `bash` otherwise.")
+                        .long("shell")
+                        .takes_value(true)
+                        .possible_values(&["bash", "fish", "pwsh", "cmd", "nu"]),
                 ),
         )
-        .subcommand(SubCommand::with_name("list").about("Output a list of installed binary versions."))
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Outputs the commands to install the exact versions of the binaries of the current configuration file elsewhere.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("dockerfile")
+                        .about("Outputs Dockerfile instructions that install bvm and the binaries in a container."),
+                )
+                .subcommand(
+                    SubCommand::with_name("shell-script")
+                        .about("Outputs a shell script that installs bvm and the binaries on a fresh machine."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("snapshot")
+                .about("Captures the installed binaries and the versions used globally for reproducing them on another machine.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("export")
+                        .about("Outputs a snapshot of the installed binaries and the versions used globally."),
+                )
+                .subcommand(
+                    SubCommand::with_name("import")
+                        .about("Installs the binaries of a snapshot and uses the same versions globally.")
+                        .arg(
+                            Arg::with_name("file")
+                                .help("The path to the snapshot file output by `bvm snapshot export`.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                ),
+        )
+        .subcommand(SubCommand::with_name("cache-key").about("Outputs a hash of the binaries of the current configuration file and the platform for use as the CI cache key of the bvm data directory."))
         .subcommand(SubCommand::with_name("init").about("Creates an empty bvm.json file in the current directory."))
         .subcommand(
             SubCommand::with_name("add")
                 .about("Programmatically adds a binary to the bvm configuration file.")
                 .arg(
                     Arg::with_name("url_or_name")
-                        .help("The url or name of the binary.")
+                        .help("The url or name of the binary. A name may be followed by a version selector (ex. `deno@^1.3`).")
                         .takes_value(true)
                         .required(true),
                 )
                 .arg(
                     Arg::with_name("version")
-                        .help("The version to add if providing a name.")
+                        .help("The version selector to add if providing a name.")
                         .required(false),
                 )
         )
+        .subcommand(
+            SubCommand::with_name("remove")
+                .about("Programmatically removes a binary from the bvm configuration file.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("The binary name.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("uninstall")
+                        .help("Also uninstalls the binary's version when it's not used globally or by the rest of the configuration file.")
+                        .long("uninstall")
+                        .takes_value(false),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("pin")
+                .about("Adds the globally used version of a binary (or the provided version) to the bvm configuration file.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("The binary name.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .help("The version to pin instead of the globally used version.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("owner")
+                        .help("The owner of the binary when multiple owners provide a binary with the name.")
+                        .long("owner")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("shell")
+                .about("Launches a subshell or runs the command provided after `--` with the provided binary versions on the path without changing the global versions.")
+                .arg(
+                    Arg::with_name("binaries")
+                        .help("The binaries and versions to use in the subshell (ex. `deno@1.3.2 node@14`).")
+                        .value_name("name@version")
+                        .takes_value(true)
+                        .multiple(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("command")
+                        .help("The command and arguments to run instead of a subshell.")
+                        .takes_value(true)
+                        .multiple(true)
+                        .last(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about("Runs a script from the bvm configuration file with the configuration file's binaries on the path, or runs the command provided after `--` with the binaries of `--with` on the path.")
+                .setting(AppSettings::TrailingVarArg)
+                .setting(AppSettings::AllowLeadingHyphen)
+                .arg(
+                    Arg::with_name("with")
+                        .help("Binary name and version or binary manifest url to put on the path for only the command, which is installed when it's not (ex. `deno@1.3`). May be provided multiple times. The configuration file and global versions are not changed.")
+                        .long("with")
+                        .value_name("name@version")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1),
+                )
+                .arg(
+                    Arg::with_name("script_name")
+                        .help("The name of the script, or the command to run when `--with` is provided.")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("args")
+                        .help("Arguments to provide to the script.")
+                        .multiple(true)
+                        .allow_hyphen_values(true),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("prune")
+                .about("Removes the versions that haven't been run recently. Versions used globally or by the current directory's configuration file are kept.")
+                .arg(
+                    Arg::with_name("unused_for")
+                        .help("Removes the versions that haven't been run for this long (ex. 90d, 12h, or 2w).")
+                        .long("unused-for")
+                        .value_name("duration")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("Re-hashes the files of the installed binaries and reports the ones that were modified or removed since they were installed.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("Only verify the binaries matching the name selector, which may contain `*` and `?` wildcards (ex. `name` or `owner/name`).")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("repair")
+                .about("Reconciles the manifest with the installed binaries. Reinstalls or removes the binaries whose directories are missing, clears global versions of binaries that aren't installed, and asks to delete the directories that aren't in the manifest."),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("Outputs the disk usage of each installed version and the caches, highlighting the versions that can be pruned.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("Only report the binaries matching the name selector, which may contain `*` and `?` wildcards (ex. `name` or `owner/name`).")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("compact")
+                .about("Archives the files of the installed versions that aren't used globally or by the current configuration file. A compacted version is extracted again the next time it's run.")
+                .arg(
+                    Arg::with_name("binary_name")
+                        .help("Only compact the binaries matching the name selector, which may contain `*` and `?` wildcards (ex. `name` or `owner/name`).")
+                        .takes_value(true),
+                ),
+        )
         .subcommand(SubCommand::with_name("clear-url-cache").about("Clears the cache of downloaded urls. Does not remove any installed binaries."))
+        .subcommand(SubCommand::with_name("self-update").about("Updates bvm to the latest release."))
+        .subcommand(
+            SubCommand::with_name("uninstall-self")
+                .about("Reverses the environment setup done when installing bvm by removing its directories from the path and deleting the shims.")
+                .arg(
+                    Arg::with_name("purge")
+                        .help("Also removes the data and cache directories, which deletes all the installed binaries.")
+                        .long("purge")
+                        .takes_value(false),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("registry")
                 .about("Commands related to storing urls to binary version registries.")
@@ -432,20 +1959,175 @@ ARGS:
                                 .takes_value(true)
                                 .required(true)
                         )
+                        .arg(
+                            Arg::with_name("name")
+                                .help("Name to refer to the registry by in other registry commands.")
+                                .long("name")
+                                .takes_value(true)
+                        )
                 )
                 .subcommand(
                     SubCommand::with_name("remove")
-                        .about("Remove the url as a registry.")
+                        .about("Remove the url as a registry and clear its cached files.")
                         .arg(
-                            Arg::with_name("url")
-                                .help("The url of the binary registry.")
+                            Arg::with_name("url_or_name")
+                                .help("The url or name of the binary registry.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                )
+                .subcommand(
+                    SubCommand::with_name("rename")
+                        .about("Change the name of a registry.")
+                        .arg(
+                            Arg::with_name("url_or_name")
+                                .help("The url or current name of the binary registry.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("new_name")
+                                .help("The new name of the registry.")
                                 .takes_value(true)
                                 .required(true)
                         )
                 )
                 .subcommand(
                     SubCommand::with_name("list")
-                        .about("List all the urls to registries.")
+                        .about("List all the urls to registries and the binaries they provide.")
+                )
+                .subcommand(
+                    SubCommand::with_name("validate")
+                        .about("Checks a binary manifest file for problems.")
+                        .arg(
+                            Arg::with_name("path_or_url")
+                                .help("The path or url of the binary manifest file (ex. bvm.json).")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("fetch")
+                                .help("Downloads the file of each platform to ensure it exists and matches its checksum.")
+                                .long("fetch")
+                                .takes_value(false)
+                        )
+                )
+                .subcommand(
+                    SubCommand::with_name("pack")
+                        .about("Creates the binary manifest file of a release and adds it to a registry file.")
+                        .arg(
+                            Arg::with_name("spec_path")
+                                .help("The path of the file describing the release (ex. pack.json).")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("registry")
+                                .help("The path of the registry file to update. Defaults to registry.json in the directory of the spec file.")
+                                .long("registry")
+                                .takes_value(true)
+                        )
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("config")
+                .about("Commands for changing the settings in the user config.json file.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("set")
+                        .about("Sets a setting in the user config file (ex. `bvm config set dataDir /mnt/cache/bvm`).")
+                        .arg(
+                            Arg::with_name("key")
+                                .help("The setting's key.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("value")
+                                .help("The setting's value. Values that aren't JSON are treated as strings.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                )
+                .subcommand(
+                    SubCommand::with_name("unset")
+                        .about("Removes a setting from the user config file.")
+                        .arg(
+                            Arg::with_name("key")
+                                .help("The setting's key.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("util")
+                .about("Utilities for authoring binary manifest and registry files.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("hash")
+                        .about("Outputs the checksum of a file in the format expected by binary manifest files.")
+                        .arg(
+                            Arg::with_name("path_or_url")
+                                .help("The path or url of the file.")
+                                .takes_value(true)
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::with_name("algo")
+                                .help("The hashing algorithm.")
+                                .long("algo")
+                                .takes_value(true)
+                                .possible_values(&["sha256", "sha512"])
+                                .default_value("sha256"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("alias")
+                .about("Commands for aliases that can be used in place of a version of a binary.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("set")
+                        .about("Creates or updates an alias for a version of a binary.")
+                        .arg(
+                            Arg::with_name("binary_name")
+                                .help("The binary name.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("alias")
+                                .help("The name of the alias (ex. stable).")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("version")
+                                .help("The version the alias refers to.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                )
+                .subcommand(
+                    SubCommand::with_name("remove")
+                        .about("Removes an alias.")
+                        .arg(
+                            Arg::with_name("binary_name")
+                                .help("The binary name.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("alias")
+                                .help("The name of the alias.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                )
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists all the aliases.")
                 )
         )
         .subcommand(
@@ -461,6 +2143,11 @@ ARGS:
                 )
                 .subcommand(
                     SubCommand::with_name("get-pending-env-changes")
+                        .arg(
+                            Arg::with_name("shell")
+                                .long("shell")
+                                .takes_value(true)
+                        )
                 )
                 .subcommand(
                     SubCommand::with_name("clear-pending-env-changes")
@@ -470,6 +2157,19 @@ ARGS:
                 )
                 .subcommand(
                     SubCommand::with_name("get-env-vars")
+                        .arg(
+                            Arg::with_name("shell")
+                                .long("shell")
+                                .takes_value(true)
+                        )
+                )
+                .subcommand(
+                    SubCommand::with_name("shell-init")
+                        .arg(
+                            Arg::with_name("shell")
+                                .takes_value(true)
+                                .required(true)
+                        )
                 )
                 .subcommand(
                     SubCommand::with_name("get-exec-env-changes")
@@ -502,6 +2202,23 @@ ARGS:
                                 .required(true)
                         )
                 )
+                .subcommand(
+                    SubCommand::with_name("check-update")
+                        .arg(
+                            Arg::with_name("binary_name")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("version")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("version_selector")
+                                .takes_value(true)
+                        )
+                )
                 .subcommand(
                     SubCommand::with_name("unix-install")
                 )
@@ -514,7 +2231,7 @@ ARGS:
         )
         .arg(
             Arg::with_name("version")
-                .short("v")
+                .short("V")
                 .long("version")
                 .help("Prints the version.")
                 .takes_value(false),
@@ -1,7 +0,0 @@
-mod find_config_file;
-mod manipulation;
-mod read_config_file;
-
-pub use find_config_file::*;
-pub use manipulation::*;
-pub use read_config_file::*;
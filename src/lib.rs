@@ -0,0 +1,15579 @@
+//! The command line interface of bvm, which may be embedded in other applications to run its commands.
+//!
+//! Provide an event handler to `RealEnvironment::set_event_handler` to be notified of the
+//! progress of commands run with `run`, such as to show a custom progress UI. Use the `bvm-core`
+//! crate to resolve and install binaries without going through the command line interface.
+
+#[macro_use(err_obj)]
+#[macro_use(err)]
+extern crate dprint_cli_core;
+#[macro_use(coded_err, log_verbose)]
+extern crate bvm_core;
+
+#[cfg(test)]
+mod test_builders;
+
+mod arg_parser;
+
+use bvm_core::{configuration, environment, errors, plugins, registry, types, utils};
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use arg_parser::*;
+use dprint_cli_core::checksums::{
+    get_sha256_checksum, parse_checksum_path_or_url, verify_sha256_checksum, ChecksumPathOrUrl,
+};
+use dprint_cli_core::types::ErrBox;
+use environment::SYS_PATH_DELIMITER;
+use errors::{get_error_code, ErrorCode};
+use plugins::{helpers as plugin_helpers, PluginsManifest, PluginsMut, UrlInstallAction};
+use sha2::Digest;
+use types::{NameSelector, PathOrVersionSelector, VersionSelector};
+
+pub use environment::{ColorChoice, Environment, Event, LogFormat, PromptMode, RealEnvironment};
+pub use types::{BinaryName, CommandName, Version};
+
+/// Runs the bvm command line with the provided arguments, where the first argument is the executable name.
+pub fn run<TEnvironment: Environment>(environment: &TEnvironment, args: Vec<String>) -> Result<(), ErrBox> {
+    let args = parse_args(args)?;
+    // set first since it changes the location of the user config
+    environment.set_system_mode(args.system || is_system_env_var_set(environment));
+    // errors are surfaced by the commands that use the user config
+    if let Ok(Some(value)) = environment::read_user_config(environment).map(|c| c.case_insensitive_names) {
+        types::set_case_insensitive_names(value);
+    }
+    if args.offline || is_offline_env_var_set(environment) {
+        environment.set_offline(true);
+    }
+    if args.refresh {
+        environment.set_refresh(true);
+    }
+    if args.trust {
+        environment.set_trust_hosts(true);
+    }
+    if let Some(prompt_mode) = args.prompt_mode {
+        environment.set_prompt_mode(prompt_mode);
+    } else if is_ci_env_var_set(environment) {
+        environment.set_prompt_mode(PromptMode::Never);
+    }
+    if let Some(max_download_speed) = args.max_download_speed {
+        environment.set_max_download_speed(Some(max_download_speed));
+    }
+    if args.verbose {
+        environment.set_verbose(true);
+    }
+    if args.quiet {
+        environment.set_quiet(true);
+    }
+    if let Some(log_format) = args.log_format {
+        environment.set_log_format(log_format);
+    } else if environment.get_env_var("GITHUB_ACTIONS").as_deref() == Some("true") {
+        environment.set_log_format(LogFormat::GitHubActions);
+    }
+    if let Some(color) = args.color {
+        environment.set_color_choice(color);
+    }
+
+    match args.sub_command {
+        SubCommand::Help(text) => environment.log(&text),
+        SubCommand::Version => environment.log(&format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))),
+        SubCommand::Install(command) => handle_install_command(environment, command)?,
+        SubCommand::InstallUrl(command) => handle_install_url_command(environment, command)?,
+        SubCommand::InstallArchive(command) => handle_install_archive_command(environment, command)?,
+        SubCommand::Uninstall(command) => handle_uninstall_command(environment, command)?,
+        SubCommand::Link(command) => handle_link_command(environment, command)?,
+        SubCommand::Unlink(command) => handle_unlink_command(environment, command)?,
+        SubCommand::Disable(command) => set_binaries_disabled(environment, command.name_selector, true)?,
+        SubCommand::Enable(command) => set_binaries_disabled(environment, command.name_selector, false)?,
+        SubCommand::Use => handle_use_command(environment)?,
+        SubCommand::UseBinary(command) => handle_use_binary_command(environment, command)?,
+        SubCommand::List(command) => handle_list_command(environment, command)?,
+        SubCommand::ListRemote(command) => handle_list_remote_command(environment, command)?,
+        SubCommand::Changelog(command) => handle_changelog_command(environment, command)?,
+        SubCommand::Info(command) => handle_info_command(environment, command)?,
+        SubCommand::Resolve(command) => handle_resolve_command(environment, command)?,
+        SubCommand::Which(command) => handle_which_command(environment, command)?,
+        SubCommand::Why(command) => handle_why_command(environment, command)?,
+        SubCommand::Env(command) => handle_env_command(environment, command)?,
+        SubCommand::Export(command) => handle_export_command(environment, command)?,
+        SubCommand::Snapshot(command) => handle_snapshot_command(environment, command)?,
+        SubCommand::CacheKey => handle_cache_key_command(environment)?,
+        SubCommand::Init => handle_init_command(environment)?,
+        SubCommand::Prune(command) => handle_prune_command(environment, command)?,
+        SubCommand::Verify(command) => handle_verify_command(environment, command)?,
+        SubCommand::Repair => handle_repair_command(environment)?,
+        SubCommand::Stats(command) => handle_stats_command(environment, command)?,
+        SubCommand::Compact(command) => handle_compact_command(environment, command)?,
+        SubCommand::ClearUrlCache => handle_clear_url_cache(environment)?,
+        SubCommand::SelfUpdate => handle_self_update_command(environment)?,
+        SubCommand::UninstallSelf(command) => handle_uninstall_self_command(environment, command)?,
+        SubCommand::Registry(command) => handle_registry_command(environment, command)?,
+        SubCommand::Alias(command) => handle_alias_command(environment, command)?,
+        SubCommand::Config(command) => handle_config_command(environment, command)?,
+        SubCommand::Util(command) => handle_util_command(environment, command)?,
+        SubCommand::Add(command) => handle_add_command(environment, command)?,
+        SubCommand::Remove(command) => handle_remove_command(environment, command)?,
+        SubCommand::Pin(command) => handle_pin_command(environment, command)?,
+        SubCommand::Run(command) => handle_run_command(environment, command)?,
+        SubCommand::RunWith(command) => handle_run_with_command(environment, command)?,
+        SubCommand::Shell(command) => handle_shell_command(environment, command)?,
+        SubCommand::Hidden(command) => handle_hidden_command(environment, command)?,
+    }
+
+    Ok(())
+}
+
+fn is_offline_env_var_set(environment: &impl Environment) -> bool {
+    match environment.get_env_var("BVM_OFFLINE") {
+        Some(value) => value == "1" || value.to_lowercase() == "true",
+        None => false,
+    }
+}
+
+fn is_system_env_var_set(environment: &impl Environment) -> bool {
+    match environment.get_env_var(BVM_SYSTEM_ENV_VAR_NAME) {
+        Some(value) => value == "1" || value.to_lowercase() == "true",
+        None => false,
+    }
+}
+
+/// Gets if running in a CI system, which set the `CI` environment variable to a value other than `false`.
+fn is_ci_env_var_set(environment: &impl Environment) -> bool {
+    match environment.get_env_var("CI") {
+        Some(value) => !value.is_empty() && value != "0" && value.to_lowercase() != "false",
+        None => false,
+    }
+}
+
+fn handle_install_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: InstallCommand,
+) -> Result<(), ErrBox> {
+    if let Some(format) = command.dry_run {
+        return output_install_config_file_plan(environment, &command, format);
+    }
+
+    let (config_file_path, config_file) = get_config_file_or_error(environment)?;
+    let mut plugins = PluginsMut::load(environment)?;
+    plugins.set_target(command.target.clone());
+    if command.lazy {
+        plugins.set_lazy_extraction(Some(true));
+    }
+
+    // write the manifest once at the end, including the binaries installed before any failure
+    plugins.begin();
+    let result =
+        install_config_file_and_workspaces(environment, &mut plugins, &config_file_path, config_file, &command);
+    plugins.commit()?;
+    result
+}
+
+fn install_config_file_and_workspaces<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &mut PluginsMut<TEnvironment>,
+    config_file_path: &Path,
+    mut config_file: configuration::ConfigFile,
+    command: &InstallCommand,
+) -> Result<(), ErrBox> {
+    plugins.set_require_checksums(config_file.require_checksums);
+    resolve_config_file_version_aliases_for_install(environment, plugins, &mut config_file)?;
+
+    install_config_file(
+        environment,
+        plugins,
+        &environment.cwd()?,
+        config_file_path,
+        &config_file,
+        command,
+    )?;
+
+    // install the binaries of any workspace members as well
+    let config_file_dir = config_file_path.parent().unwrap();
+    for workspace in config_file.workspaces.iter() {
+        let member_dir = config_file_dir.join(workspace);
+        let member_config_file_path = match configuration::get_config_file_in_dir(environment, &member_dir) {
+            Some(file_path) => file_path,
+            None => {
+                return err!(
+                    "Could not find a bvm configuration file for workspace member '{}'.",
+                    workspace
+                )
+            }
+        };
+        let mut member_config_file = read_config_file_at_path(environment, &member_config_file_path)?;
+        plugins.set_require_checksums(Some(
+            member_config_file.require_checksums.unwrap_or(false) || config_file.require_checksums.unwrap_or(false),
+        ));
+        resolve_config_file_version_aliases_for_install(environment, plugins, &mut member_config_file)?;
+        install_config_file(
+            environment,
+            plugins,
+            &member_dir,
+            &member_config_file_path,
+            &member_config_file,
+            command,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn install_config_file<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &mut PluginsMut<TEnvironment>,
+    cwd: &Path,
+    config_file_path: &Path,
+    config_file: &configuration::ConfigFile,
+    command: &InstallCommand,
+) -> Result<(), ErrBox> {
+    if let Some(pre_install) = &config_file.on_pre_install {
+        environment.run_shell_command(cwd, pre_install)?;
+    }
+
+    log_skipped_platform_binaries(environment, plugins, config_file)?;
+    let binaries = get_config_file_binaries(plugins, config_file)?;
+    if let Some(concurrency) = environment::read_user_config(environment)?
+        .install_concurrency
+        .filter(|concurrency| *concurrency > 1)
+    {
+        let urls = binaries
+            .iter()
+            .map(|binary| (binary.path.clone(), binary.version.clone()))
+            .collect();
+        plugins.prepare_plugins(urls, concurrency);
+    }
+    let result = install_config_file_binaries(
+        environment,
+        plugins,
+        cwd,
+        config_file_path,
+        config_file,
+        &binaries,
+        command,
+    );
+    plugins.discard_prepared_plugins()?;
+    result?;
+
+    let mut installed_binaries = Vec::new();
+    for binary in binaries.iter() {
+        if let Some(installed_binary) = plugins.get_installed_binary_for_config_binary(binary)? {
+            // the shims of renamed commands resolve them from the config file
+            for command_name in installed_binary.get_command_names() {
+                let new_command_name = binary.get_command_name(&command_name);
+                if new_command_name != command_name {
+                    plugins::create_shim(environment, &new_command_name)?;
+                }
+            }
+            installed_binaries.push(installed_binary.clone());
+        }
+    }
+    output_github_actions_binaries(environment, &installed_binaries.iter().collect::<Vec<_>>())?;
+    // the hooks below may run the installed binaries, which read the manifest
+    plugins.flush()?;
+
+    if command.use_command {
+        for entry in binaries.iter() {
+            if let Some(binary) = plugins.get_installed_binary_for_config_binary(entry)?.cloned() {
+                let identifier = binary.get_identifier();
+                for command_name in binary.get_command_names() {
+                    plugins.use_global_version(command_name, plugins::GlobalBinaryLocation::Bvm(identifier.clone()))?;
+                }
+                if let Some(on_use) = &entry.on_use {
+                    plugins.flush()?;
+                    run_config_binary_hook(environment, cwd, &binary, on_use)?;
+                }
+            }
+        }
+        plugins.flush()?;
+    }
+
+    if let Some(post_install) = &config_file.on_post_install {
+        environment.run_shell_command(cwd, post_install)?;
+    }
+
+    Ok(())
+}
+
+/// Installs the binaries of a config file in order, running their install hooks.
+fn install_config_file_binaries<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &mut PluginsMut<TEnvironment>,
+    cwd: &Path,
+    config_file_path: &Path,
+    config_file: &configuration::ConfigFile,
+    binaries: &[&configuration::ConfigFileBinary],
+    command: &InstallCommand,
+) -> Result<(), ErrBox> {
+    for binary in binaries.iter().copied() {
+        let mut result = install_binary(
+            environment,
+            plugins,
+            &binary.path,
+            binary.version.as_ref(),
+            command.force,
+        );
+        if let Some(new_checksum) = result
+            .as_ref()
+            .err()
+            .and_then(|err| get_new_config_binary_checksum(binary, err))
+        {
+            if !command.accept_new_checksum {
+                return coded_err!(
+                    ErrorCode::ChecksumMismatch,
+                    "Error installing {}: {} If the binary manifest file was republished, run `bvm install --accept-new-checksum` to use its new checksum.",
+                    &binary.path.path_or_url,
+                    result.err().unwrap().to_string()
+                );
+            }
+            result =
+                accept_new_config_binary_checksum(environment, config_file_path, config_file, binary, &new_checksum)
+                    .and_then(|path| {
+                        install_binary(environment, plugins, &path, binary.version.as_ref(), command.force)
+                    });
+        }
+        if let Err(err) = result {
+            if is_skipped_optional_binary(environment, binary, &err) {
+                continue;
+            }
+            return coded_err!(
+                get_error_code(&err),
+                "Error installing {}: {}",
+                &binary.path.path_or_url,
+                err.to_string()
+            );
+        }
+        if let Some(on_install) = &binary.on_install {
+            // the hook may run the binary's shim, which reads the manifest
+            plugins.flush()?;
+            if let Some(installed_binary) = plugins.get_installed_binary_for_config_binary(binary)? {
+                run_config_binary_hook(environment, cwd, installed_binary, on_install)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Gets the checksum the binary manifest file of the config file binary has now when the
+/// error is because it no longer matches the checksum in the config file.
+fn get_new_config_binary_checksum(binary: &configuration::ConfigFileBinary, err: &ErrBox) -> Option<String> {
+    let mismatch = err.downcast_ref::<utils::ChecksumMismatchError>()?;
+    // otherwise it's the checksum of an archive, which is specified in the binary manifest file
+    if binary.path.checksum.as_ref() == Some(&mismatch.expected_checksum) {
+        Some(mismatch.actual_checksum.clone())
+    } else {
+        None
+    }
+}
+
+/// Updates the checksum of the binary in the config file for `--accept-new-checksum`, returning the
+/// path to install the binary from instead.
+fn accept_new_config_binary_checksum(
+    environment: &impl Environment,
+    config_file_path: &Path,
+    config_file: &configuration::ConfigFile,
+    binary: &configuration::ConfigFileBinary,
+    new_checksum: &str,
+) -> Result<ChecksumPathOrUrl, ErrBox> {
+    let old_checksum = binary.path.checksum.as_ref().unwrap();
+    // the index of the binary's entry in the file, which an inherited binary doesn't have
+    let binary_index = config_file
+        .binaries
+        .iter()
+        .filter(|b| !b.inherited)
+        .position(|b| std::ptr::eq(b, binary));
+    let was_replaced = match binary_index {
+        Some(binary_index) => configuration::replace_checksum_in_config_file(
+            environment,
+            config_file_path,
+            binary_index,
+            old_checksum,
+            new_checksum,
+        )?,
+        None => false,
+    };
+    if !was_replaced {
+        return err!(
+            "Could not find the checksum in {} to update it. Update it where the binary is specified instead.",
+            config_file_path.display()
+        );
+    }
+    environment.log_error(&format!(
+        "Accepted the new checksum of {}.\n  Old: {}\n  New: {}",
+        binary.path.path_or_url, old_checksum, new_checksum
+    ));
+    Ok(ChecksumPathOrUrl {
+        path_or_url: binary.path.path_or_url.clone(),
+        checksum: Some(new_checksum.to_string()),
+    })
+}
+
+/// Runs a hook specified for a binary in the config file with the binary on the path.
+fn run_config_binary_hook<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    cwd: &Path,
+    binary: &plugins::BinaryManifestItem,
+    hook_command: &str,
+) -> Result<(), ErrBox> {
+    let env_path = get_env_path_with_binaries(environment, &[binary]);
+    environment.run_shell_command_with_env_path(cwd, hook_command, &env_path)
+}
+
+/// Gets the path environment variable with the directories of the provided binaries prepended.
+fn get_env_path_with_binaries<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    binaries: &[&plugins::BinaryManifestItem],
+) -> String {
+    let mut env_paths = get_binaries_path_dirs(environment, binaries)
+        .into_iter()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    env_paths.push(environment.get_env_path());
+    env_paths.join(SYS_PATH_DELIMITER)
+}
+
+/// Gets the directories of the commands of the provided binaries along with the paths they add.
+fn get_binaries_path_dirs<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    binaries: &[&plugins::BinaryManifestItem],
+) -> Vec<PathBuf> {
+    let mut path_dirs = Vec::new();
+    for binary in binaries.iter() {
+        let plugin_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+        for command in binary.commands.iter() {
+            if let Some(command_dir) = plugin_dir.join(&command.path).parent() {
+                let command_dir = command_dir.to_path_buf();
+                if !path_dirs.contains(&command_dir) {
+                    path_dirs.push(command_dir);
+                }
+            }
+        }
+        path_dirs.extend(binary.get_resolved_env_paths(environment));
+    }
+    path_dirs
+}
+
+fn is_github_actions(environment: &impl Environment) -> bool {
+    environment.get_log_format() == LogFormat::GitHubActions
+}
+
+/// Runs the action within a collapsible group of the GitHub Actions log when outputting workflow commands.
+fn with_github_actions_group<TResult>(
+    environment: &impl Environment,
+    title: &str,
+    action: impl FnOnce() -> TResult,
+) -> TResult {
+    let is_github_actions = is_github_actions(environment);
+    if is_github_actions {
+        environment.log_error(&format!("::group::{}", title));
+    }
+    let result = action();
+    if is_github_actions {
+        environment.log_error("::endgroup::");
+    }
+    result
+}
+
+/// Adds the directories of the binaries to `$GITHUB_PATH` and their versions to `$GITHUB_OUTPUT` so
+/// they're available to the following steps of a GitHub Actions workflow.
+fn output_github_actions_binaries<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    binaries: &[&plugins::BinaryManifestItem],
+) -> Result<(), ErrBox> {
+    if !is_github_actions(environment) {
+        return Ok(());
+    }
+    let path_dirs = get_binaries_path_dirs(environment, binaries)
+        .into_iter()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    append_github_actions_file(environment, "GITHUB_PATH", &path_dirs)?;
+    let outputs = binaries
+        .iter()
+        .map(|binary| format!("{}={}", binary.name.name, binary.version))
+        .collect::<Vec<_>>();
+    append_github_actions_file(environment, "GITHUB_OUTPUT", &outputs)
+}
+
+/// Appends the lines to the file at the path of the GitHub Actions environment variable when it's set.
+fn append_github_actions_file(
+    environment: &impl Environment,
+    env_var_name: &str,
+    lines: &[String],
+) -> Result<(), ErrBox> {
+    let file_path = match environment.get_env_var(env_var_name).filter(|path| !path.is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => return Ok(()),
+    };
+    let mut text = if environment.path_exists(&file_path) {
+        environment.read_file_text(&file_path)?
+    } else {
+        String::new()
+    };
+    for line in lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    environment.write_file_text(&file_path, &text)
+}
+
+fn install_binary<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &mut PluginsMut<TEnvironment>,
+    checksum_url: &ChecksumPathOrUrl,
+    version_selector: Option<&VersionSelector>,
+    force: bool,
+) -> Result<(), ErrBox> {
+    let install_action = plugins.get_url_install_action(checksum_url, version_selector, force)?;
+    if let UrlInstallAction::Install(plugin_file) = install_action {
+        // setup the plugin
+        let binary_item =
+            with_github_actions_group(environment, &format!("Installing {}", plugin_file.display()), || {
+                plugins.setup_plugin(&plugin_file)
+            })?;
+        let identifier = binary_item.get_identifier();
+        // check if there is a global binary location set and if not, set it
+        for command_name in binary_item.get_command_names() {
+            plugins.set_global_binary_if_not_set(&identifier, &command_name)?;
+        }
+        plugins.save()?; // write for every setup plugin in case a further one fails
+        remove_versions_exceeding_retention(environment, plugins, &[identifier])?;
+    }
+    Ok(())
+}
+
+/// What an install would do, which is output instead of installing when providing `--dry-run`.
+#[derive(Default)]
+struct InstallPlan {
+    /// The binaries to install along with the path that would be downloaded.
+    install: Vec<(plugins::BinaryIdentifier, String)>,
+    already_installed: Vec<plugins::BinaryIdentifier>,
+    /// Versions that would be removed by the retention policy.
+    remove: Vec<plugins::BinaryIdentifier>,
+}
+
+impl InstallPlan {
+    fn add_install(&mut self, identifier: plugins::BinaryIdentifier, download_path: String) {
+        if !self.install.iter().any(|(planned, _)| planned == &identifier) {
+            self.install.push((identifier, download_path));
+        }
+    }
+
+    fn add_already_installed(&mut self, identifier: plugins::BinaryIdentifier) {
+        let is_planned = self.install.iter().any(|(planned, _)| planned == &identifier);
+        if !is_planned && !self.already_installed.contains(&identifier) {
+            self.already_installed.push(identifier);
+        }
+    }
+
+    fn output(&self, environment: &impl Environment, format: DryRunFormat) {
+        match format {
+            DryRunFormat::Json => {
+                fn to_json(identifier: &plugins::BinaryIdentifier) -> serde_json::Value {
+                    serde_json::json!({
+                        "name": identifier.get_binary_name().to_string(),
+                        "version": identifier.get_version().to_string(),
+                    })
+                }
+                let install = self
+                    .install
+                    .iter()
+                    .map(|(identifier, download_path)| {
+                        let mut value = to_json(identifier);
+                        value["url"] = serde_json::Value::String(download_path.clone());
+                        value
+                    })
+                    .collect::<Vec<_>>();
+                let value = serde_json::json!({
+                    "install": install,
+                    "alreadyInstalled": self.already_installed.iter().map(to_json).collect::<Vec<_>>(),
+                    "remove": self.remove.iter().map(to_json).collect::<Vec<_>>(),
+                });
+                environment.log(&serde_json::to_string_pretty(&value).unwrap());
+            }
+            DryRunFormat::Text => {
+                let mut lines = Vec::new();
+                for (identifier, download_path) in self.install.iter() {
+                    lines.push(format!(
+                        "Would install {} {} from {}",
+                        identifier.get_binary_name(),
+                        identifier.get_version(),
+                        download_path
+                    ));
+                }
+                for identifier in self.already_installed.iter() {
+                    lines.push(format!(
+                        "Already installed {} {}",
+                        identifier.get_binary_name(),
+                        identifier.get_version()
+                    ));
+                }
+                for identifier in self.remove.iter() {
+                    lines.push(format!(
+                        "Would remove {} {} to keep the most recent versions",
+                        identifier.get_binary_name(),
+                        identifier.get_version()
+                    ));
+                }
+                if lines.is_empty() {
+                    lines.push("Nothing to install".to_string());
+                }
+                environment.log(&lines.join("\n"));
+            }
+        }
+    }
+}
+
+/// Outputs what installing the binaries of the config file and its workspace members would do.
+fn output_install_config_file_plan<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: &InstallCommand,
+    format: DryRunFormat,
+) -> Result<(), ErrBox> {
+    let (config_file_path, config_file) = get_config_file_or_error(environment)?;
+    let mut config_files = vec![config_file];
+    let config_file_dir = config_file_path.parent().unwrap();
+    for workspace in config_files[0].workspaces.clone().iter() {
+        match configuration::get_config_file_in_dir(environment, &config_file_dir.join(workspace)) {
+            Some(file_path) => config_files.push(read_config_file_at_path(environment, &file_path)?),
+            None => {
+                return err!(
+                    "Could not find a bvm configuration file for workspace member '{}'.",
+                    workspace
+                )
+            }
+        }
+    }
+
+    // resolve what would be installed without saving any changes
+    let mut plugins = PluginsMut::load_disallow_write(environment);
+    plugins.set_target(command.target.clone());
+    let mut plan = InstallPlan::default();
+    for config_file in config_files.iter_mut() {
+        resolve_config_file_version_aliases_for_install(environment, &mut plugins, config_file)?;
+        log_skipped_platform_binaries(environment, &plugins, config_file)?;
+        for binary in get_config_file_binaries(&mut plugins, config_file)? {
+            let install_action = plugins
+                .get_url_install_action(&binary.path, binary.version.as_ref(), command.force)
+                .and_then(|install_action| match &install_action {
+                    UrlInstallAction::Install(plugin_file) => {
+                        let download_path = plugins.get_download_path(plugin_file)?;
+                        Ok((install_action, Some(download_path)))
+                    }
+                    UrlInstallAction::None => Ok((install_action, None)),
+                });
+            let (install_action, download_path) = match install_action {
+                Ok(result) => result,
+                Err(err) if is_skipped_optional_binary(environment, binary, &err) => continue,
+                Err(err) => {
+                    return coded_err!(
+                        get_error_code(&err),
+                        "Error installing {}: {}",
+                        &binary.path.path_or_url,
+                        err.to_string()
+                    )
+                }
+            };
+            match install_action {
+                UrlInstallAction::Install(plugin_file) => {
+                    plan.add_install(plugin_file.get_identifier(), download_path.unwrap());
+                }
+                UrlInstallAction::None => {
+                    if let Some(installed_binary) = plugins.get_installed_binary_for_config_binary(binary)? {
+                        plan.add_already_installed(installed_binary.get_identifier());
+                    }
+                }
+            }
+        }
+    }
+
+    let installed_identifiers = plan
+        .install
+        .iter()
+        .map(|(identifier, _)| identifier.clone())
+        .collect::<Vec<_>>();
+    plan.remove = get_versions_exceeding_retention(environment, &plugins.manifest, &installed_identifiers)?;
+    plan.output(environment, format);
+    Ok(())
+}
+
+/// Outputs what installing the provided urls or names would do.
+fn output_install_url_plan<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    mut plugins: PluginsMut<TEnvironment>,
+    urls: &[ChecksumPathOrUrl],
+    force: bool,
+    format: DryRunFormat,
+) -> Result<(), ErrBox> {
+    let mut plan = InstallPlan::default();
+    for url in urls.iter() {
+        match plugins.get_url_install_action(url, None, force) {
+            Ok(UrlInstallAction::Install(plugin_file)) => {
+                plan.add_install(plugin_file.get_identifier(), plugins.get_download_path(&plugin_file)?);
+            }
+            Ok(UrlInstallAction::None) => {
+                if let Some(identifier) = plugins.manifest.get_identifier_from_url(url) {
+                    plan.add_already_installed(identifier.clone());
+                }
+            }
+            Err(err) => {
+                return coded_err!(
+                    get_error_code(&err),
+                    "Error installing {}. {}",
+                    url.path_or_url,
+                    err.to_string()
+                )
+            }
+        }
+    }
+
+    let installed_identifiers = plan
+        .install
+        .iter()
+        .map(|(identifier, _)| identifier.clone())
+        .collect::<Vec<_>>();
+    plan.remove = get_versions_exceeding_retention(environment, &plugins.manifest, &installed_identifiers)?;
+    plan.output(environment, format);
+    Ok(())
+}
+
+fn handle_install_url_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: InstallUrlCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = if command.dry_run.is_some() {
+        PluginsMut::load_disallow_write(environment)
+    } else {
+        PluginsMut::load(environment)?
+    };
+    plugins.set_target(command.target.clone());
+    if command.lazy {
+        plugins.set_lazy_extraction(Some(true));
+    }
+    plugins.begin();
+    let mut registry_files = registry::RegistryFileCache::new();
+    let mut url_or_names = command.url_or_names;
+    if let Some(tag) = command.legacy_tag {
+        // it's the `<name> <tag>` form when the second name isn't a binary of a registry
+        let is_second_binary = match &url_or_names[1] {
+            UrlOrName::Name(name) => get_registry_binary_name_and_urls(environment, &name.name_selector).is_ok(),
+            UrlOrName::Url(_) => true,
+        };
+        if !is_second_binary {
+            url_or_names.truncate(1);
+            if let UrlOrName::Name(name) = &mut url_or_names[0] {
+                name.version_selector = None;
+                name.tag = Some(tag);
+            }
+        }
+    }
+
+    // download the registry files of all the names at once
+    let mut registry_urls = Vec::new();
+    for url_or_name in url_or_names.iter() {
+        if let UrlOrName::Name(name) = url_or_name {
+            registry_urls.extend(get_registry_binary_name_and_urls(environment, &name.name_selector)?.1);
+        }
+    }
+    registry_files.prefetch(environment, registry_urls)?;
+
+    let mut urls = Vec::new();
+    // the toolchains being installed and the urls of their binaries
+    let mut toolchains = Vec::new();
+    for url_or_name in url_or_names.iter() {
+        let name = match url_or_name {
+            UrlOrName::Name(name) => name,
+            UrlOrName::Url(_) => {
+                urls.push(resolve_url_or_name(
+                    environment,
+                    &mut plugins,
+                    &mut registry_files,
+                    url_or_name,
+                )?);
+                continue;
+            }
+        };
+        let (binary_name, version_info) =
+            resolve_registry_version_info(environment, &mut plugins, &mut registry_files, name)?;
+        if !version_info.is_toolchain() {
+            urls.push(version_info.get_url());
+            continue;
+        }
+        let mut toolchain_urls = Vec::new();
+        for binary in version_info.binaries.iter() {
+            let url = parse_url_or_name(binary, true).and_then(|binary_url_or_name| {
+                resolve_url_or_name(environment, &mut plugins, &mut registry_files, &binary_url_or_name)
+            });
+            match url {
+                Ok(url) => toolchain_urls.push(url),
+                Err(err) => {
+                    return coded_err!(
+                        get_error_code(&err),
+                        "Error resolving {} of toolchain {} {}. {}",
+                        binary,
+                        binary_name,
+                        version_info.version,
+                        err.to_string()
+                    )
+                }
+            }
+        }
+        urls.extend(toolchain_urls.iter().cloned());
+        toolchains.push((
+            plugins::BinaryIdentifier::new(&binary_name, &version_info.version),
+            toolchain_urls,
+        ));
+    }
+
+    if let Some(format) = command.dry_run {
+        return output_install_url_plan(environment, plugins, &urls, command.force, format);
+    }
+
+    // get the plugins that need to be installed
+    let mut plugin_files = Vec::new();
+    let mut plugin_identifiers = HashSet::new();
+    for url in urls.iter() {
+        match plugins.get_url_install_action(url, None, command.force) {
+            Ok(UrlInstallAction::None) => {
+                if urls.len() == 1 {
+                    environment.log_error("Already installed. Provide the `--force` flag to reinstall.")
+                } else {
+                    environment.log_error(&format!(
+                        "Already installed {}. Provide the `--force` flag to reinstall.",
+                        url.path_or_url
+                    ))
+                }
+            }
+            Ok(UrlInstallAction::Install(plugin_file)) => {
+                // the same binary may be provided more than once (ex. `bvm install deno deno@1`)
+                if plugin_identifiers.insert(plugin_file.get_identifier()) {
+                    plugin_files.push(*plugin_file);
+                }
+            }
+            Err(err) => {
+                return coded_err!(
+                    get_error_code(&err),
+                    "Error installing {}. {}",
+                    url.path_or_url,
+                    err.to_string()
+                )
+            }
+        }
+    }
+
+    // setup the plugins in parallel (previously installed binaries are only replaced once set up successfully)
+    let plugin_urls = plugin_files.iter().map(|p| p.url.clone()).collect::<Vec<_>>();
+    let group_title = format!(
+        "Installing {}",
+        plugin_files.iter().map(|p| p.display()).collect::<Vec<_>>().join(", ")
+    );
+    let results = if plugin_files.is_empty() {
+        Vec::new()
+    } else {
+        with_github_actions_group(environment, &group_title, || plugins.setup_plugins(plugin_files))
+    };
+    let mut first_error = None;
+    let mut installed_identifiers = Vec::new();
+    for (result, url) in results.into_iter().zip(plugin_urls) {
+        let identifier = match result {
+            Ok(identifier) => identifier,
+            Err(err) => {
+                if first_error.is_none() {
+                    first_error = Some(errors::CodedError::new(
+                        get_error_code(&err),
+                        format!("Error installing {}. {}", url, err),
+                    ));
+                }
+                continue;
+            }
+        };
+        installed_identifiers.push(identifier.clone());
+        // the binaries of toolchains are put on the path once all of them are installed
+        let is_toolchain_binary = toolchains.iter().any(|(_, toolchain_urls)| {
+            toolchain_urls
+                .iter()
+                .any(|toolchain_url| toolchain_url.path_or_url == url)
+        });
+        if !command.use_command && !is_toolchain_binary {
+            set_global_binary_if_not_set_or_log(environment, &mut plugins, &identifier)?;
+        }
+    }
+
+    if let Some(err) = first_error {
+        plugins.commit()?; // save the successful installs
+        return Err(Box::new(err));
+    }
+
+    for (toolchain_identifier, toolchain_urls) in toolchains {
+        let identifiers = toolchain_urls
+            .iter()
+            .filter_map(|url| plugins.manifest.get_identifier_from_url(url).cloned())
+            .collect::<Vec<_>>();
+        if !command.use_command {
+            use_toolchain_binaries_of_other_versions(&mut plugins, &toolchain_identifier, &identifiers)?;
+            for identifier in identifiers.iter() {
+                set_global_binary_if_not_set_or_log(environment, &mut plugins, identifier)?;
+            }
+        }
+        plugins.set_toolchain(toolchain_identifier, identifiers);
+    }
+
+    if command.use_command {
+        let mut all_command_names = Vec::new();
+        for url in urls.iter() {
+            let identifier = plugins
+                .manifest
+                .get_identifier_from_url(url)
+                .map(|identifier| identifier.clone())
+                .unwrap();
+            let command_names = plugins.manifest.get_binary(&identifier).unwrap().get_command_names();
+
+            for command_name in command_names.into_iter() {
+                plugins.use_global_version(
+                    command_name.clone(),
+                    plugins::GlobalBinaryLocation::Bvm(identifier.clone()),
+                )?;
+                all_command_names.push(command_name);
+            }
+        }
+
+        display_commands_in_config_file_warning_if_necessary(environment, &plugins.manifest, &all_command_names);
+    }
+
+    plugins.commit()?;
+    let binaries = urls
+        .iter()
+        .filter_map(|url| plugins.manifest.get_identifier_from_url(url))
+        .filter_map(|identifier| plugins.manifest.get_binary(identifier))
+        .collect::<Vec<_>>();
+    output_github_actions_binaries(environment, &binaries)?;
+    remove_versions_exceeding_retention(environment, &mut plugins, &installed_identifiers)?;
+
+    Ok(())
+}
+
+/// Uses the installed binary on the path for its commands that aren't set, otherwise logs how to use it.
+fn set_global_binary_if_not_set_or_log<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &mut PluginsMut<TEnvironment>,
+    identifier: &plugins::BinaryIdentifier,
+) -> Result<(), ErrBox> {
+    let binary_item = plugins.manifest.get_binary(identifier).unwrap();
+    let binary_name = binary_item.name.clone();
+    let version = binary_item.version.clone();
+    let command_names = binary_item.get_command_names();
+
+    let mut not_set_command_name = false;
+    for command_name in command_names.iter() {
+        if !plugins.set_global_binary_if_not_set(identifier, command_name)? {
+            not_set_command_name = true;
+        }
+    }
+    if not_set_command_name {
+        environment.log_error(&format!(
+            "Installed. Run `bvm use {} {}` to use it on the path as {}.",
+            binary_name.display_toggled_owner(!plugins.manifest.binary_name_has_same_owner(&binary_name)),
+            version,
+            utils::sentence_join(
+                &command_names
+                    .into_iter()
+                    .map(|c| format!("'{}'", c))
+                    .collect::<Vec<_>>()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Moves the commands that use the binaries of the other installed versions of the toolchain
+/// on the path to the binaries of the toolchain, so that installing a newer version upgrades them.
+fn use_toolchain_binaries_of_other_versions<TEnvironment: Environment>(
+    plugins: &mut PluginsMut<TEnvironment>,
+    toolchain_identifier: &plugins::BinaryIdentifier,
+    identifiers: &[plugins::BinaryIdentifier],
+) -> Result<(), ErrBox> {
+    let other_toolchains = plugins
+        .manifest
+        .get_toolchains_matching_name(&toolchain_identifier.get_binary_name().to_selector());
+    for other_toolchain in other_toolchains.iter().filter(|other| *other != toolchain_identifier) {
+        let other_identifiers = plugins
+            .manifest
+            .get_toolchain_binaries(other_toolchain)
+            .cloned()
+            .unwrap_or_default();
+        for other_identifier in other_identifiers.iter() {
+            let identifier = identifiers
+                .iter()
+                .find(|identifier| identifier.get_binary_name() == other_identifier.get_binary_name());
+            let identifier = match identifier {
+                Some(identifier) if identifier != other_identifier => identifier,
+                _ => continue,
+            };
+            let command_names = match plugins.manifest.get_binary(identifier) {
+                Some(binary) => binary.get_command_names(),
+                None => continue,
+            };
+            for command_name in command_names {
+                let uses_other_identifier = match plugins.manifest.get_global_binary_location(&command_name) {
+                    Some(plugins::GlobalBinaryLocation::Bvm(global_identifier)) => {
+                        &global_identifier == other_identifier
+                    }
+                    _ => false,
+                };
+                if uses_other_identifier {
+                    plugins.use_global_version(command_name, plugins::GlobalBinaryLocation::Bvm(identifier.clone()))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn handle_install_archive_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: InstallArchiveCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    plugins.set_target(command.target.clone());
+    if command.lazy {
+        plugins.set_lazy_extraction(Some(true));
+    }
+    let identifier = plugins::BinaryIdentifier::new(&command.binary_name, &command.version);
+    if plugins.manifest.has_binary(&identifier) && !command.force {
+        environment.log_error("Already installed. Provide the `--force` flag to reinstall.");
+        return Ok(());
+    }
+
+    let binary_item =
+        match plugins::get_archive_plugin_file(environment, &command.binary_name, &command.version, &command.url)
+            .and_then(|plugin_file| plugins.setup_plugin(&plugin_file).cloned())
+        {
+            Ok(binary_item) => binary_item,
+            Err(err) => {
+                return coded_err!(
+                    get_error_code(&err),
+                    "Error installing {}. {}",
+                    command.url,
+                    err.to_string()
+                )
+            }
+        };
+    let command_names = binary_item.get_command_names();
+
+    if command.use_command {
+        for command_name in command_names.iter() {
+            plugins.use_global_version(
+                command_name.clone(),
+                plugins::GlobalBinaryLocation::Bvm(identifier.clone()),
+            )?;
+        }
+        display_commands_in_config_file_warning_if_necessary(environment, &plugins.manifest, &command_names);
+    } else {
+        let mut not_set_command_name = false;
+        for command_name in command_names.iter() {
+            if !plugins.set_global_binary_if_not_set(&identifier, command_name)? {
+                not_set_command_name = true;
+            }
+        }
+        if not_set_command_name {
+            environment.log_error(&format!(
+                "Installed. Run `bvm use {} {}` to use it on the path as {}.",
+                command
+                    .binary_name
+                    .display_toggled_owner(!plugins.manifest.binary_name_has_same_owner(&command.binary_name)),
+                command.version,
+                utils::sentence_join(&command_names.iter().map(|c| format!("'{}'", c)).collect::<Vec<_>>()),
+            ));
+        }
+    }
+
+    plugins.save()?;
+    remove_versions_exceeding_retention(environment, &mut plugins, &[identifier])?;
+
+    Ok(())
+}
+
+/// Gets the binary name and the urls of the registries associated with the provided name selector.
+fn get_registry_binary_name_and_urls<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    name_selector: &NameSelector,
+) -> Result<(BinaryName, Vec<String>), ErrBox> {
+    if name_selector.has_wildcard() {
+        return err!(
+            "Wildcards are not supported when resolving '{}' from a registry. Please specify the binary name.",
+            name_selector
+        );
+    }
+
+    let registry = registry::Registry::load(environment);
+    let owner_registries = get_owner_registries(environment)?;
+    let url_results = get_registry_url_results(environment, &registry, &owner_registries, name_selector)?;
+
+    if url_results.is_empty() {
+        if let Some(url) = name_selector
+            .owner
+            .as_ref()
+            .and_then(|owner| owner_registries.get(owner))
+        {
+            return coded_err!(
+                ErrorCode::BinaryNotFound,
+                "Could not find binary '{}' in {}, which is the registry of its owner.",
+                name_selector,
+                url
+            );
+        }
+        if let Some(closest_name) = name_selector.get_closest_name(registry.names()) {
+            return coded_err!(
+                ErrorCode::BinaryNotFound,
+                "There were no registries found for the provided binary. Did you mean {}?",
+                closest_name
+            );
+        }
+        return coded_err!(
+            ErrorCode::BinaryNotFound,
+            "There were no registries found for the provided binary. Did you mean to add one using `bvm registry add <url>`?"
+        );
+    }
+
+    // display an error if there are multiple owners
+    let mut binary_names = url_results
+        .iter()
+        .map(|r| &r.owner)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(|o| format!("{}/{}", o, name_selector.name))
+        .collect::<Vec<String>>();
+    if binary_names.len() > 1 {
+        binary_names.sort();
+        return err!(
+            "There were multiple binaries with the name '{}'. Please include the owner in the name:\n  {}",
+            name_selector.name,
+            binary_names.join("\n  ")
+        );
+    }
+    let binary_name = BinaryName::new(url_results[0].owner.clone(), name_selector.name.clone());
+
+    let urls = url_results.into_iter().map(|r| r.url).collect();
+    Ok((binary_name, urls))
+}
+
+/// Gets the registries of the binaries matching the name selector. The registries of the current
+/// directory's configuration file are searched first and the binaries of an owner that's routed to
+/// a registry are only resolved from that registry.
+fn get_registry_url_results<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    registry: &registry::Registry,
+    owner_registries: &HashMap<String, String>,
+    name_selector: &NameSelector,
+) -> Result<Vec<registry::UrlResult>, ErrBox> {
+    if let Some(url) = name_selector
+        .owner
+        .as_ref()
+        .and_then(|owner| owner_registries.get(owner))
+    {
+        return get_registry_file_url_results(environment, url, name_selector);
+    }
+
+    let mut url_results = get_project_registry_urls(environment, name_selector)?;
+    if url_results.is_empty() {
+        url_results = registry.get_urls(name_selector);
+    }
+    if url_results.is_empty() {
+        url_results = get_user_config_registry_urls(environment, name_selector)?;
+    }
+    url_results.retain(|r| match owner_registries.get(&r.owner) {
+        Some(url) => url == &r.url,
+        None => true,
+    });
+
+    // names without an owner may also be a binary of a routed owner
+    if name_selector.owner.is_none() {
+        let mut routed_urls = owner_registries.values().collect::<Vec<_>>();
+        routed_urls.sort();
+        routed_urls.dedup();
+        for url in routed_urls {
+            for url_result in get_registry_file_url_results(environment, url, name_selector)? {
+                if owner_registries.get(&url_result.owner) == Some(url) && !url_results.contains(&url_result) {
+                    url_results.push(url_result);
+                }
+            }
+        }
+    }
+
+    Ok(url_results)
+}
+
+/// Gets the registry urls that the binaries of owners are only resolved from. The configuration
+/// file of the current directory overrides the ones in the user config.
+fn get_owner_registries<TEnvironment: Environment>(
+    environment: &TEnvironment,
+) -> Result<HashMap<String, String>, ErrBox> {
+    let mut owner_registries = environment::read_user_config(environment)?
+        .owner_registries
+        .unwrap_or_default();
+    if let Some((_, config_file)) = get_config_file(environment)? {
+        // like the project's registries, the hosts must be trusted since the file may come from anywhere
+        for url in config_file.owner_registries.values() {
+            plugins::verify_trusted_host(environment, url)?;
+        }
+        owner_registries.extend(config_file.owner_registries);
+    }
+    Ok(owner_registries)
+}
+
+/// Searches the registries of the current directory's configuration file for binaries matching the
+/// name selector. The hosts of the registries must be trusted since the file may come from anywhere.
+fn get_project_registry_urls<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    name_selector: &NameSelector,
+) -> Result<Vec<registry::UrlResult>, ErrBox> {
+    let mut url_results = Vec::new();
+    if let Some((_, config_file)) = get_config_file(environment)? {
+        for url in config_file.registries {
+            plugins::verify_trusted_host(environment, &url)?;
+            for url_result in get_registry_file_url_results(environment, &url, name_selector)? {
+                if !url_results.contains(&url_result) {
+                    url_results.push(url_result);
+                }
+            }
+        }
+    }
+    Ok(url_results)
+}
+
+/// Searches the registries of the user config for binaries matching the name selector.
+fn get_user_config_registry_urls<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    name_selector: &NameSelector,
+) -> Result<Vec<registry::UrlResult>, ErrBox> {
+    let mut url_results = Vec::new();
+    for url in environment::read_user_config(environment)?
+        .registries
+        .unwrap_or_default()
+    {
+        url_results.extend(get_registry_file_url_results(environment, &url, name_selector)?);
+    }
+    Ok(url_results)
+}
+
+/// Searches the registry file at the url for binaries matching the name selector.
+fn get_registry_file_url_results<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    url: &str,
+    name_selector: &NameSelector,
+) -> Result<Vec<registry::UrlResult>, ErrBox> {
+    let mut url_results = Vec::new();
+    let registry_file = registry::download_registry_file(environment, url)?;
+    for registry_binary in registry_file.binaries.iter() {
+        let binary_name = registry_binary.get_binary_name();
+        if name_selector.is_match(&binary_name) {
+            url_results.push(registry::UrlResult {
+                owner: binary_name.owner,
+                url: url.to_string(),
+            });
+        }
+    }
+    Ok(url_results)
+}
+
+fn resolve_url_or_name<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &mut PluginsMut<TEnvironment>,
+    registry_files: &mut registry::RegistryFileCache,
+    url_or_name: &UrlOrName,
+) -> Result<ChecksumPathOrUrl, ErrBox> {
+    match url_or_name {
+        UrlOrName::Url(url) => Ok(ChecksumPathOrUrl {
+            path_or_url: plugins::resolve_plugin_file_url(&environment.cwd()?, &url.path_or_url)?,
+            checksum: url.checksum.clone(),
+        }),
+        UrlOrName::Name(name) => {
+            let (binary_name, version_info) =
+                resolve_registry_version_info(environment, plugins, registry_files, name)?;
+            if version_info.is_toolchain() {
+                return err!(
+                    "{} {} is a toolchain of other binaries, which may only be installed with `bvm install`.",
+                    binary_name,
+                    version_info.version
+                );
+            }
+            Ok(version_info.get_url())
+        }
+    }
+}
+
+/// Finds the version of the binary in the registries that matches the name's version selector or tag.
+fn resolve_registry_version_info<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &mut PluginsMut<TEnvironment>,
+    registry_files: &mut registry::RegistryFileCache,
+    name: &InstallName,
+) -> Result<(BinaryName, registry::RegistryVersionInfo), ErrBox> {
+    let (binary_name, urls) = get_registry_binary_name_and_urls(environment, &name.name_selector)?;
+    if let Some(tag) = &name.tag {
+        return match find_registry_tag_version_info(environment, registry_files, &urls, &binary_name, tag)? {
+            Some(version_info) => {
+                plugins.set_registry_tag(binary_name.clone(), tag.clone(), version_info.version.clone());
+                Ok((binary_name, version_info))
+            }
+            None => coded_err!(
+                ErrorCode::VersionNotFound,
+                "Could not find tag '{}' for binary '{}' in any registry.",
+                tag,
+                name.name_selector
+            ),
+        };
+    }
+    let version_info = if let Some(version) = &name.version_selector {
+        find_version_info(environment, registry_files, &urls, &binary_name, |item| {
+            version.matches(&item.version)
+        })?
+    } else {
+        find_latest_version_info(environment, registry_files, &urls, &binary_name)?
+    };
+    return match version_info {
+        Some(version_info) => Ok((binary_name, version_info)),
+        None => match &name.version_selector {
+            Some(version) => coded_err!(
+                ErrorCode::VersionNotFound,
+                "Could not find binary '{}' matching '{}' in any registry.",
+                name.name_selector,
+                version
+            ),
+            None => coded_err!(
+                ErrorCode::BinaryNotFound,
+                "Could not find binary '{}' in any registry.",
+                name.name_selector
+            ),
+        },
+    };
+
+    fn find_version_info<TEnvironment: Environment>(
+        environment: &TEnvironment,
+        registry_files: &mut registry::RegistryFileCache,
+        urls: &Vec<String>,
+        name: &BinaryName,
+        is_match: impl Fn(&registry::RegistryVersionInfo) -> bool,
+    ) -> Result<Option<registry::RegistryVersionInfo>, ErrBox> {
+        let mut best_match: Option<registry::RegistryVersionInfo> = None;
+        for url in urls.iter() {
+            let registry_file = registry_files.get(environment, url)?;
+            if let Some(registry_binary) = registry_file.take_binary_with_name(name) {
+                for version_info in registry_binary.versions {
+                    if is_match(&version_info) && version_info.is_available_for_platforms(plugins::get_platform_keys())
+                    {
+                        if let Some(best_match_val) = &best_match {
+                            if best_match_val.version.cmp(&version_info.version) == Ordering::Less {
+                                best_match = Some(version_info);
+                            }
+                        } else {
+                            best_match = Some(version_info);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(best_match)
+    }
+}
+
+/// Finds the latest release in the registries, or the latest pre-release when there are no releases.
+fn find_latest_version_info<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    registry_files: &mut registry::RegistryFileCache,
+    urls: &Vec<String>,
+    name: &BinaryName,
+) -> Result<Option<registry::RegistryVersionInfo>, ErrBox> {
+    let mut latest_pre_release: Option<registry::RegistryVersionInfo> = None;
+    let mut latest_release: Option<registry::RegistryVersionInfo> = None;
+    for url in urls.iter() {
+        let registry_file = registry_files.get(environment, url)?;
+        if let Some(registry_binary) = registry_file.take_binary_with_name(name) {
+            for item in registry_binary.versions {
+                if !item.is_available_for_platforms(plugins::get_platform_keys()) {
+                    continue;
+                }
+                let latest = if item.version.is_prerelease() {
+                    &mut latest_pre_release
+                } else {
+                    &mut latest_release
+                };
+                if let Some(latest) = latest.as_mut() {
+                    if item.version.gt(&latest.version) {
+                        *latest = item;
+                    }
+                } else {
+                    *latest = Some(item);
+                }
+            }
+        }
+    }
+
+    Ok(latest_release.or(latest_pre_release))
+}
+
+fn handle_uninstall_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    uninstall_command: UninstallCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    if uninstall_command.interactive {
+        return uninstall_selected_binaries(environment, plugins, &uninstall_command.name_selector);
+    }
+    if plugins
+        .manifest
+        .get_binaries_matching_name(&uninstall_command.name_selector)
+        .is_empty()
+    {
+        let toolchain_identifiers = plugins
+            .manifest
+            .get_toolchains_matching_name(&uninstall_command.name_selector)
+            .into_iter()
+            .filter(|identifier| match &uninstall_command.version {
+                Some(version) if !uninstall_command.all => &identifier.get_version() == version,
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+        if !toolchain_identifiers.is_empty() {
+            return uninstall_toolchains(environment, plugins, &toolchain_identifiers);
+        }
+    }
+    let version = match uninstall_command.version {
+        Some(version) if !uninstall_command.all => version,
+        _ => return uninstall_all_matching_binaries(environment, plugins, &uninstall_command.name_selector),
+    };
+    let name_selector =
+        plugin_helpers::resolve_name_selector_owner(environment, &plugins.manifest, uninstall_command.name_selector)?;
+    let binary =
+        plugin_helpers::get_binary_with_name_and_version(&plugins.manifest, &name_selector, &version.to_selector())?;
+    let binary_identifier = binary.get_identifier();
+    run_pre_uninstall_command(environment, binary)?;
+
+    // remove the plugin from the manifest first
+    plugins.remove_binary(&binary_identifier)?;
+    plugins.save()?;
+
+    // now attempt to delete the directory
+    remove_binary_dir(environment, &binary_identifier)?;
+    plugins::remove_unused_store_files(environment)?;
+
+    Ok(())
+}
+
+/// Disables or enables the commands of the binaries matching the name selector.
+fn set_binaries_disabled<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    name_selector: NameSelector,
+    disabled: bool,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let name_selector = plugin_helpers::resolve_name_selector_owner(environment, &plugins.manifest, name_selector)?;
+    let mut binary_names = plugins
+        .manifest
+        .get_binaries_matching_name(&name_selector)
+        .into_iter()
+        .map(|binary| binary.name.clone())
+        .collect::<Vec<_>>();
+    binary_names.sort();
+    binary_names.dedup();
+    if binary_names.is_empty() {
+        return plugin_helpers::get_not_installed_error(&plugins.manifest, &name_selector);
+    }
+    if binary_names.len() > 1 && !name_selector.has_wildcard() {
+        return err!(
+            "There were multiple binaries with the name '{}'. Please include the owner in the name:\n  {}",
+            name_selector.name,
+            binary_names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+                .join("\n  ")
+        );
+    }
+
+    for binary_name in binary_names.iter() {
+        let changed = plugins.set_binary_disabled(binary_name, disabled);
+        environment.log_error(&match (disabled, changed) {
+            (true, true) => format!(
+                "Disabled {}. Its commands will run the executables on the path until it's enabled with `bvm enable {0}`.",
+                binary_name
+            ),
+            (true, false) => format!("{} is already disabled.", binary_name),
+            (false, true) => format!("Enabled {}.", binary_name),
+            (false, false) => format!("{} is not disabled.", binary_name),
+        });
+    }
+    plugins.save()
+}
+
+fn uninstall_all_matching_binaries<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: PluginsMut<TEnvironment>,
+    name_selector: &NameSelector,
+) -> Result<(), ErrBox> {
+    let mut binaries = plugins
+        .manifest
+        .get_binaries_matching_name(name_selector)
+        .into_iter()
+        .map(|binary| (binary.name.clone(), binary.version.clone()))
+        .collect::<Vec<_>>();
+    if binaries.is_empty() {
+        return plugin_helpers::get_not_installed_error(&plugins.manifest, name_selector);
+    }
+    binaries.sort();
+
+    let identifiers = binaries
+        .iter()
+        .map(|(name, version)| plugins::BinaryIdentifier::new(name, version))
+        .collect::<Vec<_>>();
+    uninstall_binaries(environment, plugins, &identifiers)
+}
+
+/// Asks which of the versions matching the name selector to uninstall then uninstalls them.
+fn uninstall_selected_binaries<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: PluginsMut<TEnvironment>,
+    name_selector: &NameSelector,
+) -> Result<(), ErrBox> {
+    let mut binaries = plugins.manifest.get_binaries_matching_name(name_selector);
+    if binaries.is_empty() {
+        if plugins.manifest.binaries().next().is_none() {
+            return err!("No binaries are installed.");
+        }
+        return plugin_helpers::get_not_installed_error(&plugins.manifest, name_selector);
+    }
+    binaries.sort();
+
+    let now = environment.get_time_secs();
+    let mut items = Vec::new();
+    for binary in binaries.iter() {
+        if binary.linked {
+            items.push(format!("{} {} (linked)", binary.name, binary.version));
+            continue;
+        }
+        if plugins.manifest.is_read_only_binary(&binary.get_identifier()) {
+            items.push(format!("{} {} (read-only root)", binary.name, binary.version));
+            continue;
+        }
+        let plugin_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+        let size = get_dir_size_if_exists(environment, &plugin_dir)?;
+        let unused_days = now.saturating_sub(plugins::get_binary_last_used_time(environment, binary)) / (24 * 60 * 60);
+        items.push(format!(
+            "{} {} - {}, last used {}",
+            binary.name,
+            binary.version,
+            utils::format_bytes(size),
+            match unused_days {
+                0 => "today".to_string(),
+                1 => "1 day ago".to_string(),
+                days => format!("{} days ago", days),
+            }
+        ));
+    }
+    let selected_indexes = match environment.multi_select("Select the versions to uninstall:", &items)? {
+        Some(selected_indexes) => selected_indexes,
+        None => return err!("Selecting the versions to uninstall requires running bvm interactively."),
+    };
+    if selected_indexes.is_empty() {
+        environment.log_error("No versions were selected.");
+        return Ok(());
+    }
+    let identifiers = selected_indexes
+        .into_iter()
+        .map(|index| binaries[index].get_identifier())
+        .collect::<Vec<_>>();
+    uninstall_binaries(environment, plugins, &identifiers)
+}
+
+fn uninstall_binaries<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    mut plugins: PluginsMut<TEnvironment>,
+    identifiers: &[plugins::BinaryIdentifier],
+) -> Result<(), ErrBox> {
+    for identifier in identifiers.iter() {
+        if let Some(binary) = plugins.manifest.get_binary(identifier) {
+            run_pre_uninstall_command(environment, binary)?;
+        }
+        plugins.remove_binary(identifier)?;
+    }
+    plugins.save()?;
+    for identifier in identifiers.iter() {
+        remove_binary_dir(environment, identifier)?;
+        environment.log_error(&format!(
+            "Removed {} {}.",
+            identifier.get_binary_name(),
+            identifier.get_version()
+        ));
+    }
+    plugins::remove_unused_store_files(environment)?;
+
+    Ok(())
+}
+
+/// Uninstalls the toolchains along with their binaries that weren't also installed by another toolchain.
+fn uninstall_toolchains<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    mut plugins: PluginsMut<TEnvironment>,
+    toolchain_identifiers: &[plugins::BinaryIdentifier],
+) -> Result<(), ErrBox> {
+    let mut binary_identifiers = Vec::new();
+    for toolchain_identifier in toolchain_identifiers.iter() {
+        binary_identifiers.extend(plugins.remove_toolchain(toolchain_identifier).unwrap_or_default());
+    }
+    let mut identifiers = Vec::new();
+    for identifier in binary_identifiers {
+        if plugins.manifest.has_binary(&identifier)
+            && !plugins.manifest.is_toolchain_binary(&identifier)
+            && !identifiers.contains(&identifier)
+        {
+            identifiers.push(identifier);
+        }
+    }
+    uninstall_binaries(environment, plugins, &identifiers)?;
+    for toolchain_identifier in toolchain_identifiers.iter() {
+        environment.log_error(&format!(
+            "Removed toolchain {} {}.",
+            toolchain_identifier.get_binary_name(),
+            toolchain_identifier.get_version()
+        ));
+    }
+    Ok(())
+}
+
+fn handle_link_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: LinkCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let identifier = plugins::BinaryIdentifier::new(&command.binary_name, &command.version);
+    if let Some(binary) = plugins.manifest.get_binary(&identifier) {
+        if !binary.linked {
+            return err!(
+                "{} {} is already installed. Link it using a different version (ex. `--version 0.0.0-dev`).",
+                command.binary_name,
+                command.version
+            );
+        }
+    }
+
+    let path = utils::normalize_path(&environment.cwd()?.join(&command.path));
+    let file_paths = if environment.is_dir(&path) {
+        let mut file_paths = environment.get_executable_file_paths(&path)?;
+        file_paths.sort();
+        file_paths
+    } else if environment.path_exists(&path) {
+        vec![path.clone()]
+    } else {
+        return err!("Could not find {}.", path.display());
+    };
+    let mut commands: Vec<plugins::BinaryManifestItemCommand> = Vec::new();
+    for file_path in file_paths {
+        let name = match file_path.file_stem() {
+            Some(name) => CommandName::from_string(name.to_string_lossy().to_string()),
+            None => continue,
+        };
+        if !commands.iter().any(|c| c.name == name) {
+            commands.push(plugins::BinaryManifestItemCommand {
+                name,
+                path: file_path.to_string_lossy().to_string(),
+            });
+        }
+    }
+    if commands.is_empty() {
+        return err!("Could not find any executables in {}.", path.display());
+    }
+
+    // the command paths are absolute, so the binary directory is left empty
+    environment.create_dir_all(&plugins::get_writable_plugin_dir(
+        environment,
+        &command.binary_name,
+        &command.version,
+    ))?;
+    let identifier = plugins.link_binary(plugins::BinaryManifestItem {
+        name: command.binary_name.clone(),
+        version: command.version.clone(),
+        created_time: environment.get_time_secs(),
+        commands,
+        source: plugins::BinaryManifestItemSource {
+            path: path.to_string_lossy().to_string(),
+            checksum: String::new(),
+        },
+        environment: None,
+        target: None,
+        linked: true,
+        on_pre_uninstall: None,
+        pending_extraction: None,
+        provenance: None,
+    })?;
+    let command_names = plugins.manifest.get_binary(&identifier).unwrap().get_command_names();
+    let mut not_set_command_name = false;
+    for command_name in command_names.iter() {
+        if !plugins.set_global_binary_if_not_set(&identifier, command_name)? {
+            not_set_command_name = true;
+        }
+    }
+    plugins.save()?;
+
+    if not_set_command_name {
+        environment.log_error(&format!(
+            "Linked. Run `bvm use {} {}` to use it on the path as {}.",
+            command
+                .binary_name
+                .display_toggled_owner(!plugins.manifest.binary_name_has_same_owner(&command.binary_name)),
+            command.version,
+            utils::sentence_join(
+                &command_names
+                    .into_iter()
+                    .map(|c| format!("'{}'", c))
+                    .collect::<Vec<_>>()
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn handle_unlink_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: UnlinkCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let identifiers = plugins
+        .manifest
+        .get_binaries_matching_name(&command.name_selector)
+        .into_iter()
+        .filter(|binary| binary.linked)
+        .filter(|binary| command.version.as_ref().map(|v| &binary.version == v).unwrap_or(true))
+        .map(|binary| binary.get_identifier())
+        .collect::<Vec<_>>();
+    if identifiers.is_empty() {
+        return match &command.version {
+            Some(version) => err!("Could not find linked binary '{}' {}.", command.name_selector, version),
+            None => err!("Could not find any linked binaries named '{}'.", command.name_selector),
+        };
+    }
+
+    for identifier in identifiers.iter() {
+        plugins.remove_binary(identifier)?;
+    }
+    plugins.save()?;
+    for identifier in identifiers.iter() {
+        remove_binary_dir(environment, identifier)?;
+    }
+
+    Ok(())
+}
+
+/// Runs the user's pre-uninstall hooks and the command the binary's manifest file provided to run
+/// before the binary is uninstalled.
+fn run_pre_uninstall_command(
+    environment: &impl Environment,
+    binary: &plugins::BinaryManifestItem,
+) -> Result<(), ErrBox> {
+    plugins::run_user_hooks(
+        environment,
+        &plugins::HookEventDetails {
+            event: plugins::HookEvent::PreUninstall,
+            name: binary.name.clone(),
+            version: binary.version.clone(),
+            command_name: None,
+        },
+    );
+    if let Some(pre_uninstall_command) = &binary.on_pre_uninstall {
+        let plugin_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+        if let Err(err) = environment.run_shell_command(&plugin_dir, pre_uninstall_command) {
+            return err!(
+                "Error running the pre-uninstall command of {} {}. {}",
+                binary.name,
+                binary.version,
+                err.to_string()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the directory of a binary that was removed from the manifest along with any empty parent directories.
+fn remove_binary_dir(environment: &impl Environment, identifier: &plugins::BinaryIdentifier) -> Result<(), ErrBox> {
+    let plugin_dir =
+        plugins::get_writable_plugin_dir(environment, &identifier.get_binary_name(), &identifier.get_version());
+    remove_plugin_dir(environment, &plugin_dir)
+}
+
+/// Removes the directory of a binary along with its name and owner directories when they're left empty.
+fn remove_plugin_dir(environment: &impl Environment, plugin_dir: &Path) -> Result<(), ErrBox> {
+    environment.remove_dir_all(plugin_dir)?;
+
+    // delete the parent directories if empty
+    let binary_name_dir = plugin_dir.parent().unwrap();
+    if environment.is_dir_empty(&binary_name_dir)? {
+        environment.remove_dir_all(&binary_name_dir)?;
+        // now delete the owner name if empty
+        let owner_name_dir = binary_name_dir.parent().unwrap();
+        if environment.is_dir_empty(&owner_name_dir)? {
+            environment.remove_dir_all(&owner_name_dir)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the oldest versions of the installed binaries that exceed the `keepVersions` retention
+/// policy of the user config, excluding versions pinned by the current config file or used globally.
+fn remove_versions_exceeding_retention<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &mut PluginsMut<TEnvironment>,
+    installed_identifiers: &[plugins::BinaryIdentifier],
+) -> Result<(), ErrBox> {
+    let removed_identifiers = get_versions_exceeding_retention(environment, &plugins.manifest, installed_identifiers)?;
+    if removed_identifiers.is_empty() {
+        return Ok(());
+    }
+
+    for identifier in removed_identifiers.iter() {
+        if let Some(binary) = plugins.manifest.get_binary(identifier) {
+            run_pre_uninstall_command(environment, binary)?;
+        }
+        plugins.remove_binary(identifier)?;
+    }
+    plugins.save()?;
+    for identifier in removed_identifiers.iter() {
+        remove_binary_dir(environment, identifier)?;
+        environment.log_error(&format!(
+            "Removed {} {} to keep the most recent versions.",
+            identifier.get_binary_name(),
+            identifier.get_version()
+        ));
+    }
+    plugins::remove_unused_store_files(environment)?;
+
+    Ok(())
+}
+
+/// Gets the versions that exceed the `keepVersions` retention policy once the provided binaries are
+/// installed, which may not be in the manifest yet.
+fn get_versions_exceeding_retention(
+    environment: &impl Environment,
+    plugin_manifest: &PluginsManifest,
+    installed_identifiers: &[plugins::BinaryIdentifier],
+) -> Result<Vec<plugins::BinaryIdentifier>, ErrBox> {
+    let user_config = environment::read_user_config(environment)?;
+    let pinned_identifiers = get_config_file_pinned_identifiers(environment, plugin_manifest)?;
+    let mut removed_identifiers = Vec::new();
+
+    for installed_identifier in installed_identifiers.iter() {
+        let binary_name = installed_identifier.get_binary_name();
+        let keep_versions = match user_config.get_keep_versions(&binary_name.owner, &binary_name.name) {
+            Some(keep_versions) => keep_versions,
+            None => continue,
+        };
+        let mut identifiers = plugin_manifest
+            .binaries()
+            .filter(|b| b.name == binary_name)
+            .map(|b| b.get_identifier())
+            .collect::<Vec<_>>();
+        for identifier in installed_identifiers.iter() {
+            if identifier.get_binary_name() == binary_name && !identifiers.contains(identifier) {
+                identifiers.push(identifier.clone());
+            }
+        }
+        identifiers.sort_by_key(|identifier| std::cmp::Reverse(identifier.get_version()));
+        for identifier in identifiers.into_iter().skip(keep_versions) {
+            if !installed_identifiers.contains(&identifier)
+                && !plugin_manifest.is_read_only_binary(&identifier)
+                && !pinned_identifiers.contains(&identifier)
+                && !plugin_manifest.has_any_global_command(&identifier)
+                && !removed_identifiers.contains(&identifier)
+            {
+                removed_identifiers.push(identifier);
+            }
+        }
+    }
+
+    Ok(removed_identifiers)
+}
+
+/// Gets the identifiers of the installed binaries used by the config file of the current directory.
+fn get_config_file_pinned_identifiers(
+    environment: &impl Environment,
+    plugin_manifest: &PluginsManifest,
+) -> Result<Vec<plugins::BinaryIdentifier>, ErrBox> {
+    let (config_file_path, mut config_file) = match get_config_file(environment)? {
+        Some(config_file) => config_file,
+        None => return Ok(Vec::new()),
+    };
+    resolve_config_file_version_aliases_for_manifest(plugin_manifest, &mut config_file)?;
+    let mut identifiers = config_file
+        .binaries
+        .iter()
+        .filter_map(|config_binary| {
+            plugin_helpers::get_installed_binary_if_associated_config_file_binary(plugin_manifest, config_binary)
+        })
+        .map(|binary| binary.get_identifier())
+        .collect::<Vec<_>>();
+    // the versions used in the project with `bvm use` are kept as well
+    let project_state = plugins::ProjectState::load(environment, config_file_path.parent().unwrap())?;
+    identifiers.extend(project_state.get_identifiers());
+    Ok(identifiers)
+}
+
+fn handle_use_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    // use all the binaries in the current configuration file
+    let mut plugins = PluginsMut::load(environment)?;
+    let (_, mut config_file) = get_config_file_or_error(environment)?;
+    resolve_config_file_version_aliases(&mut plugins, &mut config_file)?;
+
+    if environment::read_user_config(environment)?
+        .auto_install
+        .unwrap_or(false)
+    {
+        plugins.set_require_checksums(config_file.require_checksums);
+        for entry in get_config_file_binaries(&mut plugins, &config_file)? {
+            if plugins.get_installed_binary_for_config_binary(entry)?.is_none() {
+                if let Err(err) = install_binary(environment, &mut plugins, &entry.path, entry.version.as_ref(), false)
+                {
+                    return coded_err!(
+                        get_error_code(&err),
+                        "Error installing {}: {}",
+                        &entry.path.path_or_url,
+                        err.to_string()
+                    );
+                }
+            }
+        }
+    }
+
+    // find all the binaries before using any so that nothing changes when one isn't installed
+    let mut binaries = Vec::new();
+    for entry in get_config_file_binaries(&mut plugins, &config_file)? {
+        match plugins.get_installed_binary_for_config_binary(entry)?.cloned() {
+            Some(binary) => binaries.push((entry, binary)),
+            None => {
+                return coded_err!(
+                    ErrorCode::NotInstalled,
+                    "Ensure binaries are installed before using. Run `bvm install` first then `bvm use`."
+                )
+            }
+        }
+    }
+
+    for (entry, binary) in binaries.into_iter() {
+        let identifier = binary.get_identifier();
+        for command_name in binary.get_command_names() {
+            plugins.use_global_version(command_name, plugins::GlobalBinaryLocation::Bvm(identifier.clone()))?;
+        }
+        if let Some(on_use) = &entry.on_use {
+            run_config_binary_hook(environment, &environment.cwd()?, &binary, on_use)?;
+        }
+    }
+
+    plugins.save()?;
+    Ok(())
+}
+
+fn handle_env_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: EnvCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let (_, mut config_file) = get_config_file_or_error(environment)?;
+    resolve_config_file_version_aliases(&mut plugins, &mut config_file)?;
+
+    let mut binaries = Vec::new();
+    for entry in get_config_file_binaries(&mut plugins, &config_file)? {
+        match plugins.get_installed_binary_for_config_binary(entry)? {
+            Some(binary) => binaries.push(binary.clone()),
+            None => {
+                return err!(
+                    "Ensure binaries are installed before outputting the environment. Run `bvm install` first."
+                )
+            }
+        }
+    }
+
+    let mut added_env_vars = HashMap::new();
+    for binary in binaries.iter() {
+        added_env_vars.extend(
+            plugins
+                .manifest
+                .get_binary_env_vars(environment, &binary.get_identifier()),
+        );
+    }
+    let old_path = environment.get_env_path();
+    let new_path = get_env_path_with_binaries(environment, &binaries.iter().collect::<Vec<_>>());
+    let env_changes = get_env_changes(&added_env_vars, &HashMap::new(), &old_path, &new_path);
+    output_shell_env_changes(environment, &command.shell, &env_changes);
+
+    Ok(())
+}
+
+fn handle_cache_key_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    let (config_file_path, config_file) = get_config_file_or_error(environment)?;
+    let platform = plugins::get_platform_keys()[0];
+
+    // the binaries are sorted so reordering them in the configuration file doesn't change the key
+    let mut binary_lines = get_cache_key_binary_lines(&config_file);
+    let config_file_dir = config_file_path.parent().unwrap();
+    for workspace in config_file.workspaces.iter() {
+        let member_dir = config_file_dir.join(workspace);
+        if let Some(member_config_file_path) = configuration::get_config_file_in_dir(environment, &member_dir) {
+            let member_config_file = read_config_file_at_path(environment, &member_config_file_path)?;
+            binary_lines.extend(get_cache_key_binary_lines(&member_config_file));
+        }
+    }
+    binary_lines.sort();
+    binary_lines.dedup();
+
+    let mut key_text = format!("bvm {}\n{}\n", env!("CARGO_PKG_VERSION"), platform);
+    for line in binary_lines {
+        key_text.push_str(&line);
+        key_text.push('\n');
+    }
+    environment.log(&format!(
+        "bvm-{}-{}",
+        platform,
+        get_sha256_checksum(key_text.as_bytes())
+    ));
+
+    Ok(())
+}
+
+/// Gets a line for each binary of the configuration file with everything that determines what's installed for it.
+fn get_cache_key_binary_lines(config_file: &configuration::ConfigFile) -> Vec<String> {
+    config_file
+        .binaries
+        .iter()
+        .map(|binary| {
+            format!(
+                "{} {} {} {}",
+                binary.path.path_or_url,
+                binary.path.checksum.as_deref().unwrap_or("-"),
+                binary
+                    .version
+                    .as_ref()
+                    .map(|v| v.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                binary.version_alias.as_deref().unwrap_or("-"),
+            )
+        })
+        .collect()
+}
+
+const INSTALL_SCRIPT_URL: &str = "https://bvm.land/install.sh";
+/// Directory bvm and its data are installed to in exported Dockerfiles.
+const DOCKERFILE_BVM_DIR: &str = "/usr/local/bvm";
+
+fn handle_export_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: ExportSubCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let (_, mut config_file) = get_config_file_or_error(environment)?;
+    resolve_config_file_version_aliases(&mut plugins, &mut config_file)?;
+
+    let mut install_args = Vec::new();
+    for entry in get_config_file_binaries(&mut plugins, &config_file)? {
+        let install_arg = match plugins.get_installed_binary_for_config_binary(entry)? {
+            Some(binary) => get_pinned_install_arg(binary)?,
+            None => {
+                return coded_err!(
+                    ErrorCode::NotInstalled,
+                    "Ensure binaries are installed before exporting. Run `bvm install` first."
+                )
+            }
+        };
+        if !install_args.contains(&install_arg) {
+            install_args.push(install_arg);
+        }
+    }
+    let install_commands = install_args
+        .iter()
+        .map(|arg| format!("bvm-bin install --use {}", quote_sh_value(arg)))
+        .collect::<Vec<_>>();
+
+    let mut lines = Vec::new();
+    match command {
+        ExportSubCommand::Dockerfile => {
+            lines.push("# Generated by `bvm export dockerfile`. Requires curl and unzip.".to_string());
+            lines.push(format!(
+                "ENV BVM_INSTALL_DIR={0} BVM_DATA_DIR={0} BVM_CACHE_DIR={0}",
+                DOCKERFILE_BVM_DIR
+            ));
+            lines.push(format!("ENV PATH={0}/shims:{0}/bin:$PATH", DOCKERFILE_BVM_DIR));
+            lines.push(format!("RUN curl -fsSL {} | sh", INSTALL_SCRIPT_URL));
+            if !install_commands.is_empty() {
+                lines.push(format!("RUN {}", install_commands.join(" \\\n  && ")));
+            }
+        }
+        ExportSubCommand::ShellScript => {
+            lines.push("#!/bin/sh".to_string());
+            lines.push("# Generated by `bvm export shell-script`. Requires curl and unzip.".to_string());
+            lines.push("set -e".to_string());
+            lines.push(String::new());
+            lines.push("BVM_INSTALL_DIR=\"${BVM_INSTALL_DIR:-$HOME/.bvm}\"".to_string());
+            lines.push("export BVM_INSTALL_DIR".to_string());
+            lines.push("if [ ! -x \"$BVM_INSTALL_DIR/bin/bvm-bin\" ]; then".to_string());
+            lines.push(format!("  curl -fsSL {} | sh", INSTALL_SCRIPT_URL));
+            lines.push("fi".to_string());
+            lines.push("PATH=\"$BVM_INSTALL_DIR/bin:$PATH\"".to_string());
+            lines.extend(install_commands);
+        }
+    }
+    environment.log(&lines.join("\n"));
+
+    Ok(())
+}
+
+const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+/// The installed binaries and the versions used globally output by `bvm snapshot export`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    schema_version: u32,
+    binaries: Vec<SnapshotBinary>,
+    /// The location of each command, which is either `path` or `<owner>/<name>@<version>`.
+    #[serde(default)]
+    commands: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotBinary {
+    name: types::BinaryName,
+    version: types::Version,
+    /// Argument to `bvm install` that installs the exact version.
+    url: String,
+}
+
+fn handle_snapshot_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: SnapshotSubCommand,
+) -> Result<(), ErrBox> {
+    match command {
+        SnapshotSubCommand::Export => handle_snapshot_export_command(environment),
+        SnapshotSubCommand::Import(command) => handle_snapshot_import_command(environment, command),
+    }
+}
+
+fn handle_snapshot_export_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    let mut binaries = plugin_manifest.binaries().collect::<Vec<_>>();
+    binaries.sort();
+
+    let mut snapshot = Snapshot {
+        schema_version: SNAPSHOT_SCHEMA_VERSION,
+        binaries: Vec::new(),
+        commands: BTreeMap::new(),
+    };
+    let mut exported_identifiers = Vec::new();
+    for binary in binaries.iter() {
+        match get_pinned_install_arg(binary) {
+            Ok(url) => {
+                snapshot.binaries.push(SnapshotBinary {
+                    name: binary.name.clone(),
+                    version: binary.version.clone(),
+                    url,
+                });
+                exported_identifiers.push(binary.get_identifier());
+            }
+            // the snapshot goes to stdout, so mention why a binary is missing on stderr
+            Err(err) => environment.log_error(&format!("Skipped {} {}. {}", binary.name, binary.version, err)),
+        }
+    }
+    for binary in binaries.iter() {
+        for command_name in binary.get_command_names() {
+            let location = match plugin_manifest.get_global_binary_location(&command_name) {
+                Some(plugins::GlobalBinaryLocation::Bvm(identifier)) => {
+                    if !exported_identifiers.contains(&identifier) {
+                        continue;
+                    }
+                    format!("{}@{}", identifier.get_binary_name(), identifier.get_version())
+                }
+                Some(plugins::GlobalBinaryLocation::Path) => "path".to_string(),
+                None => continue,
+            };
+            snapshot.commands.insert(command_name.into_string(), location);
+        }
+    }
+
+    environment.log(&serde_json::to_string_pretty(&snapshot)?);
+    Ok(())
+}
+
+fn handle_snapshot_import_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: SnapshotImportCommand,
+) -> Result<(), ErrBox> {
+    let file_path = environment.cwd()?.join(&command.file_path);
+    let file_text = environment.read_file_text(&file_path)?;
+    let snapshot_value = match serde_json::from_str::<serde_json::Value>(&file_text) {
+        Ok(value) => value,
+        Err(err) => return err!("Error deserializing {}. {}", command.file_path, err.to_string()),
+    };
+    if let serde_json::Value::Object(map) = &snapshot_value {
+        utils::get_supported_schema_version(map, "snapshot file", SNAPSHOT_SCHEMA_VERSION)?;
+    }
+    let snapshot = match serde_json::from_value::<Snapshot>(snapshot_value) {
+        Ok(snapshot) => snapshot,
+        Err(err) => return err!("Error deserializing {}. {}", command.file_path, err.to_string()),
+    };
+
+    let mut plugins = PluginsMut::load(environment)?;
+    for binary in snapshot.binaries.iter() {
+        let checksum_url = parse_checksum_path_or_url(&binary.url);
+        if let Err(err) = install_binary(environment, &mut plugins, &checksum_url, None, false) {
+            return coded_err!(
+                get_error_code(&err),
+                "Error installing {} {}. {}",
+                binary.name,
+                binary.version,
+                err.to_string()
+            );
+        }
+    }
+
+    for (command_name, location_text) in snapshot.commands.into_iter() {
+        let location = if location_text.to_lowercase() == "path" {
+            plugins::GlobalBinaryLocation::Path
+        } else {
+            let identifier = match location_text.rfind('@') {
+                Some(index) if location_text[..index].contains('/') => {
+                    let name_parts = location_text[..index].splitn(2, '/').collect::<Vec<_>>();
+                    let name = types::BinaryName::new(name_parts[0].to_string(), name_parts[1].to_string());
+                    plugins::BinaryIdentifier::new(&name, &types::Version::parse(&location_text[index + 1..])?)
+                }
+                _ => {
+                    return err!(
+                    "Expected the location of command '{}' to be 'path' or '<owner>/<name>@<version>', but found '{}'.",
+                    command_name,
+                    location_text
+                )
+                }
+            };
+            if !plugins.manifest.has_binary(&identifier) {
+                environment.log_error(&format!(
+                    "Skipped using {} for '{}' because it's not installed.",
+                    location_text, command_name
+                ));
+                continue;
+            }
+            identifier.into()
+        };
+        plugins.use_global_version(types::CommandName::from_string(command_name), location)?;
+    }
+    plugins.save()?;
+
+    Ok(())
+}
+
+/// Gets the argument to `bvm install` that installs the exact version of the binary that's installed.
+fn get_pinned_install_arg(binary: &plugins::BinaryManifestItem) -> Result<String, ErrBox> {
+    let path = &binary.source.path;
+    if binary.linked || path.starts_with("file:") {
+        return err!(
+            "Cannot export {} {} because it was installed from a local path.",
+            binary.name,
+            binary.version
+        );
+    }
+    if plugins::is_npm_package_url(path) || plugins::is_cargo_crate_url(path) {
+        // the latest version is installed when the package doesn't specify one (ex. `npm:typescript`)
+        let package = &path[path.find(':').unwrap() + 1..];
+        if package.trim_start_matches('@').contains('@') {
+            Ok(path.clone())
+        } else {
+            Ok(format!("{}@{}", path, binary.version))
+        }
+    } else if plugins::is_github_release_url(path) {
+        if path.split("?asset=").next().unwrap().contains('@') {
+            Ok(path.clone())
+        } else {
+            err!(
+                "Cannot export {} {} because it was installed from the latest GitHub release. Specify the release tag in the configuration file instead (ex. `{}@<tag>`).",
+                binary.name,
+                binary.version,
+                path
+            )
+        }
+    } else if binary.source.checksum.is_empty() {
+        Ok(path.clone())
+    } else {
+        Ok(format!("{}@{}", path, binary.source.checksum))
+    }
+}
+
+fn quote_sh_value(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn quote_cmd_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+fn format_shell_set_env_var(shell: &EnvShell, key: &str, value: &str) -> String {
+    match shell {
+        EnvShell::Bash => format!("export {}={}", key, quote_sh_value(value)),
+        EnvShell::Fish => {
+            if key == "PATH" {
+                let paths = value
+                    .split(SYS_PATH_DELIMITER)
+                    .map(escape_fish_value)
+                    .collect::<Vec<_>>();
+                format!("set -gx PATH {}", paths.join(" "))
+            } else {
+                format!("set -gx {} {}", key, escape_fish_value(value))
+            }
+        }
+        EnvShell::Pwsh => format!("$env:{} = '{}'", key, value.replace('\'', "''")),
+        EnvShell::Cmd => format!("SET \"{}={}\"", key, value),
+        EnvShell::Nu => unreachable!("nushell environment changes are output as json"),
+    }
+}
+
+fn format_shell_remove_env_var(shell: &EnvShell, key: &str) -> String {
+    match shell {
+        EnvShell::Bash => format!("unset {}", key),
+        EnvShell::Fish => format!("set -e {}", key),
+        EnvShell::Pwsh => format!("Remove-Item Env:{} -ErrorAction SilentlyContinue", key),
+        EnvShell::Cmd => format!("SET \"{}=\"", key),
+        EnvShell::Nu => unreachable!("nushell environment changes are output as json"),
+    }
+}
+
+/// Formats the changes as a JSON record with the path as a list and removed variables as null.
+fn format_nu_env_changes(env_changes: &[(String, Option<String>)]) -> String {
+    let mut record = serde_json::Map::new();
+    for (key, value) in env_changes.iter() {
+        let value = match value {
+            Some(value) if key == "PATH" => serde_json::Value::Array(
+                value
+                    .split(SYS_PATH_DELIMITER)
+                    .filter(|path| !path.is_empty())
+                    .map(|path| serde_json::Value::String(path.to_string()))
+                    .collect(),
+            ),
+            Some(value) => serde_json::Value::String(value.clone()),
+            None => serde_json::Value::Null,
+        };
+        record.insert(key.clone(), value);
+    }
+    serde_json::Value::Object(record).to_string()
+}
+
+fn escape_fish_value(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Asks which of the installed versions to use when no version was provided. Resolves to `None` when
+/// prompting isn't possible, in which case the latest version matching the selector is used.
+fn select_use_version(
+    environment: &impl Environment,
+    plugin_manifest: &PluginsManifest,
+    name_selector: &NameSelector,
+    version_selector: &PathOrVersionSelector,
+) -> Result<Option<PathOrVersionSelector>, ErrBox> {
+    let mut binaries = plugin_manifest.get_binaries_matching_name(name_selector);
+    // binaries of different owners are picked from with the owner
+    if binaries.len() < 2 || binaries.iter().any(|b| b.name != binaries[0].name) {
+        return Ok(None);
+    }
+    binaries.sort_by(|a, b| b.version.cmp(&a.version));
+    let latest_version = match version_selector {
+        PathOrVersionSelector::Version(version_selector) => binaries
+            .iter()
+            .map(|b| &b.version)
+            .find(|version| version_selector.matches(version)),
+        PathOrVersionSelector::Path => None,
+    };
+    let items = binaries
+        .iter()
+        .map(|b| {
+            let mut item = if Some(&b.version) == latest_version {
+                format!("{} (latest)", utils::green(&b.version.to_string()))
+            } else {
+                b.version.to_string()
+            };
+            if plugin_manifest.has_any_global_command(&b.get_identifier()) {
+                item.push_str(" (current)");
+            }
+            item
+        })
+        .collect::<Vec<_>>();
+    let message = format!("Which version of {} should be used?", binaries[0].name);
+    Ok(environment
+        .select(&message, &items)?
+        .map(|index| PathOrVersionSelector::Version(binaries[index].version.to_selector())))
+}
+
+fn handle_use_binary_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    mut use_command: UseBinaryCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    use_command.name_selector =
+        plugin_helpers::resolve_name_selector_owner(environment, &plugins.manifest, use_command.name_selector)?;
+    let version_selector = match use_command.version {
+        UseVersion::Selector(version_selector) => version_selector,
+        UseVersion::Unspecified(version_selector) => select_use_version(
+            environment,
+            &plugins.manifest,
+            &use_command.name_selector,
+            &version_selector,
+        )?
+        .unwrap_or(version_selector),
+        UseVersion::Alias(alias) => PathOrVersionSelector::Version(
+            get_alias_binary_name_and_version(&plugins.manifest, &use_command.name_selector, &alias)?
+                .1
+                .to_selector(),
+        ),
+    };
+    let mut command_names = plugin_helpers::get_command_names_for_name_and_path_or_version_selector(
+        &plugins.manifest,
+        &use_command.name_selector,
+        &version_selector,
+    )?;
+    if let Some(command_name) = use_command.command_name {
+        if !command_names.contains(&command_name) {
+            return err!(
+                "Could not find command '{}' for binary '{}'. Available commands: {}",
+                command_name,
+                use_command.name_selector,
+                utils::sentence_join(&command_names.iter().map(|c| format!("'{}'", c)).collect::<Vec<_>>()),
+            );
+        }
+        command_names = vec![command_name];
+    }
+
+    let location = plugin_helpers::get_global_binary_location_for_name_and_path_or_version_selector(
+        &plugins.manifest,
+        &use_command.name_selector,
+        &version_selector,
+    )?;
+
+    if use_command.suffix {
+        let identifier = match location {
+            plugins::GlobalBinaryLocation::Bvm(identifier) => identifier,
+            plugins::GlobalBinaryLocation::Path => return err!("The `--suffix` flag cannot be used with `path`."),
+        };
+        let binary_name = identifier.get_binary_name();
+        let version = identifier.get_version();
+        for command_name in command_names {
+            let suffixed_name = CommandName::from_string(format!("{}{}", command_name, version.get_major_minor()));
+            plugins.add_suffixed_command(suffixed_name.clone(), identifier.clone(), command_name)?;
+            environment.log(&format!(
+                "Added command '{}' for {} {}.",
+                suffixed_name, binary_name, version
+            ));
+        }
+        plugins.save()?;
+        return Ok(());
+    }
+
+    for command_name in command_names.iter() {
+        match &version_selector {
+            PathOrVersionSelector::Path => {
+                if utils::get_path_executable_path(environment, &command_name).is_none() {
+                    // todo: maybe this should be a warning instead?
+                    return err!(
+                        "Could not find any installed binaries on the path that matched '{}'.",
+                        command_name
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // within a project, only the project's commands are changed
+    if !use_command.global {
+        if let Some(config_file_path) = configuration::find_config_file(environment)? {
+            let project_dir = config_file_path.parent().unwrap();
+            let mut project_state = plugins::ProjectState::load(environment, project_dir)?;
+            for command_name in command_names.iter() {
+                project_state.set_location(command_name.clone(), location.clone());
+            }
+            project_state.save(environment)?;
+            environment.log_error(&format!(
+                "Updated the {} of {} used in {}. Provide `--global` to update the globally used {0} instead.",
+                if command_names.len() == 1 {
+                    "version"
+                } else {
+                    "versions"
+                },
+                utils::sentence_join(&command_names.iter().map(|n| format!("'{}'", n)).collect::<Vec<_>>()),
+                project_dir.display()
+            ));
+            return Ok(());
+        }
+    }
+
+    for command_name in command_names.iter() {
+        plugins.use_global_version(command_name.clone(), location.clone())?;
+    }
+
+    display_commands_in_config_file_warning_if_necessary(environment, &plugins.manifest, &command_names);
+
+    plugins.save()?;
+
+    Ok(())
+}
+
+fn get_is_command_in_config_file(
+    environment: &impl Environment,
+    plugin_manifest: &PluginsManifest,
+    command_name: &CommandName,
+) -> bool {
+    let result = get_executable_path_from_config_file(environment, &plugin_manifest, &command_name);
+    match result {
+        Ok(result) => result.map(|info| info.binary_info).flatten().is_some(),
+        Err(_) => false,
+    }
+}
+
+fn display_commands_in_config_file_warning_if_necessary(
+    environment: &impl Environment,
+    plugin_manifest: &PluginsManifest,
+    command_names: &Vec<CommandName>,
+) {
+    let command_names = command_names
+        .iter()
+        .filter(|n| get_is_command_in_config_file(environment, &plugin_manifest, &n))
+        .map(|n| n.clone())
+        .collect::<Vec<_>>();
+
+    if command_names.is_empty() {
+        return;
+    }
+
+    let message = format!(
+        concat!(
+            "Updated globally used {0} of {2}, but local {0} {1} using version specified ",
+            "in the current working directory's config file. If you wish to change the local {0}, ",
+            "then update your configuration file (check the cwd and ancestor directories for a ",
+            "bvm configuration file)."
+        ),
+        if command_names.len() == 1 {
+            "version"
+        } else {
+            "versions"
+        },
+        if command_names.len() == 1 { "remains" } else { "remain" },
+        utils::sentence_join(&command_names.iter().map(|n| format!("'{}'", n)).collect::<Vec<_>>()),
+    );
+    environment.log_error(&message);
+}
+
+fn handle_list_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: ListCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    log_manifest_problems_warning(environment, &plugin_manifest);
+    let mut binaries = plugin_manifest
+        .binaries()
+        .filter(|b| match &command.name_selector {
+            Some(name_selector) => name_selector.is_match(&b.name),
+            None => true,
+        })
+        .filter(|b| match &command.owner {
+            Some(owner) => &b.name.owner == owner,
+            None => true,
+        })
+        .map(|b| (b, None))
+        .collect::<Vec<_>>();
+
+    if command.outdated {
+        binaries = get_outdated_binaries(environment, binaries.into_iter().map(|(b, _)| b).collect())?
+            .into_iter()
+            .map(|(b, latest_version)| (b, Some(latest_version)))
+            .collect();
+    }
+
+    binaries.sort_by_key(|(binary, _)| *binary);
+    match command.sort {
+        ListSort::Name => {}
+        ListSort::Version => binaries.sort_by(|(a, _), (b, _)| b.version.cmp(&a.version)),
+        ListSort::Size => {
+            let mut sizes = HashMap::new();
+            for (binary, _) in binaries.iter() {
+                let plugin_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+                sizes.insert(
+                    binary.get_identifier(),
+                    environment.get_dir_size(&plugin_dir).unwrap_or(0),
+                );
+            }
+            binaries.sort_by(|(a, _), (b, _)| sizes[&b.get_identifier()].cmp(&sizes[&a.get_identifier()]));
+        }
+        ListSort::LastUsed => binaries.sort_by_cached_key(|(binary, _)| {
+            std::cmp::Reverse(plugins::get_binary_last_used_time(environment, binary))
+        }),
+    }
+
+    if binaries.is_empty() {
+        return Ok(());
+    }
+    if command.paths {
+        let paths = binaries
+            .into_iter()
+            .map(|(b, _)| {
+                plugins::get_plugin_dir(environment, &b.name, &b.version)
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        environment.log(&paths.join("\n"));
+    } else {
+        let lines = binaries
+            .into_iter()
+            .map(|(b, latest_version)| {
+                let mut line = format!(
+                    "{} {}",
+                    utils::bold(&b.name.to_string()),
+                    utils::green(&b.version.to_string())
+                );
+                if let Some(latest_version) = latest_version {
+                    line.push_str(&format!(" ({} available)", utils::yellow(&latest_version.to_string())));
+                }
+                if plugin_manifest.has_any_global_command(&b.get_identifier()) {
+                    line.push_str(&format!(" {}", utils::green("(global)")));
+                }
+                if plugin_manifest.is_binary_disabled(&b.name) {
+                    line.push_str(&format!(" {}", utils::yellow("(disabled)")));
+                }
+                line
+            })
+            .collect::<Vec<_>>();
+
+        environment.log(&lines.join("\n"));
+    }
+    Ok(())
+}
+
+/// Gets the binaries that are older than the latest version in their registries along with that version.
+fn get_outdated_binaries<'a, TEnvironment: Environment>(
+    environment: &TEnvironment,
+    binaries: Vec<&'a plugins::BinaryManifestItem>,
+) -> Result<Vec<(&'a plugins::BinaryManifestItem, Version)>, ErrBox> {
+    let registry = registry::Registry::load(environment);
+    let owner_registries = get_owner_registries(environment)?;
+    let mut registry_files = registry::RegistryFileCache::new();
+    let mut latest_versions: HashMap<BinaryName, Option<Version>> = HashMap::new();
+    let mut outdated_binaries = Vec::new();
+
+    for binary in binaries.into_iter() {
+        if !latest_versions.contains_key(&binary.name) {
+            let name_selector = binary.name.to_selector();
+            let urls = get_registry_url_results(environment, &registry, &owner_registries, &name_selector)?
+                .into_iter()
+                .map(|r| r.url)
+                .collect::<Vec<_>>();
+            let latest_version = find_latest_version_info(environment, &mut registry_files, &urls, &binary.name)?
+                .map(|version_info| version_info.version);
+            latest_versions.insert(binary.name.clone(), latest_version);
+        }
+        if let Some(latest_version) = &latest_versions[&binary.name] {
+            if latest_version.gt(&binary.version) {
+                outdated_binaries.push((binary, latest_version.clone()));
+            }
+        }
+    }
+
+    Ok(outdated_binaries)
+}
+
+fn handle_list_remote_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: ListRemoteCommand,
+) -> Result<(), ErrBox> {
+    let (binary_name, urls) = get_registry_binary_name_and_urls(environment, &command.name_selector)?;
+    let plugin_manifest = PluginsManifest::load(environment);
+
+    let mut versions = Vec::new();
+    for url in urls.iter() {
+        let registry_file = registry::download_registry_file(environment, url)?;
+        if let Some(registry_binary) = registry_file.take_binary_with_name(&binary_name) {
+            for version_info in registry_binary.versions {
+                let is_match = match &command.version_selector {
+                    Some(version_selector) => version_selector.matches(&version_info.version),
+                    None => true,
+                };
+                if is_match && !versions.contains(&version_info.version) {
+                    versions.push(version_info.version);
+                }
+            }
+        }
+    }
+
+    if versions.is_empty() {
+        return match &command.version_selector {
+            Some(version_selector) => coded_err!(
+                ErrorCode::VersionNotFound,
+                "Could not find binary '{}' matching '{}' in any registry.",
+                command.name_selector,
+                version_selector
+            ),
+            None => coded_err!(
+                ErrorCode::BinaryNotFound,
+                "Could not find binary '{}' in any registry.",
+                command.name_selector
+            ),
+        };
+    }
+
+    versions.sort();
+    let lines = versions
+        .into_iter()
+        .map(|version| {
+            let identifier = plugins::BinaryIdentifier::new(&binary_name, &version);
+            if plugin_manifest.get_binary(&identifier).is_some() {
+                format!("{} {}", version, utils::green("(installed)"))
+            } else {
+                version.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+    environment.log(&lines.join("\n"));
+
+    Ok(())
+}
+
+fn handle_changelog_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: ChangelogCommand,
+) -> Result<(), ErrBox> {
+    let (binary_name, urls) = get_registry_binary_name_and_urls(environment, &command.name_selector)?;
+    let mut release_notes_url: Option<String> = None;
+    let mut version_infos: Vec<registry::RegistryVersionInfo> = Vec::new();
+    for url in urls.iter() {
+        let registry_file = registry::download_registry_file(environment, url)?;
+        if let Some(registry_binary) = registry_file.take_binary_with_name(&binary_name) {
+            if release_notes_url.is_none() {
+                release_notes_url = registry_binary.release_notes_url.clone();
+            }
+            for version_info in registry_binary.versions {
+                if !version_infos.iter().any(|v| v.version == version_info.version) {
+                    version_infos.push(version_info);
+                }
+            }
+        }
+    }
+    if version_infos.is_empty() {
+        return coded_err!(
+            ErrorCode::BinaryNotFound,
+            "Could not find binary '{}' in any registry.",
+            command.name_selector
+        );
+    }
+    let release_notes_url = match release_notes_url {
+        Some(release_notes_url) => release_notes_url,
+        None => return err!("The registry of {} doesn't provide release notes.", binary_name),
+    };
+    version_infos.sort_by(|a, b| a.version.cmp(&b.version));
+
+    let version_infos = match &command.version_selector {
+        Some(version_selector) => match version_infos
+            .iter()
+            .rev()
+            .find(|v| version_selector.matches(&v.version))
+        {
+            Some(version_info) => vec![version_info],
+            None => {
+                return coded_err!(
+                    ErrorCode::VersionNotFound,
+                    "Could not find binary '{}' matching '{}' in any registry.",
+                    command.name_selector,
+                    version_selector
+                )
+            }
+        },
+        None => {
+            let plugin_manifest = PluginsManifest::load(environment);
+            let installed_version = plugin_manifest
+                .get_latest_binary_with_name(&binary_name)
+                .map(|binary| binary.version.clone());
+            let latest_version_info = version_infos
+                .iter()
+                .rev()
+                .find(|v| !v.version.is_prerelease())
+                .unwrap_or_else(|| version_infos.last().unwrap());
+            match installed_version {
+                Some(installed_version) if installed_version >= latest_version_info.version => {
+                    environment.log_error(&format!("{} {} is the latest version.", binary_name, installed_version));
+                    return Ok(());
+                }
+                // output the versions that would be upgraded through
+                Some(installed_version) => version_infos
+                    .iter()
+                    .filter(|v| {
+                        v.version > installed_version
+                            && v.version <= latest_version_info.version
+                            && !v.version.is_prerelease()
+                    })
+                    .collect(),
+                None => vec![latest_version_info],
+            }
+        }
+    };
+
+    if command.open {
+        let url = release_notes_url.replace("{version}", &version_infos.last().unwrap().version.to_string());
+        return environment.run_shell_command(&environment.cwd()?, &get_open_url_command(&url));
+    }
+    let lines = version_infos
+        .into_iter()
+        .map(|version_info| {
+            let url = release_notes_url.replace("{version}", &version_info.version.to_string());
+            match &version_info.release_date {
+                Some(release_date) => format!("{} ({}) - {}", version_info.version, release_date, url),
+                None => format!("{} - {}", version_info.version, url),
+            }
+        })
+        .collect::<Vec<_>>();
+    environment.log(&lines.join("\n"));
+
+    Ok(())
+}
+
+/// Gets the shell command that opens the url in the default browser.
+fn get_open_url_command(url: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("start \"\" \"{}\"", url)
+    } else if cfg!(target_os = "macos") {
+        format!("open \"{}\"", url)
+    } else {
+        format!("xdg-open \"{}\"", url)
+    }
+}
+
+fn handle_info_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: InfoCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    let registry = registry::Registry::load(environment);
+    let mut binaries = plugin_manifest.get_binaries_matching_name(&command.name_selector);
+    let url_results = registry.get_urls(&command.name_selector);
+
+    let mut binary_names = binaries
+        .iter()
+        .map(|b| b.name.clone())
+        .chain(
+            url_results
+                .iter()
+                .map(|r| BinaryName::new(r.owner.clone(), command.name_selector.name.clone())),
+        )
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    if binary_names.is_empty() {
+        return coded_err!(
+            ErrorCode::BinaryNotFound,
+            "Could not find binary '{}' installed or in any registry.",
+            command.name_selector
+        );
+    }
+    if binary_names.len() > 1 {
+        binary_names.sort();
+        return err!(
+            "There were multiple binaries with the name '{}'. Please include the owner in the name:\n  {}",
+            command.name_selector.name,
+            binary_names
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join("\n  ")
+        );
+    }
+    let binary_name = binary_names.remove(0);
+    let registry_urls = url_results
+        .into_iter()
+        .filter(|r| r.owner == binary_name.owner)
+        .map(|r| r.url)
+        .collect::<Vec<_>>();
+
+    let mut description = None;
+    for url in registry_urls.iter() {
+        match registry::download_registry_file(environment, url) {
+            Ok(registry_file) => {
+                if let Some(registry_binary) = registry_file.take_binary_with_name(&binary_name) {
+                    description = Some(registry_binary.description);
+                    break;
+                }
+            }
+            Err(err) => environment.log_error(&format!("Error downloading registry {}: {}", url, err)),
+        }
+    }
+
+    binaries.sort();
+    let mut binary_dirs = Vec::with_capacity(binaries.len());
+    for binary in binaries.iter() {
+        let plugin_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+        let size = environment.get_dir_size(&plugin_dir)?;
+        binary_dirs.push((plugin_dir, size));
+    }
+
+    let mut command_names = Vec::new();
+    for binary in binaries.iter() {
+        for command_name in binary.get_command_names() {
+            if !command_names.contains(&command_name) {
+                command_names.push(command_name);
+            }
+        }
+    }
+    command_names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    let commands = command_names
+        .into_iter()
+        .map(|command_name| {
+            let global_text = match plugin_manifest.get_global_binary_location(&command_name) {
+                Some(plugins::GlobalBinaryLocation::Bvm(identifier)) => {
+                    if identifier.get_binary_name() == binary_name {
+                        identifier.get_version().to_string()
+                    } else {
+                        format!("{} {}", identifier.get_binary_name(), identifier.get_version())
+                    }
+                }
+                Some(plugins::GlobalBinaryLocation::Path) => "path".to_string(),
+                None => "none".to_string(),
+            };
+            (command_name, global_text)
+        })
+        .collect::<Vec<_>>();
+
+    if command.json {
+        let versions = binaries
+            .iter()
+            .zip(binary_dirs.iter())
+            .map(|(binary, (plugin_dir, size))| {
+                let provenance = binary.provenance.as_ref().map(|provenance| {
+                    serde_json::json!({
+                        "url": provenance.url,
+                        "checksum": provenance.checksum,
+                        "registry": provenance.registry,
+                        "installedTime": utils::format_utc_time(binary.created_time),
+                        "bvmVersion": provenance.bvm_version,
+                    })
+                });
+                serde_json::json!({
+                    "version": binary.version.to_string(),
+                    "path": plugin_dir.to_string_lossy(),
+                    "size": size,
+                    "target": binary.target,
+                    "binaryManifest": binary.source.path,
+                    "provenance": provenance,
+                })
+            })
+            .collect::<Vec<_>>();
+        let commands = commands
+            .iter()
+            .map(|(command_name, global_text)| {
+                serde_json::json!({
+                    "name": command_name.as_str(),
+                    "global": global_text,
+                })
+            })
+            .collect::<Vec<_>>();
+        let value = serde_json::json!({
+            "name": binary_name.to_string(),
+            "description": description,
+            "registries": registry_urls,
+            "versions": versions,
+            "commands": commands,
+        });
+        environment.log(&serde_json::to_string_pretty(&value).unwrap());
+        return Ok(());
+    }
+
+    let mut lines = vec![format!("Name: {}", binary_name)];
+    if let Some(description) = description {
+        lines.push(format!("Description: {}", description));
+    }
+
+    if registry_urls.is_empty() {
+        lines.push("Registries: None".to_string());
+    } else {
+        lines.push("Registries:".to_string());
+        for url in registry_urls.iter() {
+            lines.push(format!("  {}", url));
+        }
+    }
+
+    if binaries.is_empty() {
+        lines.push("Installed versions: None".to_string());
+    } else {
+        lines.push("Installed versions:".to_string());
+        for (binary, (plugin_dir, size)) in binaries.iter().zip(binary_dirs.iter()) {
+            let size_text = utils::format_bytes(*size);
+            lines.push(match &binary.target {
+                Some(target) => format!(
+                    "  {} - {} ({}, {})",
+                    binary.version,
+                    plugin_dir.display(),
+                    size_text,
+                    target
+                ),
+                None => format!("  {} - {} ({})", binary.version, plugin_dir.display(), size_text),
+            });
+            if let Some(provenance) = &binary.provenance {
+                lines.push(format!("    Source: {}", provenance.url));
+                if !provenance.checksum.is_empty() {
+                    lines.push(format!("    Checksum: {}", provenance.checksum));
+                }
+                if let Some(registry_url) = &provenance.registry {
+                    lines.push(format!("    Registry: {}", registry_url));
+                }
+                lines.push(format!(
+                    "    Installed: {} with bvm {}",
+                    utils::format_utc_time(binary.created_time),
+                    provenance.bvm_version
+                ));
+            }
+        }
+
+        lines.push("Commands:".to_string());
+        for (command_name, global_text) in commands {
+            lines.push(format!("  {} - global: {}", command_name, global_text));
+        }
+    }
+
+    environment.log(&lines.join("\n"));
+
+    Ok(())
+}
+
+fn handle_which_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: WhichCommand,
+) -> Result<(), ErrBox> {
+    let resolved_command = resolve_command_executable(environment, &command.command_name)?;
+    let executable_path = resolved_command.executable_path;
+    if command.quiet {
+        environment.log(&executable_path.to_string_lossy());
+        return Ok(());
+    }
+    let plugin_manifest = PluginsManifest::load(environment);
+    let binary = plugin_manifest.binaries().find(|binary| {
+        let plugin_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+        // the commands of linked binaries have absolute paths outside the directory
+        executable_path.starts_with(&plugin_dir)
+            || binary
+                .commands
+                .iter()
+                .any(|command| plugin_dir.join(&command.path) == executable_path)
+    });
+    environment.log(&match binary {
+        Some(binary) => format!(
+            "{} ({} {})",
+            executable_path.display(),
+            utils::bold(&binary.name.to_string()),
+            utils::green(&binary.version.to_string())
+        ),
+        None => format!("{} (on the path)", executable_path.display()),
+    });
+    Ok(())
+}
+
+fn handle_resolve_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: ResolveCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    let name_selector =
+        plugin_helpers::resolve_name_selector_owner(environment, &plugin_manifest, command.name_selector)?;
+    let binary = get_binary_with_name_and_optional_version(
+        environment,
+        &plugin_manifest,
+        &name_selector,
+        command.version_selector.as_ref(),
+    )?;
+
+    let command_names = binary.get_command_names();
+    let command_name = match command.command_name {
+        Some(command_name) => {
+            if !command_names.contains(&command_name) {
+                return err!(
+                    "{} {} does not have a '{}' command. Commands: {}",
+                    binary.name,
+                    binary.version,
+                    command_name,
+                    command_names.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", ")
+                );
+            }
+            command_name
+        }
+        None => match command_names
+            .iter()
+            .find(|c| command_names.len() == 1 || c.as_str() == binary.name.name)
+        {
+            Some(command_name) => command_name.clone(),
+            None => {
+                return err!(
+                    "{} {} has multiple commands. Provide one with --command: {}",
+                    binary.name,
+                    binary.version,
+                    command_names.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", ")
+                )
+            }
+        },
+    };
+    let executable_path = plugin_helpers::get_exec_binary_command_exe_path(environment, binary, &command_name).unwrap();
+    ensure_binary_ready(environment, binary)?;
+    environment.log(&executable_path.to_string_lossy());
+
+    Ok(())
+}
+
+/// Gets the installed binary matching the version or, when there's no version, the one used by the
+/// current directory's configuration file, then the one used globally, then the latest version.
+fn get_binary_with_name_and_optional_version<'a>(
+    environment: &impl Environment,
+    plugin_manifest: &'a PluginsManifest,
+    name_selector: &NameSelector,
+    version_selector: Option<&VersionSelector>,
+) -> Result<&'a plugins::BinaryManifestItem, ErrBox> {
+    if let Some(version_selector) = version_selector {
+        return plugin_helpers::get_binary_with_name_and_version(plugin_manifest, name_selector, version_selector);
+    }
+    if let Some(binary) = get_config_file_binary_matching_name(environment, plugin_manifest, name_selector)? {
+        return Ok(binary);
+    }
+    let binaries = plugin_manifest.get_binaries_matching_name(name_selector);
+    match binaries
+        .iter()
+        .find(|b| plugin_manifest.has_any_global_command(&b.get_identifier()))
+    {
+        Some(binary) => Ok(binary),
+        None => plugin_helpers::get_binary_with_name_and_version(
+            plugin_manifest,
+            name_selector,
+            &VersionSelector::parse("*")?,
+        ),
+    }
+}
+
+fn handle_why_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: WhyCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    let name_selector =
+        plugin_helpers::resolve_name_selector_owner(environment, &plugin_manifest, command.name_selector)?;
+    let binary = get_binary_with_name_and_optional_version(
+        environment,
+        &plugin_manifest,
+        &name_selector,
+        command.version_selector.as_ref(),
+    )?;
+    let identifier = binary.get_identifier();
+    let mut lines = vec![format!("{} {}", binary.name, binary.version)];
+
+    lines.push(format!("Installed from: {}", binary.source.path));
+    let registry_url = match &binary.provenance {
+        Some(provenance) => provenance.registry.clone(),
+        None => registry::find_registry_url_of_binary(environment, &binary.name, &binary.source.path),
+    };
+    lines.push(format!(
+        "Registry: {}",
+        registry_url.unwrap_or_else(|| "None".to_string())
+    ));
+
+    let mut config_file_text = "None".to_string();
+    if let Some((config_file_path, mut config_file)) = get_config_file(environment)? {
+        resolve_config_file_version_aliases_for_manifest(&plugin_manifest, &mut config_file)?;
+        let config_binary = config_file.binaries.iter().find(|config_binary| {
+            plugin_helpers::get_installed_binary_if_associated_config_file_binary(&plugin_manifest, config_binary)
+                .map(|b| b.get_identifier() == identifier)
+                .unwrap_or(false)
+        });
+        if let Some(config_binary) = config_binary {
+            config_file_text = format!("{} ({})", config_file_path.display(), config_binary.path.path_or_url);
+        }
+    }
+    lines.push(format!("Config file: {}", config_file_text));
+
+    let mut global_command_names = binary
+        .get_command_names()
+        .into_iter()
+        .filter(|command_name| {
+            matches!(
+                plugin_manifest.get_global_binary_location(command_name),
+                Some(plugins::GlobalBinaryLocation::Bvm(global_identifier)) if global_identifier == identifier
+            )
+        })
+        .map(|command_name| command_name.as_str().to_string())
+        .collect::<Vec<_>>();
+    global_command_names.sort();
+    lines.push(format!(
+        "Global commands: {}",
+        if global_command_names.is_empty() {
+            "None".to_string()
+        } else {
+            global_command_names.join(", ")
+        }
+    ));
+
+    let mut command_names = binary.get_command_names();
+    command_names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    lines.push("Commands in the current directory:".to_string());
+    for command_name in command_names.iter() {
+        lines.push(format!(
+            "  {} - {}",
+            command_name,
+            get_command_resolution_text(environment, &plugin_manifest, command_name)?
+        ));
+    }
+
+    let pinned_identifiers = get_config_file_pinned_identifiers(environment, &plugin_manifest)?;
+    let now = environment.get_time_secs();
+    let get_unused_days =
+        |binary| now.saturating_sub(plugins::get_binary_last_used_time(environment, binary)) / (24 * 60 * 60);
+    lines.push(if binary.linked {
+        "Prune: kept because it's linked".to_string()
+    } else if plugin_manifest.is_read_only_binary(&identifier) {
+        "Prune: kept because it's from the read-only root".to_string()
+    } else if pinned_identifiers.contains(&identifier) {
+        "Prune: kept because it's used by the current directory".to_string()
+    } else if plugin_manifest.has_any_global_command(&identifier) {
+        "Prune: kept because it's used globally".to_string()
+    } else {
+        format!("Prune: may be removed (unused for {} days)", get_unused_days(binary))
+    });
+
+    let mut other_binaries = plugin_manifest
+        .binaries()
+        .filter(|b| {
+            b.name == binary.name
+                && b.version != binary.version
+                && is_prunable_binary(&plugin_manifest, &pinned_identifiers, b)
+        })
+        .collect::<Vec<_>>();
+    other_binaries.sort();
+    if other_binaries.is_empty() {
+        lines.push("Other versions prune may remove: None".to_string());
+    } else {
+        lines.push("Other versions prune may remove:".to_string());
+        for other_binary in other_binaries {
+            lines.push(format!(
+                "  {} (unused for {} days)",
+                other_binary.version,
+                get_unused_days(other_binary)
+            ));
+        }
+    }
+
+    environment.log(&lines.join("\n"));
+    Ok(())
+}
+
+/// Describes what the command resolves to in the current directory without running it.
+fn get_command_resolution_text(
+    environment: &impl Environment,
+    plugin_manifest: &PluginsManifest,
+    command_name: &CommandName,
+) -> Result<String, ErrBox> {
+    let describe_location = |location: &plugins::GlobalBinaryLocation| match location {
+        plugins::GlobalBinaryLocation::Bvm(identifier) => {
+            format!("{} {}", identifier.get_binary_name(), identifier.get_version())
+        }
+        plugins::GlobalBinaryLocation::Path => "path".to_string(),
+    };
+    if let Some(config_file_path) = configuration::find_config_file(environment)? {
+        let project_dir = config_file_path.parent().unwrap();
+        let project_state = plugins::ProjectState::load(environment, project_dir)?;
+        if let Some(location) = project_state.get_location(command_name) {
+            let is_installed = match &location {
+                plugins::GlobalBinaryLocation::Bvm(identifier) => plugin_manifest
+                    .get_binary(identifier)
+                    .map(|binary| !plugin_manifest.is_binary_disabled(&binary.name))
+                    .unwrap_or(false),
+                plugins::GlobalBinaryLocation::Path => true,
+            };
+            if is_installed {
+                return Ok(format!(
+                    "{} from `bvm use` in {}",
+                    describe_location(&location),
+                    project_dir.display()
+                ));
+            }
+        }
+    }
+    if let Some(info) = get_executable_path_from_config_file(environment, plugin_manifest, command_name)? {
+        if let Some(binary_info) = info.binary_info {
+            let config_file_path = configuration::find_config_file(environment)?.unwrap();
+            return Ok(format!(
+                "{} {} from {}",
+                binary_info.binary.name,
+                binary_info.binary.version,
+                config_file_path.display()
+            ));
+        }
+    }
+    Ok(match plugin_manifest.get_global_binary_location(command_name) {
+        Some(location) => format!("{} (global)", describe_location(&location)),
+        None => "not found".to_string(),
+    })
+}
+
+/// Gets the installed binary of the current directory's configuration file that matches the name.
+fn get_config_file_binary_matching_name<'a>(
+    environment: &impl Environment,
+    plugin_manifest: &'a PluginsManifest,
+    name_selector: &NameSelector,
+) -> Result<Option<&'a plugins::BinaryManifestItem>, ErrBox> {
+    let mut config_file = match get_config_file(environment)? {
+        Some((_, config_file)) => config_file,
+        None => return Ok(None),
+    };
+    resolve_config_file_version_aliases_for_manifest(plugin_manifest, &mut config_file)?;
+    let target = plugins::get_install_target(environment, None)?;
+    for config_binary in config_file.binaries.iter().filter(|b| b.is_for_platform(target)) {
+        if let Some(binary) =
+            plugin_helpers::get_installed_binary_if_associated_config_file_binary(plugin_manifest, config_binary)
+        {
+            if name_selector.is_match(&binary.name) {
+                return Ok(Some(binary));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn handle_init_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    for file_name in configuration::CONFIG_FILE_NAMES.iter() {
+        if environment.path_exists(&PathBuf::from(file_name)) {
+            return err!("A {} file already exists in the current directory.", file_name);
+        }
+    }
+
+    let config_path = PathBuf::from(configuration::CONFIG_FILE_NAME);
+    environment.write_file_text(&config_path, "{\n  \"binaries\": [\n  ]\n}\n")?;
+    environment.log(&format!("Created {}", configuration::CONFIG_FILE_NAME));
+    Ok(())
+}
+
+fn handle_prune_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: PruneCommand,
+) -> Result<(), ErrBox> {
+    remove_invalid_cached_downloads(environment)?;
+    let mut plugins = PluginsMut::load(environment)?;
+    let pinned_identifiers = get_config_file_pinned_identifiers(environment, &plugins.manifest)?;
+    let cutoff_time = environment.get_time_secs().saturating_sub(command.unused_for_secs);
+    let mut removed_binaries = plugins
+        .manifest
+        .binaries()
+        .filter(|binary| {
+            is_prunable_binary(&plugins.manifest, &pinned_identifiers, binary)
+                && plugins::get_binary_last_used_time(environment, binary) < cutoff_time
+        })
+        .map(|binary| (binary.name.clone(), binary.version.clone()))
+        .collect::<Vec<_>>();
+    if removed_binaries.is_empty() {
+        environment.log_error("No versions were unused for that long.");
+        return Ok(());
+    }
+    removed_binaries.sort();
+
+    let removed_identifiers = removed_binaries
+        .iter()
+        .map(|(name, version)| plugins::BinaryIdentifier::new(name, version))
+        .collect::<Vec<_>>();
+    for identifier in removed_identifiers.iter() {
+        if let Some(binary) = plugins.manifest.get_binary(identifier) {
+            run_pre_uninstall_command(environment, binary)?;
+        }
+        plugins.remove_binary(identifier)?;
+    }
+    plugins.save()?;
+    for identifier in removed_identifiers.iter() {
+        remove_binary_dir(environment, identifier)?;
+        environment.log_error(&format!(
+            "Removed {} {}.",
+            identifier.get_binary_name(),
+            identifier.get_version()
+        ));
+    }
+    plugins::remove_unused_store_files(environment)?;
+
+    Ok(())
+}
+
+/// Removes the cached registry files and binary manifest files that are incomplete or don't match
+/// their metadata, such as ones left by a process that was stopped while writing them.
+fn remove_invalid_cached_downloads(environment: &impl Environment) -> Result<(), ErrBox> {
+    let removed_count = registry::get_registry_cache(environment).remove_invalid_entries(environment)?
+        + plugins::get_plugin_file_cache(environment).remove_invalid_entries(environment)?;
+    if removed_count > 0 {
+        environment.log_error(&format!(
+            "Removed {} invalid cached download{}.",
+            removed_count,
+            if removed_count == 1 { "" } else { "s" }
+        ));
+    }
+    Ok(())
+}
+
+/// Gets if `bvm prune` may remove the binary once it's unused for long enough.
+fn is_prunable_binary(
+    plugin_manifest: &PluginsManifest,
+    pinned_identifiers: &[plugins::BinaryIdentifier],
+    binary: &plugins::BinaryManifestItem,
+) -> bool {
+    let identifier = binary.get_identifier();
+    !binary.linked
+        && !plugin_manifest.is_read_only_binary(&identifier)
+        && !pinned_identifiers.contains(&identifier)
+        && !plugin_manifest.has_any_global_command(&identifier)
+}
+
+/// Number of days a version must be unused for `bvm stats` to suggest pruning it.
+const STATS_PRUNE_DAYS: u64 = 30;
+
+fn handle_stats_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: StatsCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    let pinned_identifiers = get_config_file_pinned_identifiers(environment, &plugin_manifest)?;
+    let mut binaries = plugin_manifest
+        .binaries()
+        .filter(|b| match &command.name_selector {
+            Some(name_selector) => name_selector.is_match(&b.name),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    if binaries.is_empty() {
+        if let Some(name_selector) = &command.name_selector {
+            return plugin_helpers::get_not_installed_error(&plugin_manifest, name_selector);
+        }
+    }
+    binaries.sort();
+
+    let now = environment.get_time_secs();
+    let mut lines = Vec::new();
+    let mut binaries_size = 0;
+    let mut prunable_count = 0;
+    let mut prunable_size = 0;
+    for binary in binaries {
+        if binary.linked {
+            // the files of linked binaries aren't managed by bvm
+            lines.push(format!("{} {} (linked)", binary.name, binary.version));
+            continue;
+        }
+        if plugin_manifest.is_read_only_binary(&binary.get_identifier()) {
+            lines.push(format!("{} {} (read-only root)", binary.name, binary.version));
+            continue;
+        }
+        let plugin_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+        let size = environment.get_dir_size(&plugin_dir)?;
+        binaries_size += size;
+        let mut line = format!("{} {} - {}", binary.name, binary.version, utils::format_bytes(size));
+        let unused_days = now.saturating_sub(plugins::get_binary_last_used_time(environment, binary)) / (24 * 60 * 60);
+        if unused_days >= STATS_PRUNE_DAYS && is_prunable_binary(&plugin_manifest, &pinned_identifiers, binary) {
+            prunable_count += 1;
+            prunable_size += size;
+            line.push_str(&format!(
+                " {}",
+                utils::yellow(&format!("(unused for {} days)", unused_days))
+            ));
+        }
+        lines.push(line);
+    }
+
+    let registry_cache_size = get_dir_size_if_exists(
+        environment,
+        &utils::get_download_cache_dir(environment).join("registry-cache"),
+    )?;
+    let store_size = get_dir_size_if_exists(environment, &environment.get_local_user_data_dir().join("store"))?;
+    if !lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines.push(format!("Binaries: {}", utils::format_bytes(binaries_size)));
+    if store_size > 0 {
+        // the files in the store are hard linked into the binary directories, so they're already counted
+        lines.push(format!(
+            "Content store: {} (shared by the binaries)",
+            utils::format_bytes(store_size)
+        ));
+    }
+    lines.push(format!("Registry cache: {}", utils::format_bytes(registry_cache_size)));
+    lines.push(format!(
+        "Total: {}",
+        utils::bold(&utils::format_bytes(binaries_size + registry_cache_size))
+    ));
+    if prunable_count > 0 {
+        lines.push(String::new());
+        lines.push(format!(
+            "{} been used in {} days ({}). Run `bvm prune --unused-for {}d` to remove {}.",
+            if prunable_count == 1 {
+                "1 version hasn't".to_string()
+            } else {
+                format!("{} versions haven't", prunable_count)
+            },
+            STATS_PRUNE_DAYS,
+            utils::format_bytes(prunable_size),
+            STATS_PRUNE_DAYS,
+            if prunable_count == 1 { "it" } else { "them" },
+        ));
+    }
+    environment.log(&lines.join("\n"));
+    Ok(())
+}
+
+fn get_dir_size_if_exists(environment: &impl Environment, dir_path: &Path) -> Result<u64, ErrBox> {
+    if environment.is_dir(dir_path) {
+        environment.get_dir_size(dir_path)
+    } else {
+        Ok(0)
+    }
+}
+
+fn handle_compact_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: CompactCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let pinned_identifiers = get_config_file_pinned_identifiers(environment, &plugins.manifest)?;
+    let mut binaries = plugins
+        .manifest
+        .binaries()
+        .filter(|binary| match &command.name_selector {
+            Some(name_selector) => name_selector.is_match(&binary.name),
+            None => true,
+        })
+        .filter(|binary| {
+            binary.pending_extraction.is_none() && is_prunable_binary(&plugins.manifest, &pinned_identifiers, binary)
+        })
+        .map(|binary| (binary.name.clone(), binary.version.clone()))
+        .collect::<Vec<_>>();
+    if binaries.is_empty() {
+        environment.log_error("No inactive versions to compact.");
+        return Ok(());
+    }
+    binaries.sort();
+
+    // record the archives in the manifest before removing the extracted files so they're never lost
+    let mut extracted_sizes = Vec::new();
+    for (name, version) in binaries.iter() {
+        let plugin_dir = plugins::get_plugin_dir(environment, name, version);
+        extracted_sizes.push(environment.get_dir_size(&plugin_dir)?);
+        plugins.compact_binary(&plugins::BinaryIdentifier::new(name, version))?;
+    }
+    plugins.save()?;
+
+    let mut reclaimed_size = 0;
+    for ((name, version), extracted_size) in binaries.iter().zip(extracted_sizes) {
+        plugins::remove_compacted_files(environment, name, version)?;
+        let compacted_size = environment.get_dir_size(&plugins::get_plugin_dir(environment, name, version))?;
+        reclaimed_size += extracted_size.saturating_sub(compacted_size);
+        environment.log_error(&format!(
+            "Compacted {} {} ({} -> {}).",
+            name,
+            version,
+            utils::format_bytes(extracted_size),
+            utils::format_bytes(compacted_size)
+        ));
+    }
+    plugins::remove_unused_store_files(environment)?;
+    environment.log_error(&format!("Reclaimed {}.", utils::format_bytes(reclaimed_size)));
+
+    Ok(())
+}
+
+fn handle_verify_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: VerifyCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    log_manifest_problems_warning(environment, &plugin_manifest);
+    let mut binaries = plugin_manifest
+        .binaries()
+        .filter(|b| !b.linked)
+        .filter(|b| match &command.name_selector {
+            Some(name_selector) => name_selector.is_match(&b.name),
+            None => true,
+        })
+        .collect::<Vec<_>>();
+    if binaries.is_empty() {
+        return match &command.name_selector {
+            Some(name_selector) => plugin_helpers::get_not_installed_error(&plugin_manifest, name_selector),
+            None => {
+                environment.log_error("No binaries are installed.");
+                Ok(())
+            }
+        };
+    }
+    binaries.sort();
+
+    let mut failed_count = 0;
+    for binary in binaries {
+        if binary.pending_extraction.is_some() {
+            log_verbose!(
+                environment,
+                "Skipped {} {} because it isn't extracted.",
+                binary.name,
+                binary.version
+            );
+            continue;
+        }
+        let plugin_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+        match plugins::verify_integrity_manifest(environment, &plugin_dir)? {
+            Some(report) if report.is_valid() => {
+                log_verbose!(environment, "Verified {} {}.", binary.name, binary.version);
+            }
+            Some(report) => {
+                failed_count += 1;
+                let mut message = format!("{} {} failed verification.", binary.name, binary.version);
+                if !report.modified.is_empty() {
+                    message.push_str(&format!("\n  Modified: {}", report.modified.join(", ")));
+                }
+                if !report.missing.is_empty() {
+                    message.push_str(&format!("\n  Missing: {}", report.missing.join(", ")));
+                }
+                environment.log_error(&message);
+            }
+            None => environment.log_error(&utils::yellow(&format!(
+                "Warning: Skipped {} {} because it was installed without an integrity manifest. Reinstall it to verify it.",
+                binary.name, binary.version
+            ))),
+        }
+    }
+
+    if failed_count > 0 {
+        err!(
+            "{} failed verification. Reinstall with `bvm install --force` to restore the original files.",
+            if failed_count == 1 {
+                "1 binary".to_string()
+            } else {
+                format!("{} binaries", failed_count)
+            }
+        )
+    } else {
+        Ok(())
+    }
+}
+
+/// Problems found when reconciling the manifest with the installed binaries.
+struct ManifestProblems {
+    /// Binaries in the manifest whose directories are missing.
+    missing_binaries: Vec<plugins::BinaryIdentifier>,
+    /// Commands whose global version is a binary that isn't in the manifest.
+    missing_global_binaries: Vec<(CommandName, plugins::BinaryIdentifier)>,
+    /// Directories of binaries that aren't in the manifest.
+    orphaned_dirs: Vec<PathBuf>,
+}
+
+impl ManifestProblems {
+    fn is_empty(&self) -> bool {
+        self.missing_binaries.is_empty() && self.missing_global_binaries.is_empty() && self.orphaned_dirs.is_empty()
+    }
+}
+
+fn get_manifest_problems(
+    environment: &impl Environment,
+    plugin_manifest: &PluginsManifest,
+) -> Result<ManifestProblems, ErrBox> {
+    // linked binaries have no directory and the directories of read-only binaries aren't managed by the user
+    let mut missing_binaries = plugin_manifest
+        .binaries()
+        .filter(|b| !b.linked && b.pending_extraction.is_none())
+        .filter(|b| !plugin_manifest.is_read_only_binary(&b.get_identifier()))
+        .filter(|b| !environment.is_dir(&plugins::get_plugin_dir(environment, &b.name, &b.version)))
+        .collect::<Vec<_>>();
+    missing_binaries.sort();
+
+    let binary_dirs = plugin_manifest
+        .binaries()
+        .map(|b| plugins::get_writable_plugin_dir(environment, &b.name, &b.version))
+        .collect::<HashSet<_>>();
+    let binaries_dir = environment.get_local_user_data_dir().join("binaries");
+    let mut orphaned_dirs = Vec::new();
+    if environment.is_dir(&binaries_dir) {
+        // binaries are stored in binaries/<owner>/<name>/<version>
+        for owner_dir in environment.get_dir_paths(&binaries_dir)? {
+            for name_dir in environment.get_dir_paths(&owner_dir)? {
+                for version_dir in environment.get_dir_paths(&name_dir)? {
+                    if !binary_dirs.contains(&version_dir) {
+                        orphaned_dirs.push(version_dir);
+                    }
+                }
+            }
+        }
+    }
+    orphaned_dirs.sort();
+
+    Ok(ManifestProblems {
+        missing_binaries: missing_binaries.into_iter().map(|b| b.get_identifier()).collect(),
+        missing_global_binaries: plugin_manifest.get_missing_global_binaries(),
+        orphaned_dirs,
+    })
+}
+
+/// Warns when the manifest doesn't match the installed binaries, such as after the binaries directory was
+/// modified by hand or by another tool.
+fn log_manifest_problems_warning(environment: &impl Environment, plugin_manifest: &PluginsManifest) {
+    match get_manifest_problems(environment, plugin_manifest) {
+        Ok(problems) if !problems.is_empty() => environment.log_error(&utils::yellow(
+            "Warning: The manifest doesn't match the installed binaries. Run `bvm repair` to fix it.",
+        )),
+        Ok(_) => {}
+        Err(err) => log_verbose!(environment, "Error checking the manifest. {}", err.to_string()),
+    }
+}
+
+fn handle_repair_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let problems = get_manifest_problems(environment, &plugins.manifest)?;
+    if problems.is_empty() {
+        environment.log_error("No problems were found.");
+        return Ok(());
+    }
+
+    // binaries whose directories are missing are reinstalled or removed from the manifest
+    for identifier in problems.missing_binaries.iter() {
+        let binary = plugins.manifest.get_binary(identifier).unwrap().clone();
+        let message = format!(
+            "The directory of {} {} is missing. Reinstall it from {}?",
+            binary.name, binary.version, binary.source.path
+        );
+        if environment.confirm(&message)? == Some(true) {
+            reinstall_binary(&mut plugins, &binary)?;
+            environment.log_error(&format!("Repaired {} {}.", binary.name, binary.version));
+        } else {
+            plugins.remove_binary(identifier)?;
+            environment.log_error(&format!(
+                "Removed {} {} from the manifest because its directory is missing.",
+                binary.name, binary.version
+            ));
+        }
+    }
+
+    // removing binaries updates their global versions, so get the ones that are still missing
+    for (command_name, identifier) in plugins.manifest.get_missing_global_binaries() {
+        plugins.clear_global_binary(&command_name)?;
+        environment.log_error(&format!(
+            "Cleared the global version of {}, which was {} {} that isn't installed.",
+            command_name,
+            identifier.get_binary_name(),
+            identifier.get_version()
+        ));
+    }
+    plugins.save()?;
+
+    if !problems.orphaned_dirs.is_empty() {
+        environment.log_error(&format!(
+            "Found directories of binaries that aren't in the manifest:\n  {}",
+            problems
+                .orphaned_dirs
+                .iter()
+                .map(|dir| dir.display().to_string())
+                .collect::<Vec<_>>()
+                .join("\n  ")
+        ));
+        match environment.confirm("Delete these directories?")? {
+            Some(true) => {
+                for dir in problems.orphaned_dirs.iter() {
+                    remove_plugin_dir(environment, dir)?;
+                }
+                plugins::remove_unused_store_files(environment)?;
+                environment.log_error(&format!(
+                    "Deleted {}.",
+                    if problems.orphaned_dirs.len() == 1 {
+                        "1 directory".to_string()
+                    } else {
+                        format!("{} directories", problems.orphaned_dirs.len())
+                    }
+                ));
+            }
+            Some(false) => {}
+            None => environment.log_error("Did not delete the directories. Provide the `--yes` flag to delete them."),
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_clear_url_cache<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    plugins.clear_cached_urls();
+    plugins.save()?;
+    Ok(())
+}
+
+fn handle_self_update_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    let specifier = plugins::GitHubReleaseSpecifier {
+        owner: "dsherret".to_string(),
+        repo: "bvm".to_string(),
+        tag: None,
+        asset_pattern: None,
+    };
+    let release = plugins::get_github_release(environment, &specifier)?;
+    let latest_version = match Version::parse(release.tag_name.trim_start_matches('v')) {
+        Ok(version) => version,
+        Err(_) => {
+            return err!(
+                "Could not parse the release tag '{}' of bvm as a version.",
+                release.tag_name
+            )
+        }
+    };
+    let current_version = Version::parse(env!("CARGO_PKG_VERSION"))?;
+    if latest_version <= current_version {
+        environment.log(&format!("bvm {} is already the latest version.", current_version));
+        return Ok(());
+    }
+
+    let asset_name = format!("bvm-{}.zip", get_bvm_release_target());
+    let asset = match release.assets.iter().find(|asset| asset.name == asset_name) {
+        Some(asset) => asset,
+        None => {
+            return err!(
+                "Could not find {} in the assets of the bvm {} release.",
+                asset_name,
+                release.tag_name
+            )
+        }
+    };
+    let checksum = match asset.get_sha256_checksum() {
+        Some(checksum) => checksum,
+        None => {
+            return err!(
+                "Could not verify {} because the bvm {} release does not provide its checksum.",
+                asset_name,
+                release.tag_name
+            )
+        }
+    };
+    let zip_bytes = environment.download_file(&asset.browser_download_url)?;
+    verify_sha256_checksum(&zip_bytes, checksum)?;
+
+    // extract beside the current executable then move the files over the current ones
+    let current_exe_path = environment.get_current_exe()?;
+    let bin_dir = match current_exe_path.parent() {
+        Some(bin_dir) => bin_dir,
+        None => return err!("Could not find the directory of {}.", current_exe_path.display()),
+    };
+    let staging_dir = bin_dir.join(".self-update");
+    let _ignore = environment.remove_dir_all(&staging_dir);
+    environment.create_dir_all(&staging_dir)?;
+    let replace_result = utils::extract_zip(
+        &format!("Extracting bvm {}...", latest_version),
+        environment,
+        &zip_bytes,
+        &staging_dir,
+    )
+    .and_then(|_| replace_bin_dir_files(environment, &staging_dir, bin_dir));
+    let _ignore = environment.remove_dir_all(&staging_dir);
+    replace_result?;
+
+    environment.log(&format!("Updated bvm from {} to {}.", current_version, latest_version));
+    Ok(())
+}
+
+/// Gets the target triple of the release matching how this executable was built.
+fn get_bvm_release_target() -> &'static str {
+    let is_aarch64 = cfg!(target_arch = "aarch64");
+    if cfg!(target_os = "windows") {
+        if is_aarch64 {
+            "aarch64-pc-windows-msvc"
+        } else {
+            "x86_64-pc-windows-msvc"
+        }
+    } else if cfg!(target_os = "macos") {
+        if is_aarch64 {
+            "aarch64-apple-darwin"
+        } else {
+            "x86_64-apple-darwin"
+        }
+    } else if cfg!(target_env = "musl") {
+        if is_aarch64 {
+            "aarch64-unknown-linux-musl"
+        } else {
+            "x86_64-unknown-linux-musl"
+        }
+    } else if is_aarch64 {
+        "aarch64-unknown-linux-gnu"
+    } else {
+        "x86_64-unknown-linux-gnu"
+    }
+}
+
+fn replace_bin_dir_files(environment: &impl Environment, staging_dir: &Path, bin_dir: &Path) -> Result<(), ErrBox> {
+    for file_path in environment.get_file_paths(staging_dir)? {
+        let new_file_path = bin_dir.join(file_path.strip_prefix(staging_dir)?);
+        // a running executable can't be replaced on windows, but it may be renamed
+        if cfg!(target_os = "windows") && environment.path_exists(&new_file_path) {
+            let old_file_path = PathBuf::from(format!("{}.old", new_file_path.display()));
+            let _ignore = environment.remove_file(&old_file_path);
+            environment.rename(&new_file_path, &old_file_path)?;
+        }
+        environment.rename(&file_path, &new_file_path)?;
+    }
+    Ok(())
+}
+
+fn handle_uninstall_self_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: UninstallSelfCommand,
+) -> Result<(), ErrBox> {
+    environment::ensure_system_privileges(environment)?;
+    let plugin_manifest = PluginsManifest::load(environment);
+    for key in plugin_manifest.get_env_vars(environment).keys() {
+        environment.remove_env_variable(key)?;
+    }
+    if environment.is_system_mode() {
+        environment.remove_env_variable(BVM_SYSTEM_ENV_VAR_NAME)?;
+    }
+    #[cfg(target_os = "windows")]
+    remove_bvm_system_paths(environment)?;
+    #[cfg(not(target_os = "windows"))]
+    remove_shell_profile_setup(environment)?;
+
+    environment.remove_dir_all(&utils::get_shim_dir(environment))?;
+
+    if command.purge {
+        for binary in plugin_manifest.binaries() {
+            run_pre_uninstall_command(environment, binary)?;
+        }
+        environment.remove_dir_all(&environment.get_local_user_data_dir())?;
+        environment.remove_dir_all(&environment.get_user_data_dir())?;
+        environment.log_error("Removed the data and cache directories.");
+    }
+
+    let bin_dir = environment.get_user_home_dir().join("bin");
+    if environment.is_dir(&bin_dir) {
+        environment.log_error(&format!(
+            "Uninstalled bvm. Delete {} to remove the bvm executables.",
+            bin_dir.display()
+        ));
+    } else {
+        environment.log_error("Uninstalled bvm.");
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn remove_shell_profile_setup(environment: &impl Environment) -> Result<(), ErrBox> {
+    environment.remove_env_variable(BVM_INSTALL_DIR_ENV_VAR_NAME)?;
+    if environment.is_system_mode() {
+        return Ok(());
+    }
+    if let Some(fish_config_dir) = get_fish_config_dir(environment) {
+        let snippet_path = fish_config_dir.join("conf.d").join("bvm.fish");
+        if environment.path_exists(&snippet_path) {
+            environment.remove_file(&snippet_path)?;
+            environment.log_error(&format!("Removed {}.", snippet_path.display()));
+        }
+    }
+    let mut managed_file_paths = Vec::new();
+    if let Some(nushell_config_dir) = get_nushell_config_dir(environment) {
+        managed_file_paths.push(nushell_config_dir.join("env.nu"));
+        managed_file_paths.push(nushell_config_dir.join("config.nu"));
+    }
+    managed_file_paths.extend(get_pwsh_profile_path(environment));
+    for file_path in managed_file_paths {
+        if environment.path_exists(&file_path) && update_managed_block(environment, &file_path, None)? {
+            environment.log_error(&format!("Removed the bvm setup from {}.", file_path.display()));
+        }
+    }
+
+    let home_dir = match environment.get_env_var("HOME") {
+        Some(home_dir) if !home_dir.is_empty() => PathBuf::from(home_dir),
+        _ => return Ok(()),
+    };
+    for file_name in environment::SHELL_PROFILE_FILE_NAMES.iter() {
+        let profile_path = home_dir.join(file_name);
+        if !environment.path_exists(&profile_path) {
+            continue;
+        }
+        let text = environment.read_file_text(&profile_path)?;
+        if let Some(new_text) = remove_bvm_setup_lines(&text) {
+            environment.write_file_text(&profile_path, &new_text)?;
+            environment.log_error(&format!("Removed the bvm setup from {}.", profile_path.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Removes the lines the install script used to say to add to a shell profile.
+/// Returns `None` when the text has none of those lines.
+#[cfg(not(target_os = "windows"))]
+fn remove_bvm_setup_lines(text: &str) -> Option<String> {
+    fn is_bvm_setup_line(line: &str) -> bool {
+        let line = line.trim();
+        line.starts_with("export BVM_INSTALL_DIR=")
+            || ((line.starts_with(". ") || line.starts_with("source ")) && line.contains("bvm-init"))
+    }
+
+    let lines = text.split('\n').collect::<Vec<_>>();
+    let kept_lines = lines
+        .iter()
+        .filter(|line| !is_bvm_setup_line(line))
+        .cloned()
+        .collect::<Vec<_>>();
+    if kept_lines.len() == lines.len() {
+        None
+    } else {
+        Some(kept_lines.join("\n"))
+    }
+}
+
+fn handle_registry_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    sub_command: RegistrySubCommand,
+) -> Result<(), ErrBox> {
+    match sub_command {
+        RegistrySubCommand::Add(command) => handle_registry_add_command(environment, command),
+        RegistrySubCommand::Remove(command) => handle_registry_remove_command(environment, command),
+        RegistrySubCommand::Rename(command) => handle_registry_rename_command(environment, command),
+        RegistrySubCommand::List => handle_registry_list_command(environment),
+        RegistrySubCommand::Validate(command) => handle_registry_validate_command(environment, command),
+        RegistrySubCommand::Pack(command) => handle_registry_pack_command(environment, command),
+    }
+}
+
+fn handle_add_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: AddCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let url = resolve_url_or_name(
+        environment,
+        &mut plugins,
+        &mut registry::RegistryFileCache::new(),
+        &command.url_or_name,
+    )?;
+    let (config_file_path, config_file) = get_config_file_or_error(environment)?;
+    plugins.set_require_checksums(config_file.require_checksums);
+
+    // install the binary
+    install_binary(environment, &mut plugins, &url, None, false)?;
+    let binary_identifier = plugins.manifest.get_identifier_from_url(&url).unwrap().clone();
+    let binary_name = binary_identifier.get_binary_name();
+
+    // get the replace index if this binary name is already in the config file
+    let mut replace_binary = None;
+    let local_binaries = config_file.binaries.iter().filter(|b| !b.inherited);
+    for (i, config_binary) in local_binaries.enumerate() {
+        // ignore errors when associating
+        if let Err(err) = plugins.ensure_url_associated(&config_binary.path) {
+            environment.log_error(&format!(
+                "Error associating {}. {}",
+                &config_binary.path.path_or_url,
+                err.to_string()
+            ));
+        } else {
+            let config_binary_name = plugins
+                .manifest
+                .get_identifier_from_url(&config_binary.path)
+                .unwrap()
+                .get_binary_name();
+            if binary_name == config_binary_name {
+                replace_binary = Some((i, config_binary));
+                break;
+            }
+        }
+    }
+
+    // now add it to the configuration file
+    let binary = plugins.manifest.get_binary(&binary_identifier).unwrap();
+    let (version_selector, tag) = match command.url_or_name {
+        UrlOrName::Url(_) => (None, None),
+        UrlOrName::Name(name) => (name.version_selector, name.tag),
+    };
+    let checksum = match url.checksum {
+        Some(checksum) => checksum,
+        None => {
+            let url_file_bytes = environment.download_file(&url.path_or_url)?;
+            get_sha256_checksum(&url_file_bytes)
+        }
+    };
+
+    configuration::add_binary_to_config_file(
+        environment,
+        &config_file_path,
+        &configuration::ConfigFileBinary {
+            path: ChecksumPathOrUrl {
+                path_or_url: url.path_or_url,
+                checksum: Some(checksum),
+            },
+            version: if tag.is_some() {
+                None
+            } else {
+                Some(version_selector.unwrap_or(VersionSelector::parse(binary.version.as_str()).unwrap()))
+            },
+            version_alias: tag,
+            // keep any hooks of the binary being replaced
+            on_install: replace_binary.and_then(|(_, b)| b.on_install.clone()),
+            on_use: replace_binary.and_then(|(_, b)| b.on_use.clone()),
+            platforms: replace_binary.and_then(|(_, b)| b.platforms.clone()),
+            commands: replace_binary.map(|(_, b)| b.commands.clone()).unwrap_or_default(),
+            optional: replace_binary.map(|(_, b)| b.optional).unwrap_or(false),
+            inherited: false,
+        },
+        replace_binary.map(|(i, _)| i),
+    )?;
+
+    plugins.save()?;
+
+    Ok(())
+}
+
+fn handle_remove_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: RemoveCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let (config_file_path, mut config_file) = get_config_file_or_error(environment)?;
+    resolve_config_file_version_aliases_for_manifest(&plugins.manifest, &mut config_file)?;
+
+    let mut remove_indexes = Vec::new();
+    let mut binary_names = Vec::new();
+    let mut installed_identifiers = Vec::new();
+    let local_binaries = config_file.binaries.iter().filter(|b| !b.inherited);
+    for (i, config_binary) in local_binaries.enumerate() {
+        let binary_name = get_config_binary_name(&mut plugins, config_binary)?;
+        if command.name_selector.is_match(&binary_name) {
+            remove_indexes.push(i);
+            if let Some(binary) = plugins.get_installed_binary_for_config_binary(config_binary)? {
+                installed_identifiers.push(binary.get_identifier());
+            }
+            if !binary_names.contains(&binary_name) {
+                binary_names.push(binary_name);
+            }
+        }
+    }
+
+    if remove_indexes.is_empty() {
+        return err!(
+            "Could not find binary '{}' in {}.",
+            command.name_selector,
+            config_file_path.display()
+        );
+    }
+    if binary_names.len() > 1 {
+        binary_names.sort_by_key(|name| name.to_string());
+        return err!(
+            "There were multiple binaries with the name '{}'. Please include the owner in the name:\n  {}",
+            command.name_selector.name,
+            binary_names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+                .join("\n  ")
+        );
+    }
+
+    // remove from the end so the indexes of the remaining binaries stay the same
+    for remove_index in remove_indexes.into_iter().rev() {
+        configuration::remove_binary_from_config_file(environment, &config_file_path, remove_index)?;
+    }
+
+    if command.uninstall {
+        let pinned_identifiers = get_config_file_pinned_identifiers(environment, &plugins.manifest)?;
+        let mut removed_identifiers = Vec::new();
+        for identifier in installed_identifiers {
+            let binary_display = format!("{} {}", identifier.get_binary_name(), identifier.get_version());
+            if pinned_identifiers.contains(&identifier) || plugins.manifest.has_any_global_command(&identifier) {
+                environment.log_error(&format!("Kept {} installed because it's still used.", binary_display));
+            } else if !removed_identifiers.contains(&identifier) {
+                if let Some(binary) = plugins.manifest.get_binary(&identifier) {
+                    run_pre_uninstall_command(environment, binary)?;
+                }
+                plugins.remove_binary(&identifier)?;
+                removed_identifiers.push(identifier);
+            }
+        }
+        plugins.save()?;
+        for identifier in removed_identifiers.iter() {
+            remove_binary_dir(environment, identifier)?;
+            environment.log_error(&format!(
+                "Uninstalled {} {}.",
+                identifier.get_binary_name(),
+                identifier.get_version()
+            ));
+        }
+        plugins::remove_unused_store_files(environment)?;
+    } else {
+        // save the associated urls
+        plugins.save()?;
+    }
+
+    Ok(())
+}
+
+fn handle_pin_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    mut command: PinCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    command.name_selector =
+        plugin_helpers::resolve_name_selector_owner(environment, &plugin_manifest, command.name_selector)?;
+    let (name_selector, version) = match command.version {
+        Some(version) => (command.name_selector, version),
+        None => {
+            let mut binaries = plugin_manifest
+                .get_binaries_matching_name(&command.name_selector)
+                .into_iter()
+                .filter(|b| plugin_manifest.has_any_global_command(&b.get_identifier()))
+                .collect::<Vec<_>>();
+            if binaries.is_empty() {
+                return err!(
+                    "Could not find a globally used version of '{}'. Provide the version to pin (ex. `bvm pin {} <version>`).",
+                    command.name_selector,
+                    command.name_selector
+                );
+            }
+            if !plugin_helpers::get_have_same_owner(&binaries) {
+                binaries.sort();
+                return err!(
+                    "There were multiple binaries with the name '{}'. Please include the owner in the name:\n  {}",
+                    command.name_selector.name,
+                    binaries
+                        .iter()
+                        .map(|b| b.name.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n  ")
+                );
+            }
+            let binary = plugin_helpers::get_latest_binary(&binaries, true).unwrap();
+            (binary.name.to_selector(), binary.version.clone())
+        }
+    };
+
+    handle_add_command(
+        environment,
+        AddCommand {
+            url_or_name: UrlOrName::Name(InstallName {
+                name_selector,
+                version_selector: Some(version.to_selector()),
+                tag: None,
+            }),
+        },
+    )
+}
+
+fn handle_run_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: RunCommand,
+) -> Result<(), ErrBox> {
+    let (config_file_path, mut config_file) = get_config_file_or_error(environment)?;
+    let script = match config_file.scripts.get(&command.script_name) {
+        Some(script) => script,
+        None => {
+            return err!(
+                "Could not find script '{}' in the configuration file.",
+                command.script_name
+            )
+        }
+    };
+    let script = script.clone();
+    let mut plugins = PluginsMut::load(environment)?;
+    resolve_config_file_version_aliases(&mut plugins, &mut config_file)?;
+
+    let mut binaries = Vec::new();
+    for entry in get_config_file_binaries(&mut plugins, &config_file)? {
+        match plugins.get_installed_binary_for_config_binary(entry)? {
+            Some(binary) => binaries.push(binary.clone()),
+            None => {
+                return err!(
+                    "Ensure binaries are installed before running a script. Run `bvm install` first then `bvm run {}`.",
+                    command.script_name
+                )
+            }
+        }
+    }
+    plugins.save()?; // save any url associations
+    let plugins_manifest = plugins.manifest.clone();
+    drop(plugins); // release the manifest lock in case the script runs bvm
+
+    let mut script_text = script;
+    for arg in command.args.iter() {
+        script_text.push(' ');
+        script_text.push_str(&quote_script_arg(arg));
+    }
+
+    let binaries = binaries.iter().collect::<Vec<_>>();
+    let mut added_env_vars = HashMap::new();
+    for binary in binaries.iter() {
+        added_env_vars.extend(plugins_manifest.get_binary_env_vars(environment, &binary.get_identifier()));
+    }
+    let old_path = environment.get_env_path();
+    let new_path = get_env_path_with_binaries(environment, &binaries);
+    let env_changes = get_env_changes(&added_env_vars, &HashMap::new(), &old_path, &new_path);
+
+    // exit with the script's exit code so it's the same as running the script directly
+    let exit_code = environment.run_shell_command_with_env_changes(
+        config_file_path.parent().unwrap(),
+        &script_text,
+        &env_changes,
+    )?;
+    if exit_code != 0 {
+        environment.exit(exit_code)?;
+    }
+
+    Ok(())
+}
+
+/// Quotes an argument appended to a script so the shell passes it to the script as is.
+fn quote_script_arg(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | '=' | ':' | ',' | '+' | '@'));
+    if is_plain {
+        arg.to_string()
+    } else if cfg!(target_os = "windows") {
+        quote_cmd_value(arg)
+    } else {
+        quote_sh_value(arg)
+    }
+}
+
+fn handle_registry_add_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: RegistryAddCommand,
+) -> Result<(), ErrBox> {
+    let mut registry = registry::Registry::load(environment);
+    if let Some(name) = &command.name {
+        verify_registry_name(&registry, name, &command.url)?;
+    }
+    // always associate with the registry's current binaries
+    environment.set_refresh(true);
+    let registry_file = registry::download_registry_file(environment, &command.url)?;
+
+    // clear any previous associations if they exist
+    let previous_name = registry.get_url_name(&command.url).cloned();
+    registry.remove_url(&command.url);
+
+    // add the current ones
+    if registry_file.binaries.is_empty() {
+        environment.log_error("For some reason the registry was empty. Did not associate any binaries with this url.");
+    } else {
+        environment.log("Associated binaries:");
+        let mut previous_matches: Vec<(BinaryName, Vec<String>)> = Vec::new();
+
+        // todo: display description of binary here
+        for registry_binary in registry_file.binaries {
+            let binary_name = registry_binary.get_binary_name();
+            let current_urls = registry.get_urls(&binary_name.to_selector());
+
+            if current_urls.len() > 0 {
+                previous_matches.push((binary_name.clone(), current_urls.into_iter().map(|u| u.url).collect()));
+            }
+
+            environment.log(&format!("* {} - {}", binary_name, registry_binary.description));
+            registry.add_url(binary_name, command.url.clone());
+        }
+        registry.set_url_name(&command.url, command.name.or(previous_name));
+
+        for (binary_name, previous_urls) in previous_matches {
+            environment.log(&format!(
+                "\nWARNING! This may be ok, but the '{}' binary was already associated to the following url(s): {} -- They are now all associated and binary selection will go through each registry to find a matching version.",
+                binary_name,
+                previous_urls.join(", ")
+            ));
+        }
+    }
+
+    registry.save(environment)?;
+    Ok(())
+}
+
+fn handle_registry_remove_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: RegistryRemoveCommand,
+) -> Result<(), ErrBox> {
+    let mut registry = registry::Registry::load(environment);
+    let url = registry.find_url(&command.url_or_name).unwrap_or(command.url_or_name);
+    registry.remove_url(&url);
+    registry.save(environment)?;
+    registry::remove_cached_registry_file(environment, &url)?;
+
+    let plugin_manifest = PluginsManifest::load(environment);
+    let mut installed_binaries = plugin_manifest
+        .binaries()
+        .filter(|binary| binary.provenance.as_ref().and_then(|p| p.registry.as_ref()) == Some(&url))
+        .map(|binary| format!("  {} {}", binary.name, binary.version))
+        .collect::<Vec<_>>();
+    installed_binaries.sort();
+    if !installed_binaries.is_empty() {
+        environment.log_error(&utils::yellow(&format!(
+            "Warning: The following installed binaries came from the removed registry. They remain installed, but other versions of them can no longer be installed from it:\n{}",
+            installed_binaries.join("\n")
+        )));
+    }
+    Ok(())
+}
+
+fn handle_registry_rename_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: RegistryRenameCommand,
+) -> Result<(), ErrBox> {
+    let mut registry = registry::Registry::load(environment);
+    let url = match registry.find_url(&command.url_or_name) {
+        Some(url) => url,
+        None => {
+            return err!(
+                "Could not find a registry with the url or name '{}'.",
+                command.url_or_name
+            )
+        }
+    };
+    verify_registry_name(&registry, &command.new_name, &url)?;
+    registry.set_url_name(&url, Some(command.new_name.clone()));
+    registry.save(environment)?;
+    environment.log(&format!("Renamed the registry at {} to '{}'.", url, command.new_name));
+    Ok(())
+}
+
+/// Ensures the name may be given to the registry at the url.
+fn verify_registry_name(registry: &registry::Registry, name: &str, url: &str) -> Result<(), ErrBox> {
+    if name.is_empty() || name.contains("://") || name.contains(char::is_whitespace) {
+        return err!("Registry names may not be empty, contain whitespace, or contain '://'.");
+    }
+    match registry.find_url(name) {
+        Some(other_url) if other_url != url => {
+            err!("The name '{}' is already used by the registry at {}.", name, other_url)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn handle_registry_validate_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: RegistryValidateCommand,
+) -> Result<(), ErrBox> {
+    let url = plugins::resolve_plugin_file_url(&environment.cwd()?, &command.path_or_url)?;
+    let problems = plugins::validate_plugin_file(environment, &url, command.fetch)?;
+    if problems.is_empty() {
+        environment.log(&format!("{} is valid.", command.path_or_url));
+        Ok(())
+    } else {
+        for problem in problems.iter() {
+            environment.log_error(problem);
+        }
+        err!(
+            "Found {} problem{} in {}.",
+            problems.len(),
+            if problems.len() == 1 { "" } else { "s" },
+            command.path_or_url
+        )
+    }
+}
+
+fn handle_registry_pack_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: RegistryPackCommand,
+) -> Result<(), ErrBox> {
+    let cwd = environment.cwd()?;
+    let spec_path = cwd.join(&command.spec_path);
+    let spec_dir = spec_path.parent().unwrap();
+    let spec = plugins::read_pack_spec(environment, &spec_path)?;
+    let plugin_file = plugins::pack_plugin_file(environment, spec_dir, &spec)?;
+
+    let registry_path = match &command.registry_path {
+        Some(registry_path) => cwd.join(registry_path),
+        None => spec_dir.join("registry.json"),
+    };
+    let registry_dir = registry_path.parent().unwrap();
+    let plugin_file_name = match spec.url.split(['?', '#']).next().unwrap().rsplit('/').next() {
+        Some(file_name) if file_name.ends_with(".json") => file_name,
+        _ => return err!("Expected the 'url' of the binary manifest file to end with a .json file name."),
+    };
+    let plugin_file_path = registry_dir.join(plugin_file_name);
+    let plugin_file_text = format!("{}\n", serde_json::to_string_pretty(&plugin_file)?);
+    let platforms = plugins::PLATFORM_KEYS
+        .iter()
+        .filter(|key| plugin_file.get_platform_info(key).is_some())
+        .map(|key| key.to_string())
+        .collect::<Vec<_>>();
+
+    let registry_text = if environment.path_exists(&registry_path) {
+        Some(environment.read_file_text(&registry_path)?)
+    } else {
+        None
+    };
+    let registry_text = registry::add_version_to_registry_file(
+        registry_text.as_deref(),
+        &spec.get_binary_name(),
+        &spec.description,
+        registry::RegistryVersionInfo {
+            version: spec.version.clone(),
+            path: spec.url.clone(),
+            checksum: get_sha256_checksum(plugin_file_text.as_bytes()),
+            release_date: None,
+            platforms: Some(platforms.clone()),
+            binaries: Vec::new(),
+        },
+    )?;
+    environment.create_dir_all(registry_dir)?;
+    environment.write_file_text(&plugin_file_path, &plugin_file_text)?;
+    environment.write_file_text(&registry_path, &registry_text)?;
+
+    environment.log(&format!(
+        "Packed {} {} for {}.\n  Binary manifest file: {}\n  Registry file: {}",
+        spec.get_binary_name(),
+        spec.version,
+        platforms.join(", "),
+        plugin_file_path.display(),
+        registry_path.display()
+    ));
+    Ok(())
+}
+
+fn handle_registry_list_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    let registry = registry::Registry::load(environment);
+    let mut lines = Vec::new();
+    for url in registry.urls() {
+        lines.push(match registry.get_url_name(&url) {
+            Some(name) => format!("{} ({})", url, name),
+            None => url.clone(),
+        });
+        for binary_name in registry.get_url_binary_names(&url) {
+            lines.push(format!("  {}", binary_name));
+        }
+    }
+
+    if !lines.is_empty() {
+        environment.log(&lines.join("\n"));
+    }
+    Ok(())
+}
+
+fn handle_config_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    sub_command: ConfigSubCommand,
+) -> Result<(), ErrBox> {
+    match sub_command {
+        ConfigSubCommand::Set(command) => {
+            let value = match command.key.as_str() {
+                // store absolute paths so the directory doesn't depend on where bvm is run
+                "dataDir" | "cacheDir" | "readOnlyRoot" | "wslWindowsDataDir" => {
+                    let dir_path = environment.cwd()?.join(&command.value);
+                    serde_json::Value::String(dir_path.to_string_lossy().to_string())
+                }
+                _ => serde_json::from_str(&command.value).unwrap_or(serde_json::Value::String(command.value)),
+            };
+            environment::set_user_config_value(environment, &command.key, Some(value))
+        }
+        ConfigSubCommand::Unset(command) => environment::set_user_config_value(environment, &command.key, None),
+    }
+}
+
+fn handle_util_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    sub_command: UtilSubCommand,
+) -> Result<(), ErrBox> {
+    match sub_command {
+        UtilSubCommand::Hash(command) => handle_util_hash_command(environment, command),
+    }
+}
+
+fn handle_util_hash_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: UtilHashCommand,
+) -> Result<(), ErrBox> {
+    let url = plugins::resolve_plugin_file_url(&environment.cwd()?, &command.path_or_url)?;
+    let file_bytes = environment.download_file(&url)?;
+    let checksum = match command.algorithm {
+        HashAlgorithm::Sha256 => get_sha256_checksum(&file_bytes),
+        HashAlgorithm::Sha512 => format!("{:x}", sha2::Sha512::digest(&file_bytes)),
+    };
+    environment.log(&checksum);
+    Ok(())
+}
+
+fn handle_alias_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    sub_command: AliasSubCommand,
+) -> Result<(), ErrBox> {
+    match sub_command {
+        AliasSubCommand::Set(command) => handle_alias_set_command(environment, command),
+        AliasSubCommand::Remove(command) => handle_alias_remove_command(environment, command),
+        AliasSubCommand::List => handle_alias_list_command(environment),
+    }
+}
+
+fn handle_alias_set_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: AliasSetCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let binary_name = match &command.name_selector.owner {
+        Some(owner) => BinaryName::new(owner.clone(), command.name_selector.name.clone()),
+        None => {
+            let mut binary_names = plugins
+                .manifest
+                .get_binaries_matching_name(&command.name_selector)
+                .into_iter()
+                .map(|b| b.name.clone())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            if binary_names.is_empty() {
+                return err!(
+                    "Could not find any installed binaries named '{}'. Include the owner in the name to create an alias for a binary that is not installed.",
+                    command.name_selector
+                );
+            }
+            if binary_names.len() > 1 {
+                binary_names.sort();
+                return err!(
+                    "There were multiple binaries with the name '{}'. Please include the owner in the name:\n  {}",
+                    command.name_selector.name,
+                    binary_names
+                        .iter()
+                        .map(|n| n.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n  ")
+                );
+            }
+            binary_names.remove(0)
+        }
+    };
+
+    plugins.set_alias(binary_name, command.alias, command.version);
+    plugins.save()
+}
+
+fn handle_alias_remove_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: AliasRemoveCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let (binary_name, _) =
+        get_alias_binary_name_and_version(&plugins.manifest, &command.name_selector, &command.alias)?;
+    plugins.remove_alias(&binary_name, &command.alias);
+    plugins.save()
+}
+
+fn handle_alias_list_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    let lines = plugin_manifest
+        .get_aliases()
+        .into_iter()
+        .map(|(name, alias, version)| {
+            format!(
+                "{} {} -> {}",
+                utils::bold(&name.to_string()),
+                alias,
+                utils::green(&version.to_string())
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if !lines.is_empty() {
+        environment.log(&lines.join("\n"));
+    }
+    Ok(())
+}
+
+/// Gets the binary name and version of the alias for the provided name selector.
+fn get_alias_binary_name_and_version(
+    plugin_manifest: &PluginsManifest,
+    name_selector: &NameSelector,
+    alias: &str,
+) -> Result<(BinaryName, Version), ErrBox> {
+    let mut aliases = plugin_manifest
+        .get_aliases()
+        .into_iter()
+        .filter(|(name, name_alias, _)| name_selector.is_match(name) && name_alias.as_str() == alias)
+        .collect::<Vec<_>>();
+    if aliases.is_empty() {
+        return err!(
+            "Could not find alias '{}' for '{}'. Create one with `bvm alias set {} {} <version>`.",
+            alias,
+            name_selector,
+            name_selector,
+            alias
+        );
+    }
+    if aliases.len() > 1 {
+        return err!(
+            "There were multiple binaries with the name '{}' that had the alias '{}'. Please include the owner in the name:\n  {}",
+            name_selector.name,
+            alias,
+            aliases
+                .iter()
+                .map(|(name, _, _)| name.to_string())
+                .collect::<Vec<_>>()
+                .join("\n  ")
+        );
+    }
+    let (binary_name, _, version) = aliases.remove(0);
+    Ok((binary_name.clone(), version.clone()))
+}
+
+fn handle_hidden_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenSubCommand,
+) -> Result<(), ErrBox> {
+    match command {
+        HiddenSubCommand::ResolveCommand(command) => handle_hidden_resolve_command_command(environment, command),
+        HiddenSubCommand::ExecCommand(command) => handle_hidden_exec_command_command(environment, command),
+        HiddenSubCommand::GetPendingEnvChanges(command) => handle_hidden_get_pending_env_changes(environment, command),
+        HiddenSubCommand::ClearPendingEnvChanges => handle_hidden_clear_pending_env_changes_command(environment),
+        HiddenSubCommand::GetPaths => handle_hidden_get_paths_command(environment),
+        HiddenSubCommand::GetEnvVars(command) => handle_hidden_get_env_vars_command(environment, command),
+        HiddenSubCommand::ShellInit(command) => handle_hidden_shell_init_command(environment, command),
+        HiddenSubCommand::GetExecEnvChanges(command) => {
+            handle_hidden_get_exec_env_changes_command(environment, command)
+        }
+        HiddenSubCommand::GetExecCommandPath(command) => {
+            handle_hidden_get_exec_command_path_command(environment, command)
+        }
+        HiddenSubCommand::HasCommand(command) => handle_hidden_has_command(environment, command),
+        HiddenSubCommand::CheckUpdate(command) => handle_hidden_check_update_command(environment, command),
+        #[cfg(not(target_os = "windows"))]
+        HiddenSubCommand::UnixInstall => handle_hidden_unix_install_command(environment),
+        #[cfg(target_os = "windows")]
+        HiddenSubCommand::WindowsInstall => handle_hidden_windows_install_command(environment),
+        #[cfg(target_os = "windows")]
+        HiddenSubCommand::WindowsUninstall => handle_hidden_windows_uninstall_command(environment),
+    }
+}
+
+fn handle_hidden_resolve_command_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenResolveCommand,
+) -> Result<(), ErrBox> {
+    let resolved = resolve_command_executable(environment, &command.command_name)?;
+    output_env_changes(environment, &resolved.env_changes);
+    environment.log("EXEC");
+    environment.log(&resolved.executable_path.to_string_lossy());
+
+    Ok(())
+}
+
+fn handle_shell_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: ShellCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    let mut binaries = Vec::new();
+    for binary in command.binaries.iter() {
+        binaries.push(plugin_helpers::get_binary_with_name_and_version(
+            &plugin_manifest,
+            &binary.name_selector,
+            &binary.version_selector,
+        )?);
+    }
+    let (executable_path, args) = match command.command.split_first() {
+        Some((executable, args)) => (PathBuf::from(executable), args.to_vec()),
+        None => (get_shell_path(environment), Vec::new()),
+    };
+    run_executable_with_binaries(environment, &plugin_manifest, &binaries, &executable_path, &args)
+}
+
+fn handle_run_with_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: RunWithCommand,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    let mut registry_files = registry::RegistryFileCache::new();
+    let mut identifiers = Vec::new();
+    for url_or_name in command.binaries.iter() {
+        // prefer an installed version so that nothing is downloaded (tags may point at a new version, so they're resolved)
+        if let UrlOrName::Name(install_name) = url_or_name {
+            let any_version_selector = VersionSelector::parse("*")?;
+            let version_selector = match (&install_name.version_selector, &install_name.tag) {
+                (Some(version_selector), _) => Some(version_selector),
+                (None, None) => Some(&any_version_selector),
+                (None, Some(_)) => None,
+            };
+            let installed_binary = version_selector.and_then(|version_selector| {
+                plugin_helpers::get_latest_binary_matching_name_and_version(
+                    &plugins.manifest,
+                    &install_name.name_selector,
+                    version_selector,
+                )
+            });
+            if let Some(binary) = installed_binary {
+                identifiers.push(binary.get_identifier());
+                continue;
+            }
+        }
+
+        let url = resolve_url_or_name(environment, &mut plugins, &mut registry_files, url_or_name)?;
+        let identifier = match plugins.get_url_install_action(&url, None, false) {
+            Ok(UrlInstallAction::Install(plugin_file)) => {
+                // the global versions aren't set for the binary, unlike `bvm install`
+                let identifier =
+                    with_github_actions_group(environment, &format!("Installing {}", plugin_file.display()), || {
+                        plugins.setup_plugin(&plugin_file).map(|binary| binary.get_identifier())
+                    });
+                match identifier {
+                    Ok(identifier) => identifier,
+                    Err(err) => {
+                        return coded_err!(
+                            get_error_code(&err),
+                            "Error installing {}. {}",
+                            url.path_or_url,
+                            err.to_string()
+                        )
+                    }
+                }
+            }
+            Ok(UrlInstallAction::None) => plugins.manifest.get_identifier_from_url(&url).unwrap().clone(),
+            Err(err) => {
+                return coded_err!(
+                    get_error_code(&err),
+                    "Error installing {}. {}",
+                    url.path_or_url,
+                    err.to_string()
+                )
+            }
+        };
+        plugins.save()?; // write for every setup plugin in case a further one fails
+        identifiers.push(identifier);
+    }
+    plugins.save()?; // save any url associations
+    let plugin_manifest = plugins.manifest.clone();
+    drop(plugins); // release the manifest lock in case the command runs bvm
+
+    let binaries = identifiers
+        .iter()
+        .map(|identifier| plugin_manifest.get_binary(identifier).unwrap())
+        .collect::<Vec<_>>();
+    // the arg parser requires a command
+    let (executable, args) = command.command.split_first().unwrap();
+    run_executable_with_binaries(
+        environment,
+        &plugin_manifest,
+        &binaries,
+        &PathBuf::from(executable),
+        args,
+    )
+}
+
+/// Runs the executable with the binaries' directories on the path and their environment variables set, exiting
+/// with the executable's exit code when it fails.
+fn run_executable_with_binaries<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_manifest: &PluginsManifest,
+    binaries: &[&plugins::BinaryManifestItem],
+    executable_path: &Path,
+    args: &[String],
+) -> Result<(), ErrBox> {
+    let mut added_env_vars = HashMap::new();
+    for binary in binaries.iter() {
+        added_env_vars.extend(plugin_manifest.get_binary_env_vars(environment, &binary.get_identifier()));
+    }
+    let old_path = environment.get_env_path();
+    let new_path = get_env_path_with_binaries(environment, binaries);
+    let env_changes = get_env_changes(&added_env_vars, &HashMap::new(), &old_path, &new_path);
+
+    let exit_code = environment.run_executable(executable_path, args, &env_changes)?;
+    if exit_code != 0 {
+        environment.exit(exit_code)?;
+    }
+
+    Ok(())
+}
+
+fn get_shell_path<TEnvironment: Environment>(environment: &TEnvironment) -> PathBuf {
+    let shell = if cfg!(target_os = "windows") {
+        environment.get_env_var("COMSPEC")
+    } else {
+        environment.get_env_var("SHELL")
+    };
+    match shell.filter(|shell| !shell.is_empty()) {
+        Some(shell) => PathBuf::from(shell),
+        None if cfg!(target_os = "windows") => PathBuf::from("cmd.exe"),
+        None => PathBuf::from("/bin/sh"),
+    }
+}
+
+fn handle_hidden_exec_command_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenExecCommand,
+) -> Result<(), ErrBox> {
+    let resolved = resolve_command_executable(environment, &command.command_name)?;
+    #[cfg(target_os = "windows")]
+    ensure_not_linux_executable(environment, &command.command_name, &resolved.executable_path)?;
+    let update_notice = resolved
+        .binary
+        .as_ref()
+        .and_then(|binary| check_for_binary_update(environment, binary));
+    // the executable replaces the current process on unix, so the notice can't be output after it finishes
+    #[cfg(unix)]
+    if let Some(update_notice) = &update_notice {
+        environment.log_error(update_notice);
+    }
+    let (executable_path, args) =
+        match get_launcher_script(environment, &command.command_name, &resolved.executable_path) {
+            // the arguments are `$@` within the script
+            Some(script) => (
+                PathBuf::from("/bin/sh"),
+                vec!["-c".to_string(), script, command.command_name.as_str().to_string()]
+                    .into_iter()
+                    .chain(command.args)
+                    .collect::<Vec<_>>(),
+            ),
+            None => (resolved.executable_path, command.args),
+        };
+    let exit_code = environment.run_executable(&executable_path, &args, &resolved.env_changes)?;
+    #[cfg(not(unix))]
+    if let Some(update_notice) = &update_notice {
+        environment.log_error(update_notice);
+    }
+    if exit_code != 0 {
+        environment.exit(exit_code)?;
+    }
+
+    Ok(())
+}
+
+/// Directory in the user config directory with the templates of the scripts that shims run commands with.
+const LAUNCHERS_DIR_NAME: &str = "launchers";
+
+/// Gets the script to run the command's executable with from the command's launcher template or the
+/// default launcher template, with the executable path and command name filled in.
+fn get_launcher_script(
+    environment: &impl Environment,
+    command_name: &CommandName,
+    executable_path: &Path,
+) -> Option<String> {
+    if cfg!(target_os = "windows") {
+        return None;
+    }
+    let launchers_dir = environment.get_user_config_dir().join(LAUNCHERS_DIR_NAME);
+    let template = [format!("{}.sh", command_name.as_str()), "default.sh".to_string()]
+        .iter()
+        .map(|file_name| launchers_dir.join(file_name))
+        .find(|file_path| environment.path_exists(file_path))
+        .and_then(|file_path| environment.read_file_text(&file_path).ok())?;
+    log_verbose!(environment, "Running '{}' with a launcher template.", command_name);
+    Some(
+        template
+            .replace("{{executable}}", &quote_sh_value(&executable_path.to_string_lossy()))
+            .replace("{{command}}", &quote_sh_value(command_name.as_str())),
+    )
+}
+
+/// Gets a notice to output when the registry had a newer version of the binary the last time it was
+/// checked. The registry is checked again in a background process once the last check is stale, so
+/// running the command never waits on the network.
+fn check_for_binary_update<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    binary: &ResolvedBinary,
+) -> Option<String> {
+    if environment.is_offline() || is_ci_env_var_set(environment) {
+        return None;
+    }
+    match environment::read_user_config(environment) {
+        Ok(user_config) if user_config.update_notifications != Some(false) => {}
+        _ => return None,
+    }
+
+    let version_selector = binary.version_selector.clone();
+    let update_check = plugins::get_update_check(environment, &binary.name);
+    let current_time = environment.get_time_secs();
+    if update_check
+        .as_ref()
+        .map(|update_check| update_check.is_stale(current_time, version_selector.as_deref()))
+        .unwrap_or(true)
+    {
+        start_binary_update_check(environment, binary, update_check.as_ref(), current_time);
+    }
+
+    let available_version = update_check
+        .filter(|update_check| update_check.version_selector == version_selector)
+        .and_then(|update_check| update_check.available_version)
+        .filter(|version| version.gt(&binary.version))?;
+    let plugin_manifest = PluginsManifest::load(environment);
+    if plugin_manifest
+        .get_binary(&plugins::BinaryIdentifier::new(&binary.name, &available_version))
+        .is_some()
+    {
+        return None;
+    }
+    let hint = if binary.version_selector.is_some() {
+        "Update the version in the configuration file to use it.".to_string()
+    } else {
+        format!(
+            "Run `bvm install --use {} {}` to use it.",
+            binary.name, available_version
+        )
+    };
+    Some(utils::yellow(&format!(
+        "[bvm]: A newer version of {} is available: {} -> {}. {}",
+        binary.name, binary.version, available_version, hint
+    )))
+}
+
+/// Records that the binary is being checked, so no other check is started until it's stale again,
+/// then checks the registry in a background process.
+fn start_binary_update_check<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    binary: &ResolvedBinary,
+    last_update_check: Option<&plugins::UpdateCheck>,
+    current_time: u64,
+) {
+    let version_selector = binary.version_selector.clone();
+    let update_check = plugins::UpdateCheck {
+        checked_time: current_time,
+        // keep notifying about the last found version until the check replaces it
+        available_version: last_update_check
+            .filter(|update_check| update_check.version_selector == version_selector)
+            .and_then(|update_check| update_check.available_version.clone()),
+        version_selector: version_selector.clone(),
+    };
+    if let Err(err) = plugins::set_update_check(environment, &binary.name, update_check) {
+        log_verbose!(environment, "Error recording update check. {}", err);
+        return;
+    }
+
+    let mut args = vec![
+        "hidden".to_string(),
+        "check-update".to_string(),
+        binary.name.to_string(),
+        binary.version.to_string(),
+    ];
+    args.extend(version_selector);
+    if let Err(err) = environment.spawn_background_exe(&args) {
+        log_verbose!(environment, "Error starting update check. {}", err);
+    }
+}
+
+fn handle_hidden_check_update_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenCheckUpdateCommand,
+) -> Result<(), ErrBox> {
+    let (binary_name, urls) = get_registry_binary_name_and_urls(environment, &command.name_selector)?;
+    let include_prerelease = command.version.is_prerelease()
+        || command
+            .version_selector
+            .as_ref()
+            .map(|selector| selector.includes_prerelease())
+            .unwrap_or(false);
+    let mut registry_files = registry::RegistryFileCache::new();
+    let mut available_version: Option<Version> = None;
+    for url in urls.iter() {
+        let registry_file = registry_files.get(environment, url)?;
+        if let Some(registry_binary) = registry_file.take_binary_with_name(&binary_name) {
+            for item in registry_binary.versions {
+                let is_match = item.version.gt(&command.version)
+                    && (include_prerelease || !item.version.is_prerelease())
+                    && command
+                        .version_selector
+                        .as_ref()
+                        .map(|selector| selector.matches(&item.version))
+                        .unwrap_or(true)
+                    && item.is_available_for_platforms(plugins::get_platform_keys());
+                if is_match && available_version.as_ref().map(|v| item.version.gt(v)).unwrap_or(true) {
+                    available_version = Some(item.version);
+                }
+            }
+        }
+    }
+
+    plugins::set_update_check(
+        environment,
+        &binary_name,
+        plugins::UpdateCheck {
+            checked_time: environment.get_time_secs(),
+            version_selector: command.version_selector.map(|selector| selector.to_string()),
+            available_version,
+        },
+    )
+}
+
+/// Errors with a helpful message when a shim resolves to a Linux binary, such as one installed from WSL.
+#[cfg(target_os = "windows")]
+fn ensure_not_linux_executable(
+    environment: &impl Environment,
+    command_name: &CommandName,
+    executable_path: &Path,
+) -> Result<(), ErrBox> {
+    // windows executables have an extension, so only executables without one need to be read
+    if executable_path.extension().is_none() && utils::is_elf_executable(&environment.read_file(executable_path)?) {
+        return err!(
+            "'{}' resolved to a Linux binary at {}, which can't run on Windows. Run it from WSL or install a Windows version of it.",
+            command_name.as_str(),
+            executable_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Environment variable changes in the order they should be applied. A value of `None` removes the variable.
+type EnvChanges = Vec<(String, Option<String>)>;
+
+/// Resolves the path of the executable that runs the command in the current directory, which is
+/// the binary from the config file when there is one and otherwise the global binary.
+pub fn resolve_command_path<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command_name: &str,
+) -> Result<PathBuf, ErrBox> {
+    let resolved = resolve_command_executable(environment, &CommandName::from_string(command_name.to_string()))?;
+    Ok(resolved.executable_path)
+}
+
+struct ResolvedCommand {
+    executable_path: PathBuf,
+    env_changes: EnvChanges,
+    /// Binary installed by bvm that the executable belongs to, which may be updated.
+    binary: Option<ResolvedBinary>,
+}
+
+struct ResolvedBinary {
+    name: BinaryName,
+    version: Version,
+    /// Version selector of the configuration file the binary was resolved from.
+    version_selector: Option<String>,
+}
+
+/// Resolves the executable to run for the command along with the environment changes to run it with.
+fn resolve_command_executable<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command_name: &CommandName,
+) -> Result<ResolvedCommand, ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    if let Some(suffixed_command) = plugin_manifest.get_suffixed_command(command_name) {
+        if let Some(binary) = plugin_manifest.get_binary(&suffixed_command.identifier) {
+            if let Some(executable_path) =
+                plugin_helpers::get_exec_binary_command_exe_path(environment, binary, &suffixed_command.command_name)
+            {
+                plugins::record_binary_used(environment, &suffixed_command.identifier);
+                ensure_binary_ready(environment, binary)?;
+                log_verbose!(
+                    environment,
+                    "Resolved '{}' to {} of {} {}.",
+                    command_name,
+                    executable_path.display(),
+                    binary.name,
+                    binary.version
+                );
+                let env_changes = get_binary_env_changes(environment, &plugin_manifest, binary);
+                return Ok(ResolvedCommand {
+                    executable_path,
+                    env_changes,
+                    // the suffix selects the version
+                    binary: None,
+                });
+            }
+        }
+    }
+
+    if let Some(resolved) = resolve_project_state_command(environment, &plugin_manifest, command_name)? {
+        return Ok(resolved);
+    }
+
+    let info = get_executable_path_from_config_file(environment, &plugin_manifest, command_name)?;
+    let binary_info = if let Some(info) = info {
+        if let Some(binary_info) = info.binary_info {
+            Some(binary_info)
+        } else {
+            if info.had_uninstalled_binary {
+                environment.log_error(&utils::yellow(&format!(
+                    "[bvm warning]: There were some not installed binaries in the current directory (run `bvm install`). Resolving global '{}'.",
+                    command_name
+                )));
+            }
+            None
+        }
+    } else {
+        None
+    };
+
+    let is_shims_only = plugins::is_shims_only_enabled(environment)?;
+    if let Some(binary_info) = binary_info {
+        plugins::record_binary_used(environment, &binary_info.binary.get_identifier());
+        ensure_binary_ready(environment, binary_info.binary)?;
+        let executable_path = binary_info.executable_path;
+        // binaries pinned to a url in the configuration file don't have a version to update within
+        let binary = binary_info.binary;
+        let resolved_binary = binary_info
+            .version_selector
+            .filter(|_| !binary.linked)
+            .map(|version_selector| ResolvedBinary {
+                name: binary.name.clone(),
+                version: binary.version.clone(),
+                version_selector: Some(version_selector),
+            });
+        log_verbose!(
+            environment,
+            "Resolved '{}' to {} from the config file.",
+            command_name,
+            executable_path.display()
+        );
+
+        let env_changes = if is_shims_only {
+            get_binary_env_changes(environment, &plugin_manifest, binary_info.binary)
+        } else {
+            let identifier = binary_info.binary.get_identifier();
+            let command_names = binary_info.binary.get_command_names();
+            let mut plugins = PluginsMut::from_manifest_disallow_write(environment, plugin_manifest);
+            for command_name in command_names {
+                plugins.use_global_version(command_name, plugins::GlobalBinaryLocation::Bvm(identifier.clone()))?;
+            }
+            get_pending_env_changes(environment, &plugins.manifest)
+        };
+        Ok(ResolvedCommand {
+            executable_path,
+            env_changes,
+            binary: resolved_binary,
+        })
+    } else {
+        let global_exe_path = plugin_helpers::get_global_binary_file_path(environment, &plugin_manifest, command_name)?;
+        log_verbose!(
+            environment,
+            "Resolved '{}' to the global {}.",
+            command_name,
+            global_exe_path.display()
+        );
+        let mut env_changes = Vec::new();
+        let mut resolved_binary = None;
+        let global_identifier = match plugin_manifest.get_global_binary_location(command_name) {
+            Some(plugins::GlobalBinaryLocation::Bvm(identifier)) => Some(identifier),
+            _ => None,
+        };
+        // disabled binaries resolve to the executable on the path
+        if let Some(identifier) =
+            global_identifier.filter(|identifier| !plugin_manifest.is_binary_disabled(&identifier.get_binary_name()))
+        {
+            plugins::record_binary_used(environment, &identifier);
+            if let Some(binary) = plugin_manifest.get_binary(&identifier) {
+                ensure_binary_ready(environment, binary)?;
+                if is_shims_only {
+                    env_changes = get_binary_env_changes(environment, &plugin_manifest, binary);
+                }
+                if !binary.linked {
+                    resolved_binary = Some(ResolvedBinary {
+                        name: binary.name.clone(),
+                        version: binary.version.clone(),
+                        version_selector: None,
+                    });
+                }
+            }
+        }
+        Ok(ResolvedCommand {
+            executable_path: global_exe_path,
+            env_changes,
+            binary: resolved_binary,
+        })
+    }
+}
+
+/// Resolves the command from the version selected with `bvm use` in the current project.
+fn resolve_project_state_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_manifest: &PluginsManifest,
+    command_name: &CommandName,
+) -> Result<Option<ResolvedCommand>, ErrBox> {
+    let config_file_path = match configuration::find_config_file(environment)? {
+        Some(config_file_path) => config_file_path,
+        None => return Ok(None),
+    };
+    let project_state = plugins::ProjectState::load(environment, config_file_path.parent().unwrap())?;
+    match project_state.get_location(command_name) {
+        Some(plugins::GlobalBinaryLocation::Bvm(identifier)) => {
+            // fall back to the config file or global version once the binary is uninstalled or disabled
+            let binary = match plugin_manifest
+                .get_binary(&identifier)
+                .filter(|binary| !plugin_manifest.is_binary_disabled(&binary.name))
+            {
+                Some(binary) => binary,
+                None => return Ok(None),
+            };
+            let executable_path =
+                match plugin_helpers::get_exec_binary_command_exe_path(environment, binary, command_name) {
+                    Some(executable_path) => executable_path,
+                    None => return Ok(None),
+                };
+            plugins::record_binary_used(environment, &identifier);
+            ensure_binary_ready(environment, binary)?;
+            log_verbose!(
+                environment,
+                "Resolved '{}' to {} of {} {} used in the project.",
+                command_name,
+                executable_path.display(),
+                binary.name,
+                binary.version
+            );
+            Ok(Some(ResolvedCommand {
+                executable_path,
+                env_changes: get_binary_env_changes(environment, plugin_manifest, binary),
+                // the project selects the version
+                binary: None,
+            }))
+        }
+        Some(plugins::GlobalBinaryLocation::Path) => match utils::get_path_executable_path(environment, command_name) {
+            Some(executable_path) => {
+                log_verbose!(
+                    environment,
+                    "Resolved '{}' to {} on the path used in the project.",
+                    command_name,
+                    executable_path.display()
+                );
+                Ok(Some(ResolvedCommand {
+                    executable_path,
+                    env_changes: Vec::new(),
+                    binary: None,
+                }))
+            }
+            None => Ok(None),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Extracts the archive of a binary installed with lazy extraction the first time one of its commands is run
+/// and offers to repair the binary when its executables are missing.
+fn ensure_binary_ready<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    binary: &plugins::BinaryManifestItem,
+) -> Result<(), ErrBox> {
+    if binary.pending_extraction.is_some() {
+        let mut plugins = PluginsMut::load(environment)?;
+        plugins.extract_pending_binary(&binary.get_identifier())?;
+    } else {
+        repair_binary_if_broken(environment, binary)?;
+    }
+    Ok(())
+}
+
+/// Reinstalls the binary from its binary manifest file when the executables of its commands are missing
+/// (ex. deleted by a disk cleanup) instead of failing with an error from the operating system when run.
+fn repair_binary_if_broken<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    binary: &plugins::BinaryManifestItem,
+) -> Result<(), ErrBox> {
+    if binary.linked {
+        return Ok(());
+    }
+    let plugin_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+    let missing_path = binary
+        .commands
+        .iter()
+        .map(|command| plugin_dir.join(&command.path))
+        .find(|path| !environment.path_exists(path));
+    let missing_path = match missing_path {
+        Some(missing_path) => missing_path,
+        None => return Ok(()),
+    };
+
+    let message = format!(
+        "The executable {} of {} {} is missing.",
+        missing_path.display(),
+        binary.name,
+        binary.version
+    );
+    let install_arg = get_pinned_install_arg(binary).unwrap_or_else(|_| binary.source.path.clone());
+    let mut plugins = PluginsMut::load(environment)?;
+    if plugins.manifest.is_read_only_binary(&binary.get_identifier()) {
+        return coded_err!(
+            ErrorCode::NotInstalled,
+            "{} Ask the administrator of the shared installation to reinstall it.",
+            message
+        );
+    }
+    match environment.confirm(&format!("{} Reinstall it from {}?", message, binary.source.path))? {
+        Some(true) => {}
+        Some(false) => {
+            return coded_err!(
+                ErrorCode::NotInstalled,
+                "{} Reinstall it with `bvm install --force {}`.",
+                message,
+                install_arg
+            )
+        }
+        None => {
+            return coded_err!(
+                ErrorCode::NotInstalled,
+                "{} Reinstall it with `bvm install --force {}` or run the command interactively to repair it.",
+                message,
+                install_arg
+            )
+        }
+    }
+
+    reinstall_binary(&mut plugins, binary)?;
+    plugins.save()?;
+    environment.log_error(&format!("Repaired {} {}.", binary.name, binary.version));
+    Ok(())
+}
+
+/// Installs the binary again from its binary manifest file to restore its files.
+fn reinstall_binary<TEnvironment: Environment>(
+    plugins: &mut PluginsMut<TEnvironment>,
+    binary: &plugins::BinaryManifestItem,
+) -> Result<(), ErrBox> {
+    let url = ChecksumPathOrUrl {
+        path_or_url: binary.source.path.clone(),
+        checksum: Some(binary.source.checksum.clone()).filter(|checksum| !checksum.is_empty()),
+    };
+    let result = plugins
+        .get_url_install_action(&url, None, true)
+        .and_then(|action| match action {
+            UrlInstallAction::Install(plugin_file) if plugin_file.get_identifier() == binary.get_identifier() => {
+                plugins.setup_plugin(&plugin_file).map(|_| ())
+            }
+            UrlInstallAction::Install(plugin_file) => {
+                err!("{} now provides {} instead.", binary.source.path, plugin_file.display())
+            }
+            UrlInstallAction::None => Ok(()),
+        });
+    if let Err(err) = result {
+        return coded_err!(
+            get_error_code(&err),
+            "Error repairing {} {}. {}",
+            binary.name,
+            binary.version,
+            err.to_string()
+        );
+    }
+    Ok(())
+}
+
+/// Gets the environment changes for running the binary when only the shims are on the path.
+fn get_binary_env_changes<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_manifest: &PluginsManifest,
+    binary: &plugins::BinaryManifestItem,
+) -> EnvChanges {
+    let added_env_vars = plugin_manifest.get_binary_env_vars(environment, &binary.get_identifier());
+    let old_path = environment.get_env_path();
+    let new_path = get_env_path_with_binaries(environment, &[binary]);
+    get_env_changes(&added_env_vars, &HashMap::new(), &old_path, &new_path)
+}
+
+fn handle_hidden_get_pending_env_changes<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenEnvOutputCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = PluginsManifest::load(environment);
+    let env_changes = get_pending_env_changes(environment, &plugin_manifest);
+    match &command.shell {
+        Some(shell) => output_shell_env_changes(environment, shell, &env_changes),
+        None => output_env_changes(environment, &env_changes),
+    }
+
+    Ok(())
+}
+
+fn get_pending_env_changes<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_manifest: &PluginsManifest,
+) -> EnvChanges {
+    let added_env_vars = plugin_manifest.get_pending_added_env_variables(environment);
+    let removed_env_vars = plugin_manifest.get_pending_removed_env_variables(environment);
+    let old_path = environment.get_env_path();
+    let new_path = plugin_helpers::get_env_path_from_pending_env_changes(environment, &plugin_manifest);
+
+    get_env_changes(&added_env_vars, &removed_env_vars, &old_path, &new_path)
+}
+
+fn get_env_changes(
+    added_env_vars: &HashMap<String, String>,
+    removed_env_vars: &HashMap<String, String>,
+    old_path: &str,
+    new_path: &str,
+) -> EnvChanges {
+    let mut env_changes = Vec::new();
+
+    // removed
+    let removed_keys = removed_env_vars.iter().map(|(key, _)| key);
+    // sort to create some determinism for testing
+    #[cfg(test)]
+    let mut removed_keys = removed_keys.collect::<Vec<_>>();
+    #[cfg(test)]
+    removed_keys.sort();
+
+    for key in removed_keys {
+        if !added_env_vars.contains_key(key) {
+            env_changes.push((key.clone(), None));
+        }
+    }
+
+    // added
+    let added_vars = added_env_vars.iter();
+    // for determinism when testing
+    #[cfg(test)]
+    let added_vars = {
+        let mut vars = added_vars.collect::<Vec<_>>();
+        vars.sort();
+        vars
+    };
+    for (key, value) in added_vars {
+        env_changes.push((key.clone(), Some(value.clone())));
+    }
+
+    // path
+    if new_path.trim_matches(';') != old_path.trim_matches(';') {
+        env_changes.push(("PATH".to_string(), Some(new_path.to_string())));
+    }
+
+    env_changes
+}
+
+fn output_env_changes<TEnvironment: Environment>(environment: &TEnvironment, env_changes: &[(String, Option<String>)]) {
+    for (key, value) in env_changes.iter() {
+        match value {
+            Some(value) => output_set_env_var(environment, key, value),
+            None => output_unset_env_var(environment, key),
+        }
+    }
+}
+
+fn output_shell_env_changes<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    shell: &EnvShell,
+    env_changes: &[(String, Option<String>)],
+) {
+    if *shell == EnvShell::Nu {
+        environment.log(&format_nu_env_changes(env_changes));
+        return;
+    }
+    for (key, value) in env_changes.iter() {
+        environment.log(&match value {
+            Some(value) => format_shell_set_env_var(shell, key, value),
+            None => format_shell_remove_env_var(shell, key),
+        });
+    }
+}
+
+fn handle_hidden_clear_pending_env_changes_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+) -> Result<(), ErrBox> {
+    let mut plugins = PluginsMut::load(environment)?;
+    plugins.clear_pending_env_changes();
+    plugins.save()?;
+
+    Ok(())
+}
+
+fn handle_hidden_get_paths_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    let path_text = if plugins::is_shims_only_enabled(environment)? {
+        String::new()
+    } else {
+        let plugin_manifest = PluginsManifest::load(environment);
+        plugin_manifest.get_env_paths(environment).join(SYS_PATH_DELIMITER)
+    };
+
+    environment.log(&path_text);
+
+    Ok(())
+}
+
+fn handle_hidden_get_env_vars_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenEnvOutputCommand,
+) -> Result<(), ErrBox> {
+    if plugins::is_shims_only_enabled(environment)? {
+        return Ok(());
+    }
+    let plugin_manifest = PluginsManifest::load(environment);
+    let env_vars = plugin_manifest.get_env_vars(environment);
+    match &command.shell {
+        Some(shell) => output_shell_env_changes(environment, shell, &get_sorted_env_changes(env_vars)),
+        None => output_set_env_vars(environment, env_vars.iter()),
+    }
+
+    Ok(())
+}
+
+fn get_sorted_env_changes(env_vars: HashMap<String, String>) -> EnvChanges {
+    let mut env_changes = env_vars
+        .into_iter()
+        .map(|(key, value)| (key, Some(value)))
+        .collect::<Vec<_>>();
+    env_changes.sort();
+    env_changes
+}
+
+/// Script that defines the `bvm` function in fish. Commands that run binaries are handed off to the
+/// POSIX shell function since they don't change the environment of the current shell.
+const FISH_SHELL_INIT_SCRIPT: &str = r#"function bvm
+    set -l bvm_bin "$BVM_INSTALL_DIR/bin/bvm-bin"
+    if test "$argv[1]" = exec; or test "$argv[1]" = exec-command
+        sh -c '. "$BVM_INSTALL_DIR/bin/bvm"; bvm "$@"' sh $argv
+        return $status
+    end
+
+    $bvm_bin $argv
+    set -l bvm_status $status
+    if contains -- "$argv[1]" install uninstall use disable enable
+        $bvm_bin hidden get-pending-env-changes --shell fish | source
+        $bvm_bin hidden clear-pending-env-changes
+    end
+    return $bvm_status
+end"#;
+
+/// Script that defines the `bvm` function in PowerShell on Mac and Linux. Commands that run binaries are handed
+/// off to the POSIX shell function since they don't change the environment of the current shell.
+const PWSH_SHELL_INIT_SCRIPT: &str = r#"function bvm {
+    $bvmBin = Join-Path $env:BVM_INSTALL_DIR "bin/bvm-bin"
+    if ($args.Count -gt 0 -and @("exec", "exec-command") -contains $args[0]) {
+        & sh -c '. "$BVM_INSTALL_DIR/bin/bvm"; bvm "$@"' sh @args
+        return
+    }
+
+    & $bvmBin @args
+    if ($args.Count -gt 0 -and @("install", "uninstall", "use", "disable", "enable") -contains $args[0]) {
+        & $bvmBin hidden get-pending-env-changes --shell pwsh | Out-String | Invoke-Expression
+        & $bvmBin hidden clear-pending-env-changes
+    }
+}"#;
+
+fn handle_hidden_shell_init_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenShellInitCommand,
+) -> Result<(), ErrBox> {
+    let mut paths = vec![utils::get_shim_dir(environment).to_string_lossy().to_string()];
+    let mut env_changes = Vec::new();
+    if !plugins::is_shims_only_enabled(environment)? {
+        let plugin_manifest = PluginsManifest::load(environment);
+        env_changes = get_sorted_env_changes(plugin_manifest.get_env_vars(environment));
+        paths.extend(plugin_manifest.get_env_paths(environment));
+    }
+
+    match command.shell {
+        EnvShell::Fish => {
+            environment.log(FISH_SHELL_INIT_SCRIPT);
+            output_shell_env_changes(environment, &command.shell, &env_changes);
+            let paths = paths.iter().map(|path| escape_fish_value(path)).collect::<Vec<_>>();
+            environment.log(&format!("set -gx PATH {} $PATH", paths.join(" ")));
+        }
+        EnvShell::Nu => {
+            // nushell can't source a generated script, so this only outputs the variables to load
+            paths.push(environment.get_env_path());
+            env_changes.push(("PATH".to_string(), Some(paths.join(SYS_PATH_DELIMITER))));
+            output_shell_env_changes(environment, &command.shell, &env_changes);
+        }
+        EnvShell::Pwsh if !cfg!(target_os = "windows") => {
+            environment.log(PWSH_SHELL_INIT_SCRIPT);
+            output_shell_env_changes(environment, &command.shell, &env_changes);
+            environment.log(&format!(
+                "$env:PATH = '{}' + [IO.Path]::PathSeparator + $env:PATH",
+                paths.join(SYS_PATH_DELIMITER).replace('\'', "''")
+            ));
+        }
+        _ => return err!("Shell initialization is only supported for fish, nu, and pwsh on Mac and Linux."),
+    }
+
+    Ok(())
+}
+
+fn output_set_env_vars<'a, TEnvironment: Environment>(
+    environment: &TEnvironment,
+    vars: impl Iterator<Item = (&'a String, &'a String)>,
+) {
+    // for determinism when testing
+    #[cfg(test)]
+    let vars = {
+        let mut vars = vars.collect::<Vec<_>>();
+        vars.sort();
+        vars
+    };
+
+    for (key, value) in vars {
+        output_set_env_var(environment, key, value);
+    }
+}
+
+fn output_set_env_var<TEnvironment: Environment>(environment: &TEnvironment, key: &str, value: &str) {
+    if cfg!(target_os = "windows") {
+        environment.log(&format!("SET {}={}", key, value))
+    } else {
+        environment.log("ADD");
+        environment.log(&key);
+        environment.log(&value);
+    }
+}
+
+fn output_unset_env_var<TEnvironment: Environment>(environment: &TEnvironment, key: &str) {
+    if cfg!(target_os = "windows") {
+        environment.log(&format!("SET {}=", key));
+    } else {
+        environment.log("REMOVE");
+        environment.log(&key);
+    }
+}
+
+fn handle_hidden_get_exec_env_changes_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenExecEnvChangesCommand,
+) -> Result<(), ErrBox> {
+    let plugin_manifest = get_manifest_for_exec_env_changes(environment, &command)?;
+
+    // output the pending environment changes
+    output_env_changes(environment, &get_pending_env_changes(environment, &plugin_manifest));
+
+    Ok(())
+}
+
+fn get_manifest_for_exec_env_changes<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: &HiddenExecEnvChangesCommand,
+) -> Result<PluginsManifest, ErrBox> {
+    // load ensuring the changes here won't affect the system state
+    let mut plugins = PluginsMut::load_disallow_write(environment);
+
+    // go through the process of doing a "use" command
+    let command_names = plugin_helpers::get_command_names_for_name_and_path_or_version_selector(
+        &plugins.manifest,
+        &command.name_selector,
+        &command.version_selector,
+    )?;
+    let location = plugin_helpers::get_global_binary_location_for_name_and_path_or_version_selector(
+        &plugins.manifest,
+        &command.name_selector,
+        &command.version_selector,
+    )?;
+
+    for command_name in command_names {
+        plugins.use_global_version(command_name.clone(), location.clone())?;
+    }
+
+    // do not save the plugins, we just want to return a manifest that has the changes above
+    Ok(plugins.manifest.clone())
+}
+
+fn handle_hidden_get_exec_command_path_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenGetExecCommandPathCommand,
+) -> Result<(), ErrBox> {
+    let exec_path = match &command.version_selector {
+        PathOrVersionSelector::Path => {
+            if let Some(exe_path) = utils::get_path_executable_path(environment, &command.command_name) {
+                exe_path
+            } else {
+                return err!("Could not find binary on the path for the given binary name, version, and command.");
+            }
+        }
+        PathOrVersionSelector::Version(version_selector) => {
+            let plugin_manifest = PluginsManifest::load(environment);
+            let binary = plugin_helpers::get_binary_with_name_and_version(
+                &plugin_manifest,
+                &command.name_selector,
+                &version_selector,
+            )?;
+            match plugin_helpers::get_exec_binary_command_exe_path(environment, &binary, &command.command_name) {
+                Some(exe_path) => {
+                    plugins::record_binary_used(environment, &binary.get_identifier());
+                    ensure_binary_ready(environment, binary)?;
+                    exe_path
+                }
+                None => {
+                    return err!(
+                        "Could not find a matching command. Expected one of the following: {}",
+                        binary
+                            .get_command_names()
+                            .into_iter()
+                            .map(|c| c.into_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }
+            }
+        }
+    };
+    environment.log(&exec_path.to_string_lossy());
+
+    Ok(())
+}
+
+fn handle_hidden_has_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    command: HiddenHasCommandCommand,
+) -> Result<(), ErrBox> {
+    let command_name = match command.command_name {
+        Some(command_name) => command_name,
+        None => {
+            environment.log("false");
+            return Ok(());
+        }
+    };
+    let plugin_manifest = PluginsManifest::load(environment);
+    let has_command = plugin_helpers::has_command_name_for_exec(
+        environment,
+        &plugin_manifest,
+        &command.name_selector,
+        &command.version_selector,
+        &command_name,
+    )?;
+
+    environment.log(&format!("{}", has_command));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+const BVM_INSTALL_DIR_ENV_VAR_NAME: &str = "BVM_INSTALL_DIR";
+/// Persisted for all users by the system installation so that the shims use it.
+const BVM_SYSTEM_ENV_VAR_NAME: &str = "BVM_SYSTEM";
+
+#[cfg(not(target_os = "windows"))]
+fn handle_hidden_unix_install_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    environment::ensure_system_privileges(environment)?;
+    // the shell profiles source bvm-init from the install directory
+    let install_dir = match environment.get_env_var(BVM_INSTALL_DIR_ENV_VAR_NAME) {
+        Some(install_dir) if !install_dir.is_empty() => install_dir,
+        _ => environment.get_user_home_dir().to_string_lossy().to_string(),
+    };
+    if environment.is_system_mode() {
+        // the shell configurations of users are left alone since the login shells source the system profile script
+        environment.set_env_variable(BVM_SYSTEM_ENV_VAR_NAME.to_string(), "1".to_string())?;
+        environment.set_env_variable(BVM_INSTALL_DIR_ENV_VAR_NAME.to_string(), install_dir)?;
+        recreate_shims(environment)?;
+        return Ok(());
+    }
+    if let Some(fish_config_dir) = get_fish_config_dir(environment) {
+        if environment.is_dir(&fish_config_dir) {
+            let snippet_path = fish_config_dir.join("conf.d").join("bvm.fish");
+            environment.create_dir_all(snippet_path.parent().unwrap())?;
+            environment.write_file_text(&snippet_path, &get_fish_conf_d_snippet(&install_dir))?;
+        }
+    }
+    if let Some(nushell_config_dir) = get_nushell_config_dir(environment) {
+        if environment.is_dir(&nushell_config_dir) {
+            update_managed_block(
+                environment,
+                &nushell_config_dir.join("env.nu"),
+                Some(&get_nu_env_block(&install_dir)),
+            )?;
+            update_managed_block(
+                environment,
+                &nushell_config_dir.join("config.nu"),
+                Some(NU_CONFIG_BLOCK),
+            )?;
+        }
+    }
+    if let Some(pwsh_profile_path) = get_pwsh_profile_path(environment) {
+        if environment.is_dir(pwsh_profile_path.parent().unwrap()) {
+            update_managed_block(
+                environment,
+                &pwsh_profile_path,
+                Some(&get_pwsh_profile_block(&install_dir)),
+            )?;
+        }
+    }
+    environment.set_env_variable(BVM_INSTALL_DIR_ENV_VAR_NAME.to_string(), install_dir)?;
+    recreate_shims(environment)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn get_nushell_config_dir(environment: &impl Environment) -> Option<PathBuf> {
+    match environment.get_env_var("XDG_CONFIG_HOME") {
+        Some(config_dir) if !config_dir.is_empty() => Some(PathBuf::from(config_dir).join("nushell")),
+        _ => {
+            let home_dir = PathBuf::from(environment.get_env_var("HOME").filter(|dir| !dir.is_empty())?);
+            Some(if cfg!(target_os = "macos") {
+                home_dir.join("Library").join("Application Support").join("nushell")
+            } else {
+                home_dir.join(".config").join("nushell")
+            })
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn get_pwsh_profile_path(environment: &impl Environment) -> Option<PathBuf> {
+    let config_dir = match environment.get_env_var("XDG_CONFIG_HOME") {
+        Some(config_dir) if !config_dir.is_empty() => PathBuf::from(config_dir),
+        _ => PathBuf::from(environment.get_env_var("HOME").filter(|dir| !dir.is_empty())?).join(".config"),
+    };
+    Some(config_dir.join("powershell").join("Microsoft.PowerShell_profile.ps1"))
+}
+
+/// Gets the block for the PowerShell profile, which generates the setup on startup so it stays in sync with bvm.
+#[cfg(not(target_os = "windows"))]
+fn get_pwsh_profile_block(install_dir: &str) -> String {
+    format!(
+        concat!(
+            "$env:BVM_INSTALL_DIR = '{}'\n",
+            "if (Test-Path \"$env:BVM_INSTALL_DIR/bin/bvm-bin\") {{\n",
+            "    & \"$env:BVM_INSTALL_DIR/bin/bvm-bin\" hidden shell-init pwsh | Out-String | Invoke-Expression\n",
+            "}}\n",
+        ),
+        install_dir.replace('\'', "''")
+    )
+}
+
+/// Sets or removes bvm's managed block in a shell configuration file, returning if the file changed.
+#[cfg(not(target_os = "windows"))]
+fn update_managed_block(
+    environment: &impl Environment,
+    file_path: &Path,
+    contents: Option<&str>,
+) -> Result<bool, ErrBox> {
+    let text = if environment.path_exists(file_path) {
+        environment.read_file_text(file_path)?
+    } else {
+        String::new()
+    };
+    let new_text = environment::set_managed_block(&text, contents);
+    let changed = new_text != text;
+    if changed {
+        environment.write_file_text(file_path, &new_text)?;
+    }
+    Ok(changed)
+}
+
+/// Gets the block for nushell's `env.nu` file, which loads the environment of the globally used binaries.
+#[cfg(not(target_os = "windows"))]
+fn get_nu_env_block(install_dir: &str) -> String {
+    format!(
+        concat!(
+            "$env.BVM_INSTALL_DIR = r#'{}'#\n",
+            "load-env (^($env.BVM_INSTALL_DIR | path join bin bvm-bin) hidden shell-init nu | from json)\n",
+        ),
+        install_dir
+    )
+}
+
+/// Block for nushell's `config.nu` file that defines the `bvm` command. Commands that run binaries are handed off
+/// to the POSIX shell function since they don't change the environment of the current shell.
+#[cfg(not(target_os = "windows"))]
+const NU_CONFIG_BLOCK: &str = r#"def --env bvm-load-env-changes [changes: record] {
+    for key in ($changes | columns) {
+        let value = ($changes | get $key)
+        if $value == null {
+            hide-env -i $key
+        } else {
+            load-env {$key: $value}
+        }
+    }
+}
+
+def --env --wrapped bvm [...args] {
+    let bvm_bin = ($env.BVM_INSTALL_DIR | path join bin bvm-bin)
+    let sub_command = ($args | get -i 0)
+    if $sub_command in [exec exec-command] {
+        ^sh -c '. "$BVM_INSTALL_DIR/bin/bvm"; bvm "$@"' sh ...$args
+        return
+    }
+
+    ^$bvm_bin ...$args
+    if $sub_command in [install uninstall use disable enable] {
+        bvm-load-env-changes (^$bvm_bin hidden get-pending-env-changes --shell nu | from json)
+        ^$bvm_bin hidden clear-pending-env-changes
+    }
+}
+"#;
+
+#[cfg(not(target_os = "windows"))]
+fn get_fish_config_dir(environment: &impl Environment) -> Option<PathBuf> {
+    match environment.get_env_var("XDG_CONFIG_HOME") {
+        Some(config_dir) if !config_dir.is_empty() => Some(PathBuf::from(config_dir).join("fish")),
+        _ => match environment.get_env_var("HOME") {
+            Some(home_dir) if !home_dir.is_empty() => Some(PathBuf::from(home_dir).join(".config").join("fish")),
+            _ => None,
+        },
+    }
+}
+
+/// Gets the fish `conf.d` snippet, which generates the setup on startup so it stays in sync with bvm.
+#[cfg(not(target_os = "windows"))]
+fn get_fish_conf_d_snippet(install_dir: &str) -> String {
+    format!(
+        concat!(
+            "# bvm shell setup (generated by bvm)\n",
+            "set -gx BVM_INSTALL_DIR {}\n",
+            "if test -x \"$BVM_INSTALL_DIR/bin/bvm-bin\"\n",
+            "    \"$BVM_INSTALL_DIR/bin/bvm-bin\" hidden shell-init fish | source\n",
+            "end\n",
+        ),
+        escape_fish_value(install_dir)
+    )
+}
+
+#[cfg(target_os = "windows")]
+fn handle_hidden_windows_install_command<TEnvironment: Environment>(environment: &TEnvironment) -> Result<(), ErrBox> {
+    environment::ensure_system_privileges(environment)?;
+    let data_dir = environment.get_user_data_dir();
+    let bin_dir = environment.get_user_home_dir().join("bin");
+    if environment.is_system_mode() {
+        environment.set_env_variable(BVM_SYSTEM_ENV_VAR_NAME.to_string(), "1".to_string())?;
+    }
+
+    environment.ensure_system_path_pre(&PathBuf::from(&bin_dir).to_string_lossy())?;
+    environment.ensure_system_path_pre(&PathBuf::from(data_dir).join("shims").to_string_lossy())?;
+    recreate_shims(environment)?;
+
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn handle_hidden_windows_uninstall_command<TEnvironment: Environment>(
+    environment: &TEnvironment,
+) -> Result<(), ErrBox> {
+    remove_bvm_system_paths(environment)
+}
+
+#[cfg(target_os = "windows")]
+fn remove_bvm_system_paths(environment: &impl Environment) -> Result<(), ErrBox> {
+    let data_dir = environment.get_user_data_dir();
+    let bin_dir = environment.get_user_home_dir().join("bin");
+    environment.remove_system_path(&PathBuf::from(&bin_dir).to_string_lossy())?;
+    environment.remove_system_path(&PathBuf::from(data_dir).join("shims").to_string_lossy())?;
+    Ok(())
+}
+
+fn recreate_shims(environment: &impl Environment) -> Result<(), ErrBox> {
+    let shim_dir = utils::get_shim_dir(environment);
+    environment.remove_dir_all(&shim_dir)?;
+    environment.create_dir_all(&shim_dir)?;
+    let plugin_manifest = PluginsManifest::load(environment);
+    let command_names = plugin_manifest.get_all_command_names();
+    for command_name in command_names.iter() {
+        plugins::create_shim(environment, command_name)?;
+    }
+    Ok(())
+}
+
+struct ConfigFileExecutableInfo<'a> {
+    binary_info: Option<ConfigFileBinaryInfo<'a>>,
+    had_uninstalled_binary: bool,
+}
+
+struct ConfigFileBinaryInfo<'a> {
+    executable_path: PathBuf,
+    binary: &'a plugins::BinaryManifestItem,
+    version_selector: Option<String>,
+}
+
+fn get_executable_path_from_config_file<'a, TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugin_manifest: &'a PluginsManifest,
+    command_name: &CommandName,
+) -> Result<Option<ConfigFileExecutableInfo<'a>>, ErrBox> {
+    Ok(if let Some((_, mut config_file)) = get_config_file(environment)? {
+        resolve_config_file_version_aliases_for_manifest(plugin_manifest, &mut config_file)?;
+        let mut had_uninstalled_binary = false;
+        let mut binary_info = None;
+
+        let target = plugins::get_install_target(environment, None)?;
+        for config_binary in config_file.binaries.iter().filter(|b| b.is_for_platform(target)) {
+            let binary =
+                plugin_helpers::get_installed_binary_if_associated_config_file_binary(plugin_manifest, &config_binary);
+            if let Some(binary) = binary {
+                if plugin_manifest.is_binary_disabled(&binary.name) {
+                    continue;
+                }
+                for command in binary.commands.iter() {
+                    if &config_binary.get_command_name(&command.name) == command_name {
+                        let plugin_cache_dir = plugins::get_plugin_dir(environment, &binary.name, &binary.version);
+                        let executable_path = plugin_cache_dir.join(&command.path);
+
+                        binary_info = Some(ConfigFileBinaryInfo {
+                            binary,
+                            executable_path,
+                            version_selector: config_binary.version.as_ref().map(|selector| selector.to_string()),
+                        });
+
+                        break;
+                    }
+                }
+            } else {
+                had_uninstalled_binary = true;
+            }
+        }
+
+        Some(ConfigFileExecutableInfo {
+            binary_info,
+            had_uninstalled_binary,
+        })
+    } else {
+        None
+    })
+}
+
+/// Gets the binaries of the config file that are used on the target, where local binaries take
+/// precedence over inherited ones with the same name.
+fn get_config_file_binaries<'a, TEnvironment: Environment>(
+    plugins: &mut PluginsMut<TEnvironment>,
+    config_file: &'a configuration::ConfigFile,
+) -> Result<Vec<&'a configuration::ConfigFileBinary>, ErrBox> {
+    let target = plugins.get_target()?;
+    let platform_binaries = config_file
+        .binaries
+        .iter()
+        .filter(|b| b.is_for_platform(target))
+        .collect::<Vec<_>>();
+    if !platform_binaries.iter().any(|b| b.inherited) {
+        return Ok(platform_binaries);
+    }
+
+    let mut local_binary_names = Vec::new();
+    for config_binary in platform_binaries.iter().filter(|b| !b.inherited) {
+        local_binary_names.push(get_config_binary_name(plugins, config_binary)?);
+    }
+
+    let mut binaries = Vec::new();
+    for config_binary in platform_binaries {
+        if config_binary.inherited && local_binary_names.contains(&get_config_binary_name(plugins, config_binary)?) {
+            continue;
+        }
+        binaries.push(config_binary);
+    }
+    Ok(binaries)
+}
+
+/// Logs the binaries of the config file that aren't installed because they're not used on the target.
+fn log_skipped_platform_binaries<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &PluginsMut<TEnvironment>,
+    config_file: &configuration::ConfigFile,
+) -> Result<(), ErrBox> {
+    let target = plugins.get_target()?;
+    for config_binary in config_file.binaries.iter().filter(|b| !b.is_for_platform(target)) {
+        environment.log_error(&format!(
+            "Skipped {} because it's only used on {}.",
+            config_binary.path.path_or_url,
+            config_binary.platforms.as_ref().unwrap().join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Gets if the install of the config file binary failed because it doesn't support the target and it's
+/// optional, which is logged instead of failing the install.
+fn is_skipped_optional_binary(
+    environment: &impl Environment,
+    config_binary: &configuration::ConfigFileBinary,
+    err: &ErrBox,
+) -> bool {
+    if config_binary.optional && err.downcast_ref::<plugins::UnsupportedPlatformError>().is_some() {
+        environment.log_error(&format!(
+            "Skipped optional binary {}: {}",
+            config_binary.path.path_or_url, err
+        ));
+        true
+    } else {
+        false
+    }
+}
+
+fn get_config_binary_name<TEnvironment: Environment>(
+    plugins: &mut PluginsMut<TEnvironment>,
+    config_binary: &configuration::ConfigFileBinary,
+) -> Result<BinaryName, ErrBox> {
+    plugins.ensure_url_associated(&config_binary.path)?;
+    Ok(plugins
+        .manifest
+        .get_identifier_from_url(&config_binary.path)
+        .unwrap()
+        .get_binary_name())
+}
+
+/// Finds the version info of a registry tag. When not specified by the registry, the
+/// "latest" tag refers to the latest non-pre-release version.
+fn find_registry_tag_version_info<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    registry_files: &mut registry::RegistryFileCache,
+    urls: &[String],
+    name: &BinaryName,
+    tag: &str,
+) -> Result<Option<registry::RegistryVersionInfo>, ErrBox> {
+    let mut tag_version = None;
+    let mut version_infos = Vec::new();
+    for url in urls.iter() {
+        let registry_file = registry_files.get(environment, url)?;
+        if let Some(registry_binary) = registry_file.take_binary_with_name(name) {
+            if tag_version.is_none() {
+                tag_version = registry_binary.tags.get(tag).cloned();
+            }
+            version_infos.extend(registry_binary.versions);
+        }
+    }
+
+    let tag_version = match tag_version {
+        Some(tag_version) => tag_version,
+        None if tag == "latest" => {
+            match version_infos
+                .iter()
+                .filter(|info| !info.version.is_prerelease())
+                .map(|info| &info.version)
+                .max()
+            {
+                Some(version) => version.clone(),
+                None => return Ok(None),
+            }
+        }
+        None => return Ok(None),
+    };
+
+    match version_infos.into_iter().find(|info| info.version == tag_version) {
+        Some(version_info) => Ok(Some(version_info)),
+        None => err!(
+            "Registry tag '{}' of binary '{}' referred to version '{}', which could not be found in any registry.",
+            tag,
+            name,
+            tag_version
+        ),
+    }
+}
+
+/// Resolves the version aliases and registry tags of the config file binaries before installing.
+/// Registry tags are resolved from the registries and the installed binary will be the binary
+/// of the version the tag currently refers to.
+fn resolve_config_file_version_aliases_for_install<TEnvironment: Environment>(
+    environment: &TEnvironment,
+    plugins: &mut PluginsMut<TEnvironment>,
+    config_file: &mut configuration::ConfigFile,
+) -> Result<(), ErrBox> {
+    let mut registry_files = registry::RegistryFileCache::new();
+    let target = plugins.get_target()?;
+    for config_binary in config_file.binaries.iter_mut().filter(|b| b.is_for_platform(target)) {
+        let tag = match &config_binary.version_alias {
+            Some(tag) => tag.clone(),
+            None => continue,
+        };
+        let binary_name = get_config_binary_name(plugins, config_binary)?;
+        // user defined aliases take precedence over registry tags
+        if plugins.manifest.get_alias_version(&binary_name, &tag).is_some() {
+            continue;
+        }
+
+        let urls = registry::Registry::load(environment)
+            .get_urls(&binary_name.to_selector())
+            .into_iter()
+            .map(|r| r.url)
+            .collect::<Vec<_>>();
+        match find_registry_tag_version_info(environment, &mut registry_files, &urls, &binary_name, &tag)? {
+            Some(version_info) => {
+                plugins.set_registry_tag(binary_name, tag, version_info.version.clone());
+                config_binary.version = Some(version_info.version.to_selector());
+                config_binary.path = version_info.get_url();
+            }
+            None => {
+                return err!(
+                    "Could not find an alias or registry tag '{}' for '{}'. Create an alias with `bvm alias set {} {} <version>`.",
+                    tag,
+                    binary_name,
+                    binary_name,
+                    tag
+                )
+            }
+        }
+    }
+
+    resolve_config_file_version_aliases(plugins, config_file)
+}
+
+/// Resolves the versions of any binaries in the config file that specify a version alias.
+fn resolve_config_file_version_aliases<TEnvironment: Environment>(
+    plugins: &mut PluginsMut<TEnvironment>,
+    config_file: &mut configuration::ConfigFile,
+) -> Result<(), ErrBox> {
+    // associate the urls in order to be able to tell the binary names
+    let target = plugins.get_target()?;
+    for config_binary in config_file.binaries.iter().filter(|b| b.is_for_platform(target)) {
+        if config_binary.version_alias.is_some() && config_binary.version.is_none() {
+            plugins.ensure_url_associated(&config_binary.path)?;
+        }
+    }
+    resolve_config_file_version_aliases_for_manifest(&plugins.manifest, config_file)
+}
+
+/// Resolves the version aliases of the config file binaries whose urls are associated in the manifest.
+/// Registry tags are resolved to the version they referred to when last installed.
+fn resolve_config_file_version_aliases_for_manifest(
+    plugin_manifest: &PluginsManifest,
+    config_file: &mut configuration::ConfigFile,
+) -> Result<(), ErrBox> {
+    for config_binary in config_file.binaries.iter_mut().filter(|b| b.version.is_none()) {
+        if let Some(alias) = &config_binary.version_alias {
+            if let Some(identifier) = plugin_manifest.get_identifier_from_url(&config_binary.path) {
+                let binary_name = identifier.get_binary_name();
+                let version = plugin_manifest
+                    .get_alias_version(&binary_name, alias)
+                    .or_else(|| plugin_manifest.get_registry_tag_version(&binary_name, alias));
+                match version {
+                    Some(version) => config_binary.version = Some(version.to_selector()),
+                    None => {
+                        return err!(
+                            "Could not find alias '{}' for '{}'. Create one with `bvm alias set {} {} <version>` or run `bvm install` if it is a registry tag.",
+                            alias,
+                            binary_name,
+                            binary_name,
+                            alias
+                        )
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn get_config_file_or_error(environment: &impl Environment) -> Result<(PathBuf, configuration::ConfigFile), ErrBox> {
+    match get_config_file(environment)? {
+        Some(config_file) => Ok(config_file),
+        None => {
+            coded_err!(
+                ErrorCode::ConfigNotFound,
+                "Could not find a bvm configuration file in the current directory or its ancestors. Perhaps create one with `bvm init`?"
+            )
+        }
+    }
+}
+
+fn get_config_file(environment: &impl Environment) -> Result<Option<(PathBuf, configuration::ConfigFile)>, ErrBox> {
+    if let Some(config_file_path) = configuration::find_config_file(environment)? {
+        log_verbose!(environment, "Using config file {}.", config_file_path.display());
+        let config_file = read_config_file_at_path(environment, &config_file_path)?;
+        Ok(Some((config_file_path, config_file)))
+    } else {
+        Ok(None)
+    }
+}
+
+fn read_config_file_at_path(
+    environment: &impl Environment,
+    config_file_path: &Path,
+) -> Result<configuration::ConfigFile, ErrBox> {
+    let config_file_text = environment.read_file_text(config_file_path)?;
+    let format = configuration::ConfigFileFormat::from_path(config_file_path);
+    let config_file = match configuration::read_config_file(&config_file_text, format) {
+        Ok(file) => file,
+        Err(err) => {
+            return coded_err!(
+                ErrorCode::InvalidConfig,
+                "Error reading {}: {}",
+                config_file_path.display(),
+                err.to_string()
+            )
+        }
+    };
+    configuration::resolve_config_file_extends(environment, config_file_path, config_file)
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    use super::registry;
+    use super::{resolve_command_path, run};
+    use crate::environment::{Environment, Event, PromptMode, TestEnvironment, SYS_PATH_DELIMITER};
+    use crate::errors::{get_error_code, ErrorCode};
+    use crate::test_builders::{EnvironmentBuilder, PluginDownloadType};
+    use crate::types::{BinaryName, CommandName, Version};
+    use crate::utils;
+    use dprint_cli_core::types::ErrBox;
+
+    pub const PATH_SEPARATOR: &'static str = if cfg!(target_os = "windows") { "\\" } else { "/" };
+
+    macro_rules! assert_logs {
+        ($environment:expr, []) => {
+            let logged_messages = $environment.take_logged_messages();
+            assert_eq!(logged_messages, Vec::<String>::new());
+        };
+        ($environment:expr, $messages:expr) => {
+            let logged_messages = $environment.take_logged_messages();
+            assert_eq!(logged_messages, $messages);
+        };
+    }
+
+    macro_rules! assert_logs_errors {
+        ($environment:expr, []) => {
+            let errors = $environment.take_logged_errors();
+            assert_eq!(errors, Vec::<String>::new());
+        };
+        ($environment:expr, $errors:expr) => {
+            let errors = $environment.take_logged_errors();
+            assert_eq!(errors, $errors);
+        };
+    }
+
+    macro_rules! assert_has_path {
+        ($environment:expr, $path:expr) => {
+            assert_eq!($environment.path_exists(&PathBuf::from($path)), true);
+        };
+    }
+
+    macro_rules! assert_not_has_path {
+        ($environment:expr, $path:expr) => {
+            assert_eq!($environment.path_exists(&PathBuf::from($path)), false);
+        };
+    }
+
+    macro_rules! assert_resolves_name {
+        ($environment:expr, $name:expr, $binary_path:expr) => {
+            run_cli(vec!["hidden", "resolve-command", $name], &$environment).unwrap();
+            assert_logs!($environment, ["EXEC".to_string(), $binary_path.clone()]);
+        };
+    }
+
+    macro_rules! assert_resolves {
+        ($environment:expr, $binary_path:expr) => {
+            assert_resolves_name!($environment, "name", $binary_path)
+        };
+    }
+
+    macro_rules! assert_get_paths {
+        ($environment:expr, []) => {
+            assert_get_paths!($environment, Vec::<String>::new());
+        };
+        ($environment:expr, $paths:expr) => {
+            run_cli(vec!["hidden", "get-paths"], &$environment).unwrap();
+            let paths_text = $paths.join(SYS_PATH_DELIMITER);
+            assert_logs!($environment, [paths_text]);
+        };
+    }
+
+    macro_rules! assert_get_env_vars {
+        ($environment:expr, [$(($key:expr, $value:expr)),*]) => {
+            run_cli(vec!["hidden", "get-env-vars"], &$environment)
+                .unwrap();
+
+            #[allow(unused_mut)]
+            let mut expected_logs = Vec::<String>::new();
+            if cfg!(target_os="windows") {
+                $(
+                    expected_logs.push(format!("SET {}={}", $key, $value));
+                )*
+            } else {
+                $(
+                    expected_logs.push("ADD".to_string());
+                    expected_logs.push($key.to_string());
+                    expected_logs.push($value.to_string());
+                )*
+            }
+            assert_logs!($environment, expected_logs);
+        };
+    }
+
+    macro_rules! assert_get_pending_env_changes {
+        ($environment:expr, [$(($key:expr, $value:expr)),*], [$($remove_key:expr),*], $new_path:expr) => {
+            run_cli(vec!["hidden", "get-pending-env-changes"], &$environment)
+                .unwrap();
+            assert_logged_env_changes!($environment, [$(($key, $value)),*], [$($remove_key),*], $new_path);
+        };
+    }
+
+    macro_rules! assert_logged_env_changes {
+        ($environment:expr, [$(($key:expr, $value:expr)),*], [$($remove_key:expr),*], $new_path:expr) => {
+            let mut expected_logs = Vec::<String>::new();
+            $(
+                if cfg!(target_os="windows") {
+                    expected_logs.push(format!("SET {}=", $remove_key));
+                } else {
+                    expected_logs.push("REMOVE".to_string());
+                    expected_logs.push($remove_key.to_string());
+                }
+            )*
+
+            $(
+                if cfg!(target_os="windows") {
+                    expected_logs.push(format!("SET {}={}", $key, $value));
+                } else {
+                    expected_logs.push("ADD".to_string());
+                    expected_logs.push($key.to_string());
+                    expected_logs.push($value.to_string());
+                }
+            )*
+
+            if !$new_path.is_empty() {
+                if cfg!(target_os="windows") {
+                    expected_logs.push(format!("SET PATH={}", $new_path));
+                } else {
+                    expected_logs.push("ADD".to_string());
+                    expected_logs.push("PATH".to_string());
+                    expected_logs.push($new_path.to_string());
+                }
+            }
+
+            assert_logs!($environment, expected_logs);
+        };
+    }
+
+    macro_rules! assert_exec_command_path {
+        ($environment:expr, $name:expr, $version:expr, $command:expr, $binary_path:expr) => {
+            run_cli(
+                vec!["hidden", "get-exec-command-path", $name, $version, $command],
+                &$environment,
+            )
+            .unwrap();
+            assert_logs!($environment, vec![$binary_path.clone()]);
+        };
+    }
+
+    macro_rules! assert_has_command {
+        ($environment:expr, $name:expr, $version:expr, $command:expr, $result:expr) => {
+            run_cli(
+                vec!["hidden", "has-command", $name, $version, $command],
+                &$environment,
+            )
+            .unwrap();
+            assert_logs!($environment, [$result.to_string()]);
+        };
+    }
+
+    macro_rules! install_url {
+        ($environment:expr, $url:expr) => {
+            run_cli(vec!["install", $url], &$environment).unwrap();
+        };
+    }
+
+    #[test]
+    fn should_output_version() {
+        let environment = TestEnvironment::new();
+        run_cli(vec!["--version"], &environment).unwrap();
+        assert_logs!(environment, [format!("bvm {}", env!("CARGO_PKG_VERSION"))]);
+    }
+
+    #[test]
+    fn should_init() {
+        let environment = TestEnvironment::new();
+        run_cli(vec!["init"], &environment).unwrap();
+        assert_logs!(environment, ["Created bvm.json"]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("bvm.json")).unwrap(),
+            "{\n  \"binaries\": [\n  ]\n}\n"
+        );
+    }
+
+    #[test]
+    fn should_error_if_init_has_file() {
+        let environment = TestEnvironment::new();
+        environment.write_file_text(&PathBuf::from("bvm.json"), "").unwrap();
+        let error_text = run_cli(vec!["init"], &environment).err().unwrap();
+        assert_eq!(
+            error_text.to_string(),
+            "A bvm.json file already exists in the current directory."
+        );
+    }
+
+    #[test]
+    fn should_error_if_init_has_hidden_file() {
+        let environment = TestEnvironment::new();
+        environment.write_file_text(&PathBuf::from(".bvm.json"), "").unwrap();
+        let error_text = run_cli(vec!["init"], &environment).err().unwrap();
+        assert_eq!(
+            error_text.to_string(),
+            "A .bvm.json file already exists in the current directory."
+        );
+    }
+
+    #[test]
+    fn install_url_command_no_path() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+
+        // install the package
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        // check setup was correct
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        assert_has_path!(environment, &binary_path);
+        assert_has_path!(environment, &get_shim_path("name"));
+
+        // try to resolve the command globally
+        assert_resolves!(environment, binary_path);
+
+        // try to use the path version, it should fail
+        let error_message = run_cli(vec!["use", "name", "path"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find any installed binaries on the path that matched 'name'."
+        );
+    }
+
+    #[test]
+    fn install_url_command_path() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let path_exe_path = builder.add_binary_to_path("name");
+        let environment = builder.build();
+
+        // install the package
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/name 1.0.0...",
+                "Installed. Run `bvm use name 1.0.0` to use it on the path as 'name'."
+            ]
+        );
+
+        // try to resolve globally, it should use command on path
+        assert_resolves!(environment, path_exe_path);
+
+        // now use the installed version
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        assert_resolves!(environment, binary_path);
+
+        // switch back to the path
+        run_cli(vec!["use", "name", "path"], &environment).unwrap();
+        assert_resolves!(&environment, path_exe_path);
+    }
+
+    #[test]
+    fn install_url_command_previous_install() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        let third_binary_path = get_binary_path("owner", "name", "3.0.0");
+        let fourth_binary_path = get_binary_path("owner", "name", "4.0.0");
+        let fourth_binary_path_second = get_binary_path_second("owner", "name", "4.0.0");
+
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "3.0.0");
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package4.json", "owner", "name", "4.0.0");
+        let environment = builder.build();
+
+        // install the first package
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0...",]);
+
+        // now install the second
+        install_url!(environment, "http://localhost/package2.json");
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/name 2.0.0...",
+                "Installed. Run `bvm use name 2.0.0` to use it on the path as 'name'."
+            ]
+        );
+        assert_resolves!(&environment, first_binary_path);
+
+        // use the second package
+        run_cli(vec!["use", "name", "2.0.0"], &environment).unwrap();
+        assert_resolves!(&environment, second_binary_path);
+
+        // install the third package with --use
+        run_cli(vec!["install", "--use", "http://localhost/package3.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 3.0.0...",]);
+        assert_resolves!(&environment, third_binary_path);
+
+        // install the fourth package
+        install_url!(environment, "http://localhost/package4.json");
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/name 4.0.0...",
+                "Installed. Run `bvm use name 4.0.0` to use it on the path as 'name' and 'name-second'."
+            ]
+        );
+        assert_resolves!(&environment, third_binary_path);
+
+        // now install the fourth package again, but with --use
+        run_cli(vec!["install", "--use", "http://localhost/package4.json"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Already installed. Provide the `--force` flag to reinstall."]
+        );
+        assert_resolves!(&environment, fourth_binary_path);
+        assert_resolves_name!(&environment, "name-second", fourth_binary_path_second);
+
+        // now install with --force
+        run_cli(
+            vec!["install", "--force", "http://localhost/package4.json"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 4.0.0...",]);
+        assert_resolves!(&environment, fourth_binary_path);
+        assert_resolves_name!(&environment, "name-second", fourth_binary_path_second);
+    }
+
+    #[test]
+    fn install_url_command_tar_gz() {
+        let builder = EnvironmentBuilder::new();
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+
+        builder.create_remote_tar_gz_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+
+        // install and check setup (the archive is extracted while it's downloaded)
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, []);
+        assert_eq!(environment.take_streamed_urls().len(), 1);
+        assert_has_path!(environment, &binary_path);
+        assert_has_path!(environment, &get_shim_path("name"));
+
+        // yeah, this isn't realistic, but it's just some dummy data to ensure the file was extracted correctly
+        if cfg!(target_os = "windows") {
+            assert_eq!(
+                environment.read_file_text(&PathBuf::from(binary_path)).unwrap(),
+                "test-name-https://github.com/dsherret/bvm/releases/download/1.0.0/name-windows.tar.gz"
+            );
+        }
+    }
+
+    #[test]
+    fn install_url_cancelled() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_tar_gz_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        let url = "https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux.tar.gz";
+        environment.set_remote_file_cancelled(url);
+
+        let err = run_cli(vec!["install", "http://localhost/package.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Error installing http://localhost/package.json. Cancelled downloading {}.",
+                url
+            )
+        );
+        assert_eq!(get_error_code(&err), ErrorCode::Cancelled);
+        // the partially extracted files and the manifest lock were removed and the binary wasn't added
+        assert_eq!(
+            environment.get_file_paths(&PathBuf::from("/data")).unwrap(),
+            vec![PathBuf::from("/data/binaries-manifest.json")]
+        );
+        let err = run_cli(vec!["resolve", "name"], &environment).err().unwrap();
+        assert_eq!(get_error_code(&err), ErrorCode::NotInstalled);
+    }
+
+    #[test]
+    fn install_url_github_release() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_github_release("owner", "name", "v1.0.0");
+        let environment = builder.build();
+
+        install_url!(environment, "gh:owner/name@v1.0.0");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        assert_has_path!(environment, &binary_path);
+        assert_resolves!(environment, binary_path);
+        // the release doesn't provide a digest, so the asset is only downloaded to install it
+        let downloaded_assets = environment
+            .take_downloaded_urls()
+            .into_iter()
+            .filter(|url| url.starts_with("https://github.com/"))
+            .collect::<Vec<_>>();
+        assert_eq!(downloaded_assets.len(), 1);
+
+        let error_message = run_cli(vec!["install", "gh:owner/name@v2.0.0"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing gh:owner/name@v2.0.0. Could not find file at url https://api.github.com/repos/owner/name/releases/tags/v2.0.0"
+        );
+    }
+
+    #[test]
+    fn install_url_local_plugin_file() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_local_zip_package("/project/tools/name.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        run_cli(vec!["install", "./tools/name.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        assert_has_path!(environment, &binary_path);
+        assert_resolves!(environment, binary_path);
+
+        // file urls resolve to the same binary
+        run_cli(vec!["install", "file:///project/tools/name.json"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Already installed. Provide the `--force` flag to reinstall."]
+        );
+    }
+
+    #[test]
+    fn install_command_local_plugin_file() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_local_zip_package("/project/tools/name.json", "owner", "name", "1.0.0");
+        builder.create_bvmrc(vec!["./tools/name.json"]);
+        let environment = builder.build();
+        environment.set_cwd("/project/sub-dir");
+
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn install_archive_url() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_archive("http://localhost/name.zip", vec!["name", "name-second"]);
+        let environment = builder.build();
+
+        let args = vec![
+            "install",
+            "--name",
+            "owner/name",
+            "--version",
+            "1.2.3",
+            "http://localhost/name.zip",
+        ];
+        run_cli(args.clone(), &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.2.3..."]);
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.2.3"));
+        assert_resolves_name!(
+            environment,
+            "name-second",
+            get_binary_path_second("owner", "name", "1.2.3")
+        );
+
+        run_cli(args, &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Already installed. Provide the `--force` flag to reinstall."]
+        );
+
+        let error_message = run_cli(
+            vec![
+                "install",
+                "--name",
+                "name",
+                "--version",
+                "1.2.3",
+                "http://localhost/name.zip",
+            ],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Expected the `--name` to include an owner (ex. `owner/tool`), but found 'name'."
+        );
+    }
+
+    #[test]
+    fn install_archive_url_no_executables() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_archive("http://localhost/name.zip", vec![]);
+        let environment = builder.build();
+
+        let error_message = run_cli(
+            vec![
+                "install",
+                "--name",
+                "owner/name",
+                "--version",
+                "1.0.0",
+                "http://localhost/name.zip",
+            ],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/name.zip. Could not find any executables in the archive for owner/name 1.0.0."
+        );
+        environment.clear_logs();
+    }
+
+    #[test]
+    fn install_content_store() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_archive("http://localhost/name.zip", vec!["name"]);
+        let environment = builder.build();
+        environment
+            .write_file_text(&PathBuf::from("/data/config.json"), r#"{ "contentStore": true }"#)
+            .unwrap();
+        let install_version = |version: &str| {
+            run_cli(
+                vec![
+                    "install",
+                    "--name",
+                    "owner/name",
+                    "--version",
+                    version,
+                    "http://localhost/name.zip",
+                ],
+                &environment,
+            )
+            .unwrap();
+        };
+        install_version("1.0.0");
+        install_version("2.0.0");
+        environment.clear_logs();
+
+        // both versions are linked from the same store file
+        let store_file_paths = environment.get_file_paths(&PathBuf::from("/local-data/store")).unwrap();
+        assert_eq!(store_file_paths.len(), 1);
+        let store_file_bytes = environment.read_file(&store_file_paths[0]).unwrap();
+        for version in ["1.0.0", "2.0.0"].iter() {
+            let binary_path = PathBuf::from(get_binary_path("owner", "name", version));
+            assert_eq!(environment.read_file(&binary_path).unwrap(), store_file_bytes);
+        }
+
+        // the store file is only removed once no binary uses it
+        run_cli(vec!["uninstall", "owner/name", "1.0.0"], &environment).unwrap();
+        assert_has_path!(environment, &store_file_paths[0].to_string_lossy().to_string());
+        run_cli(vec!["uninstall", "owner/name", "2.0.0"], &environment).unwrap();
+        assert!(!environment.path_exists(&store_file_paths[0]));
+    }
+
+    #[test]
+    fn install_lazy_extraction() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        run_cli(vec!["install", "--lazy", "http://localhost/package.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        // only the archive is stored until a command is run
+        let binary_dir = PathBuf::from(get_binary_dir("owner", "name", "1.0.0"));
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        assert_eq!(environment.path_exists(&PathBuf::from(&binary_path)), false);
+        assert_eq!(environment.path_exists(&binary_dir.join(".bvm-archive")), true);
+        let binary_file_path = PathBuf::from("/data/binaries-manifest")
+            .join("owner")
+            .join("name")
+            .join("1.0.0.json");
+        let is_pending = || {
+            environment
+                .read_file_text(&binary_file_path)
+                .unwrap()
+                .contains("pendingExtraction")
+        };
+        assert_eq!(is_pending(), true);
+
+        assert_resolves!(environment, binary_path);
+        assert_has_path!(environment, &binary_path);
+        assert_eq!(environment.path_exists(&binary_dir.join(".bvm-archive")), false);
+        assert_eq!(is_pending(), false);
+
+        // only extracts once
+        assert_resolves!(environment, binary_path);
+
+        // extracts when installing normally
+        run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_has_path!(environment, &binary_path);
+        assert_eq!(environment.path_exists(&binary_dir.join(".bvm-archive")), false);
+    }
+
+    #[test]
+    fn install_dry_run() {
+        let builder = EnvironmentBuilder::new();
+        for version in 1..=3 {
+            builder.create_remote_zip_package(
+                &format!("http://localhost/package{}.json", version),
+                "owner",
+                "name",
+                &format!("{}.0.0", version),
+            );
+        }
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package3.json", "http://localhost/other.json"]);
+        let environment = builder.build();
+        environment.set_cwd("/project");
+        environment
+            .write_file_text(&PathBuf::from("/data/config.json"), r#"{ "keepVersions": 1 }"#)
+            .unwrap();
+        install_url!(environment, "http://localhost/package1.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/other.json");
+        environment.clear_logs();
+        environment.take_written_files();
+        environment.take_downloaded_urls();
+        let os = if cfg!(target_os = "windows") {
+            "windows"
+        } else if cfg!(target_os = "macos") {
+            "mac"
+        } else {
+            "linux"
+        };
+
+        // 1.0.0 is kept because it's the global version
+        run_cli(vec!["install", "--dry-run"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [format!(
+                concat!(
+                    "Would install owner/name 3.0.0 from https://github.com/dsherret/bvm/releases/download/3.0.0/name-{}.zip\n",
+                    "Already installed owner/other 1.0.0\n",
+                    "Would remove owner/name 2.0.0 to keep the most recent versions"
+                ),
+                os
+            )]
+        );
+        // only the binary manifest file is downloaded
+        assert_eq!(environment.take_written_files(), Vec::<PathBuf>::new());
+        assert_eq!(
+            environment.take_downloaded_urls(),
+            vec!["http://localhost/package3.json".to_string()]
+        );
+        assert_not_has_path!(environment, get_binary_path("owner", "name", "3.0.0"));
+        assert_has_path!(environment, get_binary_path("owner", "name", "2.0.0"));
+
+        run_cli(
+            vec!["install", "--dry-run", "--json", "http://localhost/package3.json"],
+            &environment,
+        )
+        .unwrap();
+        let logged_messages = environment.take_logged_messages();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&logged_messages[0]).unwrap(),
+            serde_json::json!({
+                "install": [{
+                    "name": "owner/name",
+                    "version": "3.0.0",
+                    "url": format!("https://github.com/dsherret/bvm/releases/download/3.0.0/name-{}.zip", os),
+                }],
+                "alreadyInstalled": [],
+                "remove": [{ "name": "owner/name", "version": "2.0.0" }],
+            })
+        );
+        assert_eq!(environment.take_written_files(), Vec::<PathBuf>::new());
+
+        run_cli(
+            vec!["install", "--dry-run", "http://localhost/other.json"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs!(environment, ["Already installed owner/other 1.0.0"]);
+
+        let error_message = run_cli(vec!["install", "--json"], &environment).err().unwrap();
+        assert!(error_message
+            .to_string()
+            .contains("The following required arguments were not provided:\n    --dry-run"));
+    }
+
+    #[test]
+    fn install_keep_versions() {
+        let builder = EnvironmentBuilder::new();
+        for version in 1..=5 {
+            builder.create_remote_zip_package(
+                &format!("http://localhost/package{}.json", version),
+                "owner",
+                "name",
+                &format!("{}.0.0", version),
+            );
+        }
+        builder.create_remote_archive("http://localhost/other.zip", vec!["other"]);
+        builder.create_bvmrc(vec!["http://localhost/package3.json"]);
+        let environment = builder.build();
+        environment.set_cwd("/project");
+        environment
+            .write_file_text(
+                &PathBuf::from("/data/config.json"),
+                r#"{ "keepVersions": 2, "binaries": { "other": { "keepVersions": 1 } } }"#,
+            )
+            .unwrap();
+        install_url!(environment, "http://localhost/package1.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        environment.clear_logs();
+
+        install_url!(environment, "http://localhost/package4.json");
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/name 4.0.0...",
+                "Installed. Run `bvm use name 4.0.0` to use it on the path as 'name'.",
+                "Removed owner/name 2.0.0 to keep the most recent versions."
+            ]
+        );
+        // 1.0.0 is kept because it's the global version
+        assert_has_path!(environment, get_binary_path("owner", "name", "1.0.0"));
+        assert_not_has_path!(environment, get_binary_path("owner", "name", "2.0.0"));
+
+        // 3.0.0 is kept because it's pinned by the config file
+        install_url!(environment, "http://localhost/package5.json");
+        environment.clear_logs();
+        assert_has_path!(environment, get_binary_path("owner", "name", "3.0.0"));
+        assert_has_path!(environment, get_binary_path("owner", "name", "4.0.0"));
+
+        // the binary specific setting overrides the global one
+        let install_other_version = |version: &str| {
+            run_cli(
+                vec![
+                    "install",
+                    "--name",
+                    "owner/other",
+                    "--version",
+                    version,
+                    "http://localhost/other.zip",
+                ],
+                &environment,
+            )
+            .unwrap();
+        };
+        install_other_version("1.0.0");
+        install_other_version("2.0.0");
+        run_cli(vec!["use", "--global", "owner/other", "2.0.0"], &environment).unwrap();
+        install_other_version("3.0.0");
+        environment.clear_logs();
+        assert_not_has_path!(environment, get_binary_path("owner", "other", "1.0.0"));
+        assert_has_path!(environment, get_binary_path("owner", "other", "2.0.0"));
+        assert_has_path!(environment, get_binary_path("owner", "other", "3.0.0"));
+    }
+
+    #[test]
+    fn install_url_command_use_with_config_file_same_command() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner2", "name", "2.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json"]);
+        let environment = builder.build();
+
+        // install the package
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        // install and use the other package
+        run_cli(vec!["install", "--use", "http://localhost/package2.json"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner2/name 2.0.0...",
+                concat!(
+                    "Updated globally used version of 'name', but local version remains using version specified ",
+                    "in the current working directory's config file. If you wish to change the local version, ",
+                    "then update your configuration file (check the cwd and ancestor directories for a bvm ",
+                    "configuration file)."
+                )
+            ]
+        );
+
+        // should still resolve to the cwd's binary
+        assert_resolves!(&environment, first_binary_path);
+    }
+
+    #[test]
+    fn install_url_pre_and_post_install() {
+        let builder = EnvironmentBuilder::new();
+        let first_staging_dir = get_binary_staging_dir("owner", "name", "1.0.0");
+        let binary_dir = get_binary_dir("owner", "name", "1.0.0");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.on_pre_install("command1");
+        plugin_builder.on_post_install("command2");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [
+                (first_staging_dir, "command1".to_string()),
+                // the post install command is run once the binary is in its directory
+                (binary_dir, "command2".to_string()),
+            ]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn install_url_retries_transient_failures() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        let zip_url = "https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux.zip";
+        environment.add_remote_file_failures("http://localhost/package.json", vec![503]);
+        environment.add_remote_file_failures(zip_url, vec![502]);
+
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(
+            environment,
+            [
+                "Warning: Error downloading http://localhost/package.json. Status: 503. Retrying in 1s (attempt 2 of 3)...",
+                "Warning: Error downloading https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux.zip. Status: 502. Retrying in 1s (attempt 2 of 3)...",
+                "Extracting archive for owner/name 1.0.0..."
+            ]
+        );
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+
+        // the backoff doubles and the policy is configurable
+        run_cli(
+            vec![
+                "config",
+                "set",
+                "downloadRetry",
+                r#"{ "attempts": 3, "backoffMs": 500, "retryOnStatus": [502] }"#,
+            ],
+            &environment,
+        )
+        .unwrap();
+        environment.take_sleep_durations();
+        environment.add_remote_file_failures(zip_url, vec![502, 502, 502]);
+        let error_message = run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            format!(
+                "Error installing http://localhost/package.json. Error downloading {}. Status: 502",
+                zip_url
+            )
+        );
+        environment.clear_logs();
+        assert_eq!(
+            environment.take_sleep_durations(),
+            [
+                std::time::Duration::from_millis(500),
+                std::time::Duration::from_millis(1000)
+            ]
+        );
+
+        // statuses that aren't transient fail immediately
+        environment.add_remote_file_failures(zip_url, vec![404]);
+        let err = run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Error installing http://localhost/package.json. Error downloading {}. Status: 404",
+                zip_url
+            )
+        );
+        assert_eq!(
+            crate::environment::format_error_log_message(crate::environment::LogFormat::Json, &err),
+            serde_json::json!({
+                "level": "error",
+                "code": "download-status",
+                "message": err.to_string(),
+            })
+            .to_string()
+        );
+        assert!(environment.take_sleep_durations().is_empty());
+    }
+
+    #[test]
+    fn max_download_speed() {
+        let environment = TestEnvironment::new();
+        assert_eq!(environment.get_max_download_speed(), None);
+        run_cli(vec!["list", "--max-download-speed", "500K"], &environment).unwrap();
+        assert_eq!(environment.get_max_download_speed(), Some(512_000));
+
+        let error_message = run_cli(vec!["list", "--max-download-speed", "fast"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Invalid size 'fast'. Expected a number optionally followed by K, M, or G (ex. 500K)."
+        );
+
+        run_cli(vec!["config", "set", "maxDownloadSpeed", "2M"], &environment).unwrap();
+        let error_message = run_cli(vec!["config", "set", "maxDownloadSpeed", "fast"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Invalid value for 'maxDownloadSpeed'. Invalid size 'fast'. Expected a number optionally followed by K, M, or G (ex. 500K)."
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn install_url_smoke_test() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder
+            .smoke_test(vec!["--version"], Some(r"^name 1\.0\.0"))
+            .download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+        environment.set_executable_output("name", 0, "name 1.0.0\n");
+
+        install_url!(environment, "http://localhost/package.json");
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+        environment.clear_logs();
+
+        // fails when the output doesn't match and keeps the existing install
+        environment.set_executable_output("name", 0, "other 2.0.0\n");
+        let error_message = run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package.json. Did not install owner/name 1.0.0 because its smoke test failed. The output of `name --version` did not match `^name 1\\.0\\.0`.\nother 2.0.0"
+        );
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+        assert_has_path!(environment, &get_binary_path("owner", "name", "1.0.0"));
+        assert_eq!(
+            environment.path_exists(&PathBuf::from("/local-data/staging/owner/name/1.0.0")),
+            false
+        );
+        assert_eq!(
+            environment.is_dir(&PathBuf::from("/local-data/staging/owner/name/1.0.0.previous")),
+            false
+        );
+
+        // fails when the binary doesn't execute
+        environment.set_executable_output(
+            "name",
+            127,
+            "name: error while loading shared libraries: libc.so.6: cannot open shared object file",
+        );
+        let error_message = run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            concat!(
+                "Error installing http://localhost/package.json. Did not install owner/name 1.0.0 because its smoke test failed. ",
+                "`name --version` exited with code 127. It may be built for a different architecture or need a library that isn't installed.\n",
+                "name: error while loading shared libraries: libc.so.6: cannot open shared object file"
+            )
+        );
+        environment.clear_logs();
+
+        // skipped when installing for a provided target
+        run_cli(
+            vec![
+                "install",
+                "--force",
+                "--target",
+                "linux-x86_64",
+                "http://localhost/package.json",
+            ],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn install_url_mirrors() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder
+            .download_type(PluginDownloadType::Zip)
+            .move_archives_to_mirror("https://mirror.example.com");
+        plugin_builder.build();
+        let environment = builder.build();
+
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(
+            environment,
+            [
+                "Warning: Falling back to https://mirror.example.com/dsherret/bvm/releases/download/1.0.0/name-linux.zip after failing to download https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux.zip: Could not find file at url https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux.zip",
+                "Extracting archive for owner/name 1.0.0..."
+            ]
+        );
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+        // the mirror is recorded as where the binary came from
+        assert_eq!(
+            get_binary_provenance(&environment, "owner", "name", "1.0.0").url,
+            "https://mirror.example.com/dsherret/bvm/releases/download/1.0.0/name-linux.zip"
+        );
+
+        // a mirror that doesn't match the checksum isn't used
+        environment.add_remote_file(
+            "https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux.zip",
+            b"corrupted".to_vec(),
+        );
+        environment
+            .remove_remote_file("https://mirror.example.com/dsherret/bvm/releases/download/1.0.0/name-linux.zip");
+        let error_message = run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package.json. Could not find file at url https://mirror.example.com/dsherret/bvm/releases/download/1.0.0/name-linux.zip"
+        );
+        let logged_errors = environment.take_logged_errors();
+        assert_eq!(logged_errors.len(), 1);
+        assert!(logged_errors[0].contains("did not match the expected checksum"));
+        // the download that didn't match is kept for inspecting it
+        assert_has_path!(
+            environment,
+            &format!(
+                "/data/quarantine/{}-name-linux.zip",
+                dprint_cli_core::checksums::get_sha256_checksum(b"corrupted")
+            )
+        );
+    }
+
+    #[test]
+    fn install_url_source_provider() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder = builder.create_plugin_builder(
+            "https://example.jfrog.io/artifactory/generic-local/package.json",
+            "owner",
+            "name",
+            "1.0.0",
+        );
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+
+        // served from the generic repository of the Artifactory instance
+        install_url!(environment, "artifactory://example.jfrog.io/generic-local/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+
+        let error_message = run_cli(vec!["install", "oci://ghcr.io/owner/name:1.0.0"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing oci://ghcr.io/owner/name:1.0.0. Error resolving oci://ghcr.io/owner/name:1.0.0. Expected a repository and the digest of a blob (ex. oci://ghcr.io/owner/repo@sha256:...)."
+        );
+    }
+
+    #[test]
+    fn install_url_tar_gz_mirrors() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_tar_gz_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder
+            .download_type(PluginDownloadType::TarGz)
+            .move_archives_to_mirror("https://mirror.example.com");
+        plugin_builder.build();
+        let environment = builder.build();
+        // serve a valid archive that doesn't match the checksum from the original location
+        let other_archive_bytes = environment
+            .remove_remote_file("https://github.com/dsherret/bvm/releases/download/1.0.0/other-linux.tar.gz")
+            .unwrap();
+        environment.add_remote_file(
+            "https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux.tar.gz",
+            other_archive_bytes,
+        );
+
+        install_url!(environment, "http://localhost/package.json");
+        let logged_errors = environment.take_logged_errors();
+        assert_eq!(logged_errors.len(), 1);
+        assert!(logged_errors[0].contains("did not match the expected checksum"));
+        assert_eq!(
+            environment.take_streamed_urls(),
+            vec![
+                "https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux.tar.gz",
+                "https://mirror.example.com/dsherret/bvm/releases/download/1.0.0/name-linux.tar.gz",
+            ]
+        );
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+        // the files extracted from the archive that didn't match were removed
+        assert_eq!(
+            environment.path_exists(&PathBuf::from(get_binary_path("owner", "name", "1.0.0")).with_file_name("other")),
+            false
+        );
+    }
+
+    #[test]
+    fn uninstall_pre_uninstall() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.on_pre_uninstall("remove-caches");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+        assert_eq!(environment.take_run_shell_commands(), []);
+
+        run_cli(vec!["uninstall", "name", "1.0.0"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [(get_binary_dir("owner", "name", "1.0.0"), "remove-caches".to_string())]
+        );
+        assert_not_has_path!(environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn user_hooks() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        run_cli(
+            vec![
+                "config",
+                "set",
+                "hooks",
+                r#"{"postInstall":["notify install"],"postUse":["notify use"],"preUninstall":["notify uninstall"]}"#,
+            ],
+            &environment,
+        )
+        .unwrap();
+        // the hooks run once the manifest lock is released so they may run bvm commands that take it
+        let is_locked_values = Arc::new(Mutex::new(Vec::new()));
+        environment.add_shell_command_callback("notify install", {
+            let is_locked_values = is_locked_values.clone();
+            move |environment| {
+                let lock_file_path = PathBuf::from("/data/binaries-manifest.json.lock");
+                is_locked_values
+                    .lock()
+                    .unwrap()
+                    .push(environment.path_exists(&lock_file_path));
+            }
+        });
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+        assert_eq!(is_locked_values.lock().unwrap().clone(), vec![false]);
+        let cwd = environment.cwd().unwrap().to_string_lossy().to_string();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [
+                (cwd.clone(), "notify install".to_string()),
+                (cwd.clone(), "notify use".to_string())
+            ]
+        );
+        let binary_dir = get_binary_dir("owner", "name", "1.0.0");
+        let inputs = environment.take_run_shell_command_inputs();
+        assert_eq!(
+            inputs[0],
+            (
+                vec![
+                    ("BVM_HOOK_EVENT".to_string(), "post-install".to_string()),
+                    ("BVM_HOOK_OWNER".to_string(), "owner".to_string()),
+                    ("BVM_HOOK_NAME".to_string(), "name".to_string()),
+                    ("BVM_HOOK_VERSION".to_string(), "1.0.0".to_string()),
+                    ("BVM_HOOK_DIR".to_string(), binary_dir.clone()),
+                ],
+                format!(
+                    r#"{{"command":null,"dir":{},"event":"post-install","name":"name","owner":"owner","version":"1.0.0"}}"#,
+                    serde_json::to_string(&binary_dir).unwrap()
+                )
+            )
+        );
+        assert_eq!(
+            inputs[1].0.last().unwrap(),
+            &("BVM_HOOK_COMMAND".to_string(), "name".to_string())
+        );
+        assert_eq!(
+            inputs[1].1,
+            format!(
+                r#"{{"command":"name","dir":{},"event":"post-use","name":"name","owner":"owner","version":"1.0.0"}}"#,
+                serde_json::to_string(&binary_dir).unwrap()
+            )
+        );
+
+        run_cli(vec!["uninstall", "name", "1.0.0"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [(cwd, "notify uninstall".to_string())]
+        );
+        assert_eq!(
+            environment.take_run_shell_command_inputs()[0].0[0],
+            ("BVM_HOOK_EVENT".to_string(), "pre-uninstall".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_command_repairs_missing_executable() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        let binary_path = PathBuf::from(get_binary_path("owner", "name", "1.0.0"));
+        environment.remove_file(&binary_path).unwrap();
+        environment.clear_logs();
+
+        // fails when not interactive
+        let err = run_cli(vec!["hidden", "resolve-command", "name"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(get_error_code(&err), ErrorCode::NotInstalled);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "The executable {} of owner/name 1.0.0 is missing. Reinstall it with `bvm install --force http://localhost/package.json@{}` or run the command interactively to repair it.",
+                binary_path.display(),
+                checksum,
+            )
+        );
+
+        // offers to reinstall it
+        environment.set_confirm_answer(Some(true));
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+        environment.set_confirm_answer(None);
+        assert_logs_errors!(
+            environment,
+            [
+                format!(
+                    "The executable {} of owner/name 1.0.0 is missing. Reinstall it from http://localhost/package.json?",
+                    binary_path.display()
+                ),
+                "Extracting archive for owner/name 1.0.0...".to_string(),
+                "Repaired owner/name 1.0.0.".to_string(),
+            ]
+        );
+        assert_eq!(environment.path_exists(&binary_path), true);
+    }
+
+    #[test]
+    fn install_url_npm_package() {
+        let builder = EnvironmentBuilder::new();
+        builder.add_binary_to_path("npm");
+        let environment = builder.build();
+        let npm_command = "npm install --prefix . --no-save --no-package-lock name@1.0.0";
+        environment.add_shell_command_file(
+            npm_command,
+            "node_modules/name/package.json",
+            r#"{ "name": "name", "bin": { "name": "bin/name.js", "name-second": "bin/second.js" } }"#
+                .as_bytes()
+                .to_vec(),
+        );
+
+        install_url!(environment, "npm:name@1.0.0");
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [(get_binary_staging_dir("npm", "name", "1.0.0"), npm_command.to_string())]
+        );
+        let bin_dir = PathBuf::from(get_binary_dir("npm", "name", "1.0.0"))
+            .join("node_modules")
+            .join(".bin");
+        let get_bin_path = |name: &str| {
+            let file_name = if cfg!(target_os = "windows") {
+                format!("{}.cmd", name)
+            } else {
+                name.to_string()
+            };
+            bin_dir.join(file_name).to_string_lossy().to_string()
+        };
+        // npm creates the executables of the package's bins
+        for name in ["name", "name-second"] {
+            environment.write_file(&PathBuf::from(get_bin_path(name)), b"").unwrap();
+        }
+        assert_resolves!(environment, get_bin_path("name"));
+        assert_resolves_name!(environment, "name-second", get_bin_path("name-second"));
+    }
+
+    #[test]
+    fn install_url_npm_package_no_npm() {
+        let environment = EnvironmentBuilder::new().build();
+        let error_message = run_cli(vec!["install", "npm:name@1.0.0"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing npm:name@1.0.0. Could not find npm on the path. Install Node.js to install npm packages."
+        );
+    }
+
+    #[test]
+    fn install_url_installer_packages() {
+        let builder = EnvironmentBuilder::new();
+        builder.add_binary_to_path("pkgutil");
+        let environment = builder.build();
+        environment.add_remote_file("http://localhost/tool.pkg", "pkg".as_bytes().to_vec());
+        environment.add_remote_file("http://localhost/tool.msi", "msi".as_bytes().to_vec());
+        let add_plugin_file = |url: &str, version: &str| {
+            environment.add_remote_file(
+                url,
+                format!(
+                    r#"{{
+    "schemaVersion": 1,
+    "owner": "owner",
+    "name": "tool",
+    "version": "{}",
+    "description": "Some description.",
+    "darwin-x86_64": {{
+        "path": "http://localhost/tool.pkg",
+        "type": "pkg",
+        "checksum": "{}",
+        "commands": [{{ "name": "tool", "path": "bin/tool" }}]
+    }},
+    "windows-x86_64": {{
+        "path": "http://localhost/tool.msi",
+        "type": "msi",
+        "checksum": "{}",
+        "commands": [{{ "name": "tool", "path": "Tool/tool.exe" }}]
+    }}
+}}"#,
+                    version,
+                    dprint_cli_core::checksums::get_sha256_checksum("pkg".as_bytes()),
+                    dprint_cli_core::checksums::get_sha256_checksum("msi".as_bytes()),
+                )
+                .into_bytes(),
+            );
+        };
+        add_plugin_file("http://localhost/package.json", "1.0.0");
+        add_plugin_file("http://localhost/package2.json", "2.0.0");
+        let pkgutil_command = "pkgutil --expand-full .bvm-installer/installer.pkg .bvm-installer/expanded";
+        environment.add_shell_command_file(pkgutil_command, ".bvm-installer/expanded/Distribution", Vec::new());
+        environment.add_shell_command_file(
+            pkgutil_command,
+            ".bvm-installer/expanded/tool.pkg/Payload/bin/tool",
+            Vec::new(),
+        );
+
+        // moves the files of the payload into the binary's directory
+        run_cli(
+            vec!["install", "--target", "darwin-x86_64", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [(
+                get_binary_staging_dir("owner", "tool", "1.0.0"),
+                pkgutil_command.to_string()
+            )]
+        );
+        let binary_dir = PathBuf::from(get_binary_dir("owner", "tool", "1.0.0"));
+        assert_eq!(environment.path_exists(&binary_dir.join("bin").join("tool")), true);
+        assert_eq!(environment.path_exists(&binary_dir.join(".bvm-installer")), false);
+
+        // errors when the installer can't be isolated
+        environment.add_shell_command_file(
+            pkgutil_command,
+            ".bvm-installer/expanded/tool.pkg/Scripts/postinstall",
+            Vec::new(),
+        );
+        let error_message = run_cli(
+            vec!["install", "--target", "darwin-x86_64", "http://localhost/package2.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package2.json. Could not install owner/tool 2.0.0 into an isolated directory because its pkg installer runs installation scripts."
+        );
+        environment.clear_logs();
+        environment.take_run_shell_commands();
+        let error_message = run_cli(
+            vec![
+                "install",
+                "--target",
+                "windows-x86_64",
+                "http://localhost/package2.json",
+            ],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package2.json. Could not find msiexec on the path. Installing msi installers requires Windows."
+        );
+        environment.clear_logs();
+    }
+
+    #[test]
+    fn install_url_app_image() {
+        let environment = EnvironmentBuilder::new().build();
+        environment.add_remote_file("http://localhost/tool.AppImage", "appimage".as_bytes().to_vec());
+        let add_plugin_file = |url: &str, version: &str| {
+            environment.add_remote_file(
+                url,
+                format!(
+                    r#"{{
+    "schemaVersion": 1,
+    "owner": "owner",
+    "name": "tool",
+    "version": "{}",
+    "description": "Some description.",
+    "linux-x86_64": {{
+        "path": "http://localhost/tool.AppImage",
+        "type": "appimage",
+        "checksum": "{}",
+        "commands": [{{ "name": "tool", "path": "tool.AppImage" }}]
+    }}
+}}"#,
+                    version,
+                    dprint_cli_core::checksums::get_sha256_checksum("appimage".as_bytes()),
+                )
+                .into_bytes(),
+            );
+        };
+        add_plugin_file("http://localhost/package.json", "1.0.0");
+        add_plugin_file("http://localhost/package2.json", "2.0.0");
+        let extract_command = "./\"tool.AppImage\" --appimage-extract";
+        environment.add_shell_command_file(extract_command, "squashfs-root/AppRun", Vec::new());
+
+        // extracts the AppImage when FUSE isn't available
+        run_cli(
+            vec!["install", "--target", "linux-x86_64", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [(
+                get_binary_staging_dir("owner", "tool", "1.0.0"),
+                extract_command.to_string()
+            )]
+        );
+        let binary_dir = PathBuf::from(get_binary_dir("owner", "tool", "1.0.0"));
+        assert_eq!(
+            environment.read_file_text(&binary_dir.join("tool.AppImage")).unwrap(),
+            "#!/bin/sh\nexec \"$(dirname \"$0\")/tool.AppDir/AppRun\" \"$@\"\n"
+        );
+        assert_eq!(
+            environment.path_exists(&binary_dir.join("tool.AppDir").join("AppRun")),
+            true
+        );
+        assert_eq!(environment.path_exists(&binary_dir.join("squashfs-root")), false);
+
+        // otherwise keeps the AppImage as is
+        environment.set_env_var("BVM_APPIMAGE_EXTRACT", "0");
+        run_cli(
+            vec!["install", "--target", "linux-x86_64", "http://localhost/package2.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_eq!(environment.take_run_shell_commands().len(), 0);
+        let binary_dir = PathBuf::from(get_binary_dir("owner", "tool", "2.0.0"));
+        assert_eq!(
+            environment.read_file_text(&binary_dir.join("tool.AppImage")).unwrap(),
+            "appimage"
+        );
+        assert_eq!(environment.path_exists(&binary_dir.join("tool.AppDir")), false);
+    }
+
+    #[test]
+    fn install_url_cargo_crate() {
+        let builder = EnvironmentBuilder::new();
+        builder.add_binary_to_path("cargo");
+        let environment = builder.build();
+        let cargo_command = "cargo install --root . --locked --version 1.0.0 name";
+        let exe_suffix = if cfg!(target_os = "windows") { ".exe" } else { "" };
+        environment.add_shell_command_file(cargo_command, &format!("bin/name{}", exe_suffix), Vec::new());
+        environment.add_shell_command_file(cargo_command, ".crates.toml", Vec::new());
+
+        install_url!(environment, "cargo:name@1.0.0");
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [(
+                get_binary_staging_dir("cargo", "name", "1.0.0"),
+                cargo_command.to_string()
+            )]
+        );
+        let binary_path = PathBuf::from(get_binary_dir("cargo", "name", "1.0.0"))
+            .join("bin")
+            .join(format!("name{}", exe_suffix));
+        assert_resolves!(environment, binary_path.to_string_lossy().to_string());
+    }
+
+    #[test]
+    fn install_url_failure_keeps_previous_install() {
+        let builder = EnvironmentBuilder::new();
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package2.json", "owner", "name", "1.0.0");
+        plugin_builder.output_dir("../other");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        let error_message = run_cli(
+            vec!["install", "--force", "http://localhost/package2.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package2.json. Invalid path '../other'. A path cannot go down directories."
+        );
+        assert_eq!(
+            environment.is_dir_deleted(&PathBuf::from(get_binary_staging_dir("owner", "name", "1.0.0"))),
+            true
+        );
+        assert_has_path!(environment, &binary_path);
+        assert_resolves!(&environment, binary_path);
+    }
+
+    #[test]
+    fn install_url_same_binary_more_than_once() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+
+        run_cli(
+            vec![
+                "install",
+                "http://localhost/package.json",
+                "http://localhost/package.json",
+            ],
+            &environment,
+        )
+        .unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn install_url_waits_for_manifest_lock() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        let lock_file_path = PathBuf::from("/data/binaries-manifest.json.lock");
+        environment.write_file_text(&lock_file_path, "1").unwrap();
+
+        let error_message = run_cli(vec!["install", "http://localhost/package.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            format!(
+                "Timed out waiting for another bvm process to finish. If no other bvm process is running, delete the lock file at {}",
+                if cfg!(target_os = "windows") {
+                    "/data\\binaries-manifest.json.lock"
+                } else {
+                    "/data/binaries-manifest.json.lock"
+                }
+            )
+        );
+        assert_logs_errors!(environment, ["Waiting for another bvm process to finish..."]);
+
+        // should release the lock once done
+        environment.remove_file(&lock_file_path).unwrap();
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_not_has_path!(environment, &lock_file_path.to_string_lossy().to_string());
+    }
+
+    #[test]
+    fn install_url_removes_stale_manifest_lock() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        let lock_file_path = PathBuf::from("/data/binaries-manifest.json.lock");
+        environment.set_time_secs(1000);
+
+        // a lock that was refreshed recently is waited on
+        environment.write_file_text(&lock_file_path, "1 950").unwrap();
+        assert_eq!(
+            run_cli(vec!["install", "http://localhost/package.json"], &environment).is_err(),
+            true
+        );
+        assert_logs_errors!(environment, ["Waiting for another bvm process to finish..."]);
+
+        // a lock that wasn't refreshed for too long is removed
+        environment.write_file_text(&lock_file_path, "1 500").unwrap();
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_not_has_path!(environment, &lock_file_path.to_string_lossy().to_string());
+
+        // a recent lock of a process that exited is removed
+        run_cli(vec!["uninstall", "name", "1.0.0"], &environment).unwrap();
+        environment.write_file_text(&lock_file_path, "12345 950").unwrap();
+        environment.set_process_exited(12345);
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_not_has_path!(environment, &lock_file_path.to_string_lossy().to_string());
+    }
+
+    #[test]
+    fn install_url_output_dir() {
+        let builder = EnvironmentBuilder::new();
+        let first_bin_dir = get_binary_dir("owner", "name", "1.0.0");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package1.json", "owner", "name", "1.0.0");
+        plugin_builder.output_dir("bin");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package2.json", "owner", "name", "2.0.0");
+        plugin_builder.output_dir("../bin"); // should error
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package3.json", "owner", "name", "3.0.0");
+        #[cfg(target_os = "windows")]
+        plugin_builder.output_dir("C:\\bin"); // should error
+        #[cfg(not(target_os = "windows"))]
+        plugin_builder.output_dir("/bin"); // should error
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+
+        install_url!(environment, "http://localhost/package1.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        let output_dir = PathBuf::from(first_bin_dir).join("bin");
+        let bin_name = if cfg!(target_os = "windows") {
+            "name.exe"
+        } else {
+            "name"
+        };
+        assert_eq!(environment.path_exists(&output_dir.join(bin_name)), true);
+
+        // test going down a dir
+        let err_message = run_cli(vec!["install", "http://localhost/package2.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            "Error installing http://localhost/package2.json. Invalid path '../bin'. A path cannot go down directories."
+        );
+
+        // test absolute path
+        let err_message = run_cli(vec!["install", "http://localhost/package3.json"], &environment)
+            .err()
+            .unwrap();
+        let expected_error = format!(
+            "Error installing http://localhost/package3.json. Invalid path '{}'. A path cannot be absolute.",
+            if cfg!(target_os = "windows") { "C:\\bin" } else { "/bin" }
+        );
+        assert_eq!(err_message.to_string(), expected_error);
+    }
+
+    #[test]
+    fn install_command_no_existing_binary() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json"]);
+        let environment = builder.build();
+
+        // attempt to install in directory that doesn't have the config file
+        let error_text = run_cli(vec!["install"], &environment).err().unwrap().to_string();
+        assert_eq!(
+            error_text,
+            "Could not find a bvm configuration file in the current directory or its ancestors. Perhaps create one with `bvm init`?"
+        );
+
+        // move to the correct dir, then try again
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        // now try to resolve the binary
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        assert_resolves!(environment, binary_path);
+
+        // go up a directory and it should resolve
+        environment.set_cwd("/");
+        assert_resolves!(environment, binary_path);
+    }
+
+    #[test]
+    fn install_command_previous_install_binary() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package2.json"]);
+        let environment = builder.build();
+
+        // install a package globally
+        run_cli(vec!["install", "http://localhost/package.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        // run the install command in the correct directory
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 2.0.0..."]);
+
+        // now try to resolve the binary
+        assert_resolves!(environment, second_binary_path);
+
+        // try reinstalling, it should not output anything
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+
+        // try reinstalling, but provide --force
+        run_cli(vec!["install", "--force"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 2.0.0..."]);
+
+        // go up a directory and it should resolve to the previously set global
+        environment.set_cwd("/");
+        assert_resolves!(environment, first_binary_path);
+
+        // go back and provide --use
+        environment.set_cwd("/project");
+        run_cli(vec!["install", "--use"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+
+        // go up a directory and it should use the path from the config globally now
+        environment.set_cwd("/");
+        assert_resolves!(environment, second_binary_path);
+    }
+
+    #[test]
+    fn install_command_binary_on_path() {
+        let builder = EnvironmentBuilder::new();
+        let path_exe_path = builder.add_binary_to_path("name");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json"]);
+        let environment = builder.build();
+
+        // run the install command in the correct directory
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        // now try to resolve the binary
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        assert_resolves!(environment, binary_path);
+
+        // go up a directory and it should resolve to binary on the path still
+        environment.set_cwd("/");
+        assert_resolves!(environment, path_exe_path);
+    }
+
+    #[test]
+    fn install_command_pre_post_install() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .on_pre_install("echo \"Test\"")
+            .on_post_install("echo \"Hello world!\"")
+            .add_binary_path("http://localhost/package.json")
+            .build();
+        let environment = builder.build();
+
+        // run the install command in the correct directory
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        let logged_shell_commands = environment.take_run_shell_commands();
+        assert_eq!(
+            logged_shell_commands,
+            [
+                ("/project".to_string(), "echo \"Test\"".to_string()),
+                ("/project".to_string(), "echo \"Hello world!\"".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn install_command_post_install_runs_installed_binary() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .on_post_install("name --version")
+            .add_binary_path("http://localhost/package.json")
+            .build();
+        let environment = builder.build();
+        // the binary's shim resolves it from the manifest, so it must be written before the hook runs
+        let resolved_paths = Arc::new(Mutex::new(Vec::new()));
+        environment.add_shell_command_callback("name --version", {
+            let resolved_paths = resolved_paths.clone();
+            move |environment| {
+                let result = run_cli(vec!["resolve", "name"], environment).map(|_| environment.take_logged_messages());
+                resolved_paths
+                    .lock()
+                    .unwrap()
+                    .push(result.map_err(|err| err.to_string()));
+            }
+        });
+
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_eq!(environment.take_run_shell_commands().len(), 1);
+        assert_eq!(
+            resolved_paths.lock().unwrap().clone(),
+            vec![Ok(vec![get_binary_path("owner", "name", "1.0.0")])]
+        );
+    }
+
+    #[test]
+    fn install_command_binary_object() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object("http://localhost/package.json", None, None)
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn install_command_binary_platforms_and_optional() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name2", "1.0.0");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package3.json", "owner", "name3", "1.0.0");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.add_linux_musl_zip();
+        plugin_builder.build();
+        let environment = builder.build();
+        // only the third binary provides a musl binary
+        environment.set_env_var("BVM_TARGET", "linux-x86_64-musl");
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.json"),
+                r#"{
+  "binaries": [
+    { "path": "http://localhost/package.json", "platforms": ["windows", "darwin-aarch64"] },
+    { "path": "http://localhost/package2.json", "optional": true },
+    { "path": "http://localhost/package3.json", "platforms": ["linux"] }
+  ]
+}"#,
+            )
+            .unwrap();
+        environment.set_cwd("/project");
+
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Skipped http://localhost/package.json because it's only used on windows, darwin-aarch64.",
+                "Skipped optional binary http://localhost/package2.json: owner/name2 1.0.0 does not provide a binary for target linux-x86_64-musl.",
+                "Extracting archive for owner/name3 1.0.0..."
+            ]
+        );
+        assert_eq!(
+            environment
+                .take_downloaded_urls()
+                .contains(&"http://localhost/package.json".to_string()),
+            false
+        );
+        assert_resolves_name!(environment, "name3", get_binary_path("owner", "name3", "1.0.0"));
+
+        // fails without being optional
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.json"),
+                r#"{ "binaries": [{ "path": "http://localhost/package2.json" }] }"#,
+            )
+            .unwrap();
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package2.json: owner/name2 1.0.0 does not provide a binary for target linux-x86_64-musl."
+        );
+    }
+
+    #[test]
+    fn install_command_env_var_interpolation() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object("http://${MIRROR_HOST}/package.json", Some("${PACKAGE_CHECKSUM}"), None)
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error reading /project/bvm.json: Environment variable 'MIRROR_HOST' is not set, but is referenced in 'http://${MIRROR_HOST}/package.json'. Set it or escape the reference as $${MIRROR_HOST}."
+        );
+
+        environment.set_env_var("MIRROR_HOST", "localhost");
+        environment.set_env_var("PACKAGE_CHECKSUM", &checksum);
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+
+        // the url is stored and displayed without the values of the environment variables
+        let manifest_text = environment
+            .read_file_text(&PathBuf::from("/data/binaries-manifest.json"))
+            .unwrap();
+        assert_eq!(manifest_text.contains("http://${MIRROR_HOST}/package.json"), true);
+        assert_eq!(manifest_text.contains("http://localhost/package.json"), false);
+        run_cli(vec!["why", "name"], &environment).unwrap();
+        assert_eq!(
+            environment
+                .take_logged_messages()
+                .iter()
+                .any(|message| message.contains("Installed from: http://${MIRROR_HOST}/package.json")),
+            true
+        );
+    }
+
+    #[test]
+    fn install_command_binary_object_checksum() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object("http://localhost/package.json", Some(checksum.as_str()), None)
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+    }
+
+    #[test]
+    fn install_command_binary_object_checksum_incorrect() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let other_checksum =
+            builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object("http://localhost/package.json", Some("incorrect-checksum"), None)
+            .add_binary_object("http://localhost/other.json", Some("incorrect-checksum"), None)
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        let error = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                concat!(
+                    "Error installing http://localhost/package.json: The checksum {0} did not match the expected checksum of incorrect-checksum. ",
+                    "The download was quarantined at /data/quarantine/{0}-package.json. ",
+                    "If the binary manifest file was republished, run `bvm install --accept-new-checksum` to use its new checksum."
+                ),
+                checksum
+            )
+        );
+        assert_eq!(get_error_code(&error), ErrorCode::ChecksumMismatch);
+        assert_has_path!(environment, &format!("/data/quarantine/{}-package.json", checksum));
+        assert_not_has_path!(environment, &get_binary_path("owner", "name", "1.0.0"));
+        environment.clear_logs();
+
+        run_cli(vec!["install", "--accept-new-checksum"], &environment).unwrap();
+        let logged_errors = environment.take_logged_errors();
+        assert_eq!(
+            logged_errors[0],
+            format!(
+                "Accepted the new checksum of http://localhost/package.json.\n  Old: incorrect-checksum\n  New: {}",
+                checksum
+            )
+        );
+        // only the checksum of each binary's own entry is replaced
+        assert_eq!(
+            logged_errors[2],
+            format!(
+                "Accepted the new checksum of http://localhost/other.json.\n  Old: incorrect-checksum\n  New: {}",
+                other_checksum
+            )
+        );
+        assert_has_path!(environment, &get_binary_path("owner", "name", "1.0.0"));
+        assert_has_path!(environment, &get_binary_path("owner", "other", "1.0.0"));
+        let config_file = super::read_config_file_at_path(&environment, &PathBuf::from("/project/bvm.json")).unwrap();
+        assert_eq!(
+            config_file
+                .binaries
+                .iter()
+                .map(|b| b.path.checksum.clone().unwrap())
+                .collect::<Vec<_>>(),
+            vec![checksum, other_checksum]
+        );
+    }
+
+    #[test]
+    fn install_command_binary_object_url_checksum_incorrect() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object(&format!("http://localhost/package.json@incorrect-checksum"), None, None)
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        let error = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                concat!(
+                    "Error installing http://localhost/package.json: The checksum {0} did not match the expected checksum of incorrect-checksum. ",
+                    "The download was quarantined at /data/quarantine/{0}-package.json. ",
+                    "If the binary manifest file was republished, run `bvm install --accept-new-checksum` to use its new checksum."
+                ),
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn install_command_binary_object_existing_matching_version() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "1.1.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object(&format!("http://localhost/package.json"), None, Some("^1.0"))
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.1.0"));
+    }
+
+    #[test]
+    fn install_command_binary_object_existing_matching_version_major_minor() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.1.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "1.3.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object(&format!("http://localhost/package.json"), None, Some("1.1"))
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        // should not install because 1.1 is the same as ^1.1 in a config file
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.3.0"));
+    }
+
+    #[test]
+    fn install_command_binary_object_non_existing_matching_version() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "1.1.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object(&format!("http://localhost/package.json"), None, Some("~1.0"))
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn install_command_binary_object_version_not_match_path_errors() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object(&format!("http://localhost/package.json"), None, Some("1.1"))
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        let err = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(err.to_string(), "Error installing http://localhost/package.json: The specified version '1.1' did not match '1.0.0' in the path file. Please specify a different path or update the version.");
+
+        // should still resolve when installed without error
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn install_unknown_config_key() {
+        let environment = TestEnvironment::new();
+        environment
+            .write_file_text(
+                &PathBuf::from("/bvm.json"),
+                r#"{"test": "", "binaries": ["http://localhost/package.json"]}"#,
+            )
+            .unwrap();
+
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(error_message.to_string(), "Error reading /bvm.json: Unknown key 'test'");
+    }
+
+    #[test]
+    fn install_config_file_newer_bvm_version() {
+        let environment = TestEnvironment::new();
+        environment
+            .write_file_text(
+                &PathBuf::from("/bvm.json"),
+                r#"{"bvmVersion": "999.0.0", "test": "", "binaries": ["http://localhost/package.json"]}"#,
+            )
+            .unwrap();
+
+        // should say to upgrade instead of erroring for the keys of a newer version
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            format!(
+                "Error reading /bvm.json: This configuration file requires bvm >= 999.0.0, you have {}. Run `bvm self-update` to upgrade.",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn install_config_file_concurrency() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name2", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name3", "1.0.0");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package4.json", "owner", "name4", "1.0.0");
+        plugin_builder.on_post_install("command4");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        builder.create_bvmrc(vec![
+            "http://localhost/package.json",
+            "http://localhost/package2.json",
+            "http://localhost/package.json",
+            "http://localhost/package3.json",
+        ]);
+        let environment = builder.build();
+        environment
+            .write_file_text(&PathBuf::from("/data/config.json"), r#"{ "installConcurrency": 4 }"#)
+            .unwrap();
+        environment.set_cwd("/project");
+
+        run_cli(vec!["install"], &environment).unwrap();
+        let mut logged_errors = environment.take_logged_errors();
+        logged_errors.sort();
+        assert_eq!(
+            logged_errors,
+            vec![
+                "Extracting archive for owner/name 1.0.0...",
+                "Extracting archive for owner/name2 1.0.0...",
+                "Extracting archive for owner/name3 1.0.0...",
+            ]
+        );
+        // the binary manifest file referenced twice is only downloaded once
+        let downloaded_urls = environment.take_downloaded_urls();
+        assert_eq!(
+            downloaded_urls
+                .iter()
+                .filter(|url| *url == "http://localhost/package.json")
+                .count(),
+            1
+        );
+        assert_resolves_name!(environment, "name", get_binary_path("owner", "name", "1.0.0"));
+        assert_resolves_name!(environment, "name2", get_binary_path("owner", "name2", "1.0.0"));
+        assert_resolves_name!(environment, "name3", get_binary_path("owner", "name3", "1.0.0"));
+
+        // removes the binaries set up ahead of a failure
+        run_cli(vec!["uninstall", "--all", "name3"], &environment).unwrap();
+        environment.clear_logs();
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.json"),
+                r#"{ "binaries": ["http://localhost/missing.json", "http://localhost/package3.json", "http://localhost/package4.json"] }"#,
+            )
+            .unwrap();
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/missing.json: Could not find file at url http://localhost/missing.json"
+        );
+        environment.clear_logs();
+        assert_not_has_path!(environment, &get_binary_path("owner", "name3", "1.0.0"));
+        assert_not_has_path!(environment, &get_binary_staging_dir("owner", "name4", "1.0.0"));
+        // the post install command of a discarded binary is never run
+        assert_eq!(environment.take_run_shell_commands(), []);
+        run_cli(vec!["repair"], &environment).unwrap();
+        assert_logs_errors!(environment, ["No problems were found."]);
+    }
+
+    #[test]
+    fn uninstall_command_binary_on_path() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let path_exe_path = builder.add_binary_to_path("name");
+        let environment = builder.build();
+
+        // install and use the package
+        run_cli(vec!["install", "--use", "http://localhost/package.json"], &environment).unwrap();
+        environment.clear_logs();
+        assert_has_path!(environment, &get_shim_path("name"));
+        run_cli(vec!["uninstall", "name", "1.0.0"], &environment).unwrap();
+
+        // ensure it resolves the previous binary on the path
+        assert_resolves!(environment, path_exe_path);
+        assert_not_has_path!(environment, &get_shim_path("name"));
+        assert_not_has_path!(environment, &get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn uninstall_command_multiple_binaries() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package3.json", "owner", "name", "3.0.0");
+        let environment = builder.build();
+
+        // install and the first package
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+
+        // install and use the second package
+        run_cli(vec!["install", "--use", "http://localhost/package2.json"], &environment).unwrap();
+        environment.clear_logs();
+        assert_has_path!(environment, &get_shim_path("name"));
+
+        // now install the second package
+        run_cli(vec!["uninstall", "name", "2.0.0"], &environment).unwrap();
+
+        // ensure it resolves the first binary on the path now
+        let name_dir = PathBuf::from(&first_binary_path)
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        assert_resolves!(environment, &first_binary_path);
+        assert_has_path!(environment, &get_shim_path("name"));
+        assert_has_path!(environment, &first_binary_path);
+        assert_not_has_path!(environment, &second_binary_path);
+        assert_eq!(environment.is_dir_deleted(&name_dir), false);
+
+        // install and use the third package
+        run_cli(vec!["install", "--use", "http://localhost/package3.json"], &environment).unwrap();
+        environment.clear_logs();
+        assert_has_path!(environment, &get_shim_path("name"));
+        assert_has_path!(environment, &get_shim_path("name-second"));
+        run_cli(vec!["uninstall", "name", "3.0.0"], &environment).unwrap();
+        assert_has_path!(environment, &get_shim_path("name"));
+        assert_not_has_path!(environment, &get_shim_path("name-second"));
+
+        // uninstall the first package and it should no longer have the shim
+        run_cli(vec!["uninstall", "name", "1.0.0"], &environment).unwrap();
+        assert_not_has_path!(environment, &get_shim_path("name"));
+        assert_not_has_path!(environment, &first_binary_path);
+        assert_eq!(environment.is_dir_deleted(&name_dir), true);
+    }
+
+    #[test]
+    fn list_command_with_no_installs() {
+        let environment = TestEnvironment::new();
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, []);
+    }
+
+    #[test]
+    fn list_command_with_installs() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "b", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package4.json", "owner", "name", "2.0.0"); // same version as above
+        builder.create_remote_zip_package("http://localhost/package5.json", "david", "c", "2.1.1");
+        let environment = builder.build();
+
+        // install the packages
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        install_url!(environment, "http://localhost/package4.json");
+        install_url!(environment, "http://localhost/package5.json");
+        environment.clear_logs();
+
+        // check list
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["david/c 2.1.1 (global)\nowner/b 2.0.0 (global)\nowner/name 1.0.0 (global)\nowner/name 2.0.0"]
+        );
+    }
+
+    #[test]
+    fn wildcard_name_selectors() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "dprint", "dprint", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "myorg", "lint", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package3.json", "myorg", "lint", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package4.json", "myorg", "fmt", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package5.json", "denoland", "deno", "1.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        install_url!(environment, "http://localhost/package4.json");
+        install_url!(environment, "http://localhost/package5.json");
+        environment.clear_logs();
+
+        run_cli(vec!["list", "dprint*"], &environment).unwrap();
+        assert_logs!(environment, ["dprint/dprint 1.0.0 (global)"]);
+        run_cli(vec!["list", "--name", "myorg/*"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["myorg/fmt 1.0.0 (global)\nmyorg/lint 1.0.0 (global)\nmyorg/lint 2.0.0"]
+        );
+
+        // suggests a close name when nothing matches
+        let error_message = run_cli(vec!["uninstall", "denp", "1.0.0"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find any installed binaries named 'denp'. Did you mean denoland/deno?"
+        );
+        let error_message = run_cli(vec!["uninstall", "myorg/*", "1.0.0"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "There were multiple binaries matching 'myorg/*' that matched version '1.0.0'. Please specify a single binary.\n\nInstalled versions:\n  myorg/fmt 1.0.0\n  myorg/lint 1.0.0"
+        );
+
+        run_cli(vec!["uninstall", "myorg/*", "--all"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Removed myorg/fmt 1.0.0.",
+                "Removed myorg/lint 1.0.0.",
+                "Removed myorg/lint 2.0.0."
+            ]
+        );
+        assert_not_has_path!(environment, &get_binary_path("myorg", "lint", "2.0.0"));
+        assert_has_path!(environment, &get_binary_path("dprint", "dprint", "1.0.0"));
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["denoland/deno 1.0.0 (global)\ndprint/dprint 1.0.0 (global)"]
+        );
+
+        // wildcards can't be resolved from a registry
+        let error_message = run_cli(vec!["install", "myorg/*"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Wildcards are not supported when resolving 'myorg/*' from a registry. Please specify the binary name."
+        );
+    }
+
+    #[test]
+    fn uninstall_interactive() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        let environment = builder.build();
+        let error_message = run_cli(vec!["uninstall", "-i"], &environment).err().unwrap();
+        assert_eq!(error_message.to_string(), "No binaries are installed.");
+        environment.set_time_secs(0);
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/other.json");
+        environment.set_time_secs(3 * 24 * 60 * 60);
+        environment.clear_logs();
+
+        // requires prompting
+        let error_message = run_cli(vec!["uninstall", "--interactive"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Selecting the versions to uninstall requires running bvm interactively."
+        );
+
+        environment.set_multi_select_answer(Some(vec![0, 2]));
+        run_cli(vec!["uninstall", "--interactive"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Select the versions to uninstall:\n  owner/name 1.0.0 - 179 B, last used 3 days ago\n  owner/name 2.0.0 - 179 B, last used 3 days ago\n  owner/other 1.0.0 - 182 B, last used 3 days ago",
+                "Removed owner/name 1.0.0.",
+                "Removed owner/other 1.0.0."
+            ]
+        );
+        assert_not_has_path!(environment, &get_binary_path("owner", "name", "1.0.0"));
+        assert_has_path!(environment, &get_binary_path("owner", "name", "2.0.0"));
+        assert_not_has_path!(environment, &get_binary_path("owner", "other", "1.0.0"));
+
+        // only lists the binaries matching the name
+        environment.set_multi_select_answer(Some(Vec::new()));
+        run_cli(vec!["uninstall", "name", "-i"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Select the versions to uninstall:\n  owner/name 2.0.0 - 179 B, last used 3 days ago",
+                "No versions were selected."
+            ]
+        );
+        assert_has_path!(environment, &get_binary_path("owner", "name", "2.0.0"));
+    }
+
+    #[test]
+    fn list_command_filters_and_sorts() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "b", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package4.json", "david", "c", "2.1.1");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "3.0.0".into(),
+                checksum: "".to_string(),
+                path: "http://localhost/package5.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        install_url!(environment, "http://localhost/package4.json");
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["list", "--name", "name"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)\nowner/name 2.0.0"]);
+        run_cli(vec!["list", "--name", "david/name"], &environment).unwrap();
+        assert_logs!(environment, []);
+        run_cli(vec!["list", "--owner", "owner"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["owner/b 2.0.0 (global)\nowner/name 1.0.0 (global)\nowner/name 2.0.0"]
+        );
+
+        // only lists versions older than the latest in the registries
+        run_cli(vec!["list", "--outdated"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["owner/name 1.0.0 (3.0.0 available) (global)\nowner/name 2.0.0 (3.0.0 available)"]
+        );
+
+        run_cli(vec!["list", "--sort", "version"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["david/c 2.1.1 (global)\nowner/b 2.0.0 (global)\nowner/name 2.0.0\nowner/name 1.0.0 (global)"]
+        );
+        run_cli(vec!["list", "--sort", "size"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["owner/name 1.0.0 (global)\nowner/name 2.0.0\ndavid/c 2.1.1 (global)\nowner/b 2.0.0 (global)"]
+        );
+
+        // outputs a directory per line without decoration
+        run_cli(vec!["list", "--paths", "--name", "name"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [format!(
+                "{}\n{}",
+                get_binary_dir("owner", "name", "1.0.0"),
+                get_binary_dir("owner", "name", "2.0.0")
+            )]
+        );
+    }
+
+    #[test]
+    fn case_insensitive_names() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package1.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        run_cli(vec!["config", "set", "caseInsensitiveNames", "true"], &environment).unwrap();
+        run_cli(vec!["use", "Owner/NAME", "2.0.0"], &environment).unwrap();
+        assert_resolves_name!(environment, "NAME", get_binary_path("owner", "name", "2.0.0"));
+        assert_resolves!(environment, get_binary_path("owner", "name", "2.0.0"));
+
+        run_cli(vec!["config", "set", "caseInsensitiveNames", "false"], &environment).unwrap();
+        let error_message = run_cli(vec!["use", "NAME", "1.0.0"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find any installed binaries named 'NAME'. Did you mean owner/name?"
+        );
+        super::types::set_case_insensitive_names(cfg!(target_os = "windows"));
+    }
+
+    #[test]
+    fn verify_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "other", "2.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        run_cli(vec!["verify"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+
+        // simulate a corrupted file and an antivirus quarantining an executable
+        let file_name = |path: String| PathBuf::from(path).file_name().unwrap().to_string_lossy().to_string();
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path_second("owner", "name", "1.0.0");
+        environment
+            .write_file(&PathBuf::from(&binary_path), b"corrupted")
+            .unwrap();
+        environment.remove_file(&PathBuf::from(&second_binary_path)).unwrap();
+        let error_message = run_cli(vec!["verify"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "1 binary failed verification. Reinstall with `bvm install --force` to restore the original files."
+        );
+        assert_logs_errors!(
+            environment,
+            [format!(
+                "owner/name 1.0.0 failed verification.\n  Modified: {}\n  Missing: {}",
+                file_name(binary_path),
+                file_name(second_binary_path)
+            )]
+        );
+
+        // only verifies the binaries matching the name
+        run_cli(vec!["verify", "other"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+        let error_message = run_cli(vec!["verify", "othr"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find any installed binaries named 'othr'. Did you mean owner/other?"
+        );
+
+        // reinstalling restores the files
+        run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        run_cli(vec!["verify", "owner/*"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+
+        // binaries installed without an integrity manifest are skipped
+        environment
+            .remove_file(&PathBuf::from(
+                "/local-data/binaries/owner/other/2.0.0/.bvm-integrity.json",
+            ))
+            .unwrap();
+        run_cli(vec!["verify", "other"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Warning: Skipped owner/other 2.0.0 because it was installed without an integrity manifest. Reinstall it to verify it."]
+        );
+    }
+
+    #[test]
+    fn repair_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "other", "1.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+        run_cli(vec!["repair"], &environment).unwrap();
+        assert_logs_errors!(environment, ["No problems were found."]);
+
+        // reinstalls a binary whose directory is missing
+        let binary_dir = PathBuf::from(get_binary_dir("owner", "name", "1.0.0"));
+        environment.remove_dir_all(&binary_dir).unwrap();
+        environment.set_confirm_answer(Some(true));
+        run_cli(vec!["repair"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "The directory of owner/name 1.0.0 is missing. Reinstall it from http://localhost/package.json?",
+                "Extracting archive for owner/name 1.0.0...",
+                "Repaired owner/name 1.0.0.",
+            ]
+        );
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+
+        // warns about the problems when listing
+        environment.remove_dir_all(&binary_dir).unwrap();
+        let manifest_file_path = PathBuf::from("/data/binaries-manifest.json");
+        let mut index =
+            serde_json::from_str::<serde_json::Value>(&environment.read_file_text(&manifest_file_path).unwrap())
+                .unwrap();
+        index["binaries"].as_object_mut().unwrap().remove("owner||other||1.0.0");
+        environment
+            .write_file_text(&manifest_file_path, &index.to_string())
+            .unwrap();
+        let orphaned_dir = PathBuf::from(get_binary_dir("owner", "stale", "1.0.0"));
+        environment.write_file_text(&orphaned_dir.join("stale"), "").unwrap();
+        environment.clear_logs();
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)"]);
+        assert_logs_errors!(
+            environment,
+            ["Warning: The manifest doesn't match the installed binaries. Run `bvm repair` to fix it."]
+        );
+
+        // removes the binaries that aren't reinstalled and clears the global versions of missing binaries
+        environment.set_confirm_answer(Some(false));
+        run_cli(vec!["repair"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "The directory of owner/name 1.0.0 is missing. Reinstall it from http://localhost/package.json?"
+                    .to_string(),
+                "Removed owner/name 1.0.0 from the manifest because its directory is missing.".to_string(),
+                "Cleared the global version of other, which was owner/other 1.0.0 that isn't installed.".to_string(),
+                format!(
+                    "Found directories of binaries that aren't in the manifest:\n  {}\n  {}",
+                    get_binary_dir("owner", "other", "1.0.0"),
+                    orphaned_dir.display()
+                ),
+                "Delete these directories?".to_string(),
+            ]
+        );
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, []);
+        assert_logs_errors!(
+            environment,
+            ["Warning: The manifest doesn't match the installed binaries. Run `bvm repair` to fix it."]
+        );
+
+        // deletes the directories that aren't in the manifest
+        environment.set_confirm_answer(None);
+        run_cli(vec!["repair", "--yes"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                format!(
+                    "Found directories of binaries that aren't in the manifest:\n  {}\n  {}",
+                    get_binary_dir("owner", "other", "1.0.0"),
+                    orphaned_dir.display()
+                ),
+                "Delete these directories? Yes (--yes)".to_string(),
+                "Deleted 2 directories.".to_string(),
+            ]
+        );
+        assert_not_has_path!(environment, &orphaned_dir.join("stale"));
+        run_cli(vec!["repair"], &environment).unwrap();
+        assert_logs_errors!(environment, ["No problems were found."]);
+    }
+
+    #[test]
+    fn prune_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "3.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package1.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.set_time_secs(123456 + 100);
+        install_url!(environment, "http://localhost/package3.json");
+        environment.clear_logs();
+
+        // records when a version is run
+        let day_secs = 24 * 60 * 60;
+        environment.set_time_secs(123456 + 100 * day_secs);
+        run_cli(vec!["use", "name", "2.0.0"], &environment).unwrap();
+        assert_resolves!(environment, get_binary_path("owner", "name", "2.0.0"));
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["list", "--sort", "last-used"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["owner/name 2.0.0\nowner/name 3.0.0\nowner/name 1.0.0 (global)"]
+        );
+
+        // keeps the recently run and global versions
+        run_cli(vec!["prune", "--unused-for", "90d"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Removed owner/name 3.0.0."]);
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)\nowner/name 2.0.0"]);
+
+        run_cli(vec!["prune", "--unused-for", "90d"], &environment).unwrap();
+        assert_logs_errors!(environment, ["No versions were unused for that long."]);
+
+        let error_message = run_cli(vec!["prune", "--unused-for", "90"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Invalid duration '90'. Expected a number followed by s, m, h, d, or w (ex. 90d)."
+        );
+    }
+
+    #[test]
+    fn stats_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package1.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        run_cli(vec!["stats"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["owner/name 1.0.0 - 179 B\nowner/name 2.0.0 - 179 B\n\nBinaries: 358 B\nRegistry cache: 0 B\nTotal: 358 B"]
+        );
+
+        // highlights the versions that can be pruned, which excludes the global version
+        environment.set_time_secs(123456 + 40 * 24 * 60 * 60);
+        run_cli(vec!["stats"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [concat!(
+                "owner/name 1.0.0 - 179 B\nowner/name 2.0.0 - 179 B (unused for 40 days)\n\n",
+                "Binaries: 358 B\nRegistry cache: 0 B\nTotal: 358 B\n\n",
+                "1 version hasn't been used in 30 days (179 B). Run `bvm prune --unused-for 30d` to remove it."
+            )]
+        );
+
+        let err_message = run_cli(vec!["stats", "other"], &environment).err().unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            "Could not find any installed binaries named 'other'"
+        );
+    }
+
+    #[test]
+    fn compact_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package1.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+        let binary_path = get_binary_path("owner", "name", "2.0.0");
+        let binary_bytes = environment.read_file(&PathBuf::from(&binary_path)).unwrap();
+
+        // only compacts the versions that aren't used globally
+        run_cli(vec!["compact"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Compacted owner/name 2.0.0 (179 B -> 254 B).", "Reclaimed 0 B."]
+        );
+        let binary_dir = PathBuf::from(get_binary_dir("owner", "name", "2.0.0"));
+        assert_eq!(environment.path_exists(&PathBuf::from(&binary_path)), false);
+        assert_eq!(environment.path_exists(&binary_dir.join(".bvm-archive")), true);
+        assert_has_path!(environment, &get_binary_path("owner", "name", "1.0.0"));
+
+        run_cli(vec!["compact", "owner/name"], &environment).unwrap();
+        assert_logs_errors!(environment, ["No inactive versions to compact."]);
+
+        // extracts the compacted version when it's next used
+        run_cli(vec!["use", "name", "2.0.0"], &environment).unwrap();
+        assert_resolves!(environment, binary_path);
+        assert_eq!(
+            environment.read_file(&PathBuf::from(&binary_path)).unwrap(),
+            binary_bytes
+        );
+        assert_eq!(environment.path_exists(&binary_dir.join(".bvm-archive")), false);
+        run_cli(vec!["verify"], &environment).unwrap();
+        environment.clear_logs();
+    }
+
+    #[test]
+    fn use_command_multiple_command_binaries() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let first_binary_path_second = get_binary_path_second("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        let third_binary_path = get_binary_path("owner", "name", "2.1.0");
+        let third_binary_path_second = get_binary_path_second("owner", "name", "2.1.0");
+        let fourth_binary_path = get_binary_path("owner", "name", "2.1.1");
+
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package3.json", "owner", "name", "2.1.0");
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package4.json", "owner", "name", "2.1.1");
+        builder.create_remote_zip_multiple_commands_package(
+            "http://localhost/package5.json",
+            "owner",
+            "name",
+            "3.1.1-alpha",
+        );
+        let environment = builder.build();
+
+        // install the packages
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        install_url!(environment, "http://localhost/package4.json");
+        install_url!(environment, "http://localhost/package5.json");
+        environment.clear_logs();
+
+        assert_resolves!(&environment, first_binary_path);
+        assert_resolves_name!(&environment, "name-second", first_binary_path_second);
+
+        // specify full version
+        run_cli(vec!["use", "name", "2.1.0"], &environment).unwrap();
+        assert_resolves!(&environment, third_binary_path);
+        assert_resolves_name!(&environment, "name-second", third_binary_path_second);
+
+        // specify only major
+        run_cli(vec!["use", "name", "2"], &environment).unwrap();
+        assert_resolves!(&environment, fourth_binary_path);
+
+        // specify minor
+        run_cli(vec!["use", "name", "2.0"], &environment).unwrap();
+        assert_resolves!(&environment, second_binary_path);
+        run_cli(vec!["use", "name", "2.1"], &environment).unwrap();
+        assert_resolves!(&environment, fourth_binary_path);
+
+        // specify caret
+        run_cli(vec!["use", "name", "^2.0"], &environment).unwrap();
+        assert_resolves!(&environment, fourth_binary_path);
+
+        // specify tilde
+        run_cli(vec!["use", "name", "~2.0"], &environment).unwrap();
+        assert_resolves!(&environment, second_binary_path);
+
+        // specify a compound range
+        run_cli(vec!["use", "name", ">=1.0, <2.1"], &environment).unwrap();
+        assert_resolves!(&environment, second_binary_path);
+
+        // specify none (use latest, but not pre releases)
+        run_cli(vec!["use", "name"], &environment).unwrap();
+        assert_resolves!(&environment, fourth_binary_path);
+
+        // error when nothing matches
+        let err_message = run_cli(vec!["use", "name", "^3"], &environment).err().unwrap();
+        assert!(err_message
+            .to_string()
+            .starts_with("Could not find binary 'name' that matched version '^3'\n\nInstalled versions:\n  1.0.0\n"));
+    }
+
+    #[test]
+    fn use_command_single_command_of_binary() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        run_cli(vec!["use", "--command", "name-second", "name", "2.0.0"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+        assert_resolves_name!(
+            &environment,
+            "name-second",
+            get_binary_path_second("owner", "name", "2.0.0")
+        );
+
+        let err_message = run_cli(vec!["use", "--command", "other", "name", "2.0.0"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            "Could not find command 'other' for binary 'name'. Available commands: 'name' and 'name-second'"
+        );
+
+        // uninstalling the version falls back to the latest version for that command
+        run_cli(vec!["uninstall", "name", "2.0.0"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+        assert_resolves_name!(
+            &environment,
+            "name-second",
+            get_binary_path_second("owner", "name", "1.0.0")
+        );
+    }
+
+    #[test]
+    fn use_command_config_file_same_command() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner2", "name", "2.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json"]);
+        let environment = builder.build();
+
+        // install the package
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+
+        // install the other package
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        // now try to use it
+        run_cli(vec!["use", "--global", "name", "2.0.0"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [concat!(
+                "Updated globally used version of 'name', but local version remains using version specified ",
+                "in the current working directory's config file. If you wish to change the local version, ",
+                "then update your configuration file (check the cwd and ancestor directories for a bvm configuration file)."
+            )]
+        );
+
+        // should still resolve to the cwd's binary
+        assert_resolves!(&environment, first_binary_path);
+    }
+
+    #[test]
+    fn use_command_config_file() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name2", "1.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json", "http://localhost/package3.json"]);
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        run_cli(vec!["use", "--global", "name", "2.0.0"], &environment).unwrap();
+        environment.clear_logs();
+
+        // errors without changing anything when a binary isn't installed
+        environment.set_cwd("/project");
+        let error_message = run_cli(vec!["use"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Ensure binaries are installed before using. Run `bvm install` first then `bvm use`."
+        );
+        environment.set_cwd("/");
+        assert_resolves!(&environment, get_binary_path("owner", "name", "2.0.0"));
+
+        // uses the config file's binaries once they're all installed
+        install_url!(environment, "http://localhost/package3.json");
+        environment.clear_logs();
+        environment.set_cwd("/project");
+        run_cli(vec!["use"], &environment).unwrap();
+        environment.set_cwd("/");
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+        assert_resolves_name!(environment, "name2", get_binary_path("owner", "name2", "1.0.0"));
+    }
+
+    #[test]
+    fn use_command_project() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json"]);
+        let environment = builder.build();
+        // binaries used in the project run with their directory added to the path
+        let assert_resolves_in_project = |binary_path: &str| {
+            run_cli(vec!["hidden", "resolve-command", "name"], &environment).unwrap();
+            let logged_messages = environment.take_logged_messages();
+            assert_eq!(logged_messages.last().map(|m| m.as_str()), Some(binary_path));
+        };
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        // only changes the version used in the project
+        run_cli(vec!["use", "name", "2.0.0"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Updated the version of 'name' used in /project. Provide `--global` to update the globally used version instead."]
+        );
+        assert_resolves_in_project(&second_binary_path);
+        assert_eq!(
+            environment
+                .read_file_text(&PathBuf::from("/project/.bvm/.gitignore"))
+                .unwrap(),
+            "*\n"
+        );
+        environment.set_cwd("/");
+        assert_resolves!(&environment, first_binary_path);
+
+        // the global version is changed with --global
+        environment.set_cwd("/project");
+        run_cli(vec!["use", "--global", "name", "2.0.0"], &environment).unwrap();
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves_in_project(&first_binary_path);
+        environment.set_cwd("/");
+        assert_resolves!(&environment, second_binary_path);
+
+        // falls back to the config file's version once the project's version is uninstalled
+        run_cli(vec!["use", "--global", "name", "1.0.0"], &environment).unwrap();
+        environment.set_cwd("/project");
+        run_cli(vec!["use", "name", "2.0.0"], &environment).unwrap();
+        run_cli(vec!["uninstall", "name", "2.0.0"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves!(&environment, first_binary_path);
+    }
+
+    #[test]
+    fn use_command_different_owners_path() {
+        let builder = EnvironmentBuilder::new();
+
+        let path_binary_path = builder.add_binary_to_path("name");
+        let path_second_binary_path = builder.add_binary_to_path("name-second");
+
+        let second_binary_path = get_binary_path("owner2", "name", "1.0.0");
+        let second_binary_path_second = get_binary_path_second("owner2", "name", "1.0.0");
+
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_multiple_commands_package(
+            "http://localhost/package2.json",
+            "owner2",
+            "name",
+            "1.0.0",
+        );
+        let environment = builder.build();
+
+        // install the packages
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        assert_resolves!(&environment, path_binary_path);
+        assert_resolves_name!(&environment, "name-second", path_second_binary_path);
+
+        run_cli(vec!["use", "owner2/name", "1.0.0"], &environment).unwrap();
+
+        assert_resolves!(&environment, second_binary_path);
+        assert_resolves_name!(&environment, "name-second", second_binary_path_second);
+
+        // error when not specifying the owner and there are multiple on the path
+        let err_message = run_cli(vec!["use", "name", "path"], &environment).err().unwrap();
+        assert_eq!(err_message.to_string(), "There were multiple binaries with the name 'name'. Please include the owner in the name:\n  owner/name\n  owner2/name");
+
+        // should be ok when specifying other one
+        run_cli(vec!["use", "owner/name", "path"], &environment).unwrap();
+        assert_resolves!(&environment, path_binary_path);
+        assert_resolves_name!(&environment, "name-second", path_second_binary_path);
+
+        // error when not specifying the owner for a version range
+        let err_message = run_cli(vec!["use", "name", "^1.0"], &environment).err().unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            concat!(
+                "There were multiple binaries with the specified name 'name' that matched version '^1.0'. ",
+                "Please include the owner in the name.\n\nInstalled versions:\n  owner/name 1.0.0\n  owner2/name 1.0.0"
+            )
+        );
+    }
+
+    #[test]
+    fn use_command_different_owners_select() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner2", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner2", "name", "1.0.0");
+        let environment = builder.build();
+
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        // providing the owner via the flag
+        run_cli(vec!["use", "name", "1.0.0", "--owner", "owner2"], &environment).unwrap();
+        assert_resolves!(&environment, second_binary_path);
+        let err_message = run_cli(vec!["use", "owner/name", "1.0.0", "--owner", "owner2"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            "The `--owner` flag cannot be provided with a binary name that includes the owner."
+        );
+
+        // picking the owner interactively remembers it
+        environment.set_select_answer(Some(0));
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        assert_resolves!(&environment, first_binary_path);
+        assert_logs_errors!(
+            environment,
+            [
+                "There were multiple binaries with the name 'name'. Which owner should be used?\n  owner/name\n  owner2/name",
+                "Saved 'owner' as the owner to use for 'name'. Run `bvm config unset defaultOwners` to pick again."
+            ]
+        );
+
+        environment.set_select_answer(None);
+        run_cli(vec!["use", "owner2/name", "1.0.0"], &environment).unwrap();
+        assert_resolves!(&environment, second_binary_path);
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        assert_resolves!(&environment, first_binary_path);
+
+        // errors when not interactive and nothing is remembered
+        run_cli(vec!["config", "unset", "defaultOwners"], &environment).unwrap();
+        let err_message = run_cli(vec!["uninstall", "name", "1.0.0"], &environment).err().unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            concat!(
+                "There were multiple binaries with the specified name 'name' that matched version '1.0.0'. ",
+                "Please include the owner in the name.\n\nInstalled versions:\n  owner/name 1.0.0\n  owner2/name 1.0.0"
+            )
+        );
+        environment.clear_logs();
+    }
+
+    #[test]
+    fn disable_and_enable_commands() {
+        let builder = EnvironmentBuilder::new();
+        let path_binary_path = builder.add_binary_to_path("name");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.add_env_path("dir");
+        plugin_builder.add_env_var("test", "1");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        let environment = builder.build();
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        let original_path = environment.get_env_path();
+        let dir_path_str = if cfg!(target_os = "windows") {
+            "/local-data\\binaries\\owner\\name\\1.0.0\\dir"
+        } else {
+            "/local-data/binaries/owner/name/1.0.0/dir"
+        };
+
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/other.json");
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        update_with_pending_env_changes(&environment);
+        environment.clear_logs();
+        assert_resolves!(&environment, binary_path);
+
+        // removes the binary from the environment and resolves the executable on the path
+        run_cli(vec!["disable", "name"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Disabled owner/name. Its commands will run the executables on the path until it's enabled with `bvm enable owner/name`."]
+        );
+        assert_get_pending_env_changes!(environment, [], ["test"], original_path);
+        update_with_pending_env_changes(&environment);
+        assert_get_paths!(environment, []);
+        assert_resolves!(&environment, path_binary_path);
+        run_cli(vec!["disable", "name"], &environment).unwrap();
+        assert_logs_errors!(environment, ["owner/name is already disabled."]);
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["owner/name 1.0.0 (global) (disabled)\nowner/other 1.0.0 (global)"]
+        );
+
+        // errors when there's no executable on the path
+        run_cli(vec!["disable", "other"], &environment).unwrap();
+        environment.clear_logs();
+        let err_message = run_cli(vec!["hidden", "resolve-command", "other"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            "Could not find command 'other' on the path because owner/other is disabled. Run `bvm enable owner/other` to use it again."
+        );
+
+        // enabling restores the environment
+        run_cli(vec!["enable", "owner/*"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Enabled owner/name.", "Enabled owner/other."]);
+        assert_get_pending_env_changes!(
+            environment,
+            [("test", "1")],
+            [],
+            format!("{}{}{}", original_path, SYS_PATH_DELIMITER, dir_path_str)
+        );
+        update_with_pending_env_changes(&environment);
+        assert_resolves!(&environment, binary_path);
+        run_cli(vec!["enable", "name"], &environment).unwrap();
+        assert_logs_errors!(environment, ["owner/name is not disabled."]);
+    }
+
+    #[test]
+    fn use_command_suffix() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        // adds the suffixed command without changing the global version
+        run_cli(vec!["use", "name", "2.0.0", "--suffix"], &environment).unwrap();
+        assert_logs!(environment, ["Added command 'name2.0' for owner/name 2.0.0."]);
+        assert_has_path!(environment, &get_shim_path("name2.0"));
+        assert_resolves!(environment, first_binary_path);
+
+        // the suffixed command runs with the version's directory on the path
+        let original_path = environment.get_env_path();
+        run_cli(vec!["hidden", "resolve-command", "name2.0"], &environment).unwrap();
+        let new_path = format!(
+            "{}{}{}",
+            PathBuf::from("/local-data/binaries/owner/name/2.0.0").display(),
+            SYS_PATH_DELIMITER,
+            original_path
+        );
+        if cfg!(target_os = "windows") {
+            assert_logs!(
+                environment,
+                [format!("SET PATH={}", new_path), "EXEC".to_string(), second_binary_path]
+            );
+        } else {
+            assert_logs!(
+                environment,
+                [
+                    "ADD".to_string(),
+                    "PATH".to_string(),
+                    new_path,
+                    "EXEC".to_string(),
+                    second_binary_path
+                ]
+            );
+        }
+
+        let err_message = run_cli(vec!["use", "name", "path", "--suffix"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            "The `--suffix` flag cannot be used with `path`."
+        );
+
+        // the suffixed command is removed with the binary
+        run_cli(vec!["uninstall", "name", "2.0.0"], &environment).unwrap();
+        environment.clear_logs();
+        assert_not_has_path!(environment, &get_shim_path("name2.0"));
+        assert_resolves!(environment, first_binary_path);
+    }
+
+    #[test]
+    fn clear_url_cache_command_path() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json"]);
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        // install
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        // clear the url cache
+        run_cli(vec!["clear-url-cache"], &environment).unwrap();
+
+        // ensure it still resolves, but it will error
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        assert_resolves!(environment, binary_path);
+        assert_logs_errors!(environment, ["[bvm warning]: There were some not installed binaries in the current directory (run `bvm install`). Resolving global 'name'."]);
+
+        // install again, but it shouldn't install because already installed
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+
+        // should resolve without error now
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        assert_resolves!(environment, binary_path);
+    }
+
+    #[test]
+    fn ls_remote_command() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "2.0.0".into(),
+                    checksum: "".to_string(),
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum: checksum.clone(),
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        builder.create_remote_registry_file(
+            "http://localhost/registry2.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum,
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "1.1.0".into(),
+                    checksum: "".to_string(),
+                    path: "http://localhost/package3.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        run_cli(vec!["registry", "add", "http://localhost/registry2.json"], &environment).unwrap();
+        run_cli(vec!["install", "name", "1.0.0"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["ls-remote", "name"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0 (installed)\n1.1.0\n2.0.0"]);
+
+        run_cli(vec!["ls-remote", "owner/name", "1"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0 (installed)\n1.1.0"]);
+
+        let error_message = run_cli(vec!["ls-remote", "name", "3"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find binary 'name' matching '3' in any registry."
+        );
+        assert_eq!(get_error_code(&error_message), ErrorCode::VersionNotFound);
+    }
+
+    #[test]
+    fn changelog_command() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        environment.add_remote_file(
+            "http://localhost/registry.json",
+            format!(
+                r#"{{
+    "schemaVersion": 2,
+    "binaries": [{{
+        "owner": "owner",
+        "name": "name",
+        "description": "Some description.",
+        "releaseNotesUrl": "https://example.com/releases/v{{version}}",
+        "versions": [
+            {{ "version": "1.0.0", "path": "http://localhost/package.json", "checksum": "{}" }},
+            {{ "version": "1.1.0", "path": "http://localhost/package3.json", "checksum": "", "releaseDate": "2020-11-03" }},
+            {{ "version": "2.0.0", "path": "http://localhost/package2.json", "checksum": "{}" }},
+            {{ "version": "3.0.0-beta", "path": "http://localhost/package4.json", "checksum": "" }}
+        ]
+    }}]
+}}"#,
+                checksum, checksum2
+            )
+            .into_bytes(),
+        );
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        run_cli(vec!["install", "name", "1.0.0"], &environment).unwrap();
+        environment.clear_logs();
+
+        // outputs the versions newer than the installed version
+        run_cli(vec!["changelog", "name"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["1.1.0 (2020-11-03) - https://example.com/releases/v1.1.0\n2.0.0 - https://example.com/releases/v2.0.0"]
+        );
+
+        run_cli(vec!["changelog", "owner/name", "1"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["1.1.0 (2020-11-03) - https://example.com/releases/v1.1.0"]
+        );
+
+        run_cli(vec!["changelog", "name", "--open"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [(
+                environment.cwd().unwrap().to_string_lossy().to_string(),
+                super::get_open_url_command("https://example.com/releases/v2.0.0")
+            )]
+        );
+
+        run_cli(vec!["install", "name", "2.0.0"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["changelog", "name"], &environment).unwrap();
+        assert_logs_errors!(environment, ["owner/name 2.0.0 is the latest version."]);
+    }
+
+    #[test]
+    fn registry_file_schema_version_2() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "1.1.0");
+        let environment = builder.build();
+        environment.add_remote_file(
+            "http://localhost/registry/registry.json",
+            format!(
+                r#"{{
+    "schemaVersion": 2,
+    "binaries": [{{
+        "owner": "owner",
+        "name": "name",
+        "description": "Some description.",
+        "versions": [{{
+            "version": "1.0.0",
+            "path": "http://localhost/package.json",
+            "checksum": "{}",
+            "releaseDate": "2020-11-03",
+            "platforms": ["{}"]
+        }}],
+        "nextPage": "page2.json"
+    }}]
+}}"#,
+                checksum,
+                crate::plugins::get_platform_keys()[0],
+            )
+            .into_bytes(),
+        );
+        environment.add_remote_file(
+            "http://localhost/registry/page2.json",
+            format!(
+                r#"{{
+    "versions": [{{
+        "version": "1.1.0",
+        "path": "http://localhost/package2.json",
+        "checksum": "{}"
+    }}, {{
+        "version": "2.0.0",
+        "path": "http://localhost/package3.json",
+        "checksum": "",
+        "platforms": ["other-platform"]
+    }}]
+}}"#,
+                checksum2,
+            )
+            .into_bytes(),
+        );
+        run_cli(
+            vec!["registry", "add", "http://localhost/registry/registry.json"],
+            &environment,
+        )
+        .unwrap();
+
+        // skips the versions that aren't available for the current platform
+        run_cli(vec!["install", "name"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.1.0 (global)"]);
+        let error_message = run_cli(vec!["install", "name", "2"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find binary 'name' matching '2' in any registry."
+        );
+
+        // includes the versions from the next pages
+        run_cli(vec!["ls-remote", "name"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0\n1.1.0 (installed)\n2.0.0"]);
+    }
+
+    #[test]
+    fn file_schema_versions() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        let bvm_version = env!("CARGO_PKG_VERSION");
+
+        // files from before the schema version was introduced are read as the latest schema
+        let file_bytes = environment.remove_remote_file("http://localhost/package.json").unwrap();
+        let mut file = serde_json::from_slice::<serde_json::Value>(&file_bytes).unwrap();
+        file.as_object_mut().unwrap().remove("schemaVersion");
+        environment.add_remote_file("http://localhost/package.json", serde_json::to_vec(&file).unwrap());
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+
+        // says which version of bvm is needed for newer schemas
+        file["schemaVersion"] = 2.into();
+        file["version"] = "2.0.0".into();
+        environment.add_remote_file("http://localhost/package2.json", serde_json::to_vec(&file).unwrap());
+        let error_message = run_cli(vec!["install", "http://localhost/package2.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            format!(
+                "Error installing http://localhost/package2.json. This binary manifest file uses schema version 2, but bvm {} only supports up to schema version 1. Run `bvm self-update` to upgrade.",
+                bvm_version
+            )
+        );
+        environment.add_remote_file(
+            "http://localhost/registry.json",
+            r#"{ "schemaVersion": 3, "minBvmVersion": "999.0.0", "binaries": {} }"#
+                .as_bytes()
+                .to_vec(),
+        );
+        let error_message = run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            format!(
+                "This registry file requires bvm >= 999.0.0, you have {}. Run `bvm self-update` to upgrade.",
+                bvm_version
+            )
+        );
+    }
+
+    #[test]
+    fn registry_validate_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        run_cli(
+            vec!["registry", "validate", "http://localhost/package.json", "--fetch"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs!(environment, ["http://localhost/package.json is valid."]);
+
+        environment.add_remote_file(
+            "http://localhost/invalid.json",
+            r#"{
+    "schemaVersion": 1,
+    "name": "name",
+    "owner": "owner",
+    "version": "1.0.0",
+    "description": "Some description.",
+    "windows-x86_64": {
+        "path": "file.zip",
+        "type": "zip",
+        "checksum": "",
+        "commands": []
+    }
+}"#
+            .as_bytes()
+            .to_vec(),
+        );
+        let error_message = run_cli(
+            vec!["registry", "validate", "http://localhost/invalid.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Found 2 problems in http://localhost/invalid.json."
+        );
+        assert_logs_errors!(
+            environment,
+            [
+                "windows-x86_64.checksum: Expected a sha256 checksum of 64 hexadecimal characters.",
+                "windows-x86_64.commands: Expected at least one command."
+            ]
+        );
+    }
+
+    #[test]
+    fn util_hash_command() {
+        let environment = EnvironmentBuilder::new().build();
+        environment.add_remote_file("http://localhost/file.txt", "test".as_bytes().to_vec());
+        environment
+            .write_file_text(&PathBuf::from("/file.txt"), "test")
+            .unwrap();
+        let sha256 = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+        run_cli(vec!["util", "hash", "http://localhost/file.txt"], &environment).unwrap();
+        assert_logs!(environment, [sha256]);
+        run_cli(vec!["util", "hash", "/file.txt"], &environment).unwrap();
+        assert_logs!(environment, [sha256]);
+        run_cli(
+            vec!["util", "hash", "http://localhost/file.txt", "--algo", "sha512"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs!(
+            environment,
+            ["ee26b0dd4af7e749aa1a8ee3c10ae9923f618980772e473f8819a5d4940e0db27ac185f8a0e1d5f84f88bc887fd67b143732c304cc5fa9ad8e6f57f50028a8ff"]
+        );
+    }
+
+    #[test]
+    fn install_multi_version_plugin_file() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_multi_version_package(
+            "http://localhost/package.json",
+            "owner",
+            "name",
+            vec!["1.0.0", "1.1.0"],
+        );
+        let environment = builder.build();
+
+        // selects the version in the fragment
+        run_cli(vec!["install", "http://localhost/package.json#1.0.0"], &environment).unwrap();
+        // or the latest version
+        run_cli(vec!["install", "http://localhost/package.json"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)\nowner/name 1.1.0"]);
+
+        let error_message = run_cli(vec!["install", "http://localhost/package.json#2.0.0"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package.json#2.0.0. Could not find version 2.0.0 in the binary manifest file."
+        );
+
+        run_cli(
+            vec!["registry", "validate", "http://localhost/package.json#1.0.0", "--fetch"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs!(environment, ["http://localhost/package.json#1.0.0 is valid."]);
+    }
+
+    #[test]
+    fn registry_pack_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_local_archive("/release/dist/name-x86_64-unknown-linux-gnu.zip", false, vec!["name"]);
+        builder.create_local_archive("/release/dist/name-x86_64-apple-darwin.zip", false, vec!["name"]);
+        builder.create_local_archive("/release/dist/name-x86_64-pc-windows-msvc.zip", true, vec!["name"]);
+        let environment = builder.build();
+        environment
+            .write_file_text(&PathBuf::from("/release/dist/checksums.txt"), "")
+            .unwrap();
+        let write_spec = |version: &str| {
+            environment
+                .write_file_text(
+                    &PathBuf::from("/release/pack.json"),
+                    &format!(
+                        r#"{{
+    "owner": "owner",
+    "name": "name",
+    "version": "{0}",
+    "description": "Some description",
+    "url": "http://localhost/registry/{0}.json",
+    "assetsDir": "dist",
+    "assetsUrl": "http://localhost/registry/{0}/"
+}}"#,
+                        version
+                    ),
+                )
+                .unwrap();
+        };
+        write_spec("1.0.0");
+        environment.set_cwd("/release");
+        run_cli(vec!["registry", "pack", "pack.json"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["Packed owner/name 1.0.0 for linux-x86_64, darwin-x86_64, windows-x86_64.\n  Binary manifest file: /release/1.0.0.json\n  Registry file: /release/registry.json"]
+        );
+
+        // host the files to install the binary from the registry
+        let plugin_file_bytes = environment.read_file(&PathBuf::from("/release/1.0.0.json")).unwrap();
+        environment.add_remote_file("http://localhost/registry/1.0.0.json", plugin_file_bytes);
+        for file_name in [
+            "name-x86_64-unknown-linux-gnu.zip",
+            "name-x86_64-apple-darwin.zip",
+            "name-x86_64-pc-windows-msvc.zip",
+        ] {
+            let bytes = environment
+                .read_file(&PathBuf::from("/release/dist").join(file_name))
+                .unwrap();
+            environment.add_remote_file(&format!("http://localhost/registry/1.0.0/{}", file_name), bytes);
+        }
+        let registry_bytes = environment.read_file(&PathBuf::from("/release/registry.json")).unwrap();
+        environment.add_remote_file("http://localhost/registry/registry.json", registry_bytes);
+        run_cli(
+            vec!["registry", "add", "http://localhost/registry/registry.json"],
+            &environment,
+        )
+        .unwrap();
+        run_cli(vec!["install", "--use", "name", "1"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+
+        // adds newer versions first
+        write_spec("2.0.0");
+        run_cli(vec!["registry", "pack", "pack.json"], &environment).unwrap();
+        environment.clear_logs();
+        let registry_file: serde_json::Value = serde_json::from_str(
+            &environment
+                .read_file_text(&PathBuf::from("/release/registry.json"))
+                .unwrap(),
+        )
+        .unwrap();
+        let versions = registry_file["binaries"][0]["versions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v["version"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec!["2.0.0", "1.0.0"]);
+        assert_eq!(
+            registry_file["binaries"][0]["versions"][0]["path"],
+            "http://localhost/registry/2.0.0.json"
+        );
+
+        environment
+            .write_file_text(&PathBuf::from("/release/dist/name-linux.zip"), "")
+            .unwrap();
+        let err = run_cli(vec!["registry", "pack", "pack.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Found multiple assets for linux-x86_64: http://localhost/registry/2.0.0/name-linux.zip and name-x86_64-unknown-linux-gnu.zip. Specify the asset of the platform in 'platforms' instead."
+        );
+    }
+
+    #[test]
+    fn plugin_file_cache() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        environment.set_remote_file_etag("http://localhost/package.json", "\"1\"");
+        let take_downloaded_plugin_file_count = || {
+            environment
+                .take_downloaded_urls()
+                .into_iter()
+                .filter(|url| url == "http://localhost/package.json")
+                .count()
+        };
+        install_url!(environment, "http://localhost/package.json");
+        assert_eq!(take_downloaded_plugin_file_count(), 1);
+
+        // reuses the cached copy when the plugin file wasn't modified
+        run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_eq!(take_downloaded_plugin_file_count(), 0);
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+
+        // downloads it again once modified
+        environment.set_remote_file_etag("http://localhost/package.json", "\"2\"");
+        run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_eq!(take_downloaded_plugin_file_count(), 1);
+
+        // downloads it again when the cached copy doesn't match its metadata
+        let cache_dir_path = crate::plugins::get_plugin_file_cache(&environment)
+            .dir_path()
+            .to_path_buf();
+        let cached_file_path = environment
+            .get_file_paths(&cache_dir_path)
+            .unwrap()
+            .into_iter()
+            .find(|file_path| !file_path.to_string_lossy().ends_with(".meta.json"))
+            .unwrap();
+        environment.write_file_text(&cached_file_path, "{").unwrap();
+        run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_eq!(take_downloaded_plugin_file_count(), 1);
+        run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_eq!(take_downloaded_plugin_file_count(), 0);
+
+        // pruning removes the invalid cached copies
+        environment.write_file_text(&cached_file_path, "{").unwrap();
+        run_cli(vec!["prune", "--unused-for", "90d"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Removed 1 invalid cached download.",
+                "No versions were unused for that long."
+            ]
+        );
+        assert_eq!(environment.get_file_paths(&cache_dir_path).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn registry_file_cache() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+        environment.set_remote_file_etag("http://localhost/registry.json", "\"1\"");
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+        assert_eq!(
+            environment.take_downloaded_urls(),
+            vec!["http://localhost/registry.json".to_string()]
+        );
+
+        // uses the cached copy until it expires
+        run_cli(vec!["ls-remote", "name"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0"]);
+        assert_eq!(environment.take_downloaded_urls(), Vec::<String>::new());
+
+        // revalidates the cached copy once expired or when refreshing
+        environment.set_time_secs(123456 + 300);
+        run_cli(vec!["ls-remote", "name"], &environment).unwrap();
+        run_cli(vec!["ls-remote", "name", "--refresh"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0", "1.0.0"]);
+        assert_eq!(environment.take_downloaded_urls(), Vec::<String>::new());
+
+        // downloads when modified
+        environment.set_remote_file_etag("http://localhost/registry.json", "\"2\"");
+        run_cli(vec!["ls-remote", "name", "--refresh"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0"]);
+        assert_eq!(
+            environment.take_downloaded_urls(),
+            vec!["http://localhost/registry.json".to_string()]
+        );
+
+        // the ttl is configurable
+        environment.set_env_var("BVM_REGISTRY_CACHE_TTL", "0");
+        run_cli(vec!["ls-remote", "name"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0"]);
+        environment.set_remote_file_etag("http://localhost/registry.json", "\"3\"");
+        run_cli(vec!["ls-remote", "name"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0"]);
+        assert_eq!(
+            environment.take_downloaded_urls(),
+            vec!["http://localhost/registry.json".to_string()]
+        );
+    }
+
+    #[test]
+    fn registry_download_lock() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+        let lock_file_path = utils::get_download_lock_file_path(&environment, "http://localhost/registry.json");
+        environment.create_dir_all(lock_file_path.parent().unwrap()).unwrap();
+
+        // recovers from a lock left behind by a process that exited
+        environment.write_file_text(&lock_file_path, "1 100").unwrap();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+        assert_not_has_path!(environment, &lock_file_path.to_string_lossy().to_string());
+
+        // recovers from a recent lock of a process that exited
+        environment.write_file_text(&lock_file_path, "12345 123456").unwrap();
+        environment.set_process_exited(12345);
+        run_cli(vec!["ls-remote", "name", "--refresh"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0"]);
+        assert_not_has_path!(environment, &lock_file_path.to_string_lossy().to_string());
+
+        // waits for a lock held by another process until it stops being refreshed
+        environment.write_file_text(&lock_file_path, "1 123456").unwrap();
+        run_cli(vec!["ls-remote", "name", "--refresh"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0"]);
+        assert_logs_errors!(
+            environment,
+            ["Waiting for another bvm process to download http://localhost/registry.json..."]
+        );
+        assert_not_has_path!(environment, &lock_file_path.to_string_lossy().to_string());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn wsl_windows_data_dir() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+        run_cli(vec!["config", "set", "wslWindowsDataDir", "/mnt/c/bvm"], &environment).unwrap();
+
+        // ignored outside of wsl
+        assert_eq!(
+            utils::get_download_lock_file_path(&environment, "http://localhost/registry.json").starts_with("/data"),
+            true
+        );
+
+        // shares the registry cache and download locks with windows in wsl
+        environment.set_env_var("WSL_DISTRO_NAME", "Ubuntu");
+        let lock_file_path = utils::get_download_lock_file_path(&environment, "http://localhost/registry.json");
+        assert_eq!(lock_file_path.starts_with("/mnt/c/bvm/downloads"), true);
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+        assert_eq!(
+            environment.is_dir(std::path::Path::new("/mnt/c/bvm/registry-cache")),
+            true
+        );
+        assert_eq!(environment.is_dir(std::path::Path::new("/data/registry-cache")), false);
+        run_cli(vec!["install", "name", "1.0.0"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn read_only_root() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+
+        // install a binary in what becomes the read-only root (ex. when building a CI image)
+        environment.set_env_var("BVM_DATA_DIR", "/opt/bvm");
+        environment.set_env_var("BVM_CACHE_DIR", "/opt/bvm");
+        install_url!(environment, "http://localhost/package.json");
+        environment.set_env_var("BVM_DATA_DIR", "");
+        environment.set_env_var("BVM_CACHE_DIR", "");
+        environment.set_env_var("BVM_READ_ONLY_ROOT", "/opt/bvm");
+        environment.clear_logs();
+        environment.take_downloaded_urls();
+
+        // it's used without downloading it again
+        let read_only_binary_path = if cfg!(target_os = "windows") {
+            "/opt/bvm\\binaries\\owner\\name\\1.0.0\\name.exe"
+        } else {
+            "/opt/bvm/binaries/owner/name/1.0.0/name"
+        }
+        .to_string();
+        install_url!(environment, "http://localhost/package.json");
+        assert_eq!(environment.take_downloaded_urls(), Vec::<String>::new());
+        assert_has_path!(environment, &get_shim_path("name"));
+        assert_resolves!(environment, read_only_binary_path);
+        assert!(!environment.is_dir(&PathBuf::from(get_binary_dir("owner", "name", "1.0.0"))));
+
+        // new installs go to the user's directory
+        install_url!(environment, "http://localhost/package2.json");
+        run_cli(vec!["use", "name", "2.0.0"], &environment).unwrap();
+        assert_resolves!(environment, get_binary_path("owner", "name", "2.0.0"));
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        assert_resolves!(environment, read_only_binary_path);
+        environment.clear_logs();
+
+        let error_message = run_cli(vec!["uninstall", "name", "1.0.0"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Cannot uninstall owner/name 1.0.0 because it's used from the read-only root."
+        );
+        assert_has_path!(environment, &read_only_binary_path);
+
+        // the read-only root's binaries aren't written to the user's manifest
+        let manifest_text = environment
+            .read_file_text(&PathBuf::from("/data/binaries-manifest.json"))
+            .unwrap();
+        assert_eq!(manifest_text.contains("owner||name||2.0.0"), true);
+        assert_eq!(manifest_text.contains("\"owner||name||1.0.0\":{"), false);
+    }
+
+    #[test]
+    fn data_and_cache_dir_overrides() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+
+        // environment variables
+        environment.set_env_var("BVM_DATA_DIR", "/env-data");
+        environment.set_env_var("BVM_CACHE_DIR", "/env-cache");
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+        assert_has_path!(environment, PathBuf::from("/env-data").join("binaries-manifest.json"));
+        assert!(environment.is_dir(
+            &PathBuf::from("/env-cache")
+                .join("binaries")
+                .join("owner")
+                .join("name")
+                .join("1.0.0")
+        ));
+        assert!(!environment.is_dir(&PathBuf::from(get_binary_dir("owner", "name", "1.0.0"))));
+
+        // user config, which stays in the default directory
+        environment.set_env_var("BVM_DATA_DIR", "");
+        environment.set_env_var("BVM_CACHE_DIR", "");
+        run_cli(vec!["config", "set", "dataDir", "/config-data"], &environment).unwrap();
+        run_cli(vec!["config", "set", "cacheDir", "config-cache"], &environment).unwrap();
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/data/config.json")).unwrap(),
+            format!(
+                "{{\n  \"cacheDir\": {},\n  \"dataDir\": \"/config-data\"\n}}",
+                serde_json::to_string(&PathBuf::from("/").join("config-cache").to_string_lossy()).unwrap()
+            )
+        );
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+        assert_has_path!(
+            environment,
+            PathBuf::from("/config-data").join("binaries-manifest.json")
+        );
+        assert!(environment.is_dir(
+            &PathBuf::from("/")
+                .join("config-cache")
+                .join("binaries")
+                .join("owner")
+                .join("name")
+                .join("1.0.0")
+        ));
+
+        // the environment variables take precedence
+        environment.set_env_var("BVM_DATA_DIR", "/env-data");
+        assert_eq!(environment.get_user_data_dir(), PathBuf::from("/env-data"));
+
+        run_cli(vec!["config", "unset", "dataDir"], &environment).unwrap();
+        environment.set_env_var("BVM_DATA_DIR", "");
+        assert_eq!(environment.get_user_data_dir(), PathBuf::from("/data"));
+
+        // invalid settings
+        let error_message = run_cli(vec!["config", "set", "unknown", "value"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            format!(
+                "Unknown config key 'unknown'. Expected one of: {}",
+                crate::environment::USER_CONFIG_KEYS.join(", ")
+            )
+        );
+        let error_message = run_cli(vec!["config", "set", "keepVersions", "many"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Invalid value for 'keepVersions'. invalid type: string \"many\", expected usize"
+        );
+    }
+
+    #[test]
+    fn owner_registries() {
+        let builder = EnvironmentBuilder::new();
+        let public_checksum =
+            builder.create_remote_zip_package("http://localhost/public-package.json", "myorg", "tool", "1.0.0");
+        let corp_checksum =
+            builder.create_remote_zip_package("http://localhost/package.json", "myorg", "tool", "2.0.0");
+        let mirror_checksum =
+            builder.create_remote_zip_package("http://localhost/mirror-package.json", "myorg", "tool", "3.0.0");
+        let create_registry_file = |url: &str, version: &str, checksum: String, path: &str| {
+            builder.create_remote_registry_file(
+                url,
+                "myorg",
+                "tool",
+                vec![registry::RegistryVersionInfo {
+                    version: version.into(),
+                    checksum,
+                    path: path.to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                }],
+            );
+        };
+        create_registry_file(
+            "http://localhost/public.json",
+            "1.0.0",
+            public_checksum,
+            "http://localhost/public-package.json",
+        );
+        create_registry_file(
+            "http://localhost/corp.json",
+            "2.0.0",
+            corp_checksum,
+            "http://localhost/package.json",
+        );
+        create_registry_file(
+            "http://localhost/mirror.json",
+            "3.0.0",
+            mirror_checksum,
+            "http://localhost/mirror-package.json",
+        );
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/public.json"], &environment).unwrap();
+        run_cli(
+            vec![
+                "config",
+                "set",
+                "ownerRegistries",
+                r#"{ "myorg": "http://localhost/corp.json" }"#,
+            ],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+
+        // the public registry isn't used for the owner's binaries
+        run_cli(vec!["ls-remote", "myorg/tool"], &environment).unwrap();
+        assert_logs!(environment, ["2.0.0"]);
+        run_cli(vec!["ls-remote", "tool"], &environment).unwrap();
+        assert_logs!(environment, ["2.0.0"]);
+        run_cli(vec!["install", "tool"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves_name!(environment, "tool", get_binary_path("myorg", "tool", "2.0.0"));
+        assert_not_has_path!(environment, &get_binary_path("myorg", "tool", "1.0.0"));
+
+        let error_message = run_cli(vec!["install", "myorg/other"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find binary 'myorg/other' in http://localhost/corp.json, which is the registry of its owner."
+        );
+
+        // the configuration file of the current directory overrides the user config
+        builder
+            .create_bvmrc_builder()
+            .add_owner_registry("myorg", "http://localhost/mirror.json")
+            .build();
+        environment.set_cwd("/project");
+        run_cli(vec!["ls-remote", "myorg/tool"], &environment).unwrap();
+        assert_logs!(environment, ["3.0.0"]);
+
+        // the host of the configuration file's owner registry must be trusted when trusted hosts are configured
+        environment
+            .write_file_text(
+                &PathBuf::from("/data/config.json"),
+                r#"{ "trustedHosts": ["github.com"], "ownerRegistries": { "myorg": "http://localhost/corp.json" } }"#,
+            )
+            .unwrap();
+        environment.set_confirm_answer(Some(false));
+        let error_message = run_cli(vec!["ls-remote", "myorg/tool"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Did not download http://localhost/mirror.json because localhost is not a trusted host."
+        );
+        assert_logs_errors!(
+            environment,
+            ["localhost is not a trusted host. Trust it and download http://localhost/mirror.json?"]
+        );
+    }
+
+    #[test]
+    fn project_registries() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "myorg", "tool", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "myorg",
+            "tool",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        builder
+            .create_bvmrc_builder()
+            .add_registry("http://localhost/registry.json")
+            .build();
+        let environment = builder.build();
+
+        // not found outside the project
+        let error_message = run_cli(vec!["install", "tool"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "There were no registries found for the provided binary. Did you mean to add one using `bvm registry add <url>`?"
+        );
+
+        // asks to trust the registry's host on first use when trusted hosts are configured
+        environment.set_cwd("/project");
+        environment
+            .write_file_text(
+                &PathBuf::from("/data/config.json"),
+                r#"{ "trustedHosts": ["github.com"] }"#,
+            )
+            .unwrap();
+        environment.set_confirm_answer(Some(false));
+        let error_message = run_cli(vec!["install", "tool"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Did not download http://localhost/registry.json because localhost is not a trusted host."
+        );
+        environment.clear_logs();
+        environment.set_confirm_answer(Some(true));
+        run_cli(vec!["add", "tool"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "localhost is not a trusted host. Trust it and download http://localhost/registry.json?",
+                "Extracting archive for myorg/tool 1.0.0...",
+            ]
+        );
+        environment.set_confirm_answer(None);
+        run_cli(vec!["ls-remote", "tool"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0 (installed)"]);
+        assert_resolves_name!(environment, "tool", get_binary_path("myorg", "tool", "1.0.0"));
+
+        // the registry isn't added to the user's registries
+        run_cli(vec!["registry", "list"], &environment).unwrap();
+        assert_logs!(environment, []);
+    }
+
+    #[test]
+    fn user_config_defaults() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "2.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        builder.create_bvmrc(vec!["http://localhost/other.json"]);
+        let environment = builder.build();
+
+        // searches the default registries for names not associated with a registry
+        run_cli(
+            vec!["config", "set", "registries", r#"["http://localhost/registry.json"]"#],
+            &environment,
+        )
+        .unwrap();
+        run_cli(vec!["install", "name", "1.0.0"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+        run_cli(vec!["registry", "list"], &environment).unwrap();
+        assert_logs!(environment, []);
+
+        // installs the binaries of the configuration file when using them
+        environment.set_cwd("/project");
+        let error_message = run_cli(vec!["use"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Ensure binaries are installed before using. Run `bvm install` first then `bvm use`."
+        );
+        run_cli(vec!["config", "set", "autoInstall", "true"], &environment).unwrap();
+        run_cli(vec!["use"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/other 2.0.0..."]);
+        assert_resolves_name!(environment, "other", get_binary_path("owner", "other", "2.0.0"));
+
+        // target fallbacks must be known targets
+        run_cli(vec!["config", "set", "targetFallbacks", r#"["other"]"#], &environment).unwrap();
+        let error_message = run_cli(
+            vec!["install", "http://localhost/package.json", "--force"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package.json. Unknown target fallback 'other'. Supported targets: linux-x86_64, linux-x86_64-musl, darwin-x86_64, darwin-aarch64, windows-x86_64, windows-aarch64"
+        );
+    }
+
+    #[test]
+    fn install_trusted_hosts() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        let trusted_hosts_file_path = PathBuf::from("/data/trusted-hosts.json");
+        environment
+            .write_file_text(
+                &PathBuf::from("/data/config.json"),
+                r#"{ "trustedHosts": ["github.com"] }"#,
+            )
+            .unwrap();
+
+        // fails when not interactive
+        let error_message = run_cli(vec!["install", "http://localhost/package.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package.json. Did not download http://localhost/package.json because localhost is not a trusted host. Provide the `--trust` flag to trust it or add it to \"trustedHosts\" in the user config.json file."
+        );
+
+        // asks to trust the host
+        environment.set_confirm_answer(Some(false));
+        let error_message = run_cli(vec!["install", "http://localhost/package.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package.json. Did not download http://localhost/package.json because localhost is not a trusted host."
+        );
+        assert_logs_errors!(
+            environment,
+            ["localhost is not a trusted host. Trust it and download http://localhost/package.json?"]
+        );
+        environment.set_confirm_answer(Some(true));
+        run_cli(vec!["install", "http://localhost/package.json"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "localhost is not a trusted host. Trust it and download http://localhost/package.json?",
+                "Extracting archive for owner/name 1.0.0...",
+            ]
+        );
+        assert_eq!(
+            environment.read_file_text(&trusted_hosts_file_path).unwrap(),
+            "[\n  \"localhost\"\n]"
+        );
+
+        // trusts the host without asking when providing --trust
+        environment.set_confirm_answer(None);
+        run_cli(vec!["uninstall", "name", "1.0.0"], &environment).unwrap();
+        environment.remove_file(&trusted_hosts_file_path).unwrap();
+        run_cli(
+            vec!["install", "--trust", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&trusted_hosts_file_path).unwrap(),
+            "[\n  \"localhost\"\n]"
+        );
+    }
+
+    #[test]
+    fn install_prompt_mode() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        let trusted_hosts_file_path = PathBuf::from("/data/trusted-hosts.json");
+        environment
+            .write_file_text(
+                &PathBuf::from("/data/config.json"),
+                r#"{ "trustedHosts": ["github.com"] }"#,
+            )
+            .unwrap();
+        let not_trusted_message = concat!(
+            "Error installing http://localhost/package.json. Did not download http://localhost/package.json because localhost is not a trusted host. ",
+            "Provide the `--trust` flag to trust it or add it to \"trustedHosts\" in the user config.json file."
+        );
+        environment.set_confirm_answer(Some(true));
+
+        // doesn't ask when providing --no-prompt
+        let error_message = run_cli(
+            vec!["install", "--no-prompt", "http://localhost/package.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error_message.to_string(), not_trusted_message);
+
+        // doesn't ask when running in CI
+        environment.set_prompt_mode(PromptMode::Auto);
+        environment.set_env_var("CI", "true");
+        let error_message = run_cli(vec!["install", "http://localhost/package.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(error_message.to_string(), not_trusted_message);
+
+        // answers yes when providing --yes, even in CI
+        run_cli(vec!["install", "-y", "http://localhost/package.json"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "localhost is not a trusted host. Trust it and download http://localhost/package.json? Yes (--yes)",
+                "Extracting archive for owner/name 1.0.0...",
+            ]
+        );
+        assert_eq!(
+            environment.read_file_text(&trusted_hosts_file_path).unwrap(),
+            "[\n  \"localhost\"\n]"
+        );
+
+        // the flags conflict
+        let error_message = run_cli(
+            vec!["install", "--yes", "--no-prompt", "http://localhost/package.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert!(error_message
+            .to_string()
+            .contains("The argument '--no-prompt' cannot be used with '--yes'"));
+    }
+
+    #[test]
+    fn install_require_checksums() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_archive("http://localhost/name.zip", vec!["name"]);
+        builder.create_remote_github_release("owner", "other", "v1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .require_checksums(false)
+            .add_binary_path("gh:owner/other")
+            .build();
+        let environment = builder.build();
+        let release_bytes = environment
+            .download_file("https://api.github.com/repos/owner/other/releases/tags/v1.0.0")
+            .unwrap();
+        environment.add_remote_file(
+            "https://api.github.com/repos/owner/other/releases/latest",
+            release_bytes,
+        );
+        environment
+            .write_file_text(&PathBuf::from("/data/config.json"), r#"{ "requireChecksums": true }"#)
+            .unwrap();
+
+        // archives verified by the plugin file's checksum are installed
+        run_cli(vec!["install", "http://localhost/package.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        // the checksum of an archive url is unknown
+        let error_message = run_cli(
+            vec![
+                "install",
+                "--name",
+                "owner/name",
+                "--version",
+                "1.2.3",
+                "http://localhost/name.zip",
+            ],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/name.zip. Did not install owner/name 1.2.3 because checksums are required and no checksum was provided for http://localhost/name.zip."
+        );
+
+        // the release doesn't provide a digest for its assets
+        let error_message = run_cli(vec!["install", "gh:owner/other@v1.0.0"], &environment)
+            .err()
+            .unwrap();
+        assert!(error_message
+            .to_string()
+            .contains("Did not install owner/other 1.0.0 because checksums are required and no checksum was provided for https://github.com/owner/other/releases/download/v1.0.0/"));
+
+        // the project config can't opt out of the checksums the user config requires
+        environment.set_cwd("/project");
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert!(error_message
+            .to_string()
+            .contains("Did not install owner/other 1.0.0 because checksums are required and no checksum was provided for https://github.com/owner/other/releases/download/v1.0.0/"));
+
+        // the project config may require checksums when the user config doesn't
+        environment
+            .write_file_text(&PathBuf::from("/data/config.json"), r#"{ "requireChecksums": false }"#)
+            .unwrap();
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/other 1.0.0..."]);
+        run_cli(vec!["uninstall", "owner/other", "1.0.0"], &environment).unwrap();
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.json"),
+                r#"{ "requireChecksums": true, "binaries": ["gh:owner/other"] }"#,
+            )
+            .unwrap();
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert!(error_message
+            .to_string()
+            .contains("because checksums are required and no checksum was provided"));
+    }
+
+    #[test]
+    fn manifest_binary_files() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        let manifest_file_path = PathBuf::from("/data/binaries-manifest.json");
+        let binary_file_path = PathBuf::from("/data/binaries-manifest/owner/name/1.0.0.json");
+
+        // the details of each binary are stored outside the index
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        let index =
+            serde_json::from_str::<serde_json::Value>(&environment.read_file_text(&manifest_file_path).unwrap())
+                .unwrap();
+        assert_eq!(index["schemaVersion"], 2);
+        assert_eq!(
+            index["binaries"]["owner||name||1.0.0"],
+            serde_json::json!({ "name": "owner/name", "version": "1.0.0", "commands": ["name"] })
+        );
+        let item =
+            serde_json::from_str::<serde_json::Value>(&environment.read_file_text(&binary_file_path).unwrap()).unwrap();
+        assert_eq!(item["source"]["path"], "http://localhost/package.json");
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+
+        // upgrades a manifest with the binaries inline
+        let mut legacy_manifest = index.clone();
+        legacy_manifest.as_object_mut().unwrap().remove("schemaVersion");
+        legacy_manifest["binaries"]["owner||name||1.0.0"] = item;
+        environment
+            .write_file_text(&manifest_file_path, &legacy_manifest.to_string())
+            .unwrap();
+        environment.remove_file(&binary_file_path).unwrap();
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+        run_cli(vec!["hidden", "clear-pending-env-changes"], &environment).unwrap();
+        assert!(environment.path_exists(&binary_file_path));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&environment.read_file_text(&manifest_file_path).unwrap())
+                .unwrap(),
+            index
+        );
+
+        // a binary with a corrupt file is treated as not installed
+        environment.write_file_text(&binary_file_path, "{").unwrap();
+        install_url!(environment, "http://localhost/package.json");
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        let item =
+            serde_json::from_str::<serde_json::Value>(&environment.read_file_text(&binary_file_path).unwrap()).unwrap();
+        assert_eq!(item["source"]["path"], "http://localhost/package.json");
+
+        // removes the binary's file on uninstall
+        run_cli(vec!["uninstall", "owner/name", "1.0.0"], &environment).unwrap();
+        assert!(!environment.path_exists(&binary_file_path));
+    }
+
+    #[test]
+    fn manifest_batched_writes() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name2", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name3", "1.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json", "http://localhost/package2.json"]);
+        let environment = builder.build();
+        let manifest_file_path = PathBuf::from("/data/binaries-manifest.json");
+        let get_manifest_write_count = || {
+            environment
+                .take_written_files()
+                .into_iter()
+                .filter(|file_path| file_path == &manifest_file_path)
+                .count()
+        };
+        environment.take_written_files();
+
+        // installing many binaries writes the manifest once
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+        assert_eq!(get_manifest_write_count(), 1);
+        run_cli(
+            vec![
+                "install",
+                "http://localhost/package3.json",
+                "http://localhost/package.json",
+            ],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_eq!(get_manifest_write_count(), 1);
+
+        // nothing is written when nothing changed
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_eq!(get_manifest_write_count(), 0);
+        run_cli(vec!["hidden", "clear-pending-env-changes"], &environment).unwrap();
+        assert_eq!(get_manifest_write_count(), 0);
+    }
+
+    #[test]
+    fn logging_flags() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+
+        // outputs what's being done when verbose
+        run_cli(
+            vec!["install", "--verbose", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "[VERBOSE]: Downloading binary manifest file http://localhost/package.json.".to_string(),
+                format!(
+                    "[VERBOSE]: Using the {} binary of owner/name 1.0.0.",
+                    crate::plugins::get_platform_keys()[0]
+                ),
+                "Extracting archive for owner/name 1.0.0...".to_string(),
+            ]
+        );
+        environment.set_verbose(false);
+
+        // outputs messages as json
+        run_cli(
+            vec![
+                "install",
+                "--force",
+                "--log-format",
+                "json",
+                "http://localhost/package.json",
+            ],
+            &environment,
+        )
+        .unwrap();
+        assert_logs_errors!(
+            environment,
+            [r#"{"level":"info","message":"Extracting archive for owner/name 1.0.0..."}"#]
+        );
+        environment.set_log_format(crate::environment::LogFormat::Text);
+
+        // only outputs errors when quiet
+        run_cli(
+            vec!["install", "-q", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs_errors!(environment, []);
+        run_cli(vec!["list", "-q"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)"]);
+
+        let error_message = run_cli(vec!["list", "-v", "-q"], &environment).err().unwrap();
+        assert!(error_message
+            .to_string()
+            .contains("The argument '--quiet' cannot be used with '--verbose'"));
+    }
+
+    #[test]
+    fn install_events() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        let name = BinaryName::new("owner".to_string(), "name".to_string());
+        let os = if cfg!(target_os = "windows") {
+            "windows"
+        } else if cfg!(target_os = "macos") {
+            "mac"
+        } else {
+            "linux"
+        };
+
+        run_cli(vec!["install", "http://localhost/package.json"], &environment).unwrap();
+        assert_eq!(
+            environment.take_events(),
+            vec![
+                Event::DownloadStarted {
+                    name: name.clone(),
+                    version: Version::parse("1.0.0").unwrap(),
+                    url: format!(
+                        "https://github.com/dsherret/bvm/releases/download/1.0.0/name-{}.zip",
+                        os
+                    ),
+                },
+                Event::Extracted {
+                    name: name.clone(),
+                    version: Version::parse("1.0.0").unwrap(),
+                    dir_path: PathBuf::from("/local-data/binaries/owner/name/1.0.0"),
+                },
+                Event::VersionSwitched {
+                    command_name: CommandName::from_string("name".to_string()),
+                    binary: Some((name.clone(), Version::parse("1.0.0").unwrap())),
+                },
+                Event::ManifestUpdated,
+            ]
+        );
+
+        // switching versions
+        run_cli(vec!["install", "--use", "http://localhost/package2.json"], &environment).unwrap();
+        environment.take_events();
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        assert_eq!(
+            environment.take_events(),
+            vec![
+                Event::VersionSwitched {
+                    command_name: CommandName::from_string("name".to_string()),
+                    binary: Some((name.clone(), Version::parse("1.0.0").unwrap())),
+                },
+                Event::ManifestUpdated,
+            ]
+        );
+
+        // resolving a command doesn't switch the global version
+        environment.clear_logs();
+        resolve_command_path(&environment, "name").unwrap();
+        assert_eq!(environment.take_events(), Vec::new());
+    }
+
+    #[test]
+    fn color_flag() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        run_cli(vec!["install", "http://localhost/package.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        // not colored by default when the output isn't a terminal
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)"]);
+
+        run_cli(vec!["list", "--color", "always"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["\u{1b}[1mowner/name\u{1b}[0m \u{1b}[32m1.0.0\u{1b}[0m \u{1b}[32m(global)\u{1b}[0m"]
+        );
+
+        // colored automatically for a terminal unless NO_COLOR is set
+        environment.set_color_choice(crate::environment::ColorChoice::Auto);
+        environment.set_is_terminal(true);
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["\u{1b}[1mowner/name\u{1b}[0m \u{1b}[32m1.0.0\u{1b}[0m \u{1b}[32m(global)\u{1b}[0m"]
+        );
+        run_cli(vec!["list", "--color", "never"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)"]);
+
+        environment.set_color_choice(crate::environment::ColorChoice::Auto);
+        environment.set_env_var("NO_COLOR", "1");
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)"]);
+    }
+
+    #[test]
+    fn info_command() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        let first_provenance = get_binary_provenance(&environment, "owner", "name", "1.0.0");
+        let second_provenance = get_binary_provenance(&environment, "owner", "name", "2.0.0");
+        assert!(first_provenance
+            .url
+            .starts_with("https://github.com/dsherret/bvm/releases/download/1.0.0/name-"));
+        assert_eq!(
+            first_provenance.registry,
+            Some("http://localhost/registry.json".to_string())
+        );
+        assert_eq!(second_provenance.registry, None);
+        assert_eq!(first_provenance.bvm_version, env!("CARGO_PKG_VERSION"));
+
+        run_cli(vec!["info", "name"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [format!(
+                "Name: owner/name\nDescription: Some description.\nRegistries:\n  http://localhost/registry.json\nInstalled versions:\n  1.0.0 - {} (179 B, {2})\n    Source: {3}\n    Checksum: {4}\n    Registry: http://localhost/registry.json\n    Installed: 1970-01-02T10:17:36Z with bvm {7}\n  2.0.0 - {} (179 B, {2})\n    Source: {5}\n    Checksum: {6}\n    Installed: 1970-01-02T10:17:36Z with bvm {7}\nCommands:\n  name - global: 1.0.0",
+                get_binary_dir("owner", "name", "1.0.0"),
+                get_binary_dir("owner", "name", "2.0.0"),
+                crate::plugins::get_platform_keys()[0],
+                first_provenance.url,
+                first_provenance.checksum,
+                second_provenance.url,
+                second_provenance.checksum,
+                env!("CARGO_PKG_VERSION"),
+            )]
+        );
+
+        run_cli(vec!["info", "name", "--json"], &environment).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&environment.take_logged_messages()[0]).unwrap();
+        assert_eq!(value["name"], "owner/name");
+        assert_eq!(value["versions"][0]["binaryManifest"], "http://localhost/package.json");
+        assert_eq!(value["versions"][0]["provenance"]["url"], first_provenance.url.as_str());
+        assert_eq!(
+            value["versions"][0]["provenance"]["checksum"],
+            first_provenance.checksum.as_str()
+        );
+        assert_eq!(
+            value["versions"][0]["provenance"]["registry"],
+            "http://localhost/registry.json"
+        );
+        assert_eq!(
+            value["versions"][0]["provenance"]["installedTime"],
+            "1970-01-02T10:17:36Z"
+        );
+        assert_eq!(value["versions"][1]["provenance"]["registry"], serde_json::Value::Null);
+        assert_eq!(value["commands"][0]["name"], "name");
+        assert_eq!(value["commands"][0]["global"], "1.0.0");
+
+        let error_message = run_cli(vec!["info", "other"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find binary 'other' installed or in any registry."
+        );
+    }
+
+    #[test]
+    fn resolve_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_multiple_commands_package(
+            "http://localhost/package3.json",
+            "owner",
+            "multi",
+            "1.0.0",
+        );
+        builder.create_bvmrc(vec!["http://localhost/package2.json"]);
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        environment.clear_logs();
+
+        // uses the global version outside the project
+        run_cli(vec!["resolve", "name"], &environment).unwrap();
+        assert_logs!(environment, [get_binary_path("owner", "name", "1.0.0")]);
+        run_cli(vec!["resolve", "owner/name@2"], &environment).unwrap();
+        assert_logs!(environment, [get_binary_path("owner", "name", "2.0.0")]);
+
+        // uses the version of the config file
+        environment.set_cwd("/project");
+        run_cli(vec!["resolve", "name"], &environment).unwrap();
+        assert_logs!(environment, [get_binary_path("owner", "name", "2.0.0")]);
+        run_cli(vec!["resolve", "name@1.0.0"], &environment).unwrap();
+        assert_logs!(environment, [get_binary_path("owner", "name", "1.0.0")]);
+
+        // commands
+        run_cli(vec!["resolve", "multi"], &environment).unwrap();
+        assert_logs!(environment, [get_binary_path("owner", "multi", "1.0.0")]);
+        run_cli(vec!["resolve", "multi", "--command", "multi-second"], &environment).unwrap();
+        assert_logs!(environment, [get_binary_path_second("owner", "multi", "1.0.0")]);
+        let error_message = run_cli(vec!["resolve", "name", "--command", "other"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "owner/name 2.0.0 does not have a 'other' command. Commands: name"
+        );
+    }
+
+    #[test]
+    fn which_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package2.json"]);
+        let path_exe_path = builder.add_binary_to_path("other");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        run_cli(vec!["which", "name"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [format!(
+                "{} (owner/name 1.0.0)",
+                get_binary_path("owner", "name", "1.0.0")
+            )]
+        );
+        run_cli(vec!["which", "other"], &environment).unwrap();
+        assert_logs!(environment, [format!("{} (on the path)", path_exe_path)]);
+
+        // only outputs the path when quiet
+        environment.set_cwd("/project");
+        run_cli(vec!["which", "--quiet", "name"], &environment).unwrap();
+        assert_logs!(environment, [get_binary_path("owner", "name", "2.0.0")]);
+    }
+
+    #[test]
+    fn why_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let checksum = builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "3.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "3.0.0".into(),
+                checksum,
+                path: "http://localhost/package3.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        builder.create_bvmrc(vec!["http://localhost/package2.json"]);
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        run_cli(vec!["install", "name@3.0.0"], &environment).unwrap();
+        environment.clear_logs();
+        environment.set_time_secs(123456 + 10 * 24 * 60 * 60);
+
+        // explains the version used by the config file
+        environment.set_cwd("/project");
+        run_cli(vec!["why", "name"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [concat!(
+                "owner/name 2.0.0\n",
+                "Installed from: http://localhost/package2.json\n",
+                "Registry: None\n",
+                "Config file: /project/bvm.json (http://localhost/package2.json)\n",
+                "Global commands: None\n",
+                "Commands in the current directory:\n",
+                "  name - owner/name 2.0.0 from /project/bvm.json\n",
+                "Prune: kept because it's used by the current directory\n",
+                "Other versions prune may remove:\n",
+                "  3.0.0 (unused for 10 days)"
+            )]
+        );
+
+        run_cli(vec!["why", "name@3"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [concat!(
+                "owner/name 3.0.0\n",
+                "Installed from: http://localhost/package3.json\n",
+                "Registry: http://localhost/registry.json\n",
+                "Config file: None\n",
+                "Global commands: None\n",
+                "Commands in the current directory:\n",
+                "  name - owner/name 2.0.0 from /project/bvm.json\n",
+                "Prune: may be removed (unused for 10 days)\n",
+                "Other versions prune may remove: None"
+            )]
+        );
+
+        // the version selected with `bvm use` in the project takes precedence
+        run_cli(vec!["use", "name", "3.0.0"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["why", "name@1"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [concat!(
+                "owner/name 1.0.0\n",
+                "Installed from: http://localhost/package.json\n",
+                "Registry: None\n",
+                "Config file: None\n",
+                "Global commands: name\n",
+                "Commands in the current directory:\n",
+                "  name - owner/name 3.0.0 from `bvm use` in /project\n",
+                "Prune: kept because it's used globally\n",
+                "Other versions prune may remove: None"
+            )]
+        );
+
+        let error_message = run_cli(vec!["why", "other"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find any installed binaries named 'other'"
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn install_musl_binary() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.add_linux_musl_zip();
+        plugin_builder.build();
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        environment
+            .write_file_text(&PathBuf::from("/lib/ld-musl-x86_64.so.1"), "")
+            .unwrap();
+
+        // prefers the musl binary
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+        let binary_path = PathBuf::from(get_binary_dir("owner", "name", "1.0.0")).join("name");
+        assert_eq!(
+            environment.read_file_text(&binary_path).unwrap(),
+            "test-name-https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux-musl.zip"
+        );
+
+        // falls back to the glibc binary with a warning
+        install_url!(environment, "http://localhost/package2.json");
+        assert_logs_errors!(
+            environment,
+            [
+                "Warning: owner/name 2.0.0 does not provide a binary for linux-x86_64-musl. Installed the linux-x86_64 binary, which may fail to run on this musl based system.",
+                "Extracting archive for owner/name 2.0.0...",
+                "Installed. Run `bvm use name 2.0.0` to use it on the path as 'name'."
+            ]
+        );
+
+        run_cli(vec!["info", "name"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [format!(
+                "Name: owner/name\nRegistries: None\nInstalled versions:\n  1.0.0 - {} (184 B, linux-x86_64-musl)\n    Source: https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux-musl.zip\n    Checksum: {}\n    Installed: 1970-01-02T10:17:36Z with bvm {4}\n  2.0.0 - {} (179 B, linux-x86_64)\n    Source: https://github.com/dsherret/bvm/releases/download/2.0.0/name-linux.zip\n    Checksum: {}\n    Installed: 1970-01-02T10:17:36Z with bvm {4}\nCommands:\n  name - global: 1.0.0",
+                get_binary_dir("owner", "name", "1.0.0"),
+                get_binary_provenance(&environment, "owner", "name", "1.0.0").checksum,
+                get_binary_dir("owner", "name", "2.0.0"),
+                get_binary_provenance(&environment, "owner", "name", "2.0.0").checksum,
+                env!("CARGO_PKG_VERSION"),
+            )]
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn install_target_override() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.add_linux_musl_zip();
+        plugin_builder.build();
+        let environment = builder.build();
+
+        // not provided by the plugin file
+        let error_message = run_cli(
+            vec!["install", "--target", "darwin-aarch64", "http://localhost/package.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package.json. owner/name 1.0.0 does not provide a binary for target darwin-aarch64."
+        );
+
+        // unknown target in the environment variable
+        environment.set_env_var("BVM_TARGET", "other");
+        let error_message = run_cli(vec!["install", "http://localhost/package.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package.json. Unknown target 'other'. Supported targets: linux-x86_64, linux-x86_64-musl, darwin-x86_64, darwin-aarch64, windows-x86_64, windows-aarch64"
+        );
+
+        // the flag takes precedence over the environment variable
+        environment.set_env_var("BVM_TARGET", "darwin-x86_64");
+        run_cli(
+            vec![
+                "install",
+                "--target",
+                "linux-x86_64-musl",
+                "http://localhost/package.json",
+            ],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        let binary_path = PathBuf::from(get_binary_dir("owner", "name", "1.0.0")).join("name");
+        assert_eq!(
+            environment.read_file_text(&binary_path).unwrap(),
+            "test-name-https://github.com/dsherret/bvm/releases/download/1.0.0/name-linux-musl.zip"
+        );
+
+        run_cli(
+            vec!["install", "--force", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_eq!(
+            environment.read_file_text(&binary_path).unwrap(),
+            "test-name-https://github.com/dsherret/bvm/releases/download/1.0.0/name-mac.zip"
+        );
+    }
+
+    #[test]
+    fn self_update_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_bvm_release("v99.0.0");
+        let environment = builder.build();
+        let exe_name = if cfg!(target_os = "windows") {
+            "bvm-bin.exe"
+        } else {
+            "bvm-bin"
+        };
+        let exe_path = PathBuf::from("/.bvm/bin").join(exe_name);
+        environment.write_file_text(&exe_path, "old").unwrap();
+
+        run_cli(vec!["self-update"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting bvm 99.0.0..."]);
+        assert_logs!(
+            environment,
+            [format!("Updated bvm from {} to 99.0.0.", env!("CARGO_PKG_VERSION"))]
+        );
+        assert_eq!(
+            environment.read_file_text(&exe_path).unwrap(),
+            format!(
+                "test-bvm-bin-https://github.com/dsherret/bvm/releases/download/v99.0.0/bvm-{}.zip",
+                super::get_bvm_release_target()
+            )
+        );
+        assert_eq!(environment.path_exists(&PathBuf::from("/.bvm/bin/.self-update")), false);
+
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_bvm_release("v0.0.1");
+        let environment = builder.build();
+        run_cli(vec!["self-update"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [format!(
+                "bvm {} is already the latest version.",
+                env!("CARGO_PKG_VERSION")
+            )]
+        );
+    }
+
+    #[test]
+    fn registry_add_remove_list_command_path() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum: "".to_string(),
+                path: "https://localhost/test.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        builder.create_remote_registry_file(
+            "http://localhost/registry2.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "2.0.0".into(),
+                checksum: "".to_string(),
+                path: "https://localhost/test.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        builder.create_remote_registry_file(
+            "http://localhost/registry3.json",
+            "owner2",
+            "name2",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum: "".to_string(),
+                path: "https://localhost/test.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["Associated binaries:", "* owner/name - Some description."]
+        );
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap(); // add twice
+        assert_logs!(
+            environment,
+            ["Associated binaries:", "* owner/name - Some description."]
+        );
+        run_cli(vec!["registry", "add", "http://localhost/registry2.json"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [
+                "Associated binaries:",
+                "* owner/name - Some description.",
+                "\nWARNING! This may be ok, but the 'owner/name' binary was already associated to the following url(s): http://localhost/registry.json -- They are now all associated and binary selection will go through each registry to find a matching version."
+            ]
+        );
+        run_cli(vec!["registry", "add", "http://localhost/registry3.json"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["Associated binaries:", "* owner2/name2 - Some description."]
+        );
+        run_cli(vec!["registry", "list"], &environment).unwrap();
+        assert_logs!(environment, ["http://localhost/registry.json\n  owner/name\nhttp://localhost/registry2.json\n  owner/name\nhttp://localhost/registry3.json\n  owner2/name2"]);
+        run_cli(
+            vec!["registry", "remove", "http://localhost/registry.json"],
+            &environment,
+        )
+        .unwrap();
+        run_cli(
+            vec!["registry", "remove", "http://localhost/registry.json"],
+            &environment,
+        )
+        .unwrap(); // remove twice should silently ignore
+        run_cli(vec!["registry", "list"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["http://localhost/registry2.json\n  owner/name\nhttp://localhost/registry3.json\n  owner2/name2"]
+        );
+        run_cli(
+            vec!["registry", "remove", "http://localhost/registry2.json"],
+            &environment,
+        )
+        .unwrap();
+        run_cli(vec!["registry", "list"], &environment).unwrap();
+        assert_logs!(environment, ["http://localhost/registry3.json\n  owner2/name2"]);
+        run_cli(
+            vec!["registry", "remove", "http://localhost/registry3.json"],
+            &environment,
+        )
+        .unwrap();
+        run_cli(vec!["registry", "list"], &environment).unwrap();
+        assert_logs!(environment, []);
+    }
+
+    #[test]
+    fn registry_names() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        builder.create_remote_registry_file("http://localhost/registry2.json", "owner2", "name2", vec![]);
+        let environment = builder.build();
+
+        run_cli(
+            vec!["registry", "add", "http://localhost/registry.json", "--name", "tools"],
+            &environment,
+        )
+        .unwrap();
+        run_cli(vec!["registry", "add", "http://localhost/registry2.json"], &environment).unwrap();
+        environment.clear_logs();
+        let error_message = run_cli(
+            vec!["registry", "rename", "http://localhost/registry2.json", "tools"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "The name 'tools' is already used by the registry at http://localhost/registry.json."
+        );
+        let error_message = run_cli(vec!["registry", "rename", "other", "corp"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find a registry with the url or name 'other'."
+        );
+
+        // the name is kept when reassociating the url
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["registry", "rename", "tools", "corp"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["Renamed the registry at http://localhost/registry.json to 'corp'."]
+        );
+        run_cli(vec!["registry", "list"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["http://localhost/registry.json (corp)\n  owner/name\nhttp://localhost/registry2.json\n  owner2/name2"]
+        );
+
+        // removing warns about the binaries installed from it and clears its cached copy
+        run_cli(vec!["install", "name", "1.0.0"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        let get_cache_file_path = |url: &str| {
+            PathBuf::from("/data/registry-cache").join(format!(
+                "{}.json",
+                dprint_cli_core::checksums::get_sha256_checksum(url.as_bytes())
+            ))
+        };
+        assert_eq!(
+            environment.path_exists(&get_cache_file_path("http://localhost/registry.json")),
+            true
+        );
+        run_cli(vec!["registry", "remove", "corp"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Warning: The following installed binaries came from the removed registry. They remain installed, but other versions of them can no longer be installed from it:\n  owner/name 1.0.0"]
+        );
+        assert_eq!(
+            environment.path_exists(&get_cache_file_path("http://localhost/registry.json")),
+            false
+        );
+        assert_eq!(
+            environment.path_exists(&get_cache_file_path("http://localhost/registry2.json")),
+            true
+        );
+        run_cli(vec!["registry", "list"], &environment).unwrap();
+        assert_logs!(environment, ["http://localhost/registry2.json\n  owner2/name2"]);
+    }
+
+    #[test]
+    fn registry_install_command() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "1.0.1");
+        let checksum3 = builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "1.1.0");
+        let checksum4 = builder.create_remote_zip_package("http://localhost/binary.json", "other", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum,
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "1.0.1".into(),
+                    checksum: checksum2,
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "1.1.0".into(),
+                    checksum: checksum3,
+                    path: "http://localhost/package3.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        let environment = builder.build();
+
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+
+        assert_logs!(
+            environment,
+            vec!["Associated binaries:", "* owner/name - Some description."]
+        );
+
+        run_cli(vec!["install", "name", "1.0.0"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        // install latest when only specifying major
+        run_cli(vec!["install", "name", "1"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/name 1.1.0...",
+                "Installed. Run `bvm use name 1.1.0` to use it on the path as 'name'.",
+            ]
+        );
+
+        // install latest patch when specifying minor
+        run_cli(vec!["install", "name", "1.0"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/name 1.0.1...",
+                "Installed. Run `bvm use name 1.0.1` to use it on the path as 'name'.",
+            ]
+        );
+
+        run_cli(vec!["uninstall", "name", "1.0.1"], &environment).unwrap();
+        run_cli(vec!["uninstall", "name", "1.1.0"], &environment).unwrap();
+        environment.clear_logs();
+
+        // install when specifying caret
+        run_cli(vec!["install", "name", "^1.0.0"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/name 1.1.0...",
+                "Installed. Run `bvm use name 1.1.0` to use it on the path as 'name'.",
+            ]
+        );
+
+        // install when specifying tilde
+        run_cli(vec!["install", "name", "~1.0.0"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/name 1.0.1...",
+                "Installed. Run `bvm use name 1.0.1` to use it on the path as 'name'.",
+            ]
+        );
+
+        // clear up the state
+        run_cli(vec!["uninstall", "name", "1.1.0"], &environment).unwrap();
+        run_cli(vec!["uninstall", "name", "1.0.1"], &environment).unwrap();
+        environment.clear_logs();
+
+        // now update the registry file to have a different binary
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "other",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum: checksum4,
+                path: "http://localhost/binary.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let new_file_bytes = builder
+            .build()
+            .download_file(&"http://localhost/registry.json")
+            .unwrap();
+        environment.add_remote_file(&"http://localhost/registry.json", new_file_bytes);
+        // let the cached copy of the registry expire
+        environment.set_time_secs(123456 + 300);
+
+        // attempt to install the previous binary by name only and it should not exist (since we haven't reassociated the registry with the new name)
+        let err_message = run_cli(vec!["install", "name", "1.0.0"], &environment).err().unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            "Could not find binary 'name' matching '1.0.0' in any registry."
+        );
+
+        // now reassociate
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+
+        assert_logs!(
+            environment,
+            ["Associated binaries:", "* other/name - Some description."]
+        );
+
+        // and install
+        run_cli(vec!["install", "name", "1"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for other/name 1.0.0...",
+                "Installed. Run `bvm use other/name 1.0.0` to use it on the path as 'name'.",
+            ]
+        );
+    }
+
+    #[test]
+    fn registry_install_command_latest() {
+        let builder = EnvironmentBuilder::new();
+        let checksum1 = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let checksum3 = builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "2.0.1");
+        let checksum4 =
+            builder.create_remote_zip_package("http://localhost/package4.json", "owner", "name", "3.0.0-alpha");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum: checksum1,
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "2.0.1".into(),
+                    checksum: checksum3,
+                    path: "http://localhost/package3.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "2.0.0".into(),
+                    checksum: checksum2,
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "3.0.0-alpha".into(),
+                    checksum: checksum4,
+                    path: "http://localhost/package4.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        let environment = builder.build();
+
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["install", "name"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 2.0.1..."]);
+    }
+
+    #[test]
+    fn registry_install_command_latest_all_pre_releases() {
+        let builder = EnvironmentBuilder::new();
+        let checksum1 =
+            builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0-alpha");
+        let checksum2 =
+            builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "1.0.0-beta");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0-beta".into(),
+                    checksum: checksum2,
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "1.0.0-alpha".into(),
+                    checksum: checksum1,
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        let environment = builder.build();
+
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["install", "name"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0-beta..."]);
+    }
+
+    #[test]
+    fn registry_install_command_incorrect_checksum() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum: "wrong-checksum".to_string(),
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        let err = run_cli(vec!["install", "name", "1.0.0"], &environment).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Error installing http://localhost/package.json. The checksum {0} did not match the expected checksum of wrong-checksum. The download was quarantined at /data/quarantine/{0}-package.json.",
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn registry_install_command_no_registry() {
+        let environment = TestEnvironment::new();
+        let err = run_cli(vec!["install", "name", "1.0.0"], &environment).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "There were no registries found for the provided binary. Did you mean to add one using `bvm registry add <url>`?",
+        );
+    }
+
+    #[test]
+    fn registry_install_command_multiple_owners() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+
+        let checksum = builder.create_remote_zip_package("http://localhost/package2.json", "owner2", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry2.json",
+            "owner2",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package2.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        run_cli(vec!["registry", "add", "http://localhost/registry2.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        let error = run_cli(vec!["install", "name", "1.0.0"], &environment).err().unwrap();
+        assert_eq!(error.to_string(), "There were multiple binaries with the name 'name'. Please include the owner in the name:\n  owner/name\n  owner2/name");
+    }
+
+    #[test]
+    fn hidden_exec_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+
+        // arguments are passed through as-is
+        run_cli(
+            vec!["hidden", "exec-command", "name", "--version", "a \"b\""],
+            &environment,
+        )
+        .unwrap();
+        assert_eq!(
+            environment.take_run_executables(),
+            vec![(
+                PathBuf::from(get_binary_path("owner", "name", "1.0.0")),
+                vec!["--version".to_string(), "a \"b\"".to_string()],
+                Vec::new(),
+            )]
+        );
+
+        // exits with the exit code of the executable
+        environment.set_executable_exit_code(3);
+        let err_message = run_cli(vec!["hidden", "exec-command", "name"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(err_message.to_string(), "Exited with code 3");
+        environment.take_run_executables();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn launcher_templates() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_multiple_commands_package("http://localhost/package.json", "owner", "multi", "1.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+        environment.write_file_text(&PathBuf::from("/bin/sh"), "").unwrap();
+        environment
+            .write_file_text(
+                &PathBuf::from("/data/launchers/default.sh"),
+                "ulimit -n 4096\nexec wrapper --profile {{executable}} \"$@\"\n",
+            )
+            .unwrap();
+        environment
+            .write_file_text(
+                &PathBuf::from("/data/launchers/multi-second.sh"),
+                "echo {{command}}\nexec {{executable}} \"$@\"\n",
+            )
+            .unwrap();
+
+        // commands without a template use the default template
+        run_cli(vec!["hidden", "exec-command", "multi", "a b"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_executables(),
+            vec![(
+                PathBuf::from("/bin/sh"),
+                vec![
+                    "-c".to_string(),
+                    format!(
+                        "ulimit -n 4096\nexec wrapper --profile '{}' \"$@\"\n",
+                        get_binary_path("owner", "multi", "1.0.0")
+                    ),
+                    "multi".to_string(),
+                    "a b".to_string(),
+                ],
+                Vec::new(),
+            )]
+        );
+
+        run_cli(vec!["hidden", "exec-command", "multi-second"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_executables(),
+            vec![(
+                PathBuf::from("/bin/sh"),
+                vec![
+                    "-c".to_string(),
+                    format!(
+                        "echo 'multi-second'\nexec '{}' \"$@\"\n",
+                        get_binary_path_second("owner", "multi", "1.0.0")
+                    ),
+                    "multi-second".to_string(),
+                ],
+                Vec::new(),
+            )]
+        );
+
+        // runs the executable directly without a template
+        environment
+            .remove_file(&PathBuf::from("/data/launchers/default.sh"))
+            .unwrap();
+        run_cli(vec!["hidden", "exec-command", "multi"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_executables(),
+            vec![(
+                PathBuf::from(get_binary_path("owner", "multi", "1.0.0")),
+                Vec::new(),
+                Vec::new(),
+            )]
+        );
+    }
+
+    #[test]
+    fn update_notifications() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let version_info = |version: &str| registry::RegistryVersionInfo {
+            version: version.into(),
+            checksum: "".to_string(),
+            path: format!("http://localhost/package-{}.json", version),
+            release_date: None,
+            platforms: None,
+            binaries: Vec::new(),
+        };
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![version_info("1.0.0"), version_info("2.0.0"), version_info("3.0.0-beta")],
+        );
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+        let check_update_args = vec![
+            "hidden".to_string(),
+            "check-update".to_string(),
+            "owner/name".to_string(),
+            "1.0.0".to_string(),
+        ];
+
+        // starts a check in the background the first time
+        run_cli(vec!["hidden", "exec-command", "name"], &environment).unwrap();
+        assert_eq!(environment.take_background_exe_args(), vec![check_update_args.clone()]);
+        assert_logs_errors!(environment, []);
+
+        // doesn't check again until the last check is stale
+        run_cli(vec!["hidden", "exec-command", "name"], &environment).unwrap();
+        assert_eq!(environment.take_background_exe_args(), Vec::<Vec<String>>::new());
+
+        // notifies about the newest release found by the check
+        run_cli(vec!["hidden", "check-update", "owner/name", "1.0.0"], &environment).unwrap();
+        let notice = "[bvm]: A newer version of owner/name is available: 1.0.0 -> 2.0.0. Run `bvm install --use owner/name 2.0.0` to use it.";
+        run_cli(vec!["hidden", "exec-command", "name"], &environment).unwrap();
+        assert_logs_errors!(environment, [notice]);
+        assert_eq!(environment.take_background_exe_args(), Vec::<Vec<String>>::new());
+
+        // checks again after a day while still notifying
+        environment.set_time_secs(123456 + 24 * 60 * 60);
+        run_cli(vec!["hidden", "exec-command", "name"], &environment).unwrap();
+        assert_logs_errors!(environment, [notice]);
+        assert_eq!(environment.take_background_exe_args(), vec![check_update_args]);
+
+        // doesn't check or notify when disabled
+        run_cli(vec!["config", "set", "updateNotifications", "false"], &environment).unwrap();
+        environment.clear_logs();
+        environment.set_time_secs(123456 + 2 * 24 * 60 * 60);
+        run_cli(vec!["hidden", "exec-command", "name"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+        assert_eq!(environment.take_background_exe_args(), Vec::<Vec<String>>::new());
+        assert_eq!(environment.take_run_executables().len(), 5);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn link_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+        environment
+            .write_file_text(&PathBuf::from("/dev/tool/bin/tool"), "")
+            .unwrap();
+        environment
+            .write_file_text(&PathBuf::from("/dev/tool/bin/helper"), "")
+            .unwrap();
+        environment
+            .write_file_text(&PathBuf::from("/dev/name/name"), "")
+            .unwrap();
+
+        // links a directory of executables
+        run_cli(vec!["link", "tool", "/dev/tool/bin"], &environment).unwrap();
+        run_cli(vec!["hidden", "resolve-command", "tool"], &environment).unwrap();
+        assert_logs!(environment, ["EXEC".to_string(), "/dev/tool/bin/tool".to_string()]);
+        run_cli(vec!["hidden", "resolve-command", "helper"], &environment).unwrap();
+        assert_logs!(environment, ["EXEC".to_string(), "/dev/tool/bin/helper".to_string()]);
+
+        // links a single executable alongside an installed binary
+        run_cli(vec!["link", "owner/name", "/dev/name/name"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Linked. Run `bvm use name 0.0.0-local` to use it on the path as 'name'."]
+        );
+        run_cli(vec!["use", "name", "0.0.0-local"], &environment).unwrap();
+        run_cli(vec!["hidden", "resolve-command", "name"], &environment).unwrap();
+        assert_logs!(environment, ["EXEC".to_string(), "/dev/name/name".to_string()]);
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["local/tool 0.0.0-local (global)\nowner/name 0.0.0-local (global)\nowner/name 1.0.0"]
+        );
+
+        // errors linking over an installed version
+        let error_text = run_cli(
+            vec!["link", "owner/name", "/dev/name", "--version", "1.0.0"],
+            &environment,
+        )
+        .err()
+        .unwrap()
+        .to_string();
+        assert_eq!(
+            error_text,
+            "owner/name 1.0.0 is already installed. Link it using a different version (ex. `--version 0.0.0-dev`)."
+        );
+
+        // unlinks
+        run_cli(vec!["unlink", "name"], &environment).unwrap();
+        run_cli(vec!["unlink", "tool", "0.0.0-local"], &environment).unwrap();
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)"]);
+        let error_text = run_cli(vec!["unlink", "tool"], &environment).err().unwrap().to_string();
+        assert_eq!(error_text, "Could not find any linked binaries named 'tool'.");
+    }
+
+    #[test]
+    fn run_with_command() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "2.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+        let original_path = environment.get_env_path();
+        let expected_env_changes = vec![(
+            "PATH".to_string(),
+            Some(format!(
+                "{}{}{}{}{}",
+                get_binary_dir("owner", "name", "1.0.0"),
+                SYS_PATH_DELIMITER,
+                get_binary_dir("owner", "other", "2.0.0"),
+                SYS_PATH_DELIMITER,
+                original_path
+            )),
+        )];
+
+        // installs the binaries that aren't installed
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        run_cli(
+            vec![
+                "run",
+                "--with",
+                "name@1",
+                "--with",
+                "http://localhost/other.json",
+                "--",
+                &binary_path,
+                "--version",
+            ],
+            &environment,
+        )
+        .unwrap();
+        assert_eq!(
+            environment.take_run_executables(),
+            vec![(
+                PathBuf::from(&binary_path),
+                vec!["--version".to_string()],
+                expected_env_changes.clone()
+            )]
+        );
+        assert_has_path!(environment, &get_binary_path("owner", "other", "2.0.0"));
+        // the global versions aren't set
+        let error_message = run_cli(vec!["hidden", "resolve-command", "name"], &environment)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error_message.starts_with("No binary is set on the path for command 'name'."));
+        environment.clear_logs();
+        environment.take_downloaded_urls();
+
+        // uses the installed versions without downloading
+        run_cli(
+            vec!["run", "--with", "name", "--with", "other@2", "--", &binary_path],
+            &environment,
+        )
+        .unwrap();
+        assert_eq!(environment.take_downloaded_urls(), Vec::<String>::new());
+        assert_eq!(
+            environment.take_run_executables(),
+            vec![(PathBuf::from(&binary_path), Vec::new(), expected_env_changes)]
+        );
+        assert_eq!(environment.path_exists(&PathBuf::from("/project/bvm.json")), false);
+
+        // versions that aren't in the registry
+        let error_message = run_cli(vec!["run", "--with", "name@2", "--", &binary_path], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(get_error_code(&error_message), ErrorCode::VersionNotFound);
+    }
+
+    #[test]
+    fn shell_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package2.json", "owner", "name", "2.0.0");
+        plugin_builder.add_env_var("test", "2");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+        let original_path = environment.get_env_path();
+        let expected_env_changes = vec![
+            ("test".to_string(), Some("2".to_string())),
+            (
+                "PATH".to_string(),
+                Some(format!(
+                    "{}{}{}",
+                    get_binary_dir("owner", "name", "2.0.0"),
+                    SYS_PATH_DELIMITER,
+                    original_path
+                )),
+            ),
+        ];
+
+        // launches the shell
+        let shell_var_name = if cfg!(target_os = "windows") {
+            "COMSPEC"
+        } else {
+            "SHELL"
+        };
+        environment.set_env_var(shell_var_name, "/shell");
+        environment.write_file_text(&PathBuf::from("/shell"), "").unwrap();
+        run_cli(vec!["shell", "name@2"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_executables(),
+            vec![(PathBuf::from("/shell"), Vec::new(), expected_env_changes.clone())]
+        );
+
+        // runs the provided command
+        let binary_path = get_binary_path("owner", "name", "2.0.0");
+        run_cli(
+            vec!["shell", "name@2.0.0", "--", &binary_path, "--version"],
+            &environment,
+        )
+        .unwrap();
+        assert_eq!(
+            environment.take_run_executables(),
+            vec![(
+                PathBuf::from(&binary_path),
+                vec!["--version".to_string()],
+                expected_env_changes
+            )]
+        );
+
+        // the global version is unchanged
+        run_cli(vec!["hidden", "resolve-command", "name"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            ["EXEC".to_string(), get_binary_path("owner", "name", "1.0.0")]
+        );
+
+        let error_message = run_cli(vec!["shell", "name"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Expected a version for 'name' (ex. `name@1.2.3`)."
+        );
+    }
+
+    #[test]
+    fn shims_only_resolves_binary_environment() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.add_env_path("dir");
+        plugin_builder.add_env_var("test", "1");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+        environment.set_env_var("BVM_SHIMS_ONLY", "1");
+        let original_path = environment.get_env_path();
+
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+
+        // nothing is added to the path or environment variables of the shell
+        assert_get_pending_env_changes!(environment, [], [], "");
+        assert_get_paths!(environment, []);
+        assert_get_env_vars!(environment, []);
+
+        // the environment of the binary is resolved when running the shim instead
+        run_cli(vec!["hidden", "resolve-command", "name"], &environment).unwrap();
+        let binary_dir = PathBuf::from("/local-data/binaries/owner/name/1.0.0");
+        let new_path = format!(
+            "{0}{1}{2}{1}{3}",
+            binary_dir.display(),
+            SYS_PATH_DELIMITER,
+            binary_dir.join("dir").display(),
+            original_path
+        );
+        let binary_path = get_binary_path("owner", "name", "1.0.0");
+        if cfg!(target_os = "windows") {
+            assert_logs!(
+                environment,
+                [
+                    "SET test=1".to_string(),
+                    format!("SET PATH={}", new_path),
+                    "EXEC".to_string(),
+                    binary_path
+                ]
+            );
+        } else {
+            assert_logs!(
+                environment,
+                [
+                    "ADD".to_string(),
+                    "test".to_string(),
+                    "1".to_string(),
+                    "ADD".to_string(),
+                    "PATH".to_string(),
+                    new_path,
+                    "EXEC".to_string(),
+                    binary_path
+                ]
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn env_command() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.add_env_path("dir");
+        plugin_builder.add_env_var("test", "it's");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        builder.create_bvmrc(vec!["http://localhost/package.json"]);
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        let error_message = run_cli(vec!["env"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Ensure binaries are installed before outputting the environment. Run `bvm install` first."
+        );
+
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        let binary_dir = get_binary_dir("owner", "name", "1.0.0");
+        run_cli(vec!["env"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [
+                "export test='it'\\''s'".to_string(),
+                format!("export PATH='{0}:{0}/dir:/data/shims'", binary_dir)
+            ]
+        );
+        run_cli(vec!["env", "--shell", "fish"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [
+                "set -gx test 'it\\'s'".to_string(),
+                format!("set -gx PATH '{0}' '{0}/dir' '/data/shims'", binary_dir)
+            ]
+        );
+        run_cli(vec!["env", "--shell", "pwsh"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [
+                "$env:test = 'it''s'".to_string(),
+                format!("$env:PATH = '{0}:{0}/dir:/data/shims'", binary_dir)
+            ]
+        );
+        run_cli(vec!["env", "--shell", "cmd"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [
+                "SET \"test=it's\"".to_string(),
+                format!("SET \"PATH={0}:{0}/dir:/data/shims\"", binary_dir)
+            ]
+        );
+    }
+
+    #[test]
+    fn export_command() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json"]);
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        let error_message = run_cli(vec!["export", "dockerfile"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Ensure binaries are installed before exporting. Run `bvm install` first."
+        );
+
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["export", "dockerfile"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [format!(
+                concat!(
+                    "# Generated by `bvm export dockerfile`. Requires curl and unzip.\n",
+                    "ENV BVM_INSTALL_DIR=/usr/local/bvm BVM_DATA_DIR=/usr/local/bvm BVM_CACHE_DIR=/usr/local/bvm\n",
+                    "ENV PATH=/usr/local/bvm/shims:/usr/local/bvm/bin:$PATH\n",
+                    "RUN curl -fsSL https://bvm.land/install.sh | sh\n",
+                    "RUN bvm-bin install --use 'http://localhost/package.json@{}'"
+                ),
+                checksum
+            )]
+        );
+        run_cli(vec!["export", "shell-script"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [format!(
+                concat!(
+                    "#!/bin/sh\n",
+                    "# Generated by `bvm export shell-script`. Requires curl and unzip.\n",
+                    "set -e\n",
+                    "\n",
+                    "BVM_INSTALL_DIR=\"${{BVM_INSTALL_DIR:-$HOME/.bvm}}\"\n",
+                    "export BVM_INSTALL_DIR\n",
+                    "if [ ! -x \"$BVM_INSTALL_DIR/bin/bvm-bin\" ]; then\n",
+                    "  curl -fsSL https://bvm.land/install.sh | sh\n",
+                    "fi\n",
+                    "PATH=\"$BVM_INSTALL_DIR/bin:$PATH\"\n",
+                    "bvm-bin install --use 'http://localhost/package.json@{}'"
+                ),
+                checksum
+            )]
+        );
+    }
+
+    #[test]
+    fn snapshot_command() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let checksum3 = builder.create_remote_zip_multiple_commands_package(
+            "http://localhost/package3.json",
+            "owner",
+            "multi",
+            "1.0.0",
+        );
+        builder.add_binary_to_path("multi");
+        builder.add_binary_to_path("multi-second");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        run_cli(vec!["use", "name", "2.0.0"], &environment).unwrap();
+        run_cli(vec!["use", "multi", "path"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["snapshot", "export"], &environment).unwrap();
+        let snapshot_text = environment.take_logged_messages().remove(0);
+        assert_eq!(
+            snapshot_text,
+            format!(
+                concat!(
+                    "{{\n",
+                    "  \"schemaVersion\": 1,\n",
+                    "  \"binaries\": [\n",
+                    "    {{\n",
+                    "      \"name\": \"owner/multi\",\n",
+                    "      \"version\": \"1.0.0\",\n",
+                    "      \"url\": \"http://localhost/package3.json@{}\"\n",
+                    "    }},\n",
+                    "    {{\n",
+                    "      \"name\": \"owner/name\",\n",
+                    "      \"version\": \"1.0.0\",\n",
+                    "      \"url\": \"http://localhost/package.json@{}\"\n",
+                    "    }},\n",
+                    "    {{\n",
+                    "      \"name\": \"owner/name\",\n",
+                    "      \"version\": \"2.0.0\",\n",
+                    "      \"url\": \"http://localhost/package2.json@{}\"\n",
+                    "    }}\n",
+                    "  ],\n",
+                    "  \"commands\": {{\n",
+                    "    \"multi\": \"path\",\n",
+                    "    \"multi-second\": \"path\",\n",
+                    "    \"name\": \"owner/name@2.0.0\"\n",
+                    "  }}\n",
+                    "}}"
+                ),
+                checksum3, checksum, checksum2
+            )
+        );
+
+        // reproduce it on a machine without the binaries
+        environment
+            .write_file_text(&PathBuf::from("/tools.json"), &snapshot_text)
+            .unwrap();
+        run_cli(vec!["uninstall", "name", "1.0.0"], &environment).unwrap();
+        run_cli(vec!["uninstall", "name", "2.0.0"], &environment).unwrap();
+        run_cli(vec!["uninstall", "multi", "1.0.0"], &environment).unwrap();
+        run_cli(vec!["snapshot", "import", "/tools.json"], &environment).unwrap();
+        assert_has_path!(environment, &get_binary_path("owner", "name", "1.0.0"));
+        assert_resolves!(environment, get_binary_path("owner", "name", "2.0.0"));
+        environment.clear_logs();
+        run_cli(vec!["snapshot", "export"], &environment).unwrap();
+        assert_logs!(environment, [snapshot_text]);
+
+        // newer snapshot files
+        environment
+            .write_file_text(
+                &PathBuf::from("/newer.json"),
+                r#"{ "schemaVersion": 2, "minBvmVersion": "1000.0.0", "binaries": [] }"#,
+            )
+            .unwrap();
+        let error_message = run_cli(vec!["snapshot", "import", "/newer.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message
+                .to_string()
+                .starts_with("This snapshot file requires bvm >= 1000.0.0"),
+            true
+        );
+    }
+
+    #[test]
+    fn cache_key_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_bvmrc(vec!["http://localhost/package.json", "http://localhost/other.json"]);
+        let environment = builder.build();
+        environment.set_cwd("/project");
+        let get_cache_key = || {
+            run_cli(vec!["cache-key"], &environment).unwrap();
+            environment.take_logged_messages().remove(0)
+        };
+
+        let cache_key = get_cache_key();
+        let prefix = format!("bvm-{}-", crate::plugins::get_platform_keys()[0]);
+        assert_eq!(cache_key.starts_with(&prefix), true);
+        assert_eq!(cache_key.len(), prefix.len() + 64);
+        assert_eq!(get_cache_key(), cache_key);
+
+        // doesn't change when reordering the binaries
+        let config_file_path = PathBuf::from("/project/bvm.json");
+        environment
+            .write_file_text(
+                &config_file_path,
+                r#"{ "binaries": ["http://localhost/other.json", "http://localhost/package.json"] }"#,
+            )
+            .unwrap();
+        assert_eq!(get_cache_key(), cache_key);
+
+        // changes with the checksum
+        environment
+            .write_file_text(
+                &config_file_path,
+                r#"{ "binaries": ["http://localhost/other.json@123", "http://localhost/package.json"] }"#,
+            )
+            .unwrap();
+        assert_ne!(get_cache_key(), cache_key);
+    }
+
+    #[test]
+    fn github_actions_output() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "2.0.0");
+        builder.create_bvmrc(vec!["http://localhost/package.json"]);
+        let environment = builder.build();
+        environment.set_cwd("/project");
+        environment.set_env_var("GITHUB_PATH", "/github/path");
+        environment.set_env_var("GITHUB_OUTPUT", "/github/output");
+        environment
+            .write_file_text(&PathBuf::from("/github/output"), "other=value\n")
+            .unwrap();
+
+        // ignores the files when not run in github actions
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_not_has_path!(environment, "/github/path");
+
+        // detected with the environment variable
+        environment.set_env_var("GITHUB_ACTIONS", "true");
+        run_cli(vec!["install", "--force"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "::group::Installing owner/name 1.0.0",
+                "Extracting archive for owner/name 1.0.0...",
+                "::endgroup::"
+            ]
+        );
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/github/path")).unwrap(),
+            format!("{}\n", get_binary_dir("owner", "name", "1.0.0"))
+        );
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/github/output")).unwrap(),
+            "other=value\nname=1.0.0\n"
+        );
+        environment.set_log_format(crate::environment::LogFormat::Text);
+
+        // provided as a flag
+        environment.set_env_var("GITHUB_ACTIONS", "");
+        run_cli(
+            vec!["install", "--github-actions", "http://localhost/other.json"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "::group::Installing owner/other 2.0.0",
+                "Extracting archive for owner/other 2.0.0...",
+                "::endgroup::"
+            ]
+        );
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/github/output")).unwrap(),
+            "other=value\nname=1.0.0\nother=2.0.0\n"
+        );
+        environment.set_log_format(crate::environment::LogFormat::Text);
+
+        // errors are output as annotations
+        assert_eq!(
+            crate::environment::format_log_message(
+                crate::environment::LogFormat::GitHubActions,
+                crate::environment::LogLevel::Error,
+                "Error installing 100%.\nDetails"
+            ),
+            "::error::Error installing 100%25.%0ADetails"
+        );
+    }
+
+    #[test]
+    fn binary_has_environment_path_and_variable() {
+        let builder = EnvironmentBuilder::new();
+        builder.add_binary_to_path("name");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.add_env_path("dir");
+        plugin_builder.add_env_var("test", "1");
+        #[cfg(target_os = "windows")]
+        plugin_builder.add_env_var("other", "%BVM_CURRENT_BINARY_DIR%\\dir");
+        #[cfg(not(target_os = "windows"))]
+        plugin_builder.add_env_var("other", "$BVM_CURRENT_BINARY_DIR/dir");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package2.json", "owner", "name", "2.0.0");
+        plugin_builder.add_env_path("dir2");
+        plugin_builder.add_env_path(&format!("other{}path", PATH_SEPARATOR));
+        plugin_builder.add_env_var("test", "2");
+        plugin_builder.download_type(PluginDownloadType::TarGz);
+        plugin_builder.build();
+        builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "3.0.0");
+        let environment = builder.build();
+        let original_path = environment.get_env_path();
+
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        environment.clear_logs();
+
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+
+        let first_path_str = if cfg!(target_os = "windows") {
+            "/local-data\\binaries\\owner\\name\\1.0.0\\dir"
+        } else {
+            "/local-data/binaries/owner/name/1.0.0/dir"
+        };
+        let second_path_str1 = if cfg!(target_os = "windows") {
+            "/local-data\\binaries\\owner\\name\\2.0.0\\dir2"
+        } else {
+            "/local-data/binaries/owner/name/2.0.0/dir2"
+        };
+        let second_path_str2 = if cfg!(target_os = "windows") {
+            "/local-data\\binaries\\owner\\name\\2.0.0\\other\\path"
+        } else {
+            "/local-data/binaries/owner/name/2.0.0/other/path"
+        };
+
+        // check pending environment state
+        assert_get_pending_env_changes!(
+            environment,
+            [("other", first_path_str), ("test", "1")],
+            [],
+            format!("{}{}{}", original_path, SYS_PATH_DELIMITER, first_path_str)
+        );
+
+        // only windows will have updated the environment path
+        if cfg!(target_os = "windows") {
+            assert_eq!(
+                environment.get_system_path_dirs(),
+                [
+                    PathBuf::from("/data/shims"),
+                    PathBuf::from("/path-dir"),
+                    PathBuf::from(&first_path_str)
+                ]
+            );
+        }
+        assert_eq!(
+            environment.get_sys_env_variables(),
+            [
+                ("other".to_string(), first_path_str.to_string()),
+                ("test".to_string(), "1".to_string())
+            ]
+        );
+
+        // should output correctly when the path ends with delimiter
+        environment.set_env_path(&format!("{}{}", original_path, SYS_PATH_DELIMITER));
+        assert_get_pending_env_changes!(
+            environment,
+            [("other", first_path_str), ("test", "1")],
+            [],
+            format!("{}{}{}", original_path, SYS_PATH_DELIMITER, first_path_str)
+        );
+
+        // update with the current environment settings
+        update_with_pending_env_changes(&environment);
+
+        // now this should output nothing
+        assert_get_pending_env_changes!(environment, [], [], "");
+
+        // ensure this exists in get-paths and get-env-vars
+        assert_get_paths!(environment, [first_path_str]);
+        assert_get_env_vars!(environment, [("other", first_path_str), ("test", "1")]);
+
+        // now switch
+        run_cli(vec!["use", "name", "2.0.0"], &environment).unwrap();
+
+        // check pending environment state
+        assert_get_pending_env_changes!(
+            environment,
+            [("test", "2")],
+            ["other"],
+            format!(
+                "{0}{1}{2}{1}{3}",
+                original_path, SYS_PATH_DELIMITER, second_path_str1, second_path_str2
+            )
+        );
+
+        // update with the current environment settings
+        update_with_pending_env_changes(&environment);
+
+        // ensure the paths exist in get-paths now and env-vars in get-env-vars
+        assert_get_paths!(environment, [second_path_str1, second_path_str2]);
+        assert_get_env_vars!(environment, [("test", "2")]);
+
+        if cfg!(target_os = "windows") {
+            assert_eq!(
+                environment.get_system_path_dirs(),
+                [
+                    PathBuf::from("/data/shims"),
+                    PathBuf::from("/path-dir"),
+                    PathBuf::from(&second_path_str1),
+                    PathBuf::from(&second_path_str2)
+                ]
+            );
+        }
+        assert_eq!(
+            environment.get_sys_env_variables(),
+            [("test".to_string(), "2".to_string())]
+        );
+
+        // now switch
+        run_cli(vec!["use", "name", "3.0.0"], &environment).unwrap();
+
+        // check pending environment state
+        assert_get_pending_env_changes!(environment, [], ["test"], original_path);
+
+        // update with the current environment settings
+        update_with_pending_env_changes(&environment);
+
+        // ensure all pending environment changes are empty
+        assert_get_paths!(environment, []);
+        assert_get_pending_env_changes!(environment, [], [], "");
+
+        if cfg!(target_os = "windows") {
+            assert_eq!(
+                environment.get_system_path_dirs(),
+                [PathBuf::from("/data/shims"), PathBuf::from("/path-dir")]
+            );
+        }
+        assert_eq!(environment.get_sys_env_variables(), []);
+
+        // use the path version then go back to the first
+        run_cli(vec!["use", "name", "path"], &environment).unwrap();
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+
+        assert_get_pending_env_changes!(
+            environment,
+            [("other", first_path_str), ("test", "1")],
+            [],
+            format!("{}{}{}", original_path, SYS_PATH_DELIMITER, first_path_str)
+        );
+    }
+
+    #[test]
+    fn binary_path_var_absolute_and_relative() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "4.0.0");
+        #[cfg(target_os = "windows")]
+        plugin_builder.add_env_path("%BVM_CURRENT_BINARY_DIR%\\dir");
+        #[cfg(not(target_os = "windows"))]
+        plugin_builder.add_env_path("$BVM_CURRENT_BINARY_DIR/dir");
+        plugin_builder.add_env_path("/absolute"); // absolute should stay absolute
+        plugin_builder.add_env_path("relative");
+        plugin_builder.download_type(PluginDownloadType::TarGz);
+        plugin_builder.build();
+        let environment = builder.build();
+        let original_path = environment.get_env_path();
+
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+
+        let bin_dir = PathBuf::from(get_binary_dir("owner", "name", "4.0.0"));
+        let first_path = bin_dir.join("dir").to_string_lossy().to_string();
+        let second_path = "/absolute";
+        let third_path = bin_dir.join("relative").to_string_lossy().to_string();
+
+        if cfg!(target_os = "windows") {
+            assert_eq!(
+                environment.get_system_path_dirs(),
+                [
+                    PathBuf::from("/data/shims"),
+                    PathBuf::from(&first_path),
+                    PathBuf::from(&second_path),
+                    PathBuf::from(&third_path),
+                ]
+            );
+        }
+
+        assert_get_pending_env_changes!(
+            environment,
+            [],
+            [],
+            format!(
+                "{1}{0}{2}{0}{3}{0}{4}",
+                SYS_PATH_DELIMITER, original_path, first_path, second_path, third_path
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_url_no_binaries() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_bvmrc_builder().build();
+        let environment = builder.build();
+
+        // run the add command
+        environment.set_cwd("/project");
+        run_cli(vec!["add", "http://localhost/package.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/project/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/package.json",
+      "checksum": "{}",
+      "version": "1.0.0"
+    }}
+  ]
+}}
+"#,
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_url_other_binary() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "2.0.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object(&format!("http://localhost/other.json"), None, Some("~1.1"))
+            .build();
+        let environment = builder.build();
+
+        // run the add command
+        environment.set_cwd("/project");
+        run_cli(vec!["add", "http://localhost/package.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/project/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/other.json",
+      "version": "~1.1"
+    }},
+    {{
+      "path": "http://localhost/package.json",
+      "checksum": "{}",
+      "version": "1.0.0"
+    }}
+  ]
+}}
+"#,
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_url_comments_and_trailing_commas() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "2.0.0");
+        let environment = builder.build();
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.json"),
+                r#"{
+  // some comment
+  "binaries": [
+    "http://localhost/other.json", // pinned for a reason
+    /* other comment */
+  ],
+}
+"#,
+            )
+            .unwrap();
+
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["add", "http://localhost/package.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/project/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  // some comment
+  "binaries": [
+    "http://localhost/other.json", // pinned for a reason
+    /* other comment */
+    {{
+      "path": "http://localhost/package.json",
+      "checksum": "{}",
+      "version": "1.0.0"
+    }},
+  ],
+}}
+"#,
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_url_comment_no_comma() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "2.0.0");
+        let environment = builder.build();
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.json"),
+                "{\n  \"binaries\": [\n    \"http://localhost/other.json\" // comment\n  ]\n}\n",
+            )
+            .unwrap();
+
+        environment.set_cwd("/project");
+        run_cli(vec!["add", "http://localhost/package.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0...",]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/project/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    "http://localhost/other.json", // comment
+    {{
+      "path": "http://localhost/package.json",
+      "checksum": "{}",
+      "version": "1.0.0"
+    }}
+  ]
+}}
+"#,
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_registry() {
+        let builder = EnvironmentBuilder::new();
+        let checksum1 = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/other1.json", "owner", "other", "1.0.0");
+        let checksum3 = builder.create_remote_zip_package("http://localhost/other2.json", "owner", "other", "2.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry1.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum: checksum1.clone(),
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        builder.create_remote_registry_file(
+            "http://localhost/registry2.json",
+            "owner",
+            "other",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum: checksum2.clone(),
+                    path: "http://localhost/other1.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "2.0.0".into(),
+                    checksum: checksum3.clone(),
+                    path: "http://localhost/other2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        builder.create_bvmrc_builder().path("/bvm.json").build();
+        let environment = builder.build();
+
+        run_cli(vec!["registry", "add", "http://localhost/registry1.json"], &environment).unwrap();
+        run_cli(vec!["registry", "add", "http://localhost/registry2.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["add", "owner/name", "1.0.0"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        run_cli(vec!["add", "other"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/other 2.0.0..."]);
+
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/package.json",
+      "checksum": "{}",
+      "version": "1.0.0"
+    }},
+    {{
+      "path": "http://localhost/other2.json",
+      "checksum": "{}",
+      "version": "2.0.0"
+    }}
+  ]
+}}
+"#,
+                checksum1, checksum3
+            )
+        );
+
+        // now say to use ~1.0 and it should replace that in the file
+        run_cli(vec!["add", "other", "~1.0"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/other 1.0.0..."]);
+
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/package.json",
+      "checksum": "{}",
+      "version": "1.0.0"
+    }},
+    {{
+      "path": "http://localhost/other1.json",
+      "checksum": "{}",
+      "version": "~1.0"
+    }}
+  ]
+}}
+"#,
+                checksum1, checksum2
+            )
+        );
+
+        // the version selector may follow the name
+        run_cli(vec!["add", "owner/other@^2.0"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/package.json",
+      "checksum": "{}",
+      "version": "1.0.0"
+    }},
+    {{
+      "path": "http://localhost/other2.json",
+      "checksum": "{}",
+      "version": "^2.0"
+    }}
+  ]
+}}
+"#,
+                checksum1, checksum3
+            )
+        );
+
+        // specify a version that doesn't exist and it should error
+        let err = run_cli(vec!["add", "other", "~1.1"], &environment).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Could not find binary 'other' matching '~1.1' in any registry."
+        );
+    }
+
+    #[test]
+    fn remove_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "2.0.0");
+        builder.add_binary_to_path("other");
+        builder.create_bvmrc(vec!["http://localhost/package.json", "http://localhost/other.json"]);
+        let environment = builder.build();
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+        let config_file_path = PathBuf::from("/project/bvm.json");
+
+        let error_message = run_cli(vec!["remove", "missing"], &environment).err().unwrap();
+        assert_eq!(
+            error_message
+                .to_string()
+                .starts_with("Could not find binary 'missing' in "),
+            true
+        );
+
+        // uninstalls the version when it's not used globally
+        run_cli(vec!["remove", "other", "--uninstall"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Uninstalled owner/other 2.0.0."]);
+        assert_eq!(
+            environment.read_file_text(&config_file_path).unwrap(),
+            "{\n  \"binaries\": [\n    \"http://localhost/package.json\"\n  ]\n}\n"
+        );
+        assert_not_has_path!(environment, &get_binary_path("owner", "other", "2.0.0"));
+
+        // keeps the version installed when it's used globally
+        run_cli(vec!["remove", "owner/name", "--uninstall"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Kept owner/name 1.0.0 installed because it's still used."]
+        );
+        assert_eq!(
+            environment.read_file_text(&config_file_path).unwrap(),
+            "{\n  \"binaries\": []\n}\n"
+        );
+        assert_resolves!(environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn pin_command() {
+        let builder = EnvironmentBuilder::new();
+        let checksum1 = builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum: checksum1.clone(),
+                    path: "http://localhost/package1.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "2.0.0".into(),
+                    checksum: checksum2.clone(),
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        builder.create_bvmrc_builder().path("/bvm.json").build();
+        let environment = builder.build();
+
+        let err = run_cli(vec!["pin", "name"], &environment).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Could not find a globally used version of 'name'. Provide the version to pin (ex. `bvm pin name <version>`)."
+        );
+
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        run_cli(vec!["install", "--use", "name", "1.0.0"], &environment).unwrap();
+        environment.clear_logs();
+
+        // pins the globally used version
+        run_cli(vec!["pin", "name"], &environment).unwrap();
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/package1.json",
+      "checksum": "{}",
+      "version": "1.0.0"
+    }}
+  ]
+}}
+"#,
+                checksum1
+            )
+        );
+
+        // pins the provided version
+        run_cli(vec!["pin", "name", "2.0.0"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 2.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/package2.json",
+      "checksum": "{}",
+      "version": "2.0.0"
+    }}
+  ]
+}}
+"#,
+                checksum2
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_existing_url() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum: checksum.clone(),
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        builder
+            .create_bvmrc_builder()
+            .path("/bvm.json")
+            .add_binary_path("http://localhost/package.json")
+            .build();
+        let environment = builder.build();
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["add", "name", "1"], &environment).unwrap();
+
+        // should replace it
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/package.json",
+      "checksum": "{}",
+      "version": "1"
+    }}
+  ]
+}}
+"#,
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_existing_package_different_url_replaces() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .path("/bvm.json")
+            .add_binary_path("http://localhost/package.json")
+            .build();
+        let environment = builder.build();
+
+        // should also associate the existing url if not associated
+        run_cli(vec!["add", "http://localhost/package2.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/package2.json",
+      "checksum": "{}",
+      "version": "1.0.0"
+    }}
+  ]
+}}
+"#,
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_existing_package_different_url_replaces_start() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        let checksum = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/final.json", "owner", "final", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .path("/bvm.json")
+            .add_binary_object("http://localhost/package1.json", None, None)
+            .add_binary_object("http://localhost/other.json", None, Some("~1.0.0"))
+            .add_binary_object("http://localhost/final.json", None, Some("1"))
+            .build();
+        let environment = builder.build();
+
+        run_cli(vec!["add", "http://localhost/package2.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 2.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/package2.json",
+      "checksum": "{}",
+      "version": "2.0.0"
+    }},
+    {{
+      "path": "http://localhost/other.json",
+      "version": "~1.0.0"
+    }},
+    {{
+      "path": "http://localhost/final.json",
+      "version": "1"
+    }}
+  ]
+}}
+"#,
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_existing_package_different_url_replaces_middle() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        let checksum = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/final.json", "owner", "final", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .path("/bvm.json")
+            .add_binary_object("http://localhost/other.json", None, Some("~1.0.0"))
+            .add_binary_object("http://localhost/package1.json", None, None)
+            .add_binary_object("http://localhost/final.json", None, Some("1"))
+            .build();
+        let environment = builder.build();
+
+        run_cli(vec!["add", "http://localhost/package2.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 2.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/other.json",
+      "version": "~1.0.0"
+    }},
+    {{
+      "path": "http://localhost/package2.json",
+      "checksum": "{}",
+      "version": "2.0.0"
+    }},
+    {{
+      "path": "http://localhost/final.json",
+      "version": "1"
+    }}
+  ]
+}}
+"#,
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_existing_package_different_url_replaces_end() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        let checksum = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/final.json", "owner", "final", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .path("/bvm.json")
+            .add_binary_object("http://localhost/other.json", None, Some("~1.0.0"))
+            .add_binary_object("http://localhost/final.json", None, Some("1"))
+            .add_binary_object("http://localhost/package1.json", None, None)
+            .build();
+        let environment = builder.build();
+
+        run_cli(vec!["add", "http://localhost/package2.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 2.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/bvm.json")).unwrap(),
+            format!(
+                r#"{{
+  "binaries": [
+    {{
+      "path": "http://localhost/other.json",
+      "version": "~1.0.0"
+    }},
+    {{
+      "path": "http://localhost/final.json",
+      "version": "1"
+    }},
+    {{
+      "path": "http://localhost/package2.json",
+      "checksum": "{}",
+      "version": "2.0.0"
+    }}
+  ]
+}}
+"#,
+                checksum
+            )
+        );
+    }
+
+    #[test]
+    fn get_exec_env_path_gets() {
+        let builder = EnvironmentBuilder::new();
+        builder.add_binary_to_path("name");
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.add_env_path("dir");
+        plugin_builder.add_env_var("test", "1");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package2.json", "owner", "name", "2.0.0");
+        plugin_builder.add_env_path("dir2");
+        plugin_builder.add_env_path(&format!("other{}path", PATH_SEPARATOR));
+        plugin_builder.add_env_var("test", "2");
+        plugin_builder.download_type(PluginDownloadType::TarGz);
+        plugin_builder.build();
+        let environment = builder.build();
+        let original_path = environment.get_env_path();
+
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        let first_path_str = if cfg!(target_os = "windows") {
+            "/local-data\\binaries\\owner\\name\\1.0.0\\dir"
+        } else {
+            "/local-data/binaries/owner/name/1.0.0/dir"
+        };
+        let second_path_str1 = if cfg!(target_os = "windows") {
+            "/local-data\\binaries\\owner\\name\\2.0.0\\dir2"
+        } else {
+            "/local-data/binaries/owner/name/2.0.0/dir2"
+        };
+        let second_path_str2 = if cfg!(target_os = "windows") {
+            "/local-data\\binaries\\owner\\name\\2.0.0\\other\\path"
+        } else {
+            "/local-data/binaries/owner/name/2.0.0/other/path"
+        };
+
+        // should get the environment changes for the provided version
+        run_cli(vec!["hidden", "get-exec-env-changes", "name", "1"], &environment).unwrap();
+        let first_bin_env_path = format!("{}{}{}", original_path, SYS_PATH_DELIMITER, first_path_str);
+        assert_logged_env_changes!(environment, [("test", "1")], [], first_bin_env_path);
+
+        // the path should remain the same
+        if cfg!(target_os = "windows") {
+            assert_eq!(
+                environment.get_system_path_dirs(),
+                [PathBuf::from("/data/shims"), PathBuf::from("/path-dir"),]
+            );
+            assert_eq!(environment.get_sys_env_variables().is_empty(), true);
+        }
+
+        // test executing the currently used binary
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        update_with_pending_env_changes(&environment);
+
+        run_cli(vec!["hidden", "get-exec-env-changes", "name", "1.0.0"], &environment).unwrap();
+        assert_logged_env_changes!(environment, [("test", 1)], [], "");
+
+        // test executing binaries with different paths
+        run_cli(vec!["hidden", "get-exec-env-changes", "name", "^2"], &environment).unwrap();
+        assert_logged_env_changes!(
+            environment,
+            [("test", "2")],
+            [],
+            format!(
+                "{1}{0}{2}{0}{3}",
+                SYS_PATH_DELIMITER, original_path, second_path_str1, second_path_str2
+            )
+        );
+
+        // test getting the one on the path
+        run_cli(vec!["hidden", "get-exec-env-changes", "name", "path"], &environment).unwrap();
+        assert_logged_env_changes!(environment, [], ["test"], original_path);
+    }
+
+    #[test]
+    fn get_exec_command_path_and_has_command() {
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        let third_binary_dir_sub_path_dir = PathBuf::from(get_binary_dir("owner", "name", "2.1.0")).join("path-dir");
+        let third_binary_path = get_binary_path("owner", "name", "2.1.0");
+        let fourth_binary_path = get_binary_path("owner", "other", "1.0.0");
+        let fourth_binary_path_second = get_binary_path_second("owner", "other", "1.0.0");
+        let path_binary_path = get_path_binary_path("name");
+
+        let builder = EnvironmentBuilder::new();
+        builder.add_binary_to_path("name");
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder
+            .create_plugin_builder("http://localhost/package3.json", "owner", "name", "2.1.0")
+            .download_type(PluginDownloadType::Zip)
+            .add_env_path("path-dir")
+            .build();
+        builder.create_remote_zip_multiple_commands_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        let environment = builder.build();
+
+        install_url!(environment, "http://localhost/package1.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        install_url!(environment, "http://localhost/other.json");
+        environment.clear_logs();
+
+        // create an executable inside the third binary's env path
+        let third_binary_path_command_path =
+            third_binary_dir_sub_path_dir.join(get_executable_file_name("path-command"));
+        environment
+            .write_file_text(&third_binary_path_command_path, "")
+            .unwrap();
+        let third_binary_path_command_path = third_binary_path_command_path.to_string_lossy().to_string();
+
+        // test the exec command
+        assert_exec_command_path!(environment, "name", "path", "name", path_binary_path);
+        assert_exec_command_path!(environment, "name", "1", "name", first_binary_path);
+        assert_exec_command_path!(environment, "name", "2", "name", third_binary_path);
+        assert_exec_command_path!(environment, "name", "2.0", "name", second_binary_path);
+        assert_exec_command_path!(environment, "name", "^2.0", "name", third_binary_path);
+        assert_exec_command_path!(
+            environment,
+            "name",
+            "2.1.0",
+            "path-command",
+            third_binary_path_command_path
+        );
+        assert_exec_command_path!(environment, "other", "*", "other", fourth_binary_path);
+        assert_exec_command_path!(environment, "other", "*", "other-second", fourth_binary_path_second);
+        let err_message = run_cli(
+            vec!["hidden", "get-exec-command-path", "other", "*", "something"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(
+            err_message.to_string(),
+            "Could not find a matching command. Expected one of the following: other, other-second"
+        );
+
+        // test the has-command command
+        assert_has_command!(environment, "name", "1", "name", true);
+        assert_has_command!(environment, "name", "1", "other", false);
+        assert_has_command!(environment, "name", "1", "-v", false);
+        assert_has_command!(environment, "name", "2.1.0", "path-command", true);
+        assert_has_command!(environment, "other", "*", "other", true);
+        assert_has_command!(environment, "other", "*", "other-second", true);
+        assert_has_command!(environment, "other", "*", "other-second2", false);
+
+        // check when missing last argument
+        run_cli(vec!["hidden", "has-command", "name", "1"], &environment).unwrap();
+        assert_logs!(environment, ["false"]);
+    }
+
+    #[test]
+    fn hidden_resolve_command() {
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let builder = EnvironmentBuilder::new();
+        let first_path_str = if cfg!(target_os = "windows") {
+            "/local-data\\binaries\\owner\\name\\1.0.0\\dir"
+        } else {
+            "/local-data/binaries/owner/name/1.0.0/dir"
+        };
+        builder
+            .create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0")
+            .download_type(PluginDownloadType::Zip)
+            .add_env_var("test", "1")
+            .add_env_path("dir")
+            .build();
+        builder
+            .create_plugin_builder("http://localhost/package2.json", "owner", "name", "2.0.0")
+            .download_type(PluginDownloadType::Zip)
+            .add_env_var("other", "1")
+            .build();
+        builder
+            .create_bvmrc_builder()
+            .path("/bvm.json")
+            .add_binary_path("http://localhost/package.json")
+            .build();
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package.json");
+        environment.clear_logs();
+
+        run_cli(vec!["hidden", "resolve-command", "name"], &environment).unwrap();
+
+        let mut expected_logs = get_env_change_logs(
+            &[("test", "1")],
+            &["other"],
+            &format!("/data/shims{}{}", SYS_PATH_DELIMITER, &first_path_str),
+        );
+        expected_logs.push("EXEC".to_string());
+        expected_logs.push(first_binary_path);
+        assert_eq!(environment.take_logged_messages(), expected_logs);
+    }
+
+    #[test]
+    fn resolves_nearest_config_file_in_ancestor_dir() {
+        let builder = EnvironmentBuilder::new();
+        builder
+            .create_bvmrc_builder()
+            .add_binary_path("http://localhost/package.json")
+            .build();
+        builder
+            .create_bvmrc_builder()
+            .path("/project/sub/bvm.json")
+            .add_binary_path("http://localhost/package2.json")
+            .build();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        let environment = builder.build();
+
+        environment.set_cwd("/project/sub");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        // should use the config file in an ancestor directory
+        environment.set_cwd("/project/other/dir");
+        assert_resolves!(&environment, first_binary_path);
+
+        // should use the nearest config file
+        environment.set_cwd("/project/sub/dir/nested");
+        assert_resolves!(&environment, second_binary_path);
+    }
+
+    #[test]
+    fn support_hidden_config_file() {
+        let builder = EnvironmentBuilder::new();
+        builder
+            .create_bvmrc_builder()
+            .path("/project/.bvm.json")
+            .add_binary_path("http://localhost/package.json")
+            .build();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+
+        let environment = builder.build();
+
+        // install the package
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        // should still resolve to the cwd's binary
+        assert_resolves!(&environment, first_binary_path);
+    }
+
+    #[test]
+    fn config_file_extends_path() {
+        let builder = EnvironmentBuilder::new();
+        builder
+            .create_bvmrc_builder()
+            .path("/base/bvm.json")
+            .on_pre_install("echo base pre")
+            .on_post_install("echo base post")
+            .add_binary_path("http://localhost/package.json")
+            .add_binary_path("http://localhost/other.json")
+            .build();
+        builder
+            .create_bvmrc_builder()
+            .extends("../base/bvm.json")
+            .on_post_install("echo local post")
+            .add_binary_path("http://localhost/package2.json")
+            .build();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        let other_binary_path = get_binary_path("owner", "other", "1.0.0");
+        let environment = builder.build();
+
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/other 1.0.0...",
+                "Extracting archive for owner/name 2.0.0..."
+            ]
+        );
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [
+                ("/project".to_string(), "echo base pre".to_string()),
+                ("/project".to_string(), "echo local post".to_string())
+            ]
+        );
+
+        // the local binary should override the inherited one
+        assert_resolves_name!(&environment, "name", second_binary_path);
+        assert_resolves_name!(&environment, "other", other_binary_path);
+    }
+
+    #[test]
+    fn config_file_extends_url() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let environment = builder.build();
+        environment.add_remote_file(
+            "http://localhost/configs/base.json",
+            r#"{ "extends": "nested.toml", "binaries": [] }"#.as_bytes().to_vec(),
+        );
+        environment.add_remote_file(
+            "http://localhost/configs/nested.toml",
+            "[[binaries]]\npath = \"http://localhost/package.json\"\n"
+                .as_bytes()
+                .to_vec(),
+        );
+        builder
+            .create_bvmrc_builder()
+            .extends("http://localhost/configs/base.json")
+            .build();
+
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_resolves!(&environment, first_binary_path);
+
+        // uses the cached copies of the extended files instead of downloading them on every read
+        environment.take_downloaded_urls();
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_eq!(
+            environment
+                .take_downloaded_urls()
+                .iter()
+                .any(|url| url.starts_with("http://localhost/configs/")),
+            false
+        );
+        run_cli(vec!["--offline", "install"], &environment).unwrap();
+        assert_resolves!(&environment, first_binary_path);
+
+        // the host of an extended file must be trusted when trusted hosts are configured
+        environment
+            .write_file_text(
+                &PathBuf::from("/data/config.json"),
+                r#"{ "trustedHosts": ["github.com"] }"#,
+            )
+            .unwrap();
+        environment.set_confirm_answer(Some(false));
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Did not download http://localhost/configs/base.json because localhost is not a trusted host."
+        );
+        assert_logs_errors!(
+            environment,
+            ["localhost is not a trusted host. Trust it and download http://localhost/configs/base.json?"]
+        );
+    }
+
+    #[test]
+    fn config_file_extends_circular() {
+        let builder = EnvironmentBuilder::new();
+        builder
+            .create_bvmrc_builder()
+            .path("/base/bvm.json")
+            .extends("../project/bvm.json")
+            .build();
+        builder.create_bvmrc_builder().extends("../base/bvm.json").build();
+        let environment = builder.build();
+
+        environment.set_cwd("/project");
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Circular extends detected for /project/bvm.json"
+        );
+    }
+
+    #[test]
+    fn install_command_workspaces() {
+        let builder = EnvironmentBuilder::new();
+        builder
+            .create_bvmrc_builder()
+            .add_workspace("packages/a")
+            .add_workspace("packages/b")
+            .add_binary_path("http://localhost/package.json")
+            .build();
+        builder
+            .create_bvmrc_builder()
+            .path("/project/packages/a/bvm.json")
+            .on_pre_install("echo a")
+            .add_binary_path("http://localhost/package2.json")
+            .build();
+        builder
+            .create_bvmrc_builder()
+            .path("/project/packages/b/.bvm.json")
+            .add_binary_path("http://localhost/other.json")
+            .build();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        let other_binary_path = get_binary_path("owner", "other", "1.0.0");
+        let environment = builder.build();
+
+        // installing at the root should install the binaries of all the members
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Extracting archive for owner/name 1.0.0...",
+                "Extracting archive for owner/name 2.0.0...",
+                "Extracting archive for owner/other 1.0.0..."
+            ]
+        );
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [("/project/packages/a".to_string(), "echo a".to_string())]
+        );
+
+        // should resolve using the nearest member config
+        assert_resolves!(&environment, first_binary_path);
+        environment.set_cwd("/project/packages/a/src");
+        assert_resolves!(&environment, second_binary_path);
+        environment.set_cwd("/project/packages/b");
+        assert_resolves_name!(&environment, "other", other_binary_path);
+    }
+
+    #[test]
+    fn install_command_workspace_member_not_found() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_bvmrc_builder().add_workspace("packages/a").build();
+        let environment = builder.build();
+
+        environment.set_cwd("/project");
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find a bvm configuration file for workspace member 'packages/a'."
+        );
+    }
+
+    #[test]
+    fn install_command_binary_hooks() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        let environment = builder.build();
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.json"),
+                r#"{
+  "binaries": [{
+    "path": "http://localhost/package.json",
+    "onInstall": "name install",
+    "onUse": "name use"
+  }, "http://localhost/other.json"]
+}"#,
+            )
+            .unwrap();
+        environment.set_cwd("/project");
+        let binary_dir = get_binary_dir("owner", "name", "1.0.0");
+        let expected_env_path = format!("{}{}{}", binary_dir, SYS_PATH_DELIMITER, environment.get_env_path());
+
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [("/project".to_string(), "name install".to_string())]
+        );
+        assert_eq!(
+            environment.take_run_shell_command_env_paths(),
+            vec![expected_env_path.clone()]
+        );
+
+        // using should run the use hook
+        run_cli(vec!["use"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [("/project".to_string(), "name use".to_string())]
+        );
+        assert_eq!(
+            environment.take_run_shell_command_env_paths(),
+            vec![expected_env_path.clone()]
+        );
+
+        // installing with --use should run both
+        run_cli(vec!["install", "--use"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [
+                ("/project".to_string(), "name install".to_string()),
+                ("/project".to_string(), "name use".to_string())
+            ]
+        );
+        assert_eq!(
+            environment.take_run_shell_command_env_paths(),
+            [expected_env_path.clone(), expected_env_path]
+        );
+    }
+
+    #[test]
+    fn run_command() {
+        let builder = EnvironmentBuilder::new();
+        builder
+            .create_bvmrc_builder()
+            .add_script("build", "name build")
+            .add_binary_path("http://localhost/package.json")
+            .add_binary_path("http://localhost/other.json")
+            .build();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.add_env_var("test", "1");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "1.0.0");
+        let environment = builder.build();
+        environment.set_cwd("/project/sub");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["run", "build", "--release", "other"], &environment).unwrap();
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [("/project".to_string(), "name build --release other".to_string())]
+        );
+        // the script has the environment variables of the binaries
+        assert_eq!(
+            environment.take_run_shell_command_env_changes(),
+            vec![vec![
+                ("test".to_string(), Some("1".to_string())),
+                (
+                    "PATH".to_string(),
+                    Some(format!(
+                        "{1}{0}{2}{0}{3}",
+                        SYS_PATH_DELIMITER,
+                        get_binary_dir("owner", "name", "1.0.0"),
+                        get_binary_dir("owner", "other", "1.0.0"),
+                        environment.get_env_path()
+                    ))
+                ),
+            ]]
+        );
+
+        // exits with the exit code of the script
+        environment.set_executable_exit_code(3);
+        let err_message = run_cli(vec!["run", "build"], &environment).err().unwrap();
+        assert_eq!(err_message.to_string(), "Exited with code 3");
+        environment.take_run_shell_commands();
+        environment.take_run_shell_command_env_changes();
+    }
+
+    #[test]
+    fn run_command_quotes_args() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_bvmrc_builder().add_script("test", "echo").build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        run_cli(
+            vec![
+                "run",
+                "test",
+                "--filter=a",
+                "two words",
+                "$HOME",
+                "a;b",
+                "it's",
+                "",
+                "say \"hi\" & exit",
+            ],
+            &environment,
+        )
+        .unwrap();
+        let expected_command = if cfg!(target_os = "windows") {
+            r#"echo --filter=a "two words" "$HOME" "a;b" "it's" "" "say ""hi"" & exit""#
+        } else {
+            r#"echo --filter=a 'two words' '$HOME' 'a;b' 'it'\''s' '' 'say "hi" & exit'"#
+        };
+        assert_eq!(
+            environment.take_run_shell_commands(),
+            [("/project".to_string(), expected_command.to_string())]
+        );
+        environment.take_run_shell_command_env_changes();
+    }
+
+    #[test]
+    fn run_command_script_not_found() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_bvmrc_builder().add_script("build", "echo build").build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        let error_message = run_cli(vec!["run", "test"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find script 'test' in the configuration file."
+        );
+    }
+
+    #[test]
+    fn run_command_not_installed() {
+        let builder = EnvironmentBuilder::new();
+        builder
+            .create_bvmrc_builder()
+            .add_script("build", "name build")
+            .add_binary_path("http://localhost/package.json")
+            .build();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        let error_message = run_cli(vec!["run", "build"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Ensure binaries are installed before running a script. Run `bvm install` first then `bvm run build`."
+        );
+    }
+
+    #[test]
+    fn alias_command() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        let second_binary_path = get_binary_path("owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        run_cli(vec!["use", "name", "2.0.0"], &environment).unwrap();
+        assert_resolves!(&environment, second_binary_path);
+
+        run_cli(vec!["alias", "set", "name", "stable", "1.0.0"], &environment).unwrap();
+        run_cli(vec!["use", "name", "stable"], &environment).unwrap();
+        assert_resolves!(&environment, first_binary_path);
+
+        run_cli(vec!["alias", "list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name stable -> 1.0.0"]);
+
+        run_cli(vec!["alias", "remove", "name", "stable"], &environment).unwrap();
+        run_cli(vec!["alias", "list"], &environment).unwrap();
+        assert_logs!(environment, []);
+
+        let error_message = run_cli(vec!["use", "name", "stable"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find alias 'stable' for 'name'. Create one with `bvm alias set name stable <version>`."
+        );
+    }
+
+    #[test]
+    fn alias_command_invalid_alias() {
+        let environment = TestEnvironment::new();
+        let error_message = run_cli(vec!["alias", "set", "owner/name", "1.0", "1.0.0"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Invalid alias '1.0'. An alias must start with a letter, only contain letters, numbers, dashes, or underscores, and not be a version selector."
+        );
+    }
+
+    #[test]
+    fn alias_command_not_installed() {
+        let environment = TestEnvironment::new();
+        let error_message = run_cli(vec!["alias", "set", "name", "stable", "1.0.0"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find any installed binaries named 'name'. Include the owner in the name to create an alias for a binary that is not installed."
+        );
+
+        // providing the owner should work
+        run_cli(vec!["alias", "set", "owner/name", "stable", "1.0.0"], &environment).unwrap();
+        run_cli(vec!["alias", "list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name stable -> 1.0.0"]);
+
+        // binaries can be named like the other alias sub commands
+        run_cli(vec!["alias", "set", "owner/list", "stable", "1.0.0"], &environment).unwrap();
+        run_cli(vec!["alias", "remove", "owner/name", "stable"], &environment).unwrap();
+        run_cli(vec!["alias", "list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/list stable -> 1.0.0"]);
+    }
+
+    #[test]
+    fn alias_in_config_file() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object("http://localhost/package.json", None, Some("stable"))
+            .build();
+        let environment = builder.build();
+        environment.set_cwd("/project");
+
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find an alias or registry tag 'stable' for 'owner/name'. Create an alias with `bvm alias set owner/name stable <version>`."
+        );
+
+        run_cli(vec!["alias", "set", "owner/name", "stable", "1.0.0"], &environment).unwrap();
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves!(&environment, first_binary_path);
+
+        // now point the alias at a version that doesn't match the path
+        run_cli(vec!["alias", "set", "owner/name", "stable", "2.0.0"], &environment).unwrap();
+        let error_message = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            concat!(
+                "Error installing http://localhost/package.json: The specified version '2.0.0' did not match ",
+                "'1.0.0' in the path file. Please specify a different path or update the version."
+            )
+        );
+    }
+
+    #[test]
+    fn install_command_registry_tag() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let checksum3 =
+            builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "3.0.0-beta");
+        builder.create_remote_registry_file_with_tags(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum,
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "2.0.0".into(),
+                    checksum: checksum2,
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "3.0.0-beta".into(),
+                    checksum: checksum3,
+                    path: "http://localhost/package3.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+            vec![("lts", "1.0.0"), ("nightly", "3.0.0-beta")],
+        );
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["install", "--use", "name@lts"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+
+        run_cli(vec!["install", "--use", "name", "nightly"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "3.0.0-beta"));
+
+        // not specified by the registry, so should use the latest release
+        run_cli(vec!["install", "--use", "owner/name@latest"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "2.0.0"));
+
+        let error_message = run_cli(vec!["install", "name@other"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find tag 'other' for binary 'name' in any registry."
+        );
+        environment.clear_logs();
+    }
+
+    #[test]
+    fn install_command_registry_toolchain() {
+        let builder = EnvironmentBuilder::new();
+        let version_info = |version: &str, path: &str, checksum: String| registry::RegistryVersionInfo {
+            version: version.into(),
+            checksum,
+            path: path.to_string(),
+            release_date: None,
+            platforms: None,
+            binaries: Vec::new(),
+        };
+        let checksum_a1 = builder.create_remote_zip_package("http://localhost/a1.json", "owner", "a", "1.0.0");
+        let checksum_a2 = builder.create_remote_zip_package("http://localhost/a2.json", "owner", "a", "2.0.0");
+        let checksum_b1 = builder.create_remote_zip_package("http://localhost/b1.json", "owner", "b", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry-a.json",
+            "owner",
+            "a",
+            vec![
+                version_info("1.0.0", "http://localhost/a1.json", checksum_a1),
+                version_info("2.0.0", "http://localhost/a2.json", checksum_a2),
+            ],
+        );
+        builder.create_remote_registry_file(
+            "http://localhost/registry-b.json",
+            "owner",
+            "b",
+            vec![version_info("1.0.0", "http://localhost/b1.json", checksum_b1)],
+        );
+        builder.create_remote_registry_file(
+            "http://localhost/registry-toolchain.json",
+            "myorg",
+            "toolchain",
+            vec![
+                registry::RegistryVersionInfo {
+                    binaries: vec!["owner/a@1.0.0".to_string(), "owner/b@1.0.0".to_string()],
+                    ..version_info("1.0.0", "", String::new())
+                },
+                registry::RegistryVersionInfo {
+                    binaries: vec!["owner/a@2.0.0".to_string(), "owner/b@1.0.0".to_string()],
+                    ..version_info("2.0.0", "", String::new())
+                },
+            ],
+        );
+        let environment = builder.build();
+        run_cli(
+            vec!["registry", "add", "http://localhost/registry-a.json"],
+            &environment,
+        )
+        .unwrap();
+        run_cli(
+            vec!["registry", "add", "http://localhost/registry-b.json"],
+            &environment,
+        )
+        .unwrap();
+        run_cli(
+            vec!["registry", "add", "http://localhost/registry-toolchain.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["install", "toolchain@1.0.0"], &environment).unwrap();
+        assert_resolves_name!(&environment, "a", get_binary_path("owner", "a", "1.0.0"));
+        assert_resolves_name!(&environment, "b", get_binary_path("owner", "b", "1.0.0"));
+        environment.clear_logs();
+
+        // toolchains may only be installed
+        let err = run_cli(vec!["run", "--with", "toolchain", "a"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "myorg/toolchain 2.0.0 is a toolchain of other binaries, which may only be installed with `bvm install`."
+        );
+
+        // installing a newer version moves the commands on the path to its binaries
+        run_cli(vec!["install", "toolchain"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Already installed http://localhost/b1.json. Provide the `--force` flag to reinstall.",
+                "Extracting archive for owner/a 2.0.0..."
+            ]
+        );
+        assert_resolves_name!(&environment, "a", get_binary_path("owner", "a", "2.0.0"));
+        assert_resolves_name!(&environment, "b", get_binary_path("owner", "b", "1.0.0"));
+
+        // keeps the binaries of the other installed versions
+        run_cli(vec!["uninstall", "toolchain", "1.0.0"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Removed owner/a 1.0.0.", "Removed toolchain myorg/toolchain 1.0.0."]
+        );
+
+        run_cli(vec!["uninstall", "--all", "toolchain"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Removed owner/a 2.0.0.",
+                "Removed owner/b 1.0.0.",
+                "Removed toolchain myorg/toolchain 2.0.0."
+            ]
+        );
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, []);
+    }
+
+    #[test]
+    fn install_command_registry_tag_config_file() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_registry_file_with_tags(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum,
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "2.0.0".into(),
+                    checksum: checksum2,
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+            vec![("lts", "2.0.0")],
+        );
+        builder
+            .create_bvmrc_builder()
+            .add_binary_object("http://localhost/package.json", None, Some("lts"))
+            .build();
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.set_cwd("/project");
+
+        // should install the version the tag refers to instead of the path
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "2.0.0"));
+    }
+
+    #[test]
+    fn install_command_multiple_names() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let checksum3 = builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name2", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum,
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "2.0.0".into(),
+                    checksum: checksum2,
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        builder.create_remote_registry_file(
+            "http://localhost/registry2.json",
+            "owner",
+            "name2",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum: checksum3,
+                path: "http://localhost/package3.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        run_cli(vec!["registry", "add", "http://localhost/registry2.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["install", "--use", "name@1", "name2"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+        assert_resolves_name!(&environment, "name2", get_binary_path("owner", "name2", "1.0.0"));
+
+        // already installed binaries are skipped
+        run_cli(vec!["install", "name@1", "name2@1.0.0"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Already installed http://localhost/package.json. Provide the `--force` flag to reinstall.",
+                "Already installed http://localhost/package3.json. Provide the `--force` flag to reinstall."
+            ]
+        );
+
+        // the legacy name and version form should still work
+        run_cli(vec!["install", "--use", "name", "2"], &environment).unwrap();
+        environment.clear_logs();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "2.0.0"));
+
+        // a second name of a binary isn't treated as a tag
+        run_cli(vec!["install", "name", "name2"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Already installed http://localhost/package2.json. Provide the `--force` flag to reinstall.",
+                "Already installed http://localhost/package3.json. Provide the `--force` flag to reinstall."
+            ]
+        );
+    }
+
+    #[test]
+    fn install_command_offline() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 = builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum,
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "2.0.0".into(),
+                    checksum: checksum2,
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["install", "name@1.0.0"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        // uses the cached registry and installed binary
+        run_cli(vec!["install", "--offline", "name@1.0.0"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Already installed. Provide the `--force` flag to reinstall."]
+        );
+
+        let error_message = run_cli(vec!["--offline", "install", "name@2.0.0"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Error installing http://localhost/package2.json. Could not download http://localhost/package2.json because bvm is offline."
+        );
+        // the code is kept when the error is wrapped
+        assert_eq!(get_error_code(&error_message), ErrorCode::Offline);
+
+        environment.set_env_var("BVM_OFFLINE", "1");
+        let error_message = run_cli(vec!["registry", "add", "http://localhost/other.json"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find a cached copy of the registry at http://localhost/other.json. Run the command without being offline to download it."
+        );
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn install_command_force_name() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![registry::RegistryVersionInfo {
+                version: "1.0.0".into(),
+                checksum,
+                path: "http://localhost/package.json".to_string(),
+                release_date: None,
+                platforms: None,
+                binaries: Vec::new(),
+            }],
+        );
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["install", "name@1.0.0"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+
+        // simulate a corrupted install
+        let stale_file_path = PathBuf::from(get_binary_dir("owner", "name", "1.0.0")).join("stale");
+        environment.write_file_text(&stale_file_path, "").unwrap();
+
+        run_cli(vec!["install", "name@1.0.0"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Already installed. Provide the `--force` flag to reinstall."]
+        );
+        assert_eq!(environment.path_exists(&stale_file_path), true);
+
+        run_cli(vec!["install", "--force", "name@1.0.0"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_eq!(environment.path_exists(&stale_file_path), false);
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+    }
+
+    #[test]
+    fn use_command_select_version() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        builder.create_remote_zip_package("http://localhost/package3.json", "owner", "name", "3.0.0-beta");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        install_url!(environment, "http://localhost/package3.json");
+        environment.clear_logs();
+
+        // picks from the installed versions when no version is provided
+        environment.set_select_answer(Some(2));
+        run_cli(vec!["use", "name"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Which version of owner/name should be used?\n  3.0.0-beta\n  2.0.0 (latest)\n  1.0.0 (current)"]
+        );
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+
+        // uses the latest version when prompting isn't possible
+        environment.set_select_answer(None);
+        run_cli(vec!["use", "name"], &environment).unwrap();
+        assert_logs_errors!(environment, []);
+        assert_resolves!(&environment, get_binary_path("owner", "name", "2.0.0"));
+    }
+
+    #[test]
+    fn use_command_pre_release() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0-beta");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package.json");
+        install_url!(environment, "http://localhost/package2.json");
+        environment.clear_logs();
+
+        // pre-releases should be excluded unless opted into
+        run_cli(vec!["use", "name", "--pre"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "2.0.0-beta"));
+        run_cli(vec!["use", "name"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+
+        let error_message = run_cli(vec!["use", "name", "^2.0.0"], &environment).err().unwrap();
+        assert_eq!(
+            error_message.to_string(),
+            "Could not find binary 'name' that matched version '^2.0.0'\n\nInstalled versions:\n  1.0.0\n  2.0.0-beta"
+        );
+        run_cli(vec!["use", "name", "^2.0.0", "--pre"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "2.0.0-beta"));
+
+        // pre-release ranges opt in
+        run_cli(vec!["use", "name", "1.0.0"], &environment).unwrap();
+        run_cli(vec!["use", "name", "^2.0.0-alpha"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "2.0.0-beta"));
+    }
+
+    #[test]
+    fn install_command_pre_release() {
+        let builder = EnvironmentBuilder::new();
+        let checksum = builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let checksum2 =
+            builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "1.1.0-beta");
+        builder.create_remote_registry_file(
+            "http://localhost/registry.json",
+            "owner",
+            "name",
+            vec![
+                registry::RegistryVersionInfo {
+                    version: "1.0.0".into(),
+                    checksum,
+                    path: "http://localhost/package.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+                registry::RegistryVersionInfo {
+                    version: "1.1.0-beta".into(),
+                    checksum: checksum2,
+                    path: "http://localhost/package2.json".to_string(),
+                    release_date: None,
+                    platforms: None,
+                    binaries: Vec::new(),
+                },
+            ],
+        );
+        let environment = builder.build();
+        run_cli(vec!["registry", "add", "http://localhost/registry.json"], &environment).unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["ls-remote", "name", "1"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0"]);
+        run_cli(vec!["ls-remote", "name", "1", "--pre"], &environment).unwrap();
+        assert_logs!(environment, ["1.0.0\n1.1.0-beta"]);
+
+        run_cli(vec!["install", "--use", "name", "1"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.0.0"));
+        run_cli(vec!["install", "--use", "--pre", "name", "1"], &environment).unwrap();
+        assert_resolves!(&environment, get_binary_path("owner", "name", "1.1.0-beta"));
+        environment.clear_logs();
+    }
+
+    #[test]
+    fn support_toml_config_file() {
+        let builder = EnvironmentBuilder::new();
+        let first_binary_path = get_binary_path("owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.toml"),
+                "# some comment\n[[binaries]]\npath = \"http://localhost/package.json\"\n",
+            )
+            .unwrap();
+
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        assert_resolves!(&environment, first_binary_path);
+    }
+
+    #[test]
+    fn config_file_command_names() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package2.json");
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.json"),
+                r#"{
+    "binaries": [{ "path": "http://localhost/package.json", "commands": { "name": "name1" } }]
+}"#,
+            )
+            .unwrap();
+        environment.set_cwd("/project");
+        run_cli(vec!["install"], &environment).unwrap();
+        environment.clear_logs();
+
+        // the renamed command resolves from the config file and the original name is left to the global version
+        assert_eq!(environment.path_exists(&PathBuf::from(get_shim_path("name1"))), true);
+        assert_resolves_name!(&environment, "name1", get_binary_path("owner", "name", "1.0.0"));
+        assert_resolves!(&environment, get_binary_path("owner", "name", "2.0.0"));
+
+        // the renamed command is kept when replacing the binary
+        run_cli(vec!["add", "http://localhost/package2.json"], &environment).unwrap();
+        environment.clear_logs();
+        let config_file = environment.read_file_text(&PathBuf::from("/project/bvm.json")).unwrap();
+        assert_eq!(config_file.contains(r#""commands": { "name": "name1" }"#), true);
+        assert_resolves_name!(&environment, "name1", get_binary_path("owner", "name", "2.0.0"));
+
+        // the renamed command only exists in the project
+        environment.set_cwd("/");
+        let err = run_cli(vec!["hidden", "resolve-command", "name1"], &environment)
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Could not find binary on the path for command 'name1'");
+
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.json"),
+                r#"{ "binaries": [{ "path": "http://localhost/package.json", "commands": { "name": "a/b" } }] }"#,
+            )
+            .unwrap();
+        environment.set_cwd("/project");
+        let err = run_cli(vec!["install"], &environment).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Error reading /project/bvm.json: Invalid name 'a/b' for command 'name'."
+        );
+    }
+
+    #[test]
+    fn add_command_toml_config_file() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let other_checksum =
+            builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "2.0.0");
+        let new_checksum =
+            builder.create_remote_zip_package("http://localhost/package2.json", "owner", "name", "2.0.0");
+        let environment = builder.build();
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.toml"),
+                "onPreInstall = \"echo test\"\n\n[[binaries]]\npath = \"http://localhost/package.json\" # comment\n\n# other comment\n",
+            )
+            .unwrap();
+        environment.set_cwd("/project");
+
+        // adding a new binary should append a table
+        run_cli(vec!["add", "http://localhost/other.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/other 2.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/project/bvm.toml")).unwrap(),
+            format!(
+                r#"onPreInstall = "echo test"
+
+[[binaries]]
+path = "http://localhost/package.json" # comment
+
+# other comment
+
+[[binaries]]
+path = "http://localhost/other.json"
+checksum = "{}"
+version = "2.0.0"
+"#,
+                other_checksum
+            )
+        );
+
+        // adding the same binary name should update its table in place
+        run_cli(vec!["add", "http://localhost/package2.json"], &environment).unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 2.0.0..."]);
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/project/bvm.toml")).unwrap(),
+            format!(
+                r#"onPreInstall = "echo test"
+
+[[binaries]]
+path = "http://localhost/package2.json" # comment
+checksum = "{}"
+version = "2.0.0"
+
+# other comment
+
+[[binaries]]
+path = "http://localhost/other.json"
+checksum = "{}"
+version = "2.0.0"
+"#,
+                new_checksum, other_checksum
+            )
+        );
+    }
+
+    #[test]
+    fn add_command_toml_config_file_inline_array() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        builder.create_remote_zip_package("http://localhost/other.json", "owner", "other", "2.0.0");
+        let environment = builder.build();
+        environment
+            .write_file_text(
+                &PathBuf::from("/project/bvm.toml"),
+                "binaries = [\"http://localhost/other.json\"]\n",
+            )
+            .unwrap();
+        environment.set_cwd("/project");
+
+        let error_message = run_cli(vec!["add", "http://localhost/package.json"], &environment)
+            .err()
+            .unwrap();
+        assert_logs_errors!(environment, ["Extracting archive for owner/name 1.0.0..."]);
+        assert_eq!(
+            error_message.to_string(),
+            "Expected the binaries in the toml file to be specified as [[binaries]] tables."
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn unix_install_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+
+        install_url!(environment, "http://localhost/package1.json");
+        environment.clear_logs();
+
+        let shim_path = PathBuf::from(get_shim_path("name"));
+        assert_eq!(environment.path_exists(&shim_path), true);
+
+        environment.remove_file(&shim_path).unwrap();
+        run_cli(vec!["hidden", "unix-install"], &environment).unwrap();
+
+        // should have recreated the shim
+        assert_eq!(environment.path_exists(&shim_path), true);
+        // should have persisted the install directory for the shell profiles
+        assert_eq!(
+            environment.get_sys_env_variables(),
+            [("BVM_INSTALL_DIR".to_string(), "/.bvm".to_string())]
+        );
+    }
+
+    #[test]
+    fn system_mode() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        let binary_path = if cfg!(target_os = "windows") {
+            "/system\\binaries\\owner\\name\\1.0.0\\name.exe"
+        } else {
+            "/system/binaries/owner/name/1.0.0/name"
+        };
+        let shim_path = if cfg!(target_os = "windows") {
+            "/system/shims/name.cmd"
+        } else {
+            "/system/shims/name"
+        };
+
+        // errors without the privileges to change the system installation
+        environment.set_has_system_privileges(false);
+        let err_message = run_cli(
+            vec!["install", "--system", "http://localhost/package.json"],
+            &environment,
+        )
+        .err()
+        .unwrap();
+        let expected_message = if cfg!(target_os = "windows") {
+            "Changing the system-wide installation requires administrator privileges. Run the command again from an elevated terminal."
+        } else {
+            "Changing the system-wide installation requires root privileges. Run the command again with `sudo`."
+        };
+        assert_eq!(err_message.to_string(), expected_message);
+        environment.set_has_system_privileges(true);
+
+        // installs to the system directory
+        run_cli(
+            vec!["install", "--system", "http://localhost/package.json"],
+            &environment,
+        )
+        .unwrap();
+        environment.clear_logs();
+        assert_has_path!(environment, shim_path);
+        assert_not_has_path!(environment, &get_shim_path("name"));
+        assert_has_path!(environment, binary_path);
+
+        // the user installation doesn't have it
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, []);
+
+        // the environment variable uses the system installation, such as in the shims
+        environment.set_env_var("BVM_SYSTEM", "1");
+        run_cli(vec!["list"], &environment).unwrap();
+        assert_logs!(environment, ["owner/name 1.0.0 (global)"]);
+        assert_resolves!(environment, binary_path.to_string());
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn unix_install_command_system() {
+        let environment = TestEnvironment::new();
+        run_cli(vec!["hidden", "unix-install", "--system"], &environment).unwrap();
+        // the shims of the system installation use it via the persisted environment variable
+        assert_eq!(
+            environment.get_sys_env_variables(),
+            [
+                ("BVM_INSTALL_DIR".to_string(), "/system".to_string()),
+                ("BVM_SYSTEM".to_string(), "1".to_string())
+            ]
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn fish_shell_setup() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.add_env_path("dir");
+        plugin_builder.add_env_var("test", "it's");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+        environment.set_env_var("HOME", "/home");
+        environment
+            .write_file_text(&PathBuf::from("/home/.config/fish/config.fish"), "")
+            .unwrap();
+
+        // writes the conf.d snippet when fish is set up
+        run_cli(vec!["hidden", "unix-install"], &environment).unwrap();
+        assert_eq!(
+            environment
+                .read_file_text(&PathBuf::from("/home/.config/fish/conf.d/bvm.fish"))
+                .unwrap(),
+            concat!(
+                "# bvm shell setup (generated by bvm)\n",
+                "set -gx BVM_INSTALL_DIR '/.bvm'\n",
+                "if test -x \"$BVM_INSTALL_DIR/bin/bvm-bin\"\n",
+                "    \"$BVM_INSTALL_DIR/bin/bvm-bin\" hidden shell-init fish | source\n",
+                "end\n",
+            )
+        );
+
+        // outputs the pending changes in fish syntax
+        run_cli(vec!["install", "http://localhost/package.json"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(
+            vec!["hidden", "get-pending-env-changes", "--shell", "fish"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs!(
+            environment,
+            [
+                "set -gx test 'it\\'s'",
+                "set -gx PATH '/data/shims' '/local-data/binaries/owner/name/1.0.0/dir'"
+            ]
+        );
+        run_cli(vec!["hidden", "clear-pending-env-changes"], &environment).unwrap();
+
+        // outputs the setup for a new shell
+        run_cli(vec!["hidden", "shell-init", "fish"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [
+                super::FISH_SHELL_INIT_SCRIPT,
+                "set -gx test 'it\\'s'",
+                "set -gx PATH '/data/shims' '/local-data/binaries/owner/name/1.0.0/dir' $PATH"
+            ]
+        );
+
+        // removes the snippet when uninstalling
+        run_cli(vec!["uninstall-self"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Removed /home/.config/fish/conf.d/bvm.fish.", "Uninstalled bvm."]
+        );
+        assert_not_has_path!(environment, "/home/.config/fish/conf.d/bvm.fish");
+    }
+
+    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+    #[test]
+    fn nushell_shell_setup() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.add_env_path("dir");
+        plugin_builder.add_env_var("test", "it's");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+        environment.set_env_var("HOME", "/home");
+        let env_nu_path = PathBuf::from("/home/.config/nushell/env.nu");
+        let config_nu_path = PathBuf::from("/home/.config/nushell/config.nu");
+        environment
+            .write_file_text(&config_nu_path, "$env.config = {}\n")
+            .unwrap();
+
+        // writes the env.nu and config.nu blocks when nushell is set up
+        run_cli(vec!["hidden", "unix-install"], &environment).unwrap();
+        assert_eq!(
+            environment.read_file_text(&env_nu_path).unwrap(),
+            concat!(
+                "# >>> bvm >>>\n",
+                "$env.BVM_INSTALL_DIR = r#'/.bvm'#\n",
+                "load-env (^($env.BVM_INSTALL_DIR | path join bin bvm-bin) hidden shell-init nu | from json)\n",
+                "# <<< bvm <<<\n",
+            )
+        );
+        assert_eq!(
+            environment.read_file_text(&config_nu_path).unwrap(),
+            format!(
+                "$env.config = {{}}\n# >>> bvm >>>\n{}# <<< bvm <<<\n",
+                super::NU_CONFIG_BLOCK
+            )
+        );
+
+        // outputs the pending changes as a record
+        run_cli(vec!["install", "http://localhost/package.json"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(vec!["hidden", "get-pending-env-changes", "--shell", "nu"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [r#"{"PATH":["/data/shims","/local-data/binaries/owner/name/1.0.0/dir"],"test":"it's"}"#]
+        );
+        run_cli(vec!["hidden", "clear-pending-env-changes"], &environment).unwrap();
+
+        // outputs the environment for a new shell
+        run_cli(vec!["hidden", "shell-init", "nu"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [r#"{"PATH":["/data/shims","/local-data/binaries/owner/name/1.0.0/dir","/data/shims"],"test":"it's"}"#]
+        );
+
+        // removes the blocks when uninstalling
+        run_cli(vec!["uninstall-self"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Removed the bvm setup from /home/.config/nushell/env.nu.",
+                "Removed the bvm setup from /home/.config/nushell/config.nu.",
+                "Uninstalled bvm."
+            ]
+        );
+        assert_eq!(environment.read_file_text(&env_nu_path).unwrap(), "");
+        assert_eq!(
+            environment.read_file_text(&config_nu_path).unwrap(),
+            "$env.config = {}\n"
+        );
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn pwsh_shell_setup() {
+        let builder = EnvironmentBuilder::new();
+        let mut plugin_builder =
+            builder.create_plugin_builder("http://localhost/package.json", "owner", "name", "1.0.0");
+        plugin_builder.add_env_path("dir");
+        plugin_builder.add_env_var("test", "it's");
+        plugin_builder.download_type(PluginDownloadType::Zip);
+        plugin_builder.build();
+        let environment = builder.build();
+        environment.set_env_var("HOME", "/home");
+        let profile_path = PathBuf::from("/home/.config/powershell/Microsoft.PowerShell_profile.ps1");
+        environment.write_file_text(&profile_path, "Set-Alias ll ls\n").unwrap();
+
+        // adds a block to the profile when powershell is set up
+        run_cli(vec!["hidden", "unix-install"], &environment).unwrap();
+        assert_eq!(
+            environment.read_file_text(&profile_path).unwrap(),
+            concat!(
+                "Set-Alias ll ls\n",
+                "# >>> bvm >>>\n",
+                "$env:BVM_INSTALL_DIR = '/.bvm'\n",
+                "if (Test-Path \"$env:BVM_INSTALL_DIR/bin/bvm-bin\") {\n",
+                "    & \"$env:BVM_INSTALL_DIR/bin/bvm-bin\" hidden shell-init pwsh | Out-String | Invoke-Expression\n",
+                "}\n",
+                "# <<< bvm <<<\n",
+            )
+        );
+
+        // outputs the pending changes in powershell syntax
+        run_cli(vec!["install", "http://localhost/package.json"], &environment).unwrap();
+        environment.clear_logs();
+        run_cli(
+            vec!["hidden", "get-pending-env-changes", "--shell", "pwsh"],
+            &environment,
+        )
+        .unwrap();
+        assert_logs!(
+            environment,
+            [
+                "$env:test = 'it''s'",
+                "$env:PATH = '/data/shims:/local-data/binaries/owner/name/1.0.0/dir'"
+            ]
+        );
+        run_cli(vec!["hidden", "clear-pending-env-changes"], &environment).unwrap();
+
+        // outputs the setup for a new shell
+        run_cli(vec!["hidden", "shell-init", "pwsh"], &environment).unwrap();
+        assert_logs!(
+            environment,
+            [
+                super::PWSH_SHELL_INIT_SCRIPT,
+                "$env:test = 'it''s'",
+                "$env:PATH = '/data/shims:/local-data/binaries/owner/name/1.0.0/dir' + [IO.Path]::PathSeparator + $env:PATH"
+            ]
+        );
+
+        // removes the block when uninstalling
+        run_cli(vec!["uninstall-self"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            [
+                "Removed the bvm setup from /home/.config/powershell/Microsoft.PowerShell_profile.ps1.",
+                "Uninstalled bvm."
+            ]
+        );
+        assert_eq!(environment.read_file_text(&profile_path).unwrap(), "Set-Alias ll ls\n");
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn windows_install_command_installs() {
+        let environment = TestEnvironment::new();
+        environment.remove_system_path("/data/shims").unwrap();
+        run_cli(vec!["hidden", "windows-install"], &environment).unwrap();
+        assert_eq!(
+            environment.get_system_path_dirs(),
+            [PathBuf::from("/data\\shims"), PathBuf::from("/.bvm\\bin"),]
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn windows_install_command_existing_paths_installs() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+
+        install_url!(environment, "http://localhost/package1.json");
+        environment.clear_logs();
+
+        let shim_path = PathBuf::from(get_shim_path("name"));
+        assert_eq!(environment.path_exists(&shim_path), true);
+
+        environment.remove_file(&shim_path).unwrap();
+        environment.remove_system_path("/data/shims").unwrap();
+        environment.ensure_system_path_pre("/data\\shims").unwrap();
+        environment.ensure_system_path_pre("/other-dir").unwrap();
+        run_cli(vec!["hidden", "windows-install"], &environment).unwrap();
+        assert_eq!(
+            environment.get_system_path_dirs(),
+            [
+                PathBuf::from("/data\\shims"),
+                PathBuf::from("/.bvm\\bin"),
+                PathBuf::from("/other-dir")
+            ]
+        );
+        // should have recreated the shim
+        assert_eq!(environment.path_exists(&shim_path), true);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn windows_uninstall_command_uninstalls() {
+        let environment = TestEnvironment::new();
+        environment.remove_system_path("/data/shims").unwrap();
+        environment.ensure_system_path_pre("/other-dir").unwrap();
+        environment.ensure_system_path_pre("/data\\shims").unwrap();
+        environment.ensure_system_path_pre("/.bvm\\bin").unwrap();
+        run_cli(vec!["hidden", "windows-uninstall"], &environment).unwrap();
+        assert_eq!(environment.get_system_path_dirs(), [PathBuf::from("/other-dir")]);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn uninstall_self_command() {
+        let builder = EnvironmentBuilder::new();
+        builder.create_remote_zip_package("http://localhost/package1.json", "owner", "name", "1.0.0");
+        let environment = builder.build();
+        install_url!(environment, "http://localhost/package1.json");
+        environment.set_env_var("HOME", "/home");
+        run_cli(vec!["hidden", "unix-install"], &environment).unwrap();
+        environment
+            .write_file_text(
+                &PathBuf::from("/home/.bashrc"),
+                "alias ll='ls -l'\nexport BVM_INSTALL_DIR=\"/.bvm\"\n. \"$BVM_INSTALL_DIR/bin/bvm-init\"\n",
+            )
+            .unwrap();
+        environment
+            .write_file_text(&PathBuf::from("/home/.zshrc"), "export EDITOR=vim\n")
+            .unwrap();
+        environment.clear_logs();
+
+        run_cli(vec!["uninstall-self"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Removed the bvm setup from /home/.bashrc.", "Uninstalled bvm."]
+        );
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/home/.bashrc")).unwrap(),
+            "alias ll='ls -l'\n"
+        );
+        assert_eq!(
+            environment.read_file_text(&PathBuf::from("/home/.zshrc")).unwrap(),
+            "export EDITOR=vim\n"
+        );
+        assert_eq!(environment.get_sys_env_variables(), []);
+        assert_not_has_path!(environment, get_shim_path("name"));
+        // keeps the installed binaries
+        assert_has_path!(environment, get_binary_path("owner", "name", "1.0.0"));
+
+        run_cli(vec!["uninstall-self", "--purge"], &environment).unwrap();
+        assert_logs_errors!(
+            environment,
+            ["Removed the data and cache directories.", "Uninstalled bvm."]
+        );
+        assert_not_has_path!(environment, get_binary_path("owner", "name", "1.0.0"));
+        assert_eq!(environment.is_dir(&PathBuf::from("/data")), false);
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn uninstall_self_command_removes_system_paths() {
+        let environment = TestEnvironment::new();
+        environment.remove_system_path("/data/shims").unwrap();
+        environment.ensure_system_path_pre("/other-dir").unwrap();
+        environment.ensure_system_path_pre("/data\\shims").unwrap();
+        environment.ensure_system_path_pre("/.bvm\\bin").unwrap();
+        run_cli(vec!["uninstall-self"], &environment).unwrap();
+        assert_eq!(environment.get_system_path_dirs(), [PathBuf::from("/other-dir")]);
+    }
+
+    fn get_env_change_logs(added: &[(&str, &str)], removed: &[&str], new_path: &str) -> Vec<String> {
+        let mut expected_logs = Vec::new();
+        for remove_key in removed {
+            if cfg!(target_os = "windows") {
+                expected_logs.push(format!("SET {}=", remove_key));
+            } else {
+                expected_logs.push("REMOVE".to_string());
+                expected_logs.push(remove_key.to_string());
+            }
+        }
+
+        for (key, value) in added {
+            if cfg!(target_os = "windows") {
+                expected_logs.push(format!("SET {}={}", key, value));
+            } else {
+                expected_logs.push("ADD".to_string());
+                expected_logs.push(key.to_string());
+                expected_logs.push(value.to_string());
+            }
+        }
+
+        if !new_path.is_empty() {
+            if cfg!(target_os = "windows") {
+                expected_logs.push(format!("SET PATH={}", new_path));
+            } else {
+                expected_logs.push("ADD".to_string());
+                expected_logs.push("PATH".to_string());
+                expected_logs.push(new_path.to_string());
+            }
+        }
+
+        expected_logs
+    }
+
+    fn get_shim_path(name: &str) -> String {
+        if cfg!(target_os = "windows") {
+            format!("/data/shims/{}.cmd", name)
+        } else {
+            format!("/data/shims/{}", name)
+        }
+    }
+
+    fn get_binary_dir(owner: &str, name: &str, version: &str) -> String {
+        if cfg!(target_os = "windows") {
+            format!("/local-data\\binaries\\{}\\{}\\{}", owner, name, version)
+        } else {
+            format!("/local-data/binaries/{}/{}/{}", owner, name, version)
+        }
+    }
+
+    fn get_binary_staging_dir(owner: &str, name: &str, version: &str) -> String {
+        if cfg!(target_os = "windows") {
+            format!("/local-data\\staging\\{}\\{}\\{}", owner, name, version)
+        } else {
+            format!("/local-data/staging/{}/{}/{}", owner, name, version)
+        }
+    }
+
+    fn get_binary_path(owner: &str, name: &str, version: &str) -> String {
+        if cfg!(target_os = "windows") {
+            format!("/local-data\\binaries\\{}\\{}\\{}\\{1}.exe", owner, name, version)
+        } else {
+            format!("/local-data/binaries/{}/{}/{}/{1}", owner, name, version)
+        }
+    }
+
+    fn get_path_binary_path(name: &str) -> String {
+        if cfg!(target_os = "windows") {
+            format!("/path-dir\\{}.bat", name)
+        } else {
+            format!("/path-dir/{}", name)
+        }
+    }
+
+    fn get_binary_path_second(owner: &str, name: &str, version: &str) -> String {
+        if cfg!(target_os = "windows") {
+            format!(
+                "/local-data\\binaries\\{}\\{}\\{}\\{1}-second.exe",
+                owner, name, version
+            )
+        } else {
+            format!("/local-data/binaries/{}/{}/{}/{1}-second", owner, name, version)
+        }
+    }
+
+    fn get_executable_file_name(command_name: &str) -> String {
+        if cfg!(target_os = "windows") {
+            format!("{}.exe", command_name)
+        } else {
+            command_name.to_string()
+        }
+    }
+
+    fn get_binary_provenance(
+        environment: &TestEnvironment,
+        owner: &str,
+        name: &str,
+        version: &str,
+    ) -> crate::plugins::BinaryProvenance {
+        let identifier = crate::plugins::BinaryIdentifier::new(
+            &BinaryName::new(owner.to_string(), name.to_string()),
+            &version.into(),
+        );
+        let plugin_manifest = super::PluginsManifest::load(environment);
+        plugin_manifest
+            .get_binary(&identifier)
+            .unwrap()
+            .provenance
+            .clone()
+            .unwrap()
+    }
+
+    fn update_with_pending_env_changes(environment: &TestEnvironment) {
+        use super::PluginsManifest;
+        let plugin_manifest = PluginsManifest::load(environment);
+
+        for (key, _) in plugin_manifest.get_pending_removed_env_variables(environment) {
+            environment.remove_env_var(&key);
+        }
+
+        for (key, value) in plugin_manifest.get_pending_added_env_variables(environment) {
+            environment.set_env_var(&key, &value);
+        }
+        let new_path = super::plugin_helpers::get_env_path_from_pending_env_changes(environment, &plugin_manifest);
+        environment.set_env_path(&new_path);
+
+        run_cli(vec!["hidden", "clear-pending-env-changes"], &environment).unwrap();
+    }
+
+    fn run_cli(args: Vec<&str>, environment: &TestEnvironment) -> Result<(), ErrBox> {
+        let mut args: Vec<String> = args.into_iter().map(String::from).collect();
+        args.insert(0, String::from(""));
+        run(environment, args)
+    }
+}
@@ -1,12 +0,0 @@
-mod get_plugin_dir;
-pub mod helpers;
-mod manifest;
-mod plugins_mut;
-mod serialized_plugin_file;
-mod setup;
-
-pub use get_plugin_dir::*;
-pub use manifest::*;
-pub use plugins_mut::*;
-pub use serialized_plugin_file::*;
-pub use setup::create_shim;
@@ -1,9 +0,0 @@
-mod create_shim;
-mod get_plugin_file;
-mod read_plugin_file;
-mod setup_plugin;
-
-pub use create_shim::*;
-pub use get_plugin_file::*;
-use read_plugin_file::*;
-pub use setup_plugin::*;
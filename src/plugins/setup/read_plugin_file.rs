@@ -1,24 +0,0 @@
-use dprint_cli_core::types::ErrBox;
-
-use crate::plugins::SerializedPluginFile;
-
-pub fn read_plugin_file(file_bytes: &[u8]) -> Result<SerializedPluginFile, ErrBox> {
-    // todo: don't use serde because this should fail with a nice error message if the schema version is not equal
-    match serde_json::from_slice::<SerializedPluginFile>(&file_bytes) {
-        Ok(file) => {
-            if file.schema_version != 1 {
-                return err!(
-                    "Expected schema version 1, but found {}. This may indicate you need to upgrade your CLI version to use this binary.",
-                    file.schema_version
-                );
-            }
-
-            if file.name.contains("/") || file.owner.contains("/") {
-                return err!("The binary owner and name may not contain a forward slash.");
-            }
-
-            Ok(file)
-        }
-        Err(err) => err!("Error deserializing binary manifest file. {}", err.to_string()),
-    }
-}
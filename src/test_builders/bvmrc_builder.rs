@@ -16,8 +16,14 @@ struct BinaryItemObject {
 pub struct BvmrcBuilder {
     environment: TestEnvironment,
     path: Option<String>,
+    extends: Option<String>,
+    workspaces: Vec<String>,
+    scripts: Vec<(String, String)>,
     on_pre_install: Option<String>,
     on_post_install: Option<String>,
+    require_checksums: Option<bool>,
+    registries: Vec<String>,
+    owner_registries: Vec<(String, String)>,
     binaries: Vec<BinaryItem>,
 }
 
@@ -26,8 +32,14 @@ impl BvmrcBuilder {
         BvmrcBuilder {
             environment: environment.clone(),
             path: None,
+            extends: None,
+            workspaces: Vec::new(),
+            scripts: Vec::new(),
             on_pre_install: None,
             on_post_install: None,
+            require_checksums: None,
+            registries: Vec::new(),
+            owner_registries: Vec::new(),
             binaries: Vec::new(),
         }
     }
@@ -37,6 +49,21 @@ impl BvmrcBuilder {
         self
     }
 
+    pub fn extends<'a>(&'a mut self, extends: &str) -> &'a mut BvmrcBuilder {
+        self.extends = Some(extends.to_string());
+        self
+    }
+
+    pub fn add_workspace<'a>(&'a mut self, dir: &str) -> &'a mut BvmrcBuilder {
+        self.workspaces.push(dir.to_string());
+        self
+    }
+
+    pub fn add_script<'a>(&'a mut self, name: &str, command: &str) -> &'a mut BvmrcBuilder {
+        self.scripts.push((name.to_string(), command.to_string()));
+        self
+    }
+
     pub fn on_pre_install<'a>(&'a mut self, script: &str) -> &'a mut BvmrcBuilder {
         self.on_pre_install = Some(script.to_string());
         self
@@ -47,6 +74,21 @@ impl BvmrcBuilder {
         self
     }
 
+    pub fn require_checksums(&mut self, value: bool) -> &mut BvmrcBuilder {
+        self.require_checksums = Some(value);
+        self
+    }
+
+    pub fn add_registry<'a>(&'a mut self, url: &str) -> &'a mut BvmrcBuilder {
+        self.registries.push(url.to_string());
+        self
+    }
+
+    pub fn add_owner_registry<'a>(&'a mut self, owner: &str, url: &str) -> &'a mut BvmrcBuilder {
+        self.owner_registries.push((owner.to_string(), url.to_string()));
+        self
+    }
+
     pub fn add_binary_path<'a>(&'a mut self, path: &str) -> &'a mut BvmrcBuilder {
         self.binaries.push(BinaryItem::String(path.to_string()));
         self
@@ -69,12 +111,53 @@ impl BvmrcBuilder {
     pub fn build(&mut self) {
         let mut writer = String::new();
         writer.push_str("{");
+        if let Some(text) = &self.extends {
+            writer.push_str(&format!("\n  \"extends\": \"{}\",", escape_quotes(text)));
+        }
+        if !self.workspaces.is_empty() {
+            let workspaces = self
+                .workspaces
+                .iter()
+                .map(|dir| format!("\"{}\"", escape_quotes(dir)))
+                .collect::<Vec<_>>();
+            writer.push_str(&format!("\n  \"workspaces\": [{}],", workspaces.join(", ")));
+        }
+        if !self.scripts.is_empty() {
+            let scripts = self
+                .scripts
+                .iter()
+                .map(|(name, command)| format!("\"{}\": \"{}\"", escape_quotes(name), escape_quotes(command)))
+                .collect::<Vec<_>>();
+            writer.push_str(&format!("\n  \"scripts\": {{ {} }},", scripts.join(", ")));
+        }
         if let Some(text) = &self.on_pre_install {
             writer.push_str(&format!("\n  \"onPreInstall\": \"{}\",", escape_quotes(text)));
         }
         if let Some(text) = &self.on_post_install {
             writer.push_str(&format!("\n  \"onPostInstall\": \"{}\",", escape_quotes(text)));
         }
+        if let Some(value) = self.require_checksums {
+            writer.push_str(&format!("\n  \"requireChecksums\": {},", value));
+        }
+        if !self.registries.is_empty() {
+            let registries = self
+                .registries
+                .iter()
+                .map(|url| format!("\"{}\"", escape_quotes(url)))
+                .collect::<Vec<_>>();
+            writer.push_str(&format!("\n  \"registries\": [{}],", registries.join(", ")));
+        }
+        if !self.owner_registries.is_empty() {
+            let owner_registries = self
+                .owner_registries
+                .iter()
+                .map(|(owner, url)| format!("\"{}\": \"{}\"", escape_quotes(owner), escape_quotes(url)))
+                .collect::<Vec<_>>();
+            writer.push_str(&format!(
+                "\n  \"ownerRegistries\": {{ {} }},",
+                owner_registries.join(", ")
+            ));
+        }
         writer.push_str("\n  \"binaries\": [");
         for (i, binary_item) in self.binaries.iter().enumerate() {
             if i > 0 {
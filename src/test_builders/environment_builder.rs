@@ -1,4 +1,4 @@
-use super::{BvmrcBuilder, PluginFileBuilder};
+use super::{BvmrcBuilder, PlatformInfoBuilder, PluginFileBuilder};
 use crate::environment::{Environment, TestEnvironment, SYS_PATH_DELIMITER};
 
 use std::io::Write;
@@ -44,6 +44,161 @@ impl EnvironmentBuilder {
         builder.build()
     }
 
+    pub fn create_remote_github_release(&self, owner: &str, name: &str, tag: &str) {
+        let commands = vec![name.to_string()];
+        let assets = vec![
+            ("x86_64-pc-windows-msvc", true),
+            ("x86_64-apple-darwin", false),
+            ("x86_64-unknown-linux-gnu", false),
+        ]
+        .into_iter()
+        .map(|(target, is_windows)| {
+            let asset_name = format!("{}-{}.zip", name, target);
+            let url = format!(
+                "https://github.com/{}/{}/releases/download/{}/{}",
+                owner, name, tag, asset_name
+            );
+            create_remote_zip(&self.environment, &url, is_windows, &commands);
+            format!(r#"{{"name": "{}", "browser_download_url": "{}"}}"#, asset_name, url)
+        })
+        .collect::<Vec<_>>();
+        self.environment.add_remote_file(
+            &format!("https://api.github.com/repos/{}/{}/releases/tags/{}", owner, name, tag),
+            format!(r#"{{"tag_name": "{}", "assets": [{}]}}"#, tag, assets.join(", ")).into_bytes(),
+        );
+    }
+
+    /// Creates the latest release of bvm with an asset for the current operating system.
+    pub fn create_remote_bvm_release(&self, tag: &str) {
+        let asset_name = format!("bvm-{}.zip", crate::get_bvm_release_target());
+        let url = format!(
+            "https://github.com/dsherret/bvm/releases/download/{}/{}",
+            tag, asset_name
+        );
+        let commands = vec!["bvm-bin".to_string()];
+        let checksum = create_remote_zip(&self.environment, &url, cfg!(target_os = "windows"), &commands);
+        self.environment.add_remote_file(
+            "https://api.github.com/repos/dsherret/bvm/releases/latest",
+            format!(
+                r#"{{"tag_name": "{}", "assets": [{{"name": "{}", "browser_download_url": "{}", "digest": "sha256:{}"}}]}}"#,
+                tag, asset_name, url, checksum
+            )
+            .into_bytes(),
+        );
+    }
+
+    /// Creates a zip archive containing the current operating system's executables for the commands.
+    pub fn create_remote_archive(&self, url: &str, commands: Vec<&str>) {
+        let commands = commands.into_iter().map(String::from).collect::<Vec<_>>();
+        create_remote_zip(&self.environment, url, cfg!(target_os = "windows"), &commands);
+    }
+
+    /// Creates a zip archive on the file system containing the executables of the commands.
+    pub fn create_local_archive(&self, file_path: &str, is_windows: bool, commands: Vec<&str>) {
+        let commands = commands.into_iter().map(String::from).collect::<Vec<_>>();
+        let bytes = create_zip(file_path, is_windows, &commands);
+        self.environment.write_file(&PathBuf::from(file_path), &bytes).unwrap();
+    }
+
+    /// Creates a plugin file on the file system with archive paths relative to the plugin file.
+    pub fn create_local_zip_package(&self, file_path: &str, owner: &str, name: &str, version: &str) {
+        let file_path = PathBuf::from(file_path);
+        let dir_path = file_path.parent().unwrap();
+        let commands = vec![name.to_string()];
+        let mut file = PluginFileBuilder::new();
+        file.owner(owner)
+            .name(name)
+            .version(version)
+            .description("Some description");
+        let write_zip = |platform: &str, is_windows: bool| {
+            let zip_file_name = format!("{}-{}.zip", name, platform);
+            let bytes = create_zip(&zip_file_name, is_windows, &commands);
+            let checksum = dprint_cli_core::checksums::get_sha256_checksum(&bytes);
+            self.environment
+                .write_file(&dir_path.join(&zip_file_name), &bytes)
+                .unwrap();
+            (format!("./{}", zip_file_name), checksum)
+        };
+        let (windows_path, windows_checksum) = write_zip("windows", true);
+        let (mac_path, mac_checksum) = write_zip("mac", false);
+        let (linux_path, linux_checksum) = write_zip("linux", false);
+        file.windows()
+            .path(&windows_path)
+            .checksum(&windows_checksum)
+            .download_type("zip")
+            .add_command(name, &format!("{}.exe", name));
+        file.mac()
+            .path(&mac_path)
+            .checksum(&mac_checksum)
+            .download_type("zip")
+            .add_command(name, name);
+        file.linux()
+            .path(&linux_path)
+            .checksum(&linux_checksum)
+            .download_type("zip")
+            .add_command(name, name);
+        self.environment
+            .write_file_text(&file_path, &file.to_json_text())
+            .unwrap();
+    }
+
+    /// Creates a plugin file that describes multiple versions using a templated archive url.
+    pub fn create_remote_multi_version_package(&self, url: &str, owner: &str, name: &str, versions: Vec<&str>) {
+        let commands = vec![name.to_string()];
+        let platforms = [
+            ("windows-x86_64", true),
+            ("darwin-x86_64", false),
+            ("linux-x86_64", false),
+        ];
+        let versions = versions
+            .into_iter()
+            .map(|version| {
+                let checksums = platforms
+                    .iter()
+                    .map(|(platform, is_windows)| {
+                        let (os, arch) = platform.split_at(platform.find('-').unwrap());
+                        let zip_url = format!("http://localhost/v{}/{}-{}{}.zip", version, name, os, arch);
+                        let checksum = create_remote_zip(&self.environment, &zip_url, *is_windows, &commands);
+                        format!(r#""{}": "{}""#, platform, checksum)
+                    })
+                    .collect::<Vec<_>>();
+                format!(
+                    r#"{{"version": "{}", "checksums": {{{}}}}}"#,
+                    version,
+                    checksums.join(", ")
+                )
+            })
+            .collect::<Vec<_>>();
+        let platform_blocks = platforms
+            .iter()
+            .map(|(platform, is_windows)| {
+                format!(
+                    r#""{}": {{"path": "http://localhost/v{{{{version}}}}/{}-{{{{os}}}}-{{{{arch}}}}.zip", "type": "zip", "commands": [{{"name": "{}", "path": "{}{}"}}]}}"#,
+                    platform,
+                    name,
+                    name,
+                    name,
+                    if *is_windows { ".exe" } else { "" }
+                )
+            })
+            .collect::<Vec<_>>();
+        let file_text = format!(
+            r#"{{
+    "schemaVersion": 1,
+    "owner": "{}",
+    "name": "{}",
+    "description": "Some description.",
+    "versions": [{}],
+    {}
+}}"#,
+            owner,
+            name,
+            versions.join(", "),
+            platform_blocks.join(",\n    ")
+        );
+        self.environment.add_remote_file(url, file_text.into_bytes());
+    }
+
     pub fn create_plugin_builder(&self, url: &str, owner: &str, name: &str, version: &str) -> PluginBuilder {
         let mut builder = PluginBuilder::new(self.environment.clone());
 
@@ -101,6 +256,17 @@ impl EnvironmentBuilder {
         owner: &str,
         name: &str,
         items: Vec<crate::registry::RegistryVersionInfo>,
+    ) {
+        self.create_remote_registry_file_with_tags(url, owner, name, items, Vec::new());
+    }
+
+    pub fn create_remote_registry_file_with_tags(
+        &self,
+        url: &str,
+        owner: &str,
+        name: &str,
+        items: Vec<crate::registry::RegistryVersionInfo>,
+        tags: Vec<(&str, &str)>,
     ) {
         let file_text = format!(
             r#"{{
@@ -109,7 +275,8 @@ impl EnvironmentBuilder {
         "owner": "{}",
         "name": "{}",
         "description": "Some description.",
-        "versions": [{}]
+        "versions": [{}],
+        "tags": {{{}}}
     }}]
 }}"#,
             owner,
@@ -117,10 +284,17 @@ impl EnvironmentBuilder {
             items
                 .into_iter()
                 .map(|item| format!(
-                    r#"{{"version": "{}", "path": "{}", "checksum": "{}"}}"#,
-                    item.version, item.path, item.checksum
+                    r#"{{"version": "{}", "path": "{}", "checksum": "{}", "binaries": {}}}"#,
+                    item.version,
+                    item.path,
+                    item.checksum,
+                    serde_json::to_string(&item.binaries).unwrap()
                 ))
                 .collect::<Vec<_>>()
+                .join(","),
+            tags.into_iter()
+                .map(|(tag, version)| format!(r#""{}": "{}""#, tag, version))
+                .collect::<Vec<_>>()
                 .join(",")
         );
         self.environment.add_remote_file(url, file_text.into_bytes());
@@ -188,6 +362,11 @@ impl PluginBuilder {
         self
     }
 
+    pub fn smoke_test<'a>(&'a mut self, args: Vec<&str>, expected_output: Option<&str>) -> &'a mut PluginBuilder {
+        self.file.smoke_test(args, expected_output);
+        self
+    }
+
     pub fn output_dir<'a>(&'a mut self, value: &str) -> &'a mut PluginBuilder {
         self.file.windows().output_dir(value);
         self.file.linux().output_dir(value);
@@ -223,6 +402,13 @@ impl PluginBuilder {
         self
     }
 
+    pub fn on_pre_uninstall<'a>(&'a mut self, value: &str) -> &'a mut PluginBuilder {
+        self.file.windows().on_pre_uninstall(value);
+        self.file.linux().on_pre_uninstall(value);
+        self.file.mac().on_pre_uninstall(value);
+        self
+    }
+
     pub fn add_command<'a>(&'a mut self, name: &str) -> &'a mut PluginBuilder {
         assert_eq!(
             self.download_type.is_some(),
@@ -235,7 +421,7 @@ impl PluginBuilder {
         self
     }
 
-    pub fn download_type<'a>(&'a mut self, download_type: PluginDownloadType) -> &'a mut PluginBuilder {
+    pub fn download_type(&mut self, download_type: PluginDownloadType) -> &mut PluginBuilder {
         match &download_type {
             PluginDownloadType::TarGz => self.setup_binaries_tar_gz(),
             PluginDownloadType::Zip => self.setup_binaries_zip(),
@@ -244,6 +430,45 @@ impl PluginBuilder {
         self
     }
 
+    /// Moves the archive of each platform to a mirror on the provided host, which leaves the
+    /// platform's path to fail downloading.
+    pub fn move_archives_to_mirror<'a>(&'a mut self, mirror_host: &str) -> &'a mut PluginBuilder {
+        assert_eq!(
+            self.download_type.is_some(),
+            true,
+            "set a download type before moving the archives"
+        );
+        let environment = &self.environment;
+        let move_archive = |platform: &mut PlatformInfoBuilder| {
+            let path = platform.get_path().to_string();
+            let mirror_url = path.replace("https://github.com", mirror_host);
+            let bytes = environment.remove_remote_file(&path).unwrap();
+            environment.add_remote_file(&mirror_url, bytes);
+            platform.add_mirror(&mirror_url);
+        };
+        move_archive(self.file.windows());
+        move_archive(self.file.linux());
+        move_archive(self.file.mac());
+        self
+    }
+
+    /// Adds a `linux-x86_64-musl` zip with the same commands as the other platforms.
+    pub fn add_linux_musl_zip(&mut self) -> &mut PluginBuilder {
+        let commands = self.file.get_command_names();
+        let zip_url = format!(
+            "https://github.com/dsherret/bvm/releases/download/{}/{}-linux-musl.zip",
+            self.file.get_version().as_str(),
+            self.file.get_name()
+        );
+        let checksum = create_remote_zip(&self.environment, &zip_url, false, &commands);
+        let musl = self.file.linux_musl();
+        musl.path(&zip_url).checksum(&checksum);
+        for command in commands.iter() {
+            musl.add_command(command, command);
+        }
+        self
+    }
+
     fn setup_binaries_tar_gz(&mut self) {
         let commands = self.file.get_command_names();
         let name = self.file.get_name();
@@ -317,6 +542,13 @@ impl PluginBuilder {
 }
 
 fn create_remote_zip(environment: &TestEnvironment, url: &str, is_windows: bool, commands: &Vec<String>) -> String {
+    let result = create_zip(url, is_windows, commands);
+    let zip_file_checksum = dprint_cli_core::checksums::get_sha256_checksum(&result);
+    environment.add_remote_file(url, result);
+    zip_file_checksum
+}
+
+fn create_zip(url: &str, is_windows: bool, commands: &Vec<String>) -> Vec<u8> {
     let buf: Vec<u8> = Vec::new();
     let w = std::io::Cursor::new(buf);
     let mut zip = zip::ZipWriter::new(w);
@@ -328,12 +560,9 @@ fn create_remote_zip(environment: &TestEnvironment, url: &str, is_windows: bool,
             command.to_string()
         };
         zip.start_file(&file_name, options).unwrap();
-        zip.write(format!("test-{}-{}", command, url).as_bytes()).unwrap();
+        zip.write_all(format!("test-{}-{}", command, url).as_bytes()).unwrap();
     }
-    let result = zip.finish().unwrap().into_inner();
-    let zip_file_checksum = dprint_cli_core::checksums::get_sha256_checksum(&result);
-    environment.add_remote_file(url, result);
-    zip_file_checksum
+    zip.finish().unwrap().into_inner()
 }
 
 fn create_remote_tar_gz(environment: &TestEnvironment, url: &str, is_windows: bool, commands: &Vec<String>) -> String {
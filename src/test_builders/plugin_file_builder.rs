@@ -1,4 +1,4 @@
-use crate::plugins::{BinaryEnvironment, PlatformInfo, PlatformInfoCommand, SerializedPluginFile};
+use crate::plugins::{BinaryEnvironment, PlatformInfo, PlatformInfoCommand, SerializedPluginFile, SmokeTest};
 use crate::types::{CommandName, Version};
 use std::collections::HashMap;
 
@@ -6,6 +6,7 @@ pub struct PluginFileBuilder {
     file: SerializedPluginFile,
     windows: Option<PlatformInfoBuilder>,
     linux: Option<PlatformInfoBuilder>,
+    linux_musl: Option<PlatformInfoBuilder>,
     mac: Option<PlatformInfoBuilder>,
 }
 
@@ -19,11 +20,16 @@ impl PluginFileBuilder {
                 version: "1.0.0".into(),
                 description: "Some description.".to_string(),
                 linux: None,
+                linux_musl: None,
                 mac: None,
+                mac_aarch64: None,
                 windows: None,
+                windows_aarch64: None,
+                smoke_test: None,
             },
             windows: None,
             linux: None,
+            linux_musl: None,
             mac: None,
         }
     }
@@ -36,6 +42,9 @@ impl PluginFileBuilder {
         if let Some(builder) = &self.linux {
             file.linux = Some(builder.build());
         }
+        if let Some(builder) = &self.linux_musl {
+            file.linux_musl = Some(builder.build());
+        }
         if let Some(builder) = &self.mac {
             file.mac = Some(builder.build());
         }
@@ -78,21 +87,37 @@ impl PluginFileBuilder {
         self
     }
 
-    pub fn windows<'a>(&'a mut self) -> &'a mut PlatformInfoBuilder {
+    pub fn smoke_test<'a>(&'a mut self, args: Vec<&str>, expected_output: Option<&str>) -> &'a mut PluginFileBuilder {
+        self.file.smoke_test = Some(SmokeTest {
+            command: None,
+            args: args.into_iter().map(String::from).collect(),
+            expected_output: expected_output.map(String::from),
+        });
+        self
+    }
+
+    pub fn windows(&mut self) -> &mut PlatformInfoBuilder {
         if self.windows.is_none() {
             self.windows = Some(PlatformInfoBuilder::new());
         }
         self.windows.as_mut().unwrap()
     }
 
-    pub fn linux<'a>(&'a mut self) -> &'a mut PlatformInfoBuilder {
+    pub fn linux(&mut self) -> &mut PlatformInfoBuilder {
         if self.linux.is_none() {
             self.linux = Some(PlatformInfoBuilder::new());
         }
         self.linux.as_mut().unwrap()
     }
 
-    pub fn mac<'a>(&'a mut self) -> &'a mut PlatformInfoBuilder {
+    pub fn linux_musl(&mut self) -> &mut PlatformInfoBuilder {
+        if self.linux_musl.is_none() {
+            self.linux_musl = Some(PlatformInfoBuilder::new());
+        }
+        self.linux_musl.as_mut().unwrap()
+    }
+
+    pub fn mac(&mut self) -> &mut PlatformInfoBuilder {
         if self.mac.is_none() {
             self.mac = Some(PlatformInfoBuilder::new());
         }
@@ -109,12 +134,14 @@ impl PlatformInfoBuilder {
         PlatformInfoBuilder {
             info: PlatformInfo {
                 path: "".to_string(),
+                mirrors: Vec::new(),
                 checksum: "".to_string(),
                 download_type: "zip".to_string(),
                 commands: Vec::new(),
                 output_dir: None,
                 on_pre_install: None,
                 on_post_install: None,
+                on_pre_uninstall: None,
                 environment: None,
             },
         }
@@ -133,6 +160,15 @@ impl PlatformInfoBuilder {
         self
     }
 
+    pub fn get_path(&self) -> &str {
+        &self.info.path
+    }
+
+    pub fn add_mirror<'a>(&'a mut self, value: &str) -> &'a mut PlatformInfoBuilder {
+        self.info.mirrors.push(value.to_string());
+        self
+    }
+
     pub fn checksum<'a>(&'a mut self, value: &str) -> &'a mut PlatformInfoBuilder {
         self.info.checksum = value.to_string();
         self
@@ -166,6 +202,11 @@ impl PlatformInfoBuilder {
         self
     }
 
+    pub fn on_pre_uninstall<'a>(&'a mut self, value: &str) -> &'a mut PlatformInfoBuilder {
+        self.info.on_pre_uninstall = Some(value.to_string());
+        self
+    }
+
     pub fn add_env_path<'a>(&'a mut self, value: &str) -> &'a mut PlatformInfoBuilder {
         self.ensure_environment();
         self.info
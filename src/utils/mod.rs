@@ -1,13 +0,0 @@
-mod extract_tar;
-mod extract_zip;
-mod get_path_executable_path;
-mod get_shim_dir;
-mod gz_decompress;
-mod string_utils;
-
-pub use extract_tar::*;
-pub use extract_zip::*;
-pub use get_path_executable_path::*;
-pub use get_shim_dir::*;
-pub use gz_decompress::*;
-pub use string_utils::*;